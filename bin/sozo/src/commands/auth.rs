@@ -159,6 +159,7 @@ pub async fn grant(
             );
             auth::grant_writer(ui, &world, &models_contracts, transaction.into(), default_namespace)
                 .await
+                .map(|_| ())
         }
         AuthKind::Owner { owners_resources } => {
             trace!(
@@ -201,6 +202,7 @@ pub async fn revoke(
                 default_namespace,
             )
             .await
+            .map(|_| ())
         }
         AuthKind::Owner { owners_resources } => {
             trace!(
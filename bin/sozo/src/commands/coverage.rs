@@ -0,0 +1,751 @@
+//! Line coverage of the Cairo sources executed by tests, reported by `sozo test --coverage`.
+//!
+//! The tests are run by sozo instead of the test runner, with the profiler enabled, recording the
+//! Sierra statements executed by the tests and by the contract entrypoints they call. The runner
+//! drops the profiling info of the entrypoints it runs for syscalls, so the contract and library
+//! calls are executed here instead. The statements are then mapped back to the Cairo code they
+//! have been generated from, going through the code mappings of the files generated by plugins
+//! (like the `dojo_lang` ones) up to the user's source files.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{ensure, Context, Result};
+use cairo_lang_casm::hints::{Hint, StarknetHint};
+use cairo_lang_casm::operand::ResOperand;
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::ids::{FunctionWithBodyId, ModuleItemId};
+use cairo_lang_diagnostics::ToOption;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::FileLongId;
+use cairo_lang_lowering::ids::ConcreteFunctionWithBodyId;
+use cairo_lang_runner::casm_run::{extract_relocatable, MemBuffer};
+use cairo_lang_runner::{
+    build_hints_dict, Arg, CairoHintProcessor, ProfilingInfoCollectionConfig, RunResult,
+    RunResultStarknet, RunResultValue, RunnerError, SierraCasmRunner, StarknetState,
+};
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_sierra::program::{Function, Program, Statement, StatementIdx};
+use cairo_lang_sierra_generator::db::SierraGenGroup;
+use cairo_lang_sierra_generator::program_generator::SierraProgramWithDebug;
+use cairo_lang_sierra_generator::statements_locations::StatementsLocations;
+use cairo_lang_sierra_to_casm::compiler::CairoProgram;
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_starknet::contract::{find_contracts, get_contract_abi_functions, ContractInfo};
+use cairo_lang_starknet::plugin::consts::{CONSTRUCTOR_MODULE, EXTERNAL_MODULE, L1_HANDLER_MODULE};
+use cairo_lang_test_plugin::{try_extract_test_config, TestCompilation};
+use cairo_lang_test_runner::{TestCompiler, TestRunConfig};
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_vm::hint_processor::hint_processor_definition::{HintProcessorLogic, HintReference};
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::exec_scope::ExecutionScopes;
+use cairo_vm::types::relocatable::Relocatable;
+use cairo_vm::vm::errors::hint_errors::HintError;
+use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
+use cairo_vm::vm::runners::cairo_runner::{ResourceTracker, RunResources};
+use cairo_vm::vm::vm_core::VirtualMachine;
+use num_traits::ToPrimitive;
+use starknet::core::types::Felt;
+use tracing::trace;
+
+use super::gas_report::{ContractEntry, GasRecorder, GasReport};
+use super::test::{selected_tests, TestResults, LOG_TARGET};
+
+/// Gas costs of the syscalls executed here, the same as the runner's (`casm_run::gas_costs`, which
+/// is private). The runner charges one step as 100 gas.
+mod gas_costs {
+    const STEP: usize = 100;
+
+    /// Gas budget given to the called entrypoints, on top of the caller's remaining gas.
+    pub const ENTRY_POINT_INITIAL_BUDGET: usize = 100 * STEP;
+    const ENTRY_POINT: usize = ENTRY_POINT_INITIAL_BUDGET + 500 * STEP;
+
+    pub const CALL_CONTRACT: usize = 10 * STEP + ENTRY_POINT;
+    pub const LIBRARY_CALL: usize = CALL_CONTRACT;
+}
+
+/// The lines of Cairo source executed by tests.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    /// The number of times each line has been executed, keyed by source file and line number
+    /// (1-based).
+    files: BTreeMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+impl Coverage {
+    /// Runs the tests selected by `config`, recording the lines they execute in the source files
+    /// under `root`, and the costs of the entrypoints they call into `gas_report` if any.
+    ///
+    /// The results of the tests are reported as the test runner does, failing if a test failed.
+    pub fn run_tests(
+        &mut self,
+        compiler: &TestCompiler,
+        compiled: TestCompilation,
+        config: &TestRunConfig,
+        root: &Path,
+        gas_report: Option<&mut GasReport>,
+    ) -> Result<()> {
+        let statements_locations =
+            statements_locations(compiler, &compiled.sierra_program.program)?;
+
+        let TestCompilation { sierra_program, metadata } = compiled;
+        let program = sierra_program.program;
+
+        let functions = program.funcs.iter().map(|func| (func.id.clone(), func.clone())).collect();
+        let contracts_info = metadata.contracts_info.clone();
+        let gas_recorder =
+            gas_report.map(|report| GasRecorder::new(report, &metadata.contracts_info));
+
+        let metadata_config = config.gas_enabled.then(|| MetadataComputationConfig {
+            function_set_costs: metadata.function_set_costs,
+            ..Default::default()
+        });
+        let runner = SierraCasmRunner::new(
+            program,
+            metadata_config,
+            metadata.contracts_info,
+            Some(ProfilingInfoCollectionConfig::default()),
+        )?;
+
+        let mut context = TestContext {
+            runner: &runner,
+            functions,
+            contracts_info,
+            deployed: HashMap::new(),
+            steps: HashMap::new(),
+            gas_recorder,
+            depth: 0,
+        };
+
+        let (tests, filtered_out) = selected_tests(metadata.named_tests, config);
+        let mut results = TestResults::new(tests.len(), filtered_out);
+
+        for (name, test) in tests {
+            if test.ignored {
+                results.ignore(&name);
+                continue;
+            }
+
+            trace!(target: LOG_TARGET, test = %name, "Running test with coverage.");
+
+            let func = runner.find_function(&name)?;
+            context.start_test();
+            let result = context.run(func, &[], test.available_gas, StarknetState::default())?;
+            results.add(name, &test.expectation, result.value);
+        }
+
+        let executions = statement_executions(runner.get_casm_program(), &context.steps);
+        self.record_lines(&compiler.db, &statements_locations, &executions, root);
+
+        results.finish()
+    }
+
+    /// Records the executions of the lines the statements have been generated from. A line is
+    /// executed as many times as its most executed statement.
+    fn record_lines(
+        &mut self,
+        db: &RootDatabase,
+        statements_locations: &StatementsLocations,
+        executions: &HashMap<StatementIdx, u64>,
+        root: &Path,
+    ) {
+        for (statement_idx, locations) in statements_locations.locations.iter_sorted() {
+            // The first location is the code the statement has been generated from, the next
+            // ones are the places it has been inlined at.
+            let Some(location) = locations.first() else {
+                continue;
+            };
+
+            let location = location.diagnostic_location(db).user_location(db);
+
+            // Code generated by plugins without mapping to user code can't be located.
+            let FileLongId::OnDisk(path) = db.lookup_intern_file(location.file_id) else {
+                continue;
+            };
+
+            if !path.starts_with(root) {
+                continue;
+            }
+
+            let Some(position) = location.span.start.position_in_file(db, location.file_id) else {
+                continue;
+            };
+
+            let statement_executions = executions.get(statement_idx).copied().unwrap_or_default();
+            self.record_line(path, position.line + 1, statement_executions);
+        }
+    }
+
+    fn record_line(&mut self, path: PathBuf, line: usize, executions: u64) {
+        let hits = self.files.entry(path).or_default().entry(line).or_default();
+        *hits = (*hits).max(executions);
+    }
+
+    /// Returns the number of lines executed, and the number of lines that could be.
+    pub fn lines(&self) -> (usize, usize) {
+        self.files
+            .values()
+            .flat_map(|lines| lines.values())
+            .fold((0, 0), |(hit, found), hits| (hit + usize::from(*hits > 0), found + 1))
+    }
+
+    /// Formats the coverage as an LCOV tracefile.
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+
+        for (path, lines) in &self.files {
+            let hit = lines.values().filter(|hits| **hits > 0).count();
+
+            writeln!(lcov, "TN:").unwrap();
+            writeln!(lcov, "SF:{}", path.display()).unwrap();
+            for (line, hits) in lines {
+                writeln!(lcov, "DA:{line},{hits}").unwrap();
+            }
+            writeln!(lcov, "LF:{}", lines.len()).unwrap();
+            writeln!(lcov, "LH:{hit}").unwrap();
+            writeln!(lcov, "end_of_record").unwrap();
+        }
+
+        lcov
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, self.to_lcov())
+            .with_context(|| format!("Failed to write coverage report `{}`.", path.display()))
+    }
+}
+
+/// Returns the source locations of the statements of the tests Sierra program.
+///
+/// The test compilation doesn't keep them, so the Sierra program is requested again for the
+/// functions the test compiler compiles, which the database has cached.
+fn statements_locations(compiler: &TestCompiler, program: &Program) -> Result<StatementsLocations> {
+    let db = &compiler.db;
+
+    let mut entry_points = vec![];
+    if compiler.starknet {
+        for contract in find_contracts(db, &compiler.main_crate_ids) {
+            for module in [EXTERNAL_MODULE, CONSTRUCTOR_MODULE, L1_HANDLER_MODULE] {
+                entry_points.extend(
+                    get_contract_abi_functions(db, &contract, module)?
+                        .into_iter()
+                        .map(|func| ConcreteFunctionWithBodyId::from_semantic(db, func.value)),
+                );
+            }
+        }
+    }
+
+    let mut tests = vec![];
+    for crate_id in &compiler.test_crate_ids {
+        for module_id in db.crate_modules(*crate_id).iter() {
+            let Ok(module_items) = db.module_items(*module_id) else {
+                continue;
+            };
+
+            for item in module_items.iter() {
+                let ModuleItemId::FreeFunction(func_id) = item else {
+                    continue;
+                };
+                let Ok(attrs) =
+                    db.function_with_body_attributes(FunctionWithBodyId::Free(*func_id))
+                else {
+                    continue;
+                };
+
+                if let Ok(Some(_)) = try_extract_test_config(db.upcast(), attrs) {
+                    tests.extend(ConcreteFunctionWithBodyId::from_no_generics_free(db, *func_id));
+                }
+            }
+        }
+    }
+
+    let program_with_debug = db
+        .get_sierra_program_for_functions(entry_points.into_iter().chain(tests).collect())
+        .to_option()
+        .context("Compilation failed without any diagnostics.")?;
+    let SierraProgramWithDebug { program: located_program, debug_info } = &*program_with_debug;
+
+    ensure!(
+        same_layout(located_program, program),
+        "Failed to locate the statements of the compiled tests in their Cairo source."
+    );
+
+    Ok(debug_info.statements_locations.clone())
+}
+
+/// Whether both programs have the same functions and statements, regardless of their ids.
+fn same_layout(a: &Program, b: &Program) -> bool {
+    let same_statement = |a: &Statement, b: &Statement| match (a, b) {
+        (Statement::Return(a), Statement::Return(b)) => a.len() == b.len(),
+        (Statement::Invocation(a), Statement::Invocation(b)) => {
+            a.args.len() == b.args.len()
+                && a.branches.iter().map(|b| &b.target).eq(b.branches.iter().map(|b| &b.target))
+        }
+        _ => false,
+    };
+
+    a.funcs.len() == b.funcs.len()
+        && a.statements.len() == b.statements.len()
+        && a.funcs.iter().zip(&b.funcs).all(|(a, b)| a.entry_point == b.entry_point)
+        && a.statements.iter().zip(&b.statements).all(|(a, b)| same_statement(a, b))
+}
+
+/// Returns the number of times each statement has been executed, from the steps it executed.
+///
+/// Every execution of a statement starts at its first CASM instruction and runs at most all of
+/// them, the executions are then the steps divided by the instructions of the statement: exact for
+/// the statements running all their instructions, a lower bound for the branching ones.
+fn statement_executions(
+    casm_program: &CairoProgram,
+    steps: &HashMap<StatementIdx, u64>,
+) -> HashMap<StatementIdx, u64> {
+    let statements = &casm_program.debug_info.sierra_statement_info;
+
+    steps
+        .iter()
+        .filter_map(|(statement_idx, steps)| {
+            let start = statements.get(statement_idx.0)?.instruction_idx;
+            let end = statements
+                .get(statement_idx.0 + 1)
+                .map_or(casm_program.instructions.len(), |next| next.instruction_idx);
+            Some((*statement_idx, executions(*steps, end - start)))
+        })
+        .collect()
+}
+
+/// The executions of a statement of `instructions` CASM instructions having executed `steps`.
+fn executions(steps: u64, instructions: usize) -> u64 {
+    steps.div_ceil(instructions.max(1) as u64)
+}
+
+/// The state shared by the runs of a test and of the entrypoints it calls.
+struct TestContext<'a, 'r> {
+    runner: &'a SierraCasmRunner,
+    functions: HashMap<FunctionId, Function>,
+    contracts_info: OrderedHashMap<Felt, ContractInfo>,
+    /// Class hashes of the contracts deployed by the test, keyed by address. The runner's state
+    /// doesn't expose them, they are recorded from the deploy and replace class syscalls.
+    deployed: HashMap<Felt, Felt>,
+    /// Steps executed by each statement.
+    steps: HashMap<StatementIdx, u64>,
+    /// Records the costs of the entrypoints called by the test, if a gas report is requested.
+    gas_recorder: Option<GasRecorder<'r>>,
+    /// The number of runs in progress, the test being the first one.
+    depth: usize,
+}
+
+impl TestContext<'_, '_> {
+    fn start_test(&mut self) {
+        self.deployed.clear();
+        if let Some(recorder) = self.gas_recorder.as_mut() {
+            recorder.start_test();
+        }
+    }
+
+    /// Runs `func` like [`SierraCasmRunner::run_function_with_starknet_context`] does, recording
+    /// the executed statements.
+    fn run(
+        &mut self,
+        func: &Function,
+        args: &[Arg],
+        available_gas: Option<usize>,
+        starknet_state: StarknetState,
+    ) -> Result<RunResultStarknet, RunnerError> {
+        let runner = self.runner;
+        let initial_gas = runner.get_initial_available_gas(func, available_gas)?;
+        let (entry_code, builtins) = runner.create_entry_code(func, args, initial_gas)?;
+        let footer = SierraCasmRunner::create_code_footer();
+
+        let casm_program = runner.get_casm_program();
+        let (hints_dict, string_to_hint) =
+            build_hints_dict(entry_code.iter().chain(&casm_program.instructions));
+        let assembled_program = casm_program.assemble_ex(&entry_code, &footer);
+
+        let mut tracker = CoverageTracker {
+            processor: CairoHintProcessor {
+                runner: Some(runner),
+                starknet_state,
+                string_to_hint,
+                run_resources: RunResources::default(),
+                syscalls_used_resources: Default::default(),
+            },
+            is_test: self.depth == 0,
+            context: self,
+        };
+
+        tracker.context.depth += 1;
+        let result = runner.run_function(
+            func,
+            &mut tracker,
+            hints_dict,
+            assembled_program.bytecode.iter(),
+            builtins,
+        );
+        tracker.context.depth -= 1;
+
+        let RunResult { gas_counter, memory, value, used_resources, profiling_info } = result?;
+        let CairoHintProcessor { starknet_state, syscalls_used_resources, .. } = tracker.processor;
+
+        if let Some(profiling_info) = profiling_info {
+            for (statement_idx, steps) in profiling_info.sierra_statement_weights.iter_sorted() {
+                *self.steps.entry(*statement_idx).or_default() += *steps as u64;
+            }
+        }
+
+        let mut all_used_resources = syscalls_used_resources;
+        all_used_resources.basic_resources += &used_resources;
+
+        Ok(RunResultStarknet {
+            gas_counter,
+            memory,
+            value,
+            starknet_state,
+            used_resources: all_used_resources,
+            profiling_info: None,
+        })
+    }
+}
+
+/// Result of a syscall, with its return data or its revert reason.
+type SyscallResult = Result<Vec<Felt>, Vec<Felt>>;
+
+fn short_string(value: &[u8]) -> Felt {
+    Felt::from_bytes_be_slice(value)
+}
+
+fn hint_error(message: &str) -> HintError {
+    HintError::CustomHint(message.into())
+}
+
+/// Hint processor running the entrypoints called by contract and library calls with a
+/// [`TestContext`], to record the statements they execute.
+///
+/// Other syscalls are handled by the runner's processor, keeping track of the deployed contracts
+/// and replaced classes, as the runner's state isn't accessible.
+struct CoverageTracker<'a, 'c, 'r> {
+    processor: CairoHintProcessor<'a>,
+    context: &'c mut TestContext<'a, 'r>,
+    /// Whether the test itself is run, and not an entrypoint it called.
+    is_test: bool,
+}
+
+impl CoverageTracker<'_, '_, '_> {
+    /// The address of the contract being executed.
+    ///
+    /// The runner's state only exposes it through the caller context, which is opened and closed
+    /// right away to read it.
+    fn contract_address(&mut self) -> Felt {
+        let state = &mut self.processor.starknet_state;
+        let context = state.open_caller_context((Felt::ZERO, Felt::ZERO));
+        state.close_caller_context(context);
+        context.0
+    }
+
+    fn call_contract(
+        &mut self,
+        gas_counter: &mut usize,
+        address: Felt,
+        selector: Felt,
+        calldata: Vec<Felt>,
+    ) -> Result<SyscallResult, HintError> {
+        if *gas_counter < gas_costs::CALL_CONTRACT {
+            return Ok(Err(vec![short_string(b"Syscall out of gas")]));
+        }
+        *gas_counter -= gas_costs::CALL_CONTRACT;
+
+        let Some(class_hash) = self.context.deployed.get(&address) else {
+            return Ok(Err(vec![short_string(b"CONTRACT_NOT_DEPLOYED")]));
+        };
+
+        let contract_info = self
+            .context
+            .contracts_info
+            .get(class_hash)
+            .ok_or_else(|| hint_error("Deployed contract not found in registry."))?;
+
+        let Some(entry_point) = contract_info.externals.get(&selector) else {
+            return Ok(Err(vec![short_string(b"ENTRYPOINT_NOT_FOUND")]));
+        };
+        let entry_point = entry_point.clone();
+
+        let caller = self.contract_address();
+        let old_addresses = self.processor.starknet_state.open_caller_context((address, caller));
+        let result = self.call_entry_point(gas_counter, &entry_point, calldata);
+        self.processor.starknet_state.close_caller_context(old_addresses);
+
+        result
+    }
+
+    fn library_call(
+        &mut self,
+        gas_counter: &mut usize,
+        class_hash: Felt,
+        selector: Felt,
+        calldata: Vec<Felt>,
+    ) -> Result<SyscallResult, HintError> {
+        if *gas_counter < gas_costs::LIBRARY_CALL {
+            return Ok(Err(vec![short_string(b"Syscall out of gas")]));
+        }
+        *gas_counter -= gas_costs::LIBRARY_CALL;
+
+        let Some(contract_info) = self.context.contracts_info.get(&class_hash) else {
+            return Ok(Err(vec![short_string(b"CLASS_HASH_NOT_DECLARED")]));
+        };
+
+        let Some(entry_point) = contract_info.externals.get(&selector) else {
+            return Ok(Err(vec![short_string(b"ENTRYPOINT_NOT_FOUND")]));
+        };
+        let entry_point = entry_point.clone();
+
+        self.call_entry_point(gas_counter, &entry_point, calldata)
+    }
+
+    fn call_entry_point(
+        &mut self,
+        gas_counter: &mut usize,
+        entry_point: &FunctionId,
+        calldata: Vec<Felt>,
+    ) -> Result<SyscallResult, HintError> {
+        let function = self
+            .context
+            .functions
+            .get(entry_point)
+            .cloned()
+            .ok_or_else(|| hint_error("Entrypoint exists, but not found."))?;
+
+        // Contracts deployed by a reverted call are not deployed.
+        let deployed = self.context.deployed.clone();
+
+        let res = self
+            .context
+            .run(
+                &function,
+                &[Arg::Array(calldata.into_iter().map(Arg::Value).collect())],
+                Some(*gas_counter + gas_costs::ENTRY_POINT_INITIAL_BUDGET),
+                self.processor.starknet_state.clone(),
+            )
+            .map_err(|e| hint_error(&e.to_string()))?;
+
+        self.processor.syscalls_used_resources += res.used_resources;
+        *gas_counter = res.gas_counter.and_then(|gas| gas.to_usize()).unwrap_or_default();
+
+        match res.value {
+            RunResultValue::Success(value) => {
+                self.processor.starknet_state = res.starknet_state;
+
+                let unexpected = || hint_error("Unexpected return value from contract call.");
+                let [start, end] = value.as_slice() else {
+                    return Err(unexpected());
+                };
+                let start = start.to_usize().ok_or_else(unexpected)?;
+                let end = end.to_usize().ok_or_else(unexpected)?;
+
+                let data = (start..end)
+                    .map(|i| res.memory.get(i).copied().flatten().ok_or_else(unexpected))
+                    .collect::<Result<_, _>>()?;
+                Ok(Ok(data))
+            }
+            RunResultValue::Panic(mut panic_data) => {
+                self.context.deployed = deployed;
+                panic_data.push(short_string(b"ENTRYPOINT_FAILED"));
+                Ok(Err(panic_data))
+            }
+        }
+    }
+
+    /// Whether the syscall whose response is at `response` succeeded.
+    fn succeeded(vm: &mut VirtualMachine, response: Relocatable) -> Result<bool, HintError> {
+        let mut buffer = MemBuffer::new(vm, response);
+        // Gas counter.
+        buffer.next_usize()?;
+        Ok(*buffer.next_felt252()? == Felt::ZERO)
+    }
+
+    fn execute_syscall(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn std::any::Any>,
+        constants: &HashMap<String, Felt>,
+        system: &ResOperand,
+    ) -> Result<(), HintError> {
+        let system_ptr = extract_relocatable(vm, system)?;
+        let mut buffer = MemBuffer::new(vm, system_ptr);
+        let syscall = buffer.next_felt252()?.into_owned();
+        let mut gas_counter = buffer.next_usize()?;
+
+        let (name, result) = if syscall == short_string(b"CallContract") {
+            let address = buffer.next_felt252()?.into_owned();
+            let selector = buffer.next_felt252()?.into_owned();
+            let calldata = buffer.next_arr()?;
+            ("CallContract", self.call_contract(&mut gas_counter, address, selector, calldata)?)
+        } else if syscall == short_string(b"LibraryCall") {
+            let class_hash = buffer.next_felt252()?.into_owned();
+            let selector = buffer.next_felt252()?.into_owned();
+            let calldata = buffer.next_arr()?;
+            ("LibraryCall", self.library_call(&mut gas_counter, class_hash, selector, calldata)?)
+        } else if syscall == short_string(b"Deploy") {
+            let class_hash = buffer.next_felt252()?.into_owned();
+            // Salt, calldata start and end, and deploy from zero.
+            buffer.next_felt252()?;
+            buffer.next_addr()?;
+            buffer.next_addr()?;
+            buffer.next_felt252()?;
+            let response = buffer.ptr;
+
+            self.processor.execute_hint(vm, exec_scopes, hint_data, constants)?;
+
+            if Self::succeeded(vm, response)? {
+                // Skips the gas counter and the failure flag.
+                let mut buffer = MemBuffer::new(vm, (response + 2)?);
+                let address = buffer.next_felt252()?.into_owned();
+                self.context.deployed.insert(address, class_hash);
+            }
+
+            return Ok(());
+        } else if syscall == short_string(b"ReplaceClass") {
+            let class_hash = buffer.next_felt252()?.into_owned();
+            let response = buffer.ptr;
+
+            self.processor.execute_hint(vm, exec_scopes, hint_data, constants)?;
+
+            if Self::succeeded(vm, response)? {
+                let address = self.contract_address();
+                self.context.deployed.insert(address, class_hash);
+            }
+
+            return Ok(());
+        } else {
+            return self.processor.execute_hint(vm, exec_scopes, hint_data, constants);
+        };
+
+        *self.processor.syscalls_used_resources.syscalls.entry(name.into()).or_default() += 1;
+
+        buffer.write(gas_counter)?;
+        match result {
+            Ok(data) => {
+                buffer.write(Felt::ZERO)?;
+                buffer.write_arr(data.into_iter())?;
+            }
+            Err(revert_reason) => {
+                buffer.write(Felt::ONE)?;
+                buffer.write_arr(revert_reason.into_iter())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl HintProcessorLogic for CoverageTracker<'_, '_, '_> {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn std::any::Any>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let Some(Hint::Starknet(StarknetHint::SystemCall { system })) =
+            hint_data.downcast_ref::<Hint>()
+        else {
+            return self.processor.execute_hint(vm, exec_scopes, hint_data, constants);
+        };
+
+        // Only the entrypoints called by the test itself are reported, the costs of the nested
+        // calls being included in theirs.
+        let entry = match self.context.gas_recorder {
+            Some(_) if self.is_test => ContractEntry::read(vm, system)?,
+            _ => None,
+        };
+
+        let before = self.processor.syscalls_used_resources.basic_resources.clone();
+        self.execute_syscall(vm, exec_scopes, hint_data, constants, system)?;
+
+        if let (Some(entry), Some(recorder)) = (entry, self.context.gas_recorder.as_mut()) {
+            let resources = &self.processor.syscalls_used_resources.basic_resources - &before;
+            recorder.record(vm, entry, &resources);
+        }
+
+        Ok(())
+    }
+
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn std::any::Any>, VirtualMachineError> {
+        self.processor.compile_hint(hint_code, ap_tracking_data, reference_ids, references)
+    }
+}
+
+impl ResourceTracker for CoverageTracker<'_, '_, '_> {
+    fn consumed(&self) -> bool {
+        self.processor.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.processor.consume_step()
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.processor.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        self.processor.run_resources()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_lcov_records() {
+        let coverage = Coverage {
+            files: BTreeMap::from([
+                (PathBuf::from("/project/src/actions.cairo"), BTreeMap::from([(3, 12), (4, 0)])),
+                (PathBuf::from("/project/src/models.cairo"), BTreeMap::from([(10, 7)])),
+            ]),
+        };
+
+        assert_eq!(coverage.lines(), (2, 3));
+        assert_eq!(
+            coverage.to_lcov(),
+            concat!(
+                "TN:\nSF:/project/src/actions.cairo\nDA:3,12\nDA:4,0\nLF:2\nLH:1\nend_of_record\n",
+                "TN:\nSF:/project/src/models.cairo\nDA:10,7\nLF:1\nLH:1\nend_of_record\n",
+            )
+        );
+    }
+
+    #[test]
+    fn counts_statement_executions_from_steps() {
+        assert_eq!(executions(0, 3), 0);
+        assert_eq!(executions(9, 3), 3);
+        // Branching statements don't always run all their instructions.
+        assert_eq!(executions(7, 3), 3);
+        assert_eq!(executions(4, 0), 4);
+    }
+
+    #[test]
+    fn lines_are_executed_as_much_as_their_most_executed_statement() {
+        let path = PathBuf::from("/project/src/actions.cairo");
+        let mut coverage = Coverage::default();
+
+        coverage.record_line(path.clone(), 3, 2);
+        coverage.record_line(path.clone(), 3, 5);
+        coverage.record_line(path.clone(), 3, 1);
+        coverage.record_line(path.clone(), 4, 0);
+
+        assert_eq!(coverage.files[&path], BTreeMap::from([(3, 5), (4, 0)]));
+        assert_eq!(coverage.lines(), (1, 2));
+    }
+}
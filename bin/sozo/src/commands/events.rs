@@ -1,7 +1,11 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use clap::Args;
 use scarb::core::Config;
-use sozo_ops::events;
+use sozo_ops::events::FollowFilter;
+use sozo_ops::{events, model};
+use starknet::core::types::Felt;
 use tracing::trace;
 
 use super::options::starknet::StarknetOptions;
@@ -31,9 +35,31 @@ pub struct EventsArgs {
     pub continuation_token: Option<String>,
 
     #[arg(long)]
-    #[arg(help = "Print values as raw json")]
+    #[arg(help = "Print values as raw json, one event per line when following")]
     pub json: bool,
 
+    #[arg(long, conflicts_with_all = ["to_block", "continuation_token"])]
+    #[arg(help = "Follow the new events of the world as blocks are produced")]
+    pub follow: bool,
+
+    #[arg(long, requires = "follow")]
+    #[arg(help = "Also follow the events of the pending block")]
+    pub pending: bool,
+
+    #[arg(long, requires = "follow")]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Only follow the record changes of these models, given by tag or name")]
+    pub models: Option<Vec<String>>,
+
+    #[arg(long, requires = "follow")]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Only follow the record changes of the entities whose keys start with these keys")]
+    pub keys: Option<Vec<Felt>>,
+
+    #[arg(long, requires = "follow", default_value_t = 1000)]
+    #[arg(help = "Interval in milliseconds between two polls of new events")]
+    pub poll_interval: u64,
+
     #[command(flatten)]
     pub world: WorldOptions,
 
@@ -56,6 +82,41 @@ impl EventsArgs {
         trace!(?provider, "Starknet RPC client provider.");
 
         let world_address = self.world.address(env_metadata.as_ref())?;
+        let profile_name =
+            ws.current_profile().expect("Scarb profile expected at this point.").to_string();
+        trace!(profile_name, "Current profile.");
+
+        if self.follow {
+            let models = self
+                .models
+                .map(|models| {
+                    models
+                        .iter()
+                        .map(|m| model::check_tag_or_read_default_namespace(m, config))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?;
+            let filter = FollowFilter { models, keys: self.keys };
+            trace!(?filter, from_block = self.from_block, "Following events.");
+
+            return config.tokio_handle().block_on(async {
+                events::follow(
+                    &config.ui(),
+                    provider,
+                    world_address,
+                    self.from_block,
+                    self.chunk_size,
+                    filter,
+                    self.pending,
+                    Duration::from_millis(self.poll_interval),
+                    self.json,
+                    &project_dir,
+                    &profile_name,
+                )
+                .await
+            });
+        }
+
         let event_filter = events::get_event_filter(
             self.from_block,
             self.to_block,
@@ -68,10 +129,6 @@ impl EventsArgs {
             chunk_size = self.chunk_size,
             "Created event filter."
         );
-        let profile_name =
-            ws.current_profile().expect("Scarb profile expected at this point.").to_string();
-        trace!(profile_name, "Current profile.");
-
         config.tokio_handle().block_on(async {
             trace!("Starting async event parsing.");
             events::parse(
@@ -3,7 +3,7 @@ use clap::Args;
 use dojo_world::contracts::naming::ensure_namespace;
 use dojo_world::metadata::get_default_namespace_from_ws;
 use scarb::core::Config;
-use sozo_ops::execute;
+use sozo_ops::execute::{self, ContractCall};
 use tracing::trace;
 
 use super::calldata_decoder;
@@ -35,6 +35,12 @@ pub struct ExecuteArgs {
                   - no prefix: A cairo felt or any type that fit into one felt.")]
     pub calldata: Option<String>,
 
+    #[arg(long = "call", value_name = "TAG_OR_ADDRESS:ENTRYPOINT[:CALLDATA]")]
+    #[arg(help = "Additional calls executed atomically in the same transaction, after the first \
+                  one. The calldata uses the same format as the `--calldata` argument e.g., \
+                  dojo_examples-actions:move:1.")]
+    pub calls: Vec<String>,
+
     #[command(flatten)]
     pub starknet: StarknetOptions,
 
@@ -53,13 +59,29 @@ impl ExecuteArgs {
         trace!(args = ?self);
         let env_metadata = utils::load_metadata_from_config(config)?;
 
-        let tag_or_address = if utils::is_address(&self.tag_or_address) {
-            self.tag_or_address
-        } else {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-            let default_namespace = get_default_namespace_from_ws(&ws)?;
-            ensure_namespace(&self.tag_or_address, &default_namespace)
-        };
+        let mut calls = vec![(
+            self.tag_or_address.as_str(),
+            self.entrypoint.as_str(),
+            self.calldata.as_deref(),
+        )];
+        for call in &self.calls {
+            calls.push(parse_call(call)?);
+        }
+
+        let default_namespace =
+            if calls.iter().all(|(tag_or_address, _, _)| utils::is_address(tag_or_address)) {
+                None
+            } else {
+                let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+                Some(get_default_namespace_from_ws(&ws)?)
+            };
+
+        let calls = calls
+            .into_iter()
+            .map(|(tag_or_address, entrypoint, calldata)| {
+                contract_call(tag_or_address, entrypoint, calldata, default_namespace.as_deref())
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         config.tokio_handle().block_on(async {
             let world = utils::world_from_env_metadata(
@@ -73,28 +95,78 @@ impl ExecuteArgs {
 
             let tx_config = self.transaction.into();
 
-            trace!(
-                contract=?tag_or_address,
-                entrypoint=self.entrypoint,
-                calldata=?self.calldata,
-                "Executing Execute command."
-            );
-
-            let calldata = if let Some(cd) = self.calldata {
-                calldata_decoder::decode_calldata(&cd)?
-            } else {
-                vec![]
-            };
+            trace!(?calls, "Executing Execute command.");
 
-            execute::execute(
-                &config.ui(),
-                tag_or_address,
-                self.entrypoint,
-                calldata,
-                &world,
-                &tx_config,
-            )
-            .await
+            execute::execute_calls(&config.ui(), calls, &world, &tx_config).await
         })
     }
 }
+
+/// Splits a `TAG_OR_ADDRESS:ENTRYPOINT[:CALLDATA]` call. The calldata being last, it may itself
+/// contain `:` for its prefixes.
+fn parse_call(call: &str) -> Result<(&str, &str, Option<&str>)> {
+    let mut parts = call.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(tag_or_address), Some(entrypoint), calldata)
+            if !tag_or_address.is_empty() && !entrypoint.is_empty() =>
+        {
+            Ok((tag_or_address, entrypoint, calldata))
+        }
+        _ => anyhow::bail!(
+            "Call is expected to be in the format `TAG_OR_ADDRESS:ENTRYPOINT[:CALLDATA]`, found: \
+             {call}."
+        ),
+    }
+}
+
+/// Builds a call, adding the default namespace to the contract tag if it has none and decoding
+/// the calldata.
+pub(crate) fn contract_call(
+    tag_or_address: &str,
+    entrypoint: &str,
+    calldata: Option<&str>,
+    default_namespace: Option<&str>,
+) -> Result<ContractCall> {
+    let tag_or_address = match default_namespace {
+        Some(default_namespace) if !utils::is_address(tag_or_address) => {
+            ensure_namespace(tag_or_address, default_namespace)
+        }
+        _ => tag_or_address.to_string(),
+    };
+
+    let calldata = match calldata {
+        Some(calldata) if !calldata.is_empty() => calldata_decoder::decode_calldata(calldata)?,
+        _ => vec![],
+    };
+
+    Ok(ContractCall { tag_or_address, entrypoint: entrypoint.to_string(), calldata })
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_call() {
+        assert_eq!(parse_call("actions:spawn").unwrap(), ("actions", "spawn", None));
+        assert_eq!(
+            parse_call("0x1234:move:u256:1,sstr:hello").unwrap(),
+            ("0x1234", "move", Some("u256:1,sstr:hello"))
+        );
+        assert!(parse_call("actions").is_err());
+        assert!(parse_call(":spawn").is_err());
+    }
+
+    #[test]
+    fn test_contract_call() {
+        let call = contract_call("actions", "move", Some("1,u256:2"), Some("ns")).unwrap();
+        assert_eq!(call.tag_or_address, "ns-actions");
+        assert_eq!(call.calldata, vec![Felt::ONE, Felt::TWO, Felt::ZERO]);
+
+        let call = contract_call("0x1234", "spawn", None, Some("ns")).unwrap();
+        assert_eq!(call.tag_or_address, "0x1234");
+        assert!(call.calldata.is_empty());
+    }
+}
@@ -0,0 +1,705 @@
+//! Execution resources of the contract entrypoints called by tests, reported by
+//! `sozo test --gas-report`.
+//!
+//! The tests are run by sozo instead of the test runner, with a hint processor recording, for each
+//! contract call, deploy or library call made by a test, the resources the called entrypoint used.
+//! The costs of an entrypoint include the nested calls it makes.
+//!
+//! The L1 gas is an estimate: the resources are weighted with the fee weights of Starknet 0.13.1,
+//! which may differ from the ones of the network the contracts are deployed to.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use cairo_lang_casm::hints::{Hint, StarknetHint};
+use cairo_lang_casm::operand::ResOperand;
+use cairo_lang_runner::casm_run::{extract_relocatable, MemBuffer};
+use cairo_lang_runner::{build_hints_dict, CairoHintProcessor, SierraCasmRunner, StarknetState};
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_starknet::contract::ContractInfo;
+use cairo_lang_test_plugin::TestCompilation;
+use cairo_lang_test_runner::TestRunConfig;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_vm::hint_processor::hint_processor_definition::{HintProcessorLogic, HintReference};
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::types::exec_scope::ExecutionScopes;
+use cairo_vm::types::relocatable::Relocatable;
+use cairo_vm::vm::errors::hint_errors::HintError;
+use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
+use cairo_vm::vm::runners::cairo_runner::{ExecutionResources, ResourceTracker, RunResources};
+use cairo_vm::vm::vm_core::VirtualMachine;
+use prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE;
+use prettytable::{format, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+use tracing::trace;
+
+use super::test::{selected_tests, TestResults, LOG_TARGET};
+
+/// Denominator of the fee weights below.
+const FEE_WEIGHT_DENOMINATOR: u128 = 10_000;
+
+/// L1 gas charged per step, see `vm_resource_fee_cost` in the Starknet 0.13.1 versioned constants.
+const N_STEPS_FEE_WEIGHT: u128 = 25;
+
+/// L1 gas charged per builtin instance, see `vm_resource_fee_cost` in the Starknet 0.13.1
+/// versioned constants.
+fn builtin_fee_weight(builtin: BuiltinName) -> u128 {
+    match builtin {
+        BuiltinName::pedersen | BuiltinName::poseidon => 800,
+        BuiltinName::range_check => 400,
+        BuiltinName::bitwise => 1_600,
+        BuiltinName::ec_op => 25_600,
+        BuiltinName::ecdsa | BuiltinName::keccak => 51_200,
+        _ => 0,
+    }
+}
+
+/// Estimates the L1 gas Starknet charges for the given resources: the most expensive of the steps
+/// and of each builtin, by their fee weight. Only an estimate, as the fee weights depend on the
+/// Starknet version.
+fn estimate_l1_gas(resources: &ExecutionResources) -> u64 {
+    let steps = resources.n_steps as u128 * N_STEPS_FEE_WEIGHT;
+    let builtins = resources
+        .builtin_instance_counter
+        .iter()
+        .map(|(builtin, count)| *count as u128 * builtin_fee_weight(*builtin));
+
+    let max = builtins.chain([steps]).max().unwrap_or_default();
+    max.div_ceil(FEE_WEIGHT_DENOMINATOR) as u64
+}
+
+/// The resources used by all the calls to an entrypoint.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntrypointCosts {
+    pub calls: u64,
+    pub steps: u64,
+    pub builtins: BTreeMap<String, u64>,
+    pub l1_gas: u64,
+    pub max_l1_gas: u64,
+}
+
+impl EntrypointCosts {
+    fn add(&mut self, resources: &ExecutionResources) {
+        let l1_gas = estimate_l1_gas(resources);
+
+        self.calls += 1;
+        self.steps += resources.n_steps as u64;
+        self.l1_gas += l1_gas;
+        self.max_l1_gas = self.max_l1_gas.max(l1_gas);
+
+        for (builtin, count) in &resources.builtin_instance_counter {
+            if *count > 0 {
+                *self.builtins.entry(builtin.to_str().to_string()).or_default() += *count as u64;
+            }
+        }
+    }
+
+    fn average(&self, total: u64) -> u64 {
+        total.checked_div(self.calls).unwrap_or_default()
+    }
+}
+
+/// An entrypoint whose average cost increased more than the tolerated threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub contract: String,
+    pub entrypoint: String,
+    pub metric: String,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl Regression {
+    /// The increase over the baseline, in percent.
+    pub fn increase(&self) -> f64 {
+        if self.baseline == 0 {
+            return f64::INFINITY;
+        }
+
+        (self.current as f64 - self.baseline as f64) * 100.0 / self.baseline as f64
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}::{} {} regressed from {} to {} (+{:.2}%)",
+            self.contract,
+            self.entrypoint,
+            self.metric,
+            self.baseline,
+            self.current,
+            self.increase()
+        )
+    }
+}
+
+/// The costs of the entrypoints called by tests, keyed by contract and entrypoint names.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasReport {
+    pub contracts: BTreeMap<String, BTreeMap<String, EntrypointCosts>>,
+}
+
+impl GasReport {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read gas report `{}`.", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse gas report `{}`.", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write gas report `{}`.", path.display()))
+    }
+
+    /// Runs the tests selected by `config`, recording the costs of the entrypoints they call.
+    ///
+    /// The results of the tests are reported as the test runner does, failing if a test failed.
+    pub fn run_tests(&mut self, compiled: TestCompilation, config: &TestRunConfig) -> Result<()> {
+        let TestCompilation { sierra_program, metadata } = compiled;
+        let mut recorder = GasRecorder::new(self, &metadata.contracts_info);
+
+        let metadata_config = config.gas_enabled.then(|| MetadataComputationConfig {
+            function_set_costs: metadata.function_set_costs,
+            ..Default::default()
+        });
+        let runner = SierraCasmRunner::new(
+            sierra_program.program,
+            metadata_config,
+            metadata.contracts_info,
+            None,
+        )?;
+
+        let (tests, filtered_out) = selected_tests(metadata.named_tests, config);
+        let mut results = TestResults::new(tests.len(), filtered_out);
+
+        for (name, test) in tests {
+            if test.ignored {
+                results.ignore(&name);
+                continue;
+            }
+
+            trace!(target: LOG_TARGET, test = %name, "Running test with gas report.");
+
+            let func = runner.find_function(&name)?;
+            let initial_gas = runner.get_initial_available_gas(func, test.available_gas)?;
+            let (entry_code, builtins) = runner.create_entry_code(func, &[], initial_gas)?;
+            let footer = SierraCasmRunner::create_code_footer();
+
+            let casm_program = runner.get_casm_program();
+            let (hints_dict, string_to_hint) =
+                build_hints_dict(entry_code.iter().chain(&casm_program.instructions));
+            let assembled_program = casm_program.assemble_ex(&entry_code, &footer);
+
+            recorder.start_test();
+            let mut tracker = GasTracker {
+                processor: CairoHintProcessor {
+                    runner: Some(&runner),
+                    starknet_state: StarknetState::default(),
+                    string_to_hint,
+                    run_resources: RunResources::default(),
+                    syscalls_used_resources: Default::default(),
+                },
+                recorder: &mut recorder,
+            };
+
+            let result = runner.run_function(
+                func,
+                &mut tracker,
+                hints_dict,
+                assembled_program.bytecode.iter(),
+                builtins,
+            )?;
+
+            results.add(name, &test.expectation, result.value);
+        }
+
+        results.finish()
+    }
+
+    /// Returns the entrypoints whose average steps, builtins or estimated L1 gas increased by more
+    /// than `threshold` percent over `baseline`. Entrypoints missing from the baseline are
+    /// ignored, builtins missing from the baseline are compared to zero.
+    pub fn compare(&self, baseline: &GasReport, threshold: f64) -> Vec<Regression> {
+        let mut regressions = vec![];
+
+        for (contract, entrypoints) in &self.contracts {
+            for (entrypoint, costs) in entrypoints {
+                let Some(previous) =
+                    baseline.contracts.get(contract).and_then(|e| e.get(entrypoint))
+                else {
+                    continue;
+                };
+
+                let mut metrics = vec![
+                    (
+                        "steps".to_string(),
+                        previous.average(previous.steps),
+                        costs.average(costs.steps),
+                    ),
+                    (
+                        "estimated L1 gas".to_string(),
+                        previous.average(previous.l1_gas),
+                        costs.average(costs.l1_gas),
+                    ),
+                ];
+
+                let builtins = previous.builtins.keys().chain(costs.builtins.keys());
+                for builtin in builtins.collect::<BTreeSet<_>>() {
+                    let count = |costs: &EntrypointCosts| {
+                        costs.average(costs.builtins.get(builtin).copied().unwrap_or_default())
+                    };
+                    metrics.push((format!("{builtin} builtins"), count(previous), count(costs)));
+                }
+
+                for (metric, baseline, current) in metrics {
+                    let regression = Regression {
+                        contract: contract.clone(),
+                        entrypoint: entrypoint.clone(),
+                        metric,
+                        baseline,
+                        current,
+                    };
+
+                    if current > baseline && regression.increase() > threshold {
+                        regressions.push(regression);
+                    }
+                }
+            }
+        }
+
+        regressions
+    }
+
+    pub fn table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+
+        table.set_titles(Row::new(
+            [
+                "Contract",
+                "Entrypoint",
+                "Calls",
+                "Steps",
+                "Builtins",
+                "Est. L1 gas",
+                "Max est. L1 gas",
+            ]
+            .into_iter()
+            .map(|title| Cell::new_align(title, format::Alignment::CENTER))
+            .collect(),
+        ));
+
+        for (contract, entrypoints) in &self.contracts {
+            for (entrypoint, costs) in entrypoints {
+                let builtins = costs
+                    .builtins
+                    .iter()
+                    .map(|(builtin, count)| format!("{builtin}: {}", costs.average(*count)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                table.add_row(Row::new(vec![
+                    Cell::new_align(contract, format::Alignment::LEFT),
+                    Cell::new_align(entrypoint, format::Alignment::LEFT),
+                    Cell::new_align(&costs.calls.to_string(), format::Alignment::RIGHT),
+                    Cell::new_align(
+                        &costs.average(costs.steps).to_string(),
+                        format::Alignment::RIGHT,
+                    ),
+                    Cell::new_align(&builtins, format::Alignment::LEFT),
+                    Cell::new_align(
+                        &costs.average(costs.l1_gas).to_string(),
+                        format::Alignment::RIGHT,
+                    ),
+                    Cell::new_align(&costs.max_l1_gas.to_string(), format::Alignment::RIGHT),
+                ]));
+            }
+        }
+
+        table
+    }
+
+    fn record(&mut self, contract: String, entrypoint: String, resources: &ExecutionResources) {
+        self.contracts.entry(contract).or_default().entry(entrypoint).or_default().add(resources);
+    }
+}
+
+/// Splits the name of the Sierra function wrapping an external function, like
+/// `dojo_examples::actions::actions::__wrapper__ActionsImpl__spawn`, into the contract path and
+/// the entrypoint name.
+fn split_wrapper_name(name: &str) -> Option<(&str, &str)> {
+    let (contract, wrapper) = name.split_once("::__wrapper__")?;
+    let entrypoint = wrapper.rsplit_once("__").map_or(wrapper, |(_, entrypoint)| entrypoint);
+    Some((contract, entrypoint))
+}
+
+/// Names of the contracts and entrypoints of the classes available to the tests.
+#[derive(Debug, Default)]
+struct ContractNames {
+    /// Name of each contract, keyed by class hash.
+    contracts: HashMap<Felt, String>,
+    /// Name of each entrypoint, keyed by class hash and selector.
+    entrypoints: HashMap<(Felt, Felt), String>,
+}
+
+impl ContractNames {
+    fn new(contracts_info: &OrderedHashMap<Felt, ContractInfo>) -> Self {
+        let mut names = Self::default();
+
+        for (class_hash, info) in contracts_info.iter() {
+            for (selector, function) in info.externals.iter() {
+                let name = function.debug_name.as_deref().and_then(split_wrapper_name);
+
+                if let Some((contract, _)) = name {
+                    names.contracts.entry(*class_hash).or_insert_with(|| contract.to_string());
+                }
+
+                let entrypoint = name.map_or(format!("{selector:#x}"), |(_, e)| e.to_string());
+                names.entrypoints.insert((*class_hash, *selector), entrypoint);
+            }
+        }
+
+        names
+    }
+
+    fn contract(&self, class_hash: Felt) -> String {
+        self.contracts.get(&class_hash).cloned().unwrap_or_else(|| format!("{class_hash:#x}"))
+    }
+
+    fn entrypoint(&self, class_hash: Felt, selector: Felt) -> String {
+        self.entrypoints
+            .get(&(class_hash, selector))
+            .cloned()
+            .unwrap_or_else(|| format!("{selector:#x}"))
+    }
+
+    /// Returns the names of the contract and entrypoint called at `address`.
+    ///
+    /// Contracts deployed by other contracts (like Dojo systems deployed by the world) are not
+    /// known by address, the contract is then found from the classes exposing `selector`.
+    fn resolve_call(
+        &self,
+        deployed: &HashMap<Felt, Felt>,
+        address: Felt,
+        selector: Felt,
+    ) -> (String, String) {
+        if let Some(class_hash) = deployed.get(&address) {
+            return (self.contract(*class_hash), self.entrypoint(*class_hash, selector));
+        }
+
+        let classes = self
+            .entrypoints
+            .keys()
+            .filter(|(_, s)| *s == selector)
+            .map(|(class_hash, _)| *class_hash)
+            .collect::<Vec<_>>();
+
+        match classes.as_slice() {
+            [] => (format!("{address:#x}"), format!("{selector:#x}")),
+            [class_hash] => (self.contract(*class_hash), self.entrypoint(*class_hash, selector)),
+            [class_hash, ..] => {
+                let entrypoint = self.entrypoint(*class_hash, selector);
+                let mut contracts = classes.iter().map(|c| self.contract(*c)).collect::<Vec<_>>();
+                contracts.sort();
+                (contracts.join(" | "), entrypoint)
+            }
+        }
+    }
+}
+
+/// A syscall of a test entering a contract.
+pub(crate) enum ContractEntry {
+    Call {
+        address: Felt,
+        selector: Felt,
+    },
+    LibraryCall {
+        class_hash: Felt,
+        selector: Felt,
+    },
+    /// `output` points to the syscall response, holding the deployed address on success.
+    Deploy {
+        class_hash: Felt,
+        output: Relocatable,
+    },
+}
+
+impl ContractEntry {
+    /// Reads the syscall at `system`, before it is executed.
+    pub(crate) fn read(
+        vm: &mut VirtualMachine,
+        system: &ResOperand,
+    ) -> Result<Option<Self>, HintError> {
+        let system_ptr = extract_relocatable(vm, system)?;
+        let mut buffer = MemBuffer::new(vm, system_ptr);
+
+        let syscall = buffer.next_felt252()?.into_owned();
+        // Gas counter.
+        buffer.next_usize()?;
+
+        let entry = if syscall == Felt::from_bytes_be_slice(b"CallContract") {
+            let address = buffer.next_felt252()?.into_owned();
+            let selector = buffer.next_felt252()?.into_owned();
+            Self::Call { address, selector }
+        } else if syscall == Felt::from_bytes_be_slice(b"LibraryCall") {
+            let class_hash = buffer.next_felt252()?.into_owned();
+            let selector = buffer.next_felt252()?.into_owned();
+            Self::LibraryCall { class_hash, selector }
+        } else if syscall == Felt::from_bytes_be_slice(b"Deploy") {
+            let class_hash = buffer.next_felt252()?.into_owned();
+            // Salt, calldata start and end, and deploy from zero.
+            buffer.next_felt252()?;
+            buffer.next_addr()?;
+            buffer.next_addr()?;
+            buffer.next_felt252()?;
+            Self::Deploy { class_hash, output: buffer.ptr }
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(entry))
+    }
+}
+
+/// Records into a [`GasReport`] the resources used by the contract entrypoints entered by the
+/// syscalls of tests.
+pub(crate) struct GasRecorder<'r> {
+    names: ContractNames,
+    /// Class hashes of the contracts deployed by the current test, keyed by address.
+    deployed: HashMap<Felt, Felt>,
+    report: &'r mut GasReport,
+}
+
+impl<'r> GasRecorder<'r> {
+    pub(crate) fn new(
+        report: &'r mut GasReport,
+        contracts_info: &OrderedHashMap<Felt, ContractInfo>,
+    ) -> Self {
+        Self { names: ContractNames::new(contracts_info), deployed: HashMap::new(), report }
+    }
+
+    /// Forgets the contracts deployed by the previous test.
+    pub(crate) fn start_test(&mut self) {
+        self.deployed.clear();
+    }
+
+    /// Records the `resources` used by the syscall `entry` of a test, once it has been executed.
+    pub(crate) fn record(
+        &mut self,
+        vm: &mut VirtualMachine,
+        entry: ContractEntry,
+        resources: &ExecutionResources,
+    ) {
+        let (contract, entrypoint) = match entry {
+            ContractEntry::Call { address, selector } => {
+                self.names.resolve_call(&self.deployed, address, selector)
+            }
+            ContractEntry::LibraryCall { class_hash, selector } => {
+                (self.names.contract(class_hash), self.names.entrypoint(class_hash, selector))
+            }
+            ContractEntry::Deploy { class_hash, output } => {
+                if let Ok(Some(address)) = Self::deployed_address(vm, output) {
+                    self.deployed.insert(address, class_hash);
+                }
+
+                // Contracts without constructor don't execute anything when deployed.
+                if resources.n_steps == 0 {
+                    return;
+                }
+
+                (self.names.contract(class_hash), "constructor".to_string())
+            }
+        };
+
+        self.report.record(contract, entrypoint, resources);
+    }
+
+    fn deployed_address(vm: &mut VirtualMachine, output: Relocatable) -> Result<Option<Felt>> {
+        let mut buffer = MemBuffer::new(vm, output);
+        // Gas counter.
+        buffer.next_usize()?;
+
+        if *buffer.next_felt252()? != Felt::ZERO {
+            return Ok(None);
+        }
+
+        Ok(Some(buffer.next_felt252()?.into_owned()))
+    }
+}
+
+/// Hint processor recording the resources used by the contract entrypoints called from a test.
+struct GasTracker<'a, 'g, 'r> {
+    processor: CairoHintProcessor<'a>,
+    recorder: &'g mut GasRecorder<'r>,
+}
+
+impl HintProcessorLogic for GasTracker<'_, '_, '_> {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn std::any::Any>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let entry = match hint_data.downcast_ref::<Hint>() {
+            Some(Hint::Starknet(StarknetHint::SystemCall { system })) => {
+                ContractEntry::read(vm, system)?
+            }
+            _ => None,
+        };
+
+        let Some(entry) = entry else {
+            return self.processor.execute_hint(vm, exec_scopes, hint_data, constants);
+        };
+
+        // The resources of the nested run executing the entrypoint are added to the syscalls
+        // resources of the processor.
+        let before = self.processor.syscalls_used_resources.basic_resources.clone();
+        self.processor.execute_hint(vm, exec_scopes, hint_data, constants)?;
+        let resources = &self.processor.syscalls_used_resources.basic_resources - &before;
+
+        self.recorder.record(vm, entry, &resources);
+        Ok(())
+    }
+
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn std::any::Any>, VirtualMachineError> {
+        self.processor.compile_hint(hint_code, ap_tracking_data, reference_ids, references)
+    }
+}
+
+impl ResourceTracker for GasTracker<'_, '_, '_> {
+    fn consumed(&self) -> bool {
+        self.processor.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.processor.consume_step()
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.processor.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        self.processor.run_resources()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources(n_steps: usize, builtins: &[(BuiltinName, usize)]) -> ExecutionResources {
+        ExecutionResources {
+            n_steps,
+            n_memory_holes: 0,
+            builtin_instance_counter: builtins.iter().cloned().collect(),
+        }
+    }
+
+    fn report(steps: usize, calls: usize) -> GasReport {
+        let mut report = GasReport::default();
+        for _ in 0..calls {
+            report.record("actions".into(), "spawn".into(), &resources(steps, &[]));
+        }
+        report
+    }
+
+    #[test]
+    fn estimates_l1_gas_from_the_most_expensive_resource() {
+        assert_eq!(estimate_l1_gas(&resources(0, &[])), 0);
+        assert_eq!(estimate_l1_gas(&resources(1000, &[(BuiltinName::range_check, 10)])), 3);
+        assert_eq!(
+            estimate_l1_gas(&resources(
+                1000,
+                &[(BuiltinName::range_check, 10), (BuiltinName::pedersen, 100)]
+            )),
+            8
+        );
+    }
+
+    #[test]
+    fn splits_wrapper_names() {
+        assert_eq!(
+            split_wrapper_name("dojo_examples::actions::actions::__wrapper__ActionsImpl__spawn"),
+            Some(("dojo_examples::actions::actions", "spawn"))
+        );
+        assert_eq!(
+            split_wrapper_name("dojo::world::world::__wrapper__set_metadata"),
+            Some(("dojo::world::world", "set_metadata"))
+        );
+        assert_eq!(split_wrapper_name("dojo::world::world::set_metadata"), None);
+    }
+
+    #[test]
+    fn accumulates_entrypoint_costs() {
+        let mut report = GasReport::default();
+        report.record("actions".into(), "spawn".into(), &resources(1000, &[]));
+        report.record(
+            "actions".into(),
+            "spawn".into(),
+            &resources(2000, &[(BuiltinName::pedersen, 4), (BuiltinName::output, 0)]),
+        );
+
+        let costs = &report.contracts["actions"]["spawn"];
+        assert_eq!(costs.calls, 2);
+        assert_eq!(costs.average(costs.steps), 1500);
+        assert_eq!(costs.builtins, BTreeMap::from([("pedersen".to_string(), 4)]));
+        assert_eq!(costs.l1_gas, 3 + 5);
+        assert_eq!(costs.max_l1_gas, 5);
+    }
+
+    #[test]
+    fn compares_averages_to_baseline() {
+        let baseline = report(1000, 2);
+
+        assert!(report(1000, 1).compare(&baseline, 0.0).is_empty());
+        assert!(report(900, 3).compare(&baseline, 0.0).is_empty());
+        assert!(report(1040, 1).compare(&baseline, 5.0).is_empty());
+
+        let regressions = report(1100, 1).compare(&baseline, 5.0);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].metric, "steps");
+        assert_eq!((regressions[0].baseline, regressions[0].current), (1000, 1100));
+
+        let mut new_entrypoint = report(1000, 1);
+        new_entrypoint.record("actions".into(), "move".into(), &resources(5000, &[]));
+        assert!(new_entrypoint.compare(&baseline, 0.0).is_empty());
+    }
+
+    #[test]
+    fn compares_builtins_to_baseline() {
+        let mut baseline = GasReport::default();
+        baseline.record(
+            "actions".into(),
+            "spawn".into(),
+            &resources(1000, &[(BuiltinName::pedersen, 10)]),
+        );
+
+        let mut report = GasReport::default();
+        report.record(
+            "actions".into(),
+            "spawn".into(),
+            &resources(1000, &[(BuiltinName::pedersen, 12), (BuiltinName::poseidon, 1)]),
+        );
+
+        let regressions = report.compare(&baseline, 10.0);
+        let metrics = regressions.iter().map(|r| r.metric.as_str()).collect::<Vec<_>>();
+        assert_eq!(metrics, ["pedersen builtins", "poseidon builtins"]);
+        assert_eq!((regressions[1].baseline, regressions[1].current), (0, 1));
+
+        assert!(report.compare(&baseline, 25.0).iter().all(|r| r.metric == "poseidon builtins"));
+    }
+}
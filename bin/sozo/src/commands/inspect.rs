@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use dojo_world::manifest::{BASE_DIR, MANIFESTS_DIR};
+use scarb::core::Config;
+use sozo_ops::inspect;
+use tracing::trace;
+
+use super::options::starknet::StarknetOptions;
+use super::options::world::WorldOptions;
+use crate::utils;
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    #[arg(long)]
+    #[arg(help = "Output the report as JSON.")]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+}
+
+impl InspectArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        trace!(args = ?self);
+        let env_metadata = utils::load_metadata_from_config(config)?;
+
+        let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+
+        let profile_name =
+            ws.current_profile().expect("Scarb profile expected to be defined.").to_string();
+        let root_dir = ws.manifest_path().parent().unwrap().to_path_buf();
+        let manifest_dir = root_dir.join(MANIFESTS_DIR).join(&profile_name).join(BASE_DIR);
+
+        if !manifest_dir.exists() {
+            return Err(anyhow!("Build project using `sozo build` first"));
+        }
+
+        let world_address = self.world.address(env_metadata.as_ref())?;
+        let provider = self.starknet.provider(env_metadata.as_ref())?;
+
+        config.tokio_handle().block_on(async {
+            inspect::inspect(&provider, world_address, &root_dir, &profile_name, self.json)
+                .await
+                .map(|_| ())
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Context, Result};
+use camino::Utf8PathBuf;
 use clap::{Args, Subcommand};
 use dojo_world::config::Environment;
 use dojo_world::manifest::MANIFESTS_DIR;
@@ -7,6 +8,7 @@ use dojo_world::migration::TxnConfig;
 use katana_rpc_api::starknet::RPC_SPEC_VERSION;
 use scarb::core::{Config, Workspace};
 use sozo_ops::migration;
+use sozo_ops::migration::plan::MigrationPlan;
 use starknet::accounts::{Account, ConnectedAccount};
 use starknet::core::types::{BlockId, BlockTag, Felt, StarknetError};
 use starknet::core::utils::parse_cairo_short_string;
@@ -33,17 +35,38 @@ pub struct MigrateArgs {
 
     #[command(flatten)]
     account: AccountOptions,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Allow upgrades of models breaking the storage of their existing entities.")]
+    force: bool,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum MigrateCommand {
     #[command(about = "Plan the migration and output the manifests.")]
-    Plan,
+    Plan {
+        #[arg(long)]
+        #[arg(help = "Export the migration as unsigned transactions into this directory, to be \
+                      signed and sent by the owner of the world.")]
+        output: Option<Utf8PathBuf>,
+    },
     #[command(about = "Apply the migration on-chain.")]
     Apply {
         #[command(flatten)]
         transaction: TransactionOptions,
     },
+    #[command(about = "Submit the signed transactions of an exported migration plan and check \
+                       the on-chain state matches the plan.")]
+    Submit {
+        #[arg(long)]
+        #[arg(help = "The directory of the migration plan.")]
+        plan: Utf8PathBuf,
+
+        #[arg(long)]
+        #[arg(help = "JSON array of the signed transactions of the plan, in the JSON-RPC \
+                      format. If not provided, only checks the on-chain state matches the plan.")]
+        transactions: Option<Utf8PathBuf>,
+    },
 }
 
 impl MigrateArgs {
@@ -58,6 +81,7 @@ impl MigrateArgs {
             world,
             starknet,
             account,
+            force: false,
         }
     }
 
@@ -80,51 +104,84 @@ impl MigrateArgs {
             return Err(anyhow!("Build project using `sozo build` first"));
         }
 
-        let MigrateArgs { world, starknet, account, .. } = self;
+        let MigrateArgs { command, world, starknet, account, force } = self;
 
         let name = dojo_metadata.world.seed;
+        let skip_manifests = dojo_metadata.migration.map(|m| m.skip_contracts.clone());
 
-        let (world_address, account, rpc_url) = config.tokio_handle().block_on(async {
-            setup_env(&ws, account, starknet, world, &name, env_metadata.as_ref()).await
-        })?;
+        match command {
+            // Submitting signed transactions doesn't require an account.
+            MigrateCommand::Submit { plan, transactions } => {
+                let provider = starknet.provider(env_metadata.as_ref())?;
+                let ui = config.ui();
 
-        match self.command {
-            MigrateCommand::Plan => config
-                .tokio_handle()
-                .block_on(async {
-                    trace!(name, "Planning migration.");
-                    migration::migrate(
+                config.tokio_handle().block_on(async {
+                    let plan_content = MigrationPlan::load(&plan)?;
+
+                    if let Some(transactions) = transactions {
+                        trace!(%plan, %transactions, "Submitting migration plan.");
+                        migration::plan::submit(&ui, &provider, &plan_content, &transactions)
+                            .await?;
+                    }
+
+                    migration::plan::verify_plan(&ui, &provider, &plan_content).await
+                })
+            }
+            MigrateCommand::Plan { output } => config.tokio_handle().block_on(async {
+                let (world_address, account, rpc_url) =
+                    setup_env(&ws, account, starknet, world, &name, env_metadata.as_ref()).await?;
+
+                if let Some(output) = output {
+                    trace!(name, %output, "Exporting migration plan.");
+                    migration::export_plan(
                         &ws,
                         world_address,
-                        rpc_url,
                         account,
                         &name,
-                        true,
-                        TxnConfig::default(),
-                        dojo_metadata.migration.map(|m| m.skip_contracts.clone()),
+                        skip_manifests,
+                        force,
+                        &output,
                     )
                     .await
-                })
-                .map(|_| ()),
-            MigrateCommand::Apply { transaction } => config
-                .tokio_handle()
-                .block_on(async {
-                    trace!(name, "Applying migration.");
-                    let txn_config: TxnConfig = transaction.into();
-
+                    .map(|_| ())
+                } else {
+                    trace!(name, "Planning migration.");
                     migration::migrate(
                         &ws,
                         world_address,
                         rpc_url,
                         account,
                         &name,
-                        false,
-                        txn_config,
-                        dojo_metadata.migration.map(|m| m.skip_contracts.clone()),
+                        true,
+                        TxnConfig::default(),
+                        skip_manifests,
+                        force,
                     )
                     .await
-                })
-                .map(|_| ()),
+                    .map(|_| ())
+                }
+            }),
+            MigrateCommand::Apply { transaction } => config.tokio_handle().block_on(async {
+                let (world_address, account, rpc_url) =
+                    setup_env(&ws, account, starknet, world, &name, env_metadata.as_ref()).await?;
+
+                trace!(name, "Applying migration.");
+                let txn_config: TxnConfig = transaction.into();
+
+                migration::migrate(
+                    &ws,
+                    world_address,
+                    rpc_url,
+                    account,
+                    &name,
+                    false,
+                    txn_config,
+                    skip_manifests,
+                    force,
+                )
+                .await
+                .map(|_| ())
+            }),
         }
     }
 }
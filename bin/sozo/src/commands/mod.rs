@@ -11,17 +11,22 @@ pub(crate) mod call;
 pub(crate) mod calldata_decoder;
 pub(crate) mod clean;
 pub(crate) mod completions;
+pub(crate) mod coverage;
 pub(crate) mod dev;
 pub(crate) mod events;
 pub(crate) mod execute;
+pub(crate) mod gas_report;
 pub(crate) mod hash;
 pub(crate) mod init;
+pub(crate) mod inspect;
 pub(crate) mod keystore;
 pub(crate) mod migrate;
 pub(crate) mod model;
 pub(crate) mod options;
 pub(crate) mod print_env;
 pub(crate) mod register;
+pub(crate) mod run;
+pub(crate) mod signer;
 pub(crate) mod test;
 
 use account::AccountArgs;
@@ -34,11 +39,14 @@ use dev::DevArgs;
 use events::EventsArgs;
 use execute::ExecuteArgs;
 use init::InitArgs;
+use inspect::InspectArgs;
 use keystore::KeystoreArgs;
 use migrate::MigrateArgs;
 use model::ModelArgs;
 use print_env::PrintEnvArgs;
 use register::RegisterArgs;
+use run::RunArgs;
+use signer::SignerArgs;
 use test::TestArgs;
 use tracing::info_span;
 
@@ -48,6 +56,8 @@ pub enum Commands {
     Account(AccountArgs),
     #[command(about = "Manage keystore files")]
     Keystore(KeystoreArgs),
+    #[command(about = "Run a remote signer for sozo accounts")]
+    Signer(SignerArgs),
     #[command(about = "Build the world, generating the necessary artifacts for deployment")]
     Build(BuildArgs),
     #[command(about = "Initialize a new project")]
@@ -65,6 +75,8 @@ pub enum Commands {
     Execute(ExecuteArgs),
     #[command(about = "Call a world's system")]
     Call(CallArgs),
+    #[command(about = "Run a script of calls to the world's systems")]
+    Run(RunArgs),
     #[command(about = "Interact with a worlds models")]
     Model(ModelArgs),
     #[command(about = "Register new models")]
@@ -75,6 +87,8 @@ pub enum Commands {
     Events(EventsArgs),
     #[command(about = "Manage world authorization")]
     Auth(AuthArgs),
+    #[command(about = "Inspect the resources of a world and their permissions")]
+    Inspect(InspectArgs),
     #[command(about = "Generate shell completion file for specified shell")]
     Completions(CompletionsArgs),
     #[command(about = "Print information about current")]
@@ -86,6 +100,7 @@ impl fmt::Display for Commands {
         match self {
             Commands::Account(_) => write!(f, "Account"),
             Commands::Keystore(_) => write!(f, "Keystore"),
+            Commands::Signer(_) => write!(f, "Signer"),
             Commands::Build(_) => write!(f, "Build"),
             Commands::Init(_) => write!(f, "Init"),
             Commands::Clean(_) => write!(f, "Clean"),
@@ -94,11 +109,13 @@ impl fmt::Display for Commands {
             Commands::Test(_) => write!(f, "Test"),
             Commands::Execute(_) => write!(f, "Execute"),
             Commands::Call(_) => write!(f, "Call"),
+            Commands::Run(_) => write!(f, "Run"),
             Commands::Model(_) => write!(f, "Model"),
             Commands::Register(_) => write!(f, "Register"),
             Commands::Hash(_) => write!(f, "Hash"),
             Commands::Events(_) => write!(f, "Events"),
             Commands::Auth(_) => write!(f, "Auth"),
+            Commands::Inspect(_) => write!(f, "Inspect"),
             Commands::Completions(_) => write!(f, "Completions"),
             Commands::PrintEnv(_) => write!(f, "PrintEnv"),
         }
@@ -116,6 +133,7 @@ pub fn run(command: Commands, config: &Config) -> Result<()> {
     match command {
         Commands::Account(args) => args.run(config),
         Commands::Keystore(args) => args.run(config),
+        Commands::Signer(args) => args.run(config),
         Commands::Init(args) => args.run(config),
         Commands::Clean(args) => args.run(config),
         Commands::Test(args) => args.run(config),
@@ -123,8 +141,10 @@ pub fn run(command: Commands, config: &Config) -> Result<()> {
         Commands::Migrate(args) => args.run(config),
         Commands::Dev(args) => args.run(config),
         Commands::Auth(args) => args.run(config),
+        Commands::Inspect(args) => args.run(config),
         Commands::Execute(args) => args.run(config),
         Commands::Call(args) => args.run(config),
+        Commands::Run(args) => args.run(config),
         Commands::Model(args) => args.run(config),
         Commands::Register(args) => args.run(config),
         Commands::Hash(args) => args.run().map(|_| ()),
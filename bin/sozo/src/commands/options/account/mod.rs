@@ -4,10 +4,10 @@ use anyhow::{anyhow, Context, Result};
 use clap::Args;
 use dojo_world::config::Environment;
 use scarb::core::Config;
+use sozo_signers::remote::RemoteSigner;
 use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::types::{BlockId, BlockTag, Felt};
 use starknet::providers::Provider;
-use starknet::signers::LocalWallet;
 use tracing::trace;
 use url::Url;
 
@@ -17,11 +17,13 @@ use super::DOJO_ACCOUNT_ADDRESS_ENV_VAR;
 
 #[cfg(feature = "controller")]
 pub mod controller;
+mod remote;
 mod r#type;
 
 #[cfg(feature = "controller")]
 use controller::ControllerSessionAccount;
 pub use r#type::*;
+pub use remote::RemoteAccount;
 
 /// Helper type for identifying how the world address will be provided.
 /// If it's a name, it will be used as the seed for computing the address.
@@ -99,15 +101,62 @@ impl AccountOptions {
             return Ok(SozoAccount::from(account));
         }
 
-        let account = self.std_account(provider, env_metadata).await?;
-        Ok(SozoAccount::from(account))
+        if let Some(signer_url) = self.signer.signer_url(env_metadata)? {
+            let account = self.remote_account(provider, signer_url, env_metadata).await?;
+            return Ok(SozoAccount::from(account));
+        }
+
+        self.std_account(provider, env_metadata).await
+    }
+
+    /// Create an account whose transactions are signed by the remote signer at `signer_url`.
+    pub async fn remote_account<P>(
+        &self,
+        provider: P,
+        signer_url: Url,
+        env_metadata: Option<&Environment>,
+    ) -> Result<RemoteAccount<P>>
+    where
+        P: Provider,
+        P: Send + Sync,
+    {
+        let account_address = self.account_address(env_metadata)?;
+        trace!(?account_address, "Account address determined.");
+
+        let token = self.signer.signer_token.clone().ok_or_else(|| {
+            anyhow!(
+                "A token is required to authenticate to the remote signer, use `--signer-token`."
+            )
+        })?;
+
+        let signer = RemoteSigner::new(signer_url, token);
+        let public_key = signer
+            .public_key()
+            .await
+            .with_context(|| format!("Failed to reach the remote signer at {}", signer.url()))?;
+        trace!(
+            public_key = format!("{public_key:#x}"),
+            url = %signer.url(),
+            "Remote signer obtained."
+        );
+
+        let chain_id = provider.chain_id().await?;
+        trace!(?chain_id);
+
+        let encoding = if self.legacy { ExecutionEncoding::Legacy } else { ExecutionEncoding::New };
+        trace!(?encoding, "Creating RemoteAccount.");
+        let mut account = RemoteAccount::new(provider, signer, account_address, chain_id, encoding);
+
+        // The default is `Latest`, which does not reflect the nonce changes in the pending block.
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+        Ok(account)
     }
 
     pub async fn std_account<P>(
         &self,
         provider: P,
         env_metadata: Option<&Environment>,
-    ) -> Result<SingleOwnerAccount<P, LocalWallet>>
+    ) -> Result<SozoAccount<P>>
     where
         P: Provider,
         P: Send + Sync,
@@ -124,12 +173,12 @@ impl AccountOptions {
         let encoding = if self.legacy { ExecutionEncoding::Legacy } else { ExecutionEncoding::New };
         trace!(?encoding, "Creating SingleOwnerAccount.");
         let mut account =
-            SingleOwnerAccount::new(provider, signer, account_address, chain_id, encoding);
+            SingleOwnerAccount::new(provider, signer.clone(), account_address, chain_id, encoding);
 
         // The default is `Latest` in starknet-rs, which does not reflect
         // the nonce changes in the pending block.
         account.set_block_id(BlockId::Tag(BlockTag::Pending));
-        Ok(account)
+        Ok(SozoAccount::Standard(account, signer))
     }
 
     pub fn account_address(&self, env_metadata: Option<&Environment>) -> Result<Felt> {
@@ -151,7 +200,10 @@ impl AccountOptions {
 #[cfg(test)]
 mod tests {
     use clap::Parser;
+    use dojo_world::migration::{TxnConfig, TxnV3Config};
+    use dojo_world::utils::{execute_with_cfg, TransactionWaiter};
     use starknet::accounts::{Call, ExecutionEncoder};
+    use starknet::macros::{felt, selector};
     use starknet_crypto::Felt;
 
     use super::{AccountOptions, DOJO_ACCOUNT_ADDRESS_ENV_VAR};
@@ -250,4 +302,31 @@ mod tests {
         // 0x2 is the Calldata len.
         assert!(*result.get(3).unwrap() == Felt::from_hex("0x2").unwrap());
     }
+
+    #[katana_runner::katana_test(2, true)]
+    async fn std_account_sends_tipped_v3_transactions() {
+        let account_data = runner.account_data(0);
+        let private_key = account_data.private_key.as_ref().unwrap().secret_scalar();
+        let cmd = Command::parse_from([
+            "sozo",
+            "--account-address",
+            &format!("{:#x}", account_data.address),
+            "--private-key",
+            &format!("{private_key:#x}"),
+        ]);
+        let account = cmd.account.std_account(runner.provider(), None).await.unwrap();
+
+        let transfer = Call {
+            to: felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1"), felt!("0x1"), felt!("0x0")],
+        };
+        let config = TxnConfig {
+            v3: Some(TxnV3Config { tip: 1, ..Default::default() }),
+            ..Default::default()
+        };
+
+        let res = execute_with_cfg(&account, vec![transfer], &config).await.unwrap();
+        TransactionWaiter::new(res.transaction_hash, &runner.provider()).await.unwrap();
+    }
 }
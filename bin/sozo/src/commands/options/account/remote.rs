@@ -0,0 +1,495 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dojo_world::utils::{RawTransactionV3, RawTransactionV3Kind, TransactionV3Signer};
+use sozo_signers::remote::{
+    Call as RemoteCall, CallEncoding, DeclareV2, DeclareV3, InvokeV1, InvokeV3, RemoteSigner,
+    RemoteSignerError, SignRequest, Transaction,
+};
+use starknet::accounts::{
+    Account, Call, ConnectedAccount, DeclarationV2, DeclarationV3, ExecutionEncoder,
+    ExecutionEncoding, ExecutionV1, ExecutionV3, LegacyDeclaration, RawDeclarationV2,
+    RawDeclarationV3, RawExecutionV1, RawExecutionV3, RawLegacyDeclaration,
+};
+use starknet::core::types::contract::legacy::LegacyContractClass;
+use starknet::core::types::{BlockId, BlockTag, DataAvailabilityMode, Felt, FlattenedSierraClass};
+use starknet::providers::Provider;
+
+/// An account whose transactions are signed by a remote signer.
+///
+/// Along with the hash to sign, the signer is sent the transaction it has been computed from, so
+/// it can check the transaction against its policy.
+#[derive(Debug)]
+pub struct RemoteAccount<P> {
+    provider: P,
+    signer: RemoteSigner,
+    address: Felt,
+    chain_id: Felt,
+    encoding: ExecutionEncoding,
+    block_id: BlockId,
+}
+
+impl<P> RemoteAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    pub fn new(
+        provider: P,
+        signer: RemoteSigner,
+        address: Felt,
+        chain_id: Felt,
+        encoding: ExecutionEncoding,
+    ) -> Self {
+        Self {
+            provider,
+            signer,
+            address,
+            chain_id,
+            encoding,
+            block_id: BlockId::Tag(BlockTag::Latest),
+        }
+    }
+
+    pub fn set_block_id(&mut self, block_id: BlockId) -> &Self {
+        self.block_id = block_id;
+        self
+    }
+
+    fn remote_calls(calls: &[Call]) -> Vec<RemoteCall> {
+        calls.iter().map(RemoteCall::from).collect()
+    }
+
+    /// The V3 transaction sent to the signer along with the hash of `transaction`.
+    fn remote_transaction_v3(
+        &self,
+        transaction: &RawTransactionV3,
+        query_only: bool,
+    ) -> Transaction {
+        let fee_fields = &transaction.fee_fields;
+
+        match &transaction.kind {
+            RawTransactionV3Kind::Invoke { calls } => Transaction::InvokeV3(InvokeV3 {
+                sender_address: self.address,
+                chain_id: self.chain_id,
+                nonce: transaction.nonce,
+                gas: fee_fields.l1_gas,
+                gas_price: fee_fields.l1_gas_price,
+                tip: fee_fields.tip,
+                nonce_data_availability_mode: fee_fields.nonce_data_availability_mode,
+                fee_data_availability_mode: fee_fields.fee_data_availability_mode,
+                calls: Self::remote_calls(calls),
+                encoding: self.encoding.into(),
+                query_only,
+            }),
+            RawTransactionV3Kind::Declare { contract_class, compiled_class_hash } => {
+                Transaction::DeclareV3(DeclareV3 {
+                    sender_address: self.address,
+                    chain_id: self.chain_id,
+                    nonce: transaction.nonce,
+                    gas: fee_fields.l1_gas,
+                    gas_price: fee_fields.l1_gas_price,
+                    tip: fee_fields.tip,
+                    nonce_data_availability_mode: fee_fields.nonce_data_availability_mode,
+                    fee_data_availability_mode: fee_fields.fee_data_availability_mode,
+                    class_hash: contract_class.class_hash(),
+                    compiled_class_hash: *compiled_class_hash,
+                    query_only,
+                })
+            }
+        }
+    }
+
+    async fn sign(
+        &self,
+        transaction_hash: Felt,
+        transaction: Transaction,
+    ) -> Result<Vec<Felt>, RemoteSignerError> {
+        self.signer.sign(&SignRequest { transaction_hash, transaction }).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> Account for RemoteAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    type SignError = RemoteSignerError;
+
+    fn address(&self) -> Felt {
+        self.address
+    }
+
+    fn chain_id(&self) -> Felt {
+        self.chain_id
+    }
+
+    fn declare_legacy(
+        &self,
+        contract_class: Arc<LegacyContractClass>,
+    ) -> LegacyDeclaration<'_, Self> {
+        LegacyDeclaration::new(contract_class, self)
+    }
+
+    fn declare(
+        &self,
+        contract_class: Arc<FlattenedSierraClass>,
+        compiled_class_hash: Felt,
+    ) -> DeclarationV2<'_, Self> {
+        DeclarationV2::new(contract_class, compiled_class_hash, self)
+    }
+
+    fn declare_v2(
+        &self,
+        contract_class: Arc<FlattenedSierraClass>,
+        compiled_class_hash: Felt,
+    ) -> DeclarationV2<'_, Self> {
+        DeclarationV2::new(contract_class, compiled_class_hash, self)
+    }
+
+    fn declare_v3(
+        &self,
+        contract_class: Arc<FlattenedSierraClass>,
+        compiled_class_hash: Felt,
+    ) -> DeclarationV3<'_, Self> {
+        DeclarationV3::new(contract_class, compiled_class_hash, self)
+    }
+
+    fn execute(&self, calls: Vec<Call>) -> ExecutionV1<'_, Self> {
+        ExecutionV1::new(calls, self)
+    }
+
+    fn execute_v1(&self, calls: Vec<Call>) -> ExecutionV1<'_, Self> {
+        ExecutionV1::new(calls, self)
+    }
+
+    fn execute_v3(&self, calls: Vec<Call>) -> ExecutionV3<'_, Self> {
+        ExecutionV3::new(calls, self)
+    }
+
+    async fn sign_execution_v1(
+        &self,
+        execution: &RawExecutionV1,
+        query_only: bool,
+    ) -> Result<Vec<Felt>, Self::SignError> {
+        let transaction_hash =
+            execution.transaction_hash(self.chain_id, self.address, query_only, self);
+
+        let transaction = Transaction::InvokeV1(InvokeV1 {
+            sender_address: self.address,
+            chain_id: self.chain_id,
+            nonce: execution.nonce(),
+            max_fee: execution.max_fee(),
+            calls: Self::remote_calls(execution.calls()),
+            encoding: self.encoding.into(),
+            query_only,
+        });
+
+        self.sign(transaction_hash, transaction).await
+    }
+
+    async fn sign_execution_v3(
+        &self,
+        execution: &RawExecutionV3,
+        query_only: bool,
+    ) -> Result<Vec<Felt>, Self::SignError> {
+        let transaction_hash =
+            execution.transaction_hash(self.chain_id, self.address, query_only, self);
+
+        let transaction = Transaction::InvokeV3(InvokeV3 {
+            sender_address: self.address,
+            chain_id: self.chain_id,
+            nonce: execution.nonce(),
+            gas: execution.gas(),
+            gas_price: execution.gas_price(),
+            tip: 0,
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+            calls: Self::remote_calls(execution.calls()),
+            encoding: self.encoding.into(),
+            query_only,
+        });
+
+        self.sign(transaction_hash, transaction).await
+    }
+
+    async fn sign_legacy_declaration(
+        &self,
+        _declaration: &RawLegacyDeclaration,
+        _query_only: bool,
+    ) -> Result<Vec<Felt>, Self::SignError> {
+        Err(RemoteSignerError::Unsupported("Legacy declarations"))
+    }
+
+    async fn sign_declaration_v2(
+        &self,
+        declaration: &RawDeclarationV2,
+        query_only: bool,
+    ) -> Result<Vec<Felt>, Self::SignError> {
+        let transaction_hash =
+            declaration.transaction_hash(self.chain_id, self.address, query_only);
+
+        let transaction = Transaction::DeclareV2(DeclareV2 {
+            sender_address: self.address,
+            chain_id: self.chain_id,
+            nonce: declaration.nonce(),
+            max_fee: declaration.max_fee(),
+            class_hash: declaration.contract_class().class_hash(),
+            compiled_class_hash: declaration.compiled_class_hash(),
+            query_only,
+        });
+
+        self.sign(transaction_hash, transaction).await
+    }
+
+    async fn sign_declaration_v3(
+        &self,
+        declaration: &RawDeclarationV3,
+        query_only: bool,
+    ) -> Result<Vec<Felt>, Self::SignError> {
+        let transaction_hash =
+            declaration.transaction_hash(self.chain_id, self.address, query_only);
+
+        let transaction = Transaction::DeclareV3(DeclareV3 {
+            sender_address: self.address,
+            chain_id: self.chain_id,
+            nonce: declaration.nonce(),
+            gas: declaration.gas(),
+            gas_price: declaration.gas_price(),
+            tip: 0,
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+            class_hash: declaration.contract_class().class_hash(),
+            compiled_class_hash: declaration.compiled_class_hash(),
+            query_only,
+        });
+
+        self.sign(transaction_hash, transaction).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> TransactionV3Signer for RemoteAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    async fn sign_transaction_v3(
+        &self,
+        transaction: &RawTransactionV3,
+        query_only: bool,
+    ) -> Option<Result<Vec<Felt>, Self::SignError>> {
+        let transaction_hash =
+            transaction.transaction_hash(self.chain_id, self.address, query_only, self);
+        let remote_transaction = self.remote_transaction_v3(transaction, query_only);
+
+        Some(self.sign(transaction_hash, remote_transaction).await)
+    }
+}
+
+impl<P> ExecutionEncoder for RemoteAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    fn encode_calls(&self, calls: &[Call]) -> Vec<Felt> {
+        // Encoded the same way the signer does when recomputing the transaction hash.
+        CallEncoding::from(self.encoding).encode(&Self::remote_calls(calls))
+    }
+}
+
+impl<P> ConnectedAccount for RemoteAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    type Provider = P;
+
+    fn provider(&self) -> &Self::Provider {
+        &self.provider
+    }
+
+    fn block_id(&self) -> BlockId {
+        self.block_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_world::migration::{TxnConfig, TxnV3Config};
+    use dojo_world::utils::{
+        execute_with_cfg, FeeFieldsV3, RawTransactionV3, RawTransactionV3Kind, TransactionWaiter,
+    };
+    use sozo_signers::remote;
+    use starknet::accounts::{Account, Call, ExecutionEncoding};
+    use starknet::core::types::{BlockId, BlockTag, DataAvailabilityMode, Felt};
+    use starknet::macros::{felt, selector};
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Provider};
+    use starknet::signers::LocalWallet;
+    use url::Url;
+
+    use super::{RemoteAccount, RemoteSigner};
+
+    const FEE_TOKEN: Felt =
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+
+    #[katana_runner::katana_test(2, true)]
+    async fn remote_account_sends_transactions() {
+        let account_data = runner.account_data(0);
+        let wallet = LocalWallet::from(account_data.private_key.clone().unwrap());
+
+        let chain_id = runner.provider().chain_id().await.unwrap();
+        let policy = format!(
+            r#"
+            chain_id = "{chain_id:#x}"
+            sender_address = "{:#x}"
+            max_fee = "{:#x}"
+
+            [[contracts]]
+            address = "{FEE_TOKEN:#x}"
+            entrypoints = ["transfer"]
+            "#,
+            account_data.address,
+            u128::MAX,
+        );
+        let token = "s3cr3t".to_string();
+        let (address, server) = remote::serve(
+            ([127, 0, 0, 1], 0).into(),
+            wallet,
+            policy.parse().unwrap(),
+            token.clone(),
+        )
+        .unwrap();
+        tokio::spawn(server);
+
+        let signer = RemoteSigner::new(Url::parse(&format!("http://{address}")).unwrap(), token);
+        let mut account = RemoteAccount::new(
+            runner.provider(),
+            signer,
+            account_data.address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        let transfer = Call {
+            to: FEE_TOKEN,
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1"), felt!("0x1"), felt!("0x0")],
+        };
+        account.execute_v1(vec![transfer]).send().await.unwrap();
+
+        let approve = Call {
+            to: FEE_TOKEN,
+            selector: selector!("approve"),
+            calldata: vec![felt!("0x1"), felt!("0x1"), felt!("0x0")],
+        };
+        assert!(account.execute_v1(vec![approve]).send().await.is_err());
+    }
+
+    #[test]
+    fn v3_transactions_are_hashed_as_the_signer_does() {
+        // The provider and the signer are never reached, hashes are computed locally.
+        let provider =
+            JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+        let signer =
+            RemoteSigner::new(Url::parse("http://localhost:3000").unwrap(), "token".to_string());
+        let account = RemoteAccount::new(
+            provider,
+            signer,
+            felt!("0x1234"),
+            felt!("0x2"),
+            ExecutionEncoding::New,
+        );
+
+        let v3 = TxnV3Config {
+            tip: 10,
+            nonce_data_availability_mode: DataAvailabilityMode::L2,
+            fee_data_availability_mode: DataAvailabilityMode::L2,
+            ..Default::default()
+        };
+        let transfer = Call {
+            to: FEE_TOKEN,
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1"), felt!("0x1"), felt!("0x0")],
+        };
+        let transaction = RawTransactionV3 {
+            kind: RawTransactionV3Kind::Invoke { calls: vec![transfer] },
+            nonce: felt!("0x3"),
+            fee_fields: FeeFieldsV3::new(&v3, 1000, 100_000_000_000),
+        };
+
+        for query_only in [false, true] {
+            assert_eq!(
+                account.remote_transaction_v3(&transaction, query_only).transaction_hash(),
+                transaction.transaction_hash(
+                    account.chain_id(),
+                    account.address(),
+                    query_only,
+                    &account
+                )
+            );
+        }
+    }
+
+    #[katana_runner::katana_test(2, true)]
+    async fn remote_account_sends_tipped_v3_transactions() {
+        let account_data = runner.account_data(0);
+        let wallet = LocalWallet::from(account_data.private_key.clone().unwrap());
+
+        let chain_id = runner.provider().chain_id().await.unwrap();
+        let policy = format!(
+            r#"
+            chain_id = "{chain_id:#x}"
+            sender_address = "{:#x}"
+            max_gas = 1000000000
+            max_gas_price = 1000000000000000000
+            max_tip = 1
+
+            [[contracts]]
+            address = "{FEE_TOKEN:#x}"
+            entrypoints = ["transfer"]
+            "#,
+            account_data.address,
+        );
+        let token = "s3cr3t".to_string();
+        let (address, server) = remote::serve(
+            ([127, 0, 0, 1], 0).into(),
+            wallet,
+            policy.parse().unwrap(),
+            token.clone(),
+        )
+        .unwrap();
+        tokio::spawn(server);
+
+        let signer = RemoteSigner::new(Url::parse(&format!("http://{address}")).unwrap(), token);
+        let mut account = RemoteAccount::new(
+            runner.provider(),
+            signer,
+            account_data.address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        let transfer = Call {
+            to: FEE_TOKEN,
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1"), felt!("0x1"), felt!("0x0")],
+        };
+        let config = |tip| TxnConfig {
+            v3: Some(TxnV3Config { tip, ..Default::default() }),
+            ..Default::default()
+        };
+
+        let res = execute_with_cfg(&account, vec![transfer.clone()], &config(1)).await.unwrap();
+        TransactionWaiter::new(res.transaction_hash, &runner.provider()).await.unwrap();
+
+        // The tip is capped by the policy of the signer.
+        assert!(execute_with_cfg(&account, vec![transfer], &config(2)).await.is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use dojo_world::utils::{RawTransactionV3, TransactionV3Signer};
+use sozo_signers::remote::RemoteSignerError;
 use starknet::accounts::{
     single_owner, Account, Call, ConnectedAccount, DeclarationV2, DeclarationV3, ExecutionEncoder,
     ExecutionV1, ExecutionV3, LegacyDeclaration, RawDeclarationV2, RawDeclarationV3,
@@ -9,16 +11,20 @@ use starknet::accounts::{
 use starknet::core::types::contract::legacy::LegacyContractClass;
 use starknet::core::types::{BlockId, Felt, FlattenedSierraClass};
 use starknet::providers::Provider;
-use starknet::signers::{local_wallet, LocalWallet};
+use starknet::signers::{local_wallet, LocalWallet, Signer};
 
 #[cfg(feature = "controller")]
 use super::controller::ControllerSessionAccount;
+use super::remote::RemoteAccount;
 
 #[derive(Debug, thiserror::Error)]
 pub enum SozoAccountSignError {
     #[error(transparent)]
     Standard(#[from] single_owner::SignError<local_wallet::SignError>),
 
+    #[error(transparent)]
+    Remote(#[from] RemoteSignerError),
+
     #[cfg(feature = "controller")]
     #[error(transparent)]
     Controller(#[from] account_sdk::signers::SignError),
@@ -37,7 +43,10 @@ where
     P: Send,
     P: Provider,
 {
-    Standard(SingleOwnerAccount<P, LocalWallet>),
+    /// The account along with its signer, to sign the V3 transactions it can't build.
+    Standard(SingleOwnerAccount<P, LocalWallet>, LocalWallet),
+
+    Remote(RemoteAccount<P>),
 
     #[cfg(feature = "controller")]
     Controller(ControllerSessionAccount<P>),
@@ -54,7 +63,8 @@ where
 
     fn address(&self) -> Felt {
         match self {
-            Self::Standard(account) => account.address(),
+            Self::Standard(account, _) => account.address(),
+            Self::Remote(account) => account.address(),
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.address(),
         }
@@ -62,7 +72,8 @@ where
 
     fn chain_id(&self) -> Felt {
         match self {
-            Self::Standard(account) => account.chain_id(),
+            Self::Standard(account, _) => account.chain_id(),
+            Self::Remote(account) => account.chain_id(),
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.chain_id(),
         }
@@ -117,7 +128,8 @@ where
         query_only: bool,
     ) -> Result<Vec<Felt>, Self::SignError> {
         let result = match self {
-            Self::Standard(account) => account.sign_execution_v1(execution, query_only).await?,
+            Self::Standard(account, _) => account.sign_execution_v1(execution, query_only).await?,
+            Self::Remote(account) => account.sign_execution_v1(execution, query_only).await?,
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.sign_execution_v1(execution, query_only).await?,
         };
@@ -130,7 +142,8 @@ where
         query_only: bool,
     ) -> Result<Vec<Felt>, Self::SignError> {
         let result = match self {
-            Self::Standard(account) => account.sign_execution_v3(execution, query_only).await?,
+            Self::Standard(account, _) => account.sign_execution_v3(execution, query_only).await?,
+            Self::Remote(account) => account.sign_execution_v3(execution, query_only).await?,
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.sign_execution_v3(execution, query_only).await?,
         };
@@ -143,7 +156,11 @@ where
         query_only: bool,
     ) -> Result<Vec<Felt>, Self::SignError> {
         match self {
-            Self::Standard(account) => {
+            Self::Standard(account, _) => {
+                let result = account.sign_legacy_declaration(declaration, query_only).await?;
+                Ok(result)
+            }
+            Self::Remote(account) => {
                 let result = account.sign_legacy_declaration(declaration, query_only).await?;
                 Ok(result)
             }
@@ -161,7 +178,10 @@ where
         query_only: bool,
     ) -> Result<Vec<Felt>, Self::SignError> {
         let result = match self {
-            Self::Standard(account) => account.sign_declaration_v2(declaration, query_only).await?,
+            Self::Standard(account, _) => {
+                account.sign_declaration_v2(declaration, query_only).await?
+            }
+            Self::Remote(account) => account.sign_declaration_v2(declaration, query_only).await?,
 
             #[cfg(feature = "controller")]
             Self::Controller(account) => {
@@ -177,7 +197,10 @@ where
         query_only: bool,
     ) -> Result<Vec<Felt>, Self::SignError> {
         let result = match self {
-            Self::Standard(account) => account.sign_declaration_v3(declaration, query_only).await?,
+            Self::Standard(account, _) => {
+                account.sign_declaration_v3(declaration, query_only).await?
+            }
+            Self::Remote(account) => account.sign_declaration_v3(declaration, query_only).await?,
 
             #[cfg(feature = "controller")]
             Self::Controller(account) => {
@@ -188,6 +211,45 @@ where
     }
 }
 
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> TransactionV3Signer for SozoAccount<P>
+where
+    P: Provider,
+    P: Send + Sync,
+{
+    async fn sign_transaction_v3(
+        &self,
+        transaction: &RawTransactionV3,
+        query_only: bool,
+    ) -> Option<Result<Vec<Felt>, Self::SignError>> {
+        match self {
+            Self::Standard(account, signer) => {
+                let transaction_hash = transaction.transaction_hash(
+                    account.chain_id(),
+                    account.address(),
+                    query_only,
+                    account,
+                );
+
+                let result = signer
+                    .sign_hash(&transaction_hash)
+                    .await
+                    .map(|signature| vec![signature.r, signature.s])
+                    .map_err(|e| SozoAccountSignError::from(single_owner::SignError::Signer(e)));
+                Some(result)
+            }
+            Self::Remote(account) => {
+                let result = account.sign_transaction_v3(transaction, query_only).await?;
+                Some(result.map_err(Into::into))
+            }
+            // Session transactions are signed by the Controller SDK, from its own executions only.
+            #[cfg(feature = "controller")]
+            Self::Controller(_) => None,
+        }
+    }
+}
+
 impl<P> ExecutionEncoder for SozoAccount<P>
 where
     P: Provider,
@@ -195,7 +257,8 @@ where
 {
     fn encode_calls(&self, calls: &[Call]) -> Vec<Felt> {
         match self {
-            Self::Standard(account) => account.encode_calls(calls),
+            Self::Standard(account, _) => account.encode_calls(calls),
+            Self::Remote(account) => account.encode_calls(calls),
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.encode_calls(calls),
         }
@@ -211,7 +274,8 @@ where
 
     fn provider(&self) -> &Self::Provider {
         match self {
-            Self::Standard(account) => account.provider(),
+            Self::Standard(account, _) => account.provider(),
+            Self::Remote(account) => account.provider(),
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.provider(),
         }
@@ -219,7 +283,8 @@ where
 
     fn block_id(&self) -> BlockId {
         match self {
-            Self::Standard(account) => account.block_id(),
+            Self::Standard(account, _) => account.block_id(),
+            Self::Remote(account) => account.block_id(),
             #[cfg(feature = "controller")]
             Self::Controller(account) => account.block_id(),
         }
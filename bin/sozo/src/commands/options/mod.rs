@@ -8,5 +8,7 @@ const STARKNET_RPC_URL_ENV_VAR: &str = "STARKNET_RPC_URL";
 const DOJO_PRIVATE_KEY_ENV_VAR: &str = "DOJO_PRIVATE_KEY";
 const DOJO_KEYSTORE_PATH_ENV_VAR: &str = "DOJO_KEYSTORE_PATH";
 const DOJO_KEYSTORE_PASSWORD_ENV_VAR: &str = "DOJO_KEYSTORE_PASSWORD";
+const DOJO_SIGNER_URL_ENV_VAR: &str = "DOJO_SIGNER_URL";
+pub(crate) const DOJO_SIGNER_TOKEN_ENV_VAR: &str = "DOJO_SIGNER_TOKEN";
 const DOJO_ACCOUNT_ADDRESS_ENV_VAR: &str = "DOJO_ACCOUNT_ADDRESS";
 const DOJO_WORLD_ADDRESS_ENV_VAR: &str = "DOJO_WORLD_ADDRESS";
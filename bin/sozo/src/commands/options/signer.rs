@@ -6,8 +6,12 @@ use dojo_world::config::Environment;
 use starknet::core::types::Felt;
 use starknet::signers::{LocalWallet, SigningKey};
 use tracing::trace;
+use url::Url;
 
-use super::{DOJO_KEYSTORE_PASSWORD_ENV_VAR, DOJO_KEYSTORE_PATH_ENV_VAR, DOJO_PRIVATE_KEY_ENV_VAR};
+use super::{
+    DOJO_KEYSTORE_PASSWORD_ENV_VAR, DOJO_KEYSTORE_PATH_ENV_VAR, DOJO_PRIVATE_KEY_ENV_VAR,
+    DOJO_SIGNER_TOKEN_ENV_VAR, DOJO_SIGNER_URL_ENV_VAR,
+};
 
 #[derive(Debug, Args, Clone)]
 #[command(next_help_heading = "Signer options")]
@@ -16,6 +20,8 @@ use super::{DOJO_KEYSTORE_PASSWORD_ENV_VAR, DOJO_KEYSTORE_PATH_ENV_VAR, DOJO_PRI
 //   `keystore_password`. This is enforced by Clap.
 // - For `Scarb.toml`: if both private_key and keystore are specified in `Scarb.toml` private_key
 //   will take priority
+// - `signer_url` can't be combined with the other signers on the commandline. In `Scarb.toml`, it
+//   is ignored if a private key or keystore is given on the commandline.
 pub struct SignerOptions {
     #[arg(long, env = DOJO_PRIVATE_KEY_ENV_VAR)]
     #[arg(conflicts_with = "keystore_path")]
@@ -39,6 +45,21 @@ pub struct SignerOptions {
     #[arg(help = "The keystore password. Used with --keystore.")]
     #[arg(global = true)]
     pub keystore_password: Option<String>,
+
+    #[arg(long, env = DOJO_SIGNER_URL_ENV_VAR)]
+    #[arg(value_name = "URL")]
+    #[arg(help_heading = "Signer options - REMOTE")]
+    #[arg(help = "The URL of a remote signer, as served by `sozo signer serve`.")]
+    #[arg(global = true)]
+    #[arg(group = "signer")]
+    pub signer_url: Option<Url>,
+
+    #[arg(long, env = DOJO_SIGNER_TOKEN_ENV_VAR)]
+    #[arg(value_name = "TOKEN")]
+    #[arg(help_heading = "Signer options - REMOTE")]
+    #[arg(help = "The token to authenticate to the remote signer with.")]
+    #[arg(global = true)]
+    pub signer_token: Option<String>,
 }
 
 impl SignerOptions {
@@ -71,6 +92,22 @@ impl SignerOptions {
         ))
     }
 
+    /// The URL of the remote signer to use instead of a local one, if any.
+    pub fn signer_url(&self, env_metadata: Option<&Environment>) -> Result<Option<Url>> {
+        if let Some(url) = &self.signer_url {
+            return Ok(Some(url.clone()));
+        }
+
+        if self.private_key.is_some() || self.keystore_path.is_some() {
+            return Ok(None);
+        }
+
+        match env_metadata.and_then(|env| env.signer_url()) {
+            Some(url) => Ok(Some(Url::parse(url)?)),
+            None => Ok(None),
+        }
+    }
+
     pub fn private_key(&self, env_metadata: Option<&Environment>) -> Option<String> {
         if let Some(s) = &self.private_key {
             Some(s.to_owned())
@@ -103,6 +140,7 @@ mod tests {
     use clap::Parser;
     use starknet::signers::{LocalWallet, Signer, SigningKey};
     use starknet_crypto::Felt;
+    use url::Url;
 
     use super::{SignerOptions, DOJO_KEYSTORE_PASSWORD_ENV_VAR, DOJO_PRIVATE_KEY_ENV_VAR};
 
@@ -243,6 +281,50 @@ mod tests {
         assert!(parse_result.is_err());
     }
 
+    #[test]
+    fn dont_allow_both_private_key_and_signer_url() {
+        let parse_result = Command::try_parse_from([
+            "sozo",
+            "--signer-url",
+            "http://localhost:5051",
+            "--private-key",
+            "0x1",
+        ]);
+        assert!(parse_result.is_err());
+    }
+
+    #[test]
+    fn signer_url_from_args() {
+        let cmd = Command::parse_from(["sozo", "--signer-url", "http://localhost:5051"]);
+        assert_eq!(
+            cmd.signer.signer_url(None).unwrap(),
+            Some(Url::parse("http://localhost:5051").unwrap())
+        );
+    }
+
+    #[test]
+    fn signer_url_from_env_metadata() {
+        let env_metadata = dojo_world::config::Environment {
+            signer_url: Some("http://localhost:5051".to_owned()),
+            ..Default::default()
+        };
+
+        // Built directly to not depend on the signer environment variables.
+        let mut signer = SignerOptions {
+            private_key: None,
+            keystore_path: None,
+            keystore_password: None,
+            signer_url: None,
+        };
+        assert_eq!(
+            signer.signer_url(Some(&env_metadata)).unwrap(),
+            Some(Url::parse("http://localhost:5051").unwrap())
+        );
+
+        signer.private_key = Some("0x1".to_owned());
+        assert_eq!(signer.signer_url(Some(&env_metadata)).unwrap(), None);
+    }
+
     #[test]
     fn keystore_path_without_keystore_password() {
         let keystore_path = "./tests/test_data/keystore/test.json";
@@ -1,7 +1,7 @@
 use anyhow::{bail, Result};
-use clap::Args;
-use dojo_world::migration::{TxnAction, TxnConfig};
-use starknet::core::types::Felt;
+use clap::{Args, ValueEnum};
+use dojo_world::migration::{TxnAction, TxnConfig, TxnV3Config};
+use starknet::core::types::{DataAvailabilityMode, Felt};
 use tracing::trace;
 
 #[derive(Debug, Args, Default)]
@@ -12,16 +12,62 @@ pub struct TransactionOptions {
     #[arg(long_help = "The multiplier to use for the fee estimate. This value will be used on \
                        the estimated fee which will be used as the max fee for the transaction. \
                        (max_fee = estimated_fee * multiplier)")]
-    #[arg(conflicts_with = "max_fee_raw")]
+    #[arg(conflicts_with_all = ["max_fee_raw", "v3"])]
     #[arg(global = true)]
     pub fee_estimate_multiplier: Option<f64>,
 
     #[arg(long)]
     #[arg(help = "Maximum raw value to be used for fees, in Wei.")]
-    #[arg(conflicts_with = "fee_estimate_multiplier")]
+    #[arg(conflicts_with_all = ["fee_estimate_multiplier", "v3"])]
     #[arg(global = true)]
     pub max_fee_raw: Option<Felt>,
 
+    #[arg(long)]
+    #[arg(help = "Send V3 transactions, paying the fees in STRK instead of ETH.")]
+    #[arg(global = true)]
+    pub v3: bool,
+
+    #[arg(long, value_name = "AMOUNT")]
+    #[arg(help = "Maximum amount of L1 gas of V3 transactions. Estimated if not specified.")]
+    #[arg(requires = "v3")]
+    #[arg(global = true)]
+    pub l1_gas: Option<u64>,
+
+    #[arg(long, value_name = "PRICE")]
+    #[arg(help = "Maximum price of a unit of L1 gas of V3 transactions, in Fri. Estimated if \
+                  not specified.")]
+    #[arg(requires = "v3")]
+    #[arg(global = true)]
+    pub l1_gas_price: Option<u128>,
+
+    #[arg(long, value_name = "MULTIPLIER")]
+    #[arg(help = "The multiplier to use for the L1 gas estimate of V3 transactions.")]
+    #[arg(long_help = "The multiplier to use for the L1 gas estimate of V3 transactions. This \
+                       value will be used on the estimated L1 gas amount which will be used as \
+                       the maximum L1 gas amount of the transaction. (l1_gas = estimated_gas * \
+                       multiplier)")]
+    #[arg(requires = "v3", conflicts_with = "l1_gas")]
+    #[arg(global = true)]
+    pub gas_estimate_multiplier: Option<f64>,
+
+    #[arg(long, value_name = "AMOUNT")]
+    #[arg(help = "Tip of V3 transactions, paid to the sequencer.")]
+    #[arg(requires = "v3")]
+    #[arg(global = true)]
+    pub tip: Option<u64>,
+
+    #[arg(long, value_enum, value_name = "MODE")]
+    #[arg(help = "Data availability mode of the nonce of V3 transactions. Defaults to L1.")]
+    #[arg(requires = "v3")]
+    #[arg(global = true)]
+    pub nonce_da_mode: Option<DaMode>,
+
+    #[arg(long, value_enum, value_name = "MODE")]
+    #[arg(help = "Data availability mode of the fee of V3 transactions. Defaults to L1.")]
+    #[arg(requires = "v3")]
+    #[arg(global = true)]
+    pub fee_da_mode: Option<DaMode>,
+
     #[arg(long)]
     #[arg(help = "Wait until the transaction is accepted by the sequencer, returning the status \
                   and hash.")]
@@ -39,23 +85,60 @@ pub struct TransactionOptions {
     pub receipt: bool,
 }
 
+/// The data availability mode of a V3 transaction field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DaMode {
+    L1,
+    L2,
+}
+
+impl From<DaMode> for DataAvailabilityMode {
+    fn from(value: DaMode) -> Self {
+        match value {
+            DaMode::L1 => DataAvailabilityMode::L1,
+            DaMode::L2 => DataAvailabilityMode::L2,
+        }
+    }
+}
+
 impl TransactionOptions {
     pub fn init_wait() -> Self {
         TransactionOptions { wait: true, ..Default::default() }
     }
 
+    /// Returns the fee settings of V3 transactions if `--v3` is set.
+    pub fn txn_v3_config(&self) -> Option<TxnV3Config> {
+        let default = TxnV3Config::default();
+
+        self.v3.then(|| TxnV3Config {
+            l1_gas: self.l1_gas,
+            l1_gas_price: self.l1_gas_price,
+            gas_estimate_multiplier: self.gas_estimate_multiplier,
+            tip: self.tip.unwrap_or(default.tip),
+            nonce_data_availability_mode: self
+                .nonce_da_mode
+                .map_or(default.nonce_data_availability_mode, Into::into),
+            fee_data_availability_mode: self
+                .fee_da_mode
+                .map_or(default.fee_data_availability_mode, Into::into),
+        })
+    }
+
     pub fn to_txn_action(&self, simulate: bool, estimate_only: bool) -> Result<TxnAction> {
+        let v3 = self.txn_v3_config();
+
         match (estimate_only, simulate) {
             (true, true) => {
                 bail!("Both `--estimate-only` and `--simulate` cannot be used at same time.")
             }
-            (true, false) => Ok(TxnAction::Estimate),
-            (false, true) => Ok(TxnAction::Simulate),
+            (true, false) => Ok(TxnAction::Estimate { v3 }),
+            (false, true) => Ok(TxnAction::Simulate { v3 }),
             (false, false) => Ok(TxnAction::Send {
                 wait: self.wait,
                 receipt: self.receipt,
                 max_fee_raw: self.max_fee_raw,
                 fee_estimate_multiplier: self.fee_estimate_multiplier,
+                v3,
             }),
         }
     }
@@ -67,6 +150,7 @@ impl From<TransactionOptions> for TxnConfig {
             fee_estimate_multiplier = value.fee_estimate_multiplier,
             wait = value.wait,
             receipt = value.receipt,
+            v3 = value.v3,
             "Converting TransactionOptions to TxnConfig."
         );
         Self {
@@ -74,6 +158,76 @@ impl From<TransactionOptions> for TxnConfig {
             wait: value.wait,
             receipt: value.receipt,
             max_fee_raw: value.max_fee_raw,
+            v3: value.txn_v3_config(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser, Debug)]
+    struct Command {
+        #[clap(flatten)]
+        transaction: TransactionOptions,
+    }
+
+    #[test]
+    fn test_txn_config_v1() {
+        let cmd = Command::parse_from(["sozo", "--max-fee-raw", "0x10", "--wait"]);
+        let config = TxnConfig::from(cmd.transaction);
+
+        assert_eq!(config.max_fee_raw, Some(Felt::from(0x10_u64)));
+        assert!(config.wait);
+        assert!(config.v3.is_none());
+    }
+
+    #[test]
+    fn test_txn_config_v3() {
+        let cmd = Command::parse_from(["sozo", "--v3", "--l1-gas", "1000", "--l1-gas-price", "16"]);
+        let config = TxnConfig::from(cmd.transaction);
+
+        let v3 = config.v3.unwrap();
+        assert_eq!(v3.l1_gas, Some(1000));
+        assert_eq!(v3.l1_gas_price, Some(16));
+        assert_eq!(v3.gas_estimate_multiplier, None);
+        assert_eq!(v3.tip, 0);
+        assert_eq!(v3.nonce_data_availability_mode, DataAvailabilityMode::L1);
+        assert_eq!(v3.fee_data_availability_mode, DataAvailabilityMode::L1);
+
+        let cmd = Command::parse_from([
+            "sozo",
+            "--v3",
+            "--tip",
+            "10",
+            "--nonce-da-mode",
+            "l2",
+            "--fee-da-mode",
+            "l2",
+        ]);
+        let v3 = TxnConfig::from(cmd.transaction).v3.unwrap();
+        assert_eq!(v3.tip, 10);
+        assert_eq!(v3.nonce_data_availability_mode, DataAvailabilityMode::L2);
+        assert_eq!(v3.fee_data_availability_mode, DataAvailabilityMode::L2);
+        assert!(!v3.has_default_tip_and_da_modes());
+
+        // the gas estimate multiplier is only used by V3 transactions, and the fee one by V1
+        let cmd = Command::parse_from(["sozo", "--v3", "--gas-estimate-multiplier", "2"]);
+        let config = TxnConfig::from(cmd.transaction);
+        assert_eq!(config.v3.unwrap().gas_estimate_multiplier, Some(2.0));
+        assert_eq!(config.fee_estimate_multiplier, None);
+
+        assert!(Command::try_parse_from(["sozo", "--gas-estimate-multiplier", "2"]).is_err());
+        assert!(
+            Command::try_parse_from(["sozo", "--v3", "--fee-estimate-multiplier", "2"]).is_err()
+        );
+
+        assert!(Command::try_parse_from(["sozo", "--l1-gas", "1000"]).is_err());
+        assert!(Command::try_parse_from(["sozo", "--tip", "10"]).is_err());
+        assert!(Command::try_parse_from(["sozo", "--fee-da-mode", "l2"]).is_err());
+        assert!(Command::try_parse_from(["sozo", "--v3", "--max-fee-raw", "0x10"]).is_err());
+    }
+}
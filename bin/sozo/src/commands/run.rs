@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::metadata::get_default_namespace_from_ws;
+use scarb::core::Config;
+use serde::Deserialize;
+use sozo_ops::run::{self, Step};
+use tracing::trace;
+
+use super::calldata_decoder;
+use super::execute::contract_call;
+use super::options::account::AccountOptions;
+use super::options::starknet::StarknetOptions;
+use super::options::transaction::TransactionOptions;
+use super::options::world::WorldOptions;
+use crate::utils;
+
+#[derive(Debug, Args)]
+#[command(about = "Run a script of calls, executed in multicall transactions.")]
+pub struct RunArgs {
+    #[arg(help = "The path to the script, in TOML or JSON format.")]
+    pub script: Utf8PathBuf,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+
+    #[command(flatten)]
+    pub account: AccountOptions,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
+    #[command(flatten)]
+    pub transaction: TransactionOptions,
+}
+
+/// A script run by `sozo run`, e.g.:
+///
+/// ```toml
+/// batch_size = 10
+///
+/// [[steps]]
+/// kind = "execute"
+/// contract = "actions"
+/// entrypoint = "move"
+/// calldata = "1"
+///
+/// [[steps]]
+/// kind = "call"
+/// contract = "actions"
+/// entrypoint = "get_player_position"
+/// expect = "0x0,0x0,0x0"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+    /// Maximum number of calls of a multicall transaction.
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    steps: Vec<ScriptStep>,
+}
+
+fn default_batch_size() -> usize {
+    10
+}
+
+/// A step of a script. Contracts are given by tag or address, calldata and expected outputs use
+/// the format of the `sozo execute` calldata.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+enum ScriptStep {
+    Execute { contract: String, entrypoint: String, calldata: Option<String> },
+    Call { contract: String, entrypoint: String, calldata: Option<String>, expect: Option<String> },
+}
+
+impl Script {
+    fn load(path: &Utf8PathBuf) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script `{path}`"))?;
+
+        match path.extension() {
+            Some("json") => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse script `{path}`")),
+            Some("toml") => {
+                toml::from_str(&content).with_context(|| format!("Failed to parse script `{path}`"))
+            }
+            _ => anyhow::bail!("Script `{path}` is expected to be a `.toml` or `.json` file."),
+        }
+    }
+
+    fn contracts(&self) -> impl Iterator<Item = &str> {
+        self.steps.iter().map(|step| match step {
+            ScriptStep::Execute { contract, .. } | ScriptStep::Call { contract, .. } => {
+                contract.as_str()
+            }
+        })
+    }
+}
+
+impl ScriptStep {
+    fn into_step(self, default_namespace: Option<&str>) -> Result<Step> {
+        let step =
+            match self {
+                ScriptStep::Execute { contract, entrypoint, calldata } => Step::Execute(
+                    contract_call(&contract, &entrypoint, calldata.as_deref(), default_namespace)?,
+                ),
+                ScriptStep::Call { contract, entrypoint, calldata, expect } => Step::Call {
+                    call: contract_call(
+                        &contract,
+                        &entrypoint,
+                        calldata.as_deref(),
+                        default_namespace,
+                    )?,
+                    expected: expect
+                        .map(|expect| calldata_decoder::decode_calldata(&expect))
+                        .transpose()?,
+                },
+            };
+
+        Ok(step)
+    }
+}
+
+impl RunArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        trace!(args = ?self);
+        let env_metadata = utils::load_metadata_from_config(config)?;
+
+        let script = Script::load(&self.script)?;
+        let default_namespace = if script.contracts().all(utils::is_address) {
+            None
+        } else {
+            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+            Some(get_default_namespace_from_ws(&ws)?)
+        };
+
+        let batch_size = script.batch_size;
+        let steps = script
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| {
+                step.into_step(default_namespace.as_deref())
+                    .with_context(|| format!("Invalid step {index}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        config.tokio_handle().block_on(async {
+            let world = utils::world_from_env_metadata(
+                self.world,
+                self.account,
+                self.starknet,
+                &env_metadata,
+                config,
+            )
+            .await?;
+
+            let tx_config = self.transaction.into();
+
+            trace!(?steps, batch_size, "Executing Run command.");
+            run::run(&config.ui(), steps, batch_size, &world, &tx_config).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script: Script = toml::from_str(
+            r#"
+            [[steps]]
+            kind = "execute"
+            contract = "actions"
+            entrypoint = "spawn"
+
+            [[steps]]
+            kind = "call"
+            contract = "0x1234"
+            entrypoint = "get_player_position"
+            expect = "0x0,u256:1"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(script.batch_size, default_batch_size());
+        let steps = script
+            .steps
+            .into_iter()
+            .map(|step| step.into_step(Some("ns")).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(matches!(
+            &steps[0],
+            Step::Execute(call) if call.tag_or_address == "ns-actions" && call.calldata.is_empty()
+        ));
+        assert!(matches!(
+            &steps[1],
+            Step::Call { call, expected: Some(expected) }
+                if call.tag_or_address == "0x1234"
+                    && *expected == vec![Felt::ZERO, Felt::ONE, Felt::ZERO]
+        ));
+
+        let script: Script = serde_json::from_str(
+            r#"{ "batch_size": 2, "steps": [{ "kind": "execute", "contract": "actions",
+            "entrypoint": "move", "calldata": "1" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(script.batch_size, 2);
+
+        assert!(toml::from_str::<Script>("[[steps]]\nkind = \"unknown\"").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use scarb::core::Config;
+use sozo_signers::remote::{self, SignerPolicy};
+use starknet::signers::{LocalWallet, SigningKey};
+use tracing::trace;
+
+use super::options::DOJO_SIGNER_TOKEN_ENV_VAR;
+
+#[derive(Debug, Args)]
+pub struct SignerArgs {
+    #[clap(subcommand)]
+    command: SignerCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SignerCommand {
+    #[clap(about = "Serve signatures from a keystore to remote signer clients (`--signer-url`).")]
+    Serve {
+        #[clap(long, default_value = "127.0.0.1:5051")]
+        #[clap(help = "The address to listen on.")]
+        addr: SocketAddr,
+
+        #[clap(long, value_name = "PATH")]
+        #[clap(required_unless_present = "allow_all")]
+        #[clap(help = "A TOML policy file pinning the chain and the account, capping the fees \
+                       and restricting the contracts and entrypoints transactions can call.")]
+        policy: Option<PathBuf>,
+
+        #[clap(long, conflicts_with = "policy")]
+        #[clap(help = "Sign any transaction requested, without a policy.")]
+        allow_all: bool,
+
+        #[clap(long, env = DOJO_SIGNER_TOKEN_ENV_VAR, value_name = "TOKEN")]
+        #[clap(help = "The token clients must authenticate with (`--signer-token`).")]
+        token: String,
+
+        #[clap(long, help = "Supply password from command line option instead of prompt")]
+        password: Option<String>,
+
+        #[clap(help = "Path to the JSON keystore")]
+        keystore: PathBuf,
+    },
+}
+
+impl SignerArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        trace!(args = ?self);
+        match self.command {
+            SignerCommand::Serve { addr, policy, allow_all, token, password, keystore } => {
+                let password = match password {
+                    Some(password) => password,
+                    None => rpassword::prompt_password("Enter password: ")?,
+                };
+
+                let signing_key = SigningKey::from_keystore(keystore, &password)?;
+                let public_key = signing_key.verifying_key().scalar();
+                let wallet = LocalWallet::from_signing_key(signing_key);
+
+                let policy = match policy {
+                    Some(path) => SignerPolicy::from_file(&path)?,
+                    None => {
+                        debug_assert!(allow_all);
+                        config.ui().warn(
+                            "`--allow-all` given, any transaction requested will be signed. Use \
+                             `--policy` to restrict the transactions to sign.",
+                        );
+                        SignerPolicy::allow_all()
+                    }
+                };
+
+                config.tokio_handle().block_on(async {
+                    let (addr, server) = remote::serve(addr, wallet, policy, token)?;
+                    config.ui().print(format!(
+                        "Signing with public key {public_key:#x}, listening on http://{addr}"
+                    ));
+
+                    server.await;
+                    Ok(())
+                })
+            }
+        }
+    }
+}
@@ -1,12 +1,17 @@
 //! Compiles and runs tests for a Dojo project.
+use std::path::PathBuf;
+
 use anyhow::{bail, Result};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
 use cairo_lang_compiler::project::{ProjectConfig, ProjectConfigContent};
 use cairo_lang_filesystem::cfg::{Cfg, CfgSet};
 use cairo_lang_filesystem::ids::Directory;
+use cairo_lang_runner::casm_run::format_next_item;
+use cairo_lang_runner::RunResultValue;
 use cairo_lang_starknet::starknet_plugin_suite;
-use cairo_lang_test_plugin::test_plugin_suite;
+use cairo_lang_test_plugin::test_config::{PanicExpectation, TestExpectation};
+use cairo_lang_test_plugin::{test_plugin_suite, TestConfig};
 use cairo_lang_test_runner::{CompiledTestRunner, RunProfilerConfig, TestCompiler, TestRunConfig};
 use clap::Args;
 use dojo_lang::compiler::{collect_core_crate_ids, collect_external_crate_ids, Props};
@@ -20,6 +25,9 @@ use scarb::ops::{self, CompileOpts};
 use scarb_ui::args::{FeaturesSpec, PackagesFilter};
 use tracing::trace;
 
+use super::coverage::Coverage;
+use super::gas_report::GasReport;
+
 pub(crate) const LOG_TARGET: &str = "sozo::cli::commands::test";
 
 #[derive(Debug, Clone, PartialEq, clap::ValueEnum)]
@@ -60,6 +68,33 @@ pub struct TestArgs {
     /// Should we print the resource usage.
     #[arg(long, default_value_t = false)]
     print_resource_usage: bool,
+    /// Report the steps, builtins and estimated L1 gas of the contract entrypoints called by the
+    /// tests. The L1 gas is estimated with the fee weights of Starknet 0.13.1.
+    ///
+    /// The tests are then run by sozo instead of the test runner, without profiling.
+    #[arg(long, default_value_t = false)]
+    #[arg(conflicts_with_all = ["profiler_mode", "print_resource_usage"])]
+    gas_report: bool,
+    /// Save the gas report to the given file, to be used as a baseline.
+    #[arg(long, value_name = "PATH", requires = "gas_report")]
+    gas_report_save: Option<PathBuf>,
+    /// Compare the gas report to the baseline saved in the given file, failing if an entrypoint
+    /// regressed beyond the threshold.
+    #[arg(long, value_name = "PATH", requires = "gas_report")]
+    gas_report_baseline: Option<PathBuf>,
+    /// The increase of an entrypoint average steps, builtins or estimated L1 gas over the
+    /// baseline, in percent, tolerated before failing.
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0, requires = "gas_report")]
+    gas_report_threshold: f64,
+    /// Record the lines of Cairo source executed by the tests, including the contracts they call.
+    ///
+    /// The tests are then run by sozo instead of the test runner.
+    #[arg(long, default_value_t = false)]
+    #[arg(conflicts_with_all = ["profiler_mode", "print_resource_usage"])]
+    coverage: bool,
+    /// The file to write the LCOV coverage report to.
+    #[arg(long, value_name = "PATH", default_value = "lcov.info", requires = "coverage")]
+    coverage_output: PathBuf,
     /// Specify the features to activate.
     #[command(flatten)]
     features: FeaturesSpec,
@@ -112,6 +147,18 @@ impl TestArgs {
             })
             .collect::<Vec<_>>();
 
+        let run_config = || TestRunConfig {
+            filter: self.filter.clone(),
+            ignored: self.ignored,
+            include_ignored: self.include_ignored,
+            run_profiler: self.profiler_mode.clone().into(),
+            gas_enabled: self.gas_enabled,
+            print_resource_usage: self.print_resource_usage,
+        };
+
+        let mut gas_report = self.gas_report.then(GasReport::default);
+        let mut coverage = self.coverage.then(Coverage::default);
+
         for unit in compilation_units {
             let mut unit = if let CompilationUnit::Cairo(unit) = unit {
                 unit
@@ -164,30 +211,184 @@ impl TestArgs {
                 main_crate_ids.extend(collect_external_crate_ids(&db, external_contracts));
             }
 
-            let config = TestRunConfig {
-                filter: self.filter.clone(),
-                ignored: self.ignored,
-                include_ignored: self.include_ignored,
-                run_profiler: self.profiler_mode.clone().into(),
-                gas_enabled: self.gas_enabled,
-                print_resource_usage: self.print_resource_usage,
-            };
-
             let compiler =
                 TestCompiler { db: db.snapshot(), main_crate_ids, test_crate_ids, starknet: true };
 
-            let runner = CompiledTestRunner { compiled: compiler.build()?, config };
+            let compiled = compiler.build()?;
+
+            // The tests are run once, recording the coverage and the costs of the entrypoints
+            // they call when requested.
+            if let Some(coverage) = coverage.as_mut() {
+                let root = unit.main_component().package.root().as_std_path();
+                coverage.run_tests(
+                    &compiler,
+                    compiled,
+                    &run_config(),
+                    root,
+                    gas_report.as_mut(),
+                )?;
+            } else if let Some(gas_report) = gas_report.as_mut() {
+                gas_report.run_tests(compiled, &run_config())?;
+            } else {
+                let runner = CompiledTestRunner { compiled, config: run_config() };
 
-            // Database is required here for the profiler to work.
-            runner.run(Some(&db))?;
+                // Database is required here for the profiler to work.
+                runner.run(Some(&db))?;
+            }
 
             println!();
         }
 
+        if let Some(gas_report) = gas_report {
+            println!("{}", gas_report.table());
+
+            if let Some(path) = &self.gas_report_save {
+                gas_report.save(path)?;
+            }
+
+            if let Some(path) = &self.gas_report_baseline {
+                let baseline = GasReport::from_file(path)?;
+                let regressions = gas_report.compare(&baseline, self.gas_report_threshold);
+
+                for regression in &regressions {
+                    config.ui().error(regression.to_string());
+                }
+
+                if !regressions.is_empty() {
+                    bail!(
+                        "{} entrypoint cost(s) regressed beyond the {}% threshold",
+                        regressions.len(),
+                        self.gas_report_threshold
+                    );
+                }
+            }
+        }
+
+        if let Some(coverage) = coverage {
+            coverage.save(&self.coverage_output)?;
+
+            let (hit, found) = coverage.lines();
+            config.ui().print(format!(
+                "coverage: {hit}/{found} lines executed, report written to {}",
+                self.coverage_output.display()
+            ));
+        }
+
         Ok(())
     }
 }
 
+/// Returns the tests matching the filter of `config`, marked as ignored if they are not to be run
+/// as the test runner does, and the number of tests filtered out.
+pub(crate) fn selected_tests(
+    named_tests: Vec<(String, TestConfig)>,
+    config: &TestRunConfig,
+) -> (Vec<(String, TestConfig)>, usize) {
+    let total = named_tests.len();
+    let selected = named_tests
+        .into_iter()
+        .filter(|(name, _)| name.contains(&config.filter))
+        .map(|(name, mut test)| {
+            test.ignored = if config.include_ignored {
+                false
+            } else if config.ignored {
+                !test.ignored
+            } else {
+                test.ignored
+            };
+            (name, test)
+        })
+        .collect::<Vec<_>>();
+
+    let filtered_out = total - selected.len();
+    (selected, filtered_out)
+}
+
+/// Results of the tests run by sozo instead of the test runner, reported the same way.
+#[derive(Debug, Default)]
+pub(crate) struct TestResults {
+    passed: usize,
+    ignored: usize,
+    filtered_out: usize,
+    /// The failed tests, with the value they returned or panicked with.
+    failed: Vec<(String, RunResultValue)>,
+}
+
+impl TestResults {
+    pub(crate) fn new(n_tests: usize, filtered_out: usize) -> Self {
+        println!("running {n_tests} tests");
+        Self { filtered_out, ..Default::default() }
+    }
+
+    pub(crate) fn ignore(&mut self, name: &str) {
+        println!("test {name} ... ignored");
+        self.ignored += 1;
+    }
+
+    /// Records the result of a test, which passes if it ran as `expectation` expects.
+    pub(crate) fn add(
+        &mut self,
+        name: String,
+        expectation: &TestExpectation,
+        value: RunResultValue,
+    ) {
+        let passed = match (expectation, &value) {
+            (TestExpectation::Success, RunResultValue::Success(_)) => true,
+            (TestExpectation::Panics(PanicExpectation::Any), RunResultValue::Panic(_)) => true,
+            (
+                TestExpectation::Panics(PanicExpectation::Exact(expected)),
+                RunResultValue::Panic(data),
+            ) => expected == data,
+            _ => false,
+        };
+
+        if passed {
+            println!("test {name} ... ok");
+            self.passed += 1;
+        } else {
+            println!("test {name} ... fail");
+            self.failed.push((name, value));
+        }
+    }
+
+    /// Prints the summary of the run, failing if a test failed.
+    pub(crate) fn finish(self) -> Result<()> {
+        let Self { passed, ignored, filtered_out, failed } = self;
+
+        if failed.is_empty() {
+            println!(
+                "test result: ok. {passed} passed; 0 failed; {ignored} ignored; {filtered_out} \
+                 filtered out;"
+            );
+            return Ok(());
+        }
+
+        println!("failures:");
+        for (name, value) in &failed {
+            match value {
+                RunResultValue::Success(_) => {
+                    println!("   {name} - expected panic but finished successfully.")
+                }
+                RunResultValue::Panic(data) => {
+                    let mut data = data.iter().copied();
+                    let mut items = vec![];
+                    while let Some(item) = format_next_item(&mut data) {
+                        items.push(item.quote_if_string());
+                    }
+                    println!("   {name} - Panicked with [{}].", items.join(", "));
+                }
+            }
+        }
+        println!();
+
+        bail!(
+            "test result: FAILED. {passed} passed; {} failed; {ignored} ignored; {filtered_out} \
+             filtered out;",
+            failed.len()
+        )
+    }
+}
+
 pub(crate) fn build_root_database(unit: &CairoCompilationUnit) -> Result<RootDatabase> {
     let mut b = RootDatabase::builder();
     b.with_project_config(build_project_config(unit)?);
@@ -245,6 +446,12 @@ mod tests {
             profiler_mode: ProfilerMode::None,
             gas_enabled: true,
             print_resource_usage: false,
+            gas_report: false,
+            gas_report_save: None,
+            gas_report_baseline: None,
+            gas_report_threshold: 0.0,
+            coverage: false,
+            coverage_output: PathBuf::from("lcov.info"),
             features: FeaturesSpec {
                 features: vec![],
                 all_features: true,
@@ -256,4 +463,89 @@ mod tests {
         let result = test_args.run(&config);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_spawn_and_move_gas_report() {
+        let setup = CompilerTestSetup::from_examples("../../crates/dojo-core", "../../examples/");
+
+        let config = setup.build_test_config("spawn-and-move", Profile::DEV);
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let report_path = temp_dir.path().join("gas_report.json");
+
+        let test_args = TestArgs {
+            filter: "test_move".to_string(),
+            include_ignored: false,
+            ignored: false,
+            profiler_mode: ProfilerMode::None,
+            gas_enabled: true,
+            print_resource_usage: false,
+            gas_report: true,
+            gas_report_save: Some(report_path.clone()),
+            gas_report_baseline: None,
+            gas_report_threshold: 0.0,
+            coverage: false,
+            coverage_output: PathBuf::from("lcov.info"),
+            features: FeaturesSpec {
+                features: vec![],
+                all_features: true,
+                no_default_features: false,
+            },
+            packages: None,
+        };
+
+        test_args.run(&config).unwrap();
+
+        let report = GasReport::from_file(&report_path).unwrap();
+        let spawn = report
+            .contracts
+            .values()
+            .find_map(|entrypoints| entrypoints.get("spawn"))
+            .expect("spawn should be reported");
+        assert!(spawn.calls > 0 && spawn.steps > 0);
+    }
+
+    #[test]
+    fn test_spawn_and_move_coverage() {
+        let setup = CompilerTestSetup::from_examples("../../crates/dojo-core", "../../examples/");
+
+        let config = setup.build_test_config("spawn-and-move", Profile::DEV);
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let lcov_path = temp_dir.path().join("lcov.info");
+        let report_path = temp_dir.path().join("gas_report.json");
+
+        let test_args = TestArgs {
+            filter: "test_move".to_string(),
+            include_ignored: false,
+            ignored: false,
+            profiler_mode: ProfilerMode::None,
+            gas_enabled: true,
+            print_resource_usage: false,
+            gas_report: true,
+            gas_report_save: Some(report_path.clone()),
+            gas_report_baseline: None,
+            gas_report_threshold: 0.0,
+            coverage: true,
+            coverage_output: lcov_path.clone(),
+            features: FeaturesSpec {
+                features: vec![],
+                all_features: true,
+                no_default_features: false,
+            },
+            packages: None,
+        };
+
+        test_args.run(&config).unwrap();
+
+        // The systems called by the test are covered, not only the test itself.
+        let lcov = std::fs::read_to_string(&lcov_path).unwrap();
+        let actions = lcov
+            .split("end_of_record")
+            .find(|record| record.contains("actions.cairo"))
+            .expect("actions should be covered");
+        assert!(actions.lines().any(|line| line.starts_with("DA:") && !line.ends_with(",0")));
+
+        // The gas report is recorded by the same run.
+        let report = GasReport::from_file(&report_path).unwrap();
+        assert!(!report.contracts.is_empty());
+    }
 }
@@ -11,8 +11,10 @@
 //!   for more info.
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use clap::Parser;
 use common::parse::{parse_socket_address, parse_url};
@@ -27,6 +29,7 @@ use tokio::sync::broadcast;
 use tokio::sync::broadcast::Sender;
 use tokio_stream::StreamExt;
 use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::processors::declarative::{DeclarativeProcessor, ProcessorsConfig};
 use torii_core::processors::event_message::EventMessageProcessor;
 use torii_core::processors::metadata_update::MetadataUpdateProcessor;
 use torii_core::processors::register_model::RegisterModelProcessor;
@@ -35,9 +38,15 @@ use torii_core::processors::store_set_record::StoreSetRecordProcessor;
 use torii_core::processors::store_transaction::StoreTransactionProcessor;
 use torii_core::processors::store_update_member::StoreUpdateMemberProcessor;
 use torii_core::processors::store_update_record::StoreUpdateRecordProcessor;
+use torii_core::search::SearchField;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::sql::Sql;
 use torii_core::types::Model;
+use torii_grpc::server::GrpcConfig;
+use torii_relay::server::rate_limit::{
+    RateLimitConfig, DEFAULT_BAN_DURATION_SECS, DEFAULT_BAN_THRESHOLD,
+    DEFAULT_IDENTITY_MESSAGES_LIMIT, DEFAULT_PEER_MESSAGES_LIMIT, DEFAULT_RATE_LIMIT_WINDOW_SECS,
+};
 use torii_server::proxy::Proxy;
 use tracing::{error, info};
 use tracing_subscriber::{fmt, EnvFilter};
@@ -87,6 +96,32 @@ struct Args {
     #[arg(long, value_name = "PATH")]
     relay_cert_path: Option<String>,
 
+    /// Multiaddrs of other relays of the world to federate with, including their peer id
+    /// (comma-separated list, ex: /ip4/1.2.3.4/tcp/9090/p2p/<PEER_ID>)
+    #[arg(long, value_name = "MULTIADDRS", value_delimiter = ',')]
+    relay_peers: Vec<String>,
+
+    /// Maximum number of relay messages of a peer in a rate limit window
+    #[arg(long, value_name = "COUNT", default_value_t = DEFAULT_PEER_MESSAGES_LIMIT)]
+    relay_peer_rate_limit: u32,
+
+    /// Maximum number of relay messages of a signer identity in a rate limit window
+    #[arg(long, value_name = "COUNT", default_value_t = DEFAULT_IDENTITY_MESSAGES_LIMIT)]
+    relay_identity_rate_limit: u32,
+
+    /// Duration of the relay rate limit windows, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_RATE_LIMIT_WINDOW_SECS)]
+    relay_rate_limit_window: u64,
+
+    /// Number of rejected relay messages of a peer in a rate limit window after which it is
+    /// banned
+    #[arg(long, value_name = "COUNT", default_value_t = DEFAULT_BAN_THRESHOLD)]
+    relay_ban_threshold: u32,
+
+    /// Duration of the relay peer bans, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_BAN_DURATION_SECS)]
+    relay_ban_duration: u64,
+
     /// Specify allowed origins for api endpoints (comma-separated list of allowed origins, or "*"
     /// for all)
     #[arg(long)]
@@ -115,6 +150,41 @@ struct Args {
     /// Enable indexing pending blocks
     #[arg(long)]
     index_pending: bool,
+
+    /// Maximum number of concurrent provider requests and event processing tasks
+    #[arg(long, default_value = "100")]
+    max_concurrent_tasks: usize,
+
+    /// Path to a TOML file declaring processors for the events of other contracts than the world
+    #[arg(long, value_name = "PATH")]
+    processors: Option<PathBuf>,
+
+    /// Model members indexed for full-text search, as `namespace-Model.member` (comma-separated
+    /// list). Members must be `ByteArray` or `felt252` short strings
+    #[arg(long, value_name = "MEMBERS", value_delimiter = ',')]
+    search: Vec<SearchField>,
+
+    /// Address to serve the gRPC and gRPC-web endpoints at directly, in addition to the api
+    /// endpoints address. Bound to a random local port if not specified
+    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address)]
+    grpc_addr: Option<SocketAddr>,
+
+    /// Address to serve the gRPC JSON over WebSocket endpoint at directly, in addition to the api
+    /// endpoints address. Bound to a random local port if not specified
+    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address)]
+    grpc_websocket_addr: Option<SocketAddr>,
+
+    /// Compress the gRPC responses with gzip for the clients accepting it
+    #[arg(long)]
+    grpc_compression: bool,
+
+    /// Maximum size of a gRPC request message, in bytes
+    #[arg(long, value_name = "BYTES", default_value = "4194304")]
+    grpc_max_request_size: usize,
+
+    /// Maximum size of a gRPC response message, in bytes. Unlimited if not specified
+    #[arg(long, value_name = "BYTES")]
+    grpc_max_response_size: Option<usize>,
 }
 
 #[tokio::main]
@@ -164,8 +234,8 @@ async fn main() -> anyhow::Result<()> {
 
     let class_hash =
         provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), args.world_address).await?;
-    let db = Sql::new(pool.clone(), args.world_address, class_hash).await?;
-    let processors = Processors {
+    let mut db = Sql::new(pool.clone(), args.world_address, class_hash).await?;
+    let mut processors = Processors {
         event: vec![
             Box::new(RegisterModelProcessor),
             Box::new(StoreSetRecordProcessor),
@@ -179,6 +249,20 @@ async fn main() -> anyhow::Result<()> {
         ..Processors::default()
     };
 
+    if let Some(path) = &args.processors {
+        for config in ProcessorsConfig::from_path(path)?.processors {
+            let processor = DeclarativeProcessor::new(config)?;
+            processor.create_table(&mut db);
+            processors.event.push(Box::new(processor));
+        }
+
+        db.execute().await?;
+    }
+
+    if !args.search.is_empty() {
+        db.enable_search(args.search.clone()).await?;
+    }
+
     let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
 
     let mut engine = Engine::new(
@@ -190,6 +274,7 @@ async fn main() -> anyhow::Result<()> {
             start_block: args.start_block,
             events_chunk_size: args.events_chunk_size,
             index_pending: args.index_pending,
+            max_concurrent_tasks: args.max_concurrent_tasks,
             ..Default::default()
         },
         shutdown_tx.clone(),
@@ -197,12 +282,29 @@ async fn main() -> anyhow::Result<()> {
     );
 
     let shutdown_rx = shutdown_tx.subscribe();
-    let (grpc_addr, grpc_server) = torii_grpc::server::new(
+    let mut grpc_config = GrpcConfig {
+        compression: args.grpc_compression,
+        max_decoding_message_size: args.grpc_max_request_size,
+        allowed_origins: args.allowed_origins.clone(),
+        ..Default::default()
+    };
+    if let Some(addr) = args.grpc_addr {
+        grpc_config.addr = addr;
+    }
+    if let Some(addr) = args.grpc_websocket_addr {
+        grpc_config.websocket_addr = addr;
+    }
+    if let Some(size) = args.grpc_max_response_size {
+        grpc_config.max_encoding_message_size = size;
+    }
+
+    let (grpc_addrs, grpc_server) = torii_grpc::server::new(
         shutdown_rx,
         &pool,
         block_rx,
         args.world_address,
         Arc::clone(&provider),
+        grpc_config,
     )
     .await?;
 
@@ -213,10 +315,24 @@ async fn main() -> anyhow::Result<()> {
         args.relay_webrtc_port,
         args.relay_local_key_path,
         args.relay_cert_path,
+        args.relay_peers,
+        RateLimitConfig {
+            peer_messages: args.relay_peer_rate_limit,
+            identity_messages: args.relay_identity_rate_limit,
+            window: Duration::from_secs(args.relay_rate_limit_window),
+            ban_threshold: args.relay_ban_threshold,
+            ban_duration: Duration::from_secs(args.relay_ban_duration),
+        },
     )
     .expect("Failed to start libp2p relay server");
 
-    let proxy_server = Arc::new(Proxy::new(args.addr, args.allowed_origins, Some(grpc_addr), None));
+    let proxy_server = Arc::new(Proxy::new(
+        args.addr,
+        args.allowed_origins,
+        Some(grpc_addrs.grpc),
+        Some(grpc_addrs.websocket),
+        None,
+    ));
 
     let graphql_server = spawn_rebuilding_graphql_server(
         shutdown_tx.clone(),
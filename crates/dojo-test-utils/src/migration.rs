@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use camino::Utf8PathBuf;
 use dojo_world::manifest::{BaseManifest, OverlayManifest, BASE_DIR, MANIFESTS_DIR, OVERLAYS_DIR};
@@ -49,7 +51,15 @@ pub fn prepare_migration(
 
     let world = WorldDiff::compute(manifest, None, default_namespace)?;
 
-    let strat = prepare_for_migration(None, felt!("0x12345"), &target_dir, world.clone()).unwrap();
+    let strat = prepare_for_migration(
+        None,
+        felt!("0x12345"),
+        &target_dir,
+        world.clone(),
+        &HashMap::new(),
+        false,
+    )
+    .unwrap();
 
     Ok((strat, world))
 }
@@ -78,6 +88,13 @@ pub fn prepare_migration_with_world_and_seed(
     let world = WorldDiff::compute(manifest.clone(), None, default_namespace)?;
 
     let seed = cairo_short_string_to_felt(seed).unwrap();
-    let strat = prepare_for_migration(world_address, seed, &target_dir, world.clone())?;
+    let strat = prepare_for_migration(
+        world_address,
+        seed,
+        &target_dir,
+        world.clone(),
+        &HashMap::new(),
+        false,
+    )?;
     Ok((strat, world))
 }
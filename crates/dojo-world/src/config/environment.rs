@@ -7,6 +7,7 @@ pub struct Environment {
     pub private_key: Option<String>,
     pub keystore_path: Option<String>,
     pub keystore_password: Option<String>,
+    pub signer_url: Option<String>,
     pub world_address: Option<String>,
 }
 
@@ -34,4 +35,8 @@ impl Environment {
     pub fn keystore_password(&self) -> Option<&str> {
         self.keystore_password.as_deref()
     }
+
+    pub fn signer_url(&self) -> Option<&str> {
+        self.signer_url.as_deref()
+    }
 }
@@ -197,7 +197,7 @@ where
     }
 }
 
-fn parse_schema(ty: &abigen::model::Ty) -> Result<Ty, ParseError> {
+pub(crate) fn parse_schema(ty: &abigen::model::Ty) -> Result<Ty, ParseError> {
     match ty {
         abigen::model::Ty::Primitive(primitive) => {
             let ty = parse_cairo_short_string(primitive)?;
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use async_trait::async_trait;
 use starknet::core::types::Felt;
 
+use super::compatibility::SchemaChange;
 use super::{Declarable, MigrationType, StateDiff};
 
 /// Represents differences between a local and remote class.
@@ -43,6 +44,8 @@ impl Display for ClassDiff {
 pub struct ClassMigration {
     pub diff: ClassDiff,
     pub artifact_path: PathBuf,
+    /// Changes of the schema of an upgraded model, compared to the one registered in the World.
+    pub schema_changes: Vec<SchemaChange>,
 }
 
 impl ClassMigration {
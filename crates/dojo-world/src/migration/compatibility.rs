@@ -0,0 +1,271 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use cainome::cairo_serde::CairoSerde;
+use cairo_lang_runner::{Arg, RunResultValue, SierraCasmRunner, StarknetState};
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_starknet_classes::contract_class::ContractClass;
+use dojo_types::schema::{EnumOption, Member, Ty};
+use num_traits::ToPrimitive;
+use starknet::core::types::Felt;
+use starknet::core::utils::get_selector_from_name;
+
+use crate::contracts::abi::model::{FieldLayout, Layout, Ty as AbiTy};
+use crate::contracts::model::parse_schema;
+
+#[cfg(test)]
+#[path = "compatibility_test.rs"]
+mod tests;
+
+/// A change of the schema of a model, classified by its effect on the storage of the entities
+/// already stored with the previous schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// The existing entities are still read correctly, e.g. appended members or enum variants.
+    Safe(String),
+    /// The existing entities are corrupted, e.g. removed, reordered or retyped members.
+    Breaking(String),
+}
+
+impl SchemaChange {
+    pub fn is_breaking(&self) -> bool {
+        matches!(self, SchemaChange::Breaking(_))
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaChange::Safe(change) => write!(f, "{change}"),
+            SchemaChange::Breaking(change) => write!(f, "{change} (breaking)"),
+        }
+    }
+}
+
+/// The schema and the storage layout of a model, as introspected by its class.
+#[derive(Debug, Clone)]
+pub struct ModelSchema {
+    pub ty: Ty,
+    pub layout: Layout,
+}
+
+/// Reads the schema and the layout of a model from its compiled class, by running its `schema`
+/// and `layout` entrypoints which return the introspection generated by the compiler.
+pub fn local_model_schema(artifact_path: &Path) -> Result<ModelSchema> {
+    let file = File::open(artifact_path)?;
+    let class: ContractClass = serde_json::from_reader(file)?;
+    let program = class.extract_sierra_program()?;
+    let externals = class.entry_points_by_type.external;
+
+    let runner = SierraCasmRunner::new(
+        program.clone(),
+        Some(MetadataComputationConfig::default()),
+        Default::default(),
+        None,
+    )?;
+
+    let call = |name: &str| -> Result<Vec<Felt>> {
+        let selector = get_selector_from_name(name)?.to_biguint();
+        let entry_point = externals
+            .iter()
+            .find(|e| e.selector == selector)
+            .with_context(|| format!("Entrypoint `{name}` not found."))?;
+
+        let res = runner.run_function_with_starknet_context(
+            &program.funcs[entry_point.function_idx],
+            &[Arg::Array(vec![])],
+            Some(usize::MAX),
+            StarknetState::default(),
+        )?;
+
+        let RunResultValue::Success(value) = res.value else {
+            bail!("Entrypoint `{name}` panicked.");
+        };
+        let [start, end] = value.as_slice() else {
+            bail!("Unexpected return value from entrypoint `{name}`.");
+        };
+        let start = start.to_usize().context("Invalid return data start.")?;
+        let end = end.to_usize().context("Invalid return data end.")?;
+
+        (start..end)
+            .map(|i| res.memory.get(i).copied().flatten().context("Uninitialized return data."))
+            .collect()
+    };
+
+    let ty = parse_schema(&AbiTy::cairo_deserialize(&call("schema")?, 0)?)?;
+    let layout = Layout::cairo_deserialize(&call("layout")?, 0)?;
+
+    Ok(ModelSchema { ty, layout })
+}
+
+/// Compares the schema and the layout of a model registered in the World with the ones of its
+/// new class, returning every change between them.
+pub fn compare_models(old: &ModelSchema, new: &ModelSchema) -> Vec<SchemaChange> {
+    let mut changes = compare_schemas(&old.ty, &new.ty);
+
+    // a retyped member changes its layout as well, which is already reported.
+    if !changes.iter().any(SchemaChange::is_breaking) {
+        compare_layouts(&old.layout, &new.layout, &old.ty.name(), &mut changes);
+    }
+
+    changes
+}
+
+/// Compares the schema of a model stored on-chain with its new schema, returning every change
+/// between them.
+pub fn compare_schemas(old: &Ty, new: &Ty) -> Vec<SchemaChange> {
+    let mut changes = vec![];
+    compare_ty(old, new, &old.name(), &mut changes);
+    changes
+}
+
+fn compare_ty(old: &Ty, new: &Ty, path: &str, changes: &mut Vec<SchemaChange>) {
+    match (old, new) {
+        (Ty::Primitive(o), Ty::Primitive(n)) => {
+            if std::mem::discriminant(o) != std::mem::discriminant(n) {
+                changes.push(retyped(path, old, new));
+            }
+        }
+        (Ty::Struct(o), Ty::Struct(n)) => compare_members(&o.children, &n.children, path, changes),
+        (Ty::Enum(o), Ty::Enum(n)) => compare_options(&o.options, &n.options, path, changes),
+        (Ty::Tuple(o), Ty::Tuple(n)) => {
+            if o.len() != n.len() {
+                changes.push(retyped(path, old, new));
+                return;
+            }
+
+            for (i, (o, n)) in o.iter().zip(n).enumerate() {
+                compare_ty(o, n, &format!("{path}.{i}"), changes);
+            }
+        }
+        (Ty::Array(o), Ty::Array(n)) => compare_ty(&o[0], &n[0], &format!("{path}[]"), changes),
+        (Ty::ByteArray(_), Ty::ByteArray(_)) => {}
+        _ => changes.push(retyped(path, old, new)),
+    }
+}
+
+fn compare_members(old: &[Member], new: &[Member], path: &str, changes: &mut Vec<SchemaChange>) {
+    for (i, o) in old.iter().enumerate() {
+        let member_path = format!("{path}.{}", o.name);
+
+        let Some(n) = new.get(i) else {
+            changes.push(SchemaChange::Breaking(format!("`{member_path}` removed")));
+            continue;
+        };
+
+        if o.name != n.name {
+            changes.push(SchemaChange::Breaking(format!(
+                "`{member_path}` removed or moved, `{path}.{}` found at its position",
+                n.name
+            )));
+            continue;
+        }
+
+        if o.key != n.key {
+            let change = if n.key { "became a key" } else { "is no longer a key" };
+            changes.push(SchemaChange::Breaking(format!("`{member_path}` {change}")));
+            continue;
+        }
+
+        compare_ty(&o.ty, &n.ty, &member_path, changes);
+    }
+
+    for n in new.iter().skip(old.len()) {
+        let member_path = format!("{path}.{}", n.name);
+
+        // keys are part of the entity id, adding one changes the id of every entity.
+        if n.key {
+            changes.push(SchemaChange::Breaking(format!("key `{member_path}` appended")));
+        } else {
+            changes.push(SchemaChange::Safe(format!("`{member_path}` appended")));
+        }
+    }
+}
+
+fn compare_options(
+    old: &[EnumOption],
+    new: &[EnumOption],
+    path: &str,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for (i, o) in old.iter().enumerate() {
+        let option_path = format!("{path}::{}", o.name);
+
+        let Some(n) = new.get(i) else {
+            changes.push(SchemaChange::Breaking(format!("variant `{option_path}` removed")));
+            continue;
+        };
+
+        if o.name != n.name {
+            changes.push(SchemaChange::Breaking(format!(
+                "variant `{option_path}` removed or moved, `{path}::{}` found at its position",
+                n.name
+            )));
+            continue;
+        }
+
+        compare_ty(&o.ty, &n.ty, &option_path, changes);
+    }
+
+    for n in new.iter().skip(old.len()) {
+        changes.push(SchemaChange::Safe(format!("variant `{path}::{}` added", n.name)));
+    }
+}
+
+fn compare_layouts(old: &Layout, new: &Layout, path: &str, changes: &mut Vec<SchemaChange>) {
+    match (old, new) {
+        (Layout::Fixed(o), Layout::Fixed(n)) => {
+            // the bits read past the end of an entity packed with the previous layout are zero.
+            if !n.starts_with(o) {
+                changes.push(relaid(path, old, new));
+            }
+        }
+        (Layout::Struct(o), Layout::Struct(n)) => compare_fields(o, n, path, changes),
+        (Layout::Enum(o), Layout::Enum(n)) => compare_fields(o, n, path, changes),
+        (Layout::Tuple(o), Layout::Tuple(n)) => {
+            if o.len() != n.len() {
+                changes.push(relaid(path, old, new));
+                return;
+            }
+
+            for (i, (o, n)) in o.iter().zip(n).enumerate() {
+                compare_layouts(o, n, &format!("{path}.{i}"), changes);
+            }
+        }
+        (Layout::Array(o), Layout::Array(n)) => {
+            compare_layouts(&o[0], &n[0], &format!("{path}[]"), changes)
+        }
+        (Layout::ByteArray, Layout::ByteArray) => {}
+        _ => changes.push(relaid(path, old, new)),
+    }
+}
+
+/// Compares the layouts of the fields of a struct or of the variants of an enum, which are
+/// stored under their selector.
+fn compare_fields(
+    old: &[FieldLayout],
+    new: &[FieldLayout],
+    path: &str,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for o in old {
+        let field_path = format!("{path}.{:#x}", o.selector);
+
+        match new.iter().find(|n| n.selector == o.selector) {
+            Some(n) => compare_layouts(&o.layout, &n.layout, &field_path, changes),
+            None => {
+                changes.push(SchemaChange::Breaking(format!("layout of `{field_path}` removed")))
+            }
+        }
+    }
+}
+
+fn relaid(path: &str, old: &Layout, new: &Layout) -> SchemaChange {
+    SchemaChange::Breaking(format!("layout of `{path}` changed from `{old:?}` to `{new:?}`"))
+}
+
+fn retyped(path: &str, old: &Ty, new: &Ty) -> SchemaChange {
+    SchemaChange::Breaking(format!("`{path}` retyped from `{}` to `{}`", old.name(), new.name()))
+}
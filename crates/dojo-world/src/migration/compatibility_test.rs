@@ -0,0 +1,166 @@
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use starknet::core::types::Felt;
+
+use super::{compare_models, compare_schemas, ModelSchema, SchemaChange};
+use crate::contracts::abi::model::{FieldLayout, Layout};
+
+fn member(name: &str, ty: Ty, key: bool) -> Member {
+    Member { name: name.to_string(), ty, key }
+}
+
+fn model(children: Vec<Member>) -> Ty {
+    Ty::Struct(Struct { name: "Position".to_string(), children })
+}
+
+fn direction(options: &[&str]) -> Ty {
+    Ty::Enum(Enum {
+        name: "Direction".to_string(),
+        option: None,
+        options: options
+            .iter()
+            .map(|o| EnumOption { name: o.to_string(), ty: Ty::Tuple(vec![]) })
+            .collect(),
+    })
+}
+
+fn u32_ty() -> Ty {
+    Ty::Primitive(Primitive::U32(None))
+}
+
+fn player() -> Member {
+    member("player", Ty::Primitive(Primitive::ContractAddress(None)), true)
+}
+
+#[test]
+fn identical_schemas_have_no_change() {
+    let schema = model(vec![player(), member("x", u32_ty(), false), member("y", u32_ty(), false)]);
+    assert!(compare_schemas(&schema, &schema).is_empty());
+}
+
+#[test]
+fn appended_members_and_variants_are_safe() {
+    let old = model(vec![player(), member("dir", direction(&["Left", "Right"]), false)]);
+    let new = model(vec![
+        player(),
+        member("dir", direction(&["Left", "Right", "Up"]), false),
+        member("z", u32_ty(), false),
+    ]);
+
+    let changes = compare_schemas(&old, &new);
+
+    assert_eq!(
+        changes,
+        vec![
+            SchemaChange::Safe("variant `Position.dir::Up` added".to_string()),
+            SchemaChange::Safe("`Position.z` appended".to_string()),
+        ]
+    );
+    assert!(!changes.iter().any(SchemaChange::is_breaking));
+}
+
+#[test]
+fn removed_reordered_and_retyped_members_are_breaking() {
+    let old = model(vec![player(), member("x", u32_ty(), false), member("y", u32_ty(), false)]);
+
+    let removed = model(vec![player(), member("x", u32_ty(), false)]);
+    assert_eq!(
+        compare_schemas(&old, &removed),
+        vec![SchemaChange::Breaking("`Position.y` removed".to_string())]
+    );
+
+    let reordered =
+        model(vec![player(), member("y", u32_ty(), false), member("x", u32_ty(), false)]);
+    assert_eq!(compare_schemas(&old, &reordered).len(), 2);
+    assert!(compare_schemas(&old, &reordered).iter().all(SchemaChange::is_breaking));
+
+    let retyped = model(vec![
+        player(),
+        member("x", Ty::Primitive(Primitive::U64(None)), false),
+        member("y", u32_ty(), false),
+    ]);
+    assert_eq!(
+        compare_schemas(&old, &retyped),
+        vec![SchemaChange::Breaking("`Position.x` retyped from `u32` to `u64`".to_string())]
+    );
+}
+
+#[test]
+fn key_changes_are_breaking() {
+    let old = model(vec![player(), member("x", u32_ty(), false)]);
+
+    let new_key = model(vec![player(), member("x", u32_ty(), true)]);
+    assert!(compare_schemas(&old, &new_key).iter().all(SchemaChange::is_breaking));
+
+    let appended_key =
+        model(vec![player(), member("x", u32_ty(), false), member("id", u32_ty(), true)]);
+    assert_eq!(
+        compare_schemas(&old, &appended_key),
+        vec![SchemaChange::Breaking("key `Position.id` appended".to_string())]
+    );
+}
+
+#[test]
+fn removed_variants_are_breaking() {
+    let old = model(vec![player(), member("dir", direction(&["Left", "Right"]), false)]);
+    let new = model(vec![player(), member("dir", direction(&["Left"]), false)]);
+
+    assert_eq!(
+        compare_schemas(&old, &new),
+        vec![SchemaChange::Breaking("variant `Position.dir::Right` removed".to_string())]
+    );
+}
+
+fn field(selector: u64, layout: Layout) -> FieldLayout {
+    FieldLayout { selector: Felt::from(selector), layout }
+}
+
+#[test]
+fn layout_changes_are_breaking() {
+    let schema = model(vec![player(), member("x", u32_ty(), false)]);
+    let old = ModelSchema {
+        ty: schema.clone(),
+        layout: Layout::Struct(vec![field(1, Layout::Fixed(vec![32]))]),
+    };
+
+    assert!(compare_models(&old, &old).is_empty());
+
+    // the schema is unchanged, but the member is stored differently.
+    let relaid = ModelSchema {
+        ty: schema.clone(),
+        layout: Layout::Struct(vec![field(1, Layout::Fixed(vec![64]))]),
+    };
+    assert_eq!(
+        compare_models(&old, &relaid),
+        vec![SchemaChange::Breaking(
+            "layout of `Position.0x1` changed from `Fixed([32])` to `Fixed([64])`".to_string()
+        )]
+    );
+
+    let removed = ModelSchema { ty: schema.clone(), layout: Layout::Struct(vec![]) };
+    assert_eq!(
+        compare_models(&old, &removed),
+        vec![SchemaChange::Breaking("layout of `Position.0x1` removed".to_string())]
+    );
+
+    let appended = ModelSchema {
+        ty: schema,
+        layout: Layout::Struct(vec![
+            field(1, Layout::Fixed(vec![32])),
+            field(2, Layout::Fixed(vec![8])),
+        ]),
+    };
+    assert!(compare_models(&old, &appended).is_empty());
+}
+
+#[test]
+fn packed_layouts_can_be_extended() {
+    let schema = model(vec![player(), member("x", u32_ty(), false)]);
+    let old = ModelSchema { ty: schema.clone(), layout: Layout::Fixed(vec![32]) };
+
+    let extended = ModelSchema { ty: schema.clone(), layout: Layout::Fixed(vec![32, 8]) };
+    assert!(compare_models(&old, &extended).is_empty());
+
+    let shrunk = ModelSchema { ty: schema, layout: Layout::Fixed(vec![8]) };
+    assert!(compare_models(&old, &shrunk).iter().all(SchemaChange::is_breaking));
+}
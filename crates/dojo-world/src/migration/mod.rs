@@ -6,10 +6,10 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use cairo_lang_starknet_classes::contract_class::ContractClass;
-use starknet::accounts::{Account, AccountError, Call, ConnectedAccount};
+use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::contract::{CompiledClass, SierraClass};
 use starknet::core::types::{
-    BlockId, BlockTag, DeclareTransactionResult, Felt, FlattenedSierraClass,
+    BlockId, BlockTag, DataAvailabilityMode, DeclareTransactionResult, Felt, FlattenedSierraClass,
     InvokeTransactionResult, ReceiptBlock, StarknetError, TransactionReceiptWithBlockInfo,
 };
 use starknet::core::utils::{get_contract_address, CairoShortStringToFeltError};
@@ -18,9 +18,13 @@ use starknet::providers::{Provider, ProviderError};
 use thiserror::Error;
 
 use crate::contracts::naming::compute_selector_from_tag;
-use crate::utils::{TransactionExt, TransactionWaiter, TransactionWaitingError};
+use crate::utils::{
+    declare_with_cfg, execute_with_cfg, TransactionError, TransactionV3Signer, TransactionWaiter,
+    TransactionWaitingError,
+};
 
 pub mod class;
+pub mod compatibility;
 pub mod contract;
 pub mod strategy;
 pub mod world;
@@ -63,7 +67,7 @@ pub enum MigrationError<S> {
     #[error("Contract already deployed.")]
     ContractAlreadyDeployed(Felt),
     #[error(transparent)]
-    Migrator(#[from] AccountError<S>),
+    Migrator(#[from] TransactionError<S>),
     #[error(transparent)]
     CairoShortStringToFelt(#[from] CairoShortStringToFeltError),
     #[error(transparent)]
@@ -101,6 +105,48 @@ pub struct TxnConfig {
     pub wait: bool,
     pub receipt: bool,
     pub max_fee_raw: Option<Felt>,
+    /// Sends V3 transactions, paying the fees in STRK, if set. Otherwise V1 transactions paying
+    /// the fees in ETH are sent.
+    pub v3: Option<TxnV3Config>,
+}
+
+/// The fee settings of V3 transactions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TxnV3Config {
+    /// The maximum amount of L1 gas of the transaction. Estimated if `None`.
+    pub l1_gas: Option<u64>,
+    /// The maximum price of a unit of L1 gas, in fri. Estimated if `None`.
+    pub l1_gas_price: Option<u128>,
+    /// The multiplier for how much the maximum amount of L1 gas should be relative to the
+    /// estimated amount. If `None` is provided, the multiplier is set to `1.5`.
+    pub gas_estimate_multiplier: Option<f64>,
+    /// The tip paid to the sequencer.
+    pub tip: u64,
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    pub fee_data_availability_mode: DataAvailabilityMode,
+}
+
+impl Default for TxnV3Config {
+    fn default() -> Self {
+        Self {
+            l1_gas: None,
+            l1_gas_price: None,
+            gas_estimate_multiplier: None,
+            tip: 0,
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+        }
+    }
+}
+
+impl TxnV3Config {
+    /// Whether the transactions are sent without tip and with the L1 data availability modes,
+    /// the only settings the accounts of `starknet-rs` sign V3 transactions with.
+    pub fn has_default_tip_and_da_modes(&self) -> bool {
+        self.tip == 0
+            && self.nonce_data_availability_mode == DataAvailabilityMode::L1
+            && self.fee_data_availability_mode == DataAvailabilityMode::L1
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -112,9 +158,23 @@ pub enum TxnAction {
         /// The multiplier for how much the actual transaction max fee should be relative to the
         /// estimated fee. If `None` is provided, the multiplier is set to `1.1`.
         fee_estimate_multiplier: Option<f64>,
+        v3: Option<TxnV3Config>,
+    },
+    Estimate {
+        v3: Option<TxnV3Config>,
+    },
+    Simulate {
+        v3: Option<TxnV3Config>,
     },
-    Estimate,
-    Simulate,
+}
+
+impl TxnAction {
+    /// The fee settings of V3 transactions, if the action is done with a V3 transaction.
+    pub fn v3(&self) -> Option<TxnV3Config> {
+        match self {
+            Self::Send { v3, .. } | Self::Estimate { v3 } | Self::Simulate { v3 } => *v3,
+        }
+    }
 }
 
 impl TxnConfig {
@@ -132,7 +192,7 @@ pub trait Declarable {
         txn_config: &TxnConfig,
     ) -> Result<DeclareOutput, MigrationError<<A as Account>::SignError>>
     where
-        A: ConnectedAccount + Send + Sync,
+        A: TransactionV3Signer + Send + Sync,
         <A as ConnectedAccount>::Provider: Send,
     {
         let (flattened_class, casm_class_hash) =
@@ -148,11 +208,10 @@ pub trait Declarable {
             Err(e) => return Err(MigrationError::Provider(e)),
         }
 
-        let DeclareTransactionResult { transaction_hash, class_hash } = account
-            .declare_v2(Arc::new(flattened_class), casm_class_hash)
-            .send_with_cfg(txn_config)
-            .await
-            .map_err(MigrationError::Migrator)?;
+        let DeclareTransactionResult { transaction_hash, class_hash } =
+            declare_with_cfg(&account, Arc::new(flattened_class), casm_class_hash, txn_config)
+                .await
+                .map_err(MigrationError::Migrator)?;
 
         TransactionWaiter::new(transaction_hash, account.provider())
             .await
@@ -228,7 +287,7 @@ pub trait Deployable: Declarable + Sync {
         tag: &str,
     ) -> Result<DeployOutput, MigrationError<<A as Account>::SignError>>
     where
-        A: ConnectedAccount + Send + Sync,
+        A: TransactionV3Signer + Send + Sync,
         <A as ConnectedAccount>::Provider: Send,
     {
         let contract_address =
@@ -265,11 +324,10 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(MigrationError::Provider(e)),
         };
 
-        let InvokeTransactionResult { transaction_hash } = account
-            .execute_v1(vec![call])
-            .send_with_cfg(txn_config)
-            .await
-            .map_err(MigrationError::Migrator)?;
+        let InvokeTransactionResult { transaction_hash } =
+            execute_with_cfg(&account, vec![call], txn_config)
+                .await
+                .map_err(MigrationError::Migrator)?;
 
         let receipt = TransactionWaiter::new(transaction_hash, account.provider()).await?;
         let block_number = get_block_number_from_receipt(receipt);
@@ -293,7 +351,7 @@ pub trait Deployable: Declarable + Sync {
         txn_config: &TxnConfig,
     ) -> Result<DeployOutput, MigrationError<<A as Account>::SignError>>
     where
-        A: ConnectedAccount + Send + Sync,
+        A: TransactionV3Signer + Send + Sync,
         <A as ConnectedAccount>::Provider: Send,
     {
         let declare = match self.declare(&account, txn_config).await {
@@ -326,15 +384,17 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(MigrationError::Provider(e)),
         }
 
-        let txn = account.execute_v1(vec![Call {
+        let call = Call {
             calldata,
             // devnet UDC address
             selector: selector!("deployContract"),
             to: felt!("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf"),
-        }]);
+        };
 
         let InvokeTransactionResult { transaction_hash } =
-            txn.send_with_cfg(txn_config).await.map_err(MigrationError::Migrator)?;
+            execute_with_cfg(&account, vec![call], txn_config)
+                .await
+                .map_err(MigrationError::Migrator)?;
 
         let receipt = TransactionWaiter::new(transaction_hash, account.provider()).await?;
         let block_number = get_block_number_from_receipt(receipt);
@@ -365,7 +425,7 @@ pub trait Upgradable: Deployable + Declarable + Sync {
         txn_config: &TxnConfig,
     ) -> Result<UpgradeOutput, MigrationError<<A as Account>::SignError>>
     where
-        A: ConnectedAccount + Send + Sync,
+        A: TransactionV3Signer + Send + Sync,
         <A as ConnectedAccount>::Provider: Send,
     {
         let declare = match self.declare(&account, txn_config).await {
@@ -393,15 +453,12 @@ pub trait Upgradable: Deployable + Declarable + Sync {
 
         let calldata = vec![class_hash];
 
-        let InvokeTransactionResult { transaction_hash } = account
-            .execute_v1(vec![Call {
-                calldata,
-                selector: selector!("upgrade"),
-                to: contract_address,
-            }])
-            .send_with_cfg(txn_config)
-            .await
-            .map_err(MigrationError::Migrator)?;
+        let call = Call { calldata, selector: selector!("upgrade"), to: contract_address };
+
+        let InvokeTransactionResult { transaction_hash } =
+            execute_with_cfg(&account, vec![call], txn_config)
+                .await
+                .map_err(MigrationError::Migrator)?;
 
         let receipt = TransactionWaiter::new(transaction_hash, account.provider()).await?;
         let block_number = get_block_number_from_receipt(receipt);
@@ -410,7 +467,9 @@ pub trait Upgradable: Deployable + Declarable + Sync {
     }
 }
 
-fn prepare_contract_declaration_params(
+/// Reads the artifact of a contract and returns its flattened class with its compiled class hash,
+/// the parameters of its declaration.
+pub fn prepare_contract_declaration_params(
     artifact_path: &PathBuf,
 ) -> Result<(FlattenedSierraClass, Felt)> {
     let flattened_class = read_class(artifact_path)?
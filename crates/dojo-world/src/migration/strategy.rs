@@ -2,13 +2,14 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use camino::Utf8PathBuf;
 use starknet::core::types::Felt;
 use starknet::core::utils::{cairo_short_string_to_felt, get_contract_address};
 use starknet_crypto::{poseidon_hash_many, poseidon_hash_single};
 
 use super::class::{ClassDiff, ClassMigration};
+use super::compatibility::{compare_models, local_model_schema, ModelSchema, SchemaChange};
 use super::contract::{ContractDiff, ContractMigration};
 use super::world::WorldDiff;
 use super::MigrationType;
@@ -64,42 +65,64 @@ impl MigrationStrategy {
     pub fn resolve_variable(&mut self, world_address: Felt) -> Result<()> {
         for contract in self.contracts.iter_mut() {
             for field in contract.diff.init_calldata.iter_mut() {
-                if let Some(dependency) = field.strip_prefix("$contract_address:") {
-                    let dependency_contract = self.metadata.get(dependency).unwrap();
-
-                    match dependency_contract {
-                        MigrationMetadata::Contract(c) => {
-                            let contract_address = get_contract_address(
-                                generate_salt(&naming::get_name_from_tag(&c.tag)),
-                                c.base_class_hash,
-                                &[],
-                                world_address,
-                            );
-                            *field = contract_address.to_string();
-                        }
-                    }
-                } else if let Some(dependency) = field.strip_prefix("$class_hash:") {
-                    let dependency_contract = self.metadata.get(dependency).unwrap();
-                    match dependency_contract {
-                        MigrationMetadata::Contract(c) => {
-                            *field = c.local_class_hash.to_string();
-                        }
-                    }
-                }
+                *field = resolve_field(&self.metadata, field, world_address);
             }
         }
 
         Ok(())
     }
+
+    /// Resolves the variables of the init calldata of any contract of the world, including the
+    /// ones which don't need to be migrated.
+    pub fn resolve_init_calldata(&self, init_calldata: &[String]) -> Vec<String> {
+        init_calldata
+            .iter()
+            .map(|field| resolve_field(&self.metadata, field, self.world_address))
+            .collect()
+    }
+}
+
+fn resolve_field(
+    metadata: &HashMap<String, MigrationMetadata>,
+    field: &str,
+    world_address: Felt,
+) -> String {
+    if let Some(dependency) = field.strip_prefix("$contract_address:") {
+        let dependency_contract = metadata.get(dependency).unwrap();
+
+        match dependency_contract {
+            MigrationMetadata::Contract(c) => {
+                let contract_address = get_contract_address(
+                    generate_salt(&naming::get_name_from_tag(&c.tag)),
+                    c.base_class_hash,
+                    &[],
+                    world_address,
+                );
+                contract_address.to_string()
+            }
+        }
+    } else if let Some(dependency) = field.strip_prefix("$class_hash:") {
+        let dependency_contract = metadata.get(dependency).unwrap();
+        match dependency_contract {
+            MigrationMetadata::Contract(c) => c.local_class_hash.to_string(),
+        }
+    } else {
+        field.to_string()
+    }
 }
 
 /// construct migration strategy
 /// evaluate which contracts/classes need to be declared/deployed
+///
+/// The upgraded models are compared with their schema in `remote_models`, keyed by tag, and
+/// breaking changes abort the migration unless `force` is set.
 pub fn prepare_for_migration(
     world_address: Option<Felt>,
     seed: Felt,
     target_dir: &Utf8PathBuf,
     diff: WorldDiff,
+    remote_models: &HashMap<String, ModelSchema>,
+    force: bool,
 ) -> Result<MigrationStrategy> {
     let mut metadata = HashMap::new();
     let mut artifact_paths = HashMap::new();
@@ -121,7 +144,12 @@ pub fn prepare_for_migration(
         &mut metadata,
         world.is_some(),
     )?;
-    let models = evaluate_models_to_migrate(&diff.models, &artifact_paths, world.is_some())?;
+    let mut models = evaluate_models_to_migrate(&diff.models, &artifact_paths, world.is_some())?;
+
+    // A new World doesn't hold any entity.
+    if world.is_none() {
+        check_models_compatibility(&mut models, remote_models, force)?;
+    }
 
     // If world needs to be migrated, then we expect the `seed` to be provided.
     if let Some(world) = &mut world {
@@ -149,6 +177,38 @@ pub fn prepare_for_migration(
     Ok(migration)
 }
 
+fn check_models_compatibility(
+    models: &mut [ClassMigration],
+    remote_models: &HashMap<String, ModelSchema>,
+    force: bool,
+) -> Result<()> {
+    let mut breaking_models = vec![];
+
+    for model in models.iter_mut() {
+        let Some(remote) = remote_models.get(&model.diff.tag) else {
+            continue;
+        };
+
+        let local = local_model_schema(&model.artifact_path)
+            .with_context(|| format!("Failed to read schema of model {}.", model.diff.tag))?;
+
+        model.schema_changes = compare_models(remote, &local);
+        if model.schema_changes.iter().any(SchemaChange::is_breaking) {
+            breaking_models.push(model.diff.tag.clone());
+        }
+    }
+
+    if !breaking_models.is_empty() && !force {
+        bail!(
+            "Upgrading models {} would corrupt the storage of their existing entities. Use \
+             `--force` to upgrade them anyway.",
+            breaking_models.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
 fn evaluate_models_to_migrate(
     models: &[ClassDiff],
     artifact_paths: &HashMap<String, PathBuf>,
@@ -179,7 +239,11 @@ fn evaluate_class_to_migrate(
         _ => {
             let path =
                 find_artifact_path(&naming::get_filename_from_tag(&class.tag), artifact_paths)?;
-            Ok(Some(ClassMigration { diff: class.clone(), artifact_path: path.clone() }))
+            Ok(Some(ClassMigration {
+                diff: class.clone(),
+                artifact_path: path.clone(),
+                ..Default::default()
+            }))
         }
     }
 }
@@ -1,23 +1,33 @@
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
 use anyhow::Result;
+use async_trait::async_trait;
 use futures::FutureExt;
+use num_traits::ToPrimitive;
 use starknet::accounts::{
-    AccountDeploymentV1, AccountError, AccountFactory, AccountFactoryError, ConnectedAccount,
-    DeclarationV2, ExecutionV1,
+    Account, AccountDeploymentV1, AccountDeploymentV3, AccountError, AccountFactory,
+    AccountFactoryError, Call, ConnectedAccount, DeclarationV2, DeclarationV3, ExecutionEncoder,
+    ExecutionV1, ExecutionV3, SingleOwnerAccount,
 };
 use starknet::core::types::{
-    DeclareTransactionResult, DeployAccountTransactionResult, ExecutionResult, Felt,
-    InvokeTransactionResult, ReceiptBlock, StarknetError, TransactionFinalityStatus,
+    BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV3, BroadcastedInvokeTransaction,
+    BroadcastedInvokeTransactionV3, BroadcastedTransaction, DataAvailabilityMode,
+    DeclareTransactionResult, DeployAccountTransactionResult, ExecutionResult, FeeEstimate, Felt,
+    FlattenedSierraClass, InvokeTransactionResult, ReceiptBlock, ResourceBounds,
+    ResourceBoundsMapping, SimulationFlagForEstimateFee, StarknetError, TransactionFinalityStatus,
     TransactionReceipt, TransactionReceiptWithBlockInfo, TransactionStatus,
 };
+use starknet::macros::{felt, short_string};
 use starknet::providers::{Provider, ProviderError};
+use starknet::signers::Signer;
+use starknet_crypto::poseidon_hash_many;
 use tokio::time::{Instant, Interval};
 
-use crate::migration::TxnConfig;
+use crate::migration::{TxnConfig, TxnV3Config};
 
 type GetTxStatusResult = Result<TransactionStatus, ProviderError>;
 type GetTxReceiptResult = Result<TransactionReceiptWithBlockInfo, ProviderError>;
@@ -387,6 +397,501 @@ where
     }
 }
 
+impl<T> TransactionExt<T> for ExecutionV3<'_, T>
+where
+    T: ConnectedAccount + Sync,
+{
+    type R = InvokeTransactionResult;
+    type U = AccountError<T::SignError>;
+
+    /// Sets the L1 gas bounds and the gas estimate multiplier from the V3 settings of the
+    /// `TxnConfig`.
+    async fn send_with_cfg(
+        mut self,
+        txn_config: &TxnConfig,
+    ) -> Result<Self::R, AccountError<T::SignError>> {
+        if let Some(TxnV3Config { gas_estimate_multiplier: Some(gas_est_mul), .. }) = txn_config.v3
+        {
+            self = self.gas_estimate_multiplier(gas_est_mul);
+        }
+
+        if let Some(TxnV3Config { l1_gas: Some(l1_gas), .. }) = txn_config.v3 {
+            self = self.gas(l1_gas);
+        }
+
+        if let Some(TxnV3Config { l1_gas_price: Some(l1_gas_price), .. }) = txn_config.v3 {
+            self = self.gas_price(l1_gas_price);
+        }
+
+        self.send().await
+    }
+}
+
+impl<T> TransactionExt<T> for DeclarationV3<'_, T>
+where
+    T: ConnectedAccount + Sync,
+{
+    type R = DeclareTransactionResult;
+    type U = AccountError<T::SignError>;
+
+    async fn send_with_cfg(
+        mut self,
+        txn_config: &TxnConfig,
+    ) -> Result<Self::R, AccountError<T::SignError>> {
+        if let Some(TxnV3Config { gas_estimate_multiplier: Some(gas_est_mul), .. }) = txn_config.v3
+        {
+            self = self.gas_estimate_multiplier(gas_est_mul);
+        }
+
+        if let Some(TxnV3Config { l1_gas: Some(l1_gas), .. }) = txn_config.v3 {
+            self = self.gas(l1_gas);
+        }
+
+        if let Some(TxnV3Config { l1_gas_price: Some(l1_gas_price), .. }) = txn_config.v3 {
+            self = self.gas_price(l1_gas_price);
+        }
+
+        self.send().await
+    }
+}
+
+impl<T> TransactionExt<T> for AccountDeploymentV3<'_, T>
+where
+    T: AccountFactory + Sync,
+{
+    type R = DeployAccountTransactionResult;
+    type U = AccountFactoryError<T::SignError>;
+
+    async fn send_with_cfg(
+        mut self,
+        txn_config: &TxnConfig,
+    ) -> Result<Self::R, AccountFactoryError<<T>::SignError>> {
+        if let Some(TxnV3Config { gas_estimate_multiplier: Some(gas_est_mul), .. }) = txn_config.v3
+        {
+            self = self.gas_estimate_multiplier(gas_est_mul);
+        }
+
+        if let Some(TxnV3Config { l1_gas: Some(l1_gas), .. }) = txn_config.v3 {
+            self = self.gas(l1_gas);
+        }
+
+        if let Some(TxnV3Config { l1_gas_price: Some(l1_gas_price), .. }) = txn_config.v3 {
+            self = self.gas_price(l1_gas_price);
+        }
+
+        self.send().await
+    }
+}
+
+/// Cairo string for "invoke".
+const PREFIX_INVOKE: Felt = short_string!("invoke");
+
+/// Cairo string for "declare".
+const PREFIX_DECLARE: Felt = short_string!("declare");
+
+/// 2^128, added to the version of transactions only meant for fee estimation or simulation.
+const QUERY_VERSION_OFFSET: Felt = felt!("0x100000000000000000000000000000000");
+
+/// The multiplier of the estimated L1 gas amount if none is set, as in `starknet-rs`.
+const DEFAULT_GAS_ESTIMATE_MULTIPLIER: f64 = 1.5;
+
+/// The multiplier of the estimated L1 gas price, as in `starknet-rs`.
+const GAS_PRICE_ESTIMATE_MULTIPLIER: f64 = 1.5;
+
+/// The errors of the transactions sent with a `TxnConfig`.
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError<S> {
+    #[error(transparent)]
+    Account(#[from] AccountError<S>),
+    #[error("The account can't sign V3 transactions with a tip or the L2 data availability mode.")]
+    UnsupportedV3Settings,
+}
+
+impl<S> From<ProviderError> for TransactionError<S> {
+    fn from(error: ProviderError) -> Self {
+        Self::Account(AccountError::Provider(error))
+    }
+}
+
+/// The fee fields of a V3 transaction, including the tip and the data availability modes the V3
+/// transactions of `starknet-rs` are always signed without.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FeeFieldsV3 {
+    pub l1_gas: u64,
+    pub l1_gas_price: u128,
+    pub tip: u64,
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    pub fee_data_availability_mode: DataAvailabilityMode,
+}
+
+impl FeeFieldsV3 {
+    /// The fee fields with the given L1 gas bounds, and the tip and data availability modes of
+    /// `v3`.
+    pub fn new(v3: &TxnV3Config, l1_gas: u64, l1_gas_price: u128) -> Self {
+        Self {
+            l1_gas,
+            l1_gas_price,
+            tip: v3.tip,
+            nonce_data_availability_mode: v3.nonce_data_availability_mode,
+            fee_data_availability_mode: v3.fee_data_availability_mode,
+        }
+    }
+
+    /// The resource bounds of the transaction, where only L1 gas is bounded.
+    pub fn resource_bounds(&self) -> ResourceBoundsMapping {
+        ResourceBoundsMapping {
+            l1_gas: ResourceBounds {
+                max_amount: self.l1_gas,
+                max_price_per_unit: self.l1_gas_price,
+            },
+            l2_gas: ResourceBounds { max_amount: 0, max_price_per_unit: 0 },
+        }
+    }
+
+    /// Hashes a V3 transaction of `sender_address` with these fee fields. `prefix` is the type of
+    /// the transaction, and `fields` the fields hashed after the data availability modes, specific
+    /// to this type.
+    pub fn transaction_hash(
+        &self,
+        prefix: Felt,
+        sender_address: Felt,
+        chain_id: Felt,
+        nonce: Felt,
+        query_only: bool,
+        fields: &[Felt],
+    ) -> Felt {
+        let version = if query_only { QUERY_VERSION_OFFSET + Felt::THREE } else { Felt::THREE };
+
+        let fee_fields_hash = poseidon_hash_many(&[
+            self.tip.into(),
+            encode_gas_bound(b"L1_GAS", self.l1_gas, self.l1_gas_price),
+            encode_gas_bound(b"L2_GAS", 0, 0),
+        ]);
+
+        let data_availability_modes =
+            (data_availability_mode_value(self.nonce_data_availability_mode) << 32)
+                + data_availability_mode_value(self.fee_data_availability_mode);
+
+        let mut elements = vec![
+            prefix,
+            version,
+            sender_address,
+            fee_fields_hash,
+            poseidon_hash_many(&[]), // paymaster_data
+            chain_id,
+            nonce,
+            data_availability_modes.into(),
+        ];
+        elements.extend_from_slice(fields);
+
+        poseidon_hash_many(&elements)
+    }
+}
+
+fn encode_gas_bound(name: &[u8], max_amount: u64, max_price_per_unit: u128) -> Felt {
+    let mut buffer = [0u8; 32];
+    let (remainder, max_price) = buffer.split_at_mut(128 / 8);
+    let (gas_kind, amount) = remainder.split_at_mut(64 / 8);
+
+    let padding = gas_kind.len() - name.len();
+    gas_kind[padding..].copy_from_slice(name);
+    amount.copy_from_slice(&max_amount.to_be_bytes());
+    max_price.copy_from_slice(&max_price_per_unit.to_be_bytes());
+
+    Felt::from_bytes_be(&buffer)
+}
+
+fn data_availability_mode_value(mode: DataAvailabilityMode) -> u64 {
+    match mode {
+        DataAvailabilityMode::L1 => 0,
+        DataAvailabilityMode::L2 => 1,
+    }
+}
+
+/// The L1 gas bounds of a V3 transaction, those set in `v3` or derived from the fee estimate of
+/// the transaction as in `starknet-rs`. `None` if the estimate is out of range.
+pub fn l1_gas_bounds(v3: &TxnV3Config, estimate: &FeeEstimate) -> Option<(u64, u128)> {
+    let gas_price = estimate.gas_price.to_u128()?;
+
+    let l1_gas = match v3.l1_gas {
+        Some(l1_gas) => l1_gas,
+        None => {
+            let gas = estimate.overall_fee.to_u128()?.div_ceil(gas_price.max(1));
+            let multiplier = v3.gas_estimate_multiplier.unwrap_or(DEFAULT_GAS_ESTIMATE_MULTIPLIER);
+            (gas as f64 * multiplier) as u64
+        }
+    };
+
+    let l1_gas_price =
+        v3.l1_gas_price.unwrap_or((gas_price as f64 * GAS_PRICE_ESTIMATE_MULTIPLIER) as u128);
+
+    Some((l1_gas, l1_gas_price))
+}
+
+/// A V3 transaction to be signed, along with its tip and data availability modes.
+#[derive(Debug, Clone)]
+pub struct RawTransactionV3 {
+    pub kind: RawTransactionV3Kind,
+    pub nonce: Felt,
+    pub fee_fields: FeeFieldsV3,
+}
+
+#[derive(Debug, Clone)]
+pub enum RawTransactionV3Kind {
+    Invoke { calls: Vec<Call> },
+    Declare { contract_class: Arc<FlattenedSierraClass>, compiled_class_hash: Felt },
+}
+
+impl RawTransactionV3 {
+    /// Computes the hash of the transaction of `sender_address`, whose calls are encoded by
+    /// `encoder`.
+    pub fn transaction_hash<E>(
+        &self,
+        chain_id: Felt,
+        sender_address: Felt,
+        query_only: bool,
+        encoder: &E,
+    ) -> Felt
+    where
+        E: ExecutionEncoder + ?Sized,
+    {
+        let account_deployment_data = poseidon_hash_many(&[]);
+
+        let (prefix, fields) = match &self.kind {
+            RawTransactionV3Kind::Invoke { calls } => (
+                PREFIX_INVOKE,
+                vec![account_deployment_data, poseidon_hash_many(&encoder.encode_calls(calls))],
+            ),
+            RawTransactionV3Kind::Declare { contract_class, compiled_class_hash } => (
+                PREFIX_DECLARE,
+                vec![account_deployment_data, contract_class.class_hash(), *compiled_class_hash],
+            ),
+        };
+
+        self.fee_fields.transaction_hash(
+            prefix,
+            sender_address,
+            chain_id,
+            self.nonce,
+            query_only,
+            &fields,
+        )
+    }
+
+    fn broadcasted<A>(
+        &self,
+        account: &A,
+        signature: Vec<Felt>,
+        query_only: bool,
+    ) -> BroadcastedTransaction
+    where
+        A: ConnectedAccount,
+    {
+        let fee_fields = &self.fee_fields;
+
+        match &self.kind {
+            RawTransactionV3Kind::Invoke { calls } => BroadcastedTransaction::Invoke(
+                BroadcastedInvokeTransaction::V3(BroadcastedInvokeTransactionV3 {
+                    sender_address: account.address(),
+                    calldata: account.encode_calls(calls),
+                    signature,
+                    nonce: self.nonce,
+                    resource_bounds: fee_fields.resource_bounds(),
+                    tip: fee_fields.tip,
+                    paymaster_data: vec![],
+                    account_deployment_data: vec![],
+                    nonce_data_availability_mode: fee_fields.nonce_data_availability_mode,
+                    fee_data_availability_mode: fee_fields.fee_data_availability_mode,
+                    is_query: query_only,
+                }),
+            ),
+            RawTransactionV3Kind::Declare { contract_class, compiled_class_hash } => {
+                BroadcastedTransaction::Declare(BroadcastedDeclareTransaction::V3(
+                    BroadcastedDeclareTransactionV3 {
+                        sender_address: account.address(),
+                        compiled_class_hash: *compiled_class_hash,
+                        signature,
+                        nonce: self.nonce,
+                        contract_class: contract_class.clone(),
+                        resource_bounds: fee_fields.resource_bounds(),
+                        tip: fee_fields.tip,
+                        paymaster_data: vec![],
+                        account_deployment_data: vec![],
+                        nonce_data_availability_mode: fee_fields.nonce_data_availability_mode,
+                        fee_data_availability_mode: fee_fields.fee_data_availability_mode,
+                        is_query: query_only,
+                    },
+                ))
+            }
+        }
+    }
+}
+
+/// An account signing V3 transactions whatever their tip and data availability modes. The V3
+/// executions and declarations of the [`Account`] trait are always signed without tip and with
+/// the L1 data availability modes.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait TransactionV3Signer: ConnectedAccount {
+    /// Signs the transaction, or returns `None` if the account only signs V3 transactions
+    /// without tip and with the L1 data availability modes.
+    async fn sign_transaction_v3(
+        &self,
+        transaction: &RawTransactionV3,
+        query_only: bool,
+    ) -> Option<Result<Vec<Felt>, Self::SignError>>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P, S> TransactionV3Signer for SingleOwnerAccount<P, S>
+where
+    P: Provider + Sync + Send,
+    S: Signer + Sync + Send,
+{
+    // The signer of the account is out of reach, it only signs the transactions it builds.
+    async fn sign_transaction_v3(
+        &self,
+        _transaction: &RawTransactionV3,
+        _query_only: bool,
+    ) -> Option<Result<Vec<Felt>, Self::SignError>> {
+        None
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A> TransactionV3Signer for &A
+where
+    A: TransactionV3Signer + Sync,
+{
+    async fn sign_transaction_v3(
+        &self,
+        transaction: &RawTransactionV3,
+        query_only: bool,
+    ) -> Option<Result<Vec<Felt>, Self::SignError>> {
+        (*self).sign_transaction_v3(transaction, query_only).await
+    }
+}
+
+/// Signs the transaction with the account and builds it as broadcasted.
+async fn broadcasted_transaction_v3<A>(
+    account: &A,
+    transaction: &RawTransactionV3,
+    query_only: bool,
+) -> Result<BroadcastedTransaction, TransactionError<A::SignError>>
+where
+    A: TransactionV3Signer + Sync,
+{
+    let signature = account
+        .sign_transaction_v3(transaction, query_only)
+        .await
+        .ok_or(TransactionError::UnsupportedV3Settings)?
+        .map_err(AccountError::Signing)?;
+
+    Ok(transaction.broadcasted(account, signature, query_only))
+}
+
+/// Sends a V3 transaction with the tip and data availability modes of `v3`, estimating its L1 gas
+/// bounds unless both are set. Returns the hash of the transaction.
+async fn send_transaction_v3<A>(
+    account: &A,
+    kind: RawTransactionV3Kind,
+    v3: &TxnV3Config,
+) -> Result<Felt, TransactionError<A::SignError>>
+where
+    A: TransactionV3Signer + Sync,
+{
+    let nonce = account.get_nonce().await?;
+    let mut transaction = RawTransactionV3 { kind, nonce, fee_fields: FeeFieldsV3::new(v3, 0, 0) };
+
+    let (l1_gas, l1_gas_price) = match (v3.l1_gas, v3.l1_gas_price) {
+        (Some(l1_gas), Some(l1_gas_price)) => (l1_gas, l1_gas_price),
+        _ => {
+            // Estimated without resource bounds, as `starknet-rs` does.
+            let query = broadcasted_transaction_v3(account, &transaction, true).await?;
+            let estimate = account
+                .provider()
+                .estimate_fee_single(
+                    query,
+                    Vec::<SimulationFlagForEstimateFee>::new(),
+                    account.block_id(),
+                )
+                .await?;
+
+            l1_gas_bounds(v3, &estimate).ok_or(AccountError::<A::SignError>::FeeOutOfRange)?
+        }
+    };
+    transaction.fee_fields = FeeFieldsV3::new(v3, l1_gas, l1_gas_price);
+
+    let provider = account.provider();
+    let transaction_hash = match broadcasted_transaction_v3(account, &transaction, false).await? {
+        BroadcastedTransaction::Invoke(tx) => {
+            provider.add_invoke_transaction(tx).await?.transaction_hash
+        }
+        BroadcastedTransaction::Declare(tx) => {
+            provider.add_declare_transaction(tx).await?.transaction_hash
+        }
+        BroadcastedTransaction::DeployAccount(tx) => {
+            provider.add_deploy_account_transaction(tx).await?.transaction_hash
+        }
+    };
+
+    Ok(transaction_hash)
+}
+
+/// Executes the calls in a V3 transaction if the `TxnConfig` has V3 settings, in a V1 transaction
+/// otherwise. V3 transactions with a tip or data availability modes other than L1 are signed by
+/// the account as a [`TransactionV3Signer`].
+pub async fn execute_with_cfg<A>(
+    account: &A,
+    calls: Vec<Call>,
+    txn_config: &TxnConfig,
+) -> Result<InvokeTransactionResult, TransactionError<A::SignError>>
+where
+    A: TransactionV3Signer + Sync,
+{
+    match txn_config.v3 {
+        Some(v3) if !v3.has_default_tip_and_da_modes() => {
+            let kind = RawTransactionV3Kind::Invoke { calls };
+            let transaction_hash = send_transaction_v3(account, kind, &v3).await?;
+            Ok(InvokeTransactionResult { transaction_hash })
+        }
+        Some(_) => Ok(account.execute_v3(calls).send_with_cfg(txn_config).await?),
+        None => Ok(account.execute_v1(calls).send_with_cfg(txn_config).await?),
+    }
+}
+
+/// Declares the class in a V3 transaction if the `TxnConfig` has V3 settings, in a V2 transaction
+/// otherwise. V3 transactions with a tip or data availability modes other than L1 are signed by
+/// the account as a [`TransactionV3Signer`].
+pub async fn declare_with_cfg<A>(
+    account: &A,
+    contract_class: Arc<FlattenedSierraClass>,
+    compiled_class_hash: Felt,
+    txn_config: &TxnConfig,
+) -> Result<DeclareTransactionResult, TransactionError<A::SignError>>
+where
+    A: TransactionV3Signer + Sync,
+{
+    match txn_config.v3 {
+        Some(v3) if !v3.has_default_tip_and_da_modes() => {
+            let class_hash = contract_class.class_hash();
+            let kind = RawTransactionV3Kind::Declare { contract_class, compiled_class_hash };
+            let transaction_hash = send_transaction_v3(account, kind, &v3).await?;
+            Ok(DeclareTransactionResult { transaction_hash, class_hash })
+        }
+        Some(_) => Ok(account
+            .declare_v3(contract_class, compiled_class_hash)
+            .send_with_cfg(txn_config)
+            .await?),
+        None => Ok(account
+            .declare_v2(contract_class, compiled_class_hash)
+            .send_with_cfg(txn_config)
+            .await?),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -394,15 +899,21 @@ mod tests {
     use dojo_test_utils::sequencer::{
         get_default_test_starknet_config, SequencerConfig, TestSequencer,
     };
+    use starknet::accounts::{Account, Call};
     use starknet::core::types::{
-        ComputationResources, DataAvailabilityResources, DataResources, ExecutionResources,
-        ExecutionResult, FeePayment, Felt, InvokeTransactionReceipt, PriceUnit, ReceiptBlock,
+        ComputationResources, DataAvailabilityMode, DataAvailabilityResources, DataResources,
+        EntryPointsByType, ExecutionResources, ExecutionResult, FeePayment, Felt,
+        FlattenedSierraClass, InvokeTransactionReceipt, PriceUnit, ReceiptBlock,
         TransactionFinalityStatus, TransactionReceipt, TransactionReceiptWithBlockInfo,
     };
+    use starknet::macros::felt;
     use starknet::providers::jsonrpc::HttpTransport;
     use starknet::providers::JsonRpcClient;
 
-    use super::{Duration, TransactionWaiter};
+    use super::{
+        Arc, Duration, FeeFieldsV3, RawTransactionV3, RawTransactionV3Kind, TransactionWaiter,
+    };
+    use crate::migration::TxnV3Config;
 
     #[allow(deprecated)]
     async fn create_test_sequencer() -> (TestSequencer, JsonRpcClient<HttpTransport>) {
@@ -480,6 +991,79 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn transaction_v3_hashes_match_accounts() {
+        let (sequencer, _) = create_test_sequencer().await;
+        let account = sequencer.account();
+
+        let calls = vec![Call { to: felt!("0x1"), selector: felt!("0x2"), calldata: vec![] }];
+        let contract_class = Arc::new(FlattenedSierraClass {
+            sierra_program: vec![felt!("0x1"), felt!("0x2")],
+            contract_class_version: "0.1.0".to_string(),
+            entry_points_by_type: EntryPointsByType {
+                constructor: vec![],
+                external: vec![],
+                l1_handler: vec![],
+            },
+            abi: "[]".to_string(),
+        });
+        let compiled_class_hash = felt!("0xc1a55");
+        let fee_fields = FeeFieldsV3::new(&TxnV3Config::default(), 1000, 100_000_000_000);
+
+        let invoke = RawTransactionV3 {
+            kind: RawTransactionV3Kind::Invoke { calls: calls.clone() },
+            nonce: felt!("0x3"),
+            fee_fields,
+        };
+        let declare = RawTransactionV3 {
+            kind: RawTransactionV3Kind::Declare {
+                contract_class: contract_class.clone(),
+                compiled_class_hash,
+            },
+            ..invoke.clone()
+        };
+        let hash = |transaction: &RawTransactionV3, query_only| {
+            transaction.transaction_hash(
+                account.chain_id(),
+                account.address(),
+                query_only,
+                &account,
+            )
+        };
+
+        for query_only in [false, true] {
+            let expected = account
+                .execute_v3(calls.clone())
+                .nonce(felt!("0x3"))
+                .gas(1000)
+                .gas_price(100_000_000_000)
+                .prepared()
+                .unwrap()
+                .transaction_hash(query_only);
+            assert_eq!(hash(&invoke, query_only), expected);
+
+            let expected = account
+                .declare_v3(contract_class.clone(), compiled_class_hash)
+                .nonce(felt!("0x3"))
+                .gas(1000)
+                .gas_price(100_000_000_000)
+                .prepared()
+                .unwrap()
+                .transaction_hash(query_only);
+            assert_eq!(hash(&declare, query_only), expected);
+        }
+
+        // the tip and the data availability modes are signed as well
+        let with_tip =
+            RawTransactionV3 { fee_fields: FeeFieldsV3 { tip: 1, ..fee_fields }, ..invoke.clone() };
+        assert_ne!(hash(&with_tip, false), hash(&invoke, false));
+
+        let fee_fields =
+            FeeFieldsV3 { nonce_data_availability_mode: DataAvailabilityMode::L2, ..fee_fields };
+        let with_l2_nonce = RawTransactionV3 { fee_fields, ..invoke.clone() };
+        assert_ne!(hash(&with_l2_nonce, false), hash(&invoke, false));
+    }
+
     #[test]
     fn wait_for_no_finality_status() {
         let receipt =
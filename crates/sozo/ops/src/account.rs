@@ -5,22 +5,27 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use colored::Colorize;
 use colored_json::{ColorMode, Output};
-use dojo_world::migration::{TxnAction, TxnConfig};
-use dojo_world::utils::{TransactionExt, TransactionWaiter};
+use dojo_world::migration::{TxnAction, TxnConfig, TxnV3Config};
+use dojo_world::utils::{l1_gas_bounds, FeeFieldsV3, TransactionExt, TransactionWaiter};
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use starknet::accounts::{AccountFactory, AccountFactoryError, OpenZeppelinAccountFactory};
+use starknet::accounts::{
+    AccountDeploymentV1, AccountDeploymentV3, AccountFactory, AccountFactoryError,
+    OpenZeppelinAccountFactory,
+};
 use starknet::core::serde::unsigned_field_element::UfeHex;
 use starknet::core::types::{
-    BlockId, BlockTag, FunctionCall, StarknetError, TransactionFinalityStatus,
+    BlockId, BlockTag, BroadcastedDeployAccountTransaction, BroadcastedDeployAccountTransactionV3,
+    BroadcastedTransaction, FeeEstimate, FunctionCall, SimulatedTransaction, SimulationFlag,
+    SimulationFlagForEstimateFee, StarknetError, TransactionFinalityStatus,
 };
 use starknet::core::utils::get_contract_address;
-use starknet::macros::{felt, selector};
+use starknet::macros::{felt, selector, short_string};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider, ProviderError};
 use starknet::signers::{LocalWallet, Signer, SigningKey};
-use starknet_crypto::Felt;
+use starknet_crypto::{poseidon_hash_many, Felt};
 
 use crate::utils;
 
@@ -127,6 +132,16 @@ impl MaxFeeType {
             Self::Estimated { estimate_with_buffer, .. } => *estimate_with_buffer,
         }
     }
+
+    fn estimated(estimate: Felt, fee_estimate_multiplier: Option<f64>) -> Result<Self> {
+        let fee_estimate_multiplier = fee_estimate_multiplier.unwrap_or(1.1);
+
+        let estimate_with_buffer = (((estimate.to_u64().context("Invalid u64")? as f64)
+            * fee_estimate_multiplier) as u64)
+            .into();
+
+        Ok(Self::Estimated { estimate, estimate_with_buffer })
+    }
 }
 
 #[derive(Debug)]
@@ -224,7 +239,7 @@ pub async fn deploy(
             let mut factory = OpenZeppelinAccountFactory::new(
                 undeployed_status.class_hash,
                 chain_id,
-                signer,
+                signer.clone(),
                 &provider,
             )
             .await?;
@@ -234,7 +249,22 @@ pub async fn deploy(
         }
     };
 
-    let account_deployment = factory.deploy_v1(undeployed_status.salt);
+    let account_deployment = match txn_action.v3() {
+        Some(v3) if !v3.has_default_tip_and_da_modes() => {
+            AccountDeployment::RawV3(RawAccountDeploymentV3 {
+                provider: &provider,
+                public_key: signer.get_public_key().await?.scalar(),
+                signer,
+                chain_id,
+                class_hash: undeployed_status.class_hash,
+                salt: undeployed_status.salt,
+                nonce: Felt::ZERO,
+                v3,
+            })
+        }
+        Some(_) => AccountDeployment::V3(factory.deploy_v3(undeployed_status.salt)),
+        None => AccountDeployment::V1(factory.deploy_v1(undeployed_status.salt)),
+    };
 
     let target_deployment_address = account.deploy_account_address()?;
 
@@ -249,36 +279,22 @@ pub async fn deploy(
     };
 
     match txn_action {
-        TxnAction::Send { wait, receipt, max_fee_raw, fee_estimate_multiplier } => {
-            let max_fee = if let Some(max_fee_raw) = max_fee_raw {
-                MaxFeeType::Manual { max_fee: max_fee_raw }
-            } else {
-                let estimated_fee = account_deployment
-                    .estimate_fee()
-                    .await
-                    .map_err(|err| match err {
-                        AccountFactoryError::Provider(ProviderError::StarknetError(err)) => {
-                            map_starknet_error(err)
-                        }
-                        err => anyhow::anyhow!("{}", err),
-                    })?
-                    .overall_fee;
-
-                let fee_estimate_multiplier = fee_estimate_multiplier.unwrap_or(1.1);
-
-                let estimated_fee_with_buffer =
-                    (((estimated_fee.to_u64().context("Invalid u64")? as f64)
-                        * fee_estimate_multiplier) as u64)
-                        .into();
-
-                MaxFeeType::Estimated {
-                    estimate: estimated_fee,
-                    estimate_with_buffer: estimated_fee_with_buffer,
+        TxnAction::Send { wait, receipt, max_fee_raw, fee_estimate_multiplier, v3 } => {
+            let max_fee = match account_deployment.manual_max_fee(max_fee_raw, v3) {
+                Some(max_fee) => MaxFeeType::Manual { max_fee },
+                None => {
+                    let estimated_fee = account_deployment.estimate_fee().await?;
+                    let multiplier = match v3 {
+                        Some(v3) => v3.gas_estimate_multiplier,
+                        None => fee_estimate_multiplier,
+                    };
+
+                    MaxFeeType::estimated(estimated_fee, multiplier)?
                 }
             };
 
-            let account_deployment = account_deployment.max_fee(max_fee.max_fee());
-            let txn_config = TxnConfig { fee_estimate_multiplier, wait, receipt, max_fee_raw };
+            let account_deployment = account_deployment.max_fee(&max_fee);
+            let txn_config = TxnConfig { fee_estimate_multiplier, wait, receipt, max_fee_raw, v3 };
             do_account_deploy(
                 max_fee,
                 txn_config,
@@ -294,59 +310,270 @@ pub async fn deploy(
             write_account_to_file(file, account)?;
             Ok(())
         }
-        TxnAction::Estimate => {
-            let estimated_fee = account_deployment
-                .estimate_fee()
-                .await
-                .map_err(|err| match err {
-                    AccountFactoryError::Provider(ProviderError::StarknetError(err)) => {
-                        map_starknet_error(err)
-                    }
-                    err => anyhow::anyhow!("{}", err),
-                })?
-                .overall_fee;
-
-            let decimal = utils::felt_to_bigdecimal(estimated_fee, 18);
-            println!("{} ETH", format!("{decimal}").bright_yellow());
-
+        TxnAction::Estimate { .. } => {
+            let estimated_fee = account_deployment.estimate_fee().await?;
+            print_estimated_fee(estimated_fee, account_deployment.fee_token());
             Ok(())
         }
-        TxnAction::Simulate => {
-            simulate_account_deploy(&account_deployment).await?;
+        TxnAction::Simulate { v3 } => {
+            print_simulation(account_deployment.simulate(v3).await?)?;
             Ok(())
         }
     }
 }
 
+type OzAccountFactory<'a> =
+    OpenZeppelinAccountFactory<LocalWallet, &'a JsonRpcClient<HttpTransport>>;
+
+/// The deployment of an account, in a V1 transaction paying the fees in ETH or in a V3
+/// transaction paying them in STRK.
+enum AccountDeployment<'f, 'a> {
+    V1(AccountDeploymentV1<'f, OzAccountFactory<'a>>),
+    V3(AccountDeploymentV3<'f, OzAccountFactory<'a>>),
+    /// A V3 deployment with a tip or data availability modes other than L1, that the factories of
+    /// `starknet-rs` can't sign.
+    RawV3(RawAccountDeploymentV3<'a>),
+}
+
+impl AccountDeployment<'_, '_> {
+    fn address(&self) -> Felt {
+        match self {
+            Self::V1(deployment) => deployment.address(),
+            Self::V3(deployment) => deployment.address(),
+            Self::RawV3(deployment) => deployment.address(),
+        }
+    }
+
+    fn nonce(self, nonce: Felt) -> Self {
+        match self {
+            Self::V1(deployment) => Self::V1(deployment.nonce(nonce)),
+            Self::V3(deployment) => Self::V3(deployment.nonce(nonce)),
+            Self::RawV3(deployment) => Self::RawV3(RawAccountDeploymentV3 { nonce, ..deployment }),
+        }
+    }
+
+    fn fee_token(&self) -> FeeToken {
+        match self {
+            Self::V1(_) => FeeToken::Eth,
+            Self::V3(_) | Self::RawV3(_) => FeeToken::Strk,
+        }
+    }
+
+    /// The max fee set by the user, the raw max fee of V1 transactions or the L1 gas bounds of V3
+    /// transactions.
+    fn manual_max_fee(&self, max_fee_raw: Option<Felt>, v3: Option<TxnV3Config>) -> Option<Felt> {
+        match (self, v3) {
+            (Self::V1(_), _) => max_fee_raw,
+            (
+                Self::V3(_) | Self::RawV3(_),
+                Some(TxnV3Config {
+                    l1_gas: Some(l1_gas), l1_gas_price: Some(l1_gas_price), ..
+                }),
+            ) => Some(Felt::from((l1_gas as u128).saturating_mul(l1_gas_price))),
+            (Self::V3(_) | Self::RawV3(_), _) => None,
+        }
+    }
+
+    /// Sets the max fee of V1 transactions, the L1 gas bounds of V3 transactions being set from
+    /// the `TxnConfig` when sent.
+    fn max_fee(self, max_fee: &MaxFeeType) -> Self {
+        match self {
+            Self::V1(deployment) => Self::V1(deployment.max_fee(max_fee.max_fee())),
+            deployment => deployment,
+        }
+    }
+
+    async fn estimate_fee(&self) -> Result<Felt> {
+        let estimate = match self {
+            Self::V1(deployment) => deployment.estimate_fee().await,
+            Self::V3(deployment) => deployment.estimate_fee().await,
+            Self::RawV3(deployment) => return Ok(deployment.estimate_fee().await?.overall_fee),
+        };
+
+        Ok(estimate.map_err(map_account_factory_error)?.overall_fee)
+    }
+
+    async fn simulate(self, v3: Option<TxnV3Config>) -> Result<SimulatedTransaction> {
+        let simulation = match self {
+            Self::V1(deployment) => deployment.simulate(false, false).await,
+            Self::V3(deployment) => {
+                let deployment = match v3 {
+                    Some(TxnV3Config {
+                        l1_gas: Some(l1_gas),
+                        l1_gas_price: Some(l1_gas_price),
+                        ..
+                    }) => deployment.gas(l1_gas).gas_price(l1_gas_price),
+                    _ => deployment,
+                };
+
+                deployment.simulate(false, false).await
+            }
+            Self::RawV3(deployment) => return deployment.simulate().await,
+        };
+
+        Ok(simulation?)
+    }
+
+    async fn send(self, txn_config: &TxnConfig) -> Result<Felt> {
+        let result = match self {
+            Self::V1(deployment) => deployment.send_with_cfg(txn_config).await,
+            Self::V3(deployment) => deployment.send_with_cfg(txn_config).await,
+            Self::RawV3(deployment) => return deployment.send().await,
+        };
+
+        Ok(result?.transaction_hash)
+    }
+}
+
+/// A V3 deployment of an OpenZeppelin account, built and signed here to set its tip and data
+/// availability modes.
+struct RawAccountDeploymentV3<'a> {
+    provider: &'a JsonRpcClient<HttpTransport>,
+    signer: LocalWallet,
+    public_key: Felt,
+    chain_id: Felt,
+    class_hash: Felt,
+    salt: Felt,
+    nonce: Felt,
+    v3: TxnV3Config,
+}
+
+impl RawAccountDeploymentV3<'_> {
+    fn address(&self) -> Felt {
+        get_contract_address(self.salt, self.class_hash, &[self.public_key], Felt::ZERO)
+    }
+
+    async fn estimate_fee(&self) -> Result<FeeEstimate> {
+        // Estimated without resource bounds, as `starknet-rs` does.
+        let query = self.broadcasted(FeeFieldsV3::new(&self.v3, 0, 0), true).await?;
+        let query = BroadcastedTransaction::DeployAccount(query);
+        let estimate = self
+            .provider
+            .estimate_fee_single(
+                query,
+                Vec::<SimulationFlagForEstimateFee>::new(),
+                BlockId::Tag(BlockTag::Pending),
+            )
+            .await?;
+
+        Ok(estimate)
+    }
+
+    async fn simulate(&self) -> Result<SimulatedTransaction> {
+        let transaction = self.broadcasted(self.fee_fields().await?, true).await?;
+        let transaction = BroadcastedTransaction::DeployAccount(transaction);
+        let simulation = self
+            .provider
+            .simulate_transaction(
+                BlockId::Tag(BlockTag::Pending),
+                transaction,
+                Vec::<SimulationFlag>::new(),
+            )
+            .await?;
+
+        Ok(simulation)
+    }
+
+    async fn send(&self) -> Result<Felt> {
+        let transaction = self.broadcasted(self.fee_fields().await?, false).await?;
+        Ok(self.provider.add_deploy_account_transaction(transaction).await?.transaction_hash)
+    }
+
+    /// The fee fields of the deployment, with the L1 gas bounds set by the user or estimated.
+    async fn fee_fields(&self) -> Result<FeeFieldsV3> {
+        let (l1_gas, l1_gas_price) = match (self.v3.l1_gas, self.v3.l1_gas_price) {
+            (Some(l1_gas), Some(l1_gas_price)) => (l1_gas, l1_gas_price),
+            _ => l1_gas_bounds(&self.v3, &self.estimate_fee().await?)
+                .context("Estimated fee out of range.")?,
+        };
+
+        Ok(FeeFieldsV3::new(&self.v3, l1_gas, l1_gas_price))
+    }
+
+    fn transaction_hash(&self, fee_fields: &FeeFieldsV3, query_only: bool) -> Felt {
+        fee_fields.transaction_hash(
+            short_string!("deploy_account"),
+            self.address(),
+            self.chain_id,
+            self.nonce,
+            query_only,
+            &[poseidon_hash_many(&[self.public_key]), self.class_hash, self.salt],
+        )
+    }
+
+    async fn broadcasted(
+        &self,
+        fee_fields: FeeFieldsV3,
+        query_only: bool,
+    ) -> Result<BroadcastedDeployAccountTransaction> {
+        let transaction_hash = self.transaction_hash(&fee_fields, query_only);
+        let signature = self.signer.sign_hash(&transaction_hash).await?;
+
+        Ok(BroadcastedDeployAccountTransaction::V3(BroadcastedDeployAccountTransactionV3 {
+            signature: vec![signature.r, signature.s],
+            nonce: self.nonce,
+            contract_address_salt: self.salt,
+            constructor_calldata: vec![self.public_key],
+            class_hash: self.class_hash,
+            resource_bounds: fee_fields.resource_bounds(),
+            tip: fee_fields.tip,
+            paymaster_data: vec![],
+            nonce_data_availability_mode: fee_fields.nonce_data_availability_mode,
+            fee_data_availability_mode: fee_fields.fee_data_availability_mode,
+            is_query: query_only,
+        }))
+    }
+}
+
+/// The token the fees of a transaction are paid in, ETH for V1 transactions and STRK for V3
+/// transactions.
+#[derive(Debug, Clone, Copy)]
+enum FeeToken {
+    Eth,
+    Strk,
+}
+
+impl std::fmt::Display for FeeToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eth => write!(f, "ETH"),
+            Self::Strk => write!(f, "STRK"),
+        }
+    }
+}
+
+fn print_estimated_fee(estimated_fee: Felt, token: FeeToken) {
+    let decimal = utils::felt_to_bigdecimal(estimated_fee, 18);
+    println!("{}", format!("{decimal} {token}").bright_yellow());
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn do_account_deploy(
     max_fee: MaxFeeType,
     txn_config: TxnConfig,
     target_deployment_address: Felt,
     no_confirmation: bool,
-    account_deployment: starknet::accounts::AccountDeploymentV1<
-        '_,
-        OpenZeppelinAccountFactory<LocalWallet, &JsonRpcClient<HttpTransport>>,
-    >,
+    account_deployment: AccountDeployment<'_, '_>,
     provider: &JsonRpcClient<HttpTransport>,
     poll_interval: u64,
     account: &mut AccountConfig,
 ) -> Result<(), anyhow::Error> {
+    let token = account_deployment.fee_token();
     match max_fee {
         MaxFeeType::Manual { max_fee } => {
             eprintln!(
                 "You've manually specified the account deployment fee to be {}. Therefore, fund \
                  at least:\n    {}",
-                format!("{} ETH", utils::felt_to_bigdecimal(max_fee, 18)).bright_yellow(),
-                format!("{} ETH", utils::felt_to_bigdecimal(max_fee, 18)).bright_yellow(),
+                format!("{} {token}", utils::felt_to_bigdecimal(max_fee, 18)).bright_yellow(),
+                format!("{} {token}", utils::felt_to_bigdecimal(max_fee, 18)).bright_yellow(),
             );
         }
         MaxFeeType::Estimated { estimate, estimate_with_buffer } => {
             eprintln!(
                 "The estimated account deployment fee is {}. However, to avoid failure, fund at \
                  least:\n    {}",
-                format!("{} ETH", utils::felt_to_bigdecimal(estimate, 18)).bright_yellow(),
-                format!("{} ETH", utils::felt_to_bigdecimal(estimate_with_buffer, 18))
+                format!("{} {token}", utils::felt_to_bigdecimal(estimate, 18)).bright_yellow(),
+                format!("{} {token}", utils::felt_to_bigdecimal(estimate_with_buffer, 18))
                     .bright_yellow()
             );
         }
@@ -360,8 +587,7 @@ async fn do_account_deploy(
         std::io::stdin().read_line(&mut String::new())?;
     }
 
-    let account_deployment_tx =
-        account_deployment.send_with_cfg(&txn_config).await?.transaction_hash;
+    let account_deployment_tx = account_deployment.send(&txn_config).await?;
 
     eprintln!(
         "Account deployment transaction: {}",
@@ -412,13 +638,7 @@ fn write_account_to_file(file: PathBuf, account: AccountConfig) -> Result<(), an
     Ok(())
 }
 
-async fn simulate_account_deploy(
-    account_deployment: &starknet::accounts::AccountDeploymentV1<
-        '_,
-        OpenZeppelinAccountFactory<LocalWallet, &JsonRpcClient<HttpTransport>>,
-    >,
-) -> Result<(), anyhow::Error> {
-    let simulation = account_deployment.simulate(false, false).await?;
+fn print_simulation(simulation: SimulatedTransaction) -> Result<(), anyhow::Error> {
     let simulation_json = serde_json::to_value(simulation)?;
     let simulation_json =
         colored_json::to_colored_json(&simulation_json, ColorMode::Auto(Output::StdOut))?;
@@ -472,6 +692,16 @@ fn true_as_default() -> bool {
     true
 }
 
+fn map_account_factory_error<S>(err: AccountFactoryError<S>) -> anyhow::Error
+where
+    S: std::error::Error,
+{
+    match err {
+        AccountFactoryError::Provider(ProviderError::StarknetError(err)) => map_starknet_error(err),
+        err => anyhow::anyhow!("{}", err),
+    }
+}
+
 fn map_starknet_error(err: StarknetError) -> anyhow::Error {
     match err {
         StarknetError::ContractError(err) => {
@@ -493,3 +723,66 @@ fn map_starknet_error(err: StarknetError) -> anyhow::Error {
         err => anyhow::anyhow!("{}", err),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::DataAvailabilityMode;
+    use url::Url;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn raw_v3_deployment_hashes_match_factories() {
+        // The provider is never reached, hashes are computed locally.
+        let provider =
+            JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+        let signer = LocalWallet::from_signing_key(SigningKey::from_secret_scalar(felt!("0x1")));
+        let chain_id = short_string!("SN_SEPOLIA");
+        let salt = felt!("0x5a17");
+
+        let factory = OpenZeppelinAccountFactory::new(
+            DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+            chain_id,
+            signer.clone(),
+            &provider,
+        )
+        .await
+        .unwrap();
+
+        let deployment = RawAccountDeploymentV3 {
+            provider: &provider,
+            public_key: signer.get_public_key().await.unwrap().scalar(),
+            signer,
+            chain_id,
+            class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+            salt,
+            nonce: Felt::ZERO,
+            v3: TxnV3Config::default(),
+        };
+        let fee_fields = FeeFieldsV3::new(&deployment.v3, 1000, 100_000_000_000);
+
+        for query_only in [false, true] {
+            let expected = factory
+                .deploy_v3(salt)
+                .nonce(Felt::ZERO)
+                .gas(1000)
+                .gas_price(100_000_000_000)
+                .prepared()
+                .unwrap()
+                .transaction_hash(query_only);
+
+            assert_eq!(deployment.transaction_hash(&fee_fields, query_only), expected);
+        }
+        assert_eq!(deployment.address(), factory.deploy_v3(salt).address());
+
+        let tipped = FeeFieldsV3 {
+            tip: 1,
+            fee_data_availability_mode: DataAvailabilityMode::L2,
+            ..fee_fields
+        };
+        assert_ne!(
+            deployment.transaction_hash(&tipped, false),
+            deployment.transaction_hash(&fee_fields, false)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
@@ -8,9 +9,9 @@ use dojo_world::contracts::naming::{
 use dojo_world::contracts::world::WorldContract;
 use dojo_world::contracts::WorldContractReader;
 use dojo_world::migration::TxnConfig;
-use dojo_world::utils::{TransactionExt, TransactionWaiter};
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer, TransactionWaiter};
 use scarb_ui::Ui;
-use starknet::accounts::{Account, ConnectedAccount};
+use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::{BlockId, BlockTag, Felt};
 
 use crate::migration::ui::MigrationUi;
@@ -51,6 +52,17 @@ impl FromStr for ResourceType {
     }
 }
 
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceType::Contract(name) => write!(f, "contract:{name}"),
+            ResourceType::Namespace(name) => write!(f, "namespace:{name}"),
+            ResourceType::Model(name) => write!(f, "model:{name}"),
+            ResourceType::Selector(selector) => write!(f, "selector:{selector:#x}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ResourceWriter {
     pub resource: ResourceType,
@@ -76,6 +88,12 @@ impl FromStr for ResourceWriter {
     }
 }
 
+impl fmt::Display for ResourceWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.resource, self.tag_or_address)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ResourceOwner {
     pub resource: ResourceType,
@@ -105,36 +123,27 @@ impl FromStr for ResourceOwner {
     }
 }
 
+/// Grants write access to the writers, returning the hash of the transaction if one was sent.
 pub async fn grant_writer<'a, A>(
     ui: &'a Ui,
     world: &WorldContract<A>,
     new_writers: &[ResourceWriter],
     txn_config: TxnConfig,
     default_namespace: &str,
-) -> Result<()>
+) -> Result<Option<Felt>>
 where
-    A: ConnectedAccount + Sync + Send,
+    A: TransactionV3Signer + Sync + Send,
     <A as Account>::SignError: 'static,
 {
-    let mut calls = Vec::new();
-
-    for new_writer in new_writers {
-        let resource_selector =
-            get_resource_selector(ui, world, &new_writer.resource, default_namespace).await?;
-        let contract_address =
-            utils::get_contract_address(world, &new_writer.tag_or_address).await?;
-        calls.push(world.grant_writer_getcall(&resource_selector, &contract_address.into()));
-    }
+    let calls = grant_writer_calls(ui, world, new_writers, default_namespace).await?;
 
     if !calls.is_empty() {
-        let res = world
-            .account
-            .execute_v1(calls)
-            .send_with_cfg(&txn_config)
+        let res = execute_with_cfg(&world.account, calls, &txn_config)
             .await
             .with_context(|| "Failed to send transaction")?;
 
-        TransactionWaiter::new(res.transaction_hash, &world.provider()).await?;
+        let transaction_hash = res.transaction_hash;
+        TransactionWaiter::new(transaction_hash, &world.provider()).await?;
 
         utils::handle_transaction_result(
             ui,
@@ -144,9 +153,11 @@ where
             txn_config.receipt,
         )
         .await?;
+
+        return Ok(Some(transaction_hash));
     }
 
-    Ok(())
+    Ok(None)
 }
 
 pub async fn grant_owner<A>(
@@ -157,7 +168,7 @@ pub async fn grant_owner<A>(
     default_namespace: &str,
 ) -> Result<()>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
 {
     let mut calls = Vec::new();
 
@@ -167,10 +178,7 @@ where
         calls.push(world.grant_owner_getcall(&resource_selector, &new_owner.owner.into()));
     }
 
-    let res = world
-        .account
-        .execute_v1(calls)
-        .send_with_cfg(&txn_config)
+    let res = execute_with_cfg(&world.account, calls, &txn_config)
         .await
         .with_context(|| "Failed to send transaction")?;
 
@@ -188,35 +196,26 @@ where
     Ok(())
 }
 
+/// Revokes write access of the writers, returning the hash of the transaction if one was sent.
 pub async fn revoke_writer<A>(
     ui: &Ui,
     world: &WorldContract<A>,
     new_writers: &[ResourceWriter],
     txn_config: TxnConfig,
     default_namespace: &str,
-) -> Result<()>
+) -> Result<Option<Felt>>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
 {
-    let mut calls = Vec::new();
-
-    for new_writer in new_writers {
-        let resource_selector =
-            get_resource_selector(ui, world, &new_writer.resource, default_namespace).await?;
-        let contract_address =
-            utils::get_contract_address(world, &new_writer.tag_or_address).await?;
-        calls.push(world.revoke_writer_getcall(&resource_selector, &contract_address.into()));
-    }
+    let calls = revoke_writer_calls(ui, world, new_writers, default_namespace).await?;
 
     if !calls.is_empty() {
-        let res = world
-            .account
-            .execute_v1(calls)
-            .send_with_cfg(&txn_config)
+        let res = execute_with_cfg(&world.account, calls, &txn_config)
             .await
             .with_context(|| "Failed to send transaction")?;
 
-        TransactionWaiter::new(res.transaction_hash, &world.provider()).await?;
+        let transaction_hash = res.transaction_hash;
+        TransactionWaiter::new(transaction_hash, &world.provider()).await?;
 
         utils::handle_transaction_result(
             ui,
@@ -226,9 +225,11 @@ where
             txn_config.receipt,
         )
         .await?;
+
+        return Ok(Some(transaction_hash));
     }
 
-    Ok(())
+    Ok(None)
 }
 
 pub async fn revoke_owner<A>(
@@ -239,7 +240,7 @@ pub async fn revoke_owner<A>(
     default_namespace: &str,
 ) -> Result<()>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
 {
     let mut calls = Vec::new();
 
@@ -249,10 +250,7 @@ where
         calls.push(world.revoke_owner_getcall(&resource_selector, &new_owner.owner.into()));
     }
 
-    let res = world
-        .account
-        .execute_v1(calls)
-        .send_with_cfg(&txn_config)
+    let res = execute_with_cfg(&world.account, calls, &txn_config)
         .await
         .with_context(|| "Failed to send transaction")?;
 
@@ -268,6 +266,54 @@ where
     Ok(())
 }
 
+/// Builds the calls granting write access to the writers.
+pub(crate) async fn grant_writer_calls<A>(
+    ui: &Ui,
+    world: &WorldContract<A>,
+    new_writers: &[ResourceWriter],
+    default_namespace: &str,
+) -> Result<Vec<Call>>
+where
+    A: ConnectedAccount + Sync + Send,
+    <A as Account>::SignError: 'static,
+{
+    let mut calls = Vec::new();
+
+    for new_writer in new_writers {
+        let resource_selector =
+            get_resource_selector(ui, world, &new_writer.resource, default_namespace).await?;
+        let contract_address =
+            utils::get_contract_address(world, &new_writer.tag_or_address).await?;
+        calls.push(world.grant_writer_getcall(&resource_selector, &contract_address.into()));
+    }
+
+    Ok(calls)
+}
+
+/// Builds the calls revoking write access of the writers.
+pub(crate) async fn revoke_writer_calls<A>(
+    ui: &Ui,
+    world: &WorldContract<A>,
+    new_writers: &[ResourceWriter],
+    default_namespace: &str,
+) -> Result<Vec<Call>>
+where
+    A: ConnectedAccount + Sync + Send,
+    <A as Account>::SignError: 'static,
+{
+    let mut calls = Vec::new();
+
+    for new_writer in new_writers {
+        let resource_selector =
+            get_resource_selector(ui, world, &new_writer.resource, default_namespace).await?;
+        let contract_address =
+            utils::get_contract_address(world, &new_writer.tag_or_address).await?;
+        calls.push(world.revoke_writer_getcall(&resource_selector, &contract_address.into()));
+    }
+
+    Ok(calls)
+}
+
 pub async fn get_resource_selector<A>(
     ui: &Ui,
     world: &WorldContract<A>,
@@ -13,27 +13,40 @@ pub async fn call<P: Provider + Sync + Send>(
     calldata: Vec<Felt>,
     block_id: Option<String>,
 ) -> Result<()> {
-    let contract_address = get_contract_address_from_reader(&world_reader, tag_or_address).await?;
     let block_id = if let Some(block_id) = block_id {
         parse_block_id(block_id)?
     } else {
         BlockId::Tag(BlockTag::Pending)
     };
 
-    let output = world_reader
+    let output =
+        call_output(&world_reader, tag_or_address, &entrypoint, calldata, block_id).await?;
+
+    println!("[ {} ]", output.iter().map(|o| format!("0x{:x}", o)).collect::<Vec<_>>().join(" "));
+
+    Ok(())
+}
+
+/// Calls an entrypoint of a contract, returning its output.
+pub async fn call_output<P: Provider + Sync + Send>(
+    world_reader: &WorldContractReader<P>,
+    tag_or_address: String,
+    entrypoint: &str,
+    calldata: Vec<Felt>,
+    block_id: BlockId,
+) -> Result<Vec<Felt>> {
+    let contract_address = get_contract_address_from_reader(world_reader, tag_or_address).await?;
+
+    world_reader
         .provider()
         .call(
             FunctionCall {
                 contract_address,
-                entry_point_selector: get_selector_from_name(&entrypoint)?,
+                entry_point_selector: get_selector_from_name(entrypoint)?,
                 calldata,
             },
             block_id,
         )
         .await
-        .with_context(|| format!("Failed to call {entrypoint}"))?;
-
-    println!("[ {} ]", output.iter().map(|o| format!("0x{:x}", o)).collect::<Vec<_>>().join(" "));
-
-    Ok(())
+        .with_context(|| format!("Failed to call {entrypoint}"))
 }
@@ -1,20 +1,31 @@
 use std::collections::{HashMap, VecDeque};
 use std::fs;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use cainome::cairo_serde::{ByteArray, CairoSerde};
 use cainome::parser::tokens::{CompositeInner, CompositeInnerKind, CoreBasic, Token};
 use cainome::parser::AbiParser;
 use camino::Utf8PathBuf;
-use dojo_world::contracts::naming::get_filename_from_tag;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use dojo_world::contracts::naming::{
+    compute_selector_from_tag, get_filename_from_tag, get_name_from_tag,
+};
+use dojo_world::contracts::world::WorldEvent;
 use dojo_world::manifest::{
-    AbiFormat, DeploymentManifest, ManifestMethods, BASE_CONTRACT_TAG, DEPLOYMENT_DIR,
-    MANIFESTS_DIR, TARGET_DIR, WORLD_CONTRACT_TAG,
+    AbiFormat, DeploymentManifest, ManifestMethods, Member as ManifestMember, BASE_CONTRACT_TAG,
+    DEPLOYMENT_DIR, MANIFESTS_DIR, TARGET_DIR, WORLD_CONTRACT_TAG,
 };
-use starknet::core::types::{BlockId, EventFilter, Felt};
-use starknet::core::utils::{parse_cairo_short_string, starknet_keccak};
+use scarb_ui::Ui;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use starknet::core::types::contract::AbiEntry;
+use starknet::core::types::{BlockId, BlockTag, EmittedEvent, EventFilter, Felt};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string, starknet_keccak};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
+use starknet_crypto::poseidon_hash_many;
 
 pub fn get_event_filter(
     from_block: Option<u64>,
@@ -42,17 +53,10 @@ pub async fn parse(
     profile_name: &str,
 ) -> Result<()> {
     let events_map = if !json {
-        let manifest_dir = project_dir.join(MANIFESTS_DIR).join(profile_name);
         let target_dir = project_dir.join(TARGET_DIR).join(profile_name);
-        let deployed_manifest =
-            manifest_dir.join(DEPLOYMENT_DIR).join("manifest").with_extension("toml");
-
-        if !deployed_manifest.exists() {
-            return Err(anyhow!("Run scarb migrate before running this command"));
-        }
 
         Some(extract_events(
-            &DeploymentManifest::load_from_path(&deployed_manifest)?,
+            &load_deployment_manifest(project_dir, profile_name)?,
             project_dir,
             &target_dir,
         )?)
@@ -70,6 +74,24 @@ pub async fn parse(
     Ok(())
 }
 
+fn load_deployment_manifest(
+    project_dir: &Utf8PathBuf,
+    profile_name: &str,
+) -> Result<DeploymentManifest> {
+    let deployed_manifest = project_dir
+        .join(MANIFESTS_DIR)
+        .join(profile_name)
+        .join(DEPLOYMENT_DIR)
+        .join("manifest")
+        .with_extension("toml");
+
+    if !deployed_manifest.exists() {
+        return Err(anyhow!("Run scarb migrate before running this command"));
+    }
+
+    Ok(DeploymentManifest::load_from_path(&deployed_manifest)?)
+}
+
 fn is_event(token: &Token) -> bool {
     match token {
         Token::Composite(composite) => composite.is_event,
@@ -266,14 +288,698 @@ fn process_inners(
     Ok(ret)
 }
 
+/// A record change of the World decoded with the schema of its model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreEvent {
+    pub event: String,
+    pub model: String,
+    pub entity_id: Felt,
+    /// Keys of the entity, known from the events setting its record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Vec<Felt>>,
+    /// Values of the changed members, `None` when the record is deleted.
+    pub value: Option<Value>,
+}
+
+/// Filters the followed events on the records they change.
+#[derive(Debug, Clone, Default)]
+pub struct FollowFilter {
+    /// Tags of the models.
+    pub models: Option<Vec<String>>,
+    /// First keys of the entities.
+    pub keys: Option<Vec<Felt>>,
+}
+
+impl FollowFilter {
+    fn is_empty(&self) -> bool {
+        self.models.is_none() && self.keys.is_none()
+    }
+
+    /// Whether the event changes a record of the models of the filter, of an entity whose keys
+    /// start with the keys of the filter.
+    ///
+    /// The keys of an entity are only known once its record is set, the other entities match
+    /// when the keys of the filter are all their keys.
+    fn matches(&self, event: &StoreEvent) -> bool {
+        let model_matches =
+            self.models.as_ref().map_or(true, |models| models.contains(&event.model));
+        let keys_matches = self.keys.as_ref().map_or(true, |keys| match &event.keys {
+            Some(entity_keys) => entity_keys.starts_with(keys),
+            None => poseidon_hash_many(keys) == event.entity_id,
+        });
+
+        model_matches && keys_matches
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FollowedEvent {
+    block_number: Option<u64>,
+    transaction_hash: Felt,
+    #[serde(flatten)]
+    content: FollowedContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum FollowedContent {
+    Store(StoreEvent),
+    Raw { from_address: Felt, keys: Vec<Felt>, data: Vec<Felt> },
+}
+
+/// Identifier of an event, by its transaction and its position in it.
+type EventId = (Felt, usize);
+
+/// The events of the pending block already printed, with the latest accepted block when they
+/// were last seen pending.
+#[derive(Debug, Default)]
+struct PendingEvents {
+    seen: HashMap<EventId, u64>,
+}
+
+impl PendingEvents {
+    /// Records a pending event, returns whether it's seen for the first time.
+    fn see(&mut self, id: EventId, latest_block: u64) -> bool {
+        self.seen.insert(id, latest_block).is_none()
+    }
+
+    /// Forgets an event once accepted, returns whether it was already seen pending.
+    fn accept(&mut self, id: &EventId) -> bool {
+        self.seen.remove(id).is_some()
+    }
+
+    /// Forgets the events which are no longer pending while every block accepted since they
+    /// were seen has been processed, their transaction being dropped.
+    fn evict(&mut self, latest_block: u64) {
+        self.seen.retain(|_, seen_at| *seen_at >= latest_block);
+    }
+}
+
+/// Polls the events of the World, each event being returned once.
+struct Follower<P> {
+    provider: P,
+    world_address: Felt,
+    chunk_size: u64,
+    keys: Option<Vec<Vec<Felt>>>,
+    include_pending: bool,
+    next_block: u64,
+    pending: PendingEvents,
+}
+
+impl<P> Follower<P>
+where
+    P: Provider + Send + Sync,
+{
+    /// Returns the events of the blocks accepted since the last poll, and the ones of the
+    /// pending block not returned yet if `include_pending` is set.
+    async fn poll(&mut self) -> Result<Vec<EmittedEvent>> {
+        let mut events = vec![];
+        let latest_block = self.provider.block_number().await?;
+
+        if latest_block >= self.next_block {
+            let filter =
+                self.filter(BlockId::Number(self.next_block), BlockId::Number(latest_block));
+
+            for (id, event) in identify_events(self.fetch(filter).await?) {
+                if !self.pending.accept(&id) {
+                    events.push(event);
+                }
+            }
+
+            self.next_block = latest_block + 1;
+        }
+
+        self.pending.evict(latest_block);
+
+        if self.include_pending {
+            let pending = BlockId::Tag(BlockTag::Pending);
+
+            for (id, event) in identify_events(self.fetch(self.filter(pending, pending)).await?) {
+                if self.pending.see(id, latest_block) {
+                    events.push(event);
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn filter(&self, from_block: BlockId, to_block: BlockId) -> EventFilter {
+        EventFilter {
+            from_block: Some(from_block),
+            to_block: Some(to_block),
+            address: Some(self.world_address),
+            keys: self.keys.clone(),
+        }
+    }
+
+    async fn fetch(&self, filter: EventFilter) -> Result<Vec<EmittedEvent>> {
+        let mut events = vec![];
+        let mut continuation_token = None;
+
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), continuation_token, self.chunk_size)
+                .await?;
+            events.extend(page.events);
+
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+/// Follows the events of the World as new blocks are produced, decoding the record changes with
+/// the schemas of the models of the deployment manifest.
+///
+/// Events are printed as they are received, one JSON object per line if `json` is set. The
+/// events of the pending block are printed as soon as they are seen if `include_pending` is set,
+/// and not printed again once their block is accepted.
+#[allow(clippy::too_many_arguments)]
+pub async fn follow<P>(
+    ui: &Ui,
+    provider: P,
+    world_address: Felt,
+    from_block: Option<u64>,
+    chunk_size: u64,
+    filter: FollowFilter,
+    include_pending: bool,
+    poll_interval: Duration,
+    json: bool,
+    project_dir: &Utf8PathBuf,
+    profile_name: &str,
+) -> Result<()>
+where
+    P: Provider + Send + Sync,
+{
+    let manifest = load_deployment_manifest(project_dir, profile_name)?;
+    let schemas = load_model_schemas(&manifest, project_dir)?;
+    let events_map = if json {
+        None
+    } else {
+        let target_dir = project_dir.join(TARGET_DIR).join(profile_name);
+        Some(extract_events(&manifest, project_dir, &target_dir)?)
+    };
+
+    // Record changes don't have custom keys, the models and entities are filtered once decoded.
+    let keys = if filter.is_empty() {
+        None
+    } else {
+        Some(vec![["StoreSetRecord", "StoreUpdateRecord", "StoreUpdateMember", "StoreDelRecord"]
+            .iter()
+            .map(|name| starknet_keccak(name.as_bytes()))
+            .collect()])
+    };
+
+    let next_block = match from_block {
+        Some(block) => block,
+        None => provider.block_number().await? + 1,
+    };
+
+    let mut follower = Follower {
+        provider,
+        world_address,
+        chunk_size,
+        keys,
+        include_pending,
+        next_block,
+        pending: PendingEvents::default(),
+    };
+    let mut decoder = StoreEventDecoder { schemas, entity_keys: HashMap::new() };
+
+    loop {
+        for event in follower.poll().await? {
+            print_followed_event(ui, event, &mut decoder, events_map.as_ref(), &filter, json)?;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Identifies the events by their transaction and their position in it, which doesn't change
+/// when the pending block is accepted.
+fn identify_events(events: Vec<EmittedEvent>) -> Vec<(EventId, EmittedEvent)> {
+    let mut positions: HashMap<Felt, usize> = HashMap::new();
+
+    events
+        .into_iter()
+        .map(|event| {
+            let position = positions.entry(event.transaction_hash).or_default();
+            let id = (event.transaction_hash, *position);
+            *position += 1;
+            (id, event)
+        })
+        .collect()
+}
+
+/// Decodes the record changes, keeping the keys of the entities whose record is set.
+struct StoreEventDecoder {
+    schemas: HashMap<Felt, (String, Ty)>,
+    entity_keys: HashMap<Felt, Vec<Felt>>,
+}
+
+impl StoreEventDecoder {
+    fn decode(&mut self, event: WorldEvent) -> Result<Option<StoreEvent>> {
+        let Some(mut store_event) = decode_store_event(event, &self.schemas)? else {
+            return Ok(None);
+        };
+
+        match &store_event.keys {
+            Some(keys) => {
+                self.entity_keys.insert(store_event.entity_id, keys.clone());
+            }
+            None => store_event.keys = self.entity_keys.get(&store_event.entity_id).cloned(),
+        }
+
+        Ok(Some(store_event))
+    }
+}
+
+fn print_followed_event(
+    ui: &Ui,
+    event: EmittedEvent,
+    decoder: &mut StoreEventDecoder,
+    events_map: Option<&HashMap<String, Vec<Token>>>,
+    filter: &FollowFilter,
+    json: bool,
+) -> Result<()> {
+    let store_event = match WorldEvent::try_from(event.clone()) {
+        Ok(world_event) => decoder.decode(world_event).unwrap_or_else(|e| {
+            ui.warn(format!(
+                "Failed to decode event of transaction {:#x}: {e}",
+                event.transaction_hash
+            ));
+            None
+        }),
+        Err(_) => None,
+    };
+
+    let content = match store_event {
+        Some(store_event) if filter.matches(&store_event) => FollowedContent::Store(store_event),
+        Some(_) => return Ok(()),
+        None if !filter.is_empty() => return Ok(()),
+        None => FollowedContent::Raw {
+            from_address: event.from_address,
+            keys: event.keys.clone(),
+            data: event.data.clone(),
+        },
+    };
+
+    if json {
+        let followed = FollowedEvent {
+            block_number: event.block_number,
+            transaction_hash: event.transaction_hash,
+            content,
+        };
+        println!("{}", serde_json::to_string(&followed)?);
+        return Ok(());
+    }
+
+    let block =
+        event.block_number.map_or("pending".to_string(), |number| format!("block {number}"));
+    println!("[{block}] transaction {:#x}", event.transaction_hash);
+
+    match content {
+        FollowedContent::Store(store_event) => {
+            println!("{} {} {:#x}", store_event.event, store_event.model, store_event.entity_id);
+            if let Some(value) = store_event.value {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            }
+        }
+        FollowedContent::Raw { .. } => {
+            match events_map.map(|events_map| parse_event(event.clone(), events_map)) {
+                Some(Ok(parsed_event)) => println!("{parsed_event}"),
+                _ => println!("{}", serde_json::to_string_pretty(&event)?),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the schemas of the models of the manifest from their ABI, indexed by model selector.
+fn load_model_schemas(
+    manifest: &DeploymentManifest,
+    project_dir: &Utf8PathBuf,
+) -> Result<HashMap<Felt, (String, Ty)>> {
+    let mut schemas = HashMap::new();
+
+    for model in &manifest.models {
+        let tag = &model.inner.tag;
+
+        let Some(AbiFormat::Path(abi_path)) = model.inner.abi() else {
+            continue;
+        };
+
+        let abi_path = project_dir.join(abi_path);
+        let abi: Vec<AbiEntry> = serde_json::from_str(
+            &fs::read_to_string(&abi_path)
+                .with_context(|| format!("Failed to read ABI file at path: {abi_path}"))?,
+        )?;
+
+        let schema = model_schema_from_abi(&abi, &get_name_from_tag(tag), &model.inner.members)
+            .with_context(|| format!("Failed to build schema of model {tag}"))?;
+
+        schemas.insert(compute_selector_from_tag(tag), (tag.clone(), schema));
+    }
+
+    Ok(schemas)
+}
+
+/// Builds the schema of a model from the ABI of its class, which holds the definitions of every
+/// type used by the model, and the members of its manifest, which hold its keys.
+///
+/// The struct of the model is the one taken by its `ensure_abi` entrypoint, as different modules
+/// may define structs with the same name.
+fn model_schema_from_abi(
+    abi: &[AbiEntry],
+    model_name: &str,
+    members: &[ManifestMember],
+) -> Result<Ty> {
+    let struct_name = ensure_abi_model_type(abi)
+        .ok_or_else(|| anyhow!("`ensure_abi` entrypoint of model `{model_name}` not found."))?;
+
+    let model_struct = abi
+        .iter()
+        .find_map(|entry| match entry {
+            AbiEntry::Struct(s) if s.name == struct_name => Some(s),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("Struct of model `{model_name}` not found in its ABI."))?;
+
+    let children = model_struct
+        .members
+        .iter()
+        .map(|m| {
+            let key = members.iter().any(|member| member.name == m.name && member.key);
+            Ok(Member { name: m.name.clone(), ty: ty_from_abi(abi, &m.r#type)?, key })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Ty::Struct(Struct { name: model_name.to_string(), children }))
+}
+
+/// Full path of the type taken by the `ensure_abi` entrypoint of a model class.
+fn ensure_abi_model_type(abi: &[AbiEntry]) -> Option<&str> {
+    abi.iter().find_map(|entry| match entry {
+        AbiEntry::Function(f) if f.name == "ensure_abi" => {
+            f.inputs.iter().find(|i| i.name == "model").map(|i| i.r#type.as_str())
+        }
+        AbiEntry::Interface(i) => ensure_abi_model_type(&i.items),
+        _ => None,
+    })
+}
+
+/// Builds the [`Ty`] of an ABI type.
+fn ty_from_abi(abi: &[AbiEntry], type_name: &str) -> Result<Ty> {
+    let primitive = match type_name {
+        "core::felt252" => Some(Primitive::Felt252(None)),
+        "core::bool" => Some(Primitive::Bool(None)),
+        "core::integer::u8" => Some(Primitive::U8(None)),
+        "core::integer::u16" => Some(Primitive::U16(None)),
+        "core::integer::u32" => Some(Primitive::U32(None)),
+        "core::integer::u64" => Some(Primitive::U64(None)),
+        "core::integer::u128" => Some(Primitive::U128(None)),
+        "core::integer::u256" => Some(Primitive::U256(None)),
+        "core::integer::usize" => Some(Primitive::USize(None)),
+        "core::integer::i8" => Some(Primitive::I8(None)),
+        "core::integer::i16" => Some(Primitive::I16(None)),
+        "core::integer::i32" => Some(Primitive::I32(None)),
+        "core::integer::i64" => Some(Primitive::I64(None)),
+        "core::integer::i128" => Some(Primitive::I128(None)),
+        "core::starknet::class_hash::ClassHash" => Some(Primitive::ClassHash(None)),
+        "core::starknet::contract_address::ContractAddress" => {
+            Some(Primitive::ContractAddress(None))
+        }
+        _ => None,
+    };
+
+    if let Some(primitive) = primitive {
+        return Ok(Ty::Primitive(primitive));
+    }
+
+    if type_name == "core::byte_array::ByteArray" {
+        return Ok(Ty::ByteArray(String::new()));
+    }
+
+    for array in ["core::array::Array::<", "core::array::Span::<"] {
+        if let Some(item) = type_name.strip_prefix(array).and_then(|t| t.strip_suffix('>')) {
+            return Ok(Ty::Array(vec![ty_from_abi(abi, item)?]));
+        }
+    }
+
+    if let Some(items) = type_name.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        return Ok(Ty::Tuple(
+            split_tuple_items(items)
+                .into_iter()
+                .map(|t| ty_from_abi(abi, t))
+                .collect::<Result<_>>()?,
+        ));
+    }
+
+    for entry in abi {
+        match entry {
+            AbiEntry::Struct(s) if s.name == type_name => {
+                return Ok(Ty::Struct(Struct {
+                    name: short_name(type_name).to_string(),
+                    children: s
+                        .members
+                        .iter()
+                        .map(|m| {
+                            Ok(Member {
+                                name: m.name.clone(),
+                                ty: ty_from_abi(abi, &m.r#type)?,
+                                key: false,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                }));
+            }
+            AbiEntry::Enum(e) if e.name == type_name => {
+                return Ok(Ty::Enum(Enum {
+                    name: short_name(type_name).to_string(),
+                    option: None,
+                    options: e
+                        .variants
+                        .iter()
+                        .map(|v| {
+                            Ok(EnumOption {
+                                name: v.name.clone(),
+                                ty: ty_from_abi(abi, &v.r#type)?,
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    bail!("Type `{type_name}` not found in the ABI.")
+}
+
+/// Name of a type without its path nor its generic arguments.
+fn short_name(type_name: &str) -> &str {
+    let name = type_name.split("::<").next().unwrap_or(type_name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Splits the items of a tuple type on the commas which are not nested in another type.
+fn split_tuple_items(items: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in items.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(items[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = items[start..].trim();
+    if !last.is_empty() {
+        result.push(last);
+    }
+
+    result
+}
+
+/// Decodes a record change with the schema of its model, returns `None` for the other events.
+fn decode_store_event(
+    event: WorldEvent,
+    schemas: &HashMap<Felt, (String, Ty)>,
+) -> Result<Option<StoreEvent>> {
+    let schema = |table: &Felt| {
+        schemas.get(table).ok_or_else(|| anyhow!("Unknown model with selector {table:#x}"))
+    };
+
+    let store_event = match event {
+        WorldEvent::StoreSetRecord(e) => {
+            let (model, schema) = schema(&e.table)?;
+            let mut keys = e.keys.clone();
+            let mut values = e.values;
+
+            let mut members = Map::new();
+            for member in members_of(schema) {
+                let mut ty = member.ty.clone();
+                ty.deserialize(if member.key { &mut keys } else { &mut values })?;
+                members.insert(member.name.clone(), ty_to_json(&ty)?);
+            }
+
+            StoreEvent {
+                event: "StoreSetRecord".to_string(),
+                model: model.clone(),
+                entity_id: poseidon_hash_many(&e.keys),
+                keys: Some(e.keys),
+                value: Some(Value::Object(members)),
+            }
+        }
+        WorldEvent::StoreUpdateRecord(e) => {
+            let (model, schema) = schema(&e.table)?;
+            let mut values = e.values;
+
+            let mut members = Map::new();
+            for member in members_of(schema).iter().filter(|m| !m.key) {
+                let mut ty = member.ty.clone();
+                ty.deserialize(&mut values)?;
+                members.insert(member.name.clone(), ty_to_json(&ty)?);
+            }
+
+            StoreEvent {
+                event: "StoreUpdateRecord".to_string(),
+                model: model.clone(),
+                entity_id: e.entity_id,
+                keys: None,
+                value: Some(Value::Object(members)),
+            }
+        }
+        WorldEvent::StoreUpdateMember(e) => {
+            let (model, schema) = schema(&e.table)?;
+            let member = members_of(schema)
+                .iter()
+                .find(|m| get_selector_from_name(&m.name).ok() == Some(e.member_selector))
+                .ok_or_else(|| {
+                    anyhow!("Unknown member {:#x} of model {model}", e.member_selector)
+                })?;
+
+            let mut values = e.values;
+            let mut ty = member.ty.clone();
+            ty.deserialize(&mut values)?;
+
+            let mut members = Map::new();
+            members.insert(member.name.clone(), ty_to_json(&ty)?);
+
+            StoreEvent {
+                event: "StoreUpdateMember".to_string(),
+                model: model.clone(),
+                entity_id: e.entity_id,
+                keys: None,
+                value: Some(Value::Object(members)),
+            }
+        }
+        WorldEvent::StoreDelRecord(e) => {
+            let (model, _) = schema(&e.table)?;
+
+            StoreEvent {
+                event: "StoreDelRecord".to_string(),
+                model: model.clone(),
+                entity_id: e.entity_id,
+                keys: None,
+                value: None,
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(store_event))
+}
+
+fn members_of(schema: &Ty) -> &[Member] {
+    match schema {
+        Ty::Struct(s) => &s.children,
+        _ => &[],
+    }
+}
+
+/// Converts a deserialized value into JSON, integers fitting in 64 bits being numbers and the
+/// others hex strings.
+fn ty_to_json(ty: &Ty) -> Result<Value> {
+    let value = match ty {
+        Ty::Primitive(primitive) => {
+            let value = primitive.to_sql_value()?;
+
+            match primitive {
+                Primitive::Bool(_) => Value::Bool(value != "0"),
+                Primitive::U8(_)
+                | Primitive::U16(_)
+                | Primitive::U32(_)
+                | Primitive::USize(_)
+                | Primitive::I8(_)
+                | Primitive::I16(_)
+                | Primitive::I32(_)
+                | Primitive::I64(_) => Value::Number(value.parse::<i64>()?.into()),
+                // stored as a hex string in the database
+                Primitive::U64(v) => {
+                    Value::Number(v.ok_or_else(|| anyhow!("Missing u64 value"))?.into())
+                }
+                _ => Value::String(value),
+            }
+        }
+        Ty::Struct(s) => Value::Object(
+            s.children
+                .iter()
+                .map(|m| Ok((m.name.clone(), ty_to_json(&m.ty)?)))
+                .collect::<Result<_>>()?,
+        ),
+        Ty::Enum(e) => {
+            let option = e
+                .option
+                .and_then(|option| e.options.get(option as usize))
+                .ok_or_else(|| anyhow!("Enum {} has no variant set", e.name))?;
+
+            match &option.ty {
+                Ty::Tuple(items) if items.is_empty() => Value::String(option.name.clone()),
+                ty => {
+                    let mut variant = Map::new();
+                    variant.insert(option.name.clone(), ty_to_json(ty)?);
+                    Value::Object(variant)
+                }
+            }
+        }
+        Ty::Tuple(items) | Ty::Array(items) => {
+            Value::Array(items.iter().map(ty_to_json).collect::<Result<_>>()?)
+        }
+        Ty::ByteArray(bytes) => Value::String(bytes.clone()),
+    };
+
+    Ok(value)
+}
+
 #[cfg(test)]
 mod tests {
     use cainome::parser::tokens::{Array, Composite, CompositeInner, CompositeType};
     use camino::Utf8Path;
+    use dojo_test_utils::migration::copy_spawn_and_move_db;
+    use dojo_world::contracts::abi::world::{Resource, StoreSetRecord, StoreUpdateMember};
     use dojo_world::manifest::WORLD_QUALIFIED_PATH;
-    use starknet::core::types::EmittedEvent;
+    use dojo_world::utils::TransactionWaiter;
+    use katana_runner::{KatanaRunner, KatanaRunnerConfig};
+    use serde_json::json;
+    use starknet::accounts::{Account, Call};
 
     use super::*;
+    use crate::test_utils::setup;
 
     #[test]
     fn extract_events_work_as_expected() {
@@ -607,4 +1313,370 @@ mod tests {
         let actual_output = parse_event(event, &events_map).expect("Failed to parse event");
         assert_eq!(actual_output, expected_output);
     }
+
+    fn position_schemas() -> HashMap<Felt, (String, Ty)> {
+        let u32_member = |name: &str| Member {
+            name: name.to_string(),
+            ty: Ty::Primitive(Primitive::U32(None)),
+            key: false,
+        };
+
+        let schema = Ty::Struct(Struct {
+            name: "Position".to_string(),
+            children: vec![
+                Member {
+                    name: "player".to_string(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                    key: true,
+                },
+                Member {
+                    name: "vec".to_string(),
+                    ty: Ty::Struct(Struct {
+                        name: "Vec2".to_string(),
+                        children: vec![u32_member("x"), u32_member("y")],
+                    }),
+                    key: false,
+                },
+            ],
+        });
+
+        let tag = "dojo_examples-Position".to_string();
+        HashMap::from([(compute_selector_from_tag(&tag), (tag, schema))])
+    }
+
+    #[test]
+    fn decode_store_set_record() {
+        let event = WorldEvent::StoreSetRecord(StoreSetRecord {
+            table: compute_selector_from_tag("dojo_examples-Position"),
+            keys: vec![Felt::ONE],
+            values: vec![Felt::from(3u32), Felt::from(4u32)],
+        });
+
+        let store_event = decode_store_event(event, &position_schemas()).unwrap().unwrap();
+
+        assert_eq!(
+            store_event,
+            StoreEvent {
+                event: "StoreSetRecord".to_string(),
+                model: "dojo_examples-Position".to_string(),
+                entity_id: poseidon_hash_many(&[Felt::ONE]),
+                keys: Some(vec![Felt::ONE]),
+                value: Some(json!({
+                    "player": format!("{:#064x}", Felt::ONE),
+                    "vec": { "x": 3, "y": 4 }
+                })),
+            }
+        );
+
+        let filter = FollowFilter { models: None, keys: Some(vec![Felt::ONE]) };
+        assert!(filter.matches(&store_event));
+
+        let filter = FollowFilter { models: None, keys: Some(vec![Felt::TWO]) };
+        assert!(!filter.matches(&store_event));
+
+        let filter =
+            FollowFilter { models: Some(vec!["dojo_examples-Moves".to_string()]), keys: None };
+        assert!(!filter.matches(&store_event));
+    }
+
+    #[test]
+    fn decode_store_update_member() {
+        let event = WorldEvent::StoreUpdateMember(StoreUpdateMember {
+            table: compute_selector_from_tag("dojo_examples-Position"),
+            entity_id: Felt::TWO,
+            member_selector: get_selector_from_name("vec").unwrap(),
+            values: vec![Felt::from(5u32), Felt::from(6u32)],
+        });
+
+        let store_event = decode_store_event(event, &position_schemas()).unwrap().unwrap();
+
+        assert_eq!(store_event.entity_id, Felt::TWO);
+        assert_eq!(store_event.value, Some(json!({ "vec": { "x": 5, "y": 6 } })));
+    }
+
+    #[test]
+    fn decode_store_event_of_unknown_model() {
+        let event = WorldEvent::StoreSetRecord(StoreSetRecord {
+            table: Felt::from(42u8),
+            keys: vec![],
+            values: vec![],
+        });
+
+        assert!(decode_store_event(event, &position_schemas()).is_err());
+    }
+
+    #[test]
+    fn filter_on_first_keys() {
+        let event = |keys: Option<Vec<Felt>>| StoreEvent {
+            event: "StoreUpdateRecord".to_string(),
+            model: "dojo_examples-Moves".to_string(),
+            entity_id: poseidon_hash_many(&[Felt::ONE, Felt::TWO]),
+            keys,
+            value: None,
+        };
+
+        let first_key = FollowFilter { models: None, keys: Some(vec![Felt::ONE]) };
+        let all_keys = FollowFilter { models: None, keys: Some(vec![Felt::ONE, Felt::TWO]) };
+
+        // the first keys match once the keys of the entity are known
+        assert!(first_key.matches(&event(Some(vec![Felt::ONE, Felt::TWO]))));
+        assert!(all_keys.matches(&event(Some(vec![Felt::ONE, Felt::TWO]))));
+        assert!(!first_key.matches(&event(Some(vec![Felt::TWO, Felt::ONE]))));
+
+        // and only all the keys otherwise
+        assert!(!first_key.matches(&event(None)));
+        assert!(all_keys.matches(&event(None)));
+    }
+
+    #[test]
+    fn decoder_keeps_the_keys_of_the_entities() {
+        let mut decoder =
+            StoreEventDecoder { schemas: position_schemas(), entity_keys: HashMap::new() };
+        let table = compute_selector_from_tag("dojo_examples-Position");
+        let entity_id = poseidon_hash_many(&[Felt::ONE]);
+
+        let update = || {
+            WorldEvent::StoreUpdateMember(StoreUpdateMember {
+                table,
+                entity_id,
+                member_selector: get_selector_from_name("vec").unwrap(),
+                values: vec![Felt::from(5u32), Felt::from(6u32)],
+            })
+        };
+
+        assert_eq!(decoder.decode(update()).unwrap().unwrap().keys, None);
+
+        let set = WorldEvent::StoreSetRecord(StoreSetRecord {
+            table,
+            keys: vec![Felt::ONE],
+            values: vec![Felt::from(3u32), Felt::from(4u32)],
+        });
+        decoder.decode(set).unwrap();
+
+        assert_eq!(decoder.decode(update()).unwrap().unwrap().keys, Some(vec![Felt::ONE]));
+    }
+
+    #[test]
+    fn u64_values_are_numbers() {
+        let value = ty_to_json(&Ty::Primitive(Primitive::U64(Some(u64::MAX)))).unwrap();
+        assert_eq!(value, json!(u64::MAX));
+
+        let value = ty_to_json(&Ty::Primitive(Primitive::U128(Some(1)))).unwrap();
+        assert!(value.is_string());
+    }
+
+    #[test]
+    fn events_are_identified_by_their_position_in_their_transaction() {
+        let event = |transaction_hash: Felt| EmittedEvent {
+            from_address: Felt::ZERO,
+            keys: vec![],
+            data: vec![],
+            block_hash: None,
+            block_number: None,
+            transaction_hash,
+        };
+
+        let ids = identify_events(vec![event(Felt::ONE), event(Felt::TWO), event(Felt::ONE)])
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![(Felt::ONE, 0), (Felt::TWO, 0), (Felt::ONE, 1)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn follow_world_events() {
+        let config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+        let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+        let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+        let project_dir = Utf8Path::new("../../../examples/spawn-and-move").to_path_buf();
+        let manifest = load_deployment_manifest(&project_dir, "dev").unwrap();
+        let mut decoder = StoreEventDecoder {
+            schemas: load_model_schemas(&manifest, &project_dir).unwrap(),
+            entity_keys: HashMap::new(),
+        };
+
+        let mut follower = Follower {
+            provider: sequencer.provider(),
+            world_address: world.address,
+            chunk_size: 10,
+            keys: None,
+            include_pending: true,
+            next_block: sequencer.provider().block_number().await.unwrap() + 1,
+            pending: PendingEvents::default(),
+        };
+        assert!(follower.poll().await.unwrap().is_empty());
+
+        let resource = world.resource(&compute_selector_from_tag("dojo_examples-actions"));
+        let Resource::Contract((_, actions)) = resource.call().await.unwrap() else {
+            panic!("No action contract found in world");
+        };
+        let spawn = Call {
+            to: actions.0,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata: vec![],
+        };
+        let res = world.account.execute_v1(vec![spawn]).send().await.unwrap();
+        TransactionWaiter::new(res.transaction_hash, sequencer.provider()).await.unwrap();
+
+        let events = follower.poll().await.unwrap();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|e| e.transaction_hash == res.transaction_hash));
+
+        // the events are returned once, whether they were seen pending or not
+        assert!(follower.poll().await.unwrap().is_empty());
+
+        let store_events = events
+            .into_iter()
+            .filter_map(|e| WorldEvent::try_from(e).ok())
+            .filter_map(|e| decoder.decode(e).unwrap())
+            .collect::<Vec<_>>();
+
+        let filter = FollowFilter {
+            models: Some(vec!["dojo_examples-Position".to_string()]),
+            keys: Some(vec![world.account.address()]),
+        };
+        assert!(store_events.iter().any(|e| filter.matches(e)));
+    }
+
+    #[test]
+    fn pending_events_are_returned_once() {
+        let mut pending = PendingEvents::default();
+        let id = (Felt::ONE, 0);
+
+        assert!(pending.see(id, 10));
+        assert!(!pending.see(id, 10));
+
+        // still pending while no block is accepted
+        pending.evict(10);
+        assert!(!pending.see(id, 10));
+
+        // accepted in the next block, which doesn't return it again
+        assert!(pending.accept(&id));
+        assert!(!pending.accept(&id));
+    }
+
+    #[test]
+    fn dropped_pending_events_are_evicted() {
+        let mut pending = PendingEvents::default();
+        let dropped = (Felt::ONE, 0);
+        let kept = (Felt::TWO, 0);
+
+        pending.see(dropped, 10);
+        pending.see(kept, 10);
+
+        // a block is accepted without the dropped event, the kept one being still pending
+        pending.evict(11);
+        pending.see(kept, 11);
+
+        assert!(!pending.accept(&dropped));
+        assert!(pending.accept(&kept));
+        assert!(pending.seen.is_empty());
+    }
+
+    #[test]
+    fn model_schema_is_built_from_abi() {
+        let abi: Vec<AbiEntry> = serde_json::from_value(json!([
+            {
+                "type": "interface",
+                "name": "dojo_examples::models::Iposition",
+                "items": [
+                    {
+                        "type": "function",
+                        "name": "ensure_abi",
+                        "inputs": [{ "name": "model", "type": "dojo_examples::models::Position" }],
+                        "outputs": [],
+                        "state_mutability": "view"
+                    }
+                ]
+            },
+            {
+                "type": "struct",
+                "name": "dojo_examples::others::Position",
+                "members": [
+                    { "name": "x", "type": "core::integer::u32" }
+                ]
+            },
+            {
+                "type": "struct",
+                "name": "dojo_examples::models::Position",
+                "members": [
+                    {
+                        "name": "player",
+                        "type": "core::starknet::contract_address::ContractAddress"
+                    },
+                    { "name": "vec", "type": "dojo_examples::models::Vec2" },
+                    { "name": "dir", "type": "dojo_examples::models::Direction" },
+                    {
+                        "name": "path",
+                        "type": "core::array::Array::<(core::integer::u32, core::bool)>"
+                    }
+                ]
+            },
+            {
+                "type": "struct",
+                "name": "dojo_examples::models::Vec2",
+                "members": [
+                    { "name": "x", "type": "core::integer::u32" },
+                    { "name": "y", "type": "core::integer::u32" }
+                ]
+            },
+            {
+                "type": "enum",
+                "name": "dojo_examples::models::Direction",
+                "variants": [
+                    { "name": "Left", "type": "()" },
+                    { "name": "Right", "type": "()" }
+                ]
+            }
+        ]))
+        .unwrap();
+
+        let members = vec![ManifestMember {
+            name: "player".to_string(),
+            ty: "ContractAddress".to_string(),
+            key: true,
+        }];
+
+        let schema = model_schema_from_abi(&abi, "Position", &members).unwrap();
+
+        let u32_ty = || Ty::Primitive(Primitive::U32(None));
+        let member = |name: &str, ty: Ty, key: bool| Member { name: name.to_string(), ty, key };
+        let unit = |name: &str| EnumOption { name: name.to_string(), ty: Ty::Tuple(vec![]) };
+
+        let expected = Ty::Struct(Struct {
+            name: "Position".to_string(),
+            children: vec![
+                member("player", Ty::Primitive(Primitive::ContractAddress(None)), true),
+                member(
+                    "vec",
+                    Ty::Struct(Struct {
+                        name: "Vec2".to_string(),
+                        children: vec![member("x", u32_ty(), false), member("y", u32_ty(), false)],
+                    }),
+                    false,
+                ),
+                member(
+                    "dir",
+                    Ty::Enum(Enum {
+                        name: "Direction".to_string(),
+                        option: None,
+                        options: vec![unit("Left"), unit("Right")],
+                    }),
+                    false,
+                ),
+                member(
+                    "path",
+                    Ty::Array(vec![Ty::Tuple(vec![
+                        u32_ty(),
+                        Ty::Primitive(Primitive::Bool(None)),
+                    ])]),
+                    false,
+                ),
+            ],
+        });
+
+        assert_eq!(schema, expected);
+    }
 }
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use dojo_world::contracts::world::WorldContract;
 use dojo_world::migration::TxnConfig;
-use dojo_world::utils::TransactionExt;
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer};
 use scarb_ui::Ui;
 use starknet::accounts::{Call, ConnectedAccount};
 use starknet::core::types::Felt;
@@ -9,6 +9,14 @@ use starknet::core::utils::get_selector_from_name;
 
 use crate::utils;
 
+/// A call to an entrypoint of a contract, the contract being given by its tag or its address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractCall {
+    pub tag_or_address: String,
+    pub entrypoint: String,
+    pub calldata: Vec<Felt>,
+}
+
 pub async fn execute<A>(
     ui: &Ui,
     tag_or_address: String,
@@ -18,17 +26,29 @@ pub async fn execute<A>(
     txn_config: &TxnConfig,
 ) -> Result<()>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
 {
-    let contract_address = utils::get_contract_address(world, &tag_or_address).await?;
-    let res = world
-        .account
-        .execute_v1(vec![Call {
-            calldata,
-            to: contract_address,
-            selector: get_selector_from_name(&entrypoint)?,
-        }])
-        .send_with_cfg(txn_config)
+    execute_calls(
+        ui,
+        vec![ContractCall { tag_or_address, entrypoint, calldata }],
+        world,
+        txn_config,
+    )
+    .await
+}
+
+/// Executes the calls atomically, in a single multicall transaction.
+pub async fn execute_calls<A>(
+    ui: &Ui,
+    calls: Vec<ContractCall>,
+    world: &WorldContract<A>,
+    txn_config: &TxnConfig,
+) -> Result<()>
+where
+    A: TransactionV3Signer + Sync + Send + 'static,
+{
+    let calls = resolve_calls(world, calls).await?;
+    let res = execute_with_cfg(&world.account, calls, txn_config)
         .await
         .with_context(|| "Failed to send transaction")?;
 
@@ -41,3 +61,23 @@ where
     )
     .await
 }
+
+/// Resolves the contract addresses and the entrypoint selectors of the calls.
+pub(crate) async fn resolve_calls<A>(
+    world: &WorldContract<A>,
+    calls: Vec<ContractCall>,
+) -> Result<Vec<Call>>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let mut resolved = Vec::with_capacity(calls.len());
+    for call in calls {
+        resolved.push(Call {
+            to: utils::get_contract_address(world, &call.tag_or_address).await?,
+            selector: get_selector_from_name(&call.entrypoint)?,
+            calldata: call.calldata,
+        });
+    }
+
+    Ok(resolved)
+}
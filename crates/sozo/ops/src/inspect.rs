@@ -0,0 +1,502 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use dojo_world::contracts::abi::world::Resource;
+use dojo_world::contracts::naming::{
+    compute_bytearray_hash, compute_selector_from_tag, get_namespace_from_tag, get_tag,
+};
+use dojo_world::contracts::world::WorldEvent;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::manifest::{BaseManifest, BASE_DIR, MANIFESTS_DIR};
+use serde::Serialize;
+use starknet::core::types::contract::{AbiEntry, AbiEvent, TypedAbiEvent};
+use starknet::core::types::{
+    BlockId, BlockTag, EmittedEvent, EventFilter, Felt, InvokeTransaction, Transaction,
+};
+use starknet::core::utils::starknet_keccak;
+use starknet::providers::Provider;
+
+const BLOCK_ID: BlockId = BlockId::Tag(BlockTag::Pending);
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+/// Resource of the World itself in the permissions.
+const WORLD_RESOURCE: Felt = Felt::ZERO;
+
+/// State of a resource of the local manifest compared to the World.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceStatus {
+    /// Same class hash locally and on-chain.
+    Synced,
+    /// Class hash changed locally, the next migration upgrades it.
+    Outdated,
+    /// Only in the local manifest, the next migration registers it.
+    NotDeployed,
+    /// Only in the World.
+    RemoteOnly,
+}
+
+impl fmt::Display for ResourceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceStatus::Synced => write!(f, "synced"),
+            ResourceStatus::Outdated => write!(f, "outdated"),
+            ResourceStatus::NotDeployed => write!(f, "not deployed"),
+            ResourceStatus::RemoteOnly => write!(f, "remote only"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceReport {
+    /// Tag of models and contracts, name of namespaces.
+    pub name: String,
+    pub selector: Felt,
+    pub address: Option<Felt>,
+    pub local_class_hash: Option<Felt>,
+    pub remote_class_hash: Option<Felt>,
+    pub status: ResourceStatus,
+    pub owners: Vec<Felt>,
+    pub writers: Vec<Felt>,
+}
+
+/// Event declared by the local contracts.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventReport {
+    /// Full path of the event struct.
+    pub name: String,
+    /// First key of the emitted events.
+    pub selector: Felt,
+    /// Tags of the contracts emitting the event, `world` for the World.
+    pub contracts: Vec<String>,
+}
+
+/// Resources of a World with their permissions.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldReport {
+    pub world: ResourceReport,
+    pub namespaces: Vec<ResourceReport>,
+    pub models: Vec<ResourceReport>,
+    pub contracts: Vec<ResourceReport>,
+    pub events: Vec<EventReport>,
+}
+
+/// State of the World rebuilt from its events.
+#[derive(Debug, Default)]
+struct WorldEvents {
+    namespaces: BTreeSet<String>,
+    models: BTreeSet<String>,
+    contracts: BTreeSet<String>,
+    owners: HashMap<Felt, BTreeSet<Felt>>,
+    writers: HashMap<Felt, BTreeSet<Felt>>,
+}
+
+impl WorldEvents {
+    fn permissions(&self, resources: &[Felt]) -> (Vec<Felt>, Vec<Felt>) {
+        let collect = |permissions: &HashMap<Felt, BTreeSet<Felt>>| {
+            resources
+                .iter()
+                .filter_map(|r| permissions.get(r))
+                .flatten()
+                .copied()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        };
+
+        (collect(&self.owners), collect(&self.writers))
+    }
+}
+
+/// Inspects the World, reporting every namespace, model and contract of the World and of the
+/// local manifest with their class hashes, upgrade status, owners and writers, and the events
+/// declared by the local World and contracts.
+///
+/// The owners and writers of a namespace are owners and writers of its models and contracts, and
+/// are reported with them.
+///
+/// # Arguments
+///
+/// * `provider` - Starknet provider.
+/// * `world_address` - Address of the World.
+/// * `root_dir` - Root directory of the project.
+/// * `profile_name` - Name of the profile of the manifests.
+/// * `to_json` - Prints the report as JSON.
+pub async fn inspect<P>(
+    provider: P,
+    world_address: Felt,
+    root_dir: &Utf8PathBuf,
+    profile_name: &str,
+    to_json: bool,
+) -> Result<WorldReport>
+where
+    P: Provider + Send + Sync,
+{
+    let manifest_dir = root_dir.join(MANIFESTS_DIR).join(profile_name).join(BASE_DIR);
+    let local_manifest = BaseManifest::load_from_path(&manifest_dir)
+        .with_context(|| format!("Failed to load the manifests from {manifest_dir}"))?;
+
+    let events = fetch_world_events(&provider, world_address).await?;
+
+    let world_reader = WorldContractReader::new(world_address, &provider).with_block(BLOCK_ID);
+
+    let local_class_hash = Some(local_manifest.world.inner.class_hash);
+    let remote_class_hash = Some(
+        provider
+            .get_class_hash_at(BLOCK_ID, world_address)
+            .await
+            .with_context(|| format!("World not found at {world_address:#x}"))?,
+    );
+    let (owners, writers) = events.permissions(&[WORLD_RESOURCE]);
+
+    let world = ResourceReport {
+        name: "world".to_string(),
+        selector: WORLD_RESOURCE,
+        address: Some(world_address),
+        local_class_hash,
+        remote_class_hash,
+        status: class_status(local_class_hash, remote_class_hash),
+        owners,
+        writers,
+    };
+
+    let local_models = local_manifest
+        .models
+        .iter()
+        .map(|m| (m.inner.tag.clone(), m.inner.class_hash))
+        .collect::<BTreeMap<_, _>>();
+    let local_contracts = local_manifest
+        .contracts
+        .iter()
+        .map(|c| (c.inner.tag.clone(), c.inner.class_hash))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut namespaces = vec![];
+    let local_namespaces = local_models
+        .keys()
+        .chain(local_contracts.keys())
+        .map(|tag| get_namespace_from_tag(tag))
+        .collect::<BTreeSet<_>>();
+
+    for namespace in local_namespaces.union(&events.namespaces) {
+        let selector = compute_bytearray_hash(namespace);
+        let (owners, writers) = events.permissions(&[selector]);
+
+        let status =
+            match (local_namespaces.contains(namespace), events.namespaces.contains(namespace)) {
+                (true, true) => ResourceStatus::Synced,
+                (true, false) => ResourceStatus::NotDeployed,
+                _ => ResourceStatus::RemoteOnly,
+            };
+
+        namespaces.push(ResourceReport {
+            name: namespace.clone(),
+            selector,
+            address: None,
+            local_class_hash: None,
+            remote_class_hash: None,
+            status,
+            owners,
+            writers,
+        });
+    }
+
+    let mut models = vec![];
+    for tag in local_models.keys().cloned().collect::<BTreeSet<_>>().union(&events.models) {
+        let selector = compute_selector_from_tag(tag);
+        let (address, remote_class_hash) = match world_reader.resource(&selector).call().await? {
+            Resource::Model((class_hash, address)) => {
+                (Some(address.into()), Some(class_hash.into()))
+            }
+            _ => (None, None),
+        };
+        let local_class_hash = local_models.get(tag).copied();
+        let namespace = compute_bytearray_hash(&get_namespace_from_tag(tag));
+        let (owners, writers) = events.permissions(&[selector, namespace]);
+
+        models.push(ResourceReport {
+            name: tag.clone(),
+            selector,
+            address,
+            local_class_hash,
+            remote_class_hash,
+            status: class_status(local_class_hash, remote_class_hash),
+            owners,
+            writers,
+        });
+    }
+
+    let mut contracts = vec![];
+    for tag in local_contracts.keys().cloned().collect::<BTreeSet<_>>().union(&events.contracts) {
+        let selector = compute_selector_from_tag(tag);
+        // The class hash of the resource is the one of the deployment, not updated by upgrades.
+        let (address, remote_class_hash) = match world_reader.resource(&selector).call().await? {
+            Resource::Contract((_, address)) => {
+                let address = address.into();
+                (Some(address), Some(provider.get_class_hash_at(BLOCK_ID, address).await?))
+            }
+            _ => (None, None),
+        };
+
+        // Permissions on contracts are granted either on their selector or their address.
+        let namespace = compute_bytearray_hash(&get_namespace_from_tag(tag));
+        let resources =
+            [Some(selector), address, Some(namespace)].into_iter().flatten().collect::<Vec<_>>();
+        let local_class_hash = local_contracts.get(tag).copied();
+        let (owners, writers) = events.permissions(&resources);
+
+        contracts.push(ResourceReport {
+            name: tag.clone(),
+            selector,
+            address,
+            local_class_hash,
+            remote_class_hash,
+            status: class_status(local_class_hash, remote_class_hash),
+            owners,
+            writers,
+        });
+    }
+
+    let events = local_events(&local_manifest, root_dir)?;
+
+    let report = WorldReport { world, namespaces, models, contracts, events };
+
+    if to_json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    Ok(report)
+}
+
+fn class_status(local_class_hash: Option<Felt>, remote_class_hash: Option<Felt>) -> ResourceStatus {
+    match (local_class_hash, remote_class_hash) {
+        (Some(local), Some(remote)) if local == remote => ResourceStatus::Synced,
+        (Some(_), Some(_)) => ResourceStatus::Outdated,
+        (Some(_), None) => ResourceStatus::NotDeployed,
+        (None, _) => ResourceStatus::RemoteOnly,
+    }
+}
+
+/// Collects the events declared in the ABI of the local World and contracts.
+fn local_events(local_manifest: &BaseManifest, root_dir: &Utf8PathBuf) -> Result<Vec<EventReport>> {
+    let mut events: BTreeMap<String, EventReport> = BTreeMap::new();
+
+    let emitters = [("world".to_string(), &local_manifest.world.inner.abi)]
+        .into_iter()
+        .chain(local_manifest.contracts.iter().map(|c| (c.inner.tag.clone(), &c.inner.abi)));
+
+    for (emitter, abi) in emitters {
+        let Some(abi) = abi else {
+            continue;
+        };
+
+        let abi = abi
+            .load_abi_string(root_dir)
+            .with_context(|| format!("Failed to load the ABI of {emitter}"))?;
+        let abi: Vec<AbiEntry> = serde_json::from_str(&abi)?;
+
+        for entry in abi {
+            // The enums of events group the events of a contract, and aren't emitted themselves.
+            let AbiEntry::Event(AbiEvent::Typed(TypedAbiEvent::Struct(event))) = entry else {
+                continue;
+            };
+
+            let short_name = event.name.rsplit("::").next().unwrap_or(&event.name);
+            let report = events.entry(event.name.clone()).or_insert_with(|| EventReport {
+                name: event.name.clone(),
+                selector: starknet_keccak(short_name.as_bytes()),
+                contracts: vec![],
+            });
+            report.contracts.push(emitter.clone());
+        }
+    }
+
+    Ok(events.into_values().collect())
+}
+
+/// Rebuilds the resources and the permissions of the World from its events.
+///
+/// The owner of a resource at its registration isn't part of the registration event, it's the
+/// sender of the transaction which registered it.
+async fn fetch_world_events<P>(provider: P, world_address: Felt) -> Result<WorldEvents>
+where
+    P: Provider + Send + Sync,
+{
+    let keys = [
+        "WorldSpawned",
+        "NamespaceRegistered",
+        "ModelRegistered",
+        "ContractDeployed",
+        "OwnerUpdated",
+        "WriterUpdated",
+    ]
+    .iter()
+    .map(|name| starknet_keccak(name.as_bytes()))
+    .collect();
+
+    let filter = EventFilter {
+        from_block: None,
+        to_block: None,
+        address: Some(world_address),
+        keys: Some(vec![keys]),
+    };
+
+    let mut emitted_events: Vec<EmittedEvent> = vec![];
+    let mut continuation_token = None;
+
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+            .await
+            .with_context(|| "Failed to fetch the World events")?;
+
+        emitted_events.extend(page.events);
+        continuation_token = page.continuation_token;
+
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    let mut events = WorldEvents::default();
+    let mut senders: HashMap<Felt, Option<Felt>> = HashMap::new();
+
+    for emitted_event in emitted_events {
+        let transaction_hash = emitted_event.transaction_hash;
+
+        // Events emitted by older versions of the World can't be parsed and are skipped.
+        let Ok(event) = WorldEvent::try_from(emitted_event) else {
+            continue;
+        };
+
+        let registered = match event {
+            WorldEvent::WorldSpawned(e) => {
+                let creator = e.creator.into();
+                events.owners.entry(WORLD_RESOURCE).or_default().insert(creator);
+                events
+                    .owners
+                    .entry(compute_bytearray_hash("__DOJO__"))
+                    .or_default()
+                    .insert(creator);
+                None
+            }
+            WorldEvent::NamespaceRegistered(e) => {
+                events.namespaces.insert(e.namespace.to_string()?);
+                Some(e.hash)
+            }
+            WorldEvent::ModelRegistered(e) => {
+                let tag = get_tag(&e.namespace.to_string()?, &e.name.to_string()?);
+                let selector = compute_selector_from_tag(&tag);
+                events.models.insert(tag);
+                Some(selector)
+            }
+            WorldEvent::ContractDeployed(e) => {
+                let tag = get_tag(&e.namespace.to_string()?, &e.name.to_string()?);
+                let selector = compute_selector_from_tag(&tag);
+                events.contracts.insert(tag);
+                Some(selector)
+            }
+            WorldEvent::OwnerUpdated(e) => {
+                let owners = events.owners.entry(e.resource).or_default();
+                if e.value {
+                    owners.insert(e.address.into());
+                } else {
+                    owners.remove(&e.address.into());
+                }
+                None
+            }
+            WorldEvent::WriterUpdated(e) => {
+                let writers = events.writers.entry(e.resource).or_default();
+                if e.value {
+                    writers.insert(e.contract.into());
+                } else {
+                    writers.remove(&e.contract.into());
+                }
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(resource) = registered {
+            if !senders.contains_key(&transaction_hash) {
+                let transaction = provider.get_transaction_by_hash(transaction_hash).await?;
+                senders.insert(transaction_hash, transaction_sender(&transaction));
+            }
+
+            if let Some(sender) = senders[&transaction_hash] {
+                events.owners.entry(resource).or_default().insert(sender);
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+fn transaction_sender(transaction: &Transaction) -> Option<Felt> {
+    match transaction {
+        Transaction::Invoke(InvokeTransaction::V1(tx)) => Some(tx.sender_address),
+        Transaction::Invoke(InvokeTransaction::V3(tx)) => Some(tx.sender_address),
+        _ => None,
+    }
+}
+
+fn print_report(report: &WorldReport) {
+    print_resource(&report.world);
+
+    for (title, resources) in [
+        ("Namespaces", &report.namespaces),
+        ("Models", &report.models),
+        ("Contracts", &report.contracts),
+    ] {
+        println!("\n{title}");
+
+        if resources.is_empty() {
+            println!("    none");
+        }
+
+        for resource in resources {
+            print_resource(resource);
+        }
+    }
+
+    println!("\nEvents");
+
+    if report.events.is_empty() {
+        println!("    none");
+    }
+
+    for event in &report.events {
+        println!("    {}", event.name);
+        println!("        selector    : {:#x}", event.selector);
+        println!("        emitted by  : {}", event.contracts.join(", "));
+    }
+}
+
+fn print_resource(resource: &ResourceReport) {
+    let format_hash = |hash: Option<Felt>| hash.map_or("-".to_string(), |h| format!("{h:#x}"));
+    let format_addresses = |addresses: &[Felt]| {
+        if addresses.is_empty() {
+            "-".to_string()
+        } else {
+            addresses.iter().map(|a| format!("{a:#x}")).collect::<Vec<_>>().join(", ")
+        }
+    };
+
+    println!("    {} ({})", resource.name, resource.status);
+
+    if let Some(address) = resource.address {
+        println!("        address     : {address:#x}");
+    }
+
+    if resource.local_class_hash.is_some() || resource.remote_class_hash.is_some() {
+        println!("        local class : {}", format_hash(resource.local_class_hash));
+        println!("        remote class: {}", format_hash(resource.remote_class_hash));
+    }
+
+    println!("        owners      : {}", format_addresses(&resource.owners));
+    println!("        writers     : {}", format_addresses(&resource.writers));
+}
@@ -3,10 +3,12 @@ pub mod auth;
 pub mod call;
 pub mod events;
 pub mod execute;
+pub mod inspect;
 pub mod keystore;
 pub mod migration;
 pub mod model;
 pub mod register;
+pub mod run;
 pub mod statistics;
 pub mod utils;
 
@@ -1,11 +1,13 @@
 use anyhow::Result;
 use dojo_world::contracts::WorldContract;
 use dojo_world::migration::TxnConfig;
+use dojo_world::utils::TransactionV3Signer;
 use scarb::core::Workspace;
-use starknet::accounts::ConnectedAccount;
+use starknet::core::types::Felt;
 
 use crate::auth::{grant_writer, revoke_writer, ResourceWriter};
 
+/// Grants and revokes the writers, returning the hashes of the grant and revoke transactions.
 pub async fn auto_authorize<A>(
     ws: &Workspace<'_>,
     world: &WorldContract<A>,
@@ -13,15 +15,15 @@ pub async fn auto_authorize<A>(
     default_namespace: &str,
     grant: &[ResourceWriter],
     revoke: &[ResourceWriter],
-) -> Result<()>
+) -> Result<(Option<Felt>, Option<Felt>)>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
     A::SignError: 'static,
 {
     let ui = ws.config().ui();
 
-    grant_writer(&ui, world, grant, *txn_config, default_namespace).await?;
-    revoke_writer(&ui, world, revoke, *txn_config, default_namespace).await?;
+    let grant_tx = grant_writer(&ui, world, grant, *txn_config, default_namespace).await?;
+    let revoke_tx = revoke_writer(&ui, world, revoke, *txn_config, default_namespace).await?;
 
-    Ok(())
+    Ok((grant_tx, revoke_tx))
 }
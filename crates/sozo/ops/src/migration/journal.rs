@@ -0,0 +1,319 @@
+use std::fs;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use dojo_world::contracts::abi::world::Resource;
+use dojo_world::contracts::naming::{
+    compute_bytearray_hash, compute_selector_from_tag, is_valid_tag,
+};
+use dojo_world::contracts::WorldContract;
+use dojo_world::migration::world::WorldDiff;
+use dojo_world::utils::execution_status_from_receipt;
+use itertools::Itertools;
+use scarb_ui::Ui;
+use serde::{Deserialize, Serialize};
+use starknet::accounts::{Account, ConnectedAccount};
+use starknet::core::types::{BlockId, BlockTag, ExecutionResult, Felt};
+use starknet::providers::Provider;
+
+use crate::auth::{get_resource_selector, ResourceWriter};
+use crate::utils;
+
+/// Name of the journal file, written next to the manifests of the profile.
+pub const JOURNAL_FILE_NAME: &str = "migration_journal.json";
+
+/// A step of the migration that sends a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStep {
+    Declare,
+    Deploy,
+    Register,
+    Upgrade,
+    Grant,
+    Revoke,
+    Init,
+    Metadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub step: MigrationStep,
+    /// What the step applied to: the tag of a resource, a namespace, `base` or `world` for the
+    /// core contracts and `resource,tag` for the permissions.
+    pub target: String,
+    pub transaction_hash: Felt,
+}
+
+/// Journal of the steps completed by a migration.
+///
+/// Every step is written to disk as soon as its transaction is accepted, so a migration
+/// interrupted halfway can be resumed from its first unfinished step instead of replaying the
+/// non-idempotent ones (permissions, `init` calls). The journal is removed once the migration
+/// completes.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MigrationJournal {
+    pub world_address: Felt,
+    pub entries: Vec<JournalEntry>,
+    #[serde(skip)]
+    path: Option<Utf8PathBuf>,
+}
+
+impl MigrationJournal {
+    /// Creates a journal kept in memory only.
+    pub fn new(world_address: Felt) -> Self {
+        Self { world_address, entries: vec![], path: None }
+    }
+
+    /// Loads the journal of the migration of `world_address` from `manifest_dir`.
+    ///
+    /// A journal left by the migration of another world is discarded.
+    pub fn load(manifest_dir: &Utf8Path, world_address: Felt) -> Result<Self> {
+        let path = manifest_dir.join(JOURNAL_FILE_NAME);
+
+        let mut journal = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read migration journal at {path}"))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse migration journal at {path}"))?
+        } else {
+            Self::new(world_address)
+        };
+
+        if journal.world_address != world_address {
+            journal = Self::new(world_address);
+        }
+
+        journal.path = Some(path);
+        Ok(journal)
+    }
+
+    pub fn path(&self) -> Option<&Utf8PathBuf> {
+        self.path.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn is_completed(&self, step: MigrationStep, target: &str) -> bool {
+        self.entries.iter().any(|e| e.step == step && e.target == target)
+    }
+
+    /// Returns the entries of the given step, in the order they were completed.
+    pub fn completed(&self, step: MigrationStep) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.iter().filter(move |e| e.step == step)
+    }
+
+    /// Records a completed step and persists the journal.
+    pub fn record(
+        &mut self,
+        step: MigrationStep,
+        target: impl Into<String>,
+        transaction_hash: Felt,
+    ) -> Result<()> {
+        let target = target.into();
+        if !self.is_completed(step, &target) {
+            self.entries.push(JournalEntry { step, target, transaction_hash });
+        }
+
+        self.save()
+    }
+
+    /// Forgets all the recorded steps, removing the journal from disk.
+    pub fn clear(&mut self) -> Result<()> {
+        self.entries.clear();
+
+        if let Some(path) = &self.path {
+            if path.exists() {
+                fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove migration journal at {path}"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the recorded steps against the chain state: their transactions must have been
+    /// successfully executed, the classes declared, the contracts deployed with their local class
+    /// and the permissions granted or revoked.
+    pub async fn verify<A>(
+        &self,
+        ui: &Ui,
+        world: &WorldContract<A>,
+        diff: &WorldDiff,
+        default_namespace: &str,
+    ) -> Result<()>
+    where
+        A: ConnectedAccount + Sync + Send,
+        <A as Account>::SignError: 'static,
+    {
+        let provider = world.account.provider();
+
+        for transaction_hash in self.entries.iter().map(|e| e.transaction_hash).unique() {
+            let receipt =
+                provider.get_transaction_receipt(transaction_hash).await.map_err(|e| {
+                    anyhow!("Transaction {transaction_hash:#x} not found on chain: {e}")
+                })?;
+
+            if let ExecutionResult::Reverted { reason } =
+                execution_status_from_receipt(&receipt.receipt)
+            {
+                bail!("Transaction {transaction_hash:#x} has been reverted: {reason}");
+            }
+        }
+
+        for JournalEntry { step, target, .. } in &self.entries {
+            match step {
+                MigrationStep::Declare => {
+                    let class_hash = local_class_hash(diff, target)?;
+                    provider.get_class(BlockId::Tag(BlockTag::Pending), class_hash).await.map_err(
+                        |_| anyhow!("Class {class_hash:#x} of {target} is not declared"),
+                    )?;
+                }
+                MigrationStep::Deploy | MigrationStep::Upgrade => {
+                    let contract_address = if target == "world" {
+                        self.world_address
+                    } else {
+                        match world.resource(&compute_selector_from_tag(target)).call().await? {
+                            Resource::Contract((_, address)) => address.0,
+                            _ => bail!("Contract {target} is not registered in the world"),
+                        }
+                    };
+
+                    let class_hash = provider
+                        .get_class_hash_at(BlockId::Tag(BlockTag::Pending), contract_address)
+                        .await
+                        .map_err(|_| anyhow!("Contract {target} is not deployed"))?;
+
+                    let local_class_hash = local_class_hash(diff, target)?;
+                    if class_hash != local_class_hash {
+                        bail!(
+                            "Contract {target} has class {class_hash:#x} instead of \
+                             {local_class_hash:#x}"
+                        );
+                    }
+                }
+                MigrationStep::Register => {
+                    let (selector, name) = if is_valid_tag(target) {
+                        (compute_selector_from_tag(target), "Model")
+                    } else {
+                        (compute_bytearray_hash(target), "Namespace")
+                    };
+
+                    match world.resource(&selector).call().await? {
+                        Resource::Model(_) | Resource::Namespace => {}
+                        _ => bail!("{name} {target} is not registered in the world"),
+                    }
+                }
+                MigrationStep::Grant | MigrationStep::Revoke => {
+                    let writer = ResourceWriter::from_str(target)?;
+                    let resource =
+                        get_resource_selector(ui, world, &writer.resource, default_namespace)
+                            .await?;
+                    let contract_address =
+                        utils::get_contract_address(world, &writer.tag_or_address).await?;
+
+                    let is_writer =
+                        world.is_writer(&resource, &contract_address.into()).call().await?;
+                    if is_writer != (*step == MigrationStep::Grant) {
+                        bail!(
+                            "Writer permission of {} on {} is not {}",
+                            writer.tag_or_address,
+                            writer.resource,
+                            if is_writer { "revoked" } else { "granted" }
+                        );
+                    }
+                }
+                // Their effects can't be read back from the world, the successful execution of
+                // their transactions is all there is to check.
+                MigrationStep::Init | MigrationStep::Metadata => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write migration journal at {path}"))
+    }
+}
+
+/// The class hash of a resource of the local world, `base` and `world` being the core contracts.
+fn local_class_hash(diff: &WorldDiff, target: &str) -> Result<Felt> {
+    let class_hash =
+        match target {
+            "base" => Some(diff.base.local_class_hash),
+            "world" => Some(diff.world.local_class_hash),
+            tag => diff.models.iter().find(|m| m.tag == tag).map(|m| m.local_class_hash).or_else(
+                || diff.contracts.iter().find(|c| c.tag == tag).map(|c| c.local_class_hash),
+            ),
+        };
+
+    class_hash.ok_or_else(|| anyhow!("{target} is not a resource of the local world anymore"))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn record_persists_steps() {
+        let dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let mut journal = MigrationJournal::load(dir, felt!("0x1")).unwrap();
+        assert!(journal.is_empty());
+
+        journal.record(MigrationStep::Declare, "ns-position", felt!("0xa")).unwrap();
+        journal.record(MigrationStep::Register, "ns-position", felt!("0xb")).unwrap();
+        journal.record(MigrationStep::Declare, "ns-position", felt!("0xc")).unwrap();
+
+        let journal = MigrationJournal::load(dir, felt!("0x1")).unwrap();
+        assert_eq!(journal.entries.len(), 2);
+        assert!(journal.is_completed(MigrationStep::Declare, "ns-position"));
+        assert!(journal.is_completed(MigrationStep::Register, "ns-position"));
+        assert!(!journal.is_completed(MigrationStep::Deploy, "ns-position"));
+        assert_eq!(
+            journal.completed(MigrationStep::Declare).next().unwrap().transaction_hash,
+            felt!("0xa")
+        );
+    }
+
+    #[test]
+    fn load_discards_journal_of_other_world() {
+        let dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let mut journal = MigrationJournal::load(dir, felt!("0x1")).unwrap();
+        journal.record(MigrationStep::Deploy, "world", felt!("0xa")).unwrap();
+
+        let journal = MigrationJournal::load(dir, felt!("0x2")).unwrap();
+        assert!(journal.is_empty());
+        assert_eq!(journal.world_address, felt!("0x2"));
+    }
+
+    #[test]
+    fn clear_removes_journal() {
+        let dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let mut journal = MigrationJournal::load(dir, felt!("0x1")).unwrap();
+        journal.record(MigrationStep::Init, "ns-actions", felt!("0xa")).unwrap();
+        assert!(dir.join(JOURNAL_FILE_NAME).exists());
+
+        journal.clear().unwrap();
+        assert!(journal.is_empty());
+        assert!(!dir.join(JOURNAL_FILE_NAME).exists());
+    }
+}
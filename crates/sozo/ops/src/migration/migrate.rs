@@ -6,10 +6,11 @@ use anyhow::{anyhow, bail, Context, Result};
 use cainome::cairo_serde::ByteArray;
 use camino::Utf8PathBuf;
 use dojo_world::contracts::abi::world;
+use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::naming::{
     self, compute_selector_from_tag, get_name_from_tag, get_namespace_from_tag,
 };
-use dojo_world::contracts::{cairo_utils, WorldContract};
+use dojo_world::contracts::{cairo_utils, WorldContract, WorldContractReader};
 use dojo_world::manifest::{
     AbiFormat, BaseManifest, Class, DeploymentManifest, DojoContract, DojoModel, Manifest,
     ManifestMethods, WorldContract as ManifestWorldContract, WorldMetadata, ABIS_DIR, BASE_DIR,
@@ -17,13 +18,14 @@ use dojo_world::manifest::{
 };
 use dojo_world::metadata::{dojo_metadata_from_workspace, ResourceMetadata};
 use dojo_world::migration::class::ClassMigration;
+use dojo_world::migration::compatibility::{ModelSchema, SchemaChange};
 use dojo_world::migration::contract::ContractMigration;
 use dojo_world::migration::strategy::{generate_salt, prepare_for_migration, MigrationStrategy};
 use dojo_world::migration::world::WorldDiff;
 use dojo_world::migration::{
     Declarable, Deployable, MigrationError, RegisterOutput, TxnConfig, Upgradable,
 };
-use dojo_world::utils::{TransactionExt, TransactionWaiter};
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer, TransactionWaiter};
 use futures::future;
 use itertools::Itertools;
 use scarb::core::Workspace;
@@ -39,6 +41,7 @@ use starknet::providers::{AnyProvider, Provider, ProviderError};
 use starknet::signers::LocalWallet;
 use tokio::fs;
 
+use super::journal::{MigrationJournal, MigrationStep};
 use super::ui::{bold_message, italic_message, MigrationUi};
 use super::utils::generate_resource_map;
 use super::{
@@ -51,14 +54,17 @@ pub fn prepare_migration(
     diff: WorldDiff,
     name: &str,
     world_address: Option<Felt>,
+    remote_models: &HashMap<String, ModelSchema>,
+    force: bool,
     ui: &Ui,
 ) -> Result<MigrationStrategy> {
     ui.print_step(3, "📦", "Preparing for migration...");
 
     let name = cairo_short_string_to_felt(name).with_context(|| "Failed to parse World name.")?;
 
-    let migration = prepare_for_migration(world_address, name, target_dir, diff)
-        .with_context(|| "Problem preparing for migration.")?;
+    let migration =
+        prepare_for_migration(world_address, name, target_dir, diff, remote_models, force)
+            .with_context(|| "Problem preparing for migration.")?;
 
     let info = migration.info();
 
@@ -69,18 +75,99 @@ pub fn prepare_migration(
         info.update
     ));
 
+    print_models_compatibility(&migration, ui);
+
     Ok(migration)
 }
 
+/// Reads the schema and the layout of the models registered in the World which are upgraded by
+/// the migration, to check that their new class keeps the storage of their entities readable.
+pub async fn remote_model_schemas<P>(
+    provider: P,
+    world_address: Option<Felt>,
+    diff: &WorldDiff,
+) -> Result<HashMap<String, ModelSchema>>
+where
+    P: Provider + Sync + Send,
+{
+    let Some(world_address) = world_address else {
+        return Ok(HashMap::new());
+    };
+
+    let world = WorldContractReader::new(world_address, provider);
+    let mut schemas = HashMap::new();
+
+    for model in &diff.models {
+        if !model.remote_class_hash.is_some_and(|r| r != model.local_class_hash) {
+            continue;
+        }
+
+        let tag = &model.tag;
+        let reader = world
+            .model_reader_with_tag(tag)
+            .await
+            .with_context(|| format!("Failed to read model {tag} from the World."))?;
+
+        let ty =
+            reader.schema().await.with_context(|| format!("Failed to read schema of {tag}."))?;
+        let layout =
+            reader.layout().await.with_context(|| format!("Failed to read layout of {tag}."))?;
+
+        schemas.insert(tag.clone(), ModelSchema { ty, layout });
+    }
+
+    Ok(schemas)
+}
+
+fn print_models_compatibility(strategy: &MigrationStrategy, ui: &Ui) {
+    let upgraded_models = strategy
+        .models
+        .iter()
+        .filter(|m| m.diff.remote_class_hash.is_some_and(|r| r != m.diff.local_class_hash))
+        .collect::<Vec<_>>();
+
+    if upgraded_models.is_empty() || strategy.world.is_some() {
+        return;
+    }
+
+    ui.print_header("# Models compatibility");
+
+    let mut breaking_models = vec![];
+
+    for model in upgraded_models {
+        ui.print(italic_message(&model.diff.tag).to_string());
+        if model.schema_changes.is_empty() {
+            ui.print_sub("Storage unchanged");
+        }
+
+        for change in &model.schema_changes {
+            ui.print_sub(change.to_string());
+        }
+
+        if model.schema_changes.iter().any(SchemaChange::is_breaking) {
+            breaking_models.push(model.diff.tag.clone());
+        }
+    }
+
+    // without `--force`, the strategy isn't prepared for breaking changes.
+    if !breaking_models.is_empty() {
+        ui.warn(format!(
+            "Upgrading models {} with breaking storage changes as `--force` is set.",
+            breaking_models.join(", ")
+        ));
+    }
+}
+
 pub async fn apply_diff<A>(
     ws: &Workspace<'_>,
     account: A,
     txn_config: TxnConfig,
     strategy: &MigrationStrategy,
     declarers: &[SingleOwnerAccount<AnyProvider, LocalWallet>],
+    journal: &mut MigrationJournal,
 ) -> Result<MigrationOutput>
 where
-    A: ConnectedAccount + Sync + Send,
+    A: TransactionV3Signer + Sync + Send,
     <A as ConnectedAccount>::Provider: Send,
     A::SignError: 'static,
 {
@@ -89,10 +176,11 @@ where
     ui.print_step(4, "🛠", "Migrating...");
     ui.print(" ");
 
-    let migration_output = execute_strategy(ws, strategy, account, txn_config, declarers)
-        .await
-        .map_err(|e| anyhow!(e))
-        .with_context(|| "Problem trying to migrate.")?;
+    let migration_output =
+        execute_journaled_strategy(ws, strategy, account, txn_config, declarers, journal)
+            .await
+            .map_err(|e| anyhow!(e))
+            .with_context(|| "Problem trying to migrate.")?;
 
     if migration_output.full {
         if let Some(block_number) = migration_output.world_block_number {
@@ -125,7 +213,25 @@ pub async fn execute_strategy<A>(
     declarers: &[SingleOwnerAccount<AnyProvider, LocalWallet>],
 ) -> Result<MigrationOutput>
 where
-    A: ConnectedAccount + Sync + Send,
+    A: TransactionV3Signer + Sync + Send,
+    A::Provider: Send,
+    A::SignError: 'static,
+{
+    let mut journal = MigrationJournal::new(strategy.world_address);
+    execute_journaled_strategy(ws, strategy, migrator, txn_config, declarers, &mut journal).await
+}
+
+/// Executes the strategy, recording every completed step in the journal.
+pub async fn execute_journaled_strategy<A>(
+    ws: &Workspace<'_>,
+    strategy: &MigrationStrategy,
+    migrator: A,
+    txn_config: TxnConfig,
+    declarers: &[SingleOwnerAccount<AnyProvider, LocalWallet>],
+    journal: &mut MigrationJournal,
+) -> Result<MigrationOutput>
+where
+    A: TransactionV3Signer + Sync + Send,
     A::Provider: Send,
     A::SignError: 'static,
 {
@@ -140,6 +246,7 @@ where
             match base.declare(&migrator, &txn_config).await {
                 Ok(res) => {
                     ui.print_sub(format!("Class Hash: {:#x}", res.class_hash));
+                    journal.record(MigrationStep::Declare, "base", res.transaction_hash)?;
                 }
                 Err(MigrationError::ClassAlreadyDeclared) => {
                     ui.print_sub(format!("Already declared: {:#x}", base.diff.local_class_hash));
@@ -163,7 +270,7 @@ where
             // If a migration is pending for the world, we upgrade only if the remote world
             // already exists.
             if world.diff.remote_class_hash.is_some() {
-                let _deploy_result = upgrade_contract(
+                let upgrade_result = upgrade_contract(
                     world,
                     "world",
                     world.diff.original_class_hash,
@@ -178,6 +285,12 @@ where
                     anyhow!("Failed to upgrade world: {e}")
                 })?;
 
+                let ContractUpgradeOutput::Output(upgrade_result) = upgrade_result;
+                if let Some(declare) = &upgrade_result.declare {
+                    journal.record(MigrationStep::Declare, "world", declare.transaction_hash)?;
+                }
+                journal.record(MigrationStep::Upgrade, "world", upgrade_result.transaction_hash)?;
+
                 ui.print_sub(format!(
                     "Upgraded Contract at address: {:#x}",
                     world.contract_address
@@ -194,6 +307,19 @@ where
 
                 (world_tx_hash, world_block_number) =
                     if let ContractDeploymentOutput::Output(deploy_result) = deploy_result {
+                        if let Some(declare) = &deploy_result.declare {
+                            journal.record(
+                                MigrationStep::Declare,
+                                "world",
+                                declare.transaction_hash,
+                            )?;
+                        }
+                        journal.record(
+                            MigrationStep::Deploy,
+                            "world",
+                            deploy_result.transaction_hash,
+                        )?;
+
                         (Some(deploy_result.transaction_hash), deploy_result.block_number)
                     } else {
                         (None, None)
@@ -221,14 +347,26 @@ where
     namespaces.extend(
         strategy.contracts.iter().map(|c| get_namespace_from_tag(&c.diff.tag)).collect::<Vec<_>>(),
     );
-    namespaces = namespaces.into_iter().unique().collect::<Vec<_>>();
+    // namespaces registered by an interrupted migration are not registered again.
+    namespaces = namespaces
+        .into_iter()
+        .unique()
+        .filter(|ns| !journal.is_completed(MigrationStep::Register, ns))
+        .collect::<Vec<_>>();
 
-    register_namespaces(&namespaces, world_address, &migrator, &ui, &txn_config).await?;
+    register_namespaces(&namespaces, world_address, &migrator, &ui, &txn_config, journal).await?;
 
     // TODO: rework this part when more time.
     if declarers.is_empty() {
-        match register_dojo_models(&strategy.models, world_address, &migrator, &ui, &txn_config)
-            .await
+        match register_dojo_models(
+            &strategy.models,
+            world_address,
+            &migrator,
+            &ui,
+            &txn_config,
+            journal,
+        )
+        .await
         {
             Ok(output) => {
                 migration_output.models = output.registered_models;
@@ -245,6 +383,7 @@ where
             migrator,
             &ui,
             &txn_config,
+            journal,
         )
         .await
         {
@@ -264,6 +403,7 @@ where
             &ui,
             &txn_config,
             declarers,
+            journal,
         )
         .await
         {
@@ -283,6 +423,7 @@ where
             &ui,
             &txn_config,
             declarers,
+            journal,
         )
         .await
         {
@@ -348,14 +489,17 @@ fn create_resource_metadata(resource_id: Felt, hash: String) -> Result<world::Re
 /// * `ws` - the workspace
 /// * `migrator` - the account used to migrate
 /// * `migration_output` - the output after having applied the migration plan.
+///
+/// # Returns
+/// The hash of the transaction registering the metadata.
 pub async fn upload_metadata<A>(
     ws: &Workspace<'_>,
     migrator: A,
     migration_output: MigrationOutput,
     txn_config: TxnConfig,
-) -> Result<()>
+) -> Result<Felt>
 where
-    A: ConnectedAccount + Sync + Send,
+    A: TransactionV3Signer + Sync + Send,
     <A as ConnectedAccount>::Provider: Send,
 {
     let ui = ws.config().ui();
@@ -425,7 +569,7 @@ where
     let calls = resources.iter().map(|r| world.set_metadata_getcall(r)).collect::<Vec<_>>();
 
     let InvokeTransactionResult { transaction_hash } =
-        migrator.execute_v1(calls).send_with_cfg(&txn_config).await.map_err(|e| {
+        execute_with_cfg(&migrator, calls, &txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to register metadata into the resource registry: {e}")
         })?;
@@ -440,7 +584,7 @@ where
     ui.print("");
     ui.print("\n✨ Done.");
 
-    Ok(())
+    Ok(transaction_hash)
 }
 
 async fn register_namespaces<A>(
@@ -449,11 +593,16 @@ async fn register_namespaces<A>(
     migrator: &A,
     ui: &Ui,
     txn_config: &TxnConfig,
+    journal: &mut MigrationJournal,
 ) -> Result<()>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
+    if namespaces.is_empty() {
+        return Ok(());
+    }
+
     ui.print_header(format!("# Namespaces ({})", namespaces.len()));
 
     let world = WorldContract::new(world_address, migrator);
@@ -467,13 +616,17 @@ where
         .collect::<Vec<_>>();
 
     let InvokeTransactionResult { transaction_hash } =
-        world.account.execute_v1(calls).send_with_cfg(txn_config).await.map_err(|e| {
+        execute_with_cfg(&world.account, calls, txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to register namespace to World: {e}")
         })?;
 
     TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
 
+    for ns in namespaces {
+        journal.record(MigrationStep::Register, ns, transaction_hash)?;
+    }
+
     ui.print(format!("All namespaces are registered at: {transaction_hash:#x}\n"));
 
     Ok(())
@@ -485,9 +638,10 @@ async fn register_dojo_models<A>(
     migrator: &A,
     ui: &Ui,
     txn_config: &TxnConfig,
+    journal: &mut MigrationJournal,
 ) -> Result<RegisterOutput>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     if models.is_empty() {
@@ -515,6 +669,7 @@ where
                     "Declare transaction: {:#066x}",
                     output.transaction_hash
                 ));
+                journal.record(MigrationStep::Declare, tag, output.transaction_hash)?;
                 declare_output.push(output);
             }
             Err(MigrationError::ClassAlreadyDeclared) => {
@@ -543,13 +698,17 @@ where
         .collect::<Vec<_>>();
 
     let InvokeTransactionResult { transaction_hash } =
-        world.account.execute_v1(calls).send_with_cfg(txn_config).await.map_err(|e| {
+        execute_with_cfg(&world.account, calls, txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to register models to World: {e}")
         })?;
 
     TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
 
+    for tag in &registered_models {
+        journal.record(MigrationStep::Register, tag, transaction_hash)?;
+    }
+
     ui.print(format!("All models are registered at: {transaction_hash:#x}\n"));
 
     Ok(RegisterOutput { transaction_hash, declare_output, registered_models })
@@ -563,9 +722,10 @@ async fn register_dojo_models_with_declarers<A>(
     ui: &Ui,
     txn_config: &TxnConfig,
     declarers: &[SingleOwnerAccount<AnyProvider, LocalWallet>],
+    journal: &mut MigrationJournal,
 ) -> Result<RegisterOutput>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     if models.is_empty() {
@@ -618,6 +778,7 @@ where
                         "Declare transaction: {:#066x}",
                         output.transaction_hash
                     ));
+                    journal.record(MigrationStep::Declare, &tag, output.transaction_hash)?;
                     declare_output.push(output);
                 }
                 Err(MigrationError::ClassAlreadyDeclared) => {
@@ -645,13 +806,17 @@ where
         .collect::<Vec<_>>();
 
     let InvokeTransactionResult { transaction_hash } =
-        world.account.execute_v1(calls).send_with_cfg(txn_config).await.map_err(|e| {
+        execute_with_cfg(&world.account, calls, txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to register models to World: {e}")
         })?;
 
     TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
 
+    for tag in &registered_models {
+        journal.record(MigrationStep::Register, tag, transaction_hash)?;
+    }
+
     ui.print(format!("All models are registered at: {transaction_hash:#x}\n"));
 
     Ok(RegisterOutput { transaction_hash, declare_output, registered_models })
@@ -663,9 +828,10 @@ async fn register_dojo_contracts<A>(
     migrator: A,
     ui: &Ui,
     txn_config: &TxnConfig,
+    journal: &mut MigrationJournal,
 ) -> Result<Vec<Option<ContractMigrationOutput>>>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     if contracts.is_empty() {
@@ -688,6 +854,7 @@ where
                     "Declare transaction: {:#066x}",
                     output.transaction_hash
                 ));
+                journal.record(MigrationStep::Declare, tag, output.transaction_hash)?;
                 declare_outputs.push(output);
             }
             Err(MigrationError::ClassAlreadyDeclared) => {
@@ -744,13 +911,18 @@ where
     }
 
     let InvokeTransactionResult { transaction_hash } =
-        migrator.execute_v1(calls).send_with_cfg(txn_config).await.map_err(|e| {
+        execute_with_cfg(&migrator, calls, txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to deploy contracts: {e}")
         })?;
 
     TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
 
+    for output in deploy_outputs.iter().flatten() {
+        let step = if output.was_upgraded { MigrationStep::Upgrade } else { MigrationStep::Deploy };
+        journal.record(step, &output.tag, transaction_hash)?;
+    }
+
     ui.print(format!("All contracts are deployed at: {transaction_hash:#x}\n"));
 
     Ok(deploy_outputs)
@@ -763,9 +935,10 @@ async fn register_dojo_contracts_declarers<A>(
     ui: &Ui,
     txn_config: &TxnConfig,
     declarers: &[SingleOwnerAccount<AnyProvider, LocalWallet>],
+    journal: &mut MigrationJournal,
 ) -> Result<Vec<Option<ContractMigrationOutput>>>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     if contracts.is_empty() {
@@ -815,6 +988,7 @@ where
                         "Declare transaction: {:#066x}",
                         output.transaction_hash
                     ));
+                    journal.record(MigrationStep::Declare, &tag, output.transaction_hash)?;
                     declare_outputs.push(output);
                 }
                 Err(MigrationError::ClassAlreadyDeclared) => {
@@ -872,13 +1046,18 @@ where
     }
 
     let InvokeTransactionResult { transaction_hash } =
-        migrator.execute_v1(calls).send_with_cfg(txn_config).await.map_err(|e| {
+        execute_with_cfg(&migrator, calls, txn_config).await.map_err(|e| {
             ui.verbose(format!("{e:?}"));
             anyhow!("Failed to deploy contracts: {e}")
         })?;
 
     TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
 
+    for output in deploy_outputs.iter().flatten() {
+        let step = if output.was_upgraded { MigrationStep::Upgrade } else { MigrationStep::Deploy };
+        journal.record(step, &output.tag, transaction_hash)?;
+    }
+
     ui.print(format!("All contracts are deployed at: {transaction_hash:#x}\n"));
 
     Ok(deploy_outputs)
@@ -893,7 +1072,7 @@ async fn deploy_contract<A>(
     txn_config: &TxnConfig,
 ) -> Result<ContractDeploymentOutput>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     match contract
@@ -936,7 +1115,7 @@ async fn upgrade_contract<A>(
     txn_config: &TxnConfig,
 ) -> Result<ContractUpgradeOutput>
 where
-    A: ConnectedAccount + Send + Sync,
+    A: TransactionV3Signer + Send + Sync,
     <A as ConnectedAccount>::Provider: Send,
 {
     match contract
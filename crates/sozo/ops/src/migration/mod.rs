@@ -1,14 +1,16 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use camino::Utf8Path;
 use dojo_world::contracts::naming::compute_selector_from_tag;
 use dojo_world::contracts::WorldContract;
-use dojo_world::manifest::{BASE_DIR, MANIFESTS_DIR, OVERLAYS_DIR};
+use dojo_world::manifest::{BaseManifest, BASE_DIR, MANIFESTS_DIR, OVERLAYS_DIR};
 use dojo_world::metadata::get_default_namespace_from_ws;
+use dojo_world::migration::strategy::MigrationStrategy;
 use dojo_world::migration::world::WorldDiff;
 use dojo_world::migration::{DeployOutput, TxnConfig, UpgradeOutput};
-use dojo_world::utils::{TransactionExt, TransactionWaiter};
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer, TransactionWaiter};
 use scarb::core::Workspace;
 use starknet::accounts::{Call, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::types::{Felt, InvokeTransactionResult};
@@ -21,16 +23,20 @@ use starknet_crypto::poseidon_hash_single;
 use url::Url;
 
 mod auto_auth;
+pub mod journal;
 mod migrate;
+pub mod plan;
 pub mod ui;
 mod utils;
 
 pub use self::auto_auth::auto_authorize;
+use self::journal::{MigrationJournal, MigrationStep, JOURNAL_FILE_NAME};
 use self::migrate::update_manifests_and_abis;
 pub use self::migrate::{
-    apply_diff, execute_strategy, find_authorization_diff, prepare_migration, print_strategy,
-    upload_metadata,
+    apply_diff, execute_journaled_strategy, execute_strategy, find_authorization_diff,
+    prepare_migration, print_strategy, remote_model_schemas, upload_metadata,
 };
+use self::plan::MigrationPlan;
 use self::ui::MigrationUi;
 
 #[derive(Debug, Default, Clone)]
@@ -125,9 +131,10 @@ pub async fn migrate<A>(
     dry_run: bool,
     txn_config: TxnConfig,
     skip_manifests: Option<Vec<String>>,
+    force: bool,
 ) -> Result<Option<MigrationOutput>>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
     A::Provider: Send,
     A::SignError: 'static,
 {
@@ -139,57 +146,13 @@ where
     let profile_name =
         ws.current_profile().expect("Scarb profile expected to be defined.").to_string();
     let manifest_dir = root_dir.join(MANIFESTS_DIR).join(&profile_name);
-    let manifest_base_dir = manifest_dir.join(BASE_DIR);
-    let overlay_dir = root_dir.join(OVERLAYS_DIR).join(&profile_name);
-
-    let target_dir = ws.target_dir().path_existent().unwrap();
-    let target_dir = target_dir.join(ws.config().profile().as_str());
 
     let default_namespace = get_default_namespace_from_ws(ws)?;
 
-    // Load local and remote World manifests.
-    let (local_manifest, remote_manifest) = utils::load_world_manifests(
-        &manifest_base_dir,
-        &overlay_dir,
-        &account,
-        world_address,
-        &ui,
-        skip_manifests,
-    )
-    .await
-    .map_err(|e| {
-        ui.error(e.to_string());
-        anyhow!(
-            "\n Use `sozo clean` to clean your project.\nThen, rebuild your project with `sozo \
-             build`.",
-        )
-    })?;
-
-    let generated_world_address = get_world_address(&local_manifest, name)?;
-    if let Some(world_address) = world_address {
-        if world_address != generated_world_address {
-            bail!(format!(
-                "Calculated world address ({:#x}) doesn't match provided world address. If you \
-                 are deploying with custom seed make sure `world_address` is correctly configured \
-                 (or not set) in your `dojo_{profile_name}.toml`",
-                generated_world_address
-            ))
-        }
-    }
-
-    // Calculate diff between local and remote World manifests.
-    ui.print_step(2, "🧰", "Evaluating Worlds diff...");
-    let diff =
-        WorldDiff::compute(local_manifest.clone(), remote_manifest.clone(), &default_namespace)?;
-
+    let (local_manifest, diff, strategy) =
+        compute_strategy(ws, world_address, &account, name, skip_manifests, force).await?;
     let total_diffs = diff.count_diffs();
-    ui.print_sub(format!("Total diffs found: {total_diffs}"));
-
-    if total_diffs == 0 {
-        ui.print("\n✨ No diffs found. Remote World is already up to date!");
-    }
 
-    let strategy = prepare_migration(&target_dir, diff.clone(), name, world_address, &ui)?;
     // TODO: dry run can also show the diffs for things apart from world state
     // what new authorizations would be granted, if ipfs data would change or not,
     // etc...
@@ -219,8 +182,25 @@ where
         let declarers_len = if declarers.is_empty() { 1 } else { declarers.len() };
         ui.print_sub(format!("Declarers: {}", declarers_len));
 
+        let mut journal = MigrationJournal::load(&manifest_dir, strategy.world_address)?;
+        if !journal.is_empty() {
+            let world = WorldContract::new(strategy.world_address, &account);
+            journal.verify(&ui, &world, &diff, &default_namespace).await.with_context(|| {
+                format!(
+                    "Migration journal at {} doesn't match the chain state, remove it to start \
+                     the migration over",
+                    manifest_dir.join(JOURNAL_FILE_NAME)
+                )
+            })?;
+
+            ui.print_sub(format!(
+                "Resuming migration from {} completed steps",
+                journal.entries.len()
+            ));
+        }
+
         let migration_output = if total_diffs != 0 {
-            match apply_diff(ws, &account, txn_config, &strategy, &declarers).await {
+            match apply_diff(ws, &account, txn_config, &strategy, &declarers, &mut journal).await {
                 Ok(migration_output) => Some(migration_output),
                 Err(e) => {
                     update_manifests_and_abis(
@@ -267,79 +247,292 @@ where
         )
         .await?;
 
+        // The migration is only complete once every step went through, otherwise the journal is
+        // kept for the next run to resume from.
+        let mut completed = migration_output.as_ref().map_or(true, |output| output.full);
+
+        let grant = grant
+            .into_iter()
+            .filter(|rw| !journal.is_completed(MigrationStep::Grant, &rw.to_string()))
+            .collect::<Vec<_>>();
+        let revoke = revoke
+            .into_iter()
+            .filter(|rw| !journal.is_completed(MigrationStep::Revoke, &rw.to_string()))
+            .collect::<Vec<_>>();
+
         match auto_authorize(ws, &world, &txn_config, &default_namespace, &grant, &revoke).await {
-            Ok(()) => {
+            Ok((grant_tx, revoke_tx)) => {
+                if let Some(transaction_hash) = grant_tx {
+                    for rw in &grant {
+                        journal.record(MigrationStep::Grant, rw.to_string(), transaction_hash)?;
+                    }
+                }
+                if let Some(transaction_hash) = revoke_tx {
+                    for rw in &revoke {
+                        journal.record(MigrationStep::Revoke, rw.to_string(), transaction_hash)?;
+                    }
+                }
+
                 ui.print_sub("Auto authorize completed successfully");
             }
             Err(e) => {
+                completed = false;
                 ui.print_sub(format!("Failed to auto authorize with error: {e}"));
             }
         };
 
-        if let Some(migration_output) = &migration_output {
+        // Contracts deployed by this migration, or by the interrupted one being resumed, which
+        // have not been initialized yet. Upgraded contracts are never initialized again.
+        let to_init = diff
+            .contracts
+            .iter()
+            .filter(|c| {
+                journal.is_completed(MigrationStep::Deploy, &c.tag)
+                    && !journal.is_completed(MigrationStep::Init, &c.tag)
+            })
+            .collect::<Vec<_>>();
+
+        if migration_output.is_some() || !to_init.is_empty() {
             ui.print(" ");
             ui.print_step(7, "🏗️", "Initializing contracts...");
 
             // Run dojo inits now that everything is actually deployed and permissioned.
             let mut init_calls = vec![];
-            for c in strategy.contracts {
-                let was_upgraded = migration_output
-                    .contracts
-                    .iter()
-                    .flatten()
-                    .find(|output| output.tag == c.diff.tag)
-                    .map(|output| output.was_upgraded)
-                    .unwrap_or(false);
-
-                if was_upgraded {
-                    continue;
-                }
-
-                let contract_selector = compute_selector_from_tag(&c.diff.tag);
-                let init_calldata: Vec<Felt> = c
-                    .diff
-                    .init_calldata
-                    .iter()
-                    .map(|s| Felt::from_str(s))
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let mut calldata = vec![contract_selector, Felt::from(init_calldata.len())];
-                calldata.extend(init_calldata);
-
-                init_calls.push(Call {
-                    calldata,
-                    selector: selector!("init_contract"),
-                    to: strategy.world_address,
-                });
+            for c in &to_init {
+                init_calls.push(init_call(&strategy, &c.tag, &c.init_calldata)?);
             }
 
             if !init_calls.is_empty() {
-                let InvokeTransactionResult { transaction_hash } = account
-                    .execute_v1(init_calls)
-                    .send_with_cfg(&TxnConfig::init_wait())
-                    .await
-                    .map_err(|e| {
-                        ui.verbose(format!("{e:?}"));
-                        anyhow!("Failed to deploy contracts: {e}")
-                    })?;
+                let InvokeTransactionResult { transaction_hash } =
+                    execute_with_cfg(&account, init_calls, &TxnConfig { wait: true, ..txn_config })
+                        .await
+                        .map_err(|e| {
+                            ui.verbose(format!("{e:?}"));
+                            anyhow!("Failed to deploy contracts: {e}")
+                        })?;
 
                 TransactionWaiter::new(transaction_hash, account.provider()).await?;
+
+                for c in &to_init {
+                    journal.record(MigrationStep::Init, &c.tag, transaction_hash)?;
+                }
+
                 ui.print_sub(format!("All contracts are initialized at: {transaction_hash:#x}\n"));
             } else {
                 ui.print_sub("No contracts to initialize");
             }
         }
 
-        if let Some(migration_output) = &migration_output {
-            if !ws.config().offline() {
-                upload_metadata(ws, &account, migration_output.clone(), txn_config).await?;
+        if !ws.config().offline() && !journal.is_completed(MigrationStep::Metadata, "world") {
+            let metadata_output = metadata_output(&journal, &diff, migration_output.as_ref());
+            if let Some(metadata_output) = metadata_output {
+                let transaction_hash =
+                    upload_metadata(ws, &account, metadata_output, txn_config).await?;
+                journal.record(MigrationStep::Metadata, "world", transaction_hash)?;
             }
         }
 
+        if completed {
+            journal.clear()?;
+        } else if let Some(path) = journal.path() {
+            ui.print_sub(format!(
+                "Migration journal kept at {path}, run the migration again to resume it"
+            ));
+        }
+
         Ok(migration_output)
     }
 }
 
+/// Gathers the resources whose metadata have to be uploaded, including the ones migrated by the
+/// interrupted migration being resumed.
+fn metadata_output(
+    journal: &MigrationJournal,
+    diff: &WorldDiff,
+    migration_output: Option<&MigrationOutput>,
+) -> Option<MigrationOutput> {
+    let mut output = migration_output.cloned().unwrap_or_else(|| MigrationOutput {
+        world_address: journal.world_address,
+        full: true,
+        ..Default::default()
+    });
+
+    if output.world_tx_hash.is_none() {
+        output.world_tx_hash = journal
+            .completed(MigrationStep::Deploy)
+            .find(|e| e.target == "world")
+            .map(|e| e.transaction_hash);
+    }
+
+    for m in &diff.models {
+        if journal.is_completed(MigrationStep::Register, &m.tag) && !output.models.contains(&m.tag)
+        {
+            output.models.push(m.tag.clone());
+        }
+    }
+
+    for c in &diff.contracts {
+        let migrated = output.contracts.iter().flatten().any(|o| o.tag == c.tag);
+        let was_upgraded = journal.is_completed(MigrationStep::Upgrade, &c.tag);
+
+        if !migrated && (was_upgraded || journal.is_completed(MigrationStep::Deploy, &c.tag)) {
+            output.contracts.push(Some(ContractMigrationOutput {
+                tag: c.tag.clone(),
+                base_class_hash: c.base_class_hash,
+                was_upgraded,
+                ..Default::default()
+            }));
+        }
+    }
+
+    if migration_output.is_none()
+        && output.world_tx_hash.is_none()
+        && output.models.is_empty()
+        && output.contracts.is_empty()
+    {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// Plans the migration of the World and exports it into `plan_dir` as unsigned transactions, to
+/// be signed and sent by the owner of the World.
+pub async fn export_plan<A>(
+    ws: &Workspace<'_>,
+    world_address: Option<Felt>,
+    account: A,
+    name: &str,
+    skip_manifests: Option<Vec<String>>,
+    force: bool,
+    plan_dir: &Utf8Path,
+) -> Result<MigrationPlan>
+where
+    A: ConnectedAccount + Sync + Send,
+    A::Provider: Send,
+    A::SignError: 'static,
+{
+    let ui = ws.config().ui();
+    let default_namespace = get_default_namespace_from_ws(ws)?;
+
+    let (_, diff, strategy) =
+        compute_strategy(ws, world_address, &account, name, skip_manifests, force).await?;
+
+    ui.print_step(4, "📝", "Planning migration...");
+
+    let world = WorldContract::new(strategy.world_address, &account);
+    let plan =
+        plan::build_plan(&ui, &world, &strategy, &diff, &default_namespace, plan_dir).await?;
+    plan.write(plan_dir)?;
+
+    ui.print(format!(
+        "\n✨ Migration plan of {} transactions written to {plan_dir}",
+        plan.transactions.len()
+    ));
+
+    Ok(plan)
+}
+
+/// Builds the call initializing a contract once it's deployed and permissioned.
+fn init_call(strategy: &MigrationStrategy, tag: &str, init_calldata: &[String]) -> Result<Call> {
+    let contract_selector = compute_selector_from_tag(tag);
+    let init_calldata: Vec<Felt> = strategy
+        .resolve_init_calldata(init_calldata)
+        .iter()
+        .map(|s| Felt::from_str(s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut calldata = vec![contract_selector, Felt::from(init_calldata.len())];
+    calldata.extend(init_calldata);
+
+    Ok(Call { calldata, selector: selector!("init_contract"), to: strategy.world_address })
+}
+
+/// Loads the local and remote World manifests and computes the strategy migrating the remote
+/// World to the local one.
+async fn compute_strategy<A>(
+    ws: &Workspace<'_>,
+    world_address: Option<Felt>,
+    account: &A,
+    name: &str,
+    skip_manifests: Option<Vec<String>>,
+    force: bool,
+) -> Result<(BaseManifest, WorldDiff, MigrationStrategy)>
+where
+    A: ConnectedAccount + Sync + Send,
+    A::Provider: Send,
+{
+    let ui = ws.config().ui();
+
+    // its path to a file so `parent` should never return `None`
+    let root_dir = ws.manifest_path().parent().unwrap().to_path_buf();
+
+    let profile_name =
+        ws.current_profile().expect("Scarb profile expected to be defined.").to_string();
+    let manifest_base_dir = root_dir.join(MANIFESTS_DIR).join(&profile_name).join(BASE_DIR);
+    let overlay_dir = root_dir.join(OVERLAYS_DIR).join(&profile_name);
+
+    let target_dir = ws.target_dir().path_existent().unwrap();
+    let target_dir = target_dir.join(ws.config().profile().as_str());
+
+    let default_namespace = get_default_namespace_from_ws(ws)?;
+
+    // Load local and remote World manifests.
+    let (local_manifest, remote_manifest) = utils::load_world_manifests(
+        &manifest_base_dir,
+        &overlay_dir,
+        account,
+        world_address,
+        &ui,
+        skip_manifests,
+    )
+    .await
+    .map_err(|e| {
+        ui.error(e.to_string());
+        anyhow!(
+            "\n Use `sozo clean` to clean your project.\nThen, rebuild your project with `sozo \
+             build`.",
+        )
+    })?;
+
+    let generated_world_address = get_world_address(&local_manifest, name)?;
+    if let Some(world_address) = world_address {
+        if world_address != generated_world_address {
+            bail!(format!(
+                "Calculated world address ({:#x}) doesn't match provided world address. If you \
+                 are deploying with custom seed make sure `world_address` is correctly configured \
+                 (or not set) in your `dojo_{profile_name}.toml`",
+                generated_world_address
+            ))
+        }
+    }
+
+    // Calculate diff between local and remote World manifests.
+    ui.print_step(2, "🧰", "Evaluating Worlds diff...");
+    let diff =
+        WorldDiff::compute(local_manifest.clone(), remote_manifest.clone(), &default_namespace)?;
+
+    let total_diffs = diff.count_diffs();
+    ui.print_sub(format!("Total diffs found: {total_diffs}"));
+
+    if total_diffs == 0 {
+        ui.print("\n✨ No diffs found. Remote World is already up to date!");
+    }
+
+    let remote_models = remote_model_schemas(account.provider(), world_address, &diff).await?;
+    let strategy = prepare_migration(
+        &target_dir,
+        diff.clone(),
+        name,
+        world_address,
+        &remote_models,
+        force,
+        &ui,
+    )?;
+
+    Ok((local_manifest, diff, strategy))
+}
+
 fn get_world_address(
     local_manifest: &dojo_world::manifest::BaseManifest,
     name: &str,
@@ -0,0 +1,919 @@
+//! Migration plans, exporting the migration of a World as unsigned transactions for the Worlds
+//! owned by accounts sozo can't sign for (e.g. multisigs).
+//!
+//! A plan is a directory holding a `plan.json` file and a `classes` directory with the classes to
+//! declare. `plan.json` is formatted as follows, with every felt encoded as an hex string:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "chain_id": "0x4b4154414e41",
+//!   "world_address": "0x...",
+//!   "transactions": [
+//!     {
+//!       "type": "declare",
+//!       "tag": "dojo_examples-moves",
+//!       "class_hash": "0x...",
+//!       "compiled_class_hash": "0x...",
+//!       "contract_class": "classes/0x....json"
+//!     },
+//!     {
+//!       "type": "invoke",
+//!       "description": "Register models",
+//!       "calls": [
+//!         { "to": "0x...", "entrypoint": "register_model", "selector": "0x...", "calldata": [] }
+//!       ]
+//!     }
+//!   ],
+//!   "expected": {
+//!     "classes": ["0x..."],
+//!     "contracts": [{ "tag": "dojo_examples-actions", "address": "0x...", "class_hash": "0x..." }],
+//!     "models": [{ "tag": "dojo_examples-moves", "class_hash": "0x..." }],
+//!     "writers": [
+//!       {
+//!         "tag": "dojo_examples-actions",
+//!         "address": "0x...",
+//!         "resource": "0x...",
+//!         "granted": true
+//!       }
+//!     ],
+//!     "initialized": ["dojo_examples-actions"]
+//!   }
+//! }
+//! ```
+//!
+//! The transactions have to be sent in order by the owner of the World. Declare transactions use
+//! the flattened Sierra class found at `contract_class` (relative to the plan directory), and the
+//! calls of each invoke transaction are meant to be sent as a single multicall. Nonces, fees and
+//! signatures are left to the signer, and [`submit`] checks the signed transactions are the planned
+//! ones before sending them. `expected` is the state of the World once every transaction went
+//! through, checked by [`verify_plan`].
+//!
+//! The metadata of the World are not part of the plan, as uploading them requires IPFS.
+
+use std::collections::HashSet;
+use std::fs;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use cainome::cairo_serde::{ByteArray, ContractAddress};
+use camino::{Utf8Path, Utf8PathBuf};
+use dojo_world::contracts::model::ModelReader;
+use dojo_world::contracts::naming::{
+    compute_bytearray_hash, compute_selector_from_tag, ensure_namespace, get_name_from_tag,
+    get_namespace_from_tag,
+};
+use dojo_world::contracts::{WorldContract, WorldContractReader};
+use dojo_world::migration::prepare_contract_declaration_params;
+use dojo_world::migration::strategy::{generate_salt, MigrationStrategy};
+use dojo_world::migration::world::WorldDiff;
+use dojo_world::utils::TransactionWaiter;
+use num_traits::ToPrimitive;
+use scarb_ui::Ui;
+use serde::{Deserialize, Serialize};
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, BroadcastedDeclareTransaction, BroadcastedInvokeTransaction,
+    BroadcastedTransaction, EventFilter, Felt, StarknetError,
+};
+use starknet::core::utils::get_contract_address;
+use starknet::macros::{felt, selector};
+use starknet::providers::{Provider, ProviderError};
+
+use super::ui::MigrationUi;
+use super::{find_authorization_diff, init_call, ContractMigrationOutput, MigrationOutput};
+use crate::auth::{get_resource_selector, ResourceType};
+use crate::utils;
+
+/// Name of the plan file in the plan directory.
+pub const PLAN_FILE_NAME: &str = "plan.json";
+/// Directory of the classes to declare in the plan directory.
+pub const CLASSES_DIR: &str = "classes";
+/// Version of the plan format.
+pub const PLAN_VERSION: u32 = 1;
+
+/// Universal Deployer Contract used to deploy the World.
+const UDC_ADDRESS: Felt =
+    felt!("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf");
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    pub version: u32,
+    pub chain_id: Felt,
+    pub world_address: Felt,
+    pub transactions: Vec<PlannedTransaction>,
+    pub expected: ExpectedState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlannedTransaction {
+    Declare {
+        tag: String,
+        class_hash: Felt,
+        compiled_class_hash: Felt,
+        /// Path of the flattened Sierra class, relative to the plan directory.
+        contract_class: Utf8PathBuf,
+    },
+    Invoke {
+        description: String,
+        calls: Vec<PlannedCall>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedCall {
+    pub to: Felt,
+    /// Name of the called entrypoint, for the reviewers of the plan.
+    pub entrypoint: String,
+    pub selector: Felt,
+    pub calldata: Vec<Felt>,
+}
+
+impl PlannedCall {
+    pub fn new(entrypoint: &str, call: Call) -> Self {
+        Self {
+            to: call.to,
+            entrypoint: entrypoint.to_string(),
+            selector: call.selector,
+            calldata: call.calldata,
+        }
+    }
+}
+
+impl From<&PlannedCall> for Call {
+    fn from(call: &PlannedCall) -> Self {
+        Call { to: call.to, selector: call.selector, calldata: call.calldata.clone() }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedState {
+    /// Classes declared once the plan is applied.
+    pub classes: Vec<Felt>,
+    pub contracts: Vec<ExpectedContract>,
+    pub models: Vec<ExpectedModel>,
+    pub writers: Vec<ExpectedWriter>,
+    /// Tags of the contracts initialized by the plan.
+    pub initialized: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedContract {
+    pub tag: String,
+    pub address: Felt,
+    pub class_hash: Felt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedModel {
+    pub tag: String,
+    pub class_hash: Felt,
+}
+
+/// Write permission of a contract on a resource, granted or revoked by the plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedWriter {
+    /// Tag of the contract, or its address for the contracts outside of the World.
+    pub tag: String,
+    pub address: Felt,
+    /// Selector of the resource.
+    pub resource: Felt,
+    pub granted: bool,
+}
+
+impl MigrationPlan {
+    pub fn load(plan_dir: &Utf8Path) -> Result<Self> {
+        let path = plan_dir.join(PLAN_FILE_NAME);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read migration plan at {path}"))?;
+        let plan: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse migration plan at {path}"))?;
+
+        if plan.version != PLAN_VERSION {
+            bail!("Unsupported migration plan version {}, expected {PLAN_VERSION}.", plan.version);
+        }
+
+        Ok(plan)
+    }
+
+    pub fn write(&self, plan_dir: &Utf8Path) -> Result<()> {
+        fs::create_dir_all(plan_dir)?;
+
+        let path = plan_dir.join(PLAN_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write migration plan at {path}"))
+    }
+}
+
+/// Builds the plan of the migration, writing the classes to declare into `plan_dir`.
+///
+/// The permissions of a World which is already deployed are diffed against the ones it holds,
+/// while the ones of a new World are all granted from the local diff.
+pub async fn build_plan<A>(
+    ui: &Ui,
+    world: &WorldContract<A>,
+    strategy: &MigrationStrategy,
+    diff: &WorldDiff,
+    default_namespace: &str,
+    plan_dir: &Utf8Path,
+) -> Result<MigrationPlan>
+where
+    A: ConnectedAccount + Sync + Send,
+    <A as Account>::SignError: 'static,
+{
+    let provider = world.account.provider();
+    let world_address = strategy.world_address;
+
+    let mut transactions = vec![];
+    let mut expected = ExpectedState::default();
+
+    // declarations
+    let mut artifacts = vec![];
+    if let Some(base) = &strategy.base {
+        artifacts.push(("base", &base.artifact_path));
+    }
+    if let Some(world) = &strategy.world {
+        artifacts.push(("world", &world.artifact_path));
+    }
+    artifacts.extend(strategy.models.iter().map(|m| (m.diff.tag.as_str(), &m.artifact_path)));
+    artifacts.extend(strategy.contracts.iter().map(|c| (c.diff.tag.as_str(), &c.artifact_path)));
+
+    fs::create_dir_all(plan_dir.join(CLASSES_DIR))?;
+
+    for (tag, artifact_path) in artifacts {
+        let (flattened_class, compiled_class_hash) =
+            prepare_contract_declaration_params(artifact_path)?;
+        let class_hash = flattened_class.class_hash();
+        expected.classes.push(class_hash);
+
+        match provider.get_class(BlockId::Tag(BlockTag::Pending), class_hash).await {
+            Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => {}
+            Ok(_) => continue,
+            Err(e) => return Err(e.into()),
+        }
+
+        let contract_class = Utf8PathBuf::from(CLASSES_DIR).join(format!("{class_hash:#x}.json"));
+        fs::write(plan_dir.join(&contract_class), serde_json::to_string(&flattened_class)?)
+            .with_context(|| format!("Failed to write class of {tag}"))?;
+
+        ui.print_sub(format!("Declare {tag}: {class_hash:#x}"));
+        transactions.push(PlannedTransaction::Declare {
+            tag: tag.to_string(),
+            class_hash,
+            compiled_class_hash,
+            contract_class,
+        });
+    }
+
+    // world
+    if let Some(world_migration) = &strategy.world {
+        let class_hash = world_migration.diff.local_class_hash;
+
+        let (description, call) = if world_migration.diff.remote_class_hash.is_some() {
+            let call = Call {
+                to: world_address,
+                selector: selector!("upgrade"),
+                calldata: vec![class_hash],
+            };
+            ("Upgrade world", PlannedCall::new("upgrade", call))
+        } else {
+            let base = strategy.base.as_ref().ok_or_else(|| anyhow!("Base class is missing."))?;
+            let calldata = vec![
+                class_hash,
+                world_migration.salt,
+                Felt::ZERO,
+                Felt::ONE,
+                base.diff.local_class_hash,
+            ];
+            let call = Call { to: UDC_ADDRESS, selector: selector!("deployContract"), calldata };
+            ("Deploy world", PlannedCall::new("deployContract", call))
+        };
+
+        transactions.push(PlannedTransaction::Invoke {
+            description: description.to_string(),
+            calls: vec![call],
+        });
+        expected.contracts.push(ExpectedContract {
+            tag: "world".to_string(),
+            address: world_address,
+            class_hash,
+        });
+    }
+
+    // namespaces
+    let namespaces = strategy
+        .models
+        .iter()
+        .map(|m| get_namespace_from_tag(&m.diff.tag))
+        .chain(strategy.contracts.iter().map(|c| get_namespace_from_tag(&c.diff.tag)))
+        .collect::<HashSet<_>>();
+
+    if !namespaces.is_empty() {
+        let mut namespaces = namespaces.into_iter().collect::<Vec<_>>();
+        namespaces.sort();
+
+        let calls = namespaces
+            .iter()
+            .map(|ns| {
+                let call = world.register_namespace_getcall(&ByteArray::from_string(ns)?);
+                Ok(PlannedCall::new("register_namespace", call))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        transactions.push(PlannedTransaction::Invoke {
+            description: "Register namespaces".to_string(),
+            calls,
+        });
+    }
+
+    // models
+    if !strategy.models.is_empty() {
+        let calls = strategy
+            .models
+            .iter()
+            .map(|m| {
+                expected.models.push(ExpectedModel {
+                    tag: m.diff.tag.clone(),
+                    class_hash: m.diff.local_class_hash,
+                });
+
+                let call = world.register_model_getcall(&m.diff.local_class_hash.into());
+                PlannedCall::new("register_model", call)
+            })
+            .collect::<Vec<_>>();
+
+        transactions
+            .push(PlannedTransaction::Invoke { description: "Register models".to_string(), calls });
+    }
+
+    // contracts
+    let mut migrated_contracts = vec![];
+    if !strategy.contracts.is_empty() {
+        let mut calls = vec![];
+
+        for c in &strategy.contracts {
+            let class_hash = c.diff.local_class_hash;
+            let address = get_contract_address(c.salt, c.diff.base_class_hash, &[], world_address);
+            let was_upgraded = c.diff.remote_class_hash.is_some();
+
+            let call = if was_upgraded {
+                let calldata = vec![compute_selector_from_tag(&c.diff.tag), class_hash];
+                let call =
+                    Call { to: world_address, selector: selector!("upgrade_contract"), calldata };
+                PlannedCall::new("upgrade_contract", call)
+            } else {
+                let calldata = vec![c.salt, class_hash];
+                let call =
+                    Call { to: world_address, selector: selector!("deploy_contract"), calldata };
+                PlannedCall::new("deploy_contract", call)
+            };
+
+            calls.push(call);
+            expected.contracts.push(ExpectedContract {
+                tag: c.diff.tag.clone(),
+                address,
+                class_hash,
+            });
+            migrated_contracts.push(Some(ContractMigrationOutput {
+                tag: c.diff.tag.clone(),
+                contract_address: address,
+                base_class_hash: c.diff.base_class_hash,
+                was_upgraded,
+            }));
+        }
+
+        transactions.push(PlannedTransaction::Invoke {
+            description: "Deploy contracts".to_string(),
+            calls,
+        });
+    }
+
+    // permissions
+    let writers = if diff.world.remote_class_hash.is_some() {
+        let migration_output = MigrationOutput {
+            world_address,
+            full: true,
+            models: strategy.models.iter().map(|m| m.diff.tag.clone()).collect(),
+            contracts: migrated_contracts,
+            ..Default::default()
+        };
+
+        let (grant, revoke) =
+            find_authorization_diff(ui, world, diff, Some(&migration_output), default_namespace)
+                .await?;
+
+        let mut writers = vec![];
+        for (writers_diff, granted) in [(grant, true), (revoke, false)] {
+            for writer in writers_diff {
+                let resource =
+                    get_resource_selector(ui, world, &writer.resource, default_namespace).await?;
+                let address = utils::get_contract_address(world, &writer.tag_or_address).await?;
+                writers.push(ExpectedWriter {
+                    tag: writer.tag_or_address,
+                    address,
+                    resource,
+                    granted,
+                });
+            }
+        }
+        writers
+    } else {
+        // The World can't be read before it's deployed, all the writes of the new contracts are
+        // granted from the local diff.
+        local_writers(strategy, diff, default_namespace)?
+    };
+
+    let grant_calls = writers
+        .iter()
+        .filter(|w| w.granted)
+        .map(|w| {
+            let call = world.grant_writer_getcall(&w.resource, &w.address.into());
+            PlannedCall::new("grant_writer", call)
+        })
+        .collect::<Vec<_>>();
+    if !grant_calls.is_empty() {
+        transactions.push(PlannedTransaction::Invoke {
+            description: "Grant writers".to_string(),
+            calls: grant_calls,
+        });
+    }
+
+    let revoke_calls = writers
+        .iter()
+        .filter(|w| !w.granted)
+        .map(|w| {
+            let call = world.revoke_writer_getcall(&w.resource, &w.address.into());
+            PlannedCall::new("revoke_writer", call)
+        })
+        .collect::<Vec<_>>();
+    if !revoke_calls.is_empty() {
+        transactions.push(PlannedTransaction::Invoke {
+            description: "Revoke writers".to_string(),
+            calls: revoke_calls,
+        });
+    }
+
+    expected.writers = writers;
+
+    // initialization of the new contracts
+    let init_calls = strategy
+        .contracts
+        .iter()
+        .filter(|c| c.diff.remote_class_hash.is_none())
+        .map(|c| {
+            expected.initialized.push(c.diff.tag.clone());
+            Ok(PlannedCall::new(
+                "init_contract",
+                init_call(strategy, &c.diff.tag, &c.diff.init_calldata)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if !init_calls.is_empty() {
+        transactions.push(PlannedTransaction::Invoke {
+            description: "Initialize contracts".to_string(),
+            calls: init_calls,
+        });
+    }
+
+    let chain_id = provider.chain_id().await?;
+
+    Ok(MigrationPlan { version: PLAN_VERSION, chain_id, world_address, transactions, expected })
+}
+
+/// Sends the signed transactions of a plan in order, waiting for each one to be accepted.
+///
+/// The transactions are read from a JSON array of transactions in the JSON-RPC format of
+/// `starknet_addInvokeTransaction` and `starknet_addDeclareTransaction`, and must be the ones of
+/// the plan, in the same order. None of them is sent otherwise.
+pub async fn submit<P>(
+    ui: &Ui,
+    provider: &P,
+    plan: &MigrationPlan,
+    transactions_path: &Utf8Path,
+) -> Result<Vec<Felt>>
+where
+    P: Provider + Sync + Send,
+{
+    let content = fs::read_to_string(transactions_path)
+        .with_context(|| format!("Failed to read transactions at {transactions_path}"))?;
+    let transactions: Vec<BroadcastedTransaction> = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse transactions at {transactions_path}"))?;
+
+    check_chain_id(provider, plan).await?;
+    check_transactions(plan, &transactions)?;
+
+    ui.print_header(format!("# Transactions ({})", transactions.len()));
+
+    let mut transaction_hashes = vec![];
+    for transaction in transactions {
+        let transaction_hash = match transaction {
+            BroadcastedTransaction::Invoke(tx) => {
+                provider.add_invoke_transaction(&tx).await?.transaction_hash
+            }
+            BroadcastedTransaction::Declare(tx) => {
+                provider.add_declare_transaction(&tx).await?.transaction_hash
+            }
+            BroadcastedTransaction::DeployAccount(_) => {
+                bail!("Deploy account transactions are not part of a migration plan.")
+            }
+        };
+
+        TransactionWaiter::new(transaction_hash, provider).await?;
+        ui.print_sub(format!("Transaction accepted: {transaction_hash:#x}"));
+
+        transaction_hashes.push(transaction_hash);
+    }
+
+    Ok(transaction_hashes)
+}
+
+/// Checks that the state of the World matches the one expected by the plan.
+pub async fn verify_plan<P>(ui: &Ui, provider: &P, plan: &MigrationPlan) -> Result<()>
+where
+    P: Provider + Sync + Send,
+{
+    check_chain_id(provider, plan).await?;
+
+    let block_id = BlockId::Tag(BlockTag::Pending);
+    let mut mismatches = vec![];
+
+    for class_hash in &plan.expected.classes {
+        if let Err(e) = provider.get_class(block_id, *class_hash).await {
+            mismatches.push(format!("Class {class_hash:#x} is not declared: {e}"));
+        }
+    }
+
+    for contract in &plan.expected.contracts {
+        match provider.get_class_hash_at(block_id, contract.address).await {
+            Ok(class_hash) if class_hash == contract.class_hash => {}
+            Ok(class_hash) => mismatches.push(format!(
+                "Contract {} at {:#x} has class hash {class_hash:#x}, expected {:#x}",
+                contract.tag, contract.address, contract.class_hash
+            )),
+            Err(e) => mismatches.push(format!(
+                "Contract {} is not deployed at {:#x}: {e}",
+                contract.tag, contract.address
+            )),
+        }
+    }
+
+    let world = WorldContractReader::new(plan.world_address, provider).with_block(block_id);
+    for model in &plan.expected.models {
+        match world.model_reader_with_tag(&model.tag).await {
+            Ok(reader) if reader.class_hash() == model.class_hash => {}
+            Ok(reader) => mismatches.push(format!(
+                "Model {} has class hash {:#x}, expected {:#x}",
+                model.tag,
+                reader.class_hash(),
+                model.class_hash
+            )),
+            Err(e) => mismatches.push(format!("Model {} is not registered: {e}", model.tag)),
+        }
+    }
+
+    for writer in &plan.expected.writers {
+        let is_writer =
+            world.is_writer(&writer.resource, &ContractAddress(writer.address)).call().await?;
+        if is_writer != writer.granted {
+            mismatches.push(format!(
+                "Write access of {} to resource {:#x} is not {}",
+                writer.tag,
+                writer.resource,
+                if writer.granted { "granted" } else { "revoked" }
+            ));
+        }
+    }
+
+    if !plan.expected.initialized.is_empty() {
+        let initialized = initialized_contracts(provider, plan.world_address).await?;
+        for tag in &plan.expected.initialized {
+            if !initialized.contains(&compute_selector_from_tag(tag)) {
+                mismatches.push(format!("Contract {tag} is not initialized"));
+            }
+        }
+    }
+
+    if !mismatches.is_empty() {
+        for mismatch in &mismatches {
+            ui.print_sub(mismatch);
+        }
+        bail!("On-chain state doesn't match the migration plan ({} mismatches).", mismatches.len());
+    }
+
+    ui.print_sub("On-chain state matches the migration plan");
+    Ok(())
+}
+
+/// Grants every write of the new contracts, resolving the resources from the local diff.
+fn local_writers(
+    strategy: &MigrationStrategy,
+    diff: &WorldDiff,
+    default_namespace: &str,
+) -> Result<Vec<ExpectedWriter>> {
+    let local_address = |tag: &str| {
+        let base_class_hash = diff
+            .contracts
+            .iter()
+            .find(|c| c.tag == tag)
+            .map_or(diff.base.local_class_hash, |c| c.base_class_hash);
+
+        get_contract_address(
+            generate_salt(&get_name_from_tag(tag)),
+            base_class_hash,
+            &[],
+            strategy.world_address,
+        )
+    };
+
+    let mut writers = vec![];
+    for c in &strategy.contracts {
+        for write in &c.diff.local_writes {
+            let write =
+                if write.contains(':') { write.to_string() } else { format!("m:{}", write) };
+
+            let resource = match ResourceType::from_str(&write)? {
+                ResourceType::Contract(tag_or_address) if tag_or_address.starts_with("0x") => {
+                    Felt::from_hex(&tag_or_address)?
+                }
+                ResourceType::Contract(tag) => {
+                    local_address(&ensure_namespace(&tag, default_namespace))
+                }
+                ResourceType::Model(tag) => {
+                    compute_selector_from_tag(&ensure_namespace(&tag, default_namespace))
+                }
+                ResourceType::Namespace(name) => compute_bytearray_hash(&name),
+                ResourceType::Selector(selector) => selector,
+            };
+
+            writers.push(ExpectedWriter {
+                tag: c.diff.tag.clone(),
+                address: local_address(&c.diff.tag),
+                resource,
+                granted: true,
+            });
+        }
+    }
+
+    Ok(writers)
+}
+
+/// Checks the provider is connected to the chain the plan was built for.
+async fn check_chain_id<P>(provider: &P, plan: &MigrationPlan) -> Result<()>
+where
+    P: Provider + Sync + Send,
+{
+    let chain_id = provider.chain_id().await?;
+    if chain_id != plan.chain_id {
+        bail!(
+            "Migration plan was built for chain {:#x}, but the provider is connected to chain \
+             {chain_id:#x}.",
+            plan.chain_id
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks the signed transactions are the planned ones, in the same order.
+fn check_transactions(plan: &MigrationPlan, transactions: &[BroadcastedTransaction]) -> Result<()> {
+    if transactions.len() != plan.transactions.len() {
+        bail!(
+            "Migration plan has {} transactions, but {} signed transactions were provided.",
+            plan.transactions.len(),
+            transactions.len()
+        );
+    }
+
+    for (i, (planned, signed)) in plan.transactions.iter().zip(transactions).enumerate() {
+        let (matches, description) = match (planned, signed) {
+            (
+                PlannedTransaction::Declare { tag, class_hash, compiled_class_hash, .. },
+                BroadcastedTransaction::Declare(tx),
+            ) => {
+                let declared = match tx {
+                    BroadcastedDeclareTransaction::V2(tx) => {
+                        Some((tx.contract_class.class_hash(), tx.compiled_class_hash))
+                    }
+                    BroadcastedDeclareTransaction::V3(tx) => {
+                        Some((tx.contract_class.class_hash(), tx.compiled_class_hash))
+                    }
+                    BroadcastedDeclareTransaction::V1(_) => None,
+                };
+
+                (declared == Some((*class_hash, *compiled_class_hash)), format!("Declare {tag}"))
+            }
+            (
+                PlannedTransaction::Invoke { description, calls },
+                BroadcastedTransaction::Invoke(tx),
+            ) => {
+                let calldata = match tx {
+                    BroadcastedInvokeTransaction::V1(tx) => &tx.calldata,
+                    BroadcastedInvokeTransaction::V3(tx) => &tx.calldata,
+                };
+
+                let matches = decode_execute_calldata(calldata).is_some_and(|signed| {
+                    signed.len() == calls.len()
+                        && signed.iter().zip(calls).all(|(s, p)| {
+                            s.to == p.to && s.selector == p.selector && s.calldata == p.calldata
+                        })
+                });
+                (matches, description.clone())
+            }
+            (PlannedTransaction::Declare { tag, .. }, _) => (false, format!("Declare {tag}")),
+            (PlannedTransaction::Invoke { description, .. }, _) => (false, description.clone()),
+        };
+
+        if !matches {
+            bail!("Signed transaction {i} doesn't match the planned transaction ({description}).");
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes the calls of the `__execute__` calldata of an account, as encoded by the Cairo 1
+/// accounts.
+fn decode_execute_calldata(calldata: &[Felt]) -> Option<Vec<Call>> {
+    let (count, mut calldata) = calldata.split_first()?;
+
+    let mut calls = vec![];
+    for _ in 0..count.to_usize()? {
+        let [to, selector, len, rest @ ..] = calldata else {
+            return None;
+        };
+
+        let len = len.to_usize()?;
+        if rest.len() < len {
+            return None;
+        }
+
+        calls.push(Call { to: *to, selector: *selector, calldata: rest[..len].to_vec() });
+        calldata = &rest[len..];
+    }
+
+    calldata.is_empty().then_some(calls)
+}
+
+/// Selectors of the contracts initialized by the World.
+async fn initialized_contracts<P>(provider: &P, world_address: Felt) -> Result<HashSet<Felt>>
+where
+    P: Provider + Sync + Send,
+{
+    const EVENTS_CHUNK_SIZE: u64 = 100;
+
+    let filter = EventFilter {
+        from_block: None,
+        to_block: Some(BlockId::Tag(BlockTag::Pending)),
+        address: Some(world_address),
+        keys: Some(vec![vec![selector!("ContractInitialized")]]),
+    };
+
+    let mut initialized = HashSet::new();
+    let mut continuation_token = None;
+    loop {
+        let page =
+            provider.get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE).await?;
+        initialized.extend(page.events.iter().filter_map(|e| e.data.first().copied()));
+
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(initialized)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+    use serde_json::json;
+    use starknet::core::types::BroadcastedInvokeTransactionV1;
+
+    use super::*;
+
+    fn plan() -> MigrationPlan {
+        MigrationPlan {
+            version: PLAN_VERSION,
+            chain_id: felt!("0x4b4154414e41"),
+            world_address: felt!("0x1"),
+            transactions: vec![
+                PlannedTransaction::Declare {
+                    tag: "ns-position".to_string(),
+                    class_hash: felt!("0x2"),
+                    compiled_class_hash: felt!("0x3"),
+                    contract_class: Utf8PathBuf::from("classes/0x2.json"),
+                },
+                PlannedTransaction::Invoke {
+                    description: "Register models".to_string(),
+                    calls: vec![PlannedCall::new(
+                        "register_model",
+                        Call {
+                            to: felt!("0x1"),
+                            selector: selector!("register_model"),
+                            calldata: vec![felt!("0x2")],
+                        },
+                    )],
+                },
+            ],
+            expected: ExpectedState {
+                classes: vec![felt!("0x2")],
+                contracts: vec![],
+                models: vec![ExpectedModel {
+                    tag: "ns-position".to_string(),
+                    class_hash: felt!("0x2"),
+                }],
+                writers: vec![],
+                initialized: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn plan_format() {
+        let value = serde_json::to_value(plan()).unwrap();
+
+        assert_eq!(value["chain_id"], json!("0x4b4154414e41"));
+        assert_eq!(value["transactions"][0]["type"], json!("declare"));
+        assert_eq!(value["transactions"][0]["contract_class"], json!("classes/0x2.json"));
+        assert_eq!(value["transactions"][1]["type"], json!("invoke"));
+        assert_eq!(value["transactions"][1]["calls"][0]["entrypoint"], json!("register_model"));
+        assert_eq!(value["transactions"][1]["calls"][0]["calldata"], json!(["0x2"]));
+        assert_eq!(value["expected"]["models"][0]["tag"], json!("ns-position"));
+    }
+
+    #[test]
+    fn plan_write_and_load() {
+        let dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap().join("plan");
+
+        plan().write(&dir).unwrap();
+        assert_eq!(MigrationPlan::load(&dir).unwrap(), plan());
+    }
+
+    #[test]
+    fn load_rejects_unknown_version() {
+        let dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        MigrationPlan { version: PLAN_VERSION + 1, ..plan() }.write(dir).unwrap();
+        assert!(MigrationPlan::load(dir).is_err());
+    }
+
+    #[test]
+    fn decodes_execute_calldata() {
+        let calldata = vec![
+            felt!("0x2"),
+            felt!("0x1"),
+            selector!("register_model"),
+            felt!("0x1"),
+            felt!("0x2"),
+            felt!("0x1"),
+            selector!("register_namespace"),
+            felt!("0x0"),
+        ];
+
+        let calls = decode_execute_calldata(&calldata).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].selector, selector!("register_model"));
+        assert_eq!(calls[0].calldata, vec![felt!("0x2")]);
+        assert!(calls[1].calldata.is_empty());
+
+        // the calldata of a call can't overflow the calldata of the transaction
+        assert!(decode_execute_calldata(&calldata[..4]).is_none());
+        // and nothing is left after the calls
+        let trailing = [calldata, vec![felt!("0x1")]].concat();
+        assert!(decode_execute_calldata(&trailing).is_none());
+    }
+
+    #[test]
+    fn checks_signed_transactions_match_plan() {
+        let plan = MigrationPlan { transactions: vec![plan().transactions[1].clone()], ..plan() };
+
+        let invoke = |calldata: Vec<Felt>| {
+            BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(
+                BroadcastedInvokeTransactionV1 {
+                    sender_address: felt!("0x1234"),
+                    calldata,
+                    max_fee: felt!("0x1000"),
+                    signature: vec![],
+                    nonce: felt!("0x0"),
+                    is_query: false,
+                },
+            ))
+        };
+
+        let planned = vec![felt!("0x1"), felt!("0x1"), selector!("register_model"), felt!("0x1")];
+        check_transactions(&plan, &[invoke([planned.clone(), vec![felt!("0x2")]].concat())])
+            .unwrap();
+
+        // other calldata
+        assert!(
+            check_transactions(&plan, &[invoke([planned, vec![felt!("0x3")]].concat())]).is_err()
+        );
+        // missing transaction
+        assert!(check_transactions(&plan, &[]).is_err());
+    }
+}
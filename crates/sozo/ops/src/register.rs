@@ -5,9 +5,8 @@ use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::{WorldContract, WorldContractReader};
 use dojo_world::manifest::DeploymentManifest;
 use dojo_world::migration::TxnConfig;
-use dojo_world::utils::TransactionExt;
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer};
 use scarb::core::Config;
-use starknet::accounts::ConnectedAccount;
 use starknet::core::types::Felt;
 use starknet::providers::Provider;
 
@@ -22,7 +21,7 @@ pub async fn model_register<A, P>(
     config: &Config,
 ) -> Result<()>
 where
-    A: ConnectedAccount + Sync + Send + 'static,
+    A: TransactionV3Signer + Sync + Send + 'static,
     P: Provider + Sync + Send,
 {
     let manifest = {
@@ -64,10 +63,7 @@ where
         .map(|c| world.register_model_getcall(&(*c).into()))
         .collect::<Vec<_>>();
 
-    let res = world
-        .account
-        .execute_v1(calls)
-        .send_with_cfg(&txn_config)
+    let res = execute_with_cfg(&world.account, calls, &txn_config)
         .await
         .with_context(|| "Failed to send transaction")?;
 
@@ -0,0 +1,117 @@
+use anyhow::{bail, Context, Result};
+use dojo_world::contracts::world::WorldContract;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::migration::TxnConfig;
+use dojo_world::utils::{execute_with_cfg, TransactionV3Signer};
+use scarb_ui::Ui;
+use starknet::core::types::{BlockId, BlockTag, Felt};
+
+use crate::call::call_output;
+use crate::execute::{resolve_calls, ContractCall};
+use crate::utils;
+
+/// A step of a script.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Executes the call, batched with the following executions in a multicall transaction.
+    Execute(ContractCall),
+    /// Calls a view entrypoint, failing the script if its output differs from the expected one.
+    Call { call: ContractCall, expected: Option<Vec<Felt>> },
+}
+
+/// Runs the steps of a script in order.
+///
+/// The consecutive executions are sent in multicall transactions of at most `batch_size` calls,
+/// and the calls only run once the previous executions are accepted so that they read their
+/// state changes. The script stops at the first reverted transaction or failed assertion.
+pub async fn run<A>(
+    ui: &Ui,
+    steps: Vec<Step>,
+    batch_size: usize,
+    world: &WorldContract<A>,
+    txn_config: &TxnConfig,
+) -> Result<()>
+where
+    A: TransactionV3Signer + Sync + Send + 'static,
+{
+    if batch_size == 0 {
+        bail!("The batch size must be at least 1.");
+    }
+
+    let world_reader = WorldContractReader::new(world.address, world.account.provider());
+    let mut batch = Vec::new();
+
+    for (index, step) in steps.into_iter().enumerate() {
+        match step {
+            Step::Execute(call) => {
+                batch.push(call);
+                if batch.len() == batch_size {
+                    execute_batch(ui, world, std::mem::take(&mut batch), txn_config).await?;
+                }
+            }
+            Step::Call { call, expected } => {
+                execute_batch(ui, world, std::mem::take(&mut batch), txn_config).await?;
+
+                let output = call_output(
+                    &world_reader,
+                    call.tag_or_address.clone(),
+                    &call.entrypoint,
+                    call.calldata,
+                    BlockId::Tag(BlockTag::Pending),
+                )
+                .await
+                .with_context(|| format!("Step {index} failed"))?;
+
+                ui.print(format!(
+                    "Step {index}: {}::{} = {}",
+                    call.tag_or_address,
+                    call.entrypoint,
+                    format_felts(&output)
+                ));
+
+                if let Some(expected) = expected {
+                    if output != expected {
+                        bail!(
+                            "Step {index}: `{}::{}` returned {}, expected {}.",
+                            call.tag_or_address,
+                            call.entrypoint,
+                            format_felts(&output),
+                            format_felts(&expected)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    execute_batch(ui, world, batch, txn_config).await
+}
+
+async fn execute_batch<A>(
+    ui: &Ui,
+    world: &WorldContract<A>,
+    calls: Vec<ContractCall>,
+    txn_config: &TxnConfig,
+) -> Result<()>
+where
+    A: TransactionV3Signer + Sync + Send + 'static,
+{
+    if calls.is_empty() {
+        return Ok(());
+    }
+
+    ui.print(format!("Executing {} call(s) in a multicall transaction.", calls.len()));
+
+    let calls = resolve_calls(world, calls).await?;
+    let res = execute_with_cfg(&world.account, calls, txn_config)
+        .await
+        .with_context(|| "Failed to send transaction")?;
+
+    // always waited for, the next steps depending on the state changes of this transaction
+    utils::handle_transaction_result(ui, &world.account.provider(), res, true, txn_config.receipt)
+        .await
+}
+
+fn format_felts(felts: &[Felt]) -> String {
+    format!("[ {} ]", felts.iter().map(|f| format!("{:#x}", f)).collect::<Vec<_>>().join(" "))
+}
@@ -0,0 +1,50 @@
+use dojo_test_utils::migration::copy_spawn_and_move_db;
+use dojo_world::contracts::naming::compute_selector_from_tag;
+use katana_runner::{KatanaRunner, KatanaRunnerConfig};
+use starknet::accounts::Account;
+use starknet::core::utils::starknet_keccak;
+
+use crate::inspect::{self, ResourceStatus};
+use crate::test_utils::setup;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inspect_spawn_and_move_world() {
+    let seq_config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(seq_config).expect("Failed to start runner.");
+
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+    let config = setup::load_config();
+    let root_dir = config.manifest_path().parent().unwrap().to_path_buf();
+
+    let report = inspect::inspect(sequencer.provider(), world.address, &root_dir, "dev", true)
+        .await
+        .unwrap();
+
+    assert_eq!(report.world.status, ResourceStatus::Synced);
+    assert!(report.world.owners.contains(&world.account.address()));
+
+    let namespace = report.namespaces.iter().find(|n| n.name == "dojo_examples").unwrap();
+    assert_eq!(namespace.status, ResourceStatus::Synced);
+    assert!(!namespace.owners.is_empty());
+
+    let moves = report.models.iter().find(|m| m.name == "dojo_examples-Moves").unwrap();
+    assert_eq!(moves.selector, compute_selector_from_tag("dojo_examples-Moves"));
+    assert_eq!(moves.status, ResourceStatus::Synced);
+    assert!(moves.address.is_some());
+    // the owners of the namespace own its models
+    assert!(namespace.owners.iter().all(|o| moves.owners.contains(o)));
+
+    let actions = report.contracts.iter().find(|c| c.name == "dojo_examples-actions").unwrap();
+    assert_eq!(actions.status, ResourceStatus::Synced);
+    assert!(actions.address.is_some());
+    assert_eq!(actions.remote_class_hash, actions.local_class_hash);
+    assert!(namespace.owners.iter().all(|o| actions.owners.contains(o)));
+
+    let upgraded = report.events.iter().find(|e| e.name.ends_with("::Upgraded")).unwrap();
+    assert_eq!(upgraded.selector, starknet_keccak(b"Upgraded"));
+    assert!(upgraded.contracts.contains(&"dojo_examples-actions".to_string()));
+
+    let spawned = report.events.iter().find(|e| e.name.ends_with("::WorldSpawned")).unwrap();
+    assert_eq!(spawned.contracts, vec!["world".to_string()]);
+}
@@ -1,9 +1,15 @@
 #![allow(dead_code)]
+use std::collections::HashMap;
+use std::fs;
 use std::str::{self, FromStr};
+use std::sync::Arc;
 
+use assert_fs::TempDir;
 use cainome::cairo_serde::ContractAddress;
 use camino::Utf8Path;
 use dojo_test_utils::migration::prepare_migration_with_world_and_seed;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Ty};
 use dojo_world::contracts::naming::{compute_bytearray_hash, compute_selector_from_tag};
 use dojo_world::contracts::{WorldContract, WorldContractReader};
 use dojo_world::manifest::{
@@ -14,25 +20,37 @@ use dojo_world::metadata::{
     dojo_metadata_from_workspace, get_default_namespace_from_ws, ArtifactMetadata, DojoMetadata,
     WorldMetadata, IPFS_CLIENT_URL, IPFS_PASSWORD, IPFS_USERNAME,
 };
-use dojo_world::migration::strategy::{prepare_for_migration, MigrationMetadata};
+use dojo_world::migration::compatibility::{local_model_schema, ModelSchema, SchemaChange};
+use dojo_world::migration::strategy::{
+    prepare_for_migration, MigrationMetadata, MigrationStrategy,
+};
 use dojo_world::migration::world::WorldDiff;
 use dojo_world::migration::TxnConfig;
 use dojo_world::uri::Uri;
 use futures::TryStreamExt;
 use ipfs_api_backend_hyper::{HyperBackend, IpfsApi, IpfsClient, TryFromUri};
 use katana_runner::{KatanaRunner, KatanaRunnerConfig};
-use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, BroadcastedDeclareTransaction, BroadcastedInvokeTransaction,
+    BroadcastedTransaction, Felt, FlattenedSierraClass,
+};
 use starknet::macros::felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 
 use crate::auth::ResourceType;
+use crate::migration::journal::{MigrationJournal, MigrationStep};
+use crate::migration::plan::{submit, verify_plan, MigrationPlan, PlannedTransaction};
 use crate::migration::{
-    auto_authorize, execute_strategy, find_authorization_diff, upload_metadata,
+    auto_authorize, execute_journaled_strategy, execute_strategy, export_plan,
+    find_authorization_diff, upload_metadata,
 };
 use crate::test_utils::setup;
 use crate::utils::get_contract_address_from_reader;
 
+const MOVES_TAG: &str = "dojo_examples-Moves";
+
 #[tokio::test(flavor = "multi_thread")]
 async fn default_migrate_no_dry_run() {
     let config = setup::load_config();
@@ -54,6 +72,7 @@ async fn default_migrate_no_dry_run() {
         false,
         TxnConfig::init_wait(),
         None,
+        false,
     )
     .await
     .is_ok();
@@ -128,6 +147,8 @@ async fn metadata_calculated_properly() {
         felt!("0x12345"),
         &Utf8Path::new(&target_dir).to_path_buf(),
         world,
+        &HashMap::new(),
+        false,
     )
     .unwrap();
 
@@ -169,6 +190,8 @@ async fn migration_with_correct_calldata_second_time_work_as_expected() {
         felt!("0x12345"),
         &Utf8Path::new(&target_dir).to_path_buf(),
         world,
+        &HashMap::new(),
+        false,
     )
     .unwrap();
 
@@ -203,6 +226,8 @@ async fn migration_with_correct_calldata_second_time_work_as_expected() {
         felt!("0x12345"),
         &Utf8Path::new(&target_dir).to_path_buf(),
         world,
+        &HashMap::new(),
+        false,
     )
     .unwrap();
 
@@ -214,6 +239,74 @@ async fn migration_with_correct_calldata_second_time_work_as_expected() {
     assert!(migration_output.full);
 }
 
+#[test]
+fn breaking_model_upgrade_aborts_without_force() {
+    let config = setup::load_config();
+    let ws = setup::setup_ws(&config);
+
+    let base = config.manifest_path().parent().unwrap();
+    let target_dir = Utf8Path::new(base).join("target").join("dev");
+
+    let profile_name = ws.current_profile().unwrap().to_string();
+    let manifest = BaseManifest::load_from_path(
+        &base.to_path_buf().join(MANIFESTS_DIR).join(&profile_name).join(BASE_DIR),
+    )
+    .unwrap();
+    let default_namespace = get_default_namespace_from_ws(&ws).unwrap();
+
+    // the World is already deployed, and only `Moves` is upgraded.
+    let mut diff = WorldDiff::compute(manifest, None, &default_namespace).unwrap();
+    diff.world.remote_class_hash = Some(diff.world.local_class_hash);
+    diff.base.remote_class_hash = Some(diff.base.local_class_hash);
+    for model in &mut diff.models {
+        model.remote_class_hash =
+            Some(if model.tag == MOVES_TAG { Felt::ONE } else { model.local_class_hash });
+    }
+
+    let prepare = |remote_models: &HashMap<String, ModelSchema>, force: bool| {
+        prepare_for_migration(
+            Some(felt!("0x1234")),
+            felt!("0x12345"),
+            &target_dir,
+            diff.clone(),
+            remote_models,
+            force,
+        )
+    };
+
+    let moves = |strategy: &MigrationStrategy| {
+        strategy.models.iter().find(|m| m.diff.tag == MOVES_TAG).unwrap().clone()
+    };
+
+    // the schema registered in the World is the one of the new class.
+    let strategy = prepare(&HashMap::new(), false).unwrap();
+    let local = local_model_schema(&moves(&strategy).artifact_path).unwrap();
+    assert_eq!(local.ty.name(), "Moves");
+
+    let remote_models = HashMap::from([(MOVES_TAG.to_string(), local.clone())]);
+    let strategy = prepare(&remote_models, false).unwrap();
+    assert!(moves(&strategy).schema_changes.is_empty());
+
+    // a member of the registered schema is removed by the new class.
+    let mut remote = local;
+    let Ty::Struct(schema) = &mut remote.ty else { panic!("Moves should be a struct") };
+    schema.children.push(Member {
+        name: "removed".to_string(),
+        ty: Ty::Primitive(Primitive::U8(None)),
+        key: false,
+    });
+    let remote_models = HashMap::from([(MOVES_TAG.to_string(), remote)]);
+
+    let err = prepare(&remote_models, false).unwrap_err();
+    assert!(err.to_string().contains(MOVES_TAG));
+
+    let strategy = prepare(&remote_models, true).unwrap();
+    assert_eq!(
+        moves(&strategy).schema_changes,
+        vec![SchemaChange::Breaking("`Moves.removed` removed".to_string())]
+    );
+}
+
 #[tokio::test]
 async fn migration_from_remote() {
     let config = setup::load_config();
@@ -242,6 +335,8 @@ async fn migration_from_remote() {
         felt!("0x12345"),
         &Utf8Path::new(&target_dir).to_path_buf(),
         world,
+        &HashMap::new(),
+        false,
     )
     .unwrap();
 
@@ -439,6 +534,168 @@ async fn migration_with_mismatching_world_address_and_seed() {
     assert_ne!(strategy.world_address, strategy.world.unwrap().contract_address);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn migration_resumes_from_journal() {
+    let config = setup::load_config();
+    let ws = setup::setup_ws(&config);
+    let ui = config.ui();
+
+    let (migration, diff) = setup::setup_migration(&config, "dojo_examples").unwrap();
+    let default_namespace = get_default_namespace_from_ws(&ws).unwrap();
+
+    let sequencer =
+        KatanaRunner::new_with_config(KatanaRunnerConfig { n_accounts: 10, ..Default::default() })
+            .expect("Failed to start runner.");
+
+    let mut account = sequencer.account(0);
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+    let world = WorldContract::new(migration.world_address, &account);
+
+    let dir = TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+    let mut journal = MigrationJournal::load(dir, migration.world_address).unwrap();
+
+    // The migration is interrupted before the contracts are deployed.
+    let interrupted = MigrationStrategy { contracts: vec![], ..migration.clone() };
+    execute_journaled_strategy(
+        &ws,
+        &interrupted,
+        &account,
+        TxnConfig::init_wait(),
+        &[],
+        &mut journal,
+    )
+    .await
+    .unwrap();
+
+    let mut journal = MigrationJournal::load(dir, migration.world_address).unwrap();
+    assert!(journal.is_completed(MigrationStep::Deploy, "world"));
+    for m in &migration.models {
+        assert!(journal.is_completed(MigrationStep::Register, &m.diff.tag));
+    }
+    assert_eq!(journal.completed(MigrationStep::Deploy).count(), 1);
+    journal.verify(&ui, &world, &diff, &default_namespace).await.unwrap();
+
+    // The resumed migration only applies the remaining steps, the namespaces registered by the
+    // interrupted one being skipped.
+    let remaining =
+        MigrationStrategy { world: None, base: None, models: vec![], ..migration.clone() };
+    let output = execute_journaled_strategy(
+        &ws,
+        &remaining,
+        &account,
+        TxnConfig::init_wait(),
+        &[],
+        &mut journal,
+    )
+    .await
+    .unwrap();
+
+    assert!(output.full);
+    assert_eq!(output.contracts.iter().flatten().count(), migration.contracts.len());
+    for c in &migration.contracts {
+        assert!(journal.is_completed(MigrationStep::Deploy, &c.diff.tag));
+    }
+    journal.verify(&ui, &world, &diff, &default_namespace).await.unwrap();
+
+    // A journal not matching the chain state is rejected.
+    let writer =
+        format!("model:{},{}", migration.models[0].diff.tag, migration.contracts[0].diff.tag);
+    let mut tampered = MigrationJournal::new(migration.world_address);
+    tampered.entries = journal.entries.clone();
+    tampered.record(MigrationStep::Grant, writer, journal.entries[0].transaction_hash).unwrap();
+    assert!(tampered.verify(&ui, &world, &diff, &default_namespace).await.is_err());
+
+    let transaction_hash = journal.entries[0].transaction_hash;
+    let mut tampered = MigrationJournal::new(migration.world_address);
+    tampered.record(MigrationStep::Deploy, "dojo_examples-unknown", transaction_hash).unwrap();
+    assert!(tampered.verify(&ui, &world, &diff, &default_namespace).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn migrate_from_exported_plan() {
+    let config = setup::load_config();
+    let ws = setup::setup_ws(&config);
+    let ui = config.ui();
+
+    let sequencer =
+        KatanaRunner::new_with_config(KatanaRunnerConfig { n_accounts: 10, ..Default::default() })
+            .expect("Failed to start runner.");
+    let provider = sequencer.provider();
+
+    let mut account = sequencer.account(0);
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    let dir = TempDir::new().unwrap();
+    let plan_dir = Utf8Path::from_path(dir.path()).unwrap().join("plan");
+
+    export_plan(&ws, None, &account, "dojo_examples", None, false, &plan_dir).await.unwrap();
+    let plan = MigrationPlan::load(&plan_dir).unwrap();
+
+    // The permissions and the initialization of the new world are planned as well.
+    assert!(!plan.expected.writers.is_empty());
+    assert!(!plan.expected.initialized.is_empty());
+
+    // The owner of the world signs the planned transactions without sozo.
+    let max_fee = felt!("0xde0b6b3a7640000");
+    let mut nonce = account.get_nonce().await.unwrap();
+    let mut transactions = vec![];
+
+    for tx in &plan.transactions {
+        let tx = match tx {
+            PlannedTransaction::Declare { compiled_class_hash, contract_class, .. } => {
+                let class = fs::read_to_string(plan_dir.join(contract_class)).unwrap();
+                let class: FlattenedSierraClass = serde_json::from_str(&class).unwrap();
+
+                let request = account
+                    .declare_v2(Arc::new(class), *compiled_class_hash)
+                    .nonce(nonce)
+                    .max_fee(max_fee)
+                    .prepared()
+                    .unwrap()
+                    .get_declare_request(false)
+                    .await
+                    .unwrap();
+                BroadcastedTransaction::Declare(BroadcastedDeclareTransaction::V2(request))
+            }
+            PlannedTransaction::Invoke { calls, .. } => {
+                let request = account
+                    .execute_v1(calls.iter().map(Call::from).collect())
+                    .nonce(nonce)
+                    .max_fee(max_fee)
+                    .prepared()
+                    .unwrap()
+                    .get_invoke_request(false)
+                    .await
+                    .unwrap();
+                BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(request))
+            }
+        };
+
+        transactions.push(tx);
+        nonce += Felt::ONE;
+    }
+
+    let transactions_path = plan_dir.join("transactions.json");
+
+    // Transactions not matching the plan are not sent.
+    let mut reordered = transactions.clone();
+    reordered.swap(0, 1);
+    fs::write(&transactions_path, serde_json::to_string(&reordered).unwrap()).unwrap();
+    assert!(submit(&ui, provider, &plan, &transactions_path).await.is_err());
+
+    // Neither are the transactions of a plan of another chain.
+    fs::write(&transactions_path, serde_json::to_string(&transactions).unwrap()).unwrap();
+    let other_chain = MigrationPlan { chain_id: felt!("0x1234"), ..plan.clone() };
+    assert!(submit(&ui, provider, &other_chain, &transactions_path).await.is_err());
+    assert!(verify_plan(&ui, provider, &other_chain).await.is_err());
+
+    assert!(verify_plan(&ui, provider, &plan).await.is_err());
+
+    submit(&ui, provider, &plan, &transactions_path).await.unwrap();
+    verify_plan(&ui, provider, &plan).await.unwrap();
+}
+
 /// Get the hash from a IPFS URI
 ///
 /// # Arguments
@@ -1,5 +1,8 @@
 mod auth;
 mod call;
+mod inspect;
 mod migration;
 mod model;
+mod run;
+mod transaction;
 mod utils;
@@ -0,0 +1,67 @@
+use dojo_test_utils::migration::copy_spawn_and_move_db;
+use dojo_world::contracts::naming::compute_bytearray_hash;
+use dojo_world::contracts::world::WorldContract;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::migration::TxnConfig;
+use katana_runner::{KatanaRunner, KatanaRunnerConfig};
+use scarb_ui::{OutputFormat, Ui, Verbosity};
+use starknet::accounts::Account;
+use starknet::core::types::Felt;
+
+use crate::execute::ContractCall;
+use crate::run::{self, Step};
+use crate::test_utils::setup;
+
+const CONTRACT_TAG: &str = "dojo_examples-actions";
+
+fn contract_call(entrypoint: &str, calldata: Vec<Felt>) -> ContractCall {
+    ContractCall {
+        tag_or_address: CONTRACT_TAG.to_string(),
+        entrypoint: entrypoint.to_string(),
+        calldata,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn run_steps_with_assertions() {
+    let config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+    let world = WorldContract::new(world.address, sequencer.account(0));
+    let world_reader = WorldContractReader::new(world.address, sequencer.provider());
+    let ui = Ui::new(Verbosity::Normal, OutputFormat::Text);
+    let caller = world.account.address();
+
+    // the migrator owns the namespace of the example
+    let is_owner = |expected: Felt| Step::Call {
+        call: ContractCall {
+            tag_or_address: format!("{:#x}", world.address),
+            entrypoint: "is_owner".to_string(),
+            calldata: vec![compute_bytearray_hash("dojo_examples"), caller],
+        },
+        expected: Some(vec![expected]),
+    };
+
+    let steps = vec![
+        Step::Execute(contract_call("spawn", vec![])),
+        // left, then down
+        Step::Execute(contract_call("move", vec![Felt::ONE])),
+        is_owner(Felt::ONE),
+        Step::Execute(contract_call("move", vec![Felt::from(4)])),
+    ];
+    run::run(&ui, steps, 2, &world, &TxnConfig::init_wait()).await.unwrap();
+
+    // the calls are made by the account, moving its position from the spawn one of (10, 10)
+    let position = world_reader.model_reader("dojo_examples", "Position").await.unwrap();
+    assert_eq!(
+        position.entity_storage(&[caller]).await.unwrap(),
+        vec![Felt::from(9), Felt::from(11)]
+    );
+
+    assert!(run::run(&ui, vec![is_owner(Felt::ZERO)], 2, &world, &TxnConfig::init_wait())
+        .await
+        .is_err());
+
+    assert!(run::run(&ui, vec![], 0, &world, &TxnConfig::init_wait()).await.is_err());
+}
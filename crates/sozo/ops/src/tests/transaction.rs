@@ -0,0 +1,146 @@
+use dojo_test_utils::migration::copy_spawn_and_move_db;
+use dojo_world::contracts::abi::world::Resource;
+use dojo_world::contracts::naming::compute_selector_from_tag;
+use dojo_world::contracts::world::WorldContract;
+use dojo_world::migration::{TxnConfig, TxnV3Config};
+use dojo_world::utils::{execute_with_cfg, TransactionError, TransactionWaiter};
+use katana_runner::{KatanaRunner, KatanaRunnerConfig};
+use num_traits::ToPrimitive;
+use scarb_ui::{OutputFormat, Ui, Verbosity};
+use starknet::accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::{
+    Felt, InvokeTransaction, InvokeTransactionV3, PriceUnit, Transaction, TransactionReceipt,
+};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider};
+use starknet::signers::LocalWallet;
+
+use crate::auth::{self, ResourceOwner, ResourceType};
+use crate::test_utils::setup;
+
+type Account0 = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+const ACTION_CONTRACT_TAG: &str = "dojo_examples-actions";
+
+fn v3(v3: TxnV3Config) -> TxnConfig {
+    TxnConfig { wait: true, v3: Some(v3), ..Default::default() }
+}
+
+async fn spawn_call(world: &WorldContract<Account0>) -> Call {
+    let resource = world.resource(&compute_selector_from_tag(ACTION_CONTRACT_TAG)).call().await;
+    let Resource::Contract((_, address)) = resource.unwrap() else {
+        panic!("No action contract found in world");
+    };
+
+    Call { to: address.0, selector: get_selector_from_name("spawn").unwrap(), calldata: vec![] }
+}
+
+// The V3 transaction of the hash, checking its fees were paid in STRK.
+async fn v3_transaction(
+    world: &WorldContract<Account0>,
+    transaction_hash: Felt,
+) -> InvokeTransactionV3 {
+    let provider = world.account.provider();
+    let receipt = TransactionWaiter::new(transaction_hash, provider).await.unwrap();
+    let TransactionReceipt::Invoke(receipt) = receipt.receipt else {
+        panic!("expected an invoke receipt");
+    };
+    assert_eq!(receipt.actual_fee.unit, PriceUnit::Fri);
+
+    match provider.get_transaction_by_hash(transaction_hash).await.unwrap() {
+        Transaction::Invoke(InvokeTransaction::V3(tx)) => tx,
+        tx => panic!("expected a V3 invoke transaction, got {tx:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn execute_v3_with_estimated_gas() {
+    let config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+    let call = spawn_call(&world).await;
+    let estimate = world.account.execute_v3(vec![call.clone()]).estimate_fee().await.unwrap();
+    let estimated_gas = estimate.gas_consumed.to_u64().unwrap();
+
+    // the multiplier is applied to the estimated gas amount, instead of the fee multiplier
+    let config = TxnConfig {
+        fee_estimate_multiplier: Some(1.0),
+        ..v3(TxnV3Config { gas_estimate_multiplier: Some(3.0), ..Default::default() })
+    };
+    let res = execute_with_cfg(&world.account, vec![call], &config).await.unwrap();
+
+    let tx = v3_transaction(&world, res.transaction_hash).await;
+    assert!(tx.resource_bounds.l1_gas.max_amount >= estimated_gas * 2);
+    assert_eq!(tx.tip, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn execute_v3_with_manual_gas() {
+    let config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+    let call = spawn_call(&world).await;
+    let estimate = world.account.execute_v3(vec![call.clone()]).estimate_fee().await.unwrap();
+    let l1_gas = estimate.gas_consumed.to_u64().unwrap() * 2;
+    let l1_gas_price = estimate.gas_price.to_u128().unwrap() * 2;
+
+    let config = v3(TxnV3Config {
+        l1_gas: Some(l1_gas),
+        l1_gas_price: Some(l1_gas_price),
+        ..Default::default()
+    });
+    let res = execute_with_cfg(&world.account, vec![call], &config).await.unwrap();
+
+    let tx = v3_transaction(&world, res.transaction_hash).await;
+    assert_eq!(tx.resource_bounds.l1_gas.max_amount, l1_gas);
+    assert_eq!(tx.resource_bounds.l1_gas.max_price_per_unit, l1_gas_price);
+
+    // the transaction fails when its bounds can't cover its fee
+    let config =
+        v3(TxnV3Config { l1_gas: Some(1), l1_gas_price: Some(l1_gas_price), ..Default::default() });
+    assert!(execute_with_cfg(&world.account, vec![spawn_call(&world).await], &config)
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn execute_v3_with_tip_requires_a_v3_signer() {
+    let config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+    // `SingleOwnerAccount` only signs the V3 transactions it builds, without tip
+    let config = v3(TxnV3Config { tip: 1, ..Default::default() });
+    let res = execute_with_cfg(&world.account, vec![spawn_call(&world).await], &config).await;
+    assert!(matches!(res, Err(TransactionError::UnsupportedV3Settings)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn auth_grant_owner_v3() {
+    let config = KatanaRunnerConfig { n_accounts: 10, ..Default::default() }
+        .with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(config).expect("Failed to start runner.");
+    let world = setup::setup_with_world(&sequencer).await.unwrap();
+
+    let other = sequencer.account(1).address();
+    let owner = ResourceOwner {
+        resource: "model:dojo_examples-Moves".parse::<ResourceType>().unwrap(),
+        owner: other,
+    };
+
+    // the write paths of the world send V3 transactions as well
+    let ui = Ui::new(Verbosity::Normal, OutputFormat::Text);
+    auth::grant_owner(&ui, &world, &[owner], v3(TxnV3Config::default()), "dojo_examples")
+        .await
+        .unwrap();
+
+    let is_owner = world
+        .is_owner(&compute_selector_from_tag("dojo_examples-Moves"), &other.into())
+        .call()
+        .await
+        .unwrap();
+    assert!(is_owner);
+}
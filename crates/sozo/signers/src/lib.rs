@@ -3,6 +3,8 @@ use std::env;
 use starknet::core::types::Felt;
 use starknet::signers::{LocalWallet, SigningKey};
 
+pub mod remote;
+
 pub trait FromEnv {
     fn from_env() -> anyhow::Result<Self>
     where
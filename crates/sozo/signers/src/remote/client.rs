@@ -0,0 +1,74 @@
+use serde::de::DeserializeOwned;
+use starknet::core::types::Felt;
+use url::Url;
+
+use super::{ErrorResponse, PublicKeyResponse, SignRequest, SignResponse};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteSignerError {
+    #[error("Failed to reach the remote signer: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Remote signer rejected the request ({status}): {message}")]
+    Rejected { status: u16, message: String },
+
+    #[error("{0} are not supported by remote signers.")]
+    Unsupported(&'static str),
+}
+
+/// Client of a remote signer, see the [module](super) documentation for the protocol.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: Url,
+    /// The token authenticating to the signer.
+    token: String,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: Url, token: String) -> Self {
+        Self { url, token, client: reqwest::Client::new() }
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub async fn public_key(&self) -> Result<Felt, RemoteSignerError> {
+        let response =
+            self.client.get(self.endpoint("public_key")).bearer_auth(&self.token).send().await?;
+        let response: PublicKeyResponse = Self::parse(response).await?;
+        Ok(response.public_key)
+    }
+
+    /// Requests the signature of a transaction.
+    pub async fn sign(&self, request: &SignRequest) -> Result<Vec<Felt>, RemoteSignerError> {
+        let response = self
+            .client
+            .post(self.endpoint("sign"))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?;
+        let response: SignResponse = Self::parse(response).await?;
+        Ok(response.signature)
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{path}", self.url.as_str().trim_end_matches('/'))
+    }
+
+    async fn parse<T: DeserializeOwned>(
+        response: reqwest::Response,
+    ) -> Result<T, RemoteSignerError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+
+        let body = response.text().await?;
+        let message = serde_json::from_str::<ErrorResponse>(&body).map(|e| e.error).unwrap_or(body);
+
+        Err(RemoteSignerError::Rejected { status: status.as_u16(), message })
+    }
+}
@@ -0,0 +1,589 @@
+//! A small HTTP/JSON protocol to request signatures from a signer running on another host.
+//!
+//! The signing host exposes two endpoints:
+//!
+//! - `GET /public_key` returns the public key of the signer, as `{ "public_key": "0x..." }`.
+//! - `POST /sign` takes a [`SignRequest`] and returns the signature, as `{ "signature": [...] }`.
+//!
+//! Both endpoints require the token the signer is served with, as an `Authorization: Bearer`
+//! header, and answer `401 Unauthorized` without it.
+//!
+//! A request carries the hash to sign along with the transaction it has been computed from. The
+//! signer recomputes the hash from the transaction and checks the transaction against its
+//! [`SignerPolicy`] before signing, so a client can't get an arbitrary hash signed by describing
+//! an allowed transaction. Failures are returned with a non-success status, as
+//! `{ "error": "..." }`.
+
+use serde::{Deserialize, Serialize};
+use starknet::accounts::ExecutionEncoding;
+use starknet::core::crypto::compute_hash_on_elements;
+use starknet::core::types::{DataAvailabilityMode, Felt};
+use starknet::macros::{felt, short_string};
+use starknet_crypto::poseidon_hash_many;
+
+mod client;
+mod policy;
+mod server;
+
+pub use client::{RemoteSigner, RemoteSignerError};
+pub use policy::{PolicyViolation, SignerPolicy};
+pub use server::serve;
+
+/// Cairo string for "invoke".
+const PREFIX_INVOKE: Felt = short_string!("invoke");
+
+/// Cairo string for "declare".
+const PREFIX_DECLARE: Felt = short_string!("declare");
+
+/// 2^128, added to the version of transactions only meant for fee estimation or simulation.
+const QUERY_VERSION_OFFSET: Felt = felt!("0x100000000000000000000000000000000");
+
+/// Body of a `POST /sign` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignRequest {
+    /// The hash the client expects to be signed.
+    pub transaction_hash: Felt,
+    pub transaction: Transaction,
+}
+
+/// Body of a successful `POST /sign` response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: Vec<Felt>,
+}
+
+/// Body of a successful `GET /public_key` response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyResponse {
+    pub public_key: Felt,
+}
+
+/// Body of a failed response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// The transaction a signature is requested for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transaction {
+    InvokeV1(InvokeV1),
+    InvokeV3(InvokeV3),
+    DeclareV2(DeclareV2),
+    DeclareV3(DeclareV3),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvokeV1 {
+    pub sender_address: Felt,
+    pub chain_id: Felt,
+    pub nonce: Felt,
+    pub max_fee: Felt,
+    pub calls: Vec<Call>,
+    pub encoding: CallEncoding,
+    pub query_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvokeV3 {
+    pub sender_address: Felt,
+    pub chain_id: Felt,
+    pub nonce: Felt,
+    pub gas: u64,
+    pub gas_price: u128,
+    #[serde(default)]
+    pub tip: u64,
+    #[serde(default = "l1_data_availability_mode")]
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    #[serde(default = "l1_data_availability_mode")]
+    pub fee_data_availability_mode: DataAvailabilityMode,
+    pub calls: Vec<Call>,
+    pub encoding: CallEncoding,
+    pub query_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclareV2 {
+    pub sender_address: Felt,
+    pub chain_id: Felt,
+    pub nonce: Felt,
+    pub max_fee: Felt,
+    pub class_hash: Felt,
+    pub compiled_class_hash: Felt,
+    pub query_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclareV3 {
+    pub sender_address: Felt,
+    pub chain_id: Felt,
+    pub nonce: Felt,
+    pub gas: u64,
+    pub gas_price: u128,
+    #[serde(default)]
+    pub tip: u64,
+    #[serde(default = "l1_data_availability_mode")]
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    #[serde(default = "l1_data_availability_mode")]
+    pub fee_data_availability_mode: DataAvailabilityMode,
+    pub class_hash: Felt,
+    pub compiled_class_hash: Felt,
+    pub query_only: bool,
+}
+
+/// A call of a multicall, the same as [`starknet::accounts::Call`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Call {
+    pub to: Felt,
+    pub selector: Felt,
+    pub calldata: Vec<Felt>,
+}
+
+impl From<&starknet::accounts::Call> for Call {
+    fn from(call: &starknet::accounts::Call) -> Self {
+        Self { to: call.to, selector: call.selector, calldata: call.calldata.clone() }
+    }
+}
+
+/// How the account contract expects the calls of a multicall to be encoded in the calldata of
+/// `__execute__`, the same as [`ExecutionEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallEncoding {
+    /// Cairo 0 accounts, where all the calldata is concatenated after the calls.
+    Legacy,
+    /// Cairo 1 accounts, where each call is followed by its calldata.
+    New,
+}
+
+impl From<ExecutionEncoding> for CallEncoding {
+    fn from(encoding: ExecutionEncoding) -> Self {
+        match encoding {
+            ExecutionEncoding::Legacy => Self::Legacy,
+            ExecutionEncoding::New => Self::New,
+        }
+    }
+}
+
+impl CallEncoding {
+    /// Encodes `calls` into the calldata of `__execute__`.
+    pub fn encode(&self, calls: &[Call]) -> Vec<Felt> {
+        let mut calldata = vec![calls.len().into()];
+
+        match self {
+            Self::Legacy => {
+                let mut concatenated = vec![];
+
+                for call in calls {
+                    calldata.push(call.to);
+                    calldata.push(call.selector);
+                    calldata.push(concatenated.len().into()); // data_offset
+                    calldata.push(call.calldata.len().into()); // data_len
+                    concatenated.extend_from_slice(&call.calldata);
+                }
+
+                calldata.push(concatenated.len().into());
+                calldata.extend(concatenated);
+            }
+            Self::New => {
+                for call in calls {
+                    calldata.push(call.to);
+                    calldata.push(call.selector);
+                    calldata.push(call.calldata.len().into());
+                    calldata.extend_from_slice(&call.calldata);
+                }
+            }
+        }
+
+        calldata
+    }
+}
+
+impl Transaction {
+    pub fn sender_address(&self) -> Felt {
+        match self {
+            Self::InvokeV1(tx) => tx.sender_address,
+            Self::InvokeV3(tx) => tx.sender_address,
+            Self::DeclareV2(tx) => tx.sender_address,
+            Self::DeclareV3(tx) => tx.sender_address,
+        }
+    }
+
+    pub fn chain_id(&self) -> Felt {
+        match self {
+            Self::InvokeV1(tx) => tx.chain_id,
+            Self::InvokeV3(tx) => tx.chain_id,
+            Self::DeclareV2(tx) => tx.chain_id,
+            Self::DeclareV3(tx) => tx.chain_id,
+        }
+    }
+
+    /// The calls executed by the transaction, empty for declarations.
+    pub fn calls(&self) -> &[Call] {
+        match self {
+            Self::InvokeV1(tx) => &tx.calls,
+            Self::InvokeV3(tx) => &tx.calls,
+            Self::DeclareV2(_) | Self::DeclareV3(_) => &[],
+        }
+    }
+
+    pub fn is_declare(&self) -> bool {
+        matches!(self, Self::DeclareV2(_) | Self::DeclareV3(_))
+    }
+
+    /// Computes the hash of the transaction, as the account contract will validate the signature
+    /// against it.
+    pub fn transaction_hash(&self) -> Felt {
+        match self {
+            Self::InvokeV1(tx) => compute_hash_on_elements(&[
+                PREFIX_INVOKE,
+                version(Felt::ONE, tx.query_only),
+                tx.sender_address,
+                Felt::ZERO, // entry_point_selector
+                compute_hash_on_elements(&tx.encoding.encode(&tx.calls)),
+                tx.max_fee,
+                tx.chain_id,
+                tx.nonce,
+            ]),
+            Self::InvokeV3(tx) => poseidon_hash_many(&[
+                PREFIX_INVOKE,
+                version(Felt::THREE, tx.query_only),
+                tx.sender_address,
+                hash_fee_fields(tx.tip, tx.gas, tx.gas_price),
+                poseidon_hash_many(&[]), // paymaster_data
+                tx.chain_id,
+                tx.nonce,
+                data_availability_modes(
+                    tx.nonce_data_availability_mode,
+                    tx.fee_data_availability_mode,
+                ),
+                poseidon_hash_many(&[]), // account_deployment_data
+                poseidon_hash_many(&tx.encoding.encode(&tx.calls)),
+            ]),
+            Self::DeclareV2(tx) => compute_hash_on_elements(&[
+                PREFIX_DECLARE,
+                version(Felt::TWO, tx.query_only),
+                tx.sender_address,
+                Felt::ZERO, // entry_point_selector
+                compute_hash_on_elements(&[tx.class_hash]),
+                tx.max_fee,
+                tx.chain_id,
+                tx.nonce,
+                tx.compiled_class_hash,
+            ]),
+            Self::DeclareV3(tx) => poseidon_hash_many(&[
+                PREFIX_DECLARE,
+                version(Felt::THREE, tx.query_only),
+                tx.sender_address,
+                hash_fee_fields(tx.tip, tx.gas, tx.gas_price),
+                poseidon_hash_many(&[]), // paymaster_data
+                tx.chain_id,
+                tx.nonce,
+                data_availability_modes(
+                    tx.nonce_data_availability_mode,
+                    tx.fee_data_availability_mode,
+                ),
+                poseidon_hash_many(&[]), // account_deployment_data
+                tx.class_hash,
+                tx.compiled_class_hash,
+            ]),
+        }
+    }
+}
+
+fn version(version: Felt, query_only: bool) -> Felt {
+    if query_only {
+        QUERY_VERSION_OFFSET + version
+    } else {
+        version
+    }
+}
+
+/// Transactions sent before the data availability modes were part of the protocol use L1.
+fn l1_data_availability_mode() -> DataAvailabilityMode {
+    DataAvailabilityMode::L1
+}
+
+/// Hashes the tip and resource bounds of a V3 transaction, where only L1 gas is bounded.
+fn hash_fee_fields(tip: u64, gas: u64, gas_price: u128) -> Felt {
+    poseidon_hash_many(&[
+        tip.into(),
+        encode_gas_bound(b"L1_GAS", gas, gas_price),
+        encode_gas_bound(b"L2_GAS", 0, 0),
+    ])
+}
+
+/// Packs the nonce and fee data availability modes into a single field, nonce first.
+fn data_availability_modes(nonce: DataAvailabilityMode, fee: DataAvailabilityMode) -> Felt {
+    let mode = |mode| match mode {
+        DataAvailabilityMode::L1 => 0u64,
+        DataAvailabilityMode::L2 => 1u64,
+    };
+
+    Felt::from((mode(nonce) << 32) + mode(fee))
+}
+
+fn encode_gas_bound(name: &[u8], max_amount: u64, max_price_per_unit: u128) -> Felt {
+    let mut buffer = [0u8; 32];
+    let (remainder, max_price) = buffer.split_at_mut(128 / 8);
+    let (gas_kind, amount) = remainder.split_at_mut(64 / 8);
+
+    let padding = gas_kind.len() - name.len();
+    gas_kind[padding..].copy_from_slice(name);
+    amount.copy_from_slice(&max_amount.to_be_bytes());
+    max_price.copy_from_slice(&max_price_per_unit.to_be_bytes());
+
+    Felt::from_bytes_be(&buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use starknet::accounts::{Account, ExecutionEncoder, SingleOwnerAccount};
+    use starknet::core::types::{EntryPointsByType, FlattenedSierraClass};
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::JsonRpcClient;
+    use starknet::signers::{LocalWallet, SigningKey};
+    use url::Url;
+
+    use super::*;
+
+    const CHAIN_ID: Felt = short_string!("SN_SEPOLIA");
+    const SENDER: Felt = felt!("0x1234");
+
+    fn account(
+        encoding: ExecutionEncoding,
+    ) -> SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet> {
+        // The provider is never reached, hashes are computed locally.
+        let provider =
+            JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+        let signer = LocalWallet::from_signing_key(SigningKey::from_secret_scalar(Felt::ONE));
+        SingleOwnerAccount::new(provider, signer, SENDER, CHAIN_ID, encoding)
+    }
+
+    fn calls() -> Vec<starknet::accounts::Call> {
+        vec![
+            starknet::accounts::Call {
+                to: felt!("0x1"),
+                selector: felt!("0x2"),
+                calldata: vec![felt!("0x3"), felt!("0x4")],
+            },
+            starknet::accounts::Call { to: felt!("0x5"), selector: felt!("0x6"), calldata: vec![] },
+            starknet::accounts::Call {
+                to: felt!("0x7"),
+                selector: felt!("0x8"),
+                calldata: vec![felt!("0x9")],
+            },
+        ]
+    }
+
+    fn sierra_class() -> FlattenedSierraClass {
+        FlattenedSierraClass {
+            sierra_program: vec![felt!("0x1"), felt!("0x2")],
+            contract_class_version: "0.1.0".to_string(),
+            entry_points_by_type: EntryPointsByType {
+                constructor: vec![],
+                external: vec![],
+                l1_handler: vec![],
+            },
+            abi: "[]".to_string(),
+        }
+    }
+
+    #[test]
+    fn calls_are_encoded_as_accounts_do() {
+        let remote_calls: Vec<Call> = calls().iter().map(Into::into).collect();
+
+        for encoding in [ExecutionEncoding::Legacy, ExecutionEncoding::New] {
+            assert_eq!(
+                CallEncoding::from(encoding).encode(&remote_calls),
+                account(encoding).encode_calls(&calls())
+            );
+        }
+    }
+
+    #[test]
+    fn invoke_hashes_match_accounts() {
+        for encoding in [ExecutionEncoding::Legacy, ExecutionEncoding::New] {
+            let account = account(encoding);
+
+            for query_only in [false, true] {
+                let expected = account
+                    .execute_v1(calls())
+                    .nonce(felt!("0x3"))
+                    .max_fee(felt!("0x1000"))
+                    .prepared()
+                    .unwrap()
+                    .transaction_hash(query_only);
+
+                let tx = Transaction::InvokeV1(InvokeV1 {
+                    sender_address: SENDER,
+                    chain_id: CHAIN_ID,
+                    nonce: felt!("0x3"),
+                    max_fee: felt!("0x1000"),
+                    calls: calls().iter().map(Into::into).collect(),
+                    encoding: encoding.into(),
+                    query_only,
+                });
+                assert_eq!(tx.transaction_hash(), expected);
+
+                let expected = account
+                    .execute_v3(calls())
+                    .nonce(felt!("0x3"))
+                    .gas(1000)
+                    .gas_price(100_000_000_000)
+                    .prepared()
+                    .unwrap()
+                    .transaction_hash(query_only);
+
+                let tx = Transaction::InvokeV3(InvokeV3 {
+                    sender_address: SENDER,
+                    chain_id: CHAIN_ID,
+                    nonce: felt!("0x3"),
+                    gas: 1000,
+                    gas_price: 100_000_000_000,
+                    tip: 0,
+                    nonce_data_availability_mode: DataAvailabilityMode::L1,
+                    fee_data_availability_mode: DataAvailabilityMode::L1,
+                    calls: calls().iter().map(Into::into).collect(),
+                    encoding: encoding.into(),
+                    query_only,
+                });
+                assert_eq!(tx.transaction_hash(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn declare_hashes_match_accounts() {
+        let account = account(ExecutionEncoding::New);
+        let class = Arc::new(sierra_class());
+        let class_hash = class.class_hash();
+        let compiled_class_hash = felt!("0xc1a55");
+
+        for query_only in [false, true] {
+            let expected = account
+                .declare_v2(class.clone(), compiled_class_hash)
+                .nonce(felt!("0x3"))
+                .max_fee(felt!("0x1000"))
+                .prepared()
+                .unwrap()
+                .transaction_hash(query_only);
+
+            let tx = Transaction::DeclareV2(DeclareV2 {
+                sender_address: SENDER,
+                chain_id: CHAIN_ID,
+                nonce: felt!("0x3"),
+                max_fee: felt!("0x1000"),
+                class_hash,
+                compiled_class_hash,
+                query_only,
+            });
+            assert_eq!(tx.transaction_hash(), expected);
+
+            let expected = account
+                .declare_v3(class.clone(), compiled_class_hash)
+                .nonce(felt!("0x3"))
+                .gas(1000)
+                .gas_price(100_000_000_000)
+                .prepared()
+                .unwrap()
+                .transaction_hash(query_only);
+
+            let tx = Transaction::DeclareV3(DeclareV3 {
+                sender_address: SENDER,
+                chain_id: CHAIN_ID,
+                nonce: felt!("0x3"),
+                gas: 1000,
+                gas_price: 100_000_000_000,
+                tip: 0,
+                nonce_data_availability_mode: DataAvailabilityMode::L1,
+                fee_data_availability_mode: DataAvailabilityMode::L1,
+                class_hash,
+                compiled_class_hash,
+                query_only,
+            });
+            assert_eq!(tx.transaction_hash(), expected);
+        }
+    }
+
+    #[test]
+    fn tip_and_data_availability_modes_are_hashed() {
+        let invoke = InvokeV3 {
+            sender_address: SENDER,
+            chain_id: CHAIN_ID,
+            nonce: felt!("0x3"),
+            gas: 1000,
+            gas_price: 100_000_000_000,
+            tip: 0,
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+            calls: calls().iter().map(Into::into).collect(),
+            encoding: CallEncoding::New,
+            query_only: false,
+        };
+        let hash = Transaction::InvokeV3(invoke.clone()).transaction_hash();
+
+        let tipped = Transaction::InvokeV3(InvokeV3 { tip: 1, ..invoke.clone() });
+        assert_ne!(tipped.transaction_hash(), hash);
+
+        let l2_nonce = Transaction::InvokeV3(InvokeV3 {
+            nonce_data_availability_mode: DataAvailabilityMode::L2,
+            ..invoke
+        });
+        assert_ne!(l2_nonce.transaction_hash(), hash);
+
+        assert_eq!(
+            data_availability_modes(DataAvailabilityMode::L2, DataAvailabilityMode::L1),
+            felt!("0x100000000")
+        );
+        assert_eq!(
+            data_availability_modes(DataAvailabilityMode::L1, DataAvailabilityMode::L2),
+            Felt::ONE
+        );
+    }
+
+    #[test]
+    fn v3_settings_default_to_l1_without_tip() {
+        let json = serde_json::json!({
+            "type": "invoke_v3",
+            "sender_address": "0x1234",
+            "chain_id": "0x2",
+            "nonce": "0x3",
+            "gas": 1000,
+            "gas_price": 1,
+            "calls": [],
+            "encoding": "new",
+            "query_only": false,
+        });
+
+        let Transaction::InvokeV3(tx) = serde_json::from_value(json).unwrap() else {
+            panic!("expected an invoke v3");
+        };
+        assert_eq!(tx.tip, 0);
+        assert_eq!(tx.nonce_data_availability_mode, DataAvailabilityMode::L1);
+        assert_eq!(tx.fee_data_availability_mode, DataAvailabilityMode::L1);
+    }
+
+    #[test]
+    fn sign_request_json_format() {
+        let request = SignRequest {
+            transaction_hash: felt!("0xabc"),
+            transaction: Transaction::DeclareV2(DeclareV2 {
+                sender_address: SENDER,
+                chain_id: CHAIN_ID,
+                nonce: felt!("0x3"),
+                max_fee: felt!("0x1000"),
+                class_hash: felt!("0x10"),
+                compiled_class_hash: felt!("0x20"),
+                query_only: false,
+            }),
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["transaction"]["type"], "declare_v2");
+        assert_eq!(serde_json::from_value::<SignRequest>(json).unwrap(), request);
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use starknet::core::types::Felt;
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+
+use super::Transaction;
+
+/// Restricts the transactions a remote signer accepts to sign.
+///
+/// Policies are written in TOML:
+///
+/// ```toml
+/// # The chain and the account transactions are signed for, by hex value or short string.
+/// chain_id = "SN_SEPOLIA"
+/// sender_address = "0x1234"
+///
+/// # Fee caps, a transaction version is only signed if its caps are set.
+/// # Maximum fee of V1 invokes and V2 declarations.
+/// max_fee = "0x2386f26fc10000"
+/// # Maximum L1 gas amount and price of V3 transactions.
+/// max_gas = 1000000
+/// max_gas_price = 100000000000000
+/// # Maximum tip of V3 transactions, only transactions without tip are signed when omitted.
+/// max_tip = 1000
+///
+/// # Whether classes can be declared, `false` by default.
+/// allow_declare = true
+///
+/// [[contracts]]
+/// address = "0x1234"
+/// # Entrypoints allowed on the contract, by name or selector.
+/// # Any entrypoint of the contract is allowed when omitted.
+/// entrypoints = ["spawn", "move"]
+/// ```
+///
+/// A transaction is only signed if it is sent by `sender_address` on `chain_id`, if its fee is
+/// within the caps and if every one of its calls is allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignerPolicy {
+    unrestricted: bool,
+    chain_id: Felt,
+    sender_address: Felt,
+    max_fee: Option<Felt>,
+    max_gas: Option<u64>,
+    max_gas_price: Option<u128>,
+    max_tip: Option<u64>,
+    allow_declare: bool,
+    /// Allowed contracts, with their allowed selectors if restricted.
+    contracts: HashMap<Felt, Option<HashSet<Felt>>>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("Transactions on chain {0:#x} are not allowed.")]
+    ChainNotAllowed(Felt),
+
+    #[error("Transactions sent by {0:#x} are not allowed.")]
+    SenderNotAllowed(Felt),
+
+    #[error("{0} transactions are not allowed without `{1}` in the policy.")]
+    FeeNotCapped(&'static str, &'static str),
+
+    #[error("Fee of {fee:#x} exceeds the `{cap_name}` of {cap:#x}.")]
+    FeeExceeded { fee: Felt, cap: Felt, cap_name: &'static str },
+
+    #[error("Declaring classes is not allowed.")]
+    DeclareNotAllowed,
+
+    #[error("Calls to contract {0:#x} are not allowed.")]
+    ContractNotAllowed(Felt),
+
+    #[error("Calls to selector {selector:#x} of contract {contract:#x} are not allowed.")]
+    SelectorNotAllowed { contract: Felt, selector: Felt },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    chain_id: String,
+    sender_address: String,
+    max_fee: Option<String>,
+    max_gas: Option<u64>,
+    max_gas_price: Option<u128>,
+    max_tip: Option<u64>,
+    #[serde(default)]
+    allow_declare: bool,
+    #[serde(default)]
+    contracts: Vec<ContractPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContractPolicy {
+    address: String,
+    entrypoints: Option<Vec<String>>,
+}
+
+impl SignerPolicy {
+    /// A policy allowing any transaction to be signed.
+    pub fn allow_all() -> Self {
+        Self { unrestricted: true, ..Default::default() }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read policy file `{}`", path.display()))?;
+        content.parse().with_context(|| format!("Invalid policy file `{}`", path.display()))
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.unrestricted
+    }
+
+    /// Checks that `transaction` is allowed to be signed.
+    pub fn check(&self, transaction: &Transaction) -> Result<(), PolicyViolation> {
+        if self.unrestricted {
+            return Ok(());
+        }
+
+        if transaction.chain_id() != self.chain_id {
+            return Err(PolicyViolation::ChainNotAllowed(transaction.chain_id()));
+        }
+
+        if transaction.sender_address() != self.sender_address {
+            return Err(PolicyViolation::SenderNotAllowed(transaction.sender_address()));
+        }
+
+        self.check_fee(transaction)?;
+
+        if transaction.is_declare() && !self.allow_declare {
+            return Err(PolicyViolation::DeclareNotAllowed);
+        }
+
+        for call in transaction.calls() {
+            match self.contracts.get(&call.to) {
+                None => return Err(PolicyViolation::ContractNotAllowed(call.to)),
+                Some(Some(selectors)) if !selectors.contains(&call.selector) => {
+                    return Err(PolicyViolation::SelectorNotAllowed {
+                        contract: call.to,
+                        selector: call.selector,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_fee(&self, transaction: &Transaction) -> Result<(), PolicyViolation> {
+        let (max_fee, gas, gas_price, tip) = match transaction {
+            Transaction::InvokeV1(tx) => (Some(tx.max_fee), None, None, 0),
+            Transaction::DeclareV2(tx) => (Some(tx.max_fee), None, None, 0),
+            Transaction::InvokeV3(tx) => (None, Some(tx.gas), Some(tx.gas_price), tx.tip),
+            Transaction::DeclareV3(tx) => (None, Some(tx.gas), Some(tx.gas_price), tx.tip),
+        };
+
+        if let Some(fee) = max_fee {
+            let cap = self.max_fee.ok_or(PolicyViolation::FeeNotCapped("V1 and V2", "max_fee"))?;
+            check_cap(fee, cap, "max_fee")?;
+        }
+
+        if let (Some(gas), Some(gas_price)) = (gas, gas_price) {
+            let cap = self.max_gas.ok_or(PolicyViolation::FeeNotCapped("V3", "max_gas"))?;
+            check_cap(gas.into(), cap.into(), "max_gas")?;
+
+            let cap =
+                self.max_gas_price.ok_or(PolicyViolation::FeeNotCapped("V3", "max_gas_price"))?;
+            check_cap(gas_price.into(), cap.into(), "max_gas_price")?;
+        }
+
+        if tip > 0 {
+            let cap = self.max_tip.ok_or(PolicyViolation::FeeNotCapped("Tipped V3", "max_tip"))?;
+            check_cap(tip.into(), cap.into(), "max_tip")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_cap(fee: Felt, cap: Felt, cap_name: &'static str) -> Result<(), PolicyViolation> {
+    if fee > cap {
+        return Err(PolicyViolation::FeeExceeded { fee, cap, cap_name });
+    }
+
+    Ok(())
+}
+
+impl FromStr for SignerPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let file: PolicyFile = toml::from_str(s)?;
+
+        let chain_id = parse_chain_id(&file.chain_id)?;
+        let sender_address = Felt::from_hex(&file.sender_address)
+            .with_context(|| format!("Invalid sender address `{}`", file.sender_address))?;
+        let max_fee = file
+            .max_fee
+            .map(|fee| Felt::from_str(&fee).with_context(|| format!("Invalid max fee `{fee}`")))
+            .transpose()?;
+
+        let mut contracts = HashMap::new();
+
+        for contract in file.contracts {
+            let address = Felt::from_hex(&contract.address)
+                .with_context(|| format!("Invalid contract address `{}`", contract.address))?;
+
+            let selectors = contract
+                .entrypoints
+                .map(|entrypoints| {
+                    entrypoints.iter().map(|e| parse_selector(e)).collect::<Result<HashSet<_>>>()
+                })
+                .transpose()?;
+
+            contracts.insert(address, selectors);
+        }
+
+        Ok(Self {
+            unrestricted: false,
+            chain_id,
+            sender_address,
+            max_fee,
+            max_gas: file.max_gas,
+            max_gas_price: file.max_gas_price,
+            max_tip: file.max_tip,
+            allow_declare: file.allow_declare,
+            contracts,
+        })
+    }
+}
+
+/// Parses a chain id given either by value or as a short string, such as `SN_SEPOLIA`.
+fn parse_chain_id(chain_id: &str) -> Result<Felt> {
+    if chain_id.starts_with("0x") {
+        Felt::from_hex(chain_id).with_context(|| format!("Invalid chain id `{chain_id}`"))
+    } else {
+        cairo_short_string_to_felt(chain_id)
+            .with_context(|| format!("Invalid chain id `{chain_id}`"))
+    }
+}
+
+/// Parses an entrypoint given either by name or by selector.
+fn parse_selector(entrypoint: &str) -> Result<Felt> {
+    if entrypoint.starts_with("0x") {
+        Felt::from_hex(entrypoint).with_context(|| format!("Invalid selector `{entrypoint}`"))
+    } else {
+        get_selector_from_name(entrypoint)
+            .with_context(|| format!("Invalid entrypoint name `{entrypoint}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::DataAvailabilityMode;
+    use starknet::macros::{felt, selector, short_string};
+
+    use super::*;
+    use crate::remote::{Call, CallEncoding, DeclareV2, InvokeV1, InvokeV3};
+
+    const POLICY: &str = r#"
+        chain_id = "0x2"
+        sender_address = "0x1"
+        max_fee = "0x1000"
+        max_gas = 100
+        max_gas_price = 1000
+        max_tip = 10
+
+        [[contracts]]
+        address = "0x1234"
+        entrypoints = ["spawn", "0x2"]
+
+        [[contracts]]
+        address = "0x5678"
+    "#;
+
+    fn invoke(calls: Vec<(Felt, Felt)>) -> Transaction {
+        Transaction::InvokeV1(InvokeV1 {
+            sender_address: felt!("0x1"),
+            chain_id: felt!("0x2"),
+            nonce: felt!("0x0"),
+            max_fee: felt!("0x100"),
+            calls: calls
+                .into_iter()
+                .map(|(to, selector)| Call { to, selector, calldata: vec![] })
+                .collect(),
+            encoding: CallEncoding::New,
+            query_only: false,
+        })
+    }
+
+    fn invoke_v3(gas: u64, gas_price: u128) -> Transaction {
+        Transaction::InvokeV3(InvokeV3 {
+            sender_address: felt!("0x1"),
+            chain_id: felt!("0x2"),
+            nonce: felt!("0x0"),
+            gas,
+            gas_price,
+            tip: 0,
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+            calls: vec![Call { to: felt!("0x5678"), selector: felt!("0x1"), calldata: vec![] }],
+            encoding: CallEncoding::New,
+            query_only: false,
+        })
+    }
+
+    fn tipped_invoke_v3(tip: u64) -> Transaction {
+        let mut tx = invoke_v3(1, 1);
+        let Transaction::InvokeV3(v3) = &mut tx else { unreachable!() };
+        v3.tip = tip;
+        tx
+    }
+
+    fn declare() -> Transaction {
+        Transaction::DeclareV2(DeclareV2 {
+            sender_address: felt!("0x1"),
+            chain_id: felt!("0x2"),
+            nonce: felt!("0x0"),
+            max_fee: felt!("0x100"),
+            class_hash: felt!("0x10"),
+            compiled_class_hash: felt!("0x20"),
+            query_only: false,
+        })
+    }
+
+    #[test]
+    fn allowed_calls_pass() {
+        let policy: SignerPolicy = POLICY.parse().unwrap();
+
+        let tx = invoke(vec![
+            (felt!("0x1234"), selector!("spawn")),
+            (felt!("0x1234"), felt!("0x2")),
+            (felt!("0x5678"), selector!("anything")),
+        ]);
+        assert_eq!(policy.check(&tx), Ok(()));
+    }
+
+    #[test]
+    fn unknown_contracts_and_selectors_are_rejected() {
+        let policy: SignerPolicy = POLICY.parse().unwrap();
+
+        let tx = invoke(vec![(felt!("0x1234"), selector!("spawn")), (felt!("0x9"), felt!("0x1"))]);
+        assert_eq!(policy.check(&tx), Err(PolicyViolation::ContractNotAllowed(felt!("0x9"))));
+
+        let tx = invoke(vec![(felt!("0x1234"), selector!("move"))]);
+        assert_eq!(
+            policy.check(&tx),
+            Err(PolicyViolation::SelectorNotAllowed {
+                contract: felt!("0x1234"),
+                selector: selector!("move")
+            })
+        );
+    }
+
+    #[test]
+    fn chain_and_sender_are_pinned() {
+        let policy: SignerPolicy = POLICY.parse().unwrap();
+
+        let mut tx = invoke(vec![(felt!("0x5678"), felt!("0x1"))]);
+        let Transaction::InvokeV1(v1) = &mut tx else { unreachable!() };
+        v1.chain_id = felt!("0x3");
+        assert_eq!(policy.check(&tx), Err(PolicyViolation::ChainNotAllowed(felt!("0x3"))));
+
+        let mut tx = invoke(vec![(felt!("0x5678"), felt!("0x1"))]);
+        let Transaction::InvokeV1(v1) = &mut tx else { unreachable!() };
+        v1.sender_address = felt!("0x9");
+        assert_eq!(policy.check(&tx), Err(PolicyViolation::SenderNotAllowed(felt!("0x9"))));
+
+        let policy: SignerPolicy =
+            "chain_id = \"SN_SEPOLIA\"\nsender_address = \"0x1\"".parse().unwrap();
+        assert_eq!(policy.chain_id, short_string!("SN_SEPOLIA"));
+    }
+
+    #[test]
+    fn fees_are_capped() {
+        let policy: SignerPolicy = POLICY.parse().unwrap();
+
+        let mut tx = invoke(vec![(felt!("0x5678"), felt!("0x1"))]);
+        let Transaction::InvokeV1(v1) = &mut tx else { unreachable!() };
+        v1.max_fee = felt!("0x1001");
+        assert_eq!(
+            policy.check(&tx),
+            Err(PolicyViolation::FeeExceeded {
+                fee: felt!("0x1001"),
+                cap: felt!("0x1000"),
+                cap_name: "max_fee"
+            })
+        );
+
+        assert_eq!(policy.check(&invoke_v3(100, 1000)), Ok(()));
+        assert!(matches!(
+            policy.check(&invoke_v3(101, 1000)),
+            Err(PolicyViolation::FeeExceeded { cap_name: "max_gas", .. })
+        ));
+        assert!(matches!(
+            policy.check(&invoke_v3(100, 1001)),
+            Err(PolicyViolation::FeeExceeded { cap_name: "max_gas_price", .. })
+        ));
+
+        assert_eq!(policy.check(&tipped_invoke_v3(10)), Ok(()));
+        assert!(matches!(
+            policy.check(&tipped_invoke_v3(11)),
+            Err(PolicyViolation::FeeExceeded { cap_name: "max_tip", .. })
+        ));
+    }
+
+    #[test]
+    fn uncapped_fees_are_rejected() {
+        let policy: SignerPolicy = r#"
+            chain_id = "0x2"
+            sender_address = "0x1"
+
+            [[contracts]]
+            address = "0x5678"
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            policy.check(&invoke(vec![(felt!("0x5678"), felt!("0x1"))])),
+            Err(PolicyViolation::FeeNotCapped("V1 and V2", "max_fee"))
+        );
+        assert_eq!(
+            policy.check(&invoke_v3(1, 1)),
+            Err(PolicyViolation::FeeNotCapped("V3", "max_gas"))
+        );
+
+        let policy: SignerPolicy = r#"
+            chain_id = "0x2"
+            sender_address = "0x1"
+            max_gas = 100
+            max_gas_price = 1000
+
+            [[contracts]]
+            address = "0x5678"
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(policy.check(&invoke_v3(1, 1)), Ok(()));
+        assert_eq!(
+            policy.check(&tipped_invoke_v3(1)),
+            Err(PolicyViolation::FeeNotCapped("Tipped V3", "max_tip"))
+        );
+    }
+
+    #[test]
+    fn declare_requires_permission() {
+        let policy: SignerPolicy = POLICY.parse().unwrap();
+        assert_eq!(policy.check(&declare()), Err(PolicyViolation::DeclareNotAllowed));
+
+        let policy: SignerPolicy = r#"
+            chain_id = "0x2"
+            sender_address = "0x1"
+            max_fee = "0x100"
+            allow_declare = true
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(policy.check(&declare()), Ok(()));
+    }
+
+    #[test]
+    fn allow_all_allows_anything() {
+        let policy = SignerPolicy::allow_all();
+        assert_eq!(policy.check(&declare()), Ok(()));
+        assert_eq!(policy.check(&invoke(vec![(felt!("0x9"), felt!("0x1"))])), Ok(()));
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        const PINNED: &str = "chain_id = \"0x2\"\nsender_address = \"0x1\"\n";
+
+        assert!(format!("{PINNED}[[contracts]]\naddress = \"not_an_address\"")
+            .parse::<SignerPolicy>()
+            .is_err());
+        assert!(format!("{PINNED}[[contracts]]\naddress = \"0x1\"\nentrypoints = [\"0xzz\"]")
+            .parse::<SignerPolicy>()
+            .is_err());
+        assert!(format!("{PINNED}allow_everything = true").parse::<SignerPolicy>().is_err());
+        assert!(format!("{PINNED}max_fee = \"0xzz\"").parse::<SignerPolicy>().is_err());
+        assert!("sender_address = \"0x1\"".parse::<SignerPolicy>().is_err());
+        assert!("chain_id = \"0x2\"".parse::<SignerPolicy>().is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Serialize;
+use starknet::signers::{LocalWallet, Signer};
+use tracing::{info, warn};
+use warp::http::StatusCode;
+use warp::reject::Reject;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+use super::{ErrorResponse, PublicKeyResponse, SignRequest, SignResponse, SignerPolicy};
+
+const LOG_TARGET: &str = "sozo::signer";
+
+/// Maximum size of a sign request body, large enough for multicalls of migrations.
+const MAX_BODY_SIZE: u64 = 4 * 1024 * 1024;
+
+struct SignerState {
+    wallet: LocalWallet,
+    policy: SignerPolicy,
+    token: String,
+}
+
+impl SignerState {
+    /// Checks the `Authorization` header against the token, in constant time.
+    fn authorizes(&self, authorization: Option<&str>) -> bool {
+        let Some(token) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else {
+            return false;
+        };
+
+        token.len() == self.token.len()
+            && token.bytes().zip(self.token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+/// Serves the remote signer protocol on `address`, signing with `wallet` the transactions
+/// allowed by `policy` for the clients authenticated with `token`.
+///
+/// Returns the address the server is bound to, and the future running the server.
+pub fn serve(
+    address: SocketAddr,
+    wallet: LocalWallet,
+    policy: SignerPolicy,
+    token: String,
+) -> Result<(SocketAddr, impl Future<Output = ()> + 'static)> {
+    if token.is_empty() {
+        anyhow::bail!("The token authenticating the clients of the signer can't be empty.");
+    }
+
+    let state = Arc::new(SignerState { wallet, policy, token });
+    let with_state = warp::any().map(move || state.clone());
+
+    let authorized = warp::header::optional::<String>("authorization")
+        .and(with_state.clone())
+        .and_then(|authorization: Option<String>, state: Arc<SignerState>| async move {
+            if state.authorizes(authorization.as_deref()) {
+                Ok(())
+            } else {
+                Err(warp::reject::custom(Unauthorized))
+            }
+        })
+        .untuple_one();
+
+    let public_key = warp::get()
+        .and(warp::path("public_key"))
+        .and(warp::path::end())
+        .and(authorized.clone())
+        .and(with_state.clone())
+        .and_then(handle_public_key);
+
+    // Authenticated before the body is even read.
+    let sign = warp::post()
+        .and(warp::path("sign"))
+        .and(warp::path::end())
+        .and(authorized)
+        .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(warp::body::json())
+        .and(with_state)
+        .and_then(handle_sign);
+
+    let routes = public_key.or(sign).recover(handle_rejection);
+    Ok(warp::serve(routes).try_bind_ephemeral(address)?)
+}
+
+async fn handle_rejection(rejection: Rejection) -> Result<Response, Rejection> {
+    if rejection.find::<Unauthorized>().is_some() {
+        warn!(target: LOG_TARGET, "Rejected unauthenticated request.");
+        return Ok(error(StatusCode::UNAUTHORIZED, "Missing or invalid token.".to_string()));
+    }
+
+    Err(rejection)
+}
+
+async fn handle_public_key(state: Arc<SignerState>) -> Result<Response, Infallible> {
+    match state.wallet.get_public_key().await {
+        Ok(key) => Ok(json(StatusCode::OK, &PublicKeyResponse { public_key: key.scalar() })),
+        Err(e) => Ok(error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn handle_sign(
+    request: SignRequest,
+    state: Arc<SignerState>,
+) -> Result<Response, Infallible> {
+    let transaction_hash = request.transaction.transaction_hash();
+    let sender = request.transaction.sender_address();
+
+    if transaction_hash != request.transaction_hash {
+        warn!(
+            target: LOG_TARGET,
+            sender = format!("{sender:#x}"),
+            requested = format!("{:#x}", request.transaction_hash),
+            computed = format!("{transaction_hash:#x}"),
+            "Rejected signing request with mismatching transaction hash."
+        );
+        return Ok(error(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Transaction hash mismatch: requested {:#x}, computed {transaction_hash:#x} from \
+                 the transaction.",
+                request.transaction_hash
+            ),
+        ));
+    }
+
+    if let Err(violation) = state.policy.check(&request.transaction) {
+        warn!(
+            target: LOG_TARGET,
+            sender = format!("{sender:#x}"),
+            hash = format!("{transaction_hash:#x}"),
+            %violation,
+            "Rejected signing request."
+        );
+        return Ok(error(StatusCode::FORBIDDEN, violation.to_string()));
+    }
+
+    match state.wallet.sign_hash(&transaction_hash).await {
+        Ok(signature) => {
+            info!(
+                target: LOG_TARGET,
+                sender = format!("{sender:#x}"),
+                hash = format!("{transaction_hash:#x}"),
+                calls = request.transaction.calls().len(),
+                "Signed transaction."
+            );
+            Ok(json(StatusCode::OK, &SignResponse { signature: vec![signature.r, signature.s] }))
+        }
+        Err(e) => Ok(error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+fn json<T: Serialize>(status: StatusCode, body: &T) -> Response {
+    warp::reply::with_status(warp::reply::json(body), status).into_response()
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    json(status, &ErrorResponse { error })
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+    use starknet::macros::{felt, selector};
+    use starknet::signers::SigningKey;
+    use url::Url;
+
+    use super::*;
+    use crate::remote::{
+        Call, CallEncoding, InvokeV1, RemoteSigner, RemoteSignerError, Transaction,
+    };
+
+    fn wallet() -> LocalWallet {
+        LocalWallet::from_signing_key(SigningKey::from_secret_scalar(felt!("0x1337")))
+    }
+
+    fn request(selector: Felt) -> SignRequest {
+        let transaction = Transaction::InvokeV1(InvokeV1 {
+            sender_address: felt!("0x1"),
+            chain_id: felt!("0x2"),
+            nonce: felt!("0x0"),
+            max_fee: felt!("0x100"),
+            calls: vec![Call { to: felt!("0x1234"), selector, calldata: vec![felt!("0x1")] }],
+            encoding: CallEncoding::New,
+            query_only: false,
+        });
+
+        SignRequest { transaction_hash: transaction.transaction_hash(), transaction }
+    }
+
+    const TOKEN: &str = "s3cr3t";
+
+    const POLICY: &str = r#"
+        chain_id = "0x2"
+        sender_address = "0x1"
+        max_fee = "0x1000"
+
+        [[contracts]]
+        address = "0x1234"
+        entrypoints = ["spawn"]
+    "#;
+
+    async fn start(policy: SignerPolicy) -> Url {
+        let (address, server) =
+            serve(([127, 0, 0, 1], 0).into(), wallet(), policy, TOKEN.to_string()).unwrap();
+        tokio::spawn(server);
+        Url::parse(&format!("http://{address}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn signs_allowed_transactions() {
+        let signer = RemoteSigner::new(start(POLICY.parse().unwrap()).await, TOKEN.to_string());
+
+        let public_key = wallet().get_public_key().await.unwrap().scalar();
+        assert_eq!(signer.public_key().await.unwrap(), public_key);
+
+        let request = request(selector!("spawn"));
+        let expected = wallet().sign_hash(&request.transaction_hash).await.unwrap();
+        assert_eq!(signer.sign(&request).await.unwrap(), vec![expected.r, expected.s]);
+    }
+
+    #[tokio::test]
+    async fn rejects_transactions_outside_policy() {
+        let signer = RemoteSigner::new(start(POLICY.parse().unwrap()).await, TOKEN.to_string());
+
+        let err = signer.sign(&request(selector!("move"))).await.unwrap_err();
+        assert!(matches!(err, RemoteSignerError::Rejected { status: 403, .. }));
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatching_hashes() {
+        let signer = RemoteSigner::new(start(SignerPolicy::allow_all()).await, TOKEN.to_string());
+
+        let mut request = request(selector!("spawn"));
+        request.transaction_hash = felt!("0xdead");
+
+        let err = signer.sign(&request).await.unwrap_err();
+        assert!(matches!(err, RemoteSignerError::Rejected { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn rejects_unauthenticated_requests() {
+        let url = start(SignerPolicy::allow_all()).await;
+
+        for token in ["", "wrong", "s3cr3t2"] {
+            let signer = RemoteSigner::new(url.clone(), token.to_string());
+
+            let err = signer.public_key().await.unwrap_err();
+            assert!(matches!(err, RemoteSignerError::Rejected { status: 401, .. }));
+
+            let err = signer.sign(&request(selector!("spawn"))).await.unwrap_err();
+            assert!(matches!(err, RemoteSignerError::Rejected { status: 401, .. }));
+        }
+    }
+
+    #[test]
+    fn empty_tokens_are_refused() {
+        let policy = SignerPolicy::allow_all();
+        assert!(serve(([127, 0, 0, 1], 0).into(), wallet(), policy, String::new()).is_err());
+    }
+}
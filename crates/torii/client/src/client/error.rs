@@ -21,6 +21,8 @@ pub enum Error {
     Model(#[from] ModelError),
     #[error("Unsupported query")]
     UnsupportedQuery,
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
     #[error(transparent)]
     Schema(#[from] SchemaError),
 }
@@ -1,23 +1,31 @@
 pub mod error;
+pub mod store;
 
 use std::sync::Arc;
 
 use dojo_types::WorldMetadata;
 use dojo_world::contracts::WorldContractReader;
 use futures::lock::Mutex;
+use futures::{Stream, StreamExt};
 use parking_lot::{RwLock, RwLockReadGuard};
 use starknet::core::types::Felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 use tokio::sync::RwLock as AsyncRwLock;
-use torii_grpc::client::{EntityUpdateStreaming, EventUpdateStreaming};
-use torii_grpc::proto::world::{RetrieveEntitiesResponse, RetrieveEventsResponse};
-use torii_grpc::types::schema::Entity;
-use torii_grpc::types::{EntityKeysClause, Event, EventQuery, KeysClause, Query};
+use torii_grpc::client::{EntityUpdateStreaming, EventUpdateStreaming, ModelRegistrationStreaming};
+use torii_grpc::proto::types::AggregateGroup;
+use torii_grpc::proto::world::{
+    RetrieveAggregatesResponse, RetrieveEntitiesResponse, RetrieveEventsResponse,
+};
+use torii_grpc::types::schema::{Entity, EntityUpdate};
+use torii_grpc::types::{
+    AggregateQuery, Clause, EntityKeysClause, Event, EventQuery, KeysClause, Query,
+};
 use torii_relay::client::EventLoop;
 use torii_relay::types::Message;
 
 use crate::client::error::{Error, ParseError};
+use crate::client::store::EntityStore;
 
 // TODO: remove reliance on RPC
 #[allow(unused)]
@@ -29,6 +37,8 @@ pub struct Client {
     inner: AsyncRwLock<torii_grpc::client::WorldClient>,
     /// Relay client.
     relay_client: torii_relay::client::RelayClient,
+    /// Local store of the synced entities.
+    store: Arc<RwLock<EntityStore>>,
     /// The subscription client handle.
     /// World contract reader.
     world_reader: WorldContractReader<JsonRpcClient<HttpTransport>>,
@@ -60,6 +70,7 @@ impl Client {
             metadata: shared_metadata,
             inner: AsyncRwLock::new(grpc_client),
             relay_client,
+            store: Default::default(),
         })
     }
 
@@ -92,26 +103,60 @@ impl Client {
     /// model data. Specifying a clause can optimize the query by limiting the retrieval to specific
     /// type of entites matching keys and/or models.
     pub async fn entities(&self, query: Query) -> Result<Vec<Entity>, Error> {
+        Ok(self.entities_page(query).await?.0)
+    }
+
+    /// Retrieves a page of the entities matching the query, along with the cursor of the next page
+    /// which is empty on the last one. Set the order of the query to page through entities with
+    /// cursors.
+    pub async fn entities_page(&self, query: Query) -> Result<(Vec<Entity>, String), Error> {
         let mut grpc_client = self.inner.write().await;
-        let RetrieveEntitiesResponse { entities, total_count: _ } =
+        let RetrieveEntitiesResponse { entities, next_cursor, .. } =
             grpc_client.retrieve_entities(query).await?;
-        Ok(entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?)
+        let entities =
+            entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?;
+        Ok((entities, next_cursor))
     }
 
     /// Similary to entities, this function retrieves event messages matching the query parameter.
     pub async fn event_messages(&self, query: Query) -> Result<Vec<Entity>, Error> {
+        Ok(self.event_messages_page(query).await?.0)
+    }
+
+    /// Similary to entities_page, this function retrieves a page of event messages.
+    pub async fn event_messages_page(&self, query: Query) -> Result<(Vec<Entity>, String), Error> {
         let mut grpc_client = self.inner.write().await;
-        let RetrieveEntitiesResponse { entities, total_count: _ } =
+        let RetrieveEntitiesResponse { entities, next_cursor, .. } =
             grpc_client.retrieve_event_messages(query).await?;
-        Ok(entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?)
+        let entities =
+            entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?;
+        Ok((entities, next_cursor))
     }
 
     /// Retrieve raw starknet events matching the keys provided.
     /// If the keys are empty, it will return all events.
     pub async fn starknet_events(&self, query: EventQuery) -> Result<Vec<Event>, Error> {
+        Ok(self.starknet_events_page(query).await?.0)
+    }
+
+    /// Retrieves a page of raw starknet events, from the most recent one, along with the cursor of
+    /// the next page which is empty on the last one.
+    pub async fn starknet_events_page(
+        &self,
+        query: EventQuery,
+    ) -> Result<(Vec<Event>, String), Error> {
         let mut grpc_client = self.inner.write().await;
-        let RetrieveEventsResponse { events } = grpc_client.retrieve_events(query).await?;
-        Ok(events.into_iter().map(Event::from).collect::<Vec<Event>>())
+        let RetrieveEventsResponse { events, next_cursor } =
+            grpc_client.retrieve_events(query).await?;
+        Ok((events.into_iter().map(Event::from).collect::<Vec<Event>>(), next_cursor))
+    }
+
+    /// Retrieves the aggregates (count, sum, avg, min, max) of a model's members, optionally
+    /// filtered by a clause and grouped by some of its members.
+    pub async fn aggregates(&self, query: AggregateQuery) -> Result<Vec<AggregateGroup>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveAggregatesResponse { groups } = grpc_client.retrieve_aggregates(query).await?;
+        Ok(groups)
     }
 
     /// A direct stream to grpc subscribe entities
@@ -124,6 +169,62 @@ impl Client {
         Ok(stream)
     }
 
+    /// A direct stream to grpc subscribe entities, also filtered by the values of their models
+    /// with the clause, e.g. the moves whose `remaining` member is lower than 5.
+    pub async fn on_entity_updated_with_clause(
+        &self,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<EntityUpdateStreaming, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let stream = grpc_client.subscribe_entities_with_clause(clauses, clause).await?;
+        Ok(stream)
+    }
+
+    /// Returns the local store of the entities synced with [Client::sync_entities]. Optimistic
+    /// writes made on the store are reconciled with the updates of the synced entities.
+    pub fn entity_store(&self) -> Arc<RwLock<EntityStore>> {
+        self.store.clone()
+    }
+
+    /// Syncs the entities matching the clauses in the local entity store.
+    ///
+    /// The store is first populated with the entities matching the query, then every update of the
+    /// returned stream is applied to the store before it is yielded. The stream must be polled
+    /// for the store to stay in sync. The optimistic writes resolved by the synced updates can be
+    /// taken with [EntityStore::take_resolved_writes].
+    pub async fn sync_entities(
+        &self,
+        query: Query,
+        clauses: Vec<EntityKeysClause>,
+    ) -> Result<impl Stream<Item = <EntityUpdateStreaming as Stream>::Item>, Error> {
+        // subscribe first so that no update is missed between the query and the subscription.
+        // updates aren't filtered by the clause of the query, so that the entities which no longer
+        // match it are updated in the store
+        let stream = self.on_entity_updated(clauses).await?;
+
+        let entities = self.entities(query).await?;
+        {
+            let mut store = self.store.write();
+            for entity in entities {
+                let statuses = store.apply_update(entity);
+                store.resolve_writes(statuses);
+            }
+        }
+
+        let store = self.store.clone();
+        Ok(stream.inspect(move |update| {
+            let mut store = store.write();
+            let statuses = match update {
+                Ok((_, EntityUpdate::Updated(entity))) => store.apply_update(entity.clone()),
+                Ok((_, EntityUpdate::Deleted(hashed_keys))) => store.remove(hashed_keys),
+                // the subscription has no clause
+                Ok((_, EntityUpdate::Unmatched(_))) | Err(_) => return,
+            };
+            store.resolve_writes(statuses);
+        }))
+    }
+
     /// Update the entities subscription
     pub async fn update_entity_subscription(
         &self,
@@ -135,6 +236,31 @@ impl Client {
         Ok(())
     }
 
+    /// Update the keys clauses and the clause of the entities subscription
+    pub async fn update_entity_subscription_with_clause(
+        &self,
+        subscription_id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<(), Error> {
+        let mut grpc_client = self.inner.write().await;
+        grpc_client
+            .update_entities_subscription_with_clause(subscription_id, clauses, clause)
+            .await?;
+        Ok(())
+    }
+
+    /// A direct stream to grpc subscribe to the registration of the models of some namespaces, or
+    /// all of them if empty.
+    pub async fn on_model_registered(
+        &self,
+        namespaces: Vec<String>,
+    ) -> Result<ModelRegistrationStreaming, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let stream = grpc_client.subscribe_model_registrations(namespaces).await?;
+        Ok(stream)
+    }
+
     /// A direct stream to grpc subscribe event messages
     pub async fn on_event_message_updated(
         &self,
@@ -145,6 +271,18 @@ impl Client {
         Ok(stream)
     }
 
+    /// A direct stream to grpc subscribe event messages, also filtered by the values of their
+    /// models with the clause
+    pub async fn on_event_message_updated_with_clause(
+        &self,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<EntityUpdateStreaming, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let stream = grpc_client.subscribe_event_messages_with_clause(clauses, clause).await?;
+        Ok(stream)
+    }
+
     /// Update the event messages subscription
     pub async fn update_event_message_subscription(
         &self,
@@ -156,6 +294,20 @@ impl Client {
         Ok(())
     }
 
+    /// Update the keys clauses and the clause of the event messages subscription
+    pub async fn update_event_message_subscription_with_clause(
+        &self,
+        subscription_id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<(), Error> {
+        let mut grpc_client = self.inner.write().await;
+        grpc_client
+            .update_event_messages_subscription_with_clause(subscription_id, clauses, clause)
+            .await?;
+        Ok(())
+    }
+
     pub async fn on_starknet_event(
         &self,
         keys: Option<KeysClause>,
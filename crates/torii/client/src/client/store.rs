@@ -0,0 +1,677 @@
+//! In-process store of the entities synced from Torii.
+//!
+//! The store is fed by the entity updates of the subscriptions and can be queried locally with the
+//! same [`Clause`] as the gRPC queries, so that game clients can read the entities without a round
+//! trip. Optimistic writes are applied over the confirmed models until the update of their model
+//! arrives, at which point they are either confirmed or rolled back.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::mem;
+
+use crypto_bigint::U256;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Struct, Ty};
+use dojo_world::contracts::naming::compute_selector_from_names;
+use starknet::core::types::Felt;
+use torii_grpc::types::schema::Entity;
+use torii_grpc::types::{
+    ArrayPredicate, Clause, ComparisonOperator, KeysClause, LogicalOperator, OrderDirection,
+    PatternMatching, Query,
+};
+
+use crate::client::error::Error;
+
+/// Identifier of an optimistic write.
+pub type WriteId = u64;
+
+/// Outcome of an optimistic write once the update of its model is confirmed by Torii.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteStatus {
+    /// The confirmed model is the one that was written.
+    Confirmed,
+    /// The confirmed model differs from the written one, which is discarded.
+    RolledBack,
+}
+
+#[derive(Debug, Clone)]
+struct OptimisticWrite {
+    id: WriteId,
+    selector: Felt,
+    model: Struct,
+}
+
+#[derive(Debug, Clone, Default)]
+struct StoredEntity {
+    /// Models confirmed by Torii, by model selector.
+    confirmed: HashMap<Felt, Struct>,
+    /// Writes not confirmed yet, applied in order over the confirmed models.
+    pending: Vec<OptimisticWrite>,
+}
+
+impl StoredEntity {
+    fn models(&self) -> HashMap<&str, &Struct> {
+        let mut models = self
+            .confirmed
+            .iter()
+            .map(|(selector, model)| (*selector, model))
+            .collect::<HashMap<_, _>>();
+
+        for write in &self.pending {
+            models.insert(write.selector, &write.model);
+        }
+
+        models.into_values().map(|model| (model.name.as_str(), model)).collect()
+    }
+}
+
+/// Entities synced from Torii, keyed by their hashed keys.
+#[derive(Debug, Default)]
+pub struct EntityStore {
+    entities: HashMap<Felt, StoredEntity>,
+    next_write_id: WriteId,
+    /// Writes resolved by the updates of the synced entities, until they are taken.
+    resolved: Vec<(WriteId, WriteStatus)>,
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies an entity confirmed by Torii. The optimistic writes of the updated models are
+    /// confirmed if they match the update, and rolled back otherwise.
+    pub fn apply_update(&mut self, entity: Entity) -> Vec<(WriteId, WriteStatus)> {
+        // subscriptions first send an empty entity with their id
+        if entity.hashed_keys == Felt::ZERO {
+            return Vec::new();
+        }
+
+        let stored = self.entities.entry(entity.hashed_keys).or_default();
+
+        let mut statuses = Vec::new();
+        for model in entity.models {
+            // the models of Torii are always named by their tag
+            let Ok(selector) = model_selector(&model) else {
+                continue;
+            };
+
+            let (resolved, pending) = mem::take(&mut stored.pending)
+                .into_iter()
+                .partition::<Vec<_>, _>(|write| write.selector == selector);
+            stored.pending = pending;
+
+            for write in resolved {
+                let status = if write.model == model {
+                    WriteStatus::Confirmed
+                } else {
+                    WriteStatus::RolledBack
+                };
+                statuses.push((write.id, status));
+            }
+
+            stored.confirmed.insert(selector, model);
+        }
+
+        statuses
+    }
+
+    /// Writes a model of an entity before it is confirmed by Torii. Reads return the written model
+    /// until it is confirmed or rolled back. The model has to be named by its tag,
+    /// `namespace-Model`.
+    pub fn optimistic_write(&mut self, hashed_keys: Felt, model: Struct) -> Result<WriteId, Error> {
+        let selector = model_selector(&model)?;
+        let id = self.next_write_id;
+        self.next_write_id += 1;
+
+        self.entities.entry(hashed_keys).or_default().pending.push(OptimisticWrite {
+            id,
+            selector,
+            model,
+        });
+        Ok(id)
+    }
+
+    /// Discards an optimistic write, for instance when its transaction failed. Returns false if
+    /// the write was already confirmed or rolled back.
+    pub fn rollback(&mut self, id: WriteId) -> bool {
+        for stored in self.entities.values_mut() {
+            if let Some(index) = stored.pending.iter().position(|write| write.id == id) {
+                stored.pending.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Removes an entity deleted by Torii. Its pending writes are rolled back.
+    pub fn remove(&mut self, hashed_keys: &Felt) -> Vec<(WriteId, WriteStatus)> {
+        self.entities.remove(hashed_keys).map_or_else(Vec::new, |stored| {
+            stored.pending.into_iter().map(|write| (write.id, WriteStatus::RolledBack)).collect()
+        })
+    }
+
+    /// Takes the statuses of the optimistic writes resolved by the updates of the entities
+    /// synced with [`crate::client::Client::sync_entities`] since the last call.
+    pub fn take_resolved_writes(&mut self) -> Vec<(WriteId, WriteStatus)> {
+        mem::take(&mut self.resolved)
+    }
+
+    pub(crate) fn resolve_writes(&mut self, statuses: Vec<(WriteId, WriteStatus)>) {
+        self.resolved.extend(statuses);
+    }
+
+    /// The entity with its optimistic writes applied.
+    pub fn entity(&self, hashed_keys: &Felt) -> Option<Entity> {
+        self.entities.get(hashed_keys).map(|stored| entity(*hashed_keys, stored))
+    }
+
+    /// The entities matching the query, with their optimistic writes applied. Entities are sorted
+    /// by hashed keys so that the pagination is stable, unless the query is ordered.
+    pub fn entities(&self, query: &Query) -> Result<Vec<Entity>, Error> {
+        Ok(self.entities_page(query)?.0)
+    }
+
+    /// Similarly to [`crate::client::Client::entities_page`], the page of the entities matching
+    /// the query along with the cursor of the next page, which is empty on the last one. Cursors
+    /// are local to the store, and entities can only be ordered by their hashed keys or a member.
+    pub fn entities_page(&self, query: &Query) -> Result<(Vec<Entity>, String), Error> {
+        let (model, member, direction) = match &query.order_by {
+            None => ("", "", OrderDirection::Asc),
+            Some(order_by) if order_by.model.is_empty() && order_by.member.is_empty() => {
+                ("", "", order_by.direction.clone())
+            }
+            // the store doesn't know when Torii last updated the entities
+            Some(order_by) if order_by.model.is_empty() => return Err(Error::UnsupportedQuery),
+            Some(order_by) => {
+                (order_by.model.as_str(), order_by.member.as_str(), order_by.direction.clone())
+            }
+        };
+
+        let mut ordered = Vec::new();
+        for (keys, stored) in &self.entities {
+            let models = stored.models();
+            let matches = match &query.clause {
+                Some(clause) => clause_matches(clause, &models)?,
+                None => true,
+            };
+            if !matches {
+                continue;
+            }
+
+            // like with Torii, entities without the model of the ordered member are left out
+            let value = if model.is_empty() {
+                None
+            } else if models.contains_key(model) {
+                order_value(&models, model, member)?
+            } else {
+                continue;
+            };
+            ordered.push((value, *keys));
+        }
+
+        let compare = |a: &(Option<Value>, Felt), b: &(Option<Value>, Felt)| {
+            a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        };
+        ordered.sort_by(|a, b| match direction {
+            OrderDirection::Asc => compare(a, b),
+            OrderDirection::Desc => compare(b, a),
+        });
+
+        if !query.cursor.is_empty() {
+            let cursor = decode_cursor(&query.cursor)?;
+            ordered.retain(|entry| {
+                compare(entry, &cursor)
+                    == match direction {
+                        OrderDirection::Asc => Ordering::Greater,
+                        OrderDirection::Desc => Ordering::Less,
+                    }
+            });
+        }
+
+        let mut page = ordered
+            .into_iter()
+            .skip(query.offset as usize)
+            .take(if query.limit == 0 { usize::MAX } else { query.limit as usize + 1 })
+            .collect::<Vec<_>>();
+
+        // one more entity than the limit tells whether there's a next page
+        let mut next_cursor = String::new();
+        if query.limit != 0 && page.len() > query.limit as usize {
+            page.truncate(query.limit as usize);
+            next_cursor = page.last().map(encode_cursor).unwrap_or_default();
+        }
+
+        let entities =
+            page.into_iter().map(|(_, keys)| entity(keys, &self.entities[&keys])).collect();
+        Ok((entities, next_cursor))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+fn entity(hashed_keys: Felt, stored: &StoredEntity) -> Entity {
+    let mut models = stored.models().into_values().cloned().collect::<Vec<_>>();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Entity { hashed_keys, models }
+}
+
+// Models are reconciled by selector, so that models of other namespaces never collide
+fn model_selector(model: &Struct) -> Result<Felt, Error> {
+    let (namespace, name) =
+        model.name.split_once('-').ok_or_else(|| Error::InvalidModelName(model.name.clone()))?;
+    Ok(compute_selector_from_names(namespace, name))
+}
+
+fn clause_matches(clause: &Clause, models: &HashMap<&str, &Struct>) -> Result<bool, Error> {
+    Ok(match clause {
+        Clause::Keys(clause) => keys_matches(clause, models),
+        Clause::Member(clause) => member_values(models, &clause.model, &clause.member)
+            .iter()
+            .any(|ty| primitive_matches(ty, &clause.operator, &clause.value)),
+        Clause::Enum(clause) => member_values(models, &clause.model, &clause.member)
+            .iter()
+            .filter_map(|ty| ty.as_enum()?.option().ok())
+            .any(|option| clause.operator.matches(option.as_str(), clause.option.as_str())),
+        Clause::Array(clause) => member_values(models, &clause.model, &clause.member)
+            .iter()
+            .filter_map(|ty| ty.as_array())
+            .any(|elements| match &clause.predicate {
+                ArrayPredicate::Contains(value) => {
+                    elements.iter().any(|ty| primitive_matches(ty, &ComparisonOperator::Eq, value))
+                }
+                ArrayPredicate::Length(operator, length) => {
+                    operator.matches(elements.len(), *length as usize)
+                }
+            }),
+        Clause::Composite(clause) => {
+            // `and` stops at the first clause not matching, `or` at the first one matching
+            let short_circuit = clause.operator == LogicalOperator::Or;
+            for clause in &clause.clauses {
+                if clause_matches(clause, models)? == short_circuit {
+                    return Ok(short_circuit);
+                }
+            }
+            !short_circuit
+        }
+        // full-text search needs the index of Torii
+        Clause::Search(_) => return Err(Error::UnsupportedQuery),
+    })
+}
+
+fn keys_matches(clause: &KeysClause, models: &HashMap<&str, &Struct>) -> bool {
+    models
+        .values()
+        .filter(|model| clause.models.is_empty() || clause.models.contains(&model.name))
+        .any(|model| {
+            let mut keys = Vec::new();
+            for member in model.keys() {
+                match member.ty.serialize() {
+                    Ok(felts) => keys.extend(felts),
+                    Err(_) => return false,
+                }
+            }
+
+            if clause.pattern_matching == PatternMatching::FixedLen
+                && keys.len() != clause.keys.len()
+            {
+                return false;
+            }
+
+            clause.keys.len() <= keys.len()
+                && clause.keys.iter().zip(&keys).all(|(clause_key, key)| {
+                    clause_key.as_ref().map_or(true, |clause_key| clause_key == key)
+                })
+        })
+}
+
+// The values of a member given by its dotted path, the members of structs in arrays having a
+// value per element.
+fn member_values<'a>(models: &HashMap<&str, &'a Struct>, model: &str, member: &str) -> Vec<&'a Ty> {
+    let Some(model) = models.get(model) else {
+        return Vec::new();
+    };
+
+    let mut path = member.split('.');
+    let Some(ty) = path.next().and_then(|name| model.get(name)) else {
+        return Vec::new();
+    };
+
+    path.fold(vec![ty], |values, name| values.into_iter().flat_map(|ty| child(ty, name)).collect())
+}
+
+fn child<'a>(ty: &'a Ty, name: &str) -> Vec<&'a Ty> {
+    match ty {
+        Ty::Struct(s) => s.get(name).into_iter().collect(),
+        Ty::Tuple(elements) => {
+            name.parse::<usize>().ok().and_then(|index| elements.get(index)).into_iter().collect()
+        }
+        Ty::Array(elements) => elements.iter().flat_map(|ty| child(ty, name)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Primitives compared by value, integers of different sizes being comparable
+#[derive(PartialEq, PartialOrd)]
+enum Value {
+    Signed(i128),
+    Unsigned(u128),
+    U256(U256),
+    Felt(Felt),
+    Bool(bool),
+    // byte arrays and the option names of enums, which are ordered as text
+    Text(String),
+}
+
+fn value(primitive: &Primitive) -> Option<Value> {
+    Some(match *primitive {
+        Primitive::I8(v) => Value::Signed(v?.into()),
+        Primitive::I16(v) => Value::Signed(v?.into()),
+        Primitive::I32(v) => Value::Signed(v?.into()),
+        Primitive::I64(v) => Value::Signed(v?.into()),
+        Primitive::I128(v) => Value::Signed(v?),
+        Primitive::U8(v) => Value::Unsigned(v?.into()),
+        Primitive::U16(v) => Value::Unsigned(v?.into()),
+        Primitive::U32(v) | Primitive::USize(v) => Value::Unsigned(v?.into()),
+        Primitive::U64(v) => Value::Unsigned(v?.into()),
+        Primitive::U128(v) => Value::Unsigned(v?),
+        Primitive::U256(v) => Value::U256(v?),
+        Primitive::Bool(v) => Value::Bool(v?),
+        Primitive::Felt252(v) | Primitive::ClassHash(v) | Primitive::ContractAddress(v) => {
+            Value::Felt(v?)
+        }
+    })
+}
+
+// The value of the scalar member an entity is ordered by, none if it isn't set
+fn order_value(
+    models: &HashMap<&str, &Struct>,
+    model: &str,
+    member: &str,
+) -> Result<Option<Value>, Error> {
+    match member_values(models, model, member)[..] {
+        [Ty::Primitive(primitive)] => Ok(value(primitive)),
+        [Ty::ByteArray(bytes)] => Ok(Some(Value::Text(bytes.clone()))),
+        [Ty::Enum(enum_ty)] if enum_ty.options.iter().all(|o| o.ty == Ty::Tuple(vec![])) => {
+            Ok(enum_ty.option().ok().map(Value::Text))
+        }
+        // members in arrays and structured members can't be ordered by, like with Torii
+        _ => Err(Error::UnsupportedQuery),
+    }
+}
+
+// Cursors of the store are the hashed keys of the last entity of the page and the value it's
+// ordered by, tagged with its kind.
+fn encode_cursor((value, keys): &(Option<Value>, Felt)) -> String {
+    let value = match value {
+        None => String::new(),
+        Some(Value::Signed(v)) => format!("i:{v}"),
+        Some(Value::Unsigned(v)) => format!("u:{v}"),
+        Some(Value::U256(v)) => format!("w:{v:x}"),
+        Some(Value::Felt(v)) => format!("f:{v:#x}"),
+        Some(Value::Bool(v)) => format!("b:{v}"),
+        Some(Value::Text(v)) => format!("t:{v}"),
+    };
+    format!("{keys:#x}/{value}")
+}
+
+fn decode_cursor(cursor: &str) -> Result<(Option<Value>, Felt), Error> {
+    let invalid = || Error::InvalidCursor(cursor.to_string());
+
+    let (keys, value) = cursor.split_once('/').ok_or_else(invalid)?;
+    let keys = Felt::from_hex(keys).map_err(|_| invalid())?;
+    if value.is_empty() {
+        return Ok((None, keys));
+    }
+
+    let value = match value.split_once(':').ok_or_else(invalid)? {
+        ("i", v) => Value::Signed(v.parse().map_err(|_| invalid())?),
+        ("u", v) => Value::Unsigned(v.parse().map_err(|_| invalid())?),
+        ("w", v) if v.len() == 64 && v.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Value::U256(U256::from_be_hex(v))
+        }
+        ("f", v) => Value::Felt(Felt::from_hex(v).map_err(|_| invalid())?),
+        ("b", v) => Value::Bool(v.parse().map_err(|_| invalid())?),
+        ("t", v) => Value::Text(v.to_string()),
+        _ => return Err(invalid()),
+    };
+    Ok((Some(value), keys))
+}
+
+fn primitive_matches(ty: &Ty, operator: &ComparisonOperator, primitive: &Primitive) -> bool {
+    let (Some(lhs), Some(rhs)) = (ty.as_primitive().and_then(value), value(primitive)) else {
+        return false;
+    };
+
+    mem::discriminant(&lhs) == mem::discriminant(&rhs) && operator.matches(lhs, rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::schema::{Enum, EnumOption, Member};
+    use torii_grpc::types::{ArrayClause, CompositeClause, EnumClause, MemberClause, OrderBy};
+
+    use super::*;
+
+    fn player(address: u8, score: u32, direction: u8) -> Struct {
+        Struct {
+            name: "ns-Player".to_string(),
+            children: vec![
+                Member {
+                    name: "address".to_string(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::from(address)))),
+                    key: true,
+                },
+                Member {
+                    name: "score".to_string(),
+                    ty: Ty::Primitive(Primitive::U32(Some(score))),
+                    key: false,
+                },
+                Member {
+                    name: "direction".to_string(),
+                    ty: Ty::Enum(Enum {
+                        name: "Direction".to_string(),
+                        option: Some(direction),
+                        options: vec![
+                            EnumOption { name: "Left".to_string(), ty: Ty::Tuple(vec![]) },
+                            EnumOption { name: "Right".to_string(), ty: Ty::Tuple(vec![]) },
+                        ],
+                    }),
+                    key: false,
+                },
+                Member {
+                    name: "items".to_string(),
+                    ty: Ty::Array(vec![Ty::Primitive(Primitive::U8(Some(address)))]),
+                    key: false,
+                },
+            ],
+        }
+    }
+
+    fn store() -> EntityStore {
+        let mut store = EntityStore::new();
+        for address in 1..=3 {
+            store.apply_update(Entity {
+                hashed_keys: Felt::from(address),
+                models: vec![player(address, address as u32 * 10, address % 2)],
+            });
+        }
+        store
+    }
+
+    fn query(clause: Clause) -> Query {
+        Query { clause: Some(clause), ..Default::default() }
+    }
+
+    fn hashed_keys(entities: Vec<Entity>) -> Vec<Felt> {
+        entities.into_iter().map(|entity| entity.hashed_keys).collect()
+    }
+
+    #[test]
+    fn query_entities() {
+        let store = store();
+
+        let entities = store
+            .entities(&query(Clause::Keys(KeysClause {
+                keys: vec![Some(Felt::TWO)],
+                pattern_matching: PatternMatching::FixedLen,
+                models: vec![],
+            })))
+            .unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::TWO]);
+
+        let score = |operator, value| {
+            Clause::Member(MemberClause {
+                model: "ns-Player".to_string(),
+                member: "score".to_string(),
+                operator,
+                value: Primitive::U64(Some(value)),
+            })
+        };
+        let entities = store.entities(&query(score(ComparisonOperator::Gte, 20))).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::TWO, Felt::THREE]);
+
+        let direction = Clause::Enum(EnumClause {
+            model: "ns-Player".to_string(),
+            member: "direction".to_string(),
+            operator: ComparisonOperator::Eq,
+            option: "Right".to_string(),
+        });
+        let entities = store
+            .entities(&query(Clause::Composite(CompositeClause {
+                operator: LogicalOperator::And,
+                clauses: vec![direction, score(ComparisonOperator::Lt, 30)],
+            })))
+            .unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::ONE]);
+
+        let entities = store
+            .entities(&query(Clause::Array(ArrayClause {
+                model: "ns-Player".to_string(),
+                member: "items".to_string(),
+                predicate: ArrayPredicate::Contains(Primitive::U8(Some(3))),
+            })))
+            .unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::THREE]);
+
+        let entities =
+            store.entities(&Query { limit: 1, offset: 1, ..Default::default() }).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::TWO]);
+    }
+
+    #[test]
+    fn paginate_entities() {
+        let mut store = store();
+
+        let order_by = |member: &str, direction| {
+            Some(OrderBy { model: "ns-Player".to_string(), member: member.to_string(), direction })
+        };
+        let mut query = Query {
+            limit: 1,
+            order_by: order_by("score", OrderDirection::Desc),
+            ..Default::default()
+        };
+
+        let (entities, cursor) = store.entities_page(&query).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::THREE]);
+
+        // the cursor keeps its position while the entities before it are updated
+        store.apply_update(Entity { hashed_keys: Felt::ONE, models: vec![player(1, 40, 1)] });
+        query.cursor = cursor;
+        let (entities, cursor) = store.entities_page(&query).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::TWO]);
+        assert!(cursor.is_empty());
+
+        // entities with the same value are ordered by their hashed keys
+        let query = Query {
+            limit: 2,
+            order_by: order_by("direction", OrderDirection::Asc),
+            ..Default::default()
+        };
+        let (entities, cursor) = store.entities_page(&query).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::TWO, Felt::ONE]);
+        let (entities, cursor) = store.entities_page(&Query { cursor, ..query }).unwrap();
+        assert_eq!(hashed_keys(entities), vec![Felt::THREE]);
+        assert!(cursor.is_empty());
+
+        let query =
+            Query { order_by: order_by("items", OrderDirection::Asc), ..Default::default() };
+        assert!(matches!(store.entities_page(&query), Err(Error::UnsupportedQuery)));
+
+        let query = Query { cursor: "0x1".to_string(), ..Default::default() };
+        assert!(matches!(store.entities_page(&query), Err(Error::InvalidCursor(_))));
+    }
+
+    #[test]
+    fn optimistic_writes() {
+        let mut store = store();
+
+        // reads return the written model until it's confirmed
+        let confirmed = store.optimistic_write(Felt::ONE, player(1, 100, 0)).unwrap();
+        let rolled_back = store.optimistic_write(Felt::TWO, player(2, 100, 0)).unwrap();
+        let failed = store.optimistic_write(Felt::THREE, player(3, 100, 0)).unwrap();
+        assert_eq!(store.entity(&Felt::ONE).unwrap().models, vec![player(1, 100, 0)]);
+
+        let statuses =
+            store.apply_update(Entity { hashed_keys: Felt::ONE, models: vec![player(1, 100, 0)] });
+        assert_eq!(statuses, vec![(confirmed, WriteStatus::Confirmed)]);
+
+        let statuses =
+            store.apply_update(Entity { hashed_keys: Felt::TWO, models: vec![player(2, 50, 0)] });
+        assert_eq!(statuses, vec![(rolled_back, WriteStatus::RolledBack)]);
+        assert_eq!(store.entity(&Felt::TWO).unwrap().models, vec![player(2, 50, 0)]);
+
+        assert!(store.rollback(failed));
+        assert!(!store.rollback(failed));
+        assert_eq!(store.entity(&Felt::THREE).unwrap().models, vec![player(3, 30, 1)]);
+
+        // models are reconciled by selector, writes of other models stay pending
+        let mut position = player(1, 0, 0);
+        position.name = "ns-Position".to_string();
+        let pending = store.optimistic_write(Felt::ONE, position.clone()).unwrap();
+        let statuses =
+            store.apply_update(Entity { hashed_keys: Felt::ONE, models: vec![player(1, 10, 0)] });
+        assert!(statuses.is_empty());
+        assert_eq!(store.entity(&Felt::ONE).unwrap().models.len(), 2);
+
+        // the pending writes of a deleted entity are rolled back
+        assert_eq!(store.remove(&Felt::ONE), vec![(pending, WriteStatus::RolledBack)]);
+        assert!(store.entity(&Felt::ONE).is_none());
+        assert!(store.remove(&Felt::ONE).is_empty());
+
+        let mut unnamed = player(1, 0, 0);
+        unnamed.name = "Player".to_string();
+        assert!(matches!(
+            store.optimistic_write(Felt::ONE, unnamed),
+            Err(Error::InvalidModelName(name)) if name == "Player"
+        ));
+    }
+
+    #[test]
+    fn resolved_writes() {
+        let mut store = store();
+
+        let confirmed = store.optimistic_write(Felt::ONE, player(1, 100, 0)).unwrap();
+        let deleted = store.optimistic_write(Felt::TWO, player(2, 100, 0)).unwrap();
+
+        let statuses =
+            store.apply_update(Entity { hashed_keys: Felt::ONE, models: vec![player(1, 100, 0)] });
+        store.resolve_writes(statuses);
+        let statuses = store.remove(&Felt::TWO);
+        store.resolve_writes(statuses);
+
+        assert_eq!(
+            store.take_resolved_writes(),
+            vec![(confirmed, WriteStatus::Confirmed), (deleted, WriteStatus::RolledBack)]
+        );
+        assert!(store.take_resolved_writes().is_empty());
+    }
+}
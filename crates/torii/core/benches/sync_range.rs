@@ -0,0 +1,121 @@
+//! Indexes the `spawn-and-move-db` fixture from scratch, with all the default processors.
+//!
+//! The fixture must have been extracted beforehand with
+//! `tar -xzf spawn-and-move-db.tar.gz -C /tmp/`.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use camino::Utf8PathBuf;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dojo_test_utils::compiler::CompilerTestSetup;
+use dojo_test_utils::migration::{copy_spawn_and_move_db, prepare_migration_with_world_and_seed};
+use dojo_world::contracts::world::WorldContractReader;
+use katana_runner::{KatanaRunner, KatanaRunnerConfig};
+use scarb::compiler::Profile;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::processors::event_message::EventMessageProcessor;
+use torii_core::processors::metadata_update::MetadataUpdateProcessor;
+use torii_core::processors::register_model::RegisterModelProcessor;
+use torii_core::processors::store_del_record::StoreDelRecordProcessor;
+use torii_core::processors::store_set_record::StoreSetRecordProcessor;
+use torii_core::processors::store_transaction::StoreTransactionProcessor;
+use torii_core::processors::store_update_member::StoreUpdateMemberProcessor;
+use torii_core::processors::store_update_record::StoreUpdateRecordProcessor;
+use torii_core::sql::Sql;
+
+async fn index_world(
+    provider: &JsonRpcClient<HttpTransport>,
+    world_address: Felt,
+    max_concurrent_tasks: usize,
+) {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let class_hash =
+        provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), world_address).await.unwrap();
+    let db = Sql::new(pool, world_address, class_hash).await.unwrap();
+
+    let processors = Processors {
+        event: vec![
+            Box::new(RegisterModelProcessor),
+            Box::new(StoreSetRecordProcessor),
+            Box::new(MetadataUpdateProcessor),
+            Box::new(StoreDelRecordProcessor),
+            Box::new(EventMessageProcessor),
+            Box::new(StoreUpdateRecordProcessor),
+            Box::new(StoreUpdateMemberProcessor),
+        ],
+        transaction: vec![Box::new(StoreTransactionProcessor)],
+        ..Processors::default()
+    };
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let mut engine = Engine::new(
+        WorldContractReader::new(world_address, provider),
+        db,
+        provider,
+        processors,
+        EngineConfig { max_concurrent_tasks, ..Default::default() },
+        shutdown_tx,
+        None,
+    );
+
+    engine.sync_to_head(0, None).await.unwrap();
+}
+
+fn sync_range(c: &mut Criterion) {
+    let setup = CompilerTestSetup::from_examples("../../dojo-core", "../../../examples/");
+    let config = setup.build_test_config("spawn-and-move", Profile::DEV);
+
+    let ws = scarb::ops::read_workspace(config.manifest_path(), &config).unwrap();
+    let manifest_path = Utf8PathBuf::from(config.manifest_path().parent().unwrap());
+    let target_dir = Utf8PathBuf::from(ws.target_dir().to_string()).join("dev");
+
+    let (strat, _) = prepare_migration_with_world_and_seed(
+        manifest_path,
+        target_dir,
+        None,
+        "dojo_examples",
+        "dojo_examples",
+    )
+    .unwrap();
+
+    let seq_config = KatanaRunnerConfig::default().with_db_dir(copy_spawn_and_move_db().as_str());
+    let sequencer = KatanaRunner::new_with_config(seq_config).expect("Failed to start runner.");
+    let provider = sequencer.provider();
+
+    let runtime = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("Torii.SyncRange.SpawnAndMove");
+    group.sample_size(10).measurement_time(Duration::from_secs(30));
+
+    for max_concurrent_tasks in [1, 100] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(max_concurrent_tasks),
+            &max_concurrent_tasks,
+            |b, max_concurrent_tasks| {
+                b.iter(|| {
+                    runtime.block_on(index_world(
+                        provider,
+                        strat.world_address,
+                        *max_concurrent_tasks,
+                    ))
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sync_range);
+criterion_main!(benches);
@@ -66,6 +66,11 @@ impl ModelCache {
         Ok(schema)
     }
 
+    /// Drops the cached schema of a model, for instance when it's upgraded.
+    pub async fn invalidate(&self, selector: &Felt) {
+        self.cache.write().await.remove(selector);
+    }
+
     pub async fn clear(&self) {
         self.cache.write().await.clear();
     }
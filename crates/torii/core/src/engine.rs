@@ -1,22 +1,25 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
 use dojo_world::contracts::world::WorldContractReader;
+use futures_util::future::{try_join, try_join_all};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use starknet::core::types::{
-    BlockId, BlockTag, Event, EventFilter, Felt, MaybePendingBlockWithTxHashes,
-    MaybePendingBlockWithTxs, ReceiptBlock, Transaction, TransactionReceipt,
-    TransactionReceiptWithBlockInfo,
+    BlockId, BlockTag, EmittedEvent, Event, EventFilter, EventsPage, Felt,
+    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, ReceiptBlock, Transaction,
+    TransactionReceipt, TransactionReceiptWithBlockInfo,
 };
-use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::mpsc::Sender as BoundedSender;
 use tokio::time::sleep;
 use tracing::{error, info, trace, warn};
 
-use crate::processors::{BlockProcessor, EventProcessor, TransactionProcessor};
+use crate::processors::{BlockProcessor, EventProcessor, TaskId, TransactionProcessor};
 use crate::sql::Sql;
 #[allow(missing_debug_implementations)]
 pub struct Processors<P: Provider + Sync> {
@@ -31,6 +34,19 @@ impl<P: Provider + Sync> Default for Processors<P> {
     }
 }
 
+impl<P: Provider + Sync> Processors<P> {
+    /// Addresses of the contracts, other than the world, whose events are processed.
+    pub fn contract_addresses(&self) -> Vec<Felt> {
+        let mut addresses = vec![];
+        for address in self.event.iter().filter_map(|processor| processor.contract_address()) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+        addresses
+    }
+}
+
 pub(crate) const LOG_TARGET: &str = "tori_core::engine";
 
 #[derive(Debug)]
@@ -39,6 +55,8 @@ pub struct EngineConfig {
     pub start_block: u64,
     pub events_chunk_size: u64,
     pub index_pending: bool,
+    /// Maximum number of concurrent provider requests and event processing tasks.
+    pub max_concurrent_tasks: usize,
 }
 
 impl Default for EngineConfig {
@@ -48,6 +66,7 @@ impl Default for EngineConfig {
             start_block: 0,
             events_chunk_size: 1000,
             index_pending: false,
+            max_concurrent_tasks: 100,
         }
     }
 }
@@ -56,11 +75,13 @@ impl Default for EngineConfig {
 pub struct Engine<P: Provider + Sync> {
     world: WorldContractReader<P>,
     db: Sql,
-    provider: Box<P>,
+    provider: Arc<P>,
     processors: Processors<P>,
     config: EngineConfig,
     shutdown_tx: Sender<()>,
     block_tx: Option<BoundedSender<u64>>,
+    tasks: BTreeMap<TaskId, Vec<ParallelizedEvent>>,
+    contract_addresses: Vec<Felt>,
 }
 
 struct UnprocessedEvent {
@@ -68,6 +89,24 @@ struct UnprocessedEvent {
     data: Vec<String>,
 }
 
+/// An event whose processing is deferred to its task, see [`EventProcessor::task_identifier`].
+struct ParallelizedEvent {
+    processor_idx: usize,
+    block_number: u64,
+    block_timestamp: u64,
+    transaction_receipt: TransactionReceiptWithBlockInfo,
+    event_id: String,
+    event: Event,
+}
+
+/// A transaction of the world fetched with its receipt, ready to be processed.
+struct FetchedTransaction {
+    block_number: u64,
+    transaction_hash: Felt,
+    transaction: Transaction,
+    receipt: TransactionReceiptWithBlockInfo,
+}
+
 impl<P: Provider + Sync> Engine<P> {
     pub fn new(
         world: WorldContractReader<P>,
@@ -78,7 +117,19 @@ impl<P: Provider + Sync> Engine<P> {
         shutdown_tx: Sender<()>,
         block_tx: Option<BoundedSender<u64>>,
     ) -> Self {
-        Self { world, db, provider: Box::new(provider), processors, config, shutdown_tx, block_tx }
+        let contract_addresses = processors.contract_addresses();
+
+        Self {
+            world,
+            db,
+            provider: Arc::new(provider),
+            processors,
+            config,
+            shutdown_tx,
+            block_tx,
+            tasks: BTreeMap::new(),
+            contract_addresses,
+        }
     }
 
     pub async fn start(&mut self) -> Result<()> {
@@ -164,15 +215,21 @@ impl<P: Provider + Sync> Engine<P> {
                 continue;
             }
 
-            match self
-                .process_transaction_and_receipt(
-                    *transaction.transaction_hash(),
+            let transaction_hash = *transaction.transaction_hash();
+            let result = async {
+                let receipt = self.provider.get_transaction_receipt(transaction_hash).await?;
+                self.process_transaction_and_receipt(
+                    transaction_hash,
                     &transaction,
+                    &receipt,
                     block_number,
                     block.timestamp,
                 )
                 .await
-            {
+            }
+            .await;
+
+            match result {
                 Err(e) => {
                     match e.to_string().as_str() {
                         "TransactionHashNotFound" => {
@@ -197,6 +254,8 @@ impl<P: Provider + Sync> Engine<P> {
             pending_block_tx = Some(*transaction.transaction_hash());
         }
 
+        self.process_tasks().await?;
+
         // Set the head to the last processed pending transaction
         // Head block number should still be latest block number
         self.db.set_head(block_number - 1, pending_block_tx);
@@ -211,140 +270,287 @@ impl<P: Provider + Sync> Engine<P> {
         to: u64,
         pending_block_tx: Option<Felt>,
     ) -> Result<Option<Felt>> {
-        // Process all blocks from current to latest.
-        let get_events = |token: Option<String>| {
-            self.provider.get_events(
-                EventFilter {
-                    from_block: Some(BlockId::Number(from)),
-                    to_block: Some(BlockId::Number(to)),
-                    address: Some(self.world.address),
-                    keys: None,
-                },
-                token,
-                self.config.events_chunk_size,
+        let mut cursor = RangeCursor { pending_block_tx: pending_block_tx.map(|tx| (from, tx)) };
+
+        // The events of the world and of the other contracts are fetched separately, one page at a
+        // time, and merged so that the blocks and their transactions are processed in order.
+        let mut streams = std::iter::once(self.world.address)
+            .chain(self.contract_addresses.clone())
+            .map(|address| EventsStream::new(address, from))
+            .collect::<Vec<_>>();
+
+        // The next pages are fetched while the events of the complete blocks are processed.
+        let mut fetching = (0..streams.len()).collect::<Vec<_>>();
+        let mut events = vec![];
+        loop {
+            let pages = fetching
+                .iter()
+                .map(|idx| {
+                    let stream = &mut streams[*idx];
+                    let token = stream.continuation_token.take();
+                    self.fetch_events_page(stream.address, from, to, token)
+                })
+                .collect::<Vec<_>>();
+
+            let (pages, _) = try_join(
+                try_join_all(pages),
+                self.process_events(std::mem::take(&mut events), to, &mut cursor),
             )
-        };
+            .await?;
 
-        // handle next events pages
-        let mut events_pages = vec![get_events(None).await?];
+            for (idx, page) in fetching.iter().zip(pages) {
+                streams[*idx].push(page, to + 1);
+            }
+
+            // The blocks before the last one fetched of every contract are complete.
+            let complete_to =
+                streams.iter().filter(|stream| !stream.done).map(|stream| stream.fetched_to).min();
+            for stream in &mut streams {
+                events.extend(stream.drain_before(complete_to, to + 1));
+            }
 
-        while let Some(token) = &events_pages.last().unwrap().continuation_token {
-            events_pages.push(get_events(Some(token.clone())).await?);
+            let Some(complete_to) = complete_to else {
+                self.process_events(events, to, &mut cursor).await?;
+                break;
+            };
+
+            fetching = (0..streams.len())
+                .filter(|idx| !streams[*idx].done && streams[*idx].fetched_to == complete_to)
+                .collect();
         }
 
-        // Transactions & blocks to process
-        let mut last_block = 0_u64;
-        let mut blocks = BTreeMap::new();
+        self.process_tasks().await?;
 
-        // Flatten events pages and events according to the pending block cursor
-        // to array of (block_number, transaction_hash)
-        let mut pending_block_tx_cursor = pending_block_tx;
-        let mut transactions = vec![];
-        for events_page in &events_pages {
-            for event in &events_page.events {
-                let block_number = match event.block_number {
-                    Some(block_number) => block_number,
-                    // If the block number is not present, try to fetch it from the transaction
-                    // receipt Should not/rarely happen. Thus the additional
-                    // fetch is acceptable.
-                    None => {
-                        let TransactionReceiptWithBlockInfo { receipt, block } =
-                            self.provider.get_transaction_receipt(event.transaction_hash).await?;
-
-                        match receipt {
-                            TransactionReceipt::Invoke(_) | TransactionReceipt::L1Handler(_) => {
-                                if let ReceiptBlock::Block { block_number, .. } = block {
-                                    block_number
-                                } else {
-                                    // If the block is pending, we assume the block number is the
-                                    // latest + 1
-                                    to + 1
-                                }
-                            }
+        // We return None for the pending_block_tx because our sync_range
+        // retrieves only specific events from the world. so some transactions
+        // might get ignored and wont update the cursor.
+        // so once the sync range is done, we assume all of the tx of the block
+        // have been processed.
 
-                            _ => to + 1,
-                        }
-                    }
-                };
+        self.db.set_head(to, None);
 
-                // Keep track of last block number and fetch block timestamp
-                if block_number > last_block {
-                    let block_timestamp = self.get_block_timestamp(block_number).await?;
-                    blocks.insert(block_number, block_timestamp);
+        self.db.execute().await?;
 
-                    last_block = block_number;
-                }
+        Ok(None)
+    }
 
-                // Then we skip all transactions until we reach the last pending processed
-                // transaction (if any)
-                if let Some(tx) = pending_block_tx_cursor {
-                    if event.transaction_hash != tx {
-                        continue;
-                    }
+    /// Returns a future fetching a page of the events of a contract, which doesn't borrow the
+    /// engine so that it can be polled while a previous page is being processed.
+    fn fetch_events_page(
+        &self,
+        address: Felt,
+        from: u64,
+        to: u64,
+        token: Option<String>,
+    ) -> impl Future<Output = Result<EventsPage>> {
+        let provider = Arc::clone(&self.provider);
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from)),
+            to_block: Some(BlockId::Number(to)),
+            address: Some(address),
+            keys: None,
+        };
+        let chunk_size = self.config.events_chunk_size;
 
-                    pending_block_tx_cursor = None;
-                }
+        async move { Ok(provider.get_events(filter, token, chunk_size).await?) }
+    }
+
+    /// Processes the transactions of the events of complete blocks, in the order of the blocks
+    /// and of the transactions in their block.
+    async fn process_events(
+        &mut self,
+        events: Vec<EmittedEvent>,
+        to: u64,
+        cursor: &mut RangeCursor,
+    ) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
 
-                // Skip the latest pending block transaction events
-                // * as we might have multiple events for the same transaction
-                if let Some(tx) = pending_block_tx {
-                    if event.transaction_hash == tx {
-                        continue;
+        // Transactions of the events to process, by block
+        let mut events_transactions = BTreeMap::<u64, Vec<Felt>>::new();
+        for event in &events {
+            let block_number = match event.block_number {
+                Some(block_number) => block_number,
+                // If the block number is not present, try to fetch it from the transaction
+                // receipt Should not/rarely happen. Thus the additional
+                // fetch is acceptable.
+                None => {
+                    let TransactionReceiptWithBlockInfo { receipt, block } =
+                        self.provider.get_transaction_receipt(event.transaction_hash).await?;
+
+                    match receipt {
+                        TransactionReceipt::Invoke(_) | TransactionReceipt::L1Handler(_) => {
+                            if let ReceiptBlock::Block { block_number, .. } = block {
+                                block_number
+                            } else {
+                                // If the block is pending, we assume the block number is the
+                                // latest + 1
+                                to + 1
+                            }
+                        }
+
+                        _ => to + 1,
                     }
                 }
+            };
+
+            events_transactions.entry(block_number).or_default().push(event.transaction_hash);
+        }
 
-                if let Some((_, last_tx_hash)) = transactions.last() {
-                    // Dedup transactions
-                    // As me might have multiple events for the same transaction
-                    if *last_tx_hash == event.transaction_hash {
-                        continue;
+        // Fetch the blocks, and then the transactions with their receipts, concurrently.
+        // `buffered` keeps the results in order.
+        let this = &*self;
+        let blocks: Vec<(u64, u64, Vec<Felt>)> = stream::iter(events_transactions)
+            .map(|(block_number, transactions)| async move {
+                let (block_timestamp, block_transactions) = this.get_block(block_number).await?;
+                let transactions = order_transactions(&block_transactions, transactions);
+                Ok::<_, anyhow::Error>((block_number, block_timestamp, transactions))
+            })
+            .buffered(this.config.max_concurrent_tasks)
+            .try_collect()
+            .await?;
+
+        let mut transactions = vec![];
+        for (block_number, _, block_transactions) in &blocks {
+            // Skip the transactions of the block until the last processed pending transaction
+            // (if any), which is part of the first block of the range.
+            let mut pending_block_tx_cursor = match cursor.pending_block_tx {
+                Some((pending_block, tx))
+                    if pending_block == *block_number && block_transactions.contains(&tx) =>
+                {
+                    Some(tx)
+                }
+                _ => None,
+            };
+
+            for transaction_hash in block_transactions {
+                if let Some(tx) = pending_block_tx_cursor {
+                    if tx == *transaction_hash {
+                        pending_block_tx_cursor = None;
                     }
+                    continue;
                 }
-                transactions.push((block_number, event.transaction_hash));
+
+                transactions.push((*block_number, *transaction_hash));
             }
         }
 
-        // Process blocks
-        for (block_number, block_timestamp) in blocks.iter() {
+        let this = &*self;
+        let transactions: Vec<FetchedTransaction> = stream::iter(transactions)
+            .map(|(block_number, transaction_hash)| async move {
+                let (transaction, receipt) = try_join(
+                    this.provider.get_transaction_by_hash(transaction_hash),
+                    this.provider.get_transaction_receipt(transaction_hash),
+                )
+                .await?;
+
+                Ok::<_, anyhow::Error>(FetchedTransaction {
+                    block_number,
+                    transaction_hash,
+                    transaction,
+                    receipt,
+                })
+            })
+            .buffered(this.config.max_concurrent_tasks)
+            .try_collect()
+            .await?;
+
+        // Process each block, then its transactions and the events deferred to their task, so
+        // that they aren't held until the end of the range.
+        let mut transactions = transactions.into_iter().peekable();
+        for (block_number, block_timestamp, _) in blocks {
             if let Some(ref block_tx) = self.block_tx {
-                block_tx.send(*block_number).await?;
+                block_tx.send(block_number).await?;
             }
 
-            self.process_block(*block_number, *block_timestamp).await?;
+            self.process_block(block_number, block_timestamp).await?;
             info!(target: LOG_TARGET, block_number = %block_number, "Processed block.");
-        }
 
-        // Process all transactions
-        for (block_number, transaction_hash) in transactions {
-            // Process transaction
-            let transaction = self.provider.get_transaction_by_hash(transaction_hash).await?;
+            while let Some(FetchedTransaction { transaction_hash, transaction, receipt, .. }) =
+                transactions.next_if(|transaction| transaction.block_number == block_number)
+            {
+                self.process_transaction_and_receipt(
+                    transaction_hash,
+                    &transaction,
+                    &receipt,
+                    block_number,
+                    block_timestamp,
+                )
+                .await?;
+            }
 
-            self.process_transaction_and_receipt(
-                transaction_hash,
-                &transaction,
-                block_number,
-                blocks[&block_number],
-            )
-            .await?;
+            self.process_tasks().await?;
         }
 
-        // We return None for the pending_block_tx because our sync_range
-        // retrieves only specific events from the world. so some transactions
-        // might get ignored and wont update the cursor.
-        // so once the sync range is done, we assume all of the tx of the block
-        // have been processed.
+        Ok(())
+    }
 
-        self.db.set_head(to, None);
+    /// Processes the events deferred to their task. The tasks run concurrently, each one on its
+    /// own clone of the database, and the events of a same task are processed in order.
+    async fn process_tasks(&mut self) -> Result<()> {
+        if self.tasks.is_empty() {
+            return Ok(());
+        }
 
+        // Flush the queued queries so that the clones used by the tasks start with an empty queue.
         self.db.execute().await?;
 
-        Ok(None)
+        let tasks = std::mem::take(&mut self.tasks);
+        let (world, processors, db) = (&self.world, &self.processors.event, &self.db);
+
+        let databases: Vec<Sql> = stream::iter(tasks.into_values())
+            .map(|events| async move {
+                let mut db = db.clone();
+
+                for ParallelizedEvent {
+                    processor_idx,
+                    block_number,
+                    block_timestamp,
+                    transaction_receipt,
+                    event_id,
+                    event,
+                } in events
+                {
+                    let processor = &processors[processor_idx];
+                    if let Err(e) = processor
+                        .process(
+                            world,
+                            &mut db,
+                            block_number,
+                            block_timestamp,
+                            &transaction_receipt,
+                            &event_id,
+                            &event,
+                        )
+                        .await
+                    {
+                        error!(target: LOG_TARGET, event_name = processor.event_key(), error = %e, "Processing parallelized event.");
+                    }
+                }
+
+                db
+            })
+            .buffer_unordered(self.config.max_concurrent_tasks)
+            .collect()
+            .await;
+
+        for db in databases {
+            self.db.merge(db);
+        }
+
+        Ok(())
     }
 
-    async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
+    /// Returns the timestamp of a block and the hashes of its transactions.
+    async fn get_block(&self, block_number: u64) -> Result<(u64, Vec<Felt>)> {
         match self.provider.get_block_with_tx_hashes(BlockId::Number(block_number)).await? {
-            MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
-            MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+            MaybePendingBlockWithTxHashes::Block(block) => {
+                Ok((block.timestamp, block.transactions))
+            }
+            MaybePendingBlockWithTxHashes::PendingBlock(block) => {
+                Ok((block.timestamp, block.transactions))
+            }
         }
     }
 
@@ -352,10 +558,10 @@ impl<P: Provider + Sync> Engine<P> {
         &mut self,
         transaction_hash: Felt,
         transaction: &Transaction,
+        receipt: &TransactionReceiptWithBlockInfo,
         block_number: u64,
         block_timestamp: u64,
     ) -> Result<()> {
-        let receipt = self.provider.get_transaction_receipt(transaction_hash).await?;
         let events = match &receipt.receipt {
             TransactionReceipt::Invoke(receipt) => Some(&receipt.events),
             TransactionReceipt::L1Handler(receipt) => Some(&receipt.events),
@@ -365,23 +571,17 @@ impl<P: Provider + Sync> Engine<P> {
         if let Some(events) = events {
             let mut world_event = false;
             for (event_idx, event) in events.iter().enumerate() {
-                if event.from_address != self.world.address {
+                if event.from_address == self.world.address {
+                    world_event = true;
+                } else if !self.contract_addresses.contains(&event.from_address) {
                     continue;
                 }
 
-                world_event = true;
                 let event_id =
                     format!("{:#064x}:{:#x}:{:#04x}", block_number, transaction_hash, event_idx);
 
-                Self::process_event(
-                    self,
-                    block_number,
-                    block_timestamp,
-                    &receipt,
-                    &event_id,
-                    event,
-                )
-                .await?;
+                Self::process_event(self, block_number, block_timestamp, receipt, &event_id, event)
+                    .await?;
             }
 
             if world_event {
@@ -389,7 +589,7 @@ impl<P: Provider + Sync> Engine<P> {
                     self,
                     block_number,
                     block_timestamp,
-                    &receipt,
+                    receipt,
                     transaction_hash,
                     transaction,
                 )
@@ -442,20 +642,38 @@ impl<P: Provider + Sync> Engine<P> {
         event_id: &str,
         event: &Event,
     ) -> Result<()> {
-        self.db.store_event(
-            event_id,
-            event,
-            *transaction_receipt.receipt.transaction_hash(),
-            block_timestamp,
-        );
-        for processor in &self.processors.event {
+        if event.from_address == self.world.address {
+            self.db.store_event(
+                event_id,
+                event,
+                *transaction_receipt.receipt.transaction_hash(),
+                block_timestamp,
+            );
+        }
+
+        for (processor_idx, processor) in self.processors.event.iter().enumerate() {
+            if processor.contract_address().unwrap_or(self.world.address) != event.from_address {
+                continue;
+            }
+
             // If the processor has no event_key, means it's a catch-all processor.
             // We also validate the event
             if (processor.event_key().is_empty()
-                || get_selector_from_name(&processor.event_key())? == event.keys[0])
+                || event.keys.first() == Some(&processor.event_selector()?))
                 && processor.validate(event)
             {
-                if let Err(e) = processor
+                // Events that can be parallelized are deferred to their task, which keeps
+                // the events of a same task (e.g. entity) ordered.
+                if let Some(task_identifier) = processor.task_identifier(event) {
+                    self.tasks.entry(task_identifier).or_default().push(ParallelizedEvent {
+                        processor_idx,
+                        block_number,
+                        block_timestamp,
+                        transaction_receipt: transaction_receipt.clone(),
+                        event_id: event_id.to_string(),
+                        event: event.clone(),
+                    });
+                } else if let Err(e) = processor
                     .process(
                         &self.world,
                         &mut self.db,
@@ -486,3 +704,142 @@ impl<P: Provider + Sync> Engine<P> {
         Ok(())
     }
 }
+
+/// State carried from one batch of complete blocks to the next while syncing a range.
+struct RangeCursor {
+    /// The block of the range that was pending, with its last processed transaction, if any.
+    pending_block_tx: Option<(u64, Felt)>,
+}
+
+/// The events of a contract fetched while syncing a range, not processed yet.
+struct EventsStream {
+    address: Felt,
+    events: VecDeque<EmittedEvent>,
+    continuation_token: Option<String>,
+    /// Whether all the events of the range have been fetched.
+    done: bool,
+    /// The block of the last fetched event, the events of the previous blocks are all fetched.
+    fetched_to: u64,
+}
+
+impl EventsStream {
+    fn new(address: Felt, from: u64) -> Self {
+        Self {
+            address,
+            events: VecDeque::new(),
+            continuation_token: None,
+            done: false,
+            fetched_to: from,
+        }
+    }
+
+    /// Adds the events of the next page, the ones without block being part of `pending_block`.
+    fn push(&mut self, page: EventsPage, pending_block: u64) {
+        if let Some(event) = page.events.last() {
+            self.fetched_to = event.block_number.unwrap_or(pending_block);
+        }
+
+        self.events.extend(page.events);
+        self.done = page.continuation_token.is_none();
+        self.continuation_token = page.continuation_token;
+    }
+
+    /// Removes the events emitted before the block `to`, or all of them if there's none.
+    fn drain_before(
+        &mut self,
+        to: Option<u64>,
+        pending_block: u64,
+    ) -> impl Iterator<Item = EmittedEvent> + '_ {
+        let end = match to {
+            Some(to) => self
+                .events
+                .iter()
+                .position(|event| event.block_number.unwrap_or(pending_block) >= to)
+                .unwrap_or(self.events.len()),
+            None => self.events.len(),
+        };
+
+        self.events.drain(..end)
+    }
+}
+
+/// Orders the transactions of the events of a block as in the block, without duplicates. The
+/// transactions missing from the block (e.g. pending ones) come last.
+///
+/// The events of a block, from all the contracts, are processed together, so transactions only
+/// need to be deduplicated within their block and the previous blocks aren't remembered.
+fn order_transactions(block_transactions: &[Felt], transactions: Vec<Felt>) -> Vec<Felt> {
+    let positions = block_transactions
+        .iter()
+        .enumerate()
+        .map(|(position, hash)| (*hash, position))
+        .collect::<HashMap<_, _>>();
+
+    let mut transactions = transactions
+        .into_iter()
+        .map(|hash| (positions.get(&hash).copied().unwrap_or(usize::MAX), hash))
+        .collect::<Vec<_>>();
+    transactions.sort_unstable();
+    transactions.dedup();
+
+    transactions.into_iter().map(|(_, hash)| hash).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(events: &[(Option<u64>, u64)], continuation_token: Option<&str>) -> EventsPage {
+        EventsPage {
+            events: events
+                .iter()
+                .map(|(block_number, transaction_hash)| EmittedEvent {
+                    from_address: Felt::ONE,
+                    keys: vec![],
+                    data: vec![],
+                    block_hash: None,
+                    block_number: *block_number,
+                    transaction_hash: Felt::from(*transaction_hash),
+                })
+                .collect(),
+            continuation_token: continuation_token.map(str::to_string),
+        }
+    }
+
+    fn transactions(events: impl Iterator<Item = EmittedEvent>) -> Vec<Felt> {
+        events.map(|event| event.transaction_hash).collect()
+    }
+
+    #[test]
+    fn streams_drain_complete_blocks() {
+        let mut stream = EventsStream::new(Felt::ONE, 0);
+        stream.push(page(&[(Some(1), 1), (Some(2), 2), (Some(2), 3)], Some("next")), 10);
+        assert!(!stream.done);
+        assert_eq!(stream.fetched_to, 2);
+
+        // The events of the last fetched block may continue on the next page.
+        assert_eq!(transactions(stream.drain_before(Some(2), 10)), vec![Felt::from(1)]);
+
+        stream.push(page(&[(Some(2), 4), (None, 5)], None), 10);
+        assert!(stream.done);
+        assert_eq!(stream.fetched_to, 10);
+
+        assert_eq!(
+            transactions(stream.drain_before(None, 10)),
+            vec![Felt::from(2), Felt::from(3), Felt::from(4), Felt::from(5)]
+        );
+    }
+
+    #[test]
+    fn transactions_are_ordered_as_in_their_block() {
+        let block = [Felt::from(1), Felt::from(2), Felt::from(3)];
+
+        assert_eq!(
+            order_transactions(
+                &block,
+                vec![Felt::from(3), Felt::from(4), Felt::from(1), Felt::from(3)]
+            ),
+            vec![Felt::from(1), Felt::from(3), Felt::from(4)]
+        );
+    }
+}
@@ -51,4 +51,10 @@ pub enum QueryError {
     SqliteJoinLimit,
     #[error("Invalid namespaced model: {0}")]
     InvalidNamespacedModel(String),
+    #[error("Member not found: {0}")]
+    MemberNotFound(String),
+    #[error("Unsupported aggregate: {0}")]
+    UnsupportedAggregate(String),
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
 }
@@ -9,6 +9,7 @@ pub mod error;
 pub mod model;
 pub mod processors;
 pub mod query_queue;
+pub mod search;
 pub mod simple_broker;
 pub mod sql;
 pub mod types;
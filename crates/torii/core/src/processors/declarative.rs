@@ -0,0 +1,481 @@
+//! Event processors declared in a configuration file.
+//!
+//! Each processor maps an event of an arbitrary contract to a user defined table, whose columns
+//! are the keys and data of the event:
+//!
+//! ```toml
+//! [[processor]]
+//! contract_address = "0x0123..."
+//! event = "Transfer"
+//! table = "transfers"
+//!
+//! [[processor.keys]]
+//! name = "from"
+//! type = "ContractAddress"
+//!
+//! [[processor.data]]
+//! name = "amount"
+//! type = "u256"
+//! ```
+//!
+//! The event selector is either computed from the `event` name or given directly as `selector`.
+//! Members types are the names of the [`Primitive`] types, or `ByteArray`. In the default `insert`
+//! mode a row is inserted for each event, in the `upsert` mode the row identified by the
+//! `primary_key` columns is inserted or updated.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use async_trait::async_trait;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::world::WorldContractReader;
+use serde::Deserialize;
+use starknet::core::types::{Event, Felt, TransactionReceiptWithBlockInfo};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::Provider;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::query_queue::Argument;
+use crate::search::SEARCH_TABLE;
+use crate::sql::Sql;
+use crate::utils::utc_dt_string_from_timestamp;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::processors::declarative";
+
+/// Tables created by Torii, which can't be declared by processors.
+const RESERVED_TABLES: &[&str] = &[
+    "entities",
+    "entity_model",
+    "event_messages",
+    "event_model",
+    "events",
+    "indexers",
+    "metadata",
+    "model_members",
+    "models",
+    "system_calls",
+    "transaction_receipts",
+    "transactions",
+    "worlds",
+];
+
+/// Prefixes of the tables created by SQLite, sqlx and the full-text search index.
+const RESERVED_TABLE_PREFIXES: &[&str] = &["sqlite_", "_sqlx", SEARCH_TABLE];
+
+/// Columns added by the processors to every table.
+const RESERVED_COLUMNS: &[&str] = &["event_id", "executed_at", "created_at", "updated_at"];
+
+/// The content of a processors configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProcessorsConfig {
+    #[serde(default, rename = "processor")]
+    pub processors: Vec<DeclarativeProcessorConfig>,
+}
+
+impl ProcessorsConfig {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read processors config {}", path.display()))?;
+
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse processors config {}", path.display()))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks that the tables are distinct and don't collide with the tables of Torii.
+    pub fn validate(&self) -> Result<()> {
+        let mut tables = HashSet::new();
+
+        for processor in &self.processors {
+            let table = &processor.table;
+            validate_identifier(table)?;
+
+            let lowercase = table.to_lowercase();
+            if RESERVED_TABLES.contains(&lowercase.as_str())
+                || RESERVED_TABLE_PREFIXES.iter().any(|prefix| lowercase.starts_with(prefix))
+            {
+                bail!("Table {table} is reserved by Torii");
+            }
+
+            // Table names are case insensitive.
+            if !tables.insert(lowercase) {
+                bail!("Table {table} is declared by several processors");
+            }
+
+            for column in processor.keys.iter().chain(&processor.data) {
+                validate_identifier(&column.name)?;
+
+                if RESERVED_COLUMNS.contains(&column.name.to_lowercase().as_str()) {
+                    bail!("Column {} of table {table} is reserved", column.name);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeclarativeProcessorConfig {
+    /// Address of the contract emitting the event.
+    pub contract_address: Felt,
+    /// Name of the event, used to compute its selector.
+    pub event: Option<String>,
+    /// Selector of the event, if its name isn't given.
+    pub selector: Option<Felt>,
+    /// Name of the table the events are written to.
+    pub table: String,
+    #[serde(default)]
+    pub mode: WriteMode,
+    /// Columns identifying a row in the `upsert` mode.
+    #[serde(default)]
+    pub primary_key: Vec<String>,
+    /// Members of the event keys, without the event selector.
+    #[serde(default)]
+    pub keys: Vec<ColumnConfig>,
+    /// Members of the event data.
+    #[serde(default)]
+    pub data: Vec<ColumnConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnConfig {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteMode {
+    #[default]
+    Insert,
+    Upsert,
+}
+
+/// Processes the events of a [`DeclarativeProcessorConfig`].
+#[derive(Debug, Clone)]
+pub struct DeclarativeProcessor {
+    config: DeclarativeProcessorConfig,
+    selector: Felt,
+    keys: Ty,
+    data: Ty,
+}
+
+impl DeclarativeProcessor {
+    pub fn new(config: DeclarativeProcessorConfig) -> Result<Self> {
+        let selector = match (&config.event, config.selector) {
+            (_, Some(selector)) => selector,
+            (Some(event), None) => get_selector_from_name(event)?,
+            (None, None) => bail!("Processor of table {} has no event nor selector", config.table),
+        };
+
+        let keys = columns_schema(&config.table, &config.keys)?;
+        let data = columns_schema(&config.table, &config.data)?;
+
+        if config.mode == WriteMode::Upsert {
+            if config.primary_key.is_empty() {
+                bail!("Processor of table {} must have a primary key to upsert", config.table);
+            }
+
+            for column in &config.primary_key {
+                if !config.keys.iter().chain(&config.data).any(|c| &c.name == column) {
+                    bail!("Primary key {column} is not a member of table {}", config.table);
+                }
+            }
+        }
+
+        Ok(Self { config, selector, keys, data })
+    }
+
+    /// Enqueues the creation of the table the events are written to.
+    pub fn create_table(&self, db: &mut Sql) {
+        let mut columns = vec!["event_id TEXT NOT NULL".to_string()];
+
+        for member in self.members() {
+            let sql_type = match &member.ty {
+                Ty::Primitive(primitive) => primitive.to_sql_type().as_ref().to_string(),
+                _ => "TEXT".to_string(),
+            };
+            columns.push(format!("[{}] {sql_type}", member.name));
+        }
+
+        columns.push("executed_at DATETIME NOT NULL".to_string());
+        columns.push("created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP".to_string());
+        columns.push("updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP".to_string());
+        columns.push(format!("PRIMARY KEY ({})", self.primary_key().join(", ")));
+
+        db.enqueue(
+            format!("CREATE TABLE IF NOT EXISTS [{}] ({})", self.config.table, columns.join(", ")),
+            vec![],
+        );
+    }
+
+    /// Deserializes the event keys and data into the columns values.
+    fn row(&self, event: &Event) -> Result<Vec<(String, Argument)>> {
+        let mut keys = self.keys.clone();
+        let mut data = self.data.clone();
+
+        keys.deserialize(&mut event.keys[1..].to_vec())?;
+        data.deserialize(&mut event.data.clone())?;
+
+        let members = [keys, data]
+            .into_iter()
+            .flat_map(|ty| ty.as_struct().map(|s| s.children.clone()).unwrap_or_default());
+
+        members
+            .map(|member| {
+                let value = match &member.ty {
+                    Ty::Primitive(primitive) => primitive.to_sql_value()?,
+                    Ty::ByteArray(bytes) => bytes.clone(),
+                    _ => return Err(anyhow!("Unsupported type for member {}", member.name)),
+                };
+
+                Ok::<_, Error>((member.name, Argument::String(value)))
+            })
+            .collect()
+    }
+
+    fn members(&self) -> impl Iterator<Item = &Member> {
+        [&self.keys, &self.data]
+            .into_iter()
+            .filter_map(|ty| ty.as_struct())
+            .flat_map(|s| s.children.iter())
+    }
+
+    fn primary_key(&self) -> Vec<String> {
+        match self.config.mode {
+            WriteMode::Insert => vec!["event_id".to_string()],
+            WriteMode::Upsert => self.config.primary_key.iter().map(|c| format!("[{c}]")).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl<P> EventProcessor<P> for DeclarativeProcessor
+where
+    P: Provider + Send + Sync + std::fmt::Debug,
+{
+    fn event_key(&self) -> String {
+        self.config.event.clone().unwrap_or_else(|| format!("{:#x}", self.selector))
+    }
+
+    fn event_selector(&self) -> Result<Felt> {
+        Ok(self.selector)
+    }
+
+    fn contract_address(&self) -> Option<Felt> {
+        Some(self.config.contract_address)
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        !event.keys.is_empty()
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block_number: u64,
+        block_timestamp: u64,
+        _transaction_receipt: &TransactionReceiptWithBlockInfo,
+        event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let row = self.row(event)?;
+
+        info!(
+            target: LOG_TARGET,
+            table = %self.config.table,
+            event_id = %event_id,
+            "Declarative event.",
+        );
+
+        let mut columns = vec!["event_id".to_string(), "executed_at".to_string()];
+        let mut arguments = vec![
+            Argument::String(event_id.to_string()),
+            Argument::String(utc_dt_string_from_timestamp(block_timestamp)),
+        ];
+
+        for (name, value) in row {
+            columns.push(format!("[{name}]"));
+            arguments.push(value);
+        }
+
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let statement = match self.config.mode {
+            WriteMode::Insert => format!(
+                "INSERT OR IGNORE INTO [{}] ({}) VALUES ({placeholders})",
+                self.config.table,
+                columns.join(", ")
+            ),
+            WriteMode::Upsert => format!(
+                "INSERT INTO [{}] ({}) VALUES ({placeholders}) ON CONFLICT({}) DO UPDATE SET {}, \
+                 updated_at=CURRENT_TIMESTAMP",
+                self.config.table,
+                columns.join(", "),
+                self.primary_key().join(", "),
+                columns.iter().map(|c| format!("{c}=EXCLUDED.{c}")).collect::<Vec<_>>().join(", ")
+            ),
+        };
+
+        db.enqueue(statement, arguments);
+
+        Ok(())
+    }
+}
+
+/// Checks that a table or column name is a plain identifier, which can't escape its brackets in
+/// the statements or be mistaken for a model table.
+fn validate_identifier(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        bail!("Invalid name {name}, only letters, digits and underscores are allowed");
+    }
+
+    Ok(())
+}
+
+/// Builds the schema of the given columns, as a struct named after the table.
+fn columns_schema(table: &str, columns: &[ColumnConfig]) -> Result<Ty> {
+    let children = columns
+        .iter()
+        .map(|column| {
+            let ty = if column.ty == "ByteArray" {
+                Ty::ByteArray(String::new())
+            } else {
+                Ty::Primitive(Primitive::from_str(&column.ty).map_err(|_| {
+                    anyhow!("Unsupported type {} for member {}", column.ty, column.name)
+                })?)
+            };
+
+            Ok(Member { name: column.name.clone(), ty, key: false })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Ty::Struct(Struct { name: table.to_string(), children }))
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::Felt;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [[processor]]
+        contract_address = "0x1234"
+        event = "Transfer"
+        table = "transfers"
+
+        [[processor.keys]]
+        name = "from"
+        type = "ContractAddress"
+
+        [[processor.data]]
+        name = "amount"
+        type = "u256"
+
+        [[processor]]
+        contract_address = "0x1234"
+        selector = "0x99"
+        table = "names"
+        mode = "upsert"
+        primary_key = ["player"]
+
+        [[processor.keys]]
+        name = "player"
+        type = "ContractAddress"
+
+        [[processor.data]]
+        name = "name"
+        type = "ByteArray"
+    "#;
+
+    #[test]
+    fn parse_processors_config() {
+        let config: ProcessorsConfig = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.processors.len(), 2);
+
+        let transfers = DeclarativeProcessor::new(config.processors[0].clone()).unwrap();
+        assert_eq!(transfers.selector, get_selector_from_name("Transfer").unwrap());
+        assert_eq!(transfers.primary_key(), vec!["event_id"]);
+
+        let names = DeclarativeProcessor::new(config.processors[1].clone()).unwrap();
+        assert_eq!(names.selector, felt!("0x99"));
+        assert_eq!(names.primary_key(), vec!["[player]"]);
+    }
+
+    #[test]
+    fn invalid_processors_config() {
+        let mut config: ProcessorsConfig = toml::from_str(CONFIG).unwrap();
+
+        let mut unknown_type = config.processors[0].clone();
+        unknown_type.data[0].ty = "u512".to_string();
+        assert!(DeclarativeProcessor::new(unknown_type).is_err());
+
+        config.processors[1].primary_key = vec!["unknown".to_string()];
+        assert!(DeclarativeProcessor::new(config.processors[1].clone()).is_err());
+    }
+
+    #[test]
+    fn reserved_tables() {
+        let config: ProcessorsConfig = toml::from_str(CONFIG).unwrap();
+        config.validate().unwrap();
+
+        for table in ["entities", "Models", "entities_search_data", "sqlite_master", "ns-Position"]
+        {
+            let mut config = config.clone();
+            config.processors[0].table = table.to_string();
+            assert!(config.validate().is_err(), "{table} should be rejected");
+        }
+
+        let mut duplicated = config.clone();
+        duplicated.processors[1].table = "TRANSFERS".to_string();
+        assert!(duplicated.validate().is_err());
+
+        let mut reserved_column = config;
+        reserved_column.processors[0].data[0].name = "event_id".to_string();
+        assert!(reserved_column.validate().is_err());
+    }
+
+    #[test]
+    fn event_to_row() {
+        let config: ProcessorsConfig = toml::from_str(CONFIG).unwrap();
+        let processor = DeclarativeProcessor::new(config.processors[0].clone()).unwrap();
+
+        let event = Event {
+            from_address: felt!("0x1234"),
+            keys: vec![processor.selector, felt!("0xa")],
+            data: vec![Felt::ONE, Felt::ZERO],
+        };
+
+        let row = processor.row(&event).unwrap();
+        let row = row
+            .into_iter()
+            .map(|(name, value)| match value {
+                Argument::String(value) => (name, value),
+                _ => panic!("expected a string argument"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            row,
+            vec![
+                ("from".to_string(), format!("{:#064x}", felt!("0xa"))),
+                ("amount".to_string(), format!("0x{:064x}", 1)),
+            ]
+        );
+    }
+}
@@ -1,11 +1,15 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{Event, Felt, Transaction, TransactionReceiptWithBlockInfo};
+use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 
 use crate::sql::Sql;
 
+pub mod declarative;
 pub mod event_message;
 pub mod metadata_update;
 pub mod register_model;
@@ -19,6 +23,17 @@ const MODEL_INDEX: usize = 0;
 const NUM_KEYS_INDEX: usize = 1;
 const ENTITY_ID_INDEX: usize = 1;
 
+/// Identifier of the task an event is processed in. Events sharing the same identifier are
+/// processed sequentially, in the order they were emitted.
+pub type TaskId = u64;
+
+/// Computes the task identifier of the events touching the given entity.
+pub(crate) fn entity_task_identifier(entity_id: Felt) -> TaskId {
+    let mut hasher = DefaultHasher::new();
+    entity_id.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[async_trait]
 pub trait EventProcessor<P>
 where
@@ -26,12 +41,28 @@ where
 {
     fn event_key(&self) -> String;
 
+    /// Selector of the processed event, computed from its key by default.
+    fn event_selector(&self) -> Result<Felt> {
+        Ok(get_selector_from_name(&self.event_key())?)
+    }
+
+    /// Address of the contract emitting the processed event, the world if `None`.
+    fn contract_address(&self) -> Option<Felt> {
+        None
+    }
+
     fn event_keys_as_string(&self, event: &Event) -> String {
         event.keys.iter().map(|i| format!("{:#064x}", i)).collect::<Vec<_>>().join(",")
     }
 
     fn validate(&self, event: &Event) -> bool;
 
+    /// Returns the task this event can be processed in, concurrently with the events of the other
+    /// tasks. Events returning `None` are processed immediately, in order.
+    fn task_identifier(&self, _event: &Event) -> Option<TaskId> {
+        None
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn process(
         &self,
@@ -6,7 +6,7 @@ use starknet::core::types::{Event, TransactionReceiptWithBlockInfo};
 use starknet::providers::Provider;
 use tracing::info;
 
-use super::EventProcessor;
+use super::{entity_task_identifier, EventProcessor, TaskId};
 use crate::processors::{ENTITY_ID_INDEX, MODEL_INDEX};
 use crate::sql::Sql;
 
@@ -37,6 +37,10 @@ where
         true
     }
 
+    fn task_identifier(&self, event: &Event) -> Option<TaskId> {
+        event.data.get(ENTITY_ID_INDEX).map(|entity_id| entity_task_identifier(*entity_id))
+    }
+
     async fn process(
         &self,
         _world: &WorldContractReader<P>,
@@ -44,7 +48,7 @@ where
         _block_number: u64,
         _block_timestamp: u64,
         _transaction_receipt: &TransactionReceiptWithBlockInfo,
-        _event_id: &str,
+        event_id: &str,
         event: &Event,
     ) -> Result<(), Error> {
         let selector = event.data[MODEL_INDEX];
@@ -60,7 +64,7 @@ where
         let entity_id = event.data[ENTITY_ID_INDEX];
         let entity = model.schema().await?;
 
-        db.delete_entity(entity_id, entity).await?;
+        db.delete_entity(entity_id, entity, event_id).await?;
 
         Ok(())
     }
@@ -5,9 +5,10 @@ use dojo_world::contracts::world::WorldContractReader;
 use num_traits::ToPrimitive;
 use starknet::core::types::{Event, TransactionReceiptWithBlockInfo};
 use starknet::providers::Provider;
+use starknet_crypto::poseidon_hash_many;
 use tracing::info;
 
-use super::EventProcessor;
+use super::{entity_task_identifier, EventProcessor, TaskId};
 use crate::processors::{MODEL_INDEX, NUM_KEYS_INDEX};
 use crate::sql::Sql;
 
@@ -38,6 +39,14 @@ where
         true
     }
 
+    fn task_identifier(&self, event: &Event) -> Option<TaskId> {
+        let keys_start = NUM_KEYS_INDEX + 1;
+        let keys_end = keys_start + event.data.get(NUM_KEYS_INDEX)?.to_usize()?;
+        let keys = event.data.get(keys_start..keys_end)?;
+
+        Some(entity_task_identifier(poseidon_hash_many(keys)))
+    }
+
     async fn process(
         &self,
         _world: &WorldContractReader<P>,
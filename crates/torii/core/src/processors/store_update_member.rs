@@ -9,7 +9,7 @@ use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 use tracing::{info, warn};
 
-use super::EventProcessor;
+use super::{entity_task_identifier, EventProcessor, TaskId};
 use crate::processors::{ENTITY_ID_INDEX, MODEL_INDEX};
 use crate::sql::Sql;
 
@@ -42,6 +42,10 @@ where
         true
     }
 
+    fn task_identifier(&self, event: &Event) -> Option<TaskId> {
+        event.data.get(ENTITY_ID_INDEX).map(|entity_id| entity_task_identifier(*entity_id))
+    }
+
     async fn process(
         &self,
         _world: &WorldContractReader<P>,
@@ -31,7 +31,23 @@ impl QueryQueue {
         self.queue.push_front((statement.into(), arguments));
     }
 
+    /// Moves all the queued statements of `other` at the back of this queue.
+    pub fn append(&mut self, mut other: QueryQueue) {
+        self.queue.append(&mut other.queue);
+    }
+
+    /// Executes the queued statements in a single transaction. The statements of a failed
+    /// transaction are dropped with it.
     pub async fn execute_all(&mut self) -> sqlx::Result<u64> {
+        let result = self.execute_transaction().await;
+        if result.is_err() {
+            self.queue.clear();
+        }
+
+        result
+    }
+
+    async fn execute_transaction(&mut self) -> sqlx::Result<u64> {
         let mut total_affected = 0_u64;
         let mut tx = self.pool.begin().await?;
 
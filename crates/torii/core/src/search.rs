@@ -0,0 +1,151 @@
+//! Full-text search over the text members of the models.
+//!
+//! Searchable members are opt-in, they are given as `namespace-Model.member` and must be
+//! `ByteArray` or `felt252` short strings. Their values are kept in a single FTS5 virtual table,
+//! with a row per entity and member.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Error, Result};
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::Ty;
+use starknet::core::utils::parse_cairo_short_string;
+
+pub const SEARCH_TABLE: &str = "entities_search";
+
+/// A searchable model member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchField {
+    /// Tag of the model, `namespace-Model`.
+    pub model: String,
+    pub member: String,
+}
+
+impl FromStr for SearchField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (model, member) = s
+            .rsplit_once('.')
+            .filter(|(model, member)| model.contains('-') && !member.is_empty())
+            .ok_or_else(|| anyhow!("Invalid search field {s}, expected namespace-Model.member"))?;
+
+        Ok(Self { model: model.to_string(), member: member.to_string() })
+    }
+}
+
+impl SearchField {
+    /// Checks that the member is part of the model schema and holds text.
+    pub fn validate(&self, schema: &Ty) -> Result<()> {
+        let member = schema
+            .as_struct()
+            .and_then(|schema| schema.children.iter().find(|m| m.name == self.member))
+            .ok_or_else(|| anyhow!("Search field {}.{} not found", self.model, self.member))?;
+
+        if !matches!(member.ty, Ty::ByteArray(_) | Ty::Primitive(Primitive::Felt252(_))) {
+            bail!(
+                "Search field {}.{} must be a ByteArray or a felt252, found {}",
+                self.model,
+                self.member,
+                member.ty.name()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The statement creating the search table.
+pub fn create_search_table() -> String {
+    format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS {SEARCH_TABLE} USING fts5(id UNINDEXED, model_id \
+         UNINDEXED, member UNINDEXED, content)"
+    )
+}
+
+/// The text of a member, if it's a `ByteArray` or a `felt252` short string.
+pub fn member_text(ty: &Ty) -> Option<String> {
+    match ty {
+        Ty::ByteArray(text) => Some(text.clone()),
+        Ty::Primitive(Primitive::Felt252(Some(felt))) => parse_cairo_short_string(felt).ok(),
+        _ => None,
+    }
+}
+
+/// Builds an FTS5 query matching the rows containing all the words of the text, the last word
+/// being matched as a prefix. Words are quoted so the text can't use the FTS5 query syntax.
+pub fn search_query(text: &str) -> Option<String> {
+    let words = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}*", words.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::schema::{Member, Struct};
+    use starknet::core::types::Felt;
+    use starknet::core::utils::cairo_short_string_to_felt;
+
+    use super::*;
+
+    #[test]
+    fn parse_search_field() {
+        let field = SearchField::from_str("dojo_examples-Guild.name").unwrap();
+        assert_eq!(field.model, "dojo_examples-Guild");
+        assert_eq!(field.member, "name");
+
+        assert!(SearchField::from_str("Guild.name").is_err());
+        assert!(SearchField::from_str("dojo_examples-Guild").is_err());
+    }
+
+    #[test]
+    fn validate_search_field() {
+        let schema = Ty::Struct(Struct {
+            name: "dojo_examples-Guild".to_string(),
+            children: vec![
+                Member { name: "name".to_string(), ty: Ty::ByteArray("".to_string()), key: false },
+                Member {
+                    name: "tag".to_string(),
+                    ty: Ty::Primitive(Primitive::Felt252(None)),
+                    key: false,
+                },
+                Member {
+                    name: "size".to_string(),
+                    ty: Ty::Primitive(Primitive::U8(None)),
+                    key: false,
+                },
+            ],
+        });
+
+        let field = |member: &str| SearchField::from_str(&format!("dojo_examples-Guild.{member}"));
+        assert!(field("name").unwrap().validate(&schema).is_ok());
+        assert!(field("tag").unwrap().validate(&schema).is_ok());
+        assert!(field("size").unwrap().validate(&schema).is_err());
+        assert!(field("motto").unwrap().validate(&schema).is_err());
+    }
+
+    #[test]
+    fn text_of_members() {
+        assert_eq!(member_text(&Ty::ByteArray("Knights".to_string())).unwrap(), "Knights");
+
+        let felt = cairo_short_string_to_felt("Sword").unwrap();
+        assert_eq!(member_text(&Ty::Primitive(Primitive::Felt252(Some(felt)))).unwrap(), "Sword");
+
+        assert!(member_text(&Ty::Primitive(Primitive::U8(Some(1)))).is_none());
+        assert!(member_text(&Ty::Primitive(Primitive::Felt252(Some(Felt::MAX)))).is_none());
+    }
+
+    #[test]
+    fn quote_search_query() {
+        assert_eq!(search_query("iron sw").unwrap(), "\"iron\" \"sw\"*");
+        assert_eq!(search_query("a\"b OR").unwrap(), "\"a\"\"b\" \"OR\"*");
+        assert!(search_query("  ").is_none());
+    }
+}
@@ -6,6 +6,7 @@ use chrono::Utc;
 use dojo_types::primitive::Primitive;
 use dojo_types::schema::{EnumOption, Member, Struct, Ty};
 use dojo_world::contracts::abi::model::Layout;
+use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::naming::compute_selector_from_names;
 use dojo_world::metadata::WorldMetadata;
 use sqlx::pool::PoolConnection;
@@ -16,6 +17,7 @@ use starknet_crypto::poseidon_hash_many;
 use super::World;
 use crate::model::ModelSQLReader;
 use crate::query_queue::{Argument, QueryQueue};
+use crate::search::{create_search_table, member_text, SearchField, SEARCH_TABLE};
 use crate::simple_broker::SimpleBroker;
 use crate::types::{
     Entity as EntityUpdated, Event as EventEmitted, EventMessage as EventMessageUpdated,
@@ -37,6 +39,7 @@ pub struct Sql {
     world_address: Felt,
     pub pool: Pool<Sqlite>,
     query_queue: QueryQueue,
+    search_fields: Vec<SearchField>,
 }
 
 impl Sql {
@@ -62,7 +65,30 @@ impl Sql {
 
         query_queue.execute_all().await?;
 
-        Ok(Self { pool, world_address, query_queue })
+        Ok(Self { pool, world_address, query_queue, search_fields: Vec::new() })
+    }
+
+    /// Indexes the given members for full-text search, creating the search table if needed.
+    /// The members of the models already registered are validated and their current values
+    /// indexed, the others are validated when their model gets registered.
+    pub async fn enable_search(&mut self, fields: Vec<SearchField>) -> Result<()> {
+        self.query_queue.enqueue(create_search_table(), vec![]);
+
+        for field in &fields {
+            let (namespace, name) = field.model.split_once('-').unwrap();
+            let Ok(model) = self.model(compute_selector_from_names(namespace, name)).await else {
+                continue;
+            };
+
+            let schema = model.schema().await?;
+            field.validate(&schema)?;
+            self.build_search_backfill_queries(field, &schema).await?;
+        }
+
+        self.query_queue.execute_all().await?;
+
+        self.search_fields = fields;
+        Ok(())
     }
 
     pub async fn head(&self) -> Result<(u64, Option<Felt>)> {
@@ -153,6 +179,20 @@ impl Sql {
 
         SimpleBroker::publish(model_registered);
 
+        // the model is registered even if one of its search fields is invalid, only the field
+        // isn't indexed
+        let model_tag = format!("{}-{}", namespace, model.name());
+        let invalid_fields = self
+            .search_fields
+            .iter()
+            .filter(|field| field.model == model_tag)
+            .filter_map(|field| field.validate(&model).err().map(|e| (field.clone(), e)))
+            .collect::<Vec<_>>();
+        if let Some((_, e)) = invalid_fields.first() {
+            self.search_fields.retain(|field| invalid_fields.iter().all(|(f, _)| f != field));
+            return Err(anyhow!("{e}"));
+        }
+
         Ok(())
     }
 
@@ -199,6 +239,8 @@ impl Sql {
 
         entity_updated.updated_model = Some(entity.clone());
 
+        self.build_search_queries(&namespaced_name, &entity_id, &model_id, entity.as_struct());
+
         let path = vec![namespaced_name];
         self.build_set_entity_queries_recursive(
             path,
@@ -215,6 +257,20 @@ impl Sql {
         Ok(())
     }
 
+    /// Records the hash of an offchain message until `expires_at`, in the transaction of the next
+    /// queries executed, like the ones of [`Sql::set_entity`]. The transaction fails if the hash
+    /// is already recorded and not expired, so that a replayed message isn't set.
+    pub fn record_message_hash(&mut self, message_hash: Felt, received_at: i64, expires_at: i64) {
+        self.query_queue.push_front(
+            "INSERT INTO message_hashes (hash, expires_at) VALUES (?, ?)",
+            vec![Argument::FieldElement(message_hash), Argument::Int(expires_at)],
+        );
+        self.query_queue.push_front(
+            "DELETE FROM message_hashes WHERE expires_at <= ?",
+            vec![Argument::Int(received_at)],
+        );
+    }
+
     pub async fn set_event_message(
         &mut self,
         entity: Ty,
@@ -289,6 +345,13 @@ impl Sql {
         let wrapped_ty =
             Ty::Struct(Struct { name: model_tag.to_string(), children: vec![member.clone()] });
 
+        // event messages aren't searchable
+        if !is_event_message {
+            let (namespace, name) = model_tag.split_once('-').unwrap();
+            let model_id = format!("{:#x}", compute_selector_from_names(namespace, name));
+            self.build_search_queries(model_tag, &entity_id, &model_id, wrapped_ty.as_struct());
+        }
+
         // update model member
         self.build_set_entity_queries_recursive(
             path,
@@ -303,24 +366,104 @@ impl Sql {
         Ok(())
     }
 
-    pub async fn delete_entity(&mut self, entity_id: Felt, entity: Ty) -> Result<()> {
+    pub async fn delete_entity(
+        &mut self,
+        entity_id: Felt,
+        entity: Ty,
+        event_id: &str,
+    ) -> Result<()> {
         let entity_id = format!("{:#x}", entity_id);
         let path = vec![entity.name()];
+
+        // models emitted as event messages are deleted the same way as the ones of entities
+        let is_event_message: bool =
+            sqlx::query_scalar("SELECT NOT EXISTS (SELECT 1 FROM entities WHERE id = ?)")
+                .bind(&entity_id)
+                .fetch_one(&self.pool)
+                .await?;
+        if is_event_message {
+            return self.delete_event_message(entity_id, entity, event_id).await;
+        }
+
         // delete entity models data
-        self.build_delete_entity_queries_recursive(path, &entity_id, &entity);
+        self.build_delete_entity_queries_recursive(path, (&entity_id, false), &entity);
+
+        if !self.search_fields.is_empty() {
+            let model_tag = entity.name();
+            let (namespace, name) = model_tag.split_once('-').unwrap();
+            self.query_queue.enqueue(
+                format!("DELETE FROM {SEARCH_TABLE} WHERE id = ? AND model_id = ?"),
+                vec![
+                    Argument::String(entity_id.clone()),
+                    Argument::FieldElement(compute_selector_from_names(namespace, name)),
+                ],
+            );
+        }
         self.query_queue.execute_all().await?;
 
         // delete entity
-        let entity_deleted =
+        let mut entity_deleted =
             sqlx::query_as::<_, EntityUpdated>("DELETE FROM entities WHERE id = ? RETURNING *")
                 .bind(entity_id)
                 .fetch_one(&self.pool)
                 .await?;
 
+        // the deletion is replayed to the subscriptions resumed from an earlier event
+        sqlx::query(
+            "INSERT OR REPLACE INTO entity_tombstones (id, keys, model, event_id) VALUES (?, ?, \
+             ?, ?)",
+        )
+        .bind(&entity_deleted.id)
+        .bind(&entity_deleted.keys)
+        .bind(entity.name())
+        .bind(event_id)
+        .execute(&self.pool)
+        .await?;
+
+        // the row holds the event of the last update, subscribers resume from the deletion
+        entity_deleted.event_id = event_id.to_string();
+        entity_deleted.updated_model = Some(entity);
+        entity_deleted.deleted = true;
+
         SimpleBroker::publish(entity_deleted);
         Ok(())
     }
 
+    async fn delete_event_message(
+        &mut self,
+        entity_id: String,
+        entity: Ty,
+        event_id: &str,
+    ) -> Result<()> {
+        let path = vec![entity.name()];
+        self.build_delete_entity_queries_recursive(path, (&entity_id, true), &entity);
+
+        let model_tag = entity.name();
+        let (namespace, name) = model_tag.split_once('-').unwrap();
+        self.query_queue.enqueue(
+            "DELETE FROM event_model WHERE entity_id = ? AND model_id = ?",
+            vec![
+                Argument::String(entity_id.clone()),
+                Argument::FieldElement(compute_selector_from_names(namespace, name)),
+            ],
+        );
+        self.query_queue.execute_all().await?;
+
+        let mut event_message_deleted = sqlx::query_as::<_, EventMessageUpdated>(
+            "DELETE FROM event_messages WHERE id = ? RETURNING *",
+        )
+        .bind(entity_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        event_message_deleted.event_id = event_id.to_string();
+        event_message_deleted.updated_model = Some(entity);
+        event_message_deleted.deleted = true;
+
+        SimpleBroker::publish(event_message_deleted);
+        Ok(())
+    }
+
     pub fn set_metadata(&mut self, resource: &Felt, uri: &str, block_timestamp: u64) {
         let resource = Argument::FieldElement(*resource);
         let uri = Argument::String(uri.to_string());
@@ -587,6 +730,107 @@ impl Sql {
         }
     }
 
+    /// Indexes the current values of a validated search field, replacing its previous rows.
+    async fn build_search_backfill_queries(
+        &mut self,
+        field: &SearchField,
+        schema: &Ty,
+    ) -> Result<()> {
+        let (namespace, name) = field.model.split_once('-').unwrap();
+        let model_id = format!("{:#x}", compute_selector_from_names(namespace, name));
+        let is_felt = schema.as_struct().is_some_and(|schema| {
+            schema.children.iter().any(|m| {
+                m.name == field.member && matches!(m.ty, Ty::Primitive(Primitive::Felt252(_)))
+            })
+        });
+
+        self.query_queue.enqueue(
+            format!("DELETE FROM {SEARCH_TABLE} WHERE model_id = ? AND member = ?"),
+            vec![Argument::String(model_id.clone()), Argument::String(field.member.clone())],
+        );
+
+        // event messages aren't searchable
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT entity_id, [external_{}] FROM [{}] WHERE entity_id IS NOT NULL",
+            field.member, field.model
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (entity_id, value) in rows {
+            let Some(value) = value else {
+                continue;
+            };
+
+            // byte arrays are stored as text, felts as hex strings
+            let text = if is_felt {
+                member_text(&Ty::Primitive(Primitive::Felt252(Some(Felt::from_str(&value)?))))
+            } else {
+                Some(value)
+            };
+
+            if let Some(text) = text {
+                self.query_queue.enqueue(
+                    format!(
+                        "INSERT INTO {SEARCH_TABLE} (id, model_id, member, content) VALUES (?, ?, \
+                         ?, ?)"
+                    ),
+                    vec![
+                        Argument::String(entity_id),
+                        Argument::String(model_id.clone()),
+                        Argument::String(field.member.clone()),
+                        Argument::String(text),
+                    ],
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the search rows of the searchable members of the given model members.
+    fn build_search_queries(
+        &mut self,
+        model_tag: &str,
+        entity_id: &str,
+        model_id: &str,
+        members: Option<&Struct>,
+    ) {
+        let Some(members) = members else {
+            return;
+        };
+
+        for field in self.search_fields.iter().filter(|field| field.model == model_tag) {
+            let Some(member) = members.children.iter().find(|m| m.name == field.member) else {
+                continue;
+            };
+
+            self.query_queue.enqueue(
+                format!("DELETE FROM {SEARCH_TABLE} WHERE id = ? AND model_id = ? AND member = ?"),
+                vec![
+                    Argument::String(entity_id.to_string()),
+                    Argument::String(model_id.to_string()),
+                    Argument::String(member.name.clone()),
+                ],
+            );
+
+            if let Some(text) = member_text(&member.ty) {
+                self.query_queue.enqueue(
+                    format!(
+                        "INSERT INTO {SEARCH_TABLE} (id, model_id, member, content) VALUES (?, ?, \
+                         ?, ?)"
+                    ),
+                    vec![
+                        Argument::String(entity_id.to_string()),
+                        Argument::String(model_id.to_string()),
+                        Argument::String(member.name.clone()),
+                        Argument::String(text),
+                    ],
+                );
+            }
+        }
+    }
+
     fn build_set_entity_queries_recursive(
         &mut self,
         path: Vec<String>,
@@ -817,15 +1061,17 @@ impl Sql {
     fn build_delete_entity_queries_recursive(
         &mut self,
         path: Vec<String>,
-        entity_id: &str,
+        // The id of the entity and if the entity is an event message
+        entity_id: (&str, IsEventMessage),
         entity: &Ty,
     ) {
+        let column = if entity_id.1 { "event_message_id" } else { "entity_id" };
         match entity {
             Ty::Struct(s) => {
                 let table_id = path.join("$");
-                let statement = format!("DELETE FROM [{table_id}] WHERE entity_id = ?");
+                let statement = format!("DELETE FROM [{table_id}] WHERE {column} = ?");
                 self.query_queue
-                    .push_front(statement, vec![Argument::String(entity_id.to_string())]);
+                    .push_front(statement, vec![Argument::String(entity_id.0.to_string())]);
                 for member in s.children.iter() {
                     let mut path_clone = path.clone();
                     path_clone.push(member.name.clone());
@@ -841,9 +1087,9 @@ impl Sql {
                 }
 
                 let table_id = path.join("$");
-                let statement = format!("DELETE FROM [{table_id}] WHERE entity_id = ?");
+                let statement = format!("DELETE FROM [{table_id}] WHERE {column} = ?");
                 self.query_queue
-                    .push_front(statement, vec![Argument::String(entity_id.to_string())]);
+                    .push_front(statement, vec![Argument::String(entity_id.0.to_string())]);
 
                 for child in e.options.iter() {
                     if let Ty::Tuple(t) = &child.ty {
@@ -859,9 +1105,9 @@ impl Sql {
             }
             Ty::Array(array) => {
                 let table_id = path.join("$");
-                let statement = format!("DELETE FROM [{table_id}] WHERE entity_id = ?");
+                let statement = format!("DELETE FROM [{table_id}] WHERE {column} = ?");
                 self.query_queue
-                    .push_front(statement, vec![Argument::String(entity_id.to_string())]);
+                    .push_front(statement, vec![Argument::String(entity_id.0.to_string())]);
 
                 for member in array.iter() {
                     let mut path_clone = path.clone();
@@ -871,9 +1117,9 @@ impl Sql {
             }
             Ty::Tuple(t) => {
                 let table_id = path.join("$");
-                let statement = format!("DELETE FROM [{table_id}] WHERE entity_id = ?");
+                let statement = format!("DELETE FROM [{table_id}] WHERE {column} = ?");
                 self.query_queue
-                    .push_front(statement, vec![Argument::String(entity_id.to_string())]);
+                    .push_front(statement, vec![Argument::String(entity_id.0.to_string())]);
 
                 for (idx, member) in t.iter().enumerate() {
                     let mut path_clone = path.clone();
@@ -1125,6 +1371,17 @@ impl Sql {
         });
     }
 
+    /// Queues a statement, executed on the next call to [`Sql::execute`]. Can be used by custom
+    /// processors writing to their own tables.
+    pub fn enqueue<S: Into<String>>(&mut self, statement: S, arguments: Vec<Argument>) {
+        self.query_queue.enqueue(statement, arguments);
+    }
+
+    /// Queues the statements left by another instance, e.g. a clone used by a concurrent task.
+    pub fn merge(&mut self, other: Sql) {
+        self.query_queue.append(other.query_queue);
+    }
+
     pub async fn execute(&mut self) -> Result<()> {
         self.query_queue.execute_all().await?;
 
@@ -4,6 +4,9 @@ use cainome::cairo_serde::ContractAddress;
 use camino::Utf8PathBuf;
 use dojo_test_utils::compiler::CompilerTestSetup;
 use dojo_test_utils::migration::{copy_spawn_and_move_db, prepare_migration_with_world_and_seed};
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
 use dojo_world::contracts::naming::{compute_bytearray_hash, compute_selector_from_names};
 use dojo_world::contracts::world::{WorldContract, WorldContractReader};
 use dojo_world::migration::TxnConfig;
@@ -13,7 +16,9 @@ use scarb::compiler::Profile;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::{BlockId, BlockTag, Felt};
-use starknet::core::utils::{get_contract_address, get_selector_from_name};
+use starknet::core::utils::{
+    cairo_short_string_to_felt, get_contract_address, get_selector_from_name,
+};
 use starknet::providers::Provider;
 use starknet_crypto::poseidon_hash_many;
 use tokio::sync::broadcast;
@@ -22,6 +27,7 @@ use crate::engine::{Engine, EngineConfig, Processors};
 use crate::processors::register_model::RegisterModelProcessor;
 use crate::processors::store_del_record::StoreDelRecordProcessor;
 use crate::processors::store_set_record::StoreSetRecordProcessor;
+use crate::search::{search_query, SearchField};
 use crate::sql::Sql;
 
 pub async fn bootstrap_engine<P>(
@@ -405,6 +411,100 @@ async fn test_get_entity_keys() {
 ///
 /// # Returns
 /// The number of rows in the table.
+fn guild(name: &str, id: u32, guild_name: &str, tag: &str) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "id".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(id))),
+                key: true,
+            },
+            Member {
+                name: "name".to_string(),
+                ty: Ty::ByteArray(guild_name.to_string()),
+                key: false,
+            },
+            Member {
+                name: "tag".to_string(),
+                ty: Ty::Primitive(Primitive::Felt252(Some(
+                    cairo_short_string_to_felt(tag).unwrap(),
+                ))),
+                key: false,
+            },
+        ],
+    })
+}
+
+async fn search(text: &str, pool: &sqlx::Pool<sqlx::Sqlite>) -> Vec<String> {
+    sqlx::query_scalar("SELECT id FROM entities_search WHERE entities_search MATCH ? ORDER BY id")
+        .bind(search_query(text).unwrap())
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    db.register_model(
+        "ns",
+        guild("Guild", 0, "", ""),
+        Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+
+    let iron = format!("{:#x}", poseidon_hash_many(&[Felt::ONE]));
+    let silver = format!("{:#x}", poseidon_hash_many(&[Felt::TWO]));
+
+    // the entities set before the search is enabled are backfilled
+    db.set_entity(guild("ns-Guild", 1, "Iron Knights", "iron"), "0x1", 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let fields = ["ns-Guild.name", "ns-Guild.tag"].map(|f| SearchField::from_str(f).unwrap());
+    db.enable_search(fields.to_vec()).await.unwrap();
+    assert_eq!(search("knights", &pool).await, vec![iron.clone()]);
+    assert_eq!(search("iron", &pool).await, vec![iron.clone(), iron.clone()]);
+
+    // then they are indexed as they are set
+    db.set_entity(guild("ns-Guild", 2, "Silver Knights", "silver"), "0x2", 0).await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(search("knights", &pool).await, vec![iron.clone(), silver.clone()]);
+
+    let name = Member {
+        name: "name".to_string(),
+        ty: Ty::ByteArray("Silver Lances".to_string()),
+        key: false,
+    };
+    db.set_model_member("ns-Guild", poseidon_hash_many(&[Felt::TWO]), false, &name, "0x3", 0)
+        .await
+        .unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(search("knights", &pool).await, vec![iron.clone()]);
+    assert_eq!(search("lan", &pool).await, vec![silver.clone()]);
+
+    db.delete_entity(poseidon_hash_many(&[Felt::ONE]), guild("ns-Guild", 1, "", ""), "0x4")
+        .await
+        .unwrap();
+    db.execute().await.unwrap();
+    assert!(search("iron", &pool).await.is_empty());
+
+    // only text members can be searched
+    let id = SearchField::from_str("ns-Guild.id").unwrap();
+    assert!(db.enable_search(vec![id]).await.is_err());
+}
+
 async fn count_table(table_name: &str, pool: &sqlx::Pool<sqlx::Sqlite>) -> i64 {
     let count_query = format!("SELECT COUNT(*) FROM [{}]", table_name);
     let count: (i64,) = sqlx::query_as(&count_query).fetch_one(pool).await.unwrap();
@@ -0,0 +1,250 @@
+use std::str::FromStr;
+
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, ResolverContext, TypeRef};
+use async_graphql::{Error as GqlError, Name, Number, Result, Value};
+use dojo_types::primitive::{Primitive, SqlType};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Pool, Row, Sqlite};
+
+use super::inputs::where_input::{parse_where_argument, where_argument};
+use super::{TypeMapping, ValueMapping};
+use crate::query::aggregate::{
+    aggregate_column, fetch_aggregates, AggregateFunction, COUNT_COLUMN,
+};
+use crate::query::fetch_column_value;
+use crate::types::TypeData;
+use crate::utils;
+
+// Aggregates of a model, grouped by some of its members. Each group has the number of rows in it,
+// the values of the group by members, the min and max of every member and the sum and average of
+// the integer members.
+#[derive(Debug)]
+pub struct AggregateObject {
+    pub name: String,
+    pub type_name: String,
+    // members that can be grouped by, min and max
+    pub value_mapping: TypeMapping,
+    // members that can be summed and averaged
+    pub number_mapping: TypeMapping,
+}
+
+impl AggregateObject {
+    pub fn new(name: &str, type_name: &str, type_mapping: &TypeMapping) -> Self {
+        let value_mapping: TypeMapping = type_mapping
+            .iter()
+            .filter(|(_, type_data)| type_data.is_simple())
+            .map(|(name, type_data)| (name.clone(), type_data.clone()))
+            .collect();
+
+        let number_mapping = value_mapping
+            .iter()
+            .filter(|(_, type_data)| {
+                matches!(
+                    Primitive::from_str(&type_data.type_ref().to_string()),
+                    Ok(primitive) if primitive.to_sql_type() == SqlType::Integer
+                        && !matches!(primitive, Primitive::Bool(_))
+                )
+            })
+            .map(|(name, _)| (name.clone(), TypeData::Simple(TypeRef::named(TypeRef::FLOAT))))
+            .collect();
+
+        Self {
+            name: format!("{}Aggregate", name),
+            type_name: format!("{}Aggregate", type_name),
+            value_mapping,
+            number_mapping,
+        }
+    }
+
+    fn values_type_name(&self) -> String {
+        format!("{}Values", self.type_name)
+    }
+
+    fn numbers_type_name(&self) -> String {
+        format!("{}Numbers", self.type_name)
+    }
+
+    pub fn objects(&self) -> Vec<Object> {
+        let mut aggregate = Object::new(&self.type_name)
+            .field(parent_field(COUNT_COLUMN, TypeRef::named_nn(TypeRef::INT)))
+            .field(parent_field("group", TypeRef::named(self.values_type_name())))
+            .field(parent_field("min", TypeRef::named(self.values_type_name())))
+            .field(parent_field("max", TypeRef::named(self.values_type_name())));
+
+        let mut objects = vec![mapping_object(&self.values_type_name(), &self.value_mapping)];
+
+        // an object must have at least one field, sum and avg are only added for integer members
+        if !self.number_mapping.is_empty() {
+            aggregate = aggregate
+                .field(parent_field("sum", TypeRef::named(self.numbers_type_name())))
+                .field(parent_field("avg", TypeRef::named(self.numbers_type_name())));
+            objects.push(mapping_object(&self.numbers_type_name(), &self.number_mapping));
+        }
+
+        objects.push(aggregate);
+        objects
+    }
+
+    pub fn resolver(&self, model_type_name: &str, where_mapping: &TypeMapping) -> Field {
+        let value_mapping = self.value_mapping.clone();
+        let number_mapping = self.number_mapping.clone();
+        let where_mapping = where_mapping.clone();
+
+        let mut parts = model_type_name.split('_').collect::<Vec<&str>>();
+        let model = parts.pop().unwrap();
+        let namespace = parts.join("_");
+        let table_name = utils::struct_name_from_names(&namespace, model);
+
+        let field =
+            Field::new(&self.name, TypeRef::named_nn_list_nn(&self.type_name), move |ctx| {
+                let value_mapping = value_mapping.clone();
+                let number_mapping = number_mapping.clone();
+                let where_mapping = where_mapping.clone();
+                let table_name = table_name.clone();
+
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let filters = parse_where_argument(&ctx, &where_mapping)?;
+                    let group_by = parse_group_by_argument(&ctx, &value_mapping)?;
+
+                    let mut aggregates = Vec::new();
+                    for name in value_mapping.keys() {
+                        aggregates.push((AggregateFunction::Min, name.to_string()));
+                        aggregates.push((AggregateFunction::Max, name.to_string()));
+                    }
+                    for name in number_mapping.keys() {
+                        aggregates.push((AggregateFunction::Sum, name.to_string()));
+                        aggregates.push((AggregateFunction::Avg, name.to_string()));
+                    }
+
+                    let rows =
+                        fetch_aggregates(&mut conn, &table_name, &filters, &group_by, &aggregates)
+                            .await?;
+
+                    let groups = rows
+                        .iter()
+                        .map(|row| {
+                            aggregate_value_mapping(row, &value_mapping, &number_mapping, &group_by)
+                                .map(Value::Object)
+                        })
+                        .collect::<sqlx::Result<Vec<_>>>()?;
+
+                    Ok(Some(Value::List(groups)))
+                })
+            });
+
+        let field = where_argument(field, model_type_name);
+        field.argument(InputValue::new(
+            "groupBy",
+            TypeRef::named_nn_list(format!("{}OrderField", model_type_name)),
+        ))
+    }
+}
+
+// Maps the `groupBy` enum values, the uppercased names of the model members, back to the members
+fn parse_group_by_argument(
+    ctx: &ResolverContext<'_>,
+    value_mapping: &TypeMapping,
+) -> Result<Vec<String>> {
+    let Some(group_by) = ctx.args.get("groupBy") else {
+        return Ok(Vec::new());
+    };
+
+    group_by
+        .list()?
+        .iter()
+        .map(|item| {
+            let enum_name = item.enum_name()?;
+            value_mapping
+                .keys()
+                .find(|name| name.to_uppercase() == enum_name)
+                .map(|name| name.to_string())
+                .ok_or_else(|| GqlError::new(format!("Cannot group by member {}", enum_name)))
+        })
+        .collect()
+}
+
+fn aggregate_value_mapping(
+    row: &SqliteRow,
+    value_mapping: &TypeMapping,
+    number_mapping: &TypeMapping,
+    group_by: &[String],
+) -> sqlx::Result<ValueMapping> {
+    let count = row.try_get::<i64, &str>(COUNT_COLUMN)?;
+
+    let mut mapping = ValueMapping::new();
+    mapping.insert(Name::new(COUNT_COLUMN), Value::from(count));
+
+    let group = if group_by.is_empty() {
+        Value::Null
+    } else {
+        let values = group_by
+            .iter()
+            .map(|name| {
+                let type_name = value_mapping[name.as_str()].type_ref().to_string();
+                let value = fetch_column_value(row, &format!("external_{}", name), &type_name)?;
+                Ok((Name::new(name), value))
+            })
+            .collect::<sqlx::Result<ValueMapping>>()?;
+        Value::Object(values)
+    };
+    mapping.insert(Name::new("group"), group);
+
+    // without group by, all the aggregates are null if no row matches the filters
+    for function in [AggregateFunction::Min, AggregateFunction::Max] {
+        let values = if count == 0 {
+            Value::Null
+        } else {
+            let values = value_mapping
+                .iter()
+                .map(|(name, type_data)| {
+                    let column = aggregate_column(function, name.as_str());
+                    let value =
+                        fetch_column_value(row, &column, &type_data.type_ref().to_string())?;
+                    Ok((name.clone(), value))
+                })
+                .collect::<sqlx::Result<ValueMapping>>()?;
+            Value::Object(values)
+        };
+        mapping.insert(Name::new(function.as_ref()), values);
+    }
+
+    for function in [AggregateFunction::Sum, AggregateFunction::Avg] {
+        let values = number_mapping
+            .keys()
+            .map(|name| {
+                let column = aggregate_column(function, name.as_str());
+                let value = row
+                    .try_get::<Option<f64>, &str>(column.as_str())?
+                    .and_then(Number::from_f64)
+                    .map_or(Value::Null, Value::Number);
+                Ok((name.clone(), value))
+            })
+            .collect::<sqlx::Result<ValueMapping>>()?;
+        mapping.insert(Name::new(function.as_ref()), Value::Object(values));
+    }
+
+    Ok(mapping)
+}
+
+fn mapping_object(type_name: &str, type_mapping: &TypeMapping) -> Object {
+    type_mapping.iter().fold(Object::new(type_name), |object, (name, type_data)| {
+        object.field(parent_field(name.as_str(), type_data.type_ref()))
+    })
+}
+
+// Resolves a field from the value of its parent object
+fn parent_field(name: &str, type_ref: TypeRef) -> Field {
+    let field_name = Name::new(name);
+
+    Field::new(name, type_ref, move |ctx| {
+        let field_name = field_name.clone();
+
+        FieldFuture::new(async move {
+            match ctx.parent_value.try_to_value()? {
+                Value::Object(values) => Ok(values.get(&field_name).cloned()),
+                _ => Err("incorrect value, requires Value::Object".into()),
+            }
+        })
+    })
+}
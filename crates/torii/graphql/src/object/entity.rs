@@ -6,18 +6,20 @@ use async_graphql::{Name, Value};
 use async_recursion::async_recursion;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
+use torii_core::search::search_query;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::Entity;
 
 use super::inputs::keys_input::keys_argument;
 use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
 use crate::constants::{
-    DATETIME_FORMAT, ENTITY_ID_COLUMN, ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME,
+    DATETIME_FORMAT, DEFAULT_LIMIT, ENTITY_ID_COLUMN, ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME,
     EVENT_ID_COLUMN, ID_COLUMN,
 };
 use crate::mapping::ENTITY_TYPE_MAPPING;
 use crate::object::{resolve_many, resolve_one};
+use crate::query::data::fetch_search_rows;
 use crate::query::{type_mapping_query, value_mapping_from_row};
 use crate::types::TypeData;
 use crate::utils;
@@ -62,7 +64,7 @@ impl ResolvableObject for EntityObject {
         );
         resolve_many = keys_argument(resolve_many);
 
-        vec![resolve_one, resolve_many]
+        vec![resolve_one, resolve_many, search_field()]
     }
 
     fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
@@ -75,14 +77,18 @@ impl ResolvableObject for EntityObject {
                     };
                     // if id is None, then subscribe to all entities
                     // if id is Some, then subscribe to only the entity with that id
-                    Ok(SimpleBroker::<Entity>::subscribe().filter_map(move |entity: Entity| {
-                        if id.is_none() || id == Some(entity.id.clone()) {
-                            Some(Ok(Value::Object(EntityObject::value_mapping(entity))))
-                        } else {
-                            // id != entity.id , then don't send anything, still listening
-                            None
-                        }
-                    }))
+                    // deletions are updates too, `entityDeleted` only streams them
+                    Ok(entity_stream(id, false))
+                })
+            })
+            .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
+            SubscriptionField::new("entityDeleted", TypeRef::named_nn(self.type_name()), |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let id = match ctx.args.get("id") {
+                        Some(id) => Some(id.string()?.to_string()),
+                        None => None,
+                    };
+                    Ok(entity_stream(id, true))
                 })
             })
             .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
@@ -90,6 +96,22 @@ impl ResolvableObject for EntityObject {
     }
 }
 
+// updates of the entity with the given id, or of all entities, either all of them or only their
+// deletions
+fn entity_stream(
+    id: Option<String>,
+    deleted_only: bool,
+) -> impl Stream<Item = async_graphql::Result<Value>> {
+    SimpleBroker::<Entity>::subscribe().filter_map(move |entity: Entity| {
+        if (!deleted_only || entity.deleted) && (id.is_none() || id == Some(entity.id.clone())) {
+            Some(Ok(Value::Object(EntityObject::value_mapping(entity))))
+        } else {
+            // otherwise don't send anything, still listening
+            None
+        }
+    })
+}
+
 impl EntityObject {
     pub fn value_mapping(entity: Entity) -> ValueMapping {
         let keys: Vec<&str> = entity.keys.split('/').filter(|&k| !k.is_empty()).collect();
@@ -113,6 +135,37 @@ impl EntityObject {
     }
 }
 
+// Entities whose searchable members match the text, requires the members to be indexed for
+// full-text search
+fn search_field() -> Field {
+    Field::new("search", TypeRef::named_nn_list_nn(ENTITY_TYPE_NAME), |ctx| {
+        FieldFuture::new(async move {
+            let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+            let text = utils::extract::<String>(ctx.args.as_index_map(), "text")?;
+            let limit = match ctx.args.get("limit") {
+                Some(limit) => limit.u64()?,
+                None => DEFAULT_LIMIT,
+            };
+
+            let Some(search_query) = search_query(&text) else {
+                return Ok(Some(Value::List(vec![])));
+            };
+
+            let rows = fetch_search_rows(&mut conn, ENTITY_TABLE, &search_query, limit).await?;
+            let entities = rows
+                .iter()
+                .map(|row| {
+                    value_mapping_from_row(row, &ENTITY_TYPE_MAPPING, false).map(Value::Object)
+                })
+                .collect::<sqlx::Result<Vec<_>>>()?;
+
+            Ok(Some(Value::List(entities)))
+        })
+    })
+    .argument(InputValue::new("text", TypeRef::named_nn(TypeRef::STRING)))
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+}
+
 fn model_union_field() -> Field {
     Field::new("models", TypeRef::named_list("ModelUnion"), move |ctx| {
         FieldFuture::new(async move {
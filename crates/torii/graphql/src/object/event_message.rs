@@ -4,7 +4,7 @@ use async_graphql::dynamic::{
 };
 use async_graphql::{Name, Value};
 use sqlx::{Pool, Sqlite};
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::EventMessage;
 
@@ -76,18 +76,22 @@ impl ResolvableObject for EventMessageObject {
                         };
                         // if id is None, then subscribe to all entities
                         // if id is Some, then subscribe to only the entity with that id
-                        Ok(SimpleBroker::<EventMessage>::subscribe().filter_map(
-                            move |entity: EventMessage| {
-                                if id.is_none() || id == Some(entity.id.clone()) {
-                                    Some(Ok(Value::Object(EventMessageObject::value_mapping(
-                                        entity,
-                                    ))))
-                                } else {
-                                    // id != entity.id , then don't send anything, still listening
-                                    None
-                                }
-                            },
-                        ))
+                        // deletions are updates too, `eventMessageDeleted` only streams them
+                        Ok(event_message_stream(id, false))
+                    })
+                },
+            )
+            .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
+            SubscriptionField::new(
+                "eventMessageDeleted",
+                TypeRef::named_nn(self.type_name()),
+                |ctx| {
+                    SubscriptionFieldFuture::new(async move {
+                        let id = match ctx.args.get("id") {
+                            Some(id) => Some(id.string()?.to_string()),
+                            None => None,
+                        };
+                        Ok(event_message_stream(id, true))
                     })
                 },
             )
@@ -96,6 +100,22 @@ impl ResolvableObject for EventMessageObject {
     }
 }
 
+// updates of the event message with the given id, or of all event messages, either all of them or
+// only their deletions
+fn event_message_stream(
+    id: Option<String>,
+    deleted_only: bool,
+) -> impl Stream<Item = async_graphql::Result<Value>> {
+    SimpleBroker::<EventMessage>::subscribe().filter_map(move |entity: EventMessage| {
+        if (!deleted_only || entity.deleted) && (id.is_none() || id == Some(entity.id.clone())) {
+            Some(Ok(Value::Object(EventMessageObject::value_mapping(entity))))
+        } else {
+            // otherwise don't send anything, still listening
+            None
+        }
+    })
+}
+
 impl EventMessageObject {
     pub fn value_mapping(entity: EventMessage) -> ValueMapping {
         let keys: Vec<&str> = entity.keys.split('/').filter(|&k| !k.is_empty()).collect();
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use async_graphql::dynamic::indexmap::IndexMap;
 use async_graphql::dynamic::{
     Field, InputObject, InputValue, ResolverContext, TypeRef, ValueAccessor,
 };
@@ -9,7 +10,10 @@ use strum::IntoEnumIterator;
 
 use super::InputObjectTrait;
 use crate::object::TypeMapping;
-use crate::query::filter::{parse_filter, Comparator, Filter, FilterValue};
+use crate::query::filter::{
+    length_filter_member, parse_contains_filter, parse_filter, parse_length_filter, Comparator,
+    Filter, FilterValue, CONTAINS_SUFFIX, LENGTH_SUFFIX,
+};
 use crate::types::TypeData;
 
 #[derive(Debug)]
@@ -21,42 +25,137 @@ pub struct WhereInputObject {
 impl WhereInputObject {
     // Iterate through an object's type mapping and create a new mapping for whereInput. For each of
     // the object type (model member), we add 6 additional types for comparators (great than,
-    // not equal, etc)
+    // not equal, etc). Nested members get their own where input, filtering the rows of their
+    // table, and arrays can be filtered on their length and elements
     pub fn new(type_name: &str, object_types: &TypeMapping) -> Self {
         let where_mapping = object_types
             .iter()
-            .filter(|(_, type_data)| !type_data.is_nested() && !type_data.is_list())
-            .flat_map(|(type_name, type_data)| {
-                // TODO: filter on nested and enum objects
-                if type_data.type_ref() == TypeRef::named("Enum")
-                    || type_data.type_ref() == TypeRef::named("bool")
-                {
-                    return vec![(Name::new(type_name), type_data.clone())];
+            .flat_map(|(type_name, type_data)| match type_data {
+                TypeData::Nested((nested_type, nested_mapping)) => {
+                    nested_where_input(type_name, &nested_type.to_string(), nested_mapping)
+                        .into_iter()
+                        .collect()
                 }
-
-                Comparator::iter().fold(
-                    vec![(Name::new(type_name), type_data.clone())],
-                    |mut acc, comparator| {
-                        let name = format!("{}{}", type_name, comparator.as_ref());
-
-                        match comparator {
-                            Comparator::In | Comparator::NotIn => acc.push((
-                                Name::new(name),
-                                TypeData::List(Box::new(type_data.clone())),
-                            )),
-                            _ => {
-                                acc.push((Name::new(name), type_data.clone()));
-                            }
-                        }
-
-                        acc
-                    },
-                )
+                TypeData::List(inner) => list_where_mapping(type_name, inner),
+                TypeData::Simple(_) => simple_where_mapping(type_name, type_data),
             })
             .collect();
 
         Self { type_name: format!("{}WhereInput", type_name), type_mapping: where_mapping }
     }
+
+    // Input objects of the nested members, they have to be registered along the where input
+    pub fn nested_input_objects(&self) -> Vec<InputObject> {
+        fn collect(type_mapping: &TypeMapping, objects: &mut IndexMap<String, InputObject>) {
+            for type_data in type_mapping.values() {
+                let nested = match type_data {
+                    TypeData::List(inner) => inner.as_ref(),
+                    type_data => type_data,
+                };
+
+                if let TypeData::Nested((type_ref, nested_mapping)) = nested {
+                    let type_name = type_ref.to_string();
+                    if !objects.contains_key(&type_name) {
+                        objects.insert(type_name.clone(), input_object(&type_name, nested_mapping));
+                        collect(nested_mapping, objects);
+                    }
+                }
+            }
+        }
+
+        let mut objects = IndexMap::new();
+        collect(&self.type_mapping, &mut objects);
+        objects.into_values().collect()
+    }
+}
+
+fn simple_where_mapping(type_name: &Name, type_data: &TypeData) -> Vec<(Name, TypeData)> {
+    if type_data.type_ref() == TypeRef::named("Enum")
+        || type_data.type_ref() == TypeRef::named("bool")
+    {
+        return vec![(Name::new(type_name), type_data.clone())];
+    }
+
+    Comparator::iter().fold(
+        vec![(Name::new(type_name), type_data.clone())],
+        |mut acc, comparator| {
+            let name = format!("{}{}", type_name, comparator.as_ref());
+
+            match comparator {
+                Comparator::In | Comparator::NotIn => {
+                    acc.push((Name::new(name), TypeData::List(Box::new(type_data.clone()))))
+                }
+                _ => {
+                    acc.push((Name::new(name), type_data.clone()));
+                }
+            }
+
+            acc
+        },
+    )
+}
+
+// Arrays are filtered on their length, and on their elements if they are simple types or nested
+// objects. Nested elements filters are wrapped in a list to tell them apart from nested members.
+fn list_where_mapping(type_name: &Name, inner: &TypeData) -> Vec<(Name, TypeData)> {
+    let mut mapping = Vec::new();
+
+    match inner {
+        TypeData::Simple(_) => {
+            mapping.push((Name::new(format!("{}{}", type_name, CONTAINS_SUFFIX)), inner.clone()))
+        }
+        TypeData::Nested((nested_type, nested_mapping)) => {
+            if let Some((name, nested)) =
+                nested_where_input(type_name, &nested_type.to_string(), nested_mapping)
+            {
+                mapping.push((name, TypeData::List(Box::new(nested))));
+            }
+        }
+        TypeData::List(_) => {}
+    }
+
+    let length_name = format!("{}{}", type_name, LENGTH_SUFFIX);
+    let length_type = TypeData::Simple(TypeRef::named(TypeRef::INT));
+    mapping.push((Name::new(&length_name), length_type.clone()));
+    mapping.extend(
+        [Comparator::Gt, Comparator::Gte, Comparator::Lt, Comparator::Lte, Comparator::Neq]
+            .iter()
+            .map(|comparator| {
+                (Name::new(format!("{}{}", length_name, comparator.as_ref())), length_type.clone())
+            }),
+    );
+
+    mapping
+}
+
+fn nested_where_input(
+    type_name: &Name,
+    nested_type: &str,
+    nested_mapping: &TypeMapping,
+) -> Option<(Name, TypeData)> {
+    let nested_where = WhereInputObject::new(nested_type, nested_mapping);
+
+    // input objects must have at least one field
+    if nested_where.type_mapping.is_empty() {
+        return None;
+    }
+
+    Some((
+        Name::new(type_name),
+        TypeData::Nested((TypeRef::named(nested_where.type_name), nested_where.type_mapping)),
+    ))
+}
+
+fn input_object(type_name: &str, type_mapping: &TypeMapping) -> InputObject {
+    type_mapping.iter().fold(InputObject::new(type_name), |acc, (ty_name, ty)| {
+        // nested elements filters take a single input
+        let type_ref = match ty {
+            TypeData::List(inner) if inner.is_nested() => inner.type_ref(),
+            ty => ty.type_ref(),
+        };
+
+        acc.field(InputValue::new(ty_name.to_string(), type_ref))
+    })
 }
 
 impl InputObjectTrait for WhereInputObject {
@@ -69,9 +168,7 @@ impl InputObjectTrait for WhereInputObject {
     }
 
     fn input_object(&self) -> InputObject {
-        self.type_mapping.iter().fold(InputObject::new(self.type_name()), |acc, (ty_name, ty)| {
-            acc.field(InputValue::new(ty_name.to_string(), ty.type_ref()))
-        })
+        input_object(self.type_name(), &self.type_mapping)
     }
 }
 
@@ -84,48 +181,101 @@ pub fn parse_where_argument(
     where_mapping: &TypeMapping,
 ) -> Result<Option<Vec<Filter>>> {
     ctx.args.get("where").map_or(Ok(None), |where_input| {
-        let input_object = where_input.object()?;
-        where_mapping
-            .iter()
-            .filter_map(|(type_name, type_data)| {
-                input_object.get(type_name).map(|input| match type_data {
-                    TypeData::Simple(_) => {
-                        if type_data.type_ref() == TypeRef::named("Enum") {
-                            let value = input.string().unwrap();
-                            return Ok(Some(parse_filter(
-                                type_name,
-                                FilterValue::String(value.to_string()),
-                            )));
-                        }
+        parse_where_input(where_input, where_mapping, &[], false).map(Some)
+    })
+}
+
+// Parses the filters of a where input, `path` being the path of the nested table of the input
+// relative to the model table, and `in_list` whether the input filters the elements of an array
+fn parse_where_input(
+    where_input: ValueAccessor<'_>,
+    where_mapping: &TypeMapping,
+    path: &[String],
+    in_list: bool,
+) -> Result<Vec<Filter>> {
+    let input_object = where_input.object()?;
+    let mut filters = Vec::new();
+
+    for (type_name, type_data) in where_mapping {
+        let Some(input) = input_object.get(type_name) else {
+            continue;
+        };
 
+        let member_filters = match type_data {
+            TypeData::Simple(_) => {
+                let filter = if length_filter_member(type_name).is_some() {
+                    // the length of the arrays nested in an array can't be told apart, their
+                    // elements all share the same table
+                    if in_list {
+                        return Err(GqlError::new(format!(
+                            "Cannot filter on {} of arrays nested in an array",
+                            type_name
+                        )));
+                    }
+
+                    let length = input.i64().map_err(|_| {
+                        GqlError::new(format!("Expected integer on field {}", type_name))
+                    })?;
+                    parse_length_filter(type_name, length)
+                } else {
+                    let filter_value = if type_data.type_ref() == TypeRef::named("Enum") {
+                        FilterValue::String(input.string().unwrap().to_string())
+                    } else {
                         let primitive = Primitive::from_str(&type_data.type_ref().to_string())?;
-                        let filter_value = match primitive.to_sql_type() {
+                        match primitive.to_sql_type() {
                             SqlType::Integer => parse_integer(input, type_name, primitive)?,
                             SqlType::Text => parse_string(input, type_name, primitive)?,
-                        };
+                        }
+                    };
 
-                        Ok(Some(parse_filter(type_name, filter_value)))
-                    }
-                    TypeData::List(inner) => {
-                        let list = input.list()?;
-                        let values = list
-                            .iter()
-                            .map(|value| {
-                                let primitive = Primitive::from_str(&inner.type_ref().to_string())?;
-                                match primitive.to_sql_type() {
-                                    SqlType::Integer => parse_integer(value, type_name, primitive),
-                                    SqlType::Text => parse_string(value, type_name, primitive),
-                                }
-                            })
-                            .collect::<Result<Vec<_>>>()?;
-
-                        Ok(Some(parse_filter(type_name, FilterValue::List(values))))
+                    if type_name.ends_with(CONTAINS_SUFFIX) {
+                        parse_contains_filter(type_name, filter_value)
+                    } else {
+                        parse_filter(type_name, filter_value)
                     }
-                    _ => Err(GqlError::new("Nested types are not supported")),
-                })
-            })
-            .collect::<Result<Option<Vec<_>>>>()
-    })
+                };
+
+                vec![at_path(filter, path)]
+            }
+            TypeData::Nested((_, nested_mapping)) => {
+                let mut nested_path = path.to_vec();
+                nested_path.push(type_name.to_string());
+                parse_where_input(input, nested_mapping, &nested_path, in_list)?
+            }
+            // the members of the array elements are stored in the `data` table of the array
+            TypeData::List(inner) if inner.is_nested() => {
+                let nested_mapping = inner.type_mapping().unwrap();
+                let mut nested_path = path.to_vec();
+                nested_path.extend([type_name.to_string(), "data".to_string()]);
+                parse_where_input(input, nested_mapping, &nested_path, true)?
+            }
+            TypeData::List(inner) => {
+                let list = input.list()?;
+                let values = list
+                    .iter()
+                    .map(|value| {
+                        let primitive = Primitive::from_str(&inner.type_ref().to_string())?;
+                        match primitive.to_sql_type() {
+                            SqlType::Integer => parse_integer(value, type_name, primitive),
+                            SqlType::Text => parse_string(value, type_name, primitive),
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                vec![at_path(parse_filter(type_name, FilterValue::List(values)), path)]
+            }
+        };
+
+        filters.extend(member_filters);
+    }
+
+    Ok(filters)
+}
+
+// filters are parsed relative to the table of their input
+fn at_path(mut filter: Filter, path: &[String]) -> Filter {
+    filter.path.splice(0..0, path.iter().cloned());
+    filter
 }
 
 fn parse_integer(
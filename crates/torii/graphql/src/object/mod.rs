@@ -1,3 +1,4 @@
+pub mod aggregate;
 pub mod connection;
 pub mod entity;
 pub mod event;
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use sqlx::{FromRow, Pool, Sqlite};
 
+use super::aggregate::AggregateObject;
 use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
 use super::inputs::order_input::{order_argument, parse_order_argument, OrderInputObject};
 use super::inputs::where_input::{parse_where_argument, where_argument, WhereInputObject};
@@ -41,14 +42,16 @@ pub struct ModelDataObject {
     pub type_mapping: TypeMapping,
     pub where_input: WhereInputObject,
     pub order_input: OrderInputObject,
+    pub aggregate: AggregateObject,
 }
 
 impl ModelDataObject {
     pub fn new(name: String, type_name: String, type_mapping: TypeMapping) -> Self {
         let where_input = WhereInputObject::new(type_name.as_str(), &type_mapping);
         let order_input = OrderInputObject::new(type_name.as_str(), &type_mapping);
+        let aggregate = AggregateObject::new(&name, &type_name, &type_mapping);
         let plural_name = format!("{}Models", name);
-        Self { name, plural_name, type_name, type_mapping, where_input, order_input }
+        Self { name, plural_name, type_name, type_mapping, where_input, order_input, aggregate }
     }
 }
 
@@ -81,13 +84,16 @@ impl BasicObject for ModelDataObject {
         root = root.field(event_message_field());
 
         objects.push(root);
+        objects.extend(self.aggregate.objects());
         objects
     }
 }
 
 impl ResolvableObject for ModelDataObject {
     fn input_objects(&self) -> Option<Vec<InputObject>> {
-        Some(vec![self.where_input.input_object(), self.order_input.input_object()])
+        let mut objects = vec![self.where_input.input_object(), self.order_input.input_object()];
+        objects.extend(self.where_input.nested_input_objects());
+        Some(objects)
     }
 
     fn enum_objects(&self) -> Option<Vec<Enum>> {
@@ -145,7 +151,9 @@ impl ResolvableObject for ModelDataObject {
         field = where_argument(field, self.type_name());
         field = order_argument(field, self.type_name());
 
-        vec![field]
+        let aggregate = self.aggregate.resolver(self.type_name(), &self.where_input.type_mapping);
+
+        vec![field, aggregate]
     }
 }
 
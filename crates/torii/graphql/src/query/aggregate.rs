@@ -0,0 +1,72 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Result, SqliteConnection};
+use strum_macros::AsRefStr;
+
+use super::data::build_conditions;
+use super::filter::Filter;
+
+pub const COUNT_COLUMN: &str = "count";
+
+#[derive(AsRefStr, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum AggregateFunction {
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    // SUM is casted to REAL so that both numeric aggregates decode the same way
+    fn expression(&self, column: &str) -> String {
+        match self {
+            AggregateFunction::Sum => format!("CAST(SUM({column}) AS REAL)"),
+            AggregateFunction::Avg => format!("AVG({column})"),
+            AggregateFunction::Min => format!("MIN({column})"),
+            AggregateFunction::Max => format!("MAX({column})"),
+        }
+    }
+}
+
+// Name of the column holding the aggregate of a member, eg "min$external_score"
+pub fn aggregate_column(function: AggregateFunction, field: &str) -> String {
+    format!("{}$external_{}", function.as_ref(), field)
+}
+
+// Returns one row per group, each row has a `count` column, a `external_{member}` column for every
+// group by member and a column named by `aggregate_column` for every aggregate. Without group by
+// members, a single row aggregates all the rows matching the filters.
+pub async fn fetch_aggregates(
+    conn: &mut SqliteConnection,
+    table_name: &str,
+    filters: &Option<Vec<Filter>>,
+    group_by: &[String],
+    aggregates: &[(AggregateFunction, String)],
+) -> Result<Vec<SqliteRow>> {
+    let group_columns =
+        group_by.iter().map(|field| format!("external_{}", field)).collect::<Vec<_>>();
+
+    let mut columns = vec![format!("COUNT(*) AS {COUNT_COLUMN}")];
+    columns.extend(group_columns.iter().cloned());
+    columns.extend(aggregates.iter().map(|(function, field)| {
+        format!(
+            "{} AS [{}]",
+            function.expression(&format!("external_{}", field)),
+            aggregate_column(*function, field)
+        )
+    }));
+
+    let mut query = format!("SELECT {} FROM [{}]", columns.join(", "), table_name);
+
+    let conditions = build_conditions(table_name, &None, filters);
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+
+    if !group_columns.is_empty() {
+        let group_columns = group_columns.join(", ");
+        query.push_str(&format!(" GROUP BY {group_columns} ORDER BY {group_columns}"));
+    }
+
+    sqlx::query(&query).fetch_all(conn).await
+}
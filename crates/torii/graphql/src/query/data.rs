@@ -1,6 +1,7 @@
 use async_graphql::connection::PageInfo;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Result, Row, SqliteConnection};
+use torii_core::search::SEARCH_TABLE;
 
 use super::filter::{Filter, FilterValue};
 use super::order::{CursorDirection, Direction, Order};
@@ -14,7 +15,7 @@ pub async fn count_rows(
     filters: &Option<Vec<Filter>>,
 ) -> Result<i64> {
     let mut query = format!("SELECT COUNT(*) FROM [{}]", table_name);
-    let conditions = build_conditions(keys, filters);
+    let conditions = build_conditions(table_name, keys, filters);
 
     if !conditions.is_empty() {
         query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
@@ -24,6 +25,22 @@ pub async fn count_rows(
     Ok(result.0)
 }
 
+// Rows of the entities whose searchable members match the text, the best matches first
+pub async fn fetch_search_rows(
+    conn: &mut SqliteConnection,
+    table_name: &str,
+    search_query: &str,
+    limit: u64,
+) -> Result<Vec<SqliteRow>> {
+    let query = format!(
+        "SELECT [{table_name}].* FROM [{table_name}] JOIN (SELECT id, MIN(rank) AS rank FROM \
+         {SEARCH_TABLE} WHERE {SEARCH_TABLE} MATCH ? GROUP BY id) AS search ON search.id = \
+         [{table_name}].id ORDER BY search.rank LIMIT ?"
+    );
+
+    sqlx::query(&query).bind(search_query).bind(limit as i64).fetch_all(conn).await
+}
+
 pub async fn fetch_world_address(conn: &mut SqliteConnection) -> Result<String> {
     let query = "SELECT world_address FROM worlds".to_string();
     let res: (String,) = sqlx::query_as(&query).fetch_one(conn).await?;
@@ -51,7 +68,7 @@ pub async fn fetch_multiple_rows(
     connection: &ConnectionArguments,
     total_count: i64,
 ) -> Result<(Vec<SqliteRow>, PageInfo)> {
-    let mut conditions = build_conditions(keys, filters);
+    let mut conditions = build_conditions(table_name, keys, filters);
 
     let mut cursor_param = &connection.after;
     if let Some(after_cursor) = &connection.after {
@@ -208,7 +225,11 @@ fn handle_cursor(
     }
 }
 
-fn build_conditions(keys: &Option<Vec<String>>, filters: &Option<Vec<Filter>>) -> Vec<String> {
+pub(crate) fn build_conditions(
+    table_name: &str,
+    keys: &Option<Vec<String>>,
+    filters: &Option<Vec<Filter>>,
+) -> Vec<String> {
     let mut conditions = Vec::new();
 
     if let Some(keys) = keys {
@@ -224,27 +245,51 @@ fn build_conditions(keys: &Option<Vec<String>>, filters: &Option<Vec<Filter>>) -
     }
 
     if let Some(filters) = filters {
-        conditions.extend(filters.iter().map(|filter| match &filter.value {
-            FilterValue::Int(i) => format!("{} {} {}", filter.field, filter.comparator, i),
-            FilterValue::String(s) => format!("{} {} '{}'", filter.field, filter.comparator, s),
-            FilterValue::List(list) => {
-                let values = list
-                    .iter()
-                    .map(|value| match value {
-                        FilterValue::Int(i) => i.to_string(),
-                        FilterValue::String(s) => format!("'{}'", s),
-                        FilterValue::List(_) => unreachable!(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("{} {} ({})", filter.field, filter.comparator, values)
-            }
-        }));
+        conditions.extend(filters.iter().map(|filter| filter_condition(table_name, filter)));
     }
 
     conditions
 }
 
+fn filter_condition(table_name: &str, filter: &Filter) -> String {
+    let value = match &filter.value {
+        FilterValue::Int(i) => i.to_string(),
+        FilterValue::String(s) => format!("'{}'", s),
+        FilterValue::List(list) => {
+            let values = list
+                .iter()
+                .map(|value| match value {
+                    FilterValue::Int(i) => i.to_string(),
+                    FilterValue::String(s) => format!("'{}'", s),
+                    FilterValue::List(_) => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", values)
+        }
+    };
+    let predicate = format!("{} {} {}", filter.field, filter.comparator, value);
+
+    if filter.path.is_empty() {
+        return predicate;
+    }
+
+    // nested members are stored in their own table, sharing the id of the model row. a row matches
+    // if any of the rows of the nested table, eg the elements of an array, matches
+    let nested_table = format!("{}${}", table_name, filter.path.join("$"));
+    if !filter.length {
+        return format!("id IN (SELECT id FROM [{}] WHERE {})", nested_table, predicate);
+    }
+
+    // empty arrays have no rows, so when they match the length filter the rows of the arrays that
+    // don't match it are excluded instead
+    format!(
+        "CASE WHEN 0 {} {} THEN id NOT IN (SELECT id FROM [{}] GROUP BY id HAVING NOT ({})) ELSE \
+         id IN (SELECT id FROM [{}] GROUP BY id HAVING {}) END",
+        filter.comparator, value, nested_table, predicate, nested_table, predicate
+    )
+}
+
 fn keys_to_pattern(keys: &[String], use_regex: bool) -> String {
     let pattern = keys
         .iter()
@@ -4,6 +4,9 @@ use async_graphql::Name;
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, Display, EnumIter};
 
+pub const LENGTH_SUFFIX: &str = "LENGTH";
+pub const CONTAINS_SUFFIX: &str = "CONTAINS";
+
 #[derive(AsRefStr, Debug, Clone, PartialEq, EnumIter)]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Comparator {
@@ -51,6 +54,11 @@ pub struct Filter {
     pub field: String,
     pub comparator: Comparator,
     pub value: FilterValue,
+    // Path of the nested table holding the member, relative to the model table. Empty for members
+    // of the model table
+    pub path: Vec<String>,
+    // Whether the filter compares the number of rows of the nested table, the length of an array
+    pub length: bool,
 }
 
 pub fn parse_filter(input: &Name, value: FilterValue) -> Filter {
@@ -62,10 +70,51 @@ pub fn parse_filter(input: &Name, value: FilterValue) -> Filter {
                 field: format!("external_{}", field),
                 comparator: comparator.clone(),
                 value,
+                path: Vec::new(),
+                length: false,
             };
         }
     }
 
     // If no suffix found assume equality comparison
-    Filter { field: format!("external_{}", input), comparator: Comparator::Eq, value }
+    Filter {
+        field: format!("external_{}", input),
+        comparator: Comparator::Eq,
+        value,
+        path: Vec::new(),
+        length: false,
+    }
+}
+
+// The array member filtered on its length by a where input field, eg `scores` for `scoresLENGTHGT`
+pub fn length_filter_member(input: &str) -> Option<&str> {
+    let input = Comparator::iter()
+        .find_map(|comparator| input.strip_suffix(comparator.as_ref()))
+        .unwrap_or(input);
+
+    input.strip_suffix(LENGTH_SUFFIX)
+}
+
+// Filters arrays on their length, eg `scoresLENGTHGT`, by counting the rows of the array table
+pub fn parse_length_filter(input: &Name, length: i64) -> Filter {
+    let mut filter = parse_filter(input, FilterValue::Int(length));
+    let member = length_filter_member(input).unwrap_or(input);
+
+    filter.path = vec![member.to_string()];
+    filter.field = "COUNT(*)".to_string();
+    filter.length = true;
+    filter
+}
+
+// Filters arrays of simple types on their elements, eg `scoresCONTAINS`
+pub fn parse_contains_filter(input: &Name, value: FilterValue) -> Filter {
+    let member = input.strip_suffix(CONTAINS_SUFFIX).unwrap_or(input);
+
+    Filter {
+        field: "external_data".to_string(),
+        comparator: Comparator::Eq,
+        value,
+        path: vec![member.to_string()],
+        length: false,
+    }
 }
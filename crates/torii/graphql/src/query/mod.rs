@@ -16,6 +16,7 @@ use crate::constants::{
 use crate::object::model_data::ModelMember;
 use crate::types::{TypeData, TypeMapping, ValueMapping};
 
+pub mod aggregate;
 pub mod data;
 pub mod filter;
 pub mod order;
@@ -216,16 +217,24 @@ fn fetch_value(
         field_name.to_string().to_case(Case::Snake)
     };
 
+    fetch_column_value(row, &column_name, type_name)
+}
+
+pub(crate) fn fetch_column_value(
+    row: &SqliteRow,
+    column_name: &str,
+    type_name: &str,
+) -> sqlx::Result<Value> {
     match Primitive::from_str(type_name) {
         // fetch boolean
         Ok(Primitive::Bool(_)) => {
-            Ok(Value::from(matches!(row.try_get::<i64, &str>(&column_name)?, BOOLEAN_TRUE)))
+            Ok(Value::from(matches!(row.try_get::<i64, &str>(column_name)?, BOOLEAN_TRUE)))
         }
         // fetch integer/string base on sql type
         Ok(ty) => match ty.to_sql_type() {
-            SqlType::Integer => row.try_get::<i64, &str>(&column_name).map(Value::from),
+            SqlType::Integer => row.try_get::<i64, &str>(column_name).map(Value::from),
             SqlType::Text => Ok(remove_hex_leading_zeros(
-                row.try_get::<String, &str>(&column_name).map(Value::from)?,
+                row.try_get::<String, &str>(column_name).map(Value::from)?,
             )),
         },
         // fetch everything else
@@ -233,13 +242,13 @@ fn fetch_value(
             let value = match type_name {
                 "DateTime" => {
                     let dt = row
-                        .try_get::<DateTime<Utc>, &str>(&column_name)
+                        .try_get::<DateTime<Utc>, &str>(column_name)
                         .expect("Should be a stored as UTC Datetime")
                         .to_rfc3339();
                     Value::from(dt)
                 }
                 _ => {
-                    let s = row.try_get::<String, &str>(&column_name)?;
+                    let s = row.try_get::<String, &str>(column_name)?;
                     Value::from(s)
                 }
             };
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use async_graphql::dynamic::Schema;
+    use serde_json::Value;
+
+    use crate::schema::build_schema;
+    use crate::tests::{run_graphql_query, spinup_types_test};
+
+    async fn records_aggregate_query(schema: &Schema, arg: &str) -> Value {
+        let query = format!(
+            r#"
+          {{
+             typesTestRecordAggregate {} {{
+              count
+              group {{
+                type_bool
+              }}
+              min {{
+                type_u16
+                type_u64
+              }}
+              max {{
+                type_u16
+                type_u64
+              }}
+              sum {{
+                type_u16
+              }}
+              avg {{
+                type_u16
+              }}
+            }}
+          }}
+        "#,
+            arg,
+        );
+
+        let result = run_graphql_query(schema, &query).await;
+        result
+            .get("typesTestRecordAggregate")
+            .ok_or("typesTestRecordAggregate not found")
+            .unwrap()
+            .clone()
+    }
+
+    // End to end test spins up a test sequencer and deploys types-test project, this takes a while
+    // to run so combine all related tests into one
+    #[tokio::test(flavor = "multi_thread")]
+    async fn aggregate_test() -> Result<()> {
+        let pool = spinup_types_test().await?;
+        let schema = build_schema(&pool).await.unwrap();
+
+        // aggregates of all the records
+        let aggregates = records_aggregate_query(&schema, "").await;
+        let aggregate = &aggregates[0];
+        assert_eq!(aggregates.as_array().unwrap().len(), 1);
+        assert_eq!(aggregate["count"], 10);
+        assert_eq!(aggregate["group"], Value::Null);
+        assert_eq!(aggregate["min"]["type_u16"], 0);
+        assert_eq!(aggregate["max"]["type_u16"], 9);
+        assert_eq!(aggregate["min"]["type_u64"], "0x0");
+        assert_eq!(aggregate["max"]["type_u64"], "0x9");
+        assert_eq!(aggregate["sum"]["type_u16"], 45.0);
+        assert_eq!(aggregate["avg"]["type_u16"], 4.5);
+
+        // aggregates of the records matching the where filter
+        let aggregates = records_aggregate_query(&schema, "(where: { type_u16GTE: 5 })").await;
+        let aggregate = &aggregates[0];
+        assert_eq!(aggregate["count"], 5);
+        assert_eq!(aggregate["min"]["type_u16"], 5);
+        assert_eq!(aggregate["sum"]["type_u16"], 35.0);
+
+        // no record matches the where filter
+        let aggregates = records_aggregate_query(&schema, "(where: { type_u16GT: 9 })").await;
+        let aggregate = &aggregates[0];
+        assert_eq!(aggregate["count"], 0);
+        assert_eq!(aggregate["min"], Value::Null);
+        assert_eq!(aggregate["sum"]["type_u16"], Value::Null);
+
+        // aggregates grouped by a member
+        let aggregates = records_aggregate_query(&schema, "(groupBy: [TYPE_BOOL])").await;
+        let groups = aggregates.as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0]["group"]["type_bool"], false);
+        assert_eq!(groups[0]["count"], 5);
+        assert_eq!(groups[0]["sum"]["type_u16"], 25.0);
+        assert_eq!(groups[1]["group"]["type_bool"], true);
+        assert_eq!(groups[1]["count"], 5);
+        assert_eq!(groups[1]["sum"]["type_u16"], 20.0);
+
+        // aggregates grouped by a member and filtered
+        let aggregates =
+            records_aggregate_query(&schema, "(where: { type_u16LT: 3 }, groupBy: [TYPE_BOOL])")
+                .await;
+        let groups = aggregates.as_array().unwrap();
+        assert_eq!(groups[0]["count"], 1);
+        assert_eq!(groups[1]["count"], 2);
+        assert_eq!(groups[1]["max"]["type_u16"], 2);
+
+        Ok(())
+    }
+}
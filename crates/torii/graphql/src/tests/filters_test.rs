@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+    use async_graphql::dynamic::Schema;
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use dojo_world::contracts::abi::model::Layout;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use starknet::core::types::Felt;
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    fn player(name: &str, address: u8, scores: &[u8]) -> Ty {
+        Ty::Struct(Struct {
+            name: name.to_string(),
+            children: vec![
+                Member {
+                    name: "player".to_string(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::from(address)))),
+                    key: true,
+                },
+                Member {
+                    name: "scores".to_string(),
+                    ty: Ty::Array(
+                        scores.iter().map(|s| Ty::Primitive(Primitive::U8(Some(*s)))).collect(),
+                    ),
+                    key: false,
+                },
+            ],
+        })
+    }
+
+    async fn total_count(schema: &Schema, where_input: &str) -> i64 {
+        let query = format!("{{ nsPlayerModels(where: {{ {} }}) {{ totalCount }} }}", where_input);
+        let result = run_graphql_query(schema, &query).await;
+        result["nsPlayerModels"]["totalCount"].as_i64().unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_array_filters() -> Result<()> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await?;
+        sqlx::migrate!("../migrations").run(&pool).await?;
+
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await?;
+        let model = Ty::Struct(Struct {
+            name: "Player".to_string(),
+            children: vec![
+                Member {
+                    name: "player".to_string(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                    key: true,
+                },
+                Member {
+                    name: "scores".to_string(),
+                    ty: Ty::Array(vec![Ty::Primitive(Primitive::U8(None))]),
+                    key: false,
+                },
+            ],
+        });
+        db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+            .await?;
+
+        let scores: [&[u8]; 3] = [&[1, 2, 3], &[5], &[]];
+        for (idx, scores) in scores.into_iter().enumerate() {
+            db.set_entity(player("ns-Player", idx as u8 + 1, scores), &format!("{idx:#x}"), 0)
+                .await?;
+        }
+        db.execute().await?;
+
+        let schema = build_schema(&pool).await?;
+
+        // elements
+        assert_eq!(total_count(&schema, "scoresCONTAINS: 5").await, 1);
+        assert_eq!(total_count(&schema, "scoresCONTAINS: 2").await, 1);
+        assert_eq!(total_count(&schema, "scoresCONTAINS: 4").await, 0);
+
+        // length, the empty array has no rows in the array table
+        assert_eq!(total_count(&schema, "scoresLENGTH: 1").await, 1);
+        assert_eq!(total_count(&schema, "scoresLENGTH: 0").await, 1);
+        assert_eq!(total_count(&schema, "scoresLENGTHGT: 0").await, 2);
+        assert_eq!(total_count(&schema, "scoresLENGTHLT: 2").await, 2);
+        assert_eq!(total_count(&schema, "scoresLENGTHNEQ: 3").await, 2);
+        assert_eq!(total_count(&schema, "scoresLENGTHGTE: 1, scoresCONTAINS: 3").await, 1);
+
+        Ok(())
+    }
+}
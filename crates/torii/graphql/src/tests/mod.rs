@@ -30,11 +30,14 @@ use torii_core::processors::store_del_record::StoreDelRecordProcessor;
 use torii_core::processors::store_set_record::StoreSetRecordProcessor;
 use torii_core::sql::Sql;
 
+mod aggregate_test;
 mod entities_test;
 mod events_test;
+mod filters_test;
 mod metadata_test;
 mod models_ordering_test;
 mod models_test;
+mod search_test;
 mod subscription_test;
 
 use crate::schema::build_schema;
@@ -297,6 +297,24 @@ mod tests {
         let connection: Connection<Record> = serde_json::from_value(records).unwrap();
         assert_eq!(connection.total_count, 7);
 
+        // where filter on nested member
+        let records = records_model_query(
+            &schema,
+            "(where: { type_deeply_nested: { type_nested_more: { type_numberGTE: 7 } } })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 3);
+
+        // where filter on nested enum member
+        let records = records_model_query(
+            &schema,
+            "(where: { type_nested_one: { depth: \"One\" }, type_u8LT: 4 })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 4);
+
         // *** ORDER TESTING ***
 
         // order on random u8 DESC (number)
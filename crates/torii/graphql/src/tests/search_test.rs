@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+    use async_graphql::dynamic::Schema;
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use dojo_world::contracts::abi::model::Layout;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use starknet::core::types::Felt;
+    use starknet::core::utils::cairo_short_string_to_felt;
+    use starknet_crypto::poseidon_hash_many;
+    use torii_core::search::SearchField;
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    fn guild(name: &str, id: u32, guild_name: &str, tag: &str) -> Ty {
+        Ty::Struct(Struct {
+            name: name.to_string(),
+            children: vec![
+                Member {
+                    name: "id".to_string(),
+                    ty: Ty::Primitive(Primitive::U32(Some(id))),
+                    key: true,
+                },
+                Member {
+                    name: "name".to_string(),
+                    ty: Ty::ByteArray(guild_name.to_string()),
+                    key: false,
+                },
+                Member {
+                    name: "tag".to_string(),
+                    ty: Ty::Primitive(Primitive::Felt252(Some(
+                        cairo_short_string_to_felt(tag).unwrap(),
+                    ))),
+                    key: false,
+                },
+            ],
+        })
+    }
+
+    async fn search(schema: &Schema, args: &str) -> Vec<String> {
+        let query = format!("{{ search({}) {{ id }} }}", args);
+        let result = run_graphql_query(schema, &query).await;
+        let mut ids = result["search"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entity| entity["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_search() -> Result<()> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await?;
+        sqlx::migrate!("../migrations").run(&pool).await?;
+
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await?;
+        let fields = ["ns-Guild.name", "ns-Guild.tag"].map(|f| SearchField::from_str(f).unwrap());
+        db.enable_search(fields.to_vec()).await?;
+
+        let model = guild("Guild", 0, "", "");
+        db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+            .await?;
+
+        let guilds =
+            [(1, "Iron Knights", "iron"), (2, "Silver Knights", "silver"), (3, "Bards", "lute")];
+        for (id, name, tag) in guilds {
+            db.set_entity(guild("ns-Guild", id, name, tag), &format!("{id:#x}"), 0).await?;
+        }
+        db.execute().await?;
+
+        let schema = build_schema(&pool).await?;
+        let id = |id: Felt| format!("{:#x}", poseidon_hash_many(&[id]));
+
+        let mut knights = vec![id(Felt::ONE), id(Felt::TWO)];
+        knights.sort();
+        assert_eq!(search(&schema, r#"text: "knights""#).await, knights);
+        assert_eq!(search(&schema, r#"text: "knights", limit: 1"#).await.len(), 1);
+
+        // the last word is matched as a prefix, on any of the searchable members
+        assert_eq!(search(&schema, r#"text: "lu""#).await, vec![id(Felt::THREE)]);
+        assert_eq!(search(&schema, r#"text: "silver kni""#).await, vec![id(Felt::TWO)]);
+        assert!(search(&schema, r#"text: "iron bards""#).await.is_empty());
+        assert!(search(&schema, r#"text: " ""#).await.is_empty());
+
+        Ok(())
+    }
+}
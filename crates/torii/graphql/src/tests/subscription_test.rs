@@ -249,6 +249,83 @@ mod tests {
         rx.recv().await.unwrap();
     }
 
+    #[sqlx::test(migrations = "../migrations")]
+    #[serial]
+    async fn test_entity_deleted_subscription(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+
+        model_fixtures(&mut db).await;
+        // 0. Preprocess expected entity value
+        let namespace = "types_test".to_string();
+        let model_name = "Record".to_string();
+        let key = vec![Felt::ONE];
+        let entity_id = format!("{:#x}", poseidon_hash_many(&key));
+        let block_timestamp = 1710754478_u64;
+        let keys_str = key.iter().map(|k| format!("{:#x}", k)).collect::<Vec<String>>().join(",");
+        let delete_event_id = format!("0x{:064x}:0x{:04x}:0x{:04x}", 1, 0, 0);
+
+        let expected_value: async_graphql::Value = value!({
+            "entityDeleted": {
+                "id": entity_id,
+                "keys": vec![keys_str],
+                "eventId": delete_event_id.clone(),
+            }
+        });
+
+        let model = Ty::Struct(Struct {
+            name: utils::struct_name_from_names(&namespace, &model_name),
+            children: vec![
+                Member {
+                    name: "record_id".to_string(),
+                    key: false,
+                    ty: Ty::Primitive(Primitive::U32(Some(0))),
+                },
+                Member {
+                    name: "typeContractAddress".to_string(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::ONE))),
+                },
+            ],
+        });
+        db.set_entity(
+            model.clone(),
+            &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, 0),
+            block_timestamp,
+        )
+        .await
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(10);
+
+        tokio::spawn(async move {
+            // 1. Open process and sleep.Go to execute subscription
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            // Delete the entity, the update of its models isn't sent to `entityDeleted`
+            db.delete_entity(Felt::from_str(&entity_id).unwrap(), model, &delete_event_id)
+                .await
+                .unwrap();
+
+            tx.send(()).await.unwrap();
+        });
+
+        // 2. The subscription is executed and it is listening, waiting for publish() to be executed
+        let response_value = run_graphql_subscription(
+            &pool,
+            r#"subscription {
+                entityDeleted {
+                    id
+                    keys
+                    eventId
+                }
+            }"#,
+        )
+        .await;
+        // 3. The subscription has received the deletion
+        assert_eq!(expected_value, response_value);
+        rx.recv().await.unwrap();
+    }
+
     #[sqlx::test(migrations = "../migrations")]
     #[serial]
     async fn test_model_subscription(pool: SqlitePool) {
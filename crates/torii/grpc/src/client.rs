@@ -1,18 +1,31 @@
 //! Client implementation for the gRPC service.
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::num::ParseIntError;
+use std::sync::Arc;
+use std::time::Duration;
 
-use futures_util::stream::MapOk;
-use futures_util::{Stream, StreamExt, TryStreamExt};
+use dojo_types::schema::ModelMetadata;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use parking_lot::Mutex;
 use starknet::core::types::{Felt, FromStrError, StateDiff, StateUpdate};
+use tonic::codec::CompressionEncoding;
+use tracing::warn;
 
+use crate::proto;
+use crate::proto::types::EntityUpdateType;
 use crate::proto::world::{
-    world_client, MetadataRequest, RetrieveEntitiesRequest, RetrieveEntitiesResponse,
-    RetrieveEventsRequest, RetrieveEventsResponse, SubscribeEntitiesRequest,
-    SubscribeEntityResponse, SubscribeEventsRequest, SubscribeEventsResponse,
-    SubscribeModelsRequest, SubscribeModelsResponse, UpdateEntitiesSubscriptionRequest,
+    world_client, MetadataRequest, RetrieveAggregatesRequest, RetrieveAggregatesResponse,
+    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEventsRequest,
+    RetrieveEventsResponse, SubscribeEntitiesRequest, SubscribeEntityResponse,
+    SubscribeEventsRequest, SubscribeEventsResponse, SubscribeModelRegistrationsRequest,
+    SubscribeModelRegistrationsResponse, SubscribeModelsRequest, SubscribeModelsResponse,
+    UpdateEntitiesSubscriptionRequest,
+};
+use crate::types::schema::{Entity, EntityUpdate, SchemaError};
+use crate::types::{
+    AggregateQuery, Clause, EntityKeysClause, Event, EventQuery, KeysClause, ModelKeysClause, Query,
 };
-use crate::types::schema::{Entity, SchemaError};
-use crate::types::{EntityKeysClause, Event, EventQuery, KeysClause, ModelKeysClause, Query};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -29,14 +42,80 @@ pub enum Error {
     Schema(#[from] SchemaError),
 }
 
+pub(crate) const LOG_TARGET: &str = "torii::grpc::client";
+
+/// Backoff of the reconnection of the subscriptions when their stream fails or ends.
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnection attempt, doubled after each failed attempt.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Number of failed attempts after which the stream yields the error and ends. Retries
+    /// forever if `None`.
+    pub max_retries: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retries: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_backoff)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Inner = world_client::WorldClient<tonic::transport::Channel>;
+#[cfg(target_arch = "wasm32")]
+type Inner = world_client::WorldClient<tonic_web_wasm_client::Client>;
+
+// The filters of an entities or event messages subscription.
+#[derive(Debug, Clone)]
+struct EntitySubscription {
+    clauses: Vec<EntityKeysClause>,
+    clause: Option<Clause>,
+    /// Id given by the server, 0 until the subscription is started.
+    id: SubscriptionId,
+}
+
+impl EntitySubscription {
+    fn request(&self, cursor: String) -> SubscribeEntitiesRequest {
+        SubscribeEntitiesRequest {
+            clauses: self.clauses.iter().cloned().map(Into::into).collect(),
+            cursor,
+            clause: self.clause.clone().map(Into::into),
+            subscription_id: self.id,
+        }
+    }
+
+    fn update_request(&self, subscription_id: SubscriptionId) -> UpdateEntitiesSubscriptionRequest {
+        UpdateEntitiesSubscriptionRequest {
+            subscription_id,
+            clauses: self.clauses.iter().cloned().map(Into::into).collect(),
+            clause: self.clause.clone().map(Into::into),
+        }
+    }
+}
+
+// The filters of the subscriptions by id, updated when the subscriptions are updated so that
+// they are resumed with their latest filters.
+type Subscriptions = Arc<Mutex<HashMap<SubscriptionId, Arc<Mutex<EntitySubscription>>>>>;
+
 #[derive(Debug)]
 /// A lightweight wrapper around the grpc client.
 pub struct WorldClient {
     _world_address: Felt,
-    #[cfg(not(target_arch = "wasm32"))]
-    inner: world_client::WorldClient<tonic::transport::Channel>,
-    #[cfg(target_arch = "wasm32")]
-    inner: world_client::WorldClient<tonic_web_wasm_client::Client>,
+    inner: Inner,
+    reconnect: ReconnectConfig,
+    entity_subscriptions: Subscriptions,
+    event_message_subscriptions: Subscriptions,
 }
 
 impl WorldClient {
@@ -49,6 +128,9 @@ impl WorldClient {
         Ok(Self {
             _world_address,
             inner: world_client::WorldClient::connect(dst).await.map_err(Error::Transport)?,
+            reconnect: ReconnectConfig::default(),
+            entity_subscriptions: Default::default(),
+            event_message_subscriptions: Default::default(),
         })
     }
 
@@ -58,9 +140,33 @@ impl WorldClient {
         Ok(Self {
             _world_address,
             inner: world_client::WorldClient::new(tonic_web_wasm_client::Client::new(endpoint)),
+            reconnect: ReconnectConfig::default(),
+            entity_subscriptions: Default::default(),
+            event_message_subscriptions: Default::default(),
         })
     }
 
+    /// Sets the backoff of the reconnection of the subscriptions.
+    pub fn with_reconnect(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Compresses the requests with gzip and asks the server for gzip compressed responses.
+    pub fn with_compression(mut self) -> Self {
+        self.inner = self
+            .inner
+            .send_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Gzip);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of the request and response messages.
+    pub fn with_max_message_size(mut self, size: usize) -> Self {
+        self.inner = self.inner.max_decoding_message_size(size).max_encoding_message_size(size);
+        self
+    }
+
     /// Retrieve the metadata of the World.
     pub async fn metadata(&mut self) -> Result<dojo_types::WorldMetadata, Error> {
         self.inner
@@ -103,118 +209,231 @@ impl WorldClient {
         self.inner.retrieve_events(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
     }
 
+    /// Retrieve the aggregates of a model, grouped by some of its members.
+    pub async fn retrieve_aggregates(
+        &mut self,
+        query: AggregateQuery,
+    ) -> Result<RetrieveAggregatesResponse, Error> {
+        let request = RetrieveAggregatesRequest { query: Some(query.into()) };
+        self.inner
+            .retrieve_aggregates(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     /// Subscribe to entities updates of a World.
+    ///
+    /// The subscription reconnects if its stream fails, and resumes from the last update
+    /// received, the missed updates being replayed by the server, deletions included. A resumed
+    /// subscription keeps its id, and starts with an empty entity like at the start of the
+    /// subscription.
     pub async fn subscribe_entities(
         &mut self,
         clauses: Vec<EntityKeysClause>,
     ) -> Result<EntityUpdateStreaming, Error> {
-        let clauses = clauses.into_iter().map(|c| c.into()).collect();
-        let stream = self
-            .inner
-            .subscribe_entities(SubscribeEntitiesRequest { clauses })
-            .await
-            .map_err(Error::Grpc)
-            .map(|res| res.into_inner())?;
+        self.subscribe_entities_with_clause(clauses, None).await
+    }
 
-        Ok(EntityUpdateStreaming(stream.map_ok(Box::new(|res| {
-            res.entity.map_or(
-                (res.subscription_id, Entity { hashed_keys: Felt::ZERO, models: vec![] }),
-                |entity| (res.subscription_id, entity.try_into().expect("must able to serialize")),
-            )
-        }))))
+    /// Subscribe to entities updates of a World, filtered by their keys with the keys clauses and
+    /// by the values of their models with a clause evaluated by the server on each update. An
+    /// entity which no longer matches the clause is yielded once as [`EntityUpdate::Unmatched`].
+    pub async fn subscribe_entities_with_clause(
+        &mut self,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<EntityUpdateStreaming, Error> {
+        let subscription = Arc::new(Mutex::new(EntitySubscription { clauses, clause, id: 0 }));
+        let inner = self.inner.clone();
+        let subscribe = {
+            let subscription = Arc::clone(&subscription);
+            move |cursor| {
+                let mut inner = inner.clone();
+                let request = subscription.lock().request(cursor);
+                async move { inner.subscribe_entities(request).await.map(|res| res.into_inner()) }
+            }
+        };
+
+        let stream = resumable(subscribe, self.reconnect.clone()).await.map_err(Error::Grpc)?;
+        Ok(EntityUpdateStreaming(boxed(entity_updates(
+            stream,
+            subscription,
+            Arc::clone(&self.entity_subscriptions),
+        ))))
     }
 
-    /// Update an entities subscription.
+    /// Update an entities subscription. The clause of the subscription, if any, is removed.
     pub async fn update_entities_subscription(
         &mut self,
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
     ) -> Result<(), Error> {
-        let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        self.update_entities_subscription_with_clause(subscription_id, clauses, None).await
+    }
+
+    /// Update the keys clauses and the clause of an entities subscription.
+    pub async fn update_entities_subscription_with_clause(
+        &mut self,
+        subscription_id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<(), Error> {
+        let subscription = EntitySubscription { clauses, clause, id: subscription_id };
+        let resumed = self.entity_subscriptions.lock().get(&subscription_id).cloned();
+        if let Some(resumed) = resumed {
+            *resumed.lock() = subscription.clone();
+        }
 
         self.inner
-            .update_entities_subscription(UpdateEntitiesSubscriptionRequest {
-                subscription_id,
-                clauses,
-            })
+            .update_entities_subscription(subscription.update_request(subscription_id))
             .await
             .map_err(Error::Grpc)
             .map(|res| res.into_inner())
     }
 
-    /// Subscribe to event messages of a World.
+    /// Subscribe to event messages of a World. The subscription is resumed like the entities
+    /// subscriptions.
     pub async fn subscribe_event_messages(
         &mut self,
         clauses: Vec<EntityKeysClause>,
     ) -> Result<EntityUpdateStreaming, Error> {
-        let clauses = clauses.into_iter().map(|c| c.into()).collect();
-        let stream = self
-            .inner
-            .subscribe_event_messages(SubscribeEntitiesRequest { clauses })
-            .await
-            .map_err(Error::Grpc)
-            .map(|res| res.into_inner())?;
+        self.subscribe_event_messages_with_clause(clauses, None).await
+    }
 
-        Ok(EntityUpdateStreaming(stream.map_ok(Box::new(|res| {
-            res.entity.map_or(
-                (res.subscription_id, Entity { hashed_keys: Felt::ZERO, models: vec![] }),
-                |entity| (res.subscription_id, entity.try_into().expect("must able to serialize")),
-            )
-        }))))
+    /// Subscribe to event messages of a World, filtered like the entities of
+    /// [`WorldClient::subscribe_entities_with_clause`].
+    pub async fn subscribe_event_messages_with_clause(
+        &mut self,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<EntityUpdateStreaming, Error> {
+        let subscription = Arc::new(Mutex::new(EntitySubscription { clauses, clause, id: 0 }));
+        let inner = self.inner.clone();
+        let subscribe = {
+            let subscription = Arc::clone(&subscription);
+            move |cursor| {
+                let mut inner = inner.clone();
+                let request = subscription.lock().request(cursor);
+                async move {
+                    let response = inner.subscribe_event_messages(request).await?;
+                    Ok(response.into_inner())
+                }
+            }
+        };
+
+        let stream = resumable(subscribe, self.reconnect.clone()).await.map_err(Error::Grpc)?;
+        Ok(EntityUpdateStreaming(boxed(entity_updates(
+            stream,
+            subscription,
+            Arc::clone(&self.event_message_subscriptions),
+        ))))
     }
 
-    /// Update an event messages subscription.
+    /// Update an event messages subscription. The clause of the subscription, if any, is removed.
     pub async fn update_event_messages_subscription(
         &mut self,
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
     ) -> Result<(), Error> {
-        let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        self.update_event_messages_subscription_with_clause(subscription_id, clauses, None).await
+    }
+
+    /// Update the keys clauses and the clause of an event messages subscription.
+    pub async fn update_event_messages_subscription_with_clause(
+        &mut self,
+        subscription_id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+    ) -> Result<(), Error> {
+        let subscription = EntitySubscription { clauses, clause, id: subscription_id };
+        let resumed = self.event_message_subscriptions.lock().get(&subscription_id).cloned();
+        if let Some(resumed) = resumed {
+            *resumed.lock() = subscription.clone();
+        }
+
         self.inner
-            .update_event_messages_subscription(UpdateEntitiesSubscriptionRequest {
-                subscription_id,
-                clauses,
-            })
+            .update_event_messages_subscription(subscription.update_request(subscription_id))
             .await
             .map_err(Error::Grpc)
             .map(|res| res.into_inner())
     }
 
-    /// Subscribe to the events of a World.
+    /// Subscribe to the events of a World. The subscription reconnects if its stream fails, and
+    /// resumes from the last event received.
     pub async fn subscribe_events(
         &mut self,
         keys: Option<KeysClause>,
     ) -> Result<EventUpdateStreaming, Error> {
-        let keys = keys.map(|c| c.into());
-
-        let stream = self
-            .inner
-            .subscribe_events(SubscribeEventsRequest { keys })
-            .await
-            .map_err(Error::Grpc)
-            .map(|res| res.into_inner())?;
+        let keys: Option<proto::types::KeysClause> = keys.map(|c| c.into());
+        let inner = self.inner.clone();
+        let subscribe = move |cursor| {
+            let mut inner = inner.clone();
+            let keys = keys.clone();
+            async move {
+                inner
+                    .subscribe_events(SubscribeEventsRequest { keys, cursor })
+                    .await
+                    .map(|res| res.into_inner())
+            }
+        };
 
-        Ok(EventUpdateStreaming(stream.map_ok(Box::new(|res| match res.event {
+        let stream = resumable(subscribe, self.reconnect.clone()).await.map_err(Error::Grpc)?;
+        Ok(EventUpdateStreaming(boxed(stream.map_ok(|res| match res.event {
             Some(event) => event.into(),
             None => Event { keys: vec![], data: vec![], transaction_hash: Felt::ZERO },
         }))))
     }
 
-    /// Subscribe to the model diff for a set of models of a World.
+    /// Subscribe to the registration of the models of some namespaces, or all of them if empty.
+    /// The subscription reconnects if its stream fails, but the models registered while
+    /// disconnected are not replayed.
+    pub async fn subscribe_model_registrations(
+        &mut self,
+        namespaces: Vec<String>,
+    ) -> Result<ModelRegistrationStreaming, Error> {
+        let inner = self.inner.clone();
+        let subscribe = move |_| {
+            let mut inner = inner.clone();
+            let namespaces = namespaces.clone();
+            async move {
+                inner
+                    .subscribe_model_registrations(SubscribeModelRegistrationsRequest {
+                        namespaces,
+                    })
+                    .await
+                    .map(|res| res.into_inner())
+            }
+        };
+
+        let stream = resumable(subscribe, self.reconnect.clone()).await.map_err(Error::Grpc)?;
+        // the first message of a subscription has no model
+        Ok(ModelRegistrationStreaming(boxed(stream.try_filter_map(|res| async move {
+            Ok(res.model.map(|model| model.try_into().expect("must able to serialize")))
+        }))))
+    }
+
+    /// Subscribe to the model diff for a set of models of a World. The subscription reconnects
+    /// if its stream fails, but the diffs published while disconnected are not replayed.
     pub async fn subscribe_model_diffs(
         &mut self,
         models_keys: Vec<ModelKeysClause>,
     ) -> Result<ModelDiffsStreaming, Error> {
-        let stream = self
-            .inner
-            .subscribe_models(SubscribeModelsRequest {
-                models_keys: models_keys.into_iter().map(|e| e.into()).collect(),
-            })
-            .await
-            .map_err(Error::Grpc)
-            .map(|res| res.into_inner())?;
+        let models_keys: Vec<proto::types::ModelKeysClause> =
+            models_keys.into_iter().map(|e| e.into()).collect();
+        let inner = self.inner.clone();
+        let subscribe = move |_| {
+            let mut inner = inner.clone();
+            let models_keys = models_keys.clone();
+            async move {
+                inner
+                    .subscribe_models(SubscribeModelsRequest { models_keys })
+                    .await
+                    .map(|res| res.into_inner())
+            }
+        };
 
-        Ok(ModelDiffsStreaming(stream.map_ok(Box::new(|res| match res.model_update {
+        let stream = resumable(subscribe, self.reconnect.clone()).await.map_err(Error::Grpc)?;
+        Ok(ModelDiffsStreaming(boxed(stream.map_ok(|res| match res.model_update {
             Some(update) => {
                 TryInto::<StateUpdate>::try_into(update).expect("must able to serialize")
             }
@@ -223,16 +442,49 @@ impl WorldClient {
     }
 }
 
-type ModelDiffMappedStream = MapOk<
-    tonic::Streaming<SubscribeModelsResponse>,
-    Box<dyn Fn(SubscribeModelsResponse) -> StateUpdate + Send>,
->;
+#[cfg(not(target_arch = "wasm32"))]
+type BoxStream<T> = futures_util::stream::BoxStream<'static, Result<T, tonic::Status>>;
+#[cfg(target_arch = "wasm32")]
+type BoxStream<T> = futures_util::stream::LocalBoxStream<'static, Result<T, tonic::Status>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn boxed<T, S>(stream: S) -> BoxStream<T>
+where
+    S: Stream<Item = Result<T, tonic::Status>> + Send + 'static,
+{
+    stream.boxed()
+}
 
-#[derive(Debug)]
-pub struct ModelDiffsStreaming(ModelDiffMappedStream);
+#[cfg(target_arch = "wasm32")]
+fn boxed<T, S>(stream: S) -> BoxStream<T>
+where
+    S: Stream<Item = Result<T, tonic::Status>> + 'static,
+{
+    stream.boxed_local()
+}
+
+pub struct ModelDiffsStreaming(BoxStream<StateUpdate>);
 
 impl Stream for ModelDiffsStreaming {
-    type Item = <ModelDiffMappedStream as Stream>::Item;
+    type Item = Result<StateUpdate, tonic::Status>;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
+
+impl std::fmt::Debug for ModelDiffsStreaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelDiffsStreaming").finish_non_exhaustive()
+    }
+}
+
+pub struct ModelRegistrationStreaming(BoxStream<ModelMetadata>);
+
+impl Stream for ModelRegistrationStreaming {
+    type Item = Result<ModelMetadata, tonic::Status>;
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
@@ -241,17 +493,18 @@ impl Stream for ModelDiffsStreaming {
     }
 }
 
+impl std::fmt::Debug for ModelRegistrationStreaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelRegistrationStreaming").finish_non_exhaustive()
+    }
+}
+
 type SubscriptionId = u64;
-type EntityMappedStream = MapOk<
-    tonic::Streaming<SubscribeEntityResponse>,
-    Box<dyn Fn(SubscribeEntityResponse) -> (SubscriptionId, Entity) + Send>,
->;
 
-#[derive(Debug)]
-pub struct EntityUpdateStreaming(EntityMappedStream);
+pub struct EntityUpdateStreaming(BoxStream<(SubscriptionId, EntityUpdate)>);
 
 impl Stream for EntityUpdateStreaming {
-    type Item = <EntityMappedStream as Stream>::Item;
+    type Item = Result<(SubscriptionId, EntityUpdate), tonic::Status>;
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
@@ -260,16 +513,16 @@ impl Stream for EntityUpdateStreaming {
     }
 }
 
-type EventMappedStream = MapOk<
-    tonic::Streaming<SubscribeEventsResponse>,
-    Box<dyn Fn(SubscribeEventsResponse) -> Event + Send>,
->;
+impl std::fmt::Debug for EntityUpdateStreaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityUpdateStreaming").finish_non_exhaustive()
+    }
+}
 
-#[derive(Debug)]
-pub struct EventUpdateStreaming(EventMappedStream);
+pub struct EventUpdateStreaming(BoxStream<Event>);
 
 impl Stream for EventUpdateStreaming {
-    type Item = <EventMappedStream as Stream>::Item;
+    type Item = Result<Event, tonic::Status>;
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
@@ -278,6 +531,211 @@ impl Stream for EventUpdateStreaming {
     }
 }
 
+impl std::fmt::Debug for EventUpdateStreaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventUpdateStreaming").finish_non_exhaustive()
+    }
+}
+
+/// A subscription response, carrying the event id to resume the subscription from.
+trait Resumable {
+    fn event_id(&self) -> &str;
+}
+
+impl Resumable for SubscribeEntityResponse {
+    fn event_id(&self) -> &str {
+        &self.event_id
+    }
+}
+
+impl Resumable for SubscribeEventsResponse {
+    fn event_id(&self) -> &str {
+        &self.event_id
+    }
+}
+
+impl Resumable for SubscribeModelsResponse {
+    fn event_id(&self) -> &str {
+        ""
+    }
+}
+
+impl Resumable for SubscribeModelRegistrationsResponse {
+    fn event_id(&self) -> &str {
+        ""
+    }
+}
+
+/// The position of a subscription in the updates of the server.
+#[derive(Debug, Default)]
+struct Cursor {
+    /// Event id of the latest update received.
+    event_id: String,
+    /// Event ids received since the block of the latest update, the server replaying this whole
+    /// block when the subscription is resumed.
+    received: HashSet<String>,
+}
+
+impl Cursor {
+    // Records the event id of an update. Returns false if the update was already received.
+    fn advance(&mut self, event_id: &str) -> bool {
+        if event_id.is_empty() {
+            return true;
+        }
+
+        if !self.received.insert(event_id.to_string()) {
+            return false;
+        }
+
+        // event ids are prefixed by their zero padded block number
+        if event_id > self.event_id.as_str() {
+            let block = |id: &str| id.split(':').next().unwrap_or_default().to_string();
+            let new_block = block(event_id);
+            if new_block != block(&self.event_id) {
+                self.received.retain(|id| block(id) >= new_block);
+            }
+
+            self.event_id = event_id.to_string();
+        }
+
+        true
+    }
+}
+
+struct Resume<S, R> {
+    subscribe: S,
+    config: ReconnectConfig,
+    stream: Option<tonic::Streaming<R>>,
+    cursor: Cursor,
+    done: bool,
+}
+
+// Subscribes and returns a stream of the responses, which resubscribes from the cursor with a
+// backoff when the stream fails or ends.
+async fn resumable<S, F, R>(
+    mut subscribe: S,
+    config: ReconnectConfig,
+) -> Result<impl Stream<Item = Result<R, tonic::Status>>, tonic::Status>
+where
+    S: FnMut(String) -> F,
+    F: Future<Output = Result<tonic::Streaming<R>, tonic::Status>>,
+    R: Resumable,
+{
+    let stream = subscribe(String::new()).await?;
+    let state =
+        Resume { subscribe, config, stream: Some(stream), cursor: Cursor::default(), done: false };
+
+    Ok(stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+
+        loop {
+            if let Some(stream) = &mut state.stream {
+                match stream.next().await {
+                    Some(Ok(res)) => {
+                        if state.cursor.advance(res.event_id()) {
+                            return Some((Ok(res), state));
+                        }
+                        continue;
+                    }
+                    Some(Err(status)) => {
+                        warn!(target: LOG_TARGET, status = %status, "Subscription stream failed.");
+                    }
+                    None => {
+                        warn!(target: LOG_TARGET, "Subscription stream ended.");
+                    }
+                }
+            }
+            state.stream = None;
+
+            let mut attempt = 0;
+            while state.stream.is_none() {
+                sleep(state.config.backoff(attempt)).await;
+
+                match (state.subscribe)(state.cursor.event_id.clone()).await {
+                    Ok(stream) => state.stream = Some(stream),
+                    Err(status) => {
+                        attempt += 1;
+                        warn!(target: LOG_TARGET, attempt, status = %status, "Resubscribing.");
+
+                        if state.config.max_retries.is_some_and(|max| attempt >= max) {
+                            state.done = true;
+                            return Some((Err(status), state));
+                        }
+                    }
+                }
+            }
+        }
+    }))
+}
+
+// Maps the entity responses, registering the subscription by id so that its updates change the
+// filters it's resumed with.
+fn entity_updates(
+    stream: impl Stream<Item = Result<SubscribeEntityResponse, tonic::Status>>,
+    subscription: Arc<Mutex<EntitySubscription>>,
+    subscriptions: Subscriptions,
+) -> impl Stream<Item = Result<(SubscriptionId, EntityUpdate), tonic::Status>> {
+    let registration = Registration { subscription, subscriptions };
+    stream.map_ok(move |res| {
+        registration.register(res.subscription_id);
+
+        let update = match res.entity {
+            Some(entity) if res.update_type() == EntityUpdateType::Deleted => {
+                EntityUpdate::Deleted(Felt::from_bytes_be_slice(&entity.hashed_keys))
+            }
+            Some(entity) if res.update_type() == EntityUpdateType::Unmatched => {
+                EntityUpdate::Unmatched(Felt::from_bytes_be_slice(&entity.hashed_keys))
+            }
+            Some(entity) => {
+                EntityUpdate::Updated(entity.try_into().expect("must able to serialize"))
+            }
+            None => EntityUpdate::Updated(Entity { hashed_keys: Felt::ZERO, models: vec![] }),
+        };
+
+        (res.subscription_id, update)
+    })
+}
+
+// The registration of a subscription by id, removed when its stream is dropped.
+struct Registration {
+    subscription: Arc<Mutex<EntitySubscription>>,
+    subscriptions: Subscriptions,
+}
+
+impl Registration {
+    fn register(&self, id: SubscriptionId) {
+        let mut subscription = self.subscription.lock();
+        if subscription.id == id {
+            return;
+        }
+
+        // the id changes if the server still had the subscription in use when it was resumed
+        let mut subscriptions = self.subscriptions.lock();
+        subscriptions.remove(&subscription.id);
+        subscriptions.insert(id, Arc::clone(&self.subscription));
+        subscription.id = id;
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let id = self.subscription.lock().id;
+        self.subscriptions.lock().remove(&id);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    let _ = wasm_timer::Delay::new(duration).await;
+}
+
 fn empty_state_update() -> StateUpdate {
     StateUpdate {
         block_hash: Felt::ZERO,
@@ -293,3 +751,34 @@ fn empty_state_update() -> StateUpdate {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_skips_replayed_updates() {
+        let event_id = |block: u64, tx: u64| format!("{:#064x}:{:#x}:{:#04x}", block, tx, 0);
+        let mut cursor = Cursor::default();
+
+        assert!(cursor.advance(""));
+        assert!(cursor.advance(&event_id(1, 0xb)));
+        assert!(cursor.advance(&event_id(1, 0xa)));
+        assert_eq!(cursor.event_id, event_id(1, 0xb));
+
+        // the block of the cursor is replayed on reconnection
+        assert!(!cursor.advance(&event_id(1, 0xa)));
+        assert!(!cursor.advance(&event_id(1, 0xb)));
+        assert!(cursor.advance(&event_id(2, 0xa)));
+        assert_eq!(cursor.received.len(), 1);
+        assert_eq!(cursor.event_id, event_id(2, 0xa));
+    }
+
+    #[test]
+    fn reconnect_backoff() {
+        let config = ReconnectConfig::default();
+        assert_eq!(config.backoff(0), Duration::from_millis(500));
+        assert_eq!(config.backoff(2), Duration::from_secs(2));
+        assert_eq!(config.backoff(100), config.max_backoff);
+    }
+}
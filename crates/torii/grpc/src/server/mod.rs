@@ -1,5 +1,6 @@
 pub mod logger;
 pub mod subscriptions;
+pub mod websocket;
 
 #[cfg(test)]
 mod tests;
@@ -11,14 +12,19 @@ use std::pin::Pin;
 use std::str;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use dojo_types::primitive::{Primitive, PrimitiveError};
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use dojo_types::primitive::{Primitive, PrimitiveError, SqlType};
 use dojo_types::schema::Ty;
 use dojo_world::contracts::naming::compute_selector_from_names;
 use futures::Stream;
+use hyper::http::{HeaderName, HeaderValue, Method};
 use proto::world::{
-    MetadataRequest, MetadataResponse, RetrieveEntitiesRequest, RetrieveEntitiesResponse,
-    RetrieveEventsRequest, RetrieveEventsResponse, SubscribeModelsRequest, SubscribeModelsResponse,
+    MetadataRequest, MetadataResponse, RetrieveAggregatesRequest, RetrieveAggregatesResponse,
+    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEventsRequest,
+    RetrieveEventsResponse, SubscribeModelsRequest, SubscribeModelsResponse,
     UpdateEntitiesSubscriptionRequest,
 };
 use sqlx::prelude::FromRow;
@@ -27,35 +33,52 @@ use sqlx::{Pool, Row, Sqlite};
 use starknet::core::types::Felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
-use subscriptions::event::EventManager;
+use subscriptions::event::{EventManager, Replay as EventReplay};
 use tokio::net::TcpListener;
 use tokio::sync::mpsc::Receiver;
 use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::codec::CompressionEncoding;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
+use tonic_web::GrpcWebLayer;
 use torii_core::cache::ModelCache;
 use torii_core::error::{Error, ParseError, QueryError};
 use torii_core::model::{build_sql_query, map_row_to_ty};
+use torii_core::search::{search_query, SEARCH_TABLE};
+use tower::util::option_layer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
-use self::subscriptions::entity::EntityManager;
-use self::subscriptions::event_message::EventMessageManager;
+use self::subscriptions::entity::{
+    entity_with_models, EntityManager, EntityTables, Replay, SubscriptionClause, ENTITY_TABLES,
+};
+use self::subscriptions::event_message::{EventMessageManager, EVENT_MESSAGE_TABLES};
 use self::subscriptions::model_diff::{ModelDiffRequest, StateDiffManager};
+use self::subscriptions::model_registration::ModelRegistrationManager;
+use crate::proto::types::aggregate_value::Value as AggregateValueType;
 use crate::proto::types::clause::ClauseType;
 use crate::proto::world::world_server::WorldServer;
 use crate::proto::world::{
-    SubscribeEntitiesRequest, SubscribeEntityResponse, SubscribeEventsResponse,
+    SubscribeEntitiesRequest, SubscribeEntityResponse, SubscribeEventsRequest,
+    SubscribeEventsResponse, SubscribeModelRegistrationsRequest,
+    SubscribeModelRegistrationsResponse,
 };
 use crate::proto::{self};
 use crate::types::schema::SchemaError;
 use crate::types::ComparisonOperator;
 
-pub(crate) static ENTITIES_TABLE: &str = "entities";
-pub(crate) static ENTITIES_MODEL_RELATION_TABLE: &str = "entity_model";
-pub(crate) static ENTITIES_ENTITY_RELATION_COLUMN: &str = "entity_id";
+const GRPC_WEB_ALLOW_HEADERS: [&str; 4] =
+    ["x-grpc-web", "content-type", "x-user-agent", "grpc-timeout"];
+const GRPC_WEB_EXPOSED_HEADERS: [&str; 3] =
+    ["grpc-status", "grpc-message", "grpc-status-details-bin"];
+
+pub(crate) const ENTITIES_TABLE: &str = "entities";
+pub(crate) const ENTITIES_MODEL_RELATION_TABLE: &str = "entity_model";
+pub(crate) const ENTITIES_ENTITY_RELATION_COLUMN: &str = "entity_id";
+pub(crate) const ENTITIES_TOMBSTONES_TABLE: &str = "entity_tombstones";
 
-pub(crate) static EVENT_MESSAGES_TABLE: &str = "event_messages";
-pub(crate) static EVENT_MESSAGES_MODEL_RELATION_TABLE: &str = "event_model";
-pub(crate) static EVENT_MESSAGES_ENTITY_RELATION_COLUMN: &str = "event_message_id";
+pub(crate) const EVENT_MESSAGES_TABLE: &str = "event_messages";
+pub(crate) const EVENT_MESSAGES_MODEL_RELATION_TABLE: &str = "event_model";
+pub(crate) const EVENT_MESSAGES_ENTITY_RELATION_COLUMN: &str = "event_message_id";
 
 impl From<SchemaError> for Error {
     fn from(err: SchemaError) -> Self {
@@ -81,6 +104,7 @@ pub struct DojoWorld {
     event_message_manager: Arc<EventMessageManager>,
     event_manager: Arc<EventManager>,
     state_diff_manager: Arc<StateDiffManager>,
+    model_registration_manager: Arc<ModelRegistrationManager>,
 }
 
 impl DojoWorld {
@@ -95,6 +119,7 @@ impl DojoWorld {
         let event_message_manager = Arc::new(EventMessageManager::default());
         let event_manager = Arc::new(EventManager::default());
         let state_diff_manager = Arc::new(StateDiffManager::default());
+        let model_registration_manager = Arc::new(ModelRegistrationManager::default());
 
         tokio::task::spawn(subscriptions::model_diff::Service::new_with_block_rcv(
             block_rx,
@@ -117,6 +142,12 @@ impl DojoWorld {
 
         tokio::task::spawn(subscriptions::event::Service::new(Arc::clone(&event_manager)));
 
+        tokio::task::spawn(subscriptions::model_registration::Service::new(
+            pool.clone(),
+            Arc::clone(&model_registration_manager),
+            Arc::clone(&model_cache),
+        ));
+
         Self {
             pool,
             world_address,
@@ -125,6 +156,7 @@ impl DojoWorld {
             event_message_manager,
             event_manager,
             state_diff_manager,
+            model_registration_manager,
         }
     }
 }
@@ -210,20 +242,6 @@ impl DojoWorld {
         .await
     }
 
-    async fn events_all(&self, limit: u32, offset: u32) -> Result<Vec<proto::types::Event>, Error> {
-        let query = r#"
-            SELECT keys, data, transaction_hash
-            FROM events
-            ORDER BY id DESC
-            LIMIT ? OFFSET ?
-         "#
-        .to_string();
-
-        let row_events: Vec<(String, String, String)> =
-            sqlx::query_as(&query).bind(limit).bind(offset).fetch_all(&self.pool).await?;
-        row_events.iter().map(map_row_to_event).collect()
-    }
-
     pub(crate) async fn query_by_hashed_keys(
         &self,
         table: &str,
@@ -458,31 +476,11 @@ impl DojoWorld {
         Ok((entities, total_count))
     }
 
-    pub(crate) async fn events_by_keys(
-        &self,
-        keys_clause: &proto::types::KeysClause,
-        limit: Option<u32>,
-        offset: Option<u32>,
-    ) -> Result<Vec<proto::types::Event>, Error> {
-        let keys_pattern = build_keys_pattern(keys_clause)?;
-
-        let events_query = r#"
-            SELECT keys, data, transaction_hash
-            FROM events
-            WHERE keys REGEXP ?
-            ORDER BY id DESC
-            LIMIT ? OFFSET ?
-        "#
-        .to_string();
+    async fn model_schema(&self, model: &str) -> Result<Ty, Error> {
+        let (namespace, name) =
+            model.split_once('-').ok_or(QueryError::InvalidNamespacedModel(model.to_string()))?;
 
-        let row_events: Vec<(String, String, String)> = sqlx::query_as(&events_query)
-            .bind(&keys_pattern)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-        row_events.iter().map(map_row_to_event).collect()
+        Ok(self.model_cache.schema(&compute_selector_from_names(namespace, name)).await?)
     }
 
     pub(crate) async fn query_by_member(
@@ -490,22 +488,16 @@ impl DojoWorld {
         table: &str,
         model_relation_table: &str,
         entity_relation_column: &str,
-        member_clause: proto::types::MemberClause,
+        clause_type: ClauseType,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
-        let comparison_operator = ComparisonOperator::from_repr(member_clause.operator as usize)
-            .expect("invalid comparison operator");
-
-        let primitive: Primitive =
-            member_clause.value.ok_or(QueryError::MissingParam("value".into()))?.try_into()?;
-
-        let comparison_value = primitive.to_sql_value()?;
+        let model = clause_model(&clause_type)?;
+        let (namespace, model_name) =
+            model.split_once('-').ok_or(QueryError::InvalidNamespacedModel(model.to_string()))?;
 
-        let (namespace, model) = member_clause
-            .model
-            .split_once('-')
-            .ok_or(QueryError::InvalidNamespacedModel(member_clause.model.clone()))?;
+        let schema = self.model_schema(model).await?;
+        let (condition, bind_values) = model_clause_condition(model, &schema, &clause_type)?;
 
         let models_query = format!(
             r#"
@@ -516,7 +508,7 @@ impl DojoWorld {
             HAVING INSTR(model_ids, '{:#x}') > 0
             LIMIT 1
         "#,
-            compute_selector_from_names(namespace, model)
+            compute_selector_from_names(namespace, model_name)
         );
         let (models_str,): (String,) = sqlx::query_as(&models_query).fetch_one(&self.pool).await?;
 
@@ -527,33 +519,35 @@ impl DojoWorld {
             .map_err(ParseError::FromStr)?;
         let schemas = self.model_cache.schemas(&model_ids).await?;
 
-        let table_name = member_clause.model;
-        let column_name = format!("external_{}", member_clause.member);
         let (entity_query, arrays_queries, count_query) = build_sql_query(
             &schemas,
             table,
             entity_relation_column,
-            Some(&format!("[{table_name}].{column_name} {comparison_operator} ?")),
+            Some(&condition),
             None,
             limit,
             offset,
         )?;
 
-        let total_count = sqlx::query_scalar(&count_query)
-            .bind(comparison_value.clone())
-            .fetch_one(&self.pool)
-            .await?;
+        let mut count_query = sqlx::query_scalar(&count_query);
+        for value in &bind_values {
+            count_query = count_query.bind(value);
+        }
+        let total_count = count_query.fetch_one(&self.pool).await?;
+
+        let mut entity_query = sqlx::query(&entity_query);
+        for value in &bind_values {
+            entity_query = entity_query.bind(value);
+        }
+        let db_entities = entity_query.bind(limit).bind(offset).fetch_all(&self.pool).await?;
 
-        let db_entities = sqlx::query(&entity_query)
-            .bind(comparison_value.clone())
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
         let mut arrays_rows = HashMap::new();
         for (name, query) in arrays_queries {
-            let rows =
-                sqlx::query(&query).bind(comparison_value.clone()).fetch_all(&self.pool).await?;
+            let mut query = sqlx::query(&query);
+            for value in &bind_values {
+                query = query.bind(value);
+            }
+            let rows = query.fetch_all(&self.pool).await?;
             arrays_rows.insert(name, rows);
         }
 
@@ -564,6 +558,90 @@ impl DojoWorld {
         Ok((entities_collection, total_count))
     }
 
+    pub(crate) async fn query_by_search(
+        &self,
+        table: &str,
+        model_relation_table: &str,
+        entity_relation_column: &str,
+        search: proto::types::SearchClause,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
+        let Some(search_query) = search_query(&search.text) else {
+            return Ok((Vec::new(), 0));
+        };
+
+        let count_query = format!(
+            r#"
+            SELECT COUNT(*)
+            FROM {table}
+            WHERE {table}.id IN (SELECT id FROM {SEARCH_TABLE} WHERE {SEARCH_TABLE} MATCH ?)
+            "#
+        );
+        let total_count: u32 =
+            sqlx::query_scalar(&count_query).bind(&search_query).fetch_one(&self.pool).await?;
+
+        if total_count == 0 {
+            return Ok((Vec::new(), 0));
+        }
+
+        // entities are ranked by their best matching member
+        let query = format!(
+            r#"
+            SELECT {table}.id, group_concat({model_relation_table}.model_id) as model_ids
+            FROM {table}
+            JOIN (
+                SELECT id, MIN(rank) AS rank
+                FROM {SEARCH_TABLE}
+                WHERE {SEARCH_TABLE} MATCH ?
+                GROUP BY id
+            ) AS search ON search.id = {table}.id
+            JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
+            GROUP BY {table}.id
+            ORDER BY MIN(search.rank)
+            LIMIT ? OFFSET ?
+            "#
+        );
+
+        let db_entities: Vec<(String, String)> = sqlx::query_as(&query)
+            .bind(&search_query)
+            .bind(limit.unwrap_or(u32::MAX))
+            .bind(offset.unwrap_or(0))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut entities = Vec::with_capacity(db_entities.len());
+        for (entity_id, models_str) in &db_entities {
+            let model_ids: Vec<Felt> = models_str
+                .split(',')
+                .map(Felt::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(ParseError::FromStr)?;
+            let schemas = self.model_cache.schemas(&model_ids).await?;
+
+            let (entity_query, arrays_queries, _) = build_sql_query(
+                &schemas,
+                table,
+                entity_relation_column,
+                Some(&format!("[{table}].id = ?")),
+                Some(&format!("[{table}].id = ?")),
+                None,
+                None,
+            )?;
+
+            let row = sqlx::query(&entity_query).bind(entity_id).fetch_one(&self.pool).await?;
+            let mut arrays_rows = HashMap::new();
+            for (name, query) in arrays_queries {
+                let rows = sqlx::query(&query).bind(entity_id).fetch_all(&self.pool).await?;
+                arrays_rows.insert(name, rows);
+            }
+
+            entities.push(map_row_to_entity(&row, &arrays_rows, schemas.clone())?);
+        }
+
+        Ok((entities, total_count))
+    }
+
     async fn query_by_composite(
         &self,
         table: &str,
@@ -575,8 +653,7 @@ impl DojoWorld {
     ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
         // different types of clauses
         let mut where_clauses = Vec::new();
-        let mut model_clauses: HashMap<String, Vec<(String, ComparisonOperator, String)>> =
-            HashMap::new();
+        let mut model_clauses: HashMap<String, Vec<(String, Vec<String>)>> = HashMap::new();
         let mut having_clauses = Vec::new();
 
         // bind valeus for prepared statement
@@ -598,27 +675,20 @@ impl DojoWorld {
                     let keys_pattern = build_keys_pattern(&keys)?;
                     where_clauses.push(format!("{table}.keys REGEXP '{keys_pattern}'"));
                 }
-                ClauseType::Member(member) => {
-                    let comparison_operator =
-                        ComparisonOperator::from_repr(member.operator as usize)
-                            .expect("invalid comparison operator");
-                    let value: Primitive = member.value.unwrap().try_into()?;
-                    let comparison_value = value.to_sql_value()?;
-
-                    let column_name = format!("external_{}", member.member);
-
-                    model_clauses.entry(member.model.clone()).or_default().push((
-                        column_name,
-                        comparison_operator,
-                        comparison_value,
-                    ));
-
-                    let (namespace, model) = member
-                        .model
+                clause_type @ (ClauseType::Member(_)
+                | ClauseType::Enum(_)
+                | ClauseType::Array(_)) => {
+                    let model = clause_model(&clause_type)?.to_string();
+                    let schema = self.model_schema(&model).await?;
+                    let condition = model_clause_condition(&model, &schema, &clause_type)?;
+
+                    let (namespace, name) = model
                         .split_once('-')
-                        .ok_or(QueryError::InvalidNamespacedModel(member.model.clone()))?;
-                    let model_id: Felt = compute_selector_from_names(namespace, model);
+                        .ok_or(QueryError::InvalidNamespacedModel(model.clone()))?;
+                    let model_id: Felt = compute_selector_from_names(namespace, name);
                     having_clauses.push(format!("INSTR(model_ids, '{:#x}') > 0", model_id));
+
+                    model_clauses.entry(model).or_default().push(condition);
                 }
                 _ => return Err(QueryError::UnsupportedQuery.into()),
             }
@@ -628,9 +698,9 @@ impl DojoWorld {
         for (model, clauses) in model_clauses {
             let model_conditions = clauses
                 .into_iter()
-                .map(|(column, op, value)| {
-                    bind_values.push(value);
-                    format!("[{}].{} {} ?", model, column, op)
+                .map(|(condition, values)| {
+                    bind_values.extend(values);
+                    condition
                 })
                 .collect::<Vec<_>>()
                 .join(" AND ");
@@ -727,42 +797,151 @@ impl DojoWorld {
         Ok((entities, total_count))
     }
 
+    // Pages through the entities matching the clause, ordered by their id, their last update or a
+    // member of a model. Unlike offsets, the cursor on the ordered value and the entity id keeps
+    // pages consistent while entities are written.
+    async fn query_paginated(
+        &self,
+        tables: EntityTables,
+        query: proto::types::Query,
+    ) -> Result<RetrieveEntitiesResponse, Error> {
+        let EntityTables { table, model_relation_table, entity_relation_column, .. } = tables;
+
+        let (condition, mut bind_values) =
+            match self.subscription_clause(tables, query.clause).await? {
+                Some(SubscriptionClause { condition, bind_values, .. }) => (condition, bind_values),
+                None => ("1 = 1".to_string(), Vec::new()),
+            };
+
+        let order_by = query.order_by.unwrap_or_default();
+        let order = match (order_by.model.as_str(), order_by.member.as_str()) {
+            ("", "") => None,
+            ("", "updated_at") => Some(OrderColumn {
+                join: String::new(),
+                expr: format!("[{table}].updated_at"),
+                placeholder: "?".to_string(),
+            }),
+            ("", member) => return Err(QueryError::MemberNotFound(member.to_string()).into()),
+            (model, member) => {
+                let schema = self.model_schema(model).await?;
+                Some(order_column(model, &schema, member, table, entity_relation_column)?)
+            }
+        };
+        let (direction, comparison) = match order_by.direction() {
+            proto::types::OrderDirection::Desc => ("DESC", "<"),
+            proto::types::OrderDirection::Asc => ("ASC", ">"),
+        };
+        let order_join = order.as_ref().map(|order| order.join.as_str()).unwrap_or_default();
+
+        // entities without the model of the ordered member are left out
+        let count_query = format!(
+            r#"
+            SELECT COUNT(DISTINCT [{table}].id)
+            FROM [{table}]
+            {order_join}
+            WHERE {condition}
+            "#
+        );
+        let mut count_query = sqlx::query_scalar::<_, u32>(&count_query);
+        for value in &bind_values {
+            count_query = count_query.bind(value);
+        }
+        let total_count = count_query.fetch_one(&self.pool).await?;
+
+        // sqlite sorts nulls first, so entities without an ordered value come before the others
+        // in ascending order and after them in descending order
+        let cursor_condition = match (query.cursor.is_empty(), &order) {
+            (true, _) => String::new(),
+            (false, None) => {
+                let (id, _) = decode_cursor(&query.cursor)?;
+                bind_values.push(id);
+                format!("AND [{table}].id {comparison} ?")
+            }
+            (false, Some(OrderColumn { expr, placeholder, .. })) => {
+                match decode_cursor(&query.cursor)? {
+                    (id, Some(value)) => {
+                        bind_values.extend([value.clone(), value, id]);
+                        let nulls = if direction == "DESC" {
+                            format!(" OR {expr} IS NULL")
+                        } else {
+                            String::new()
+                        };
+                        format!(
+                            "AND ({expr} {comparison} {placeholder} OR ({expr} = {placeholder} \
+                             AND [{table}].id {comparison} ?){nulls})"
+                        )
+                    }
+                    (id, None) => {
+                        bind_values.push(id);
+                        let values = if direction == "ASC" {
+                            format!(" OR {expr} IS NOT NULL")
+                        } else {
+                            String::new()
+                        };
+                        format!("AND (({expr} IS NULL AND [{table}].id {comparison} ?){values})")
+                    }
+                }
+            }
+        };
+
+        let (order_value, order_clause) = match &order {
+            Some(OrderColumn { expr, .. }) => (
+                format!("CAST({expr} AS TEXT)"),
+                format!("{expr} {direction}, [{table}].id {direction}"),
+            ),
+            None => ("NULL".to_string(), format!("[{table}].id {direction}")),
+        };
+        let query_str = format!(
+            r#"
+            SELECT [{table}].id, group_concat({model_relation_table}.model_id) as model_ids,
+                {order_value} as order_value
+            FROM [{table}]
+            JOIN {model_relation_table} ON [{table}].id = {model_relation_table}.entity_id
+            {order_join}
+            WHERE {condition} {cursor_condition}
+            GROUP BY [{table}].id
+            ORDER BY {order_clause}
+            LIMIT ? OFFSET ?
+            "#
+        );
+
+        let mut db_query = sqlx::query_as(&query_str);
+        for value in &bind_values {
+            db_query = db_query.bind(value);
+        }
+        // one more entity than the limit tells whether there's a next page
+        let mut db_entities: Vec<(String, String, Option<String>)> = db_query
+            .bind(query.limit.saturating_add(1))
+            .bind(query.offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_cursor = next_page_cursor(&mut db_entities, query.limit, |(id, _, value)| {
+            encode_cursor(id, value.as_deref())
+        });
+
+        let mut entities = Vec::with_capacity(db_entities.len());
+        for (entity_id, models_str, _) in &db_entities {
+            let model_ids: Vec<Felt> = models_str
+                .split(',')
+                .map(Felt::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(ParseError::FromStr)?;
+            let schemas = self.model_cache.schemas(&model_ids).await?;
+
+            entities.push(entity_with_models(&self.pool, tables, entity_id, schemas).await?);
+        }
+
+        Ok(RetrieveEntitiesResponse { entities, total_count, next_cursor })
+    }
+
     pub async fn model_metadata(
         &self,
         namespace: &str,
         name: &str,
     ) -> Result<proto::types::ModelMetadata, Error> {
-        // selector
         let model = compute_selector_from_names(namespace, name);
-
-        let (name, class_hash, contract_address, packed_size, unpacked_size, layout): (
-            String,
-            String,
-            String,
-            u32,
-            u32,
-            String,
-        ) = sqlx::query_as(
-            "SELECT namespace, name, class_hash, contract_address, packed_size, unpacked_size, \
-             layout FROM models WHERE id = ?",
-        )
-        .bind(format!("{:#x}", model))
-        .fetch_one(&self.pool)
-        .await?;
-
-        let schema = self.model_cache.schema(&model).await?;
-        let layout = layout.as_bytes().to_vec();
-
-        Ok(proto::types::ModelMetadata {
-            namespace: namespace.to_string(),
-            name,
-            layout,
-            class_hash,
-            contract_address,
-            packed_size,
-            unpacked_size,
-            schema: serde_json::to_vec(&schema).unwrap(),
-        })
+        model_metadata(&self.pool, &self.model_cache, model).await
     }
 
     async fn subscribe_models(
@@ -793,17 +972,93 @@ impl DojoWorld {
         self.state_diff_manager.add_subscriber(subs).await
     }
 
+    async fn subscribe_model_registrations(
+        &self,
+        namespaces: Vec<String>,
+    ) -> Result<
+        Receiver<Result<proto::world::SubscribeModelRegistrationsResponse, tonic::Status>>,
+        Error,
+    > {
+        self.model_registration_manager.add_subscriber(namespaces).await
+    }
+
     async fn subscribe_entities(
         &self,
         keys: Vec<proto::types::EntityKeysClause>,
+        clause: Option<proto::types::Clause>,
+        subscription_id: u64,
+        cursor: String,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
-        self.entity_manager.add_subscriber(keys.into_iter().map(|keys| keys.into()).collect()).await
+        let clause = self.subscriber_clause(ENTITY_TABLES, clause).await?;
+        let replay = self.replay(ENTITY_TABLES, cursor);
+        self.entity_manager
+            .add_subscriber(
+                keys.into_iter().map(|keys| keys.into()).collect(),
+                clause,
+                (subscription_id != 0).then_some(subscription_id),
+                replay,
+            )
+            .await
+    }
+
+    // Compiles the clause of a subscription to a condition on the entities of the tables.
+    async fn subscription_clause(
+        &self,
+        tables: EntityTables,
+        clause: Option<proto::types::Clause>,
+    ) -> Result<Option<SubscriptionClause>, Error> {
+        let Some(clause) = clause else {
+            return Ok(None);
+        };
+
+        let mut models = Vec::new();
+        clause_models(&clause, &mut models)?;
+
+        let mut schemas = HashMap::new();
+        for model in models {
+            let schema = self.model_schema(&model).await?;
+            schemas.insert(model, schema);
+        }
+
+        let mut bind_values = Vec::new();
+        let condition = build_subscription_condition(tables, &schemas, clause, &mut bind_values)?;
+
+        Ok(Some(SubscriptionClause::new(condition, bind_values)))
+    }
+
+    // Compiles the clause of a subscriber, recording the entities it currently matches so that
+    // the subscriber is notified when they no longer match it.
+    async fn subscriber_clause(
+        &self,
+        tables: EntityTables,
+        clause: Option<proto::types::Clause>,
+    ) -> Result<Option<SubscriptionClause>, Error> {
+        let clause = self.subscription_clause(tables, clause).await?;
+        if let Some(clause) = &clause {
+            clause.match_current(&self.pool, tables).await?;
+        }
+
+        Ok(clause)
+    }
+
+    // The replay of the updates since the cursor of a resumed subscription.
+    fn replay(&self, tables: EntityTables, cursor: String) -> Option<Replay> {
+        (!cursor.is_empty()).then(|| Replay {
+            pool: self.pool.clone(),
+            cache: Arc::clone(&self.model_cache),
+            tables,
+            cursor,
+        })
     }
 
     async fn retrieve_entities(
         &self,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
+        if !query.cursor.is_empty() || query.order_by.is_some() {
+            return self.query_paginated(ENTITY_TABLES, query).await;
+        }
+
         let (entities, total_count) = match query.clause {
             None => self.entities_all(query.limit, query.offset).await?,
             Some(clause) => {
@@ -841,12 +1096,25 @@ impl DojoWorld {
                         )
                         .await?
                     }
-                    ClauseType::Member(member) => {
+                    clause_type @ (ClauseType::Member(_)
+                    | ClauseType::Enum(_)
+                    | ClauseType::Array(_)) => {
                         self.query_by_member(
                             ENTITIES_TABLE,
                             ENTITIES_MODEL_RELATION_TABLE,
                             ENTITIES_ENTITY_RELATION_COLUMN,
-                            member,
+                            clause_type,
+                            Some(query.limit),
+                            Some(query.offset),
+                        )
+                        .await?
+                    }
+                    ClauseType::Search(search) => {
+                        self.query_by_search(
+                            ENTITIES_TABLE,
+                            ENTITIES_MODEL_RELATION_TABLE,
+                            ENTITIES_ENTITY_RELATION_COLUMN,
+                            search,
                             Some(query.limit),
                             Some(query.offset),
                         )
@@ -867,15 +1135,25 @@ impl DojoWorld {
             }
         };
 
-        Ok(RetrieveEntitiesResponse { entities, total_count })
+        Ok(RetrieveEntitiesResponse { entities, total_count, next_cursor: String::new() })
     }
 
     async fn subscribe_event_messages(
         &self,
         clauses: Vec<proto::types::EntityKeysClause>,
+        clause: Option<proto::types::Clause>,
+        subscription_id: u64,
+        cursor: String,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
+        let clause = self.subscriber_clause(EVENT_MESSAGE_TABLES, clause).await?;
+        let replay = self.replay(EVENT_MESSAGE_TABLES, cursor);
         self.event_message_manager
-            .add_subscriber(clauses.into_iter().map(|keys| keys.into()).collect())
+            .add_subscriber(
+                clauses.into_iter().map(|keys| keys.into()).collect(),
+                clause,
+                (subscription_id != 0).then_some(subscription_id),
+                replay,
+            )
             .await
     }
 
@@ -883,6 +1161,10 @@ impl DojoWorld {
         &self,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
+        if !query.cursor.is_empty() || query.order_by.is_some() {
+            return self.query_paginated(EVENT_MESSAGE_TABLES, query).await;
+        }
+
         let (entities, total_count) = match query.clause {
             None => self.event_messages_all(query.limit, query.offset).await?,
             Some(clause) => {
@@ -920,17 +1202,21 @@ impl DojoWorld {
                         )
                         .await?
                     }
-                    ClauseType::Member(member) => {
+                    clause_type @ (ClauseType::Member(_)
+                    | ClauseType::Enum(_)
+                    | ClauseType::Array(_)) => {
                         self.query_by_member(
                             EVENT_MESSAGES_TABLE,
                             EVENT_MESSAGES_MODEL_RELATION_TABLE,
                             EVENT_MESSAGES_ENTITY_RELATION_COLUMN,
-                            member,
+                            clause_type,
                             Some(query.limit),
                             Some(query.offset),
                         )
                         .await?
                     }
+                    // event messages aren't indexed for full-text search
+                    ClauseType::Search(_) => return Err(QueryError::UnsupportedQuery.into()),
                     ClauseType::Composite(composite) => {
                         self.query_by_composite(
                             EVENT_MESSAGES_TABLE,
@@ -946,28 +1232,273 @@ impl DojoWorld {
             }
         };
 
-        Ok(RetrieveEntitiesResponse { entities, total_count })
+        Ok(RetrieveEntitiesResponse { entities, total_count, next_cursor: String::new() })
     }
 
+    // Events are paged from the most recent one, the cursor being the id of the last event of the
+    // previous page.
     async fn retrieve_events(
         &self,
         query: &proto::types::EventQuery,
     ) -> Result<proto::world::RetrieveEventsResponse, Error> {
-        let events = match &query.keys {
-            None => self.events_all(query.limit, query.offset).await?,
-            Some(keys) => self.events_by_keys(keys, Some(query.limit), Some(query.offset)).await?,
+        let mut conditions = Vec::new();
+        let mut bind_values = Vec::new();
+
+        if let Some(keys_clause) = &query.keys {
+            conditions.push("keys REGEXP ?");
+            bind_values.push(build_keys_pattern(keys_clause)?);
+        }
+
+        if !query.cursor.is_empty() {
+            let (id, _) = decode_cursor(&query.cursor)?;
+            conditions.push("id < ?");
+            bind_values.push(id);
+        }
+
+        let where_clause = if !conditions.is_empty() {
+            format!("WHERE {}", conditions.join(" AND "))
+        } else {
+            String::new()
         };
-        Ok(RetrieveEventsResponse { events })
+
+        let events_query = format!(
+            r#"
+            SELECT id, keys, data, transaction_hash
+            FROM events
+            {where_clause}
+            ORDER BY id DESC
+            LIMIT ? OFFSET ?
+        "#
+        );
+
+        let mut db_query = sqlx::query_as(&events_query);
+        for value in &bind_values {
+            db_query = db_query.bind(value);
+        }
+        // one more event than the limit tells whether there's a next page
+        let mut row_events: Vec<(String, String, String, String)> = db_query
+            .bind(query.limit.saturating_add(1))
+            .bind(query.offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_cursor =
+            next_page_cursor(&mut row_events, query.limit, |(id, ..)| encode_cursor(id, None));
+
+        let events = row_events
+            .into_iter()
+            .map(|(_, keys, data, transaction_hash)| {
+                map_row_to_event(&(keys, data, transaction_hash))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(RetrieveEventsResponse { events, next_cursor })
     }
 
     async fn subscribe_events(
         &self,
         clause: proto::types::KeysClause,
+        cursor: String,
     ) -> Result<Receiver<Result<proto::world::SubscribeEventsResponse, tonic::Status>>, Error> {
-        self.event_manager.add_subscriber(clause.into()).await
+        let replay = (!cursor.is_empty()).then(|| EventReplay { pool: self.pool.clone(), cursor });
+        self.event_manager.add_subscriber(clause.into(), replay).await
+    }
+
+    async fn retrieve_aggregates(
+        &self,
+        query: proto::types::AggregateQuery,
+    ) -> Result<proto::world::RetrieveAggregatesResponse, Error> {
+        let model_schema = self.model_schema(&query.model).await?;
+        let schema =
+            model_schema.as_struct().ok_or(QueryError::ModelNotFound(query.model.clone()))?;
+
+        let member = |name: &str| {
+            schema
+                .children
+                .iter()
+                .find(|member| member.name == name)
+                .ok_or(QueryError::MemberNotFound(name.to_string()))
+        };
+
+        // the group by members are selected as `group.{member}` and the aggregated members as
+        // `{index}.{member}`, so they can be mapped back to their type with `map_row_to_ty`
+        let mut columns = vec!["COUNT(*) AS count".to_string()];
+        let mut group_columns = Vec::with_capacity(query.group_by.len());
+        for name in &query.group_by {
+            if !is_scalar_ty(&member(name)?.ty) {
+                return Err(QueryError::UnsupportedAggregate(format!("group by {name}")).into());
+            }
+
+            columns.push(format!("[external_{name}] AS [group.{name}]"));
+            group_columns.push(format!("[external_{name}]"));
+        }
+
+        for (idx, aggregate) in query.aggregates.iter().enumerate() {
+            let function = aggregate.function();
+            let column = format!("[external_{}]", aggregate.member);
+            let alias = format!("[{idx}.{}]", aggregate.member);
+
+            let expression = match function {
+                proto::types::AggregateFunction::Count => continue,
+                proto::types::AggregateFunction::Sum | proto::types::AggregateFunction::Avg => {
+                    let is_number = matches!(
+                        &member(&aggregate.member)?.ty,
+                        Ty::Primitive(primitive) if primitive.to_sql_type() == SqlType::Integer
+                            && !matches!(primitive, Primitive::Bool(_))
+                    );
+                    if !is_number {
+                        return Err(QueryError::UnsupportedAggregate(format!(
+                            "{} of {}",
+                            function.as_str_name(),
+                            aggregate.member
+                        ))
+                        .into());
+                    }
+
+                    if function == proto::types::AggregateFunction::Sum {
+                        format!("CAST(SUM({column}) AS REAL)")
+                    } else {
+                        format!("AVG({column})")
+                    }
+                }
+                proto::types::AggregateFunction::Min | proto::types::AggregateFunction::Max => {
+                    if !is_scalar_ty(&member(&aggregate.member)?.ty) {
+                        return Err(QueryError::UnsupportedAggregate(format!(
+                            "{} of {}",
+                            function.as_str_name(),
+                            aggregate.member
+                        ))
+                        .into());
+                    }
+
+                    format!("{}({column})", function.as_str_name())
+                }
+            };
+
+            columns.push(format!("{expression} AS {alias}"));
+        }
+
+        let mut bind_values = Vec::new();
+        let where_clause = match query.clause {
+            Some(clause) => {
+                format!(
+                    "WHERE {}",
+                    build_aggregate_clause(&query.model, &model_schema, clause, &mut bind_values)?
+                )
+            }
+            None => String::new(),
+        };
+
+        let group_clause = if !group_columns.is_empty() {
+            let group_columns = group_columns.join(", ");
+            format!("GROUP BY {group_columns} ORDER BY {group_columns}")
+        } else {
+            String::new()
+        };
+
+        let aggregates_query = format!(
+            "SELECT {} FROM [{}] {where_clause} {group_clause}",
+            columns.join(", "),
+            query.model
+        );
+
+        let mut db_query = sqlx::query(&aggregates_query);
+        for value in &bind_values {
+            db_query = db_query.bind(value);
+        }
+        let rows = db_query.fetch_all(&self.pool).await?;
+
+        let arrays_rows = HashMap::new();
+        let mut groups = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let count = row.try_get::<i64, &str>("count")?;
+
+            let group = query
+                .group_by
+                .iter()
+                .map(|name| {
+                    let mut member = member(name)?.clone();
+                    map_row_to_ty("group", name, &mut member.ty, row, &arrays_rows)?;
+                    Ok(proto::types::Member::from(member))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let values = query
+                .aggregates
+                .iter()
+                .enumerate()
+                .map(|(idx, aggregate)| {
+                    let alias = format!("{idx}.{}", aggregate.member);
+                    let value = match aggregate.function() {
+                        proto::types::AggregateFunction::Count => {
+                            Some(AggregateValueType::Count(count as u64))
+                        }
+                        proto::types::AggregateFunction::Sum
+                        | proto::types::AggregateFunction::Avg => row
+                            .try_get::<Option<f64>, &str>(&alias)?
+                            .map(AggregateValueType::Number),
+                        // without group by, MIN and MAX are null if no row matches the clause
+                        _ if count == 0 => None,
+                        _ => {
+                            let mut ty = member(&aggregate.member)?.ty.clone();
+                            map_row_to_ty(
+                                &idx.to_string(),
+                                &aggregate.member,
+                                &mut ty,
+                                row,
+                                &arrays_rows,
+                            )?;
+                            Some(AggregateValueType::Ty(ty.into()))
+                        }
+                    };
+
+                    Ok(proto::types::AggregateValue { aggregate: Some(aggregate.clone()), value })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            groups.push(proto::types::AggregateGroup { group, values });
+        }
+
+        Ok(RetrieveAggregatesResponse { groups })
     }
 }
 
+pub(crate) async fn model_metadata(
+    pool: &Pool<Sqlite>,
+    model_cache: &ModelCache,
+    model: Felt,
+) -> Result<proto::types::ModelMetadata, Error> {
+    let (namespace, name, class_hash, contract_address, packed_size, unpacked_size, layout): (
+        String,
+        String,
+        String,
+        String,
+        u32,
+        u32,
+        String,
+    ) = sqlx::query_as(
+        "SELECT namespace, name, class_hash, contract_address, packed_size, unpacked_size, layout \
+         FROM models WHERE id = ?",
+    )
+    .bind(format!("{:#x}", model))
+    .fetch_one(pool)
+    .await?;
+
+    let schema = model_cache.schema(&model).await?;
+    let layout = layout.as_bytes().to_vec();
+
+    Ok(proto::types::ModelMetadata {
+        namespace,
+        name,
+        layout,
+        class_hash,
+        contract_address,
+        packed_size,
+        unpacked_size,
+        schema: serde_json::to_vec(&schema).unwrap(),
+    })
+}
+
 fn process_event_field(data: &str) -> Result<Vec<Vec<u8>>, Error> {
     Ok(data
         .trim_end_matches('/')
@@ -1002,6 +1533,410 @@ fn map_row_to_entity(
     Ok(proto::types::Entity { hashed_keys: hashed_keys.to_bytes_be().to_vec(), models })
 }
 
+// aggregates are only computed on members stored in a single column of the model table
+fn is_scalar_ty(ty: &Ty) -> bool {
+    match ty {
+        Ty::Primitive(_) | Ty::ByteArray(_) => true,
+        Ty::Enum(enum_ty) => enum_ty.options.iter().all(|option| option.ty == Ty::Tuple(vec![])),
+        _ => false,
+    }
+}
+
+// builds the where clause of an aggregate query on the table of the given model
+fn build_aggregate_clause(
+    model: &str,
+    schema: &Ty,
+    clause: proto::types::Clause,
+    bind_values: &mut Vec<String>,
+) -> Result<String, Error> {
+    match clause.clause_type.ok_or(QueryError::MissingParam("clause_type".into()))? {
+        ClauseType::HashedKeys(hashed_keys) => {
+            let ids = hashed_keys
+                .hashed_keys
+                .iter()
+                .map(|id| format!("'{:#x}'", Felt::from_bytes_be_slice(id)))
+                .collect::<Vec<_>>();
+
+            Ok(format!("entity_id IN ({})", ids.join(", ")))
+        }
+        clause_type @ (ClauseType::Member(_) | ClauseType::Enum(_) | ClauseType::Array(_)) => {
+            if clause_model(&clause_type)? != model {
+                return Err(QueryError::UnsupportedQuery.into());
+            }
+
+            let (condition, values) = model_clause_condition(model, schema, &clause_type)?;
+            bind_values.extend(values);
+
+            Ok(condition)
+        }
+        ClauseType::Composite(composite) => {
+            if composite.clauses.is_empty() {
+                return Ok("1 = 1".to_string());
+            }
+
+            let operator = match composite.operator() {
+                proto::types::LogicalOperator::And => " AND ",
+                proto::types::LogicalOperator::Or => " OR ",
+            };
+            let clauses = composite
+                .clauses
+                .into_iter()
+                .map(|clause| build_aggregate_clause(model, schema, clause, bind_values))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Ok(format!("({})", clauses.join(operator)))
+        }
+        ClauseType::Keys(_) | ClauseType::Search(_) => Err(QueryError::UnsupportedQuery.into()),
+    }
+}
+
+// a member of a model, stored in the column `external_{name}` of `table`
+struct MemberColumn {
+    table: String,
+    name: String,
+    ty: Ty,
+    // whether the member is nested in an array, a model row then has many rows in `table`
+    in_array: bool,
+}
+
+// resolves a dotted member path, eg `position.vec.x`, to the table and column storing it. tuple
+// elements are referred to by their index and enum payloads by the name of their option
+fn member_column(model: &str, schema: &Ty, path: &str) -> Result<MemberColumn, Error> {
+    let not_found = || QueryError::MemberNotFound(path.to_string());
+
+    let mut table = model.to_string();
+    let mut ty = schema.clone();
+    let mut in_array = false;
+
+    let mut segments = path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let (name, child) = match &ty {
+            Ty::Struct(s) => s
+                .children
+                .iter()
+                .find(|member| member.name == segment)
+                .map(|member| (member.name.clone(), member.ty.clone())),
+            Ty::Tuple(t) => segment
+                .trim_start_matches('_')
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| t.get(idx).map(|element| (format!("_{idx}"), element.clone()))),
+            Ty::Enum(e) => e
+                .options
+                .iter()
+                .find(|option| option.name == segment)
+                .map(|option| (option.name.clone(), option.ty.clone())),
+            _ => None,
+        }
+        .ok_or_else(not_found)?;
+
+        if segments.peek().is_none() {
+            return Ok(MemberColumn { table, name, ty: child, in_array });
+        }
+
+        table = format!("{table}${name}");
+        ty = match child {
+            // the members of the array elements are stored in the `data` table
+            Ty::Array(elements) => {
+                in_array = true;
+                table = format!("{table}$data");
+                elements[0].clone()
+            }
+            child => child,
+        };
+    }
+
+    Err(not_found().into())
+}
+
+// condition on the rows of the model table, for a predicate on the column of a member. members of
+// nested tables match if any of their rows matches the predicate
+fn member_condition(model: &str, column: &MemberColumn, predicate: &str) -> String {
+    if column.table == model {
+        format!("[{model}].external_{} {predicate}", column.name)
+    } else {
+        format!(
+            "[{model}].id IN (SELECT id FROM [{}] WHERE external_{} {predicate})",
+            column.table, column.name
+        )
+    }
+}
+
+fn clause_model(clause_type: &ClauseType) -> Result<&str, Error> {
+    match clause_type {
+        ClauseType::Member(member) => Ok(&member.model),
+        ClauseType::Enum(enum_clause) => Ok(&enum_clause.model),
+        ClauseType::Array(array) => Ok(&array.model),
+        _ => Err(QueryError::UnsupportedQuery.into()),
+    }
+}
+
+// builds the condition of a member, enum or array clause on the table of the given model, along
+// with the values to bind to it
+fn model_clause_condition(
+    model: &str,
+    schema: &Ty,
+    clause_type: &ClauseType,
+) -> Result<(String, Vec<String>), Error> {
+    match clause_type {
+        ClauseType::Member(member) => {
+            let column = member_column(model, schema, &member.member)?;
+            if matches!(column.ty, Ty::Struct(_) | Ty::Tuple(_) | Ty::Array(_)) {
+                return Err(QueryError::UnsupportedQuery.into());
+            }
+
+            let comparison_operator = ComparisonOperator::from_repr(member.operator as usize)
+                .expect("invalid comparison operator");
+            let value: Primitive =
+                member.value.clone().ok_or(QueryError::MissingParam("value".into()))?.try_into()?;
+
+            Ok((
+                member_condition(model, &column, &format!("{comparison_operator} ?")),
+                vec![value.to_sql_value()?],
+            ))
+        }
+        ClauseType::Enum(enum_clause) => {
+            let column = member_column(model, schema, &enum_clause.member)?;
+            let Ty::Enum(enum_ty) = &column.ty else {
+                return Err(QueryError::UnsupportedQuery.into());
+            };
+
+            if !enum_ty.options.iter().any(|option| option.name == enum_clause.option) {
+                return Err(QueryError::MemberNotFound(format!(
+                    "{}.{}",
+                    enum_clause.member, enum_clause.option
+                ))
+                .into());
+            }
+
+            let comparison_operator = ComparisonOperator::from_repr(enum_clause.operator as usize)
+                .expect("invalid comparison operator");
+            if !matches!(comparison_operator, ComparisonOperator::Eq | ComparisonOperator::Neq) {
+                return Err(QueryError::UnsupportedQuery.into());
+            }
+
+            Ok((
+                member_condition(model, &column, &format!("{comparison_operator} ?")),
+                vec![enum_clause.option.clone()],
+            ))
+        }
+        ClauseType::Array(array) => {
+            let column = member_column(model, schema, &array.member)?;
+            let Ty::Array(elements) = &column.ty else {
+                return Err(QueryError::UnsupportedQuery.into());
+            };
+            let array_table = format!("{}${}", column.table, column.name);
+
+            match array.predicate.as_ref().ok_or(QueryError::MissingParam("predicate".into()))? {
+                proto::types::array_clause::Predicate::Contains(value) => {
+                    if !is_scalar_ty(&elements[0]) {
+                        return Err(QueryError::UnsupportedQuery.into());
+                    }
+
+                    let value: Primitive = value.clone().try_into()?;
+                    Ok((
+                        format!(
+                            "[{model}].id IN (SELECT id FROM [{array_table}] WHERE external_data \
+                             = ?)"
+                        ),
+                        vec![value.to_sql_value()?],
+                    ))
+                }
+                proto::types::array_clause::Predicate::Length(length) => {
+                    // the elements of all the arrays nested in an array share the same table
+                    if column.in_array {
+                        return Err(QueryError::UnsupportedQuery.into());
+                    }
+
+                    let comparison_operator =
+                        ComparisonOperator::from_repr(length.operator as usize)
+                            .expect("invalid comparison operator");
+                    let predicate = format!("COUNT(*) {comparison_operator} {}", length.value);
+
+                    // empty arrays have no rows, so when they match the predicate the rows of the
+                    // arrays that don't match it are excluded instead
+                    let condition = if comparison_operator.matches(0, length.value) {
+                        format!(
+                            "[{model}].id NOT IN (SELECT id FROM [{array_table}] GROUP BY id \
+                             HAVING NOT ({predicate}))"
+                        )
+                    } else {
+                        format!(
+                            "[{model}].id IN (SELECT id FROM [{array_table}] GROUP BY id HAVING \
+                             {predicate})"
+                        )
+                    };
+
+                    Ok((condition, Vec::new()))
+                }
+            }
+        }
+        _ => Err(QueryError::UnsupportedQuery.into()),
+    }
+}
+
+// collects the models of the member, enum and array clauses of a clause tree
+fn clause_models(clause: &proto::types::Clause, models: &mut Vec<String>) -> Result<(), Error> {
+    match clause.clause_type.as_ref().ok_or(QueryError::MissingParam("clause_type".into()))? {
+        ClauseType::Composite(composite) => {
+            for clause in &composite.clauses {
+                clause_models(clause, models)?;
+            }
+        }
+        clause_type @ (ClauseType::Member(_) | ClauseType::Enum(_) | ClauseType::Array(_)) => {
+            models.push(clause_model(clause_type)?.to_string());
+        }
+        ClauseType::HashedKeys(_) | ClauseType::Keys(_) | ClauseType::Search(_) => {}
+    }
+
+    Ok(())
+}
+
+// builds the condition of a subscription clause on the rows of the entities table, the model
+// clauses being evaluated on the rows of their model table referencing the entity
+fn build_subscription_condition(
+    tables: EntityTables,
+    schemas: &HashMap<String, Ty>,
+    clause: proto::types::Clause,
+    bind_values: &mut Vec<String>,
+) -> Result<String, Error> {
+    let EntityTables { table, entity_relation_column, .. } = tables;
+
+    match clause.clause_type.ok_or(QueryError::MissingParam("clause_type".into()))? {
+        ClauseType::HashedKeys(hashed_keys) => {
+            if hashed_keys.hashed_keys.is_empty() {
+                return Ok("1 = 1".to_string());
+            }
+
+            let ids = hashed_keys
+                .hashed_keys
+                .iter()
+                .map(|id| format!("'{:#x}'", Felt::from_bytes_be_slice(id)))
+                .collect::<Vec<_>>();
+
+            Ok(format!("[{table}].id IN ({})", ids.join(", ")))
+        }
+        ClauseType::Keys(keys) => {
+            let keys_pattern = build_keys_pattern(&keys)?;
+            Ok(format!("[{table}].keys REGEXP '{keys_pattern}'"))
+        }
+        clause_type @ (ClauseType::Member(_) | ClauseType::Enum(_) | ClauseType::Array(_)) => {
+            let model = clause_model(&clause_type)?;
+            let schema =
+                schemas.get(model).ok_or_else(|| QueryError::ModelNotFound(model.to_string()))?;
+
+            let (condition, values) = model_clause_condition(model, schema, &clause_type)?;
+            bind_values.extend(values);
+
+            Ok(format!(
+                "[{table}].id IN (SELECT [{model}].{entity_relation_column} FROM [{model}] WHERE \
+                 {condition})"
+            ))
+        }
+        ClauseType::Composite(composite) => {
+            if composite.clauses.is_empty() {
+                return Ok("1 = 1".to_string());
+            }
+
+            let operator = match composite.operator() {
+                proto::types::LogicalOperator::And => " AND ",
+                proto::types::LogicalOperator::Or => " OR ",
+            };
+            let clauses = composite
+                .clauses
+                .into_iter()
+                .map(|clause| build_subscription_condition(tables, schemas, clause, bind_values))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Ok(format!("({})", clauses.join(operator)))
+        }
+        ClauseType::Search(search) => {
+            let Some(search_query) = search_query(&search.text) else {
+                return Ok("0 = 1".to_string());
+            };
+            bind_values.push(search_query);
+
+            Ok(format!(
+                "[{table}].id IN (SELECT id FROM {SEARCH_TABLE} WHERE {SEARCH_TABLE} MATCH ?)"
+            ))
+        }
+    }
+}
+
+// the column of an ordered query, the placeholder of the cursor value casting it to the type of
+// the column so that integers aren't compared as text
+struct OrderColumn {
+    join: String,
+    expr: String,
+    placeholder: String,
+}
+
+// the member of a model the entities are ordered by, as an expression on the row of the model table
+fn order_column(
+    model: &str,
+    schema: &Ty,
+    path: &str,
+    table: &str,
+    entity_relation_column: &str,
+) -> Result<OrderColumn, Error> {
+    let column = member_column(model, schema, path)?;
+    if column.in_array || !is_scalar_ty(&column.ty) {
+        return Err(QueryError::UnsupportedQuery.into());
+    }
+
+    let expr = if column.table == model {
+        format!("[{model}].external_{}", column.name)
+    } else {
+        format!("(SELECT external_{} FROM [{}] WHERE id = [{model}].id)", column.name, column.table)
+    };
+    let placeholder = match &column.ty {
+        Ty::Primitive(primitive) if primitive.to_sql_type() == SqlType::Integer => {
+            "CAST(? AS INTEGER)".to_string()
+        }
+        _ => "?".to_string(),
+    };
+
+    Ok(OrderColumn {
+        join: format!("JOIN [{model}] ON [{model}].{entity_relation_column} = [{table}].id"),
+        expr,
+        placeholder,
+    })
+}
+
+// cursors are encoded like the ones of the graphql connections, the primary part being the id of
+// the entity or event and the secondary one the value it's ordered by, left out when it's null
+fn encode_cursor(id: &str, value: Option<&str>) -> String {
+    match value {
+        Some(value) => general_purpose::STANDARD.encode(format!("cursor/{id}/{value}")),
+        None => general_purpose::STANDARD.encode(format!("cursor/{id}")),
+    }
+}
+
+fn decode_cursor(cursor: &str) -> Result<(String, Option<String>), Error> {
+    let invalid = || QueryError::InvalidCursor(cursor.to_string());
+
+    let bytes = general_purpose::STANDARD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+    // ids never contain slashes, unlike some ordered values
+    match decoded.splitn(3, '/').collect::<Vec<_>>()[..] {
+        ["cursor", id] => Ok((id.to_string(), None)),
+        ["cursor", id, value] => Ok((id.to_string(), Some(value.to_string()))),
+        _ => Err(invalid().into()),
+    }
+}
+
+// drops the row fetched past the limit, returning the cursor of the last row of the page if there
+// is a next one
+fn next_page_cursor<T>(rows: &mut Vec<T>, limit: u32, cursor: impl Fn(&T) -> String) -> String {
+    if rows.len() <= limit as usize {
+        return String::new();
+    }
+
+    rows.truncate(limit as usize);
+    rows.last().map(cursor).unwrap_or_default()
+}
+
 // this builds a sql safe regex pattern to match against for keys
 fn build_keys_pattern(clause: &proto::types::KeysClause) -> Result<String, Error> {
     let keys = clause
@@ -1027,6 +1962,8 @@ fn build_keys_pattern(clause: &proto::types::KeysClause) -> Result<String, Error
 type ServiceResult<T> = Result<Response<T>, Status>;
 type SubscribeModelsResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeModelsResponse, Status>> + Send>>;
+type SubscribeModelRegistrationsResponseStream =
+    Pin<Box<dyn Stream<Item = Result<SubscribeModelRegistrationsResponse, Status>> + Send>>;
 type SubscribeEntitiesResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeEntityResponse, Status>> + Send>>;
 type SubscribeEventsResponseStream =
@@ -1035,6 +1972,7 @@ type SubscribeEventsResponseStream =
 #[tonic::async_trait]
 impl proto::world::world_server::World for DojoWorld {
     type SubscribeModelsStream = SubscribeModelsResponseStream;
+    type SubscribeModelRegistrationsStream = SubscribeModelRegistrationsResponseStream;
     type SubscribeEntitiesStream = SubscribeEntitiesResponseStream;
     type SubscribeEventMessagesStream = SubscribeEntitiesResponseStream;
     type SubscribeEventsStream = SubscribeEventsResponseStream;
@@ -1063,13 +2001,30 @@ impl proto::world::world_server::World for DojoWorld {
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::SubscribeModelsStream))
     }
 
+    async fn subscribe_model_registrations(
+        &self,
+        request: Request<SubscribeModelRegistrationsRequest>,
+    ) -> ServiceResult<Self::SubscribeModelRegistrationsStream> {
+        let SubscribeModelRegistrationsRequest { namespaces } = request.into_inner();
+        let rx = self
+            .subscribe_model_registrations(namespaces)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(
+            Box::pin(ReceiverStream::new(rx)) as Self::SubscribeModelRegistrationsStream
+        ))
+    }
+
     async fn subscribe_entities(
         &self,
         request: Request<SubscribeEntitiesRequest>,
     ) -> ServiceResult<Self::SubscribeEntitiesStream> {
-        let SubscribeEntitiesRequest { clauses } = request.into_inner();
-        let rx =
-            self.subscribe_entities(clauses).await.map_err(|e| Status::internal(e.to_string()))?;
+        let SubscribeEntitiesRequest { clauses, cursor, clause, subscription_id } =
+            request.into_inner();
+        let rx = self
+            .subscribe_entities(clauses, clause, subscription_id, cursor)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::SubscribeEntitiesStream))
     }
@@ -1078,11 +2033,17 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<UpdateEntitiesSubscriptionRequest>,
     ) -> ServiceResult<()> {
-        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses } = request.into_inner();
+        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses, clause } =
+            request.into_inner();
+        let clause = self
+            .subscriber_clause(ENTITY_TABLES, clause)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
         self.entity_manager
             .update_subscriber(
                 subscription_id,
                 clauses.into_iter().map(|keys| keys.into()).collect(),
+                clause,
             )
             .await;
 
@@ -1108,9 +2069,10 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<SubscribeEntitiesRequest>,
     ) -> ServiceResult<Self::SubscribeEntitiesStream> {
-        let SubscribeEntitiesRequest { clauses } = request.into_inner();
+        let SubscribeEntitiesRequest { clauses, cursor, clause, subscription_id } =
+            request.into_inner();
         let rx = self
-            .subscribe_event_messages(clauses)
+            .subscribe_event_messages(clauses, clause, subscription_id, cursor)
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
@@ -1121,11 +2083,17 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<UpdateEntitiesSubscriptionRequest>,
     ) -> ServiceResult<()> {
-        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses } = request.into_inner();
+        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses, clause } =
+            request.into_inner();
+        let clause = self
+            .subscriber_clause(EVENT_MESSAGE_TABLES, clause)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
         self.event_message_manager
             .update_subscriber(
                 subscription_id,
                 clauses.into_iter().map(|keys| keys.into()).collect(),
+                clause,
             )
             .await;
 
@@ -1166,14 +2134,100 @@ impl proto::world::world_server::World for DojoWorld {
 
     async fn subscribe_events(
         &self,
-        request: Request<proto::world::SubscribeEventsRequest>,
+        request: Request<SubscribeEventsRequest>,
     ) -> ServiceResult<Self::SubscribeEventsStream> {
-        let keys = request.into_inner().keys.unwrap_or_default();
+        let SubscribeEventsRequest { keys, cursor } = request.into_inner();
 
-        let rx = self.subscribe_events(keys).await.map_err(|e| Status::internal(e.to_string()))?;
+        let rx = self
+            .subscribe_events(keys.unwrap_or_default(), cursor)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::SubscribeEventsStream))
     }
+
+    async fn retrieve_aggregates(
+        &self,
+        request: Request<RetrieveAggregatesRequest>,
+    ) -> Result<Response<RetrieveAggregatesResponse>, Status> {
+        let query = request
+            .into_inner()
+            .query
+            .ok_or_else(|| Status::invalid_argument("Missing query argument"))?;
+
+        let aggregates =
+            self.retrieve_aggregates(query).await.map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(aggregates))
+    }
+}
+
+/// Transport settings of the gRPC server and of its WebSocket endpoint.
+#[derive(Debug, Clone)]
+pub struct GrpcConfig {
+    /// Address of the gRPC server, which also serves gRPC-web to browsers. Bound to a random local
+    /// port by default, to be reached through the proxy.
+    pub addr: SocketAddr,
+    /// Address of the JSON over WebSocket endpoint.
+    pub websocket_addr: SocketAddr,
+    /// Compresses the responses with gzip for the clients accepting it. Compressed requests are
+    /// always accepted.
+    pub compression: bool,
+    /// Maximum size of a request message, in bytes.
+    pub max_decoding_message_size: usize,
+    /// Maximum size of a response message, in bytes.
+    pub max_encoding_message_size: usize,
+    /// Origins allowed to reach the server from a browser, `*` allowing any of them. Browsers
+    /// can't make cross-origin requests when unset.
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            websocket_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            compression: false,
+            max_decoding_message_size: 4 * 1024 * 1024,
+            max_encoding_message_size: usize::MAX,
+            allowed_origins: None,
+        }
+    }
+}
+
+// the cors of gRPC-web, for the browsers reaching the server without going through the proxy
+fn cors_layer(allowed_origins: &[String]) -> Result<CorsLayer, std::io::Error> {
+    let allow_origin = match allowed_origins {
+        [origin] if origin == "*" => AllowOrigin::mirror_request(),
+        origins => {
+            let origins = origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin).map_err(|e| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("Invalid origin {origin}: {e}"),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        }
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::POST])
+        .allow_headers(GRPC_WEB_ALLOW_HEADERS.map(HeaderName::from_static))
+        .expose_headers(GRPC_WEB_EXPOSED_HEADERS.map(HeaderName::from_static))
+        .max_age(Duration::from_secs(24 * 60 * 60)))
+}
+
+/// Addresses the gRPC server and its WebSocket endpoint are bound to.
+#[derive(Debug, Clone, Copy)]
+pub struct GrpcAddrs {
+    pub grpc: SocketAddr,
+    pub websocket: SocketAddr,
 }
 
 pub async fn new(
@@ -1182,11 +2236,12 @@ pub async fn new(
     block_rx: Receiver<u64>,
     world_address: Felt,
     provider: Arc<JsonRpcClient<HttpTransport>>,
+    config: GrpcConfig,
 ) -> Result<
-    (SocketAddr, impl Future<Output = Result<(), tonic::transport::Error>> + 'static),
+    (GrpcAddrs, impl Future<Output = Result<(), tonic::transport::Error>> + 'static),
     std::io::Error,
 > {
-    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let listener = TcpListener::bind(config.addr).await?;
     let addr = listener.local_addr()?;
 
     let reflection = tonic_reflection::server::Builder::configure()
@@ -1195,16 +2250,37 @@ pub async fn new(
         .unwrap();
 
     let world = DojoWorld::new(pool.clone(), block_rx, world_address, provider);
-    let server = WorldServer::new(world);
 
-    let server_future = Server::builder()
+    let (websocket_addr, websocket_server) =
+        websocket::new(shutdown_rx.resubscribe(), world.clone(), &config)?;
+    let cors = config.allowed_origins.as_deref().map(cors_layer).transpose()?;
+
+    let mut server = WorldServer::new(world)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(config.max_decoding_message_size)
+        .max_encoding_message_size(config.max_encoding_message_size);
+    if config.compression {
+        server = server.send_compressed(CompressionEncoding::Gzip);
+    }
+
+    let grpc_server = Server::builder()
         // GrpcWeb is over http1 so we must enable it.
         .accept_http1(true)
+        .layer(option_layer(cors))
+        .layer(GrpcWebLayer::new())
         .add_service(reflection)
-        .add_service(tonic_web::enable(server))
+        .add_service(server)
         .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async move {
             shutdown_rx.recv().await.map_or((), |_| ())
         });
 
-    Ok((addr, server_future))
+    let server_future = async move {
+        tokio::try_join!(grpc_server, async {
+            websocket_server.await;
+            Ok(())
+        })
+        .map(|_| ())
+    };
+
+    Ok((GrpcAddrs { grpc: addr, websocket: websocket_addr }, server_future))
 }
@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::iter;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use dojo_types::schema::{Struct, Ty};
 use futures::Stream;
 use futures_util::StreamExt;
+use parking_lot::Mutex;
 use rand::Rng;
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Row, Sqlite};
 use starknet::core::types::Felt;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::RwLock;
@@ -21,8 +24,12 @@ use torii_core::types::Entity;
 use tracing::{error, trace};
 
 use crate::proto;
+use crate::proto::types::EntityUpdateType;
 use crate::proto::world::SubscribeEntityResponse;
-use crate::server::map_row_to_entity;
+use crate::server::{
+    map_row_to_entity, ENTITIES_ENTITY_RELATION_COLUMN, ENTITIES_MODEL_RELATION_TABLE,
+    ENTITIES_TABLE, ENTITIES_TOMBSTONES_TABLE,
+};
 use crate::types::{EntityKeysClause, PatternMatching};
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::entity";
@@ -31,36 +38,37 @@ pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::entity"
 pub struct EntitiesSubscriber {
     /// Entity ids that the subscriber is interested in
     pub(crate) clauses: Vec<EntityKeysClause>,
+    /// Clause on the models of the entities, evaluated after each update.
+    pub(crate) clause: Option<SubscriptionClause>,
     /// The channel to send the response back to the subscriber.
     pub(crate) sender: Sender<Result<proto::world::SubscribeEntityResponse, tonic::Status>>,
 }
+
+/// Subscribers of the entities, or event messages, by subscription id.
+pub(crate) type EntitiesSubscribers = Arc<RwLock<HashMap<u64, EntitiesSubscriber>>>;
+
 #[derive(Debug, Default)]
 pub struct EntityManager {
-    subscribers: RwLock<HashMap<u64, EntitiesSubscriber>>,
+    subscribers: EntitiesSubscribers,
 }
 
 impl EntityManager {
     pub async fn add_subscriber(
         &self,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<SubscriptionClause>,
+        subscription_id: Option<u64>,
+        replay: Option<Replay>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
-        let subscription_id = rand::thread_rng().gen::<u64>();
-        let (sender, receiver) = channel(1);
-
-        // NOTE: unlock issue with firefox/safari
-        // initially send empty stream message to return from
-        // initial subscribe call
-        let _ = sender.send(Ok(SubscribeEntityResponse { entity: None, subscription_id })).await;
-
-        self.subscribers
-            .write()
-            .await
-            .insert(subscription_id, EntitiesSubscriber { clauses, sender });
-
-        Ok(receiver)
+        add_entities_subscriber(&self.subscribers, clauses, clause, subscription_id, replay).await
     }
 
-    pub async fn update_subscriber(&self, id: u64, clauses: Vec<EntityKeysClause>) {
+    pub async fn update_subscriber(
+        &self,
+        id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<SubscriptionClause>,
+    ) {
         let sender = {
             let subscribers = self.subscribers.read().await;
             if let Some(subscriber) = subscribers.get(&id) {
@@ -70,7 +78,7 @@ impl EntityManager {
             }
         };
 
-        self.subscribers.write().await.insert(id, EntitiesSubscriber { clauses, sender });
+        self.subscribers.write().await.insert(id, EntitiesSubscriber { clauses, clause, sender });
     }
 
     pub(super) async fn remove_subscriber(&self, id: u64) {
@@ -78,6 +86,62 @@ impl EntityManager {
     }
 }
 
+/// Adds a subscriber of entities, or event messages. A resumed subscription keeps its id unless
+/// it's still used by an open stream.
+///
+/// The updates since the cursor of a resumed subscription are replayed before it's added, with
+/// the subscribers locked so that the live updates are only delivered once the replay is sent.
+pub(crate) async fn add_entities_subscriber(
+    subscribers: &EntitiesSubscribers,
+    clauses: Vec<EntityKeysClause>,
+    clause: Option<SubscriptionClause>,
+    subscription_id: Option<u64>,
+    replay: Option<Replay>,
+) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
+    let mut subscribers = Arc::clone(subscribers).write_owned().await;
+    let subscription_id = match subscription_id {
+        Some(id) if subscribers.get(&id).map_or(true, |sub| sub.sender.is_closed()) => id,
+        _ => rand::thread_rng().gen::<u64>(),
+    };
+    let (sender, receiver) = channel(1);
+
+    // NOTE: unlock issue with firefox/safari
+    // initially send empty stream message to return from
+    // initial subscribe call
+    let _ = sender
+        .send(Ok(SubscribeEntityResponse {
+            entity: None,
+            subscription_id,
+            event_id: String::new(),
+            update_type: EntityUpdateType::Updated as i32,
+        }))
+        .await;
+
+    let subscriber = EntitiesSubscriber { clauses, clause, sender };
+    let Some(replay) = replay else {
+        subscribers.insert(subscription_id, subscriber);
+        return Ok(receiver);
+    };
+
+    // the receiver is only returned once subscribed, so the updates are replayed in the
+    // background, holding the lock until the subscriber is added
+    tokio::spawn(async move {
+        match replay.run(&subscriber, subscription_id).await {
+            Ok(()) => {
+                subscribers.insert(subscription_id, subscriber);
+            }
+            Err(e) => {
+                let table = replay.tables.table;
+                error!(target = LOG_TARGET, table, error = %e, "Replaying updates.");
+                // the subscriber resumes again from its cursor
+                let _ = subscriber.sender.send(Err(tonic::Status::internal(e.to_string()))).await;
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
 #[must_use = "Service does nothing unless polled"]
 #[allow(missing_debug_implementations)]
 pub struct Service {
@@ -107,172 +171,517 @@ impl Service {
         pool: Pool<Sqlite>,
         entity: &Entity,
     ) -> Result<(), Error> {
-        let mut closed_stream = Vec::new();
-        let hashed = Felt::from_str(&entity.id).map_err(ParseError::FromStr)?;
-        let keys = entity
-            .keys
-            .trim_end_matches(FELT_DELIMITER)
-            .split(FELT_DELIMITER)
-            .map(Felt::from_str)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(ParseError::FromStr)?;
-
-        for (idx, sub) in subs.subscribers.read().await.iter() {
-            // Check if the subscriber is interested in this entity
-            // If we have a clause of hashed keys, then check that the id of the entity
-            // is in the list of hashed keys.
-
-            // If we have a clause of keys, then check that the key pattern of the entity
-            // matches the key pattern of the subscriber.
-            if !sub.clauses.is_empty()
-                && !sub.clauses.iter().any(|clause| match clause {
-                    EntityKeysClause::HashedKeys(hashed_keys) => {
-                        hashed_keys.is_empty() || hashed_keys.contains(&hashed)
+        let update = PublishedUpdate {
+            id: &entity.id,
+            keys: &entity.keys,
+            event_id: &entity.event_id,
+            updated_model: entity.updated_model.as_ref(),
+            deleted: entity.deleted,
+        };
+        let closed_stream = publish_entity_update(
+            &*subs.subscribers.read().await,
+            &pool,
+            &cache,
+            ENTITY_TABLES,
+            update,
+        )
+        .await?;
+
+        for id in closed_stream {
+            trace!(target = LOG_TARGET, id = %id, "Closing entity stream.");
+            subs.remove_subscriber(id).await
+        }
+
+        Ok(())
+    }
+}
+
+impl Future for Service {
+    type Output = ();
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<Self::Output> {
+        let pin = self.get_mut();
+
+        while let Poll::Ready(Some(entity)) = pin.simple_broker.poll_next_unpin(cx) {
+            let subs = Arc::clone(&pin.subs_manager);
+            let cache = Arc::clone(&pin.model_cache);
+            let pool = pin.pool.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Service::publish_updates(subs, cache, pool, &entity).await {
+                    error!(target = LOG_TARGET, error = %e, "Publishing entity update.");
+                }
+            });
+        }
+
+        Poll::Pending
+    }
+}
+
+/// The tables storing entities, or event messages, and their models.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EntityTables {
+    pub(crate) table: &'static str,
+    /// Table relating the entities to their models.
+    pub(crate) model_relation_table: &'static str,
+    /// Column of the model tables referencing the entities.
+    pub(crate) entity_relation_column: &'static str,
+    /// Table of the deleted entities, if they can be deleted.
+    pub(crate) tombstones_table: Option<&'static str>,
+}
+
+pub(crate) const ENTITY_TABLES: EntityTables = EntityTables {
+    table: ENTITIES_TABLE,
+    model_relation_table: ENTITIES_MODEL_RELATION_TABLE,
+    entity_relation_column: ENTITIES_ENTITY_RELATION_COLUMN,
+    tombstones_table: Some(ENTITIES_TOMBSTONES_TABLE),
+};
+
+/// A clause of a subscriber, compiled to a condition on the rows of the entities table.
+#[derive(Debug)]
+pub struct SubscriptionClause {
+    pub(crate) condition: String,
+    pub(crate) bind_values: Vec<String>,
+    /// Ids of the entities matching the clause, the subscriber being notified when they no longer
+    /// match it.
+    pub(crate) matched: Mutex<HashSet<String>>,
+}
+
+impl SubscriptionClause {
+    pub(crate) fn new(condition: String, bind_values: Vec<String>) -> Self {
+        Self { condition, bind_values, matched: Default::default() }
+    }
+
+    /// Records the entities currently matching the clause.
+    pub(crate) async fn match_current(
+        &self,
+        pool: &Pool<Sqlite>,
+        tables: EntityTables,
+    ) -> Result<(), Error> {
+        let table = tables.table;
+        let query = format!("SELECT [{table}].id FROM [{table}] WHERE {}", self.condition);
+
+        let mut query = sqlx::query_scalar::<_, String>(&query);
+        for value in &self.bind_values {
+            query = query.bind(value);
+        }
+
+        *self.matched.lock() = query.fetch_all(pool).await?.into_iter().collect();
+        Ok(())
+    }
+}
+
+// sqlite limits the number of columns of a result
+const MAX_CLAUSES_PER_QUERY: usize = 256;
+
+/// Whether the entity, in its current state, matches the clauses of the subscribers. The clauses
+/// are evaluated in a single query, and one by one if it fails so that only the subscribers with
+/// a failing clause are skipped, with `None`.
+pub(crate) async fn match_clauses(
+    pool: &Pool<Sqlite>,
+    tables: EntityTables,
+    entity_id: &str,
+    clauses: &[&SubscriptionClause],
+) -> Vec<Option<bool>> {
+    if clauses.is_empty() {
+        return Vec::new();
+    }
+
+    if let Ok(matches) = query_clauses(pool, tables, entity_id, clauses).await {
+        return matches.into_iter().map(Some).collect();
+    }
+
+    let mut matches = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        match query_clauses(pool, tables, entity_id, &[clause]).await {
+            Ok(clause_matches) => matches.push(clause_matches.first().copied()),
+            Err(e) => {
+                error!(target = LOG_TARGET, entity_id, error = %e, "Matching subscription clause.");
+                matches.push(None);
+            }
+        }
+    }
+
+    matches
+}
+
+async fn query_clauses(
+    pool: &Pool<Sqlite>,
+    tables: EntityTables,
+    entity_id: &str,
+    clauses: &[&SubscriptionClause],
+) -> Result<Vec<bool>, Error> {
+    let table = tables.table;
+    let mut matches = Vec::with_capacity(clauses.len());
+
+    for clauses in clauses.chunks(MAX_CLAUSES_PER_QUERY) {
+        let columns = clauses
+            .iter()
+            .map(|clause| format!("COALESCE(({}), 0)", clause.condition))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!("SELECT {columns} FROM [{table}] WHERE [{table}].id = ?");
+
+        let mut query = sqlx::query(&query);
+        for clause in clauses {
+            for value in &clause.bind_values {
+                query = query.bind(value);
+            }
+        }
+
+        match query.bind(entity_id).fetch_optional(pool).await? {
+            Some(row) => {
+                for i in 0..clauses.len() {
+                    matches.push(row.try_get::<bool, _>(i)?);
+                }
+            }
+            // the entity was deleted since its update
+            None => matches.extend(iter::repeat(false).take(clauses.len())),
+        }
+    }
+
+    Ok(matches)
+}
+
+/// An update of an entity, or event message, published to the subscribers.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PublishedUpdate<'a> {
+    pub(crate) id: &'a str,
+    pub(crate) keys: &'a str,
+    pub(crate) event_id: &'a str,
+    pub(crate) updated_model: Option<&'a Ty>,
+    pub(crate) deleted: bool,
+}
+
+/// Sends an update of an entity, or event message, to the subscribers interested in it. The
+/// subscribers whose clause the entity no longer matches are notified once. Returns the ids of
+/// the subscribers whose stream is closed.
+pub(crate) async fn publish_entity_update(
+    subscribers: &HashMap<u64, EntitiesSubscriber>,
+    pool: &Pool<Sqlite>,
+    cache: &ModelCache,
+    tables: EntityTables,
+    update: PublishedUpdate<'_>,
+) -> Result<Vec<u64>, Error> {
+    let hashed = Felt::from_str(update.id).map_err(ParseError::FromStr)?;
+    let keys = parse_keys(update.keys)?;
+
+    // Check if the subscribers are interested in this entity
+    let subscribers = subscribers
+        .iter()
+        .filter(|(_, sub)| match_entity(&sub.clauses, &hashed, &keys, update.updated_model))
+        .collect::<Vec<_>>();
+
+    let mut updates = Vec::with_capacity(subscribers.len());
+    if update.deleted {
+        for (idx, sub) in subscribers {
+            if let Some(clause) = &sub.clause {
+                clause.matched.lock().remove(update.id);
+            }
+            updates.push((*idx, sub, EntityUpdateType::Deleted));
+        }
+    } else {
+        let clauses =
+            subscribers.iter().filter_map(|(_, sub)| sub.clause.as_ref()).collect::<Vec<_>>();
+        let mut matches = match_clauses(pool, tables, update.id, &clauses).await.into_iter();
+
+        for (idx, sub) in subscribers {
+            let update_type = match &sub.clause {
+                None => EntityUpdateType::Updated,
+                Some(clause) => match matches.next().flatten() {
+                    Some(true) => {
+                        clause.matched.lock().insert(update.id.to_string());
+                        EntityUpdateType::Updated
                     }
-                    EntityKeysClause::Keys(clause) => {
-                        // if we have a model clause, then we need to check that the entity
-                        // has an updated model and that the model name matches the clause
-                        if let Some(updated_model) = &entity.updated_model {
-                            let name = updated_model.name();
-                            let (namespace, name) = name.split_once('-').unwrap();
-
-                            if !clause.models.is_empty()
-                                && !clause.models.iter().any(|clause_model| {
-                                    let (clause_namespace, clause_model) =
-                                        clause_model.split_once('-').unwrap();
-                                    // if both namespace and model are empty, we should match all.
-                                    // if namespace is specified and model is empty or * we should
-                                    // match all models in the
-                                    // namespace if namespace
-                                    // and model are specified, we should match the
-                                    // specific model
-                                    (clause_namespace.is_empty()
-                                        || clause_namespace == namespace
-                                        || clause_namespace == "*")
-                                        && (clause_model.is_empty()
-                                            || clause_model == name
-                                            || clause_model == "*")
-                                })
-                            {
-                                return false;
-                            }
-                        }
-
-                        // if the key pattern doesnt match our subscribers key pattern, skip
-                        // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
-                        if clause.pattern_matching == PatternMatching::FixedLen
-                            && keys.len() != clause.keys.len()
-                        {
-                            return false;
-                        }
-
-                        return keys.iter().enumerate().all(|(idx, key)| {
-                            // this is going to be None if our key pattern overflows the subscriber
-                            // key pattern in this case we should skip
-                            let sub_key = clause.keys.get(idx);
-
-                            match sub_key {
-                                // the key in the subscriber must match the key of the entity
-                                // athis index
-                                Some(Some(sub_key)) => key == sub_key,
-                                // otherwise, if we have no key we should automatically match.
-                                // or.. we overflowed the subscriber key pattern
-                                // but we're in VariableLen pattern matching
-                                // so we should match all next keys
-                                _ => true,
-                            }
-                        });
+                    Some(false) if clause.matched.lock().remove(update.id) => {
+                        EntityUpdateType::Unmatched
                     }
-                })
-            {
-                continue;
+                    _ => continue,
+                },
+            };
+            updates.push((*idx, sub, update_type));
+        }
+    }
+
+    // the models of the entity are fetched once for all the subscribers
+    let mut entity = None;
+    if updates.iter().any(|(.., update_type)| *update_type == EntityUpdateType::Updated) {
+        let models = async {
+            let schemas = entity_schemas(pool, cache, tables, update.id).await?;
+            entity_with_models(pool, tables, update.id, schemas).await
+        };
+
+        match models.await {
+            Ok(models) => entity = Some(models),
+            Err(e) => {
+                let entity_id = update.id;
+                error!(target = LOG_TARGET, entity_id, error = %e, "Fetching entity models.");
             }
+        }
+    }
+
+    let mut closed_stream = Vec::new();
+    for (idx, sub, update_type) in updates {
+        let entity = match (update_type, &entity) {
+            (EntityUpdateType::Updated, Some(entity)) => entity.clone(),
+            (EntityUpdateType::Updated, None) => continue,
+            _ => {
+                proto::types::Entity { hashed_keys: hashed.to_bytes_be().to_vec(), models: vec![] }
+            }
+        };
+        let resp = proto::world::SubscribeEntityResponse {
+            entity: Some(entity),
+            subscription_id: idx,
+            event_id: update.event_id.to_string(),
+            update_type: update_type as i32,
+        };
+
+        if sub.sender.send(Ok(resp)).await.is_err() {
+            closed_stream.push(idx);
+        }
+    }
+
+    Ok(closed_stream)
+}
+
+/// Replays the updates missed by a subscriber since its cursor.
+#[derive(Debug)]
+pub struct Replay {
+    pub(crate) pool: Pool<Sqlite>,
+    pub(crate) cache: Arc<ModelCache>,
+    pub(crate) tables: EntityTables,
+    /// Event id of the last update received by the subscriber.
+    pub(crate) cursor: String,
+}
+
+impl Replay {
+    /// Sends the current state of the entities updated since the block of the cursor, and the
+    /// deletions of the entities deleted since then.
+    pub(crate) async fn run(
+        &self,
+        subscriber: &EntitiesSubscriber,
+        subscription_id: u64,
+    ) -> Result<(), Error> {
+        let EntitiesSubscriber { clauses, clause, sender } = subscriber;
+        let table = self.tables.table;
+
+        // event ids are prefixed by their block number, but the transactions are not ordered in
+        // a block, so the whole block of the cursor is replayed
+        let block = self.cursor.split(':').next().unwrap_or_default();
+        let mut query =
+            format!("SELECT id, keys, event_id, NULL AS model FROM [{table}] WHERE event_id >= ?");
+        // an entity set again after its deletion is replayed with its current state
+        if let Some(tombstones) = self.tables.tombstones_table {
+            query += &format!(
+                " UNION ALL SELECT id, keys, event_id, model FROM [{tombstones}] WHERE event_id \
+                 >= ? AND id NOT IN (SELECT id FROM [{table}])"
+            );
+        }
+        query += " ORDER BY event_id";
+
+        let mut query = sqlx::query_as(&query).bind(block);
+        if self.tables.tombstones_table.is_some() {
+            query = query.bind(block);
+        }
+        let updates: Vec<(String, String, String, Option<String>)> =
+            query.fetch_all(&self.pool).await?;
+
+        trace!(
+            target = LOG_TARGET,
+            cursor = %self.cursor,
+            count = updates.len(),
+            "Replaying entities."
+        );
+
+        for (id, keys, event_id, model) in updates {
+            let hashed = Felt::from_str(&id).map_err(ParseError::FromStr)?;
+            let keys = parse_keys(&keys)?;
+
+            // deletions are only filtered by the keys clauses, like the live ones
+            if let Some(model) = model {
+                let model = Ty::Struct(Struct { name: model, children: vec![] });
+                if !match_entity(clauses, &hashed, &keys, Some(&model)) {
+                    continue;
+                }
 
-            if entity.updated_model.is_none() {
                 let resp = proto::world::SubscribeEntityResponse {
                     entity: Some(proto::types::Entity {
                         hashed_keys: hashed.to_bytes_be().to_vec(),
                         models: vec![],
                     }),
-                    subscription_id: *idx,
+                    subscription_id,
+                    event_id,
+                    update_type: EntityUpdateType::Deleted as i32,
                 };
-
-                if sub.sender.send(Ok(resp)).await.is_err() {
-                    closed_stream.push(*idx);
+                if sender.send(Ok(resp)).await.is_err() {
+                    break;
                 }
+                continue;
+            }
 
+            let schemas = entity_schemas(&self.pool, &self.cache, self.tables, &id).await?;
+            if !schemas.iter().any(|schema| match_entity(clauses, &hashed, &keys, Some(schema))) {
                 continue;
             }
 
-            let models_query = r#"
-                    SELECT group_concat(entity_model.model_id) as model_ids
-                    FROM entities
-                    JOIN entity_model ON entities.id = entity_model.entity_id
-                    WHERE entities.id = ?
-                    GROUP BY entities.id
-                "#;
-            let (model_ids,): (String,) =
-                sqlx::query_as(models_query).bind(&entity.id).fetch_one(&pool).await?;
-            let model_ids: Vec<Felt> = model_ids
-                .split(',')
-                .map(Felt::from_str)
-                .collect::<Result<_, _>>()
-                .map_err(ParseError::FromStr)?;
-            let schemas = cache.schemas(&model_ids).await?;
-
-            let (entity_query, arrays_queries, _) = build_sql_query(
-                &schemas,
-                "entities",
-                "entity_id",
-                Some("entities.id = ?"),
-                Some("entities.id = ?"),
-                None,
-                None,
-            )?;
-
-            let row = sqlx::query(&entity_query).bind(&entity.id).fetch_one(&pool).await?;
-            let mut arrays_rows = HashMap::new();
-            for (name, query) in arrays_queries {
-                let row = sqlx::query(&query).bind(&entity.id).fetch_all(&pool).await?;
-                arrays_rows.insert(name, row);
+            if let Some(clause) = clause {
+                if match_clauses(&self.pool, self.tables, &id, &[clause]).await != [Some(true)] {
+                    continue;
+                }
+                clause.matched.lock().insert(id.clone());
             }
 
             let resp = proto::world::SubscribeEntityResponse {
-                entity: Some(map_row_to_entity(&row, &arrays_rows, schemas.clone())?),
-                subscription_id: *idx,
+                entity: Some(entity_with_models(&self.pool, self.tables, &id, schemas).await?),
+                subscription_id,
+                event_id,
+                update_type: EntityUpdateType::Updated as i32,
             };
 
-            if sub.sender.send(Ok(resp)).await.is_err() {
-                closed_stream.push(*idx);
+            if sender.send(Ok(resp)).await.is_err() {
+                break;
             }
         }
 
-        for id in closed_stream {
-            trace!(target = LOG_TARGET, id = %id, "Closing entity stream.");
-            subs.remove_subscriber(id).await
-        }
-
         Ok(())
     }
 }
 
-impl Future for Service {
-    type Output = ();
+pub(crate) fn parse_keys(keys: &str) -> Result<Vec<Felt>, Error> {
+    Ok(keys
+        .trim_end_matches(FELT_DELIMITER)
+        .split(FELT_DELIMITER)
+        .map(Felt::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ParseError::FromStr)?)
+}
 
-    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<Self::Output> {
-        let pin = self.get_mut();
+/// Whether an update of an entity matches the clauses of a subscriber.
+pub(crate) fn match_entity(
+    clauses: &[EntityKeysClause],
+    hashed: &Felt,
+    keys: &[Felt],
+    updated_model: Option<&Ty>,
+) -> bool {
+    // If we have a clause of hashed keys, then check that the id of the entity
+    // is in the list of hashed keys.
+
+    // If we have a clause of keys, then check that the key pattern of the entity
+    // matches the key pattern of the subscriber.
+    clauses.is_empty()
+        || clauses.iter().any(|clause| match clause {
+            EntityKeysClause::HashedKeys(hashed_keys) => {
+                hashed_keys.is_empty() || hashed_keys.contains(hashed)
+            }
+            EntityKeysClause::Keys(clause) => {
+                // if we have a model clause, then we need to check that the entity
+                // has an updated model and that the model name matches the clause
+                if let Some(updated_model) = updated_model {
+                    let name = updated_model.name();
+                    let (namespace, name) = name.split_once('-').unwrap();
+
+                    if !clause.models.is_empty()
+                        && !clause.models.iter().any(|clause_model| {
+                            let (clause_namespace, clause_model) =
+                                clause_model.split_once('-').unwrap();
+                            // if both namespace and model are empty, we should match all.
+                            // if namespace is specified and model is empty or * we should
+                            // match all models in the
+                            // namespace if namespace
+                            // and model are specified, we should match the
+                            // specific model
+                            (clause_namespace.is_empty()
+                                || clause_namespace == namespace
+                                || clause_namespace == "*")
+                                && (clause_model.is_empty()
+                                    || clause_model == name
+                                    || clause_model == "*")
+                        })
+                    {
+                        return false;
+                    }
+                }
 
-        while let Poll::Ready(Some(entity)) = pin.simple_broker.poll_next_unpin(cx) {
-            let subs = Arc::clone(&pin.subs_manager);
-            let cache = Arc::clone(&pin.model_cache);
-            let pool = pin.pool.clone();
-            tokio::spawn(async move {
-                if let Err(e) = Service::publish_updates(subs, cache, pool, &entity).await {
-                    error!(target = LOG_TARGET, error = %e, "Publishing entity update.");
+                // if the key pattern doesnt match our subscribers key pattern, skip
+                // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
+                if clause.pattern_matching == PatternMatching::FixedLen
+                    && keys.len() != clause.keys.len()
+                {
+                    return false;
                 }
-            });
-        }
 
-        Poll::Pending
+                keys.iter().enumerate().all(|(idx, key)| {
+                    // this is going to be None if our key pattern overflows the subscriber
+                    // key pattern in this case we should skip
+                    let sub_key = clause.keys.get(idx);
+
+                    match sub_key {
+                        // the key in the subscriber must match the key of the entity
+                        // athis index
+                        Some(Some(sub_key)) => key == sub_key,
+                        // otherwise, if we have no key we should automatically match.
+                        // or.. we overflowed the subscriber key pattern
+                        // but we're in VariableLen pattern matching
+                        // so we should match all next keys
+                        _ => true,
+                    }
+                })
+            }
+        })
+}
+
+/// The schemas of the models of an entity.
+pub(crate) async fn entity_schemas(
+    pool: &Pool<Sqlite>,
+    cache: &ModelCache,
+    tables: EntityTables,
+    entity_id: &str,
+) -> Result<Vec<Ty>, Error> {
+    let EntityTables { table, model_relation_table, .. } = tables;
+    let models_query = format!(
+        r#"
+            SELECT group_concat({model_relation_table}.model_id) as model_ids
+            FROM {table}
+            JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
+            WHERE {table}.id = ?
+            GROUP BY {table}.id
+        "#
+    );
+    let (model_ids,): (String,) =
+        sqlx::query_as(&models_query).bind(entity_id).fetch_one(pool).await?;
+    let model_ids: Vec<Felt> = model_ids
+        .split(',')
+        .map(Felt::from_str)
+        .collect::<Result<_, _>>()
+        .map_err(ParseError::FromStr)?;
+
+    cache.schemas(&model_ids).await
+}
+
+/// An entity with the values of its models.
+pub(crate) async fn entity_with_models(
+    pool: &Pool<Sqlite>,
+    tables: EntityTables,
+    entity_id: &str,
+    schemas: Vec<Ty>,
+) -> Result<proto::types::Entity, Error> {
+    let EntityTables { table, entity_relation_column, .. } = tables;
+    let filter = format!("{table}.id = ?");
+    let (entity_query, arrays_queries, _) = build_sql_query(
+        &schemas,
+        table,
+        entity_relation_column,
+        Some(&filter),
+        Some(&filter),
+        None,
+        None,
+    )?;
+
+    let row = sqlx::query(&entity_query).bind(entity_id).fetch_one(pool).await?;
+    let mut arrays_rows = HashMap::new();
+    for (name, query) in arrays_queries {
+        let rows = sqlx::query(&query).bind(entity_id).fetch_all(pool).await?;
+        arrays_rows.insert(name, rows);
     }
+
+    map_row_to_entity(&row, &arrays_rows, schemas)
 }
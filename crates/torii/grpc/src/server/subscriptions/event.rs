@@ -8,6 +8,7 @@ use std::task::{Context, Poll};
 use futures::Stream;
 use futures_util::StreamExt;
 use rand::Rng;
+use sqlx::{Pool, Sqlite};
 use starknet::core::types::Felt;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::RwLock;
@@ -33,23 +34,47 @@ pub struct EventSubscriber {
 
 #[derive(Debug, Default)]
 pub struct EventManager {
-    subscribers: RwLock<HashMap<usize, EventSubscriber>>,
+    subscribers: Arc<RwLock<HashMap<usize, EventSubscriber>>>,
 }
 
 impl EventManager {
     pub async fn add_subscriber(
         &self,
         keys: KeysClause,
+        replay: Option<Replay>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEventsResponse, tonic::Status>>, Error> {
+        let mut subscribers = Arc::clone(&self.subscribers).write_owned().await;
         let id = rand::thread_rng().gen::<usize>();
         let (sender, receiver) = channel(1);
 
         // NOTE: unlock issue with firefox/safari
         // initially send empty stream message to return from
         // initial subscribe call
-        let _ = sender.send(Ok(SubscribeEventsResponse { event: None })).await;
-
-        self.subscribers.write().await.insert(id, EventSubscriber { keys, sender });
+        let _ =
+            sender.send(Ok(SubscribeEventsResponse { event: None, event_id: String::new() })).await;
+
+        let subscriber = EventSubscriber { keys, sender };
+        let Some(replay) = replay else {
+            subscribers.insert(id, subscriber);
+            return Ok(receiver);
+        };
+
+        // the receiver is only returned once subscribed, so the events are replayed in the
+        // background, holding the lock until the subscriber is added so that the live events
+        // are delivered after the replayed ones
+        tokio::spawn(async move {
+            match replay.run(&subscriber.keys, &subscriber.sender).await {
+                Ok(()) => {
+                    subscribers.insert(id, subscriber);
+                }
+                Err(e) => {
+                    error!(target = LOG_TARGET, error = %e, "Replaying events.");
+                    // the subscriber resumes again from its cursor
+                    let _ =
+                        subscriber.sender.send(Err(tonic::Status::internal(e.to_string()))).await;
+                }
+            }
+        });
 
         Ok(receiver)
     }
@@ -73,64 +98,15 @@ impl Service {
 
     async fn publish_updates(subs: Arc<EventManager>, event: &Event) -> Result<(), Error> {
         let mut closed_stream = Vec::new();
-        let keys = event
-            .keys
-            .trim_end_matches(FELT_DELIMITER)
-            .split(FELT_DELIMITER)
-            .map(Felt::from_str)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(ParseError::from)?;
-        let data = event
-            .data
-            .trim_end_matches(FELT_DELIMITER)
-            .split(FELT_DELIMITER)
-            .map(Felt::from_str)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(ParseError::from)?;
+        let resp = event_response(event)?;
+        let keys = parse_felts(&event.keys)?;
 
         for (idx, sub) in subs.subscribers.read().await.iter() {
-            // if the key pattern doesnt match our subscribers key pattern, skip
-            // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
-            if sub.keys.pattern_matching == PatternMatching::FixedLen
-                && keys.len() != sub.keys.keys.len()
-            {
-                continue;
-            }
-
-            if !keys.iter().enumerate().all(|(idx, key)| {
-                // this is going to be None if our key pattern overflows the subscriber key pattern
-                // in this case we might want to list all events with the same
-                // key selector so we can match them all
-                let sub_key = sub.keys.keys.get(idx);
-
-                // if we have a key in the subscriber, it must match the key in the event
-                // unless its empty, which is a wildcard
-                match sub_key {
-                    // the key in the subscriber must match the key of the entity
-                    // athis index
-                    Some(Some(sub_key)) => key == sub_key,
-                    // otherwise, if we have no key we should automatically match.
-                    // or.. we overflowed the subscriber key pattern
-                    // but we're in VariableLen pattern matching
-                    // so we should match all next keys
-                    _ => true,
-                }
-            }) {
+            if !match_keys(&sub.keys, &keys) {
                 continue;
             }
 
-            let resp = proto::world::SubscribeEventsResponse {
-                event: Some(proto::types::Event {
-                    keys: keys.iter().map(|k| k.to_bytes_be().to_vec()).collect(),
-                    data: data.iter().map(|d| d.to_bytes_be().to_vec()).collect(),
-                    transaction_hash: Felt::from_str(&event.transaction_hash)
-                        .map_err(ParseError::from)?
-                        .to_bytes_be()
-                        .to_vec(),
-                }),
-            };
-
-            if sub.sender.send(Ok(resp)).await.is_err() {
+            if sub.sender.send(Ok(resp.clone())).await.is_err() {
                 closed_stream.push(*idx);
             }
         }
@@ -144,6 +120,103 @@ impl Service {
     }
 }
 
+/// Replays the events missed by a subscriber since its cursor.
+#[derive(Debug)]
+pub struct Replay {
+    pub(crate) pool: Pool<Sqlite>,
+    /// Id of the last event received by the subscriber.
+    pub(crate) cursor: String,
+}
+
+impl Replay {
+    /// Sends the events since the block of the cursor. The events of this block that were
+    /// already received are sent again, subscribers skip them with their id.
+    pub(crate) async fn run(
+        &self,
+        keys: &KeysClause,
+        sender: &Sender<Result<proto::world::SubscribeEventsResponse, tonic::Status>>,
+    ) -> Result<(), Error> {
+        // event ids are prefixed by their block number, but the transactions are not ordered in
+        // a block, so the whole block of the cursor is replayed
+        let block = self.cursor.split(':').next().unwrap_or_default();
+        let events: Vec<Event> = sqlx::query_as("SELECT * FROM events WHERE id >= ? ORDER BY id")
+            .bind(block)
+            .fetch_all(&self.pool)
+            .await?;
+
+        trace!(
+            target = LOG_TARGET,
+            cursor = %self.cursor,
+            count = events.len(),
+            "Replaying events."
+        );
+
+        for event in events {
+            if !match_keys(keys, &parse_felts(&event.keys)?) {
+                continue;
+            }
+
+            if sender.send(Ok(event_response(&event)?)).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_felts(felts: &str) -> Result<Vec<Felt>, Error> {
+    Ok(felts
+        .trim_end_matches(FELT_DELIMITER)
+        .split(FELT_DELIMITER)
+        .map(Felt::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ParseError::from)?)
+}
+
+fn event_response(event: &Event) -> Result<SubscribeEventsResponse, Error> {
+    Ok(SubscribeEventsResponse {
+        event: Some(proto::types::Event {
+            keys: parse_felts(&event.keys)?.iter().map(|k| k.to_bytes_be().to_vec()).collect(),
+            data: parse_felts(&event.data)?.iter().map(|d| d.to_bytes_be().to_vec()).collect(),
+            transaction_hash: Felt::from_str(&event.transaction_hash)
+                .map_err(ParseError::from)?
+                .to_bytes_be()
+                .to_vec(),
+        }),
+        event_id: event.id.clone(),
+    })
+}
+
+/// Whether the keys of an event match the keys clause of a subscriber.
+fn match_keys(clause: &KeysClause, keys: &[Felt]) -> bool {
+    // if the key pattern doesnt match our subscribers key pattern, skip
+    // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
+    if clause.pattern_matching == PatternMatching::FixedLen && keys.len() != clause.keys.len() {
+        return false;
+    }
+
+    keys.iter().enumerate().all(|(idx, key)| {
+        // this is going to be None if our key pattern overflows the subscriber key pattern
+        // in this case we might want to list all events with the same
+        // key selector so we can match them all
+        let sub_key = clause.keys.get(idx);
+
+        // if we have a key in the subscriber, it must match the key in the event
+        // unless its empty, which is a wildcard
+        match sub_key {
+            // the key in the subscriber must match the key of the entity
+            // athis index
+            Some(Some(sub_key)) => key == sub_key,
+            // otherwise, if we have no key we should automatically match.
+            // or.. we overflowed the subscriber key pattern
+            // but we're in VariableLen pattern matching
+            // so we should match all next keys
+            _ => true,
+        }
+    })
+}
+
 impl Future for Service {
     type Output = ();
 
@@ -1,60 +1,60 @@
-use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::str::FromStr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use futures::Stream;
 use futures_util::StreamExt;
-use rand::Rng;
 use sqlx::{Pool, Sqlite};
-use starknet::core::types::Felt;
-use tokio::sync::mpsc::{channel, Receiver};
-use tokio::sync::RwLock;
+use tokio::sync::mpsc::Receiver;
 use torii_core::cache::ModelCache;
-use torii_core::error::{Error, ParseError};
-use torii_core::model::build_sql_query;
+use torii_core::error::Error;
 use torii_core::simple_broker::SimpleBroker;
-use torii_core::sql::FELT_DELIMITER;
 use torii_core::types::EventMessage;
 use tracing::{error, trace};
 
-use super::entity::EntitiesSubscriber;
+use super::entity::{
+    add_entities_subscriber, publish_entity_update, EntitiesSubscriber, EntitiesSubscribers,
+    EntityTables, PublishedUpdate, Replay, SubscriptionClause,
+};
 use crate::proto;
-use crate::proto::world::SubscribeEntityResponse;
-use crate::server::map_row_to_entity;
-use crate::types::{EntityKeysClause, PatternMatching};
+use crate::server::{
+    EVENT_MESSAGES_ENTITY_RELATION_COLUMN, EVENT_MESSAGES_MODEL_RELATION_TABLE,
+    EVENT_MESSAGES_TABLE,
+};
+use crate::types::EntityKeysClause;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::event_message";
 
+pub(crate) const EVENT_MESSAGE_TABLES: EntityTables = EntityTables {
+    table: EVENT_MESSAGES_TABLE,
+    model_relation_table: EVENT_MESSAGES_MODEL_RELATION_TABLE,
+    entity_relation_column: EVENT_MESSAGES_ENTITY_RELATION_COLUMN,
+    tombstones_table: None,
+};
+
 #[derive(Debug, Default)]
 pub struct EventMessageManager {
-    subscribers: RwLock<HashMap<u64, EntitiesSubscriber>>,
+    subscribers: EntitiesSubscribers,
 }
 
 impl EventMessageManager {
     pub async fn add_subscriber(
         &self,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<SubscriptionClause>,
+        subscription_id: Option<u64>,
+        replay: Option<Replay>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
-        let subscription_id = rand::thread_rng().gen::<u64>();
-        let (sender, receiver) = channel(1);
-
-        // NOTE: unlock issue with firefox/safari
-        // initially send empty stream message to return from
-        // initial subscribe call
-        let _ = sender.send(Ok(SubscribeEntityResponse { entity: None, subscription_id })).await;
-
-        self.subscribers
-            .write()
-            .await
-            .insert(subscription_id, EntitiesSubscriber { clauses, sender });
-
-        Ok(receiver)
+        add_entities_subscriber(&self.subscribers, clauses, clause, subscription_id, replay).await
     }
 
-    pub async fn update_subscriber(&self, id: u64, clauses: Vec<EntityKeysClause>) {
+    pub async fn update_subscriber(
+        &self,
+        id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<SubscriptionClause>,
+    ) {
         let sender = {
             let subscribers = self.subscribers.read().await;
             if let Some(subscriber) = subscribers.get(&id) {
@@ -64,7 +64,7 @@ impl EventMessageManager {
             }
         };
 
-        self.subscribers.write().await.insert(id, EntitiesSubscriber { clauses, sender });
+        self.subscribers.write().await.insert(id, EntitiesSubscriber { clauses, clause, sender });
     }
 
     pub(super) async fn remove_subscriber(&self, id: u64) {
@@ -101,130 +101,21 @@ impl Service {
         pool: Pool<Sqlite>,
         entity: &EventMessage,
     ) -> Result<(), Error> {
-        let mut closed_stream = Vec::new();
-        let hashed = Felt::from_str(&entity.id).map_err(ParseError::FromStr)?;
-        let keys = entity
-            .keys
-            .trim_end_matches(FELT_DELIMITER)
-            .split(FELT_DELIMITER)
-            .map(Felt::from_str)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(ParseError::FromStr)?;
-
-        for (idx, sub) in subs.subscribers.read().await.iter() {
-            // Check if the subscriber is interested in this entity
-            // If we have a clause of hashed keys, then check that the id of the entity
-            // is in the list of hashed keys.
-
-            // If we have a clause of keys, then check that the key pattern of the entity
-            // matches the key pattern of the subscriber.
-            if !sub.clauses.is_empty()
-                && !sub.clauses.iter().any(|clause| match clause {
-                    EntityKeysClause::HashedKeys(hashed_keys) => {
-                        hashed_keys.is_empty() || hashed_keys.contains(&hashed)
-                    }
-                    EntityKeysClause::Keys(clause) => {
-                        // if we have a model clause, then we need to check that the entity
-                        // has an updated model and that the model name matches the clause
-                        if let Some(updated_model) = &entity.updated_model {
-                            let name = updated_model.name();
-                            let (namespace, name) = name.split_once('-').unwrap();
-
-                            if !clause.models.is_empty()
-                                && !clause.models.iter().any(|clause_model| {
-                                    let (clause_namespace, clause_model) =
-                                        clause_model.split_once('-').unwrap();
-                                    // if both namespace and model are empty, we should match all.
-                                    // if namespace is specified and model is empty or * we should
-                                    // match all models in the
-                                    // namespace if namespace
-                                    // and model are specified, we should match the
-                                    // specific model
-                                    (clause_namespace.is_empty()
-                                        || clause_namespace == namespace
-                                        || clause_namespace == "*")
-                                        && (clause_model.is_empty()
-                                            || clause_model == name
-                                            || clause_model == "*")
-                                })
-                            {
-                                return false;
-                            }
-                        }
-
-                        // if the key pattern doesnt match our subscribers key pattern, skip
-                        // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
-                        if clause.pattern_matching == PatternMatching::FixedLen
-                            && keys.len() != clause.keys.len()
-                        {
-                            return false;
-                        }
-
-                        return keys.iter().enumerate().all(|(idx, key)| {
-                            // this is going to be None if our key pattern overflows the subscriber
-                            // key pattern in this case we should skip
-                            let sub_key = clause.keys.get(idx);
-
-                            match sub_key {
-                                // the key in the subscriber must match the key of the entity
-                                // athis index
-                                Some(Some(sub_key)) => key == sub_key,
-                                // otherwise, if we have no key we should automatically match.
-                                // or.. we overflowed the subscriber key pattern
-                                // but we're in VariableLen pattern matching
-                                // so we should match all next keys
-                                _ => true,
-                            }
-                        });
-                    }
-                })
-            {
-                continue;
-            }
-
-            // publish all updates if ids is empty or only ids that are subscribed to
-            let models_query = r#"
-                    SELECT group_concat(event_model.model_id) as model_ids
-                    FROM event_messages
-                    JOIN event_model ON event_messages.id = event_model.entity_id
-                    WHERE event_messages.id = ?
-                    GROUP BY event_messages.id
-                "#;
-            let (model_ids,): (String,) =
-                sqlx::query_as(models_query).bind(&entity.id).fetch_one(&pool).await?;
-            let model_ids: Vec<Felt> = model_ids
-                .split(',')
-                .map(Felt::from_str)
-                .collect::<Result<_, _>>()
-                .map_err(ParseError::FromStr)?;
-            let schemas = cache.schemas(&model_ids).await?;
-
-            let (entity_query, arrays_queries, _) = build_sql_query(
-                &schemas,
-                "event_messages",
-                "event_message_id",
-                Some("event_messages.id = ?"),
-                Some("event_messages.id = ?"),
-                None,
-                None,
-            )?;
-
-            let row = sqlx::query(&entity_query).bind(&entity.id).fetch_one(&pool).await?;
-            let mut arrays_rows = HashMap::new();
-            for (name, query) in arrays_queries {
-                let rows = sqlx::query(&query).bind(&entity.id).fetch_all(&pool).await?;
-                arrays_rows.insert(name, rows);
-            }
-
-            let resp = proto::world::SubscribeEntityResponse {
-                entity: Some(map_row_to_entity(&row, &arrays_rows, schemas.clone())?),
-                subscription_id: *idx,
-            };
-
-            if sub.sender.send(Ok(resp)).await.is_err() {
-                closed_stream.push(*idx);
-            }
-        }
+        let update = PublishedUpdate {
+            id: &entity.id,
+            keys: &entity.keys,
+            event_id: &entity.event_id,
+            updated_model: entity.updated_model.as_ref(),
+            deleted: entity.deleted,
+        };
+        let closed_stream = publish_entity_update(
+            &*subs.subscribers.read().await,
+            &pool,
+            &cache,
+            EVENT_MESSAGE_TABLES,
+            update,
+        )
+        .await?;
 
         for id in closed_stream {
             trace!(target = LOG_TARGET, id = %id, "Closing entity stream.");
@@ -3,3 +3,4 @@ pub mod error;
 pub mod event;
 pub mod event_message;
 pub mod model_diff;
+pub mod model_registration;
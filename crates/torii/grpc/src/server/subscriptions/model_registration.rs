@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use futures_util::StreamExt;
+use rand::Rng;
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::Felt;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::RwLock;
+use torii_core::cache::ModelCache;
+use torii_core::error::{Error, ParseError};
+use torii_core::simple_broker::SimpleBroker;
+use torii_core::types::Model;
+use tracing::{error, trace};
+
+use crate::proto;
+use crate::proto::world::SubscribeModelRegistrationsResponse;
+use crate::server::model_metadata;
+
+pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::model_registration";
+
+#[derive(Debug)]
+pub struct ModelRegistrationSubscriber {
+    /// Namespaces of the models that the subscriber is interested in, all of them if empty.
+    namespaces: Vec<String>,
+    /// The channel to send the response back to the subscriber.
+    sender: Sender<Result<proto::world::SubscribeModelRegistrationsResponse, tonic::Status>>,
+}
+
+#[derive(Debug, Default)]
+pub struct ModelRegistrationManager {
+    subscribers: RwLock<HashMap<usize, ModelRegistrationSubscriber>>,
+}
+
+impl ModelRegistrationManager {
+    pub async fn add_subscriber(
+        &self,
+        namespaces: Vec<String>,
+    ) -> Result<
+        Receiver<Result<proto::world::SubscribeModelRegistrationsResponse, tonic::Status>>,
+        Error,
+    > {
+        let id = rand::thread_rng().gen::<usize>();
+        let (sender, receiver) = channel(1);
+
+        // NOTE: unlock issue with firefox/safari
+        // initially send empty stream message to return from
+        // initial subscribe call
+        let _ = sender.send(Ok(SubscribeModelRegistrationsResponse { model: None })).await;
+
+        self.subscribers
+            .write()
+            .await
+            .insert(id, ModelRegistrationSubscriber { namespaces, sender });
+
+        Ok(receiver)
+    }
+
+    pub(super) async fn remove_subscriber(&self, id: usize) {
+        self.subscribers.write().await.remove(&id);
+    }
+}
+
+#[must_use = "Service does nothing unless polled"]
+#[allow(missing_debug_implementations)]
+pub struct Service {
+    pool: Pool<Sqlite>,
+    subs_manager: Arc<ModelRegistrationManager>,
+    model_cache: Arc<ModelCache>,
+    simple_broker: Pin<Box<dyn Stream<Item = Model> + Send>>,
+}
+
+impl Service {
+    pub fn new(
+        pool: Pool<Sqlite>,
+        subs_manager: Arc<ModelRegistrationManager>,
+        model_cache: Arc<ModelCache>,
+    ) -> Self {
+        Self {
+            pool,
+            subs_manager,
+            model_cache,
+            simple_broker: Box::pin(SimpleBroker::<Model>::subscribe()),
+        }
+    }
+
+    async fn publish_updates(
+        subs: Arc<ModelRegistrationManager>,
+        cache: Arc<ModelCache>,
+        pool: Pool<Sqlite>,
+        model: &Model,
+    ) -> Result<(), Error> {
+        let mut closed_stream = Vec::new();
+        let selector = Felt::from_str(&model.id).map_err(ParseError::FromStr)?;
+
+        // the model is re-registered on upgrades, its cached schema is outdated
+        cache.invalidate(&selector).await;
+        let resp = SubscribeModelRegistrationsResponse {
+            model: Some(model_metadata(&pool, &cache, selector).await?),
+        };
+
+        for (idx, sub) in subs.subscribers.read().await.iter() {
+            if !sub.namespaces.is_empty() && !sub.namespaces.contains(&model.namespace) {
+                continue;
+            }
+
+            if sub.sender.send(Ok(resp.clone())).await.is_err() {
+                closed_stream.push(*idx);
+            }
+        }
+
+        for id in closed_stream {
+            trace!(target = LOG_TARGET, id = %id, "Closing model registrations stream.");
+            subs.remove_subscriber(id).await
+        }
+
+        Ok(())
+    }
+}
+
+impl Future for Service {
+    type Output = ();
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<Self::Output> {
+        let pin = self.get_mut();
+
+        while let Poll::Ready(Some(model)) = pin.simple_broker.poll_next_unpin(cx) {
+            let subs = Arc::clone(&pin.subs_manager);
+            let cache = Arc::clone(&pin.model_cache);
+            let pool = pin.pool.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Service::publish_updates(subs, cache, pool, &model).await {
+                    error!(target = LOG_TARGET, error = %e, "Publishing model registration.");
+                }
+            });
+        }
+
+        Poll::Pending
+    }
+}
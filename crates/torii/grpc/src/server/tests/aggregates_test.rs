@@ -0,0 +1,162 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::proto;
+use crate::proto::types::aggregate_value::Value;
+use crate::proto::types::clause::ClauseType;
+use crate::proto::types::AggregateFunction;
+use crate::server::DojoWorld;
+
+fn player(address: u8, team: u8, score: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: "ns-Player".to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::from(address)))),
+                key: true,
+            },
+            Member {
+                name: "team".to_string(),
+                ty: Ty::Primitive(Primitive::U8(Some(team))),
+                key: false,
+            },
+            Member {
+                name: "score".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(score))),
+                key: false,
+            },
+        ],
+    })
+}
+
+async fn setup() -> DojoWorld {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+
+    let mut model = player(0, 0, 0);
+    if let Ty::Struct(s) = &mut model {
+        s.name = "Player".to_string();
+    }
+    db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+        .await
+        .unwrap();
+
+    for (idx, (address, team, score)) in [(1, 1, 10), (2, 1, 30), (3, 2, 5)].into_iter().enumerate()
+    {
+        db.set_entity(player(address, team, score), &format!("{idx:#x}"), 0).await.unwrap();
+    }
+    db.execute().await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+
+    DojoWorld::new(pool, block_rx, Felt::ZERO, Arc::new(provider))
+}
+
+fn aggregate(function: AggregateFunction, member: &str) -> proto::types::Aggregate {
+    proto::types::Aggregate { function: function as i32, member: member.to_string() }
+}
+
+fn values(group: &proto::types::AggregateGroup) -> Vec<Option<Value>> {
+    group.values.iter().map(|value| value.value.clone()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_retrieve_aggregates() {
+    let world = setup().await;
+
+    let response = world
+        .retrieve_aggregates(proto::types::AggregateQuery {
+            model: "ns-Player".to_string(),
+            clause: None,
+            aggregates: vec![
+                aggregate(AggregateFunction::Count, ""),
+                aggregate(AggregateFunction::Sum, "score"),
+                aggregate(AggregateFunction::Avg, "score"),
+                aggregate(AggregateFunction::Max, "score"),
+            ],
+            group_by: vec!["team".to_string()],
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response.groups.len(), 2);
+
+    let team = |team: u8| {
+        proto::types::Member::from(Member {
+            name: "team".to_string(),
+            ty: Ty::Primitive(Primitive::U8(Some(team))),
+            key: false,
+        })
+    };
+    let score = |score: u32| Some(Value::Ty(Ty::Primitive(Primitive::U32(Some(score))).into()));
+
+    assert_eq!(response.groups[0].group, vec![team(1)]);
+    assert_eq!(
+        values(&response.groups[0]),
+        vec![
+            Some(Value::Count(2)),
+            Some(Value::Number(40.0)),
+            Some(Value::Number(20.0)),
+            score(30)
+        ]
+    );
+
+    assert_eq!(response.groups[1].group, vec![team(2)]);
+    assert_eq!(
+        values(&response.groups[1]),
+        vec![Some(Value::Count(1)), Some(Value::Number(5.0)), Some(Value::Number(5.0)), score(5)]
+    );
+
+    // without group by, a single group aggregates the rows matching the clause
+    let response = world
+        .retrieve_aggregates(proto::types::AggregateQuery {
+            model: "ns-Player".to_string(),
+            clause: Some(proto::types::Clause {
+                clause_type: Some(ClauseType::Member(proto::types::MemberClause {
+                    model: "ns-Player".to_string(),
+                    member: "score".to_string(),
+                    operator: proto::types::ComparisonOperator::Gt as i32,
+                    value: Some(Primitive::U32(Some(6)).into()),
+                })),
+            }),
+            aggregates: vec![
+                aggregate(AggregateFunction::Count, ""),
+                aggregate(AggregateFunction::Min, "score"),
+            ],
+            group_by: vec![],
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response.groups.len(), 1);
+    assert!(response.groups[0].group.is_empty());
+    assert_eq!(values(&response.groups[0]), vec![Some(Value::Count(2)), score(10)]);
+
+    // only numbers can be summed
+    assert!(world
+        .retrieve_aggregates(proto::types::AggregateQuery {
+            model: "ns-Player".to_string(),
+            clause: None,
+            aggregates: vec![aggregate(AggregateFunction::Sum, "player")],
+            group_by: vec![],
+        })
+        .await
+        .is_err());
+}
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet_crypto::poseidon_hash_many;
+use tokio::sync::mpsc::Receiver;
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::proto;
+use crate::proto::types::clause::ClauseType;
+use crate::proto::types::EntityUpdateType;
+use crate::proto::world::SubscribeEntityResponse;
+use crate::server::subscriptions::entity::{match_clauses, SubscriptionClause, ENTITY_TABLES};
+use crate::server::{
+    build_subscription_condition, clause_models, decode_cursor, encode_cursor, member_column,
+    model_clause_condition, next_page_cursor, order_column, DojoWorld,
+};
+
+const MODEL: &str = "ns-Player";
+
+fn schema() -> Ty {
+    let vec2 = Ty::Struct(Struct {
+        name: "Vec2".to_string(),
+        children: vec![
+            Member { name: "x".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+            Member { name: "y".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+        ],
+    });
+
+    Ty::Struct(Struct {
+        name: MODEL.to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                key: true,
+            },
+            Member { name: "position".to_string(), ty: vec2.clone(), key: false },
+            Member {
+                name: "direction".to_string(),
+                ty: Ty::Enum(Enum {
+                    name: "Direction".to_string(),
+                    option: None,
+                    options: vec![
+                        EnumOption { name: "None".to_string(), ty: Ty::Tuple(vec![]) },
+                        EnumOption { name: "Left".to_string(), ty: Ty::Tuple(vec![]) },
+                    ],
+                }),
+                key: false,
+            },
+            Member {
+                name: "scores".to_string(),
+                ty: Ty::Array(vec![Ty::Primitive(Primitive::U8(None))]),
+                key: false,
+            },
+            Member { name: "path".to_string(), ty: Ty::Array(vec![vec2]), key: false },
+            Member {
+                name: "pair".to_string(),
+                ty: Ty::Tuple(vec![
+                    Ty::Primitive(Primitive::U8(None)),
+                    Ty::Primitive(Primitive::U16(None)),
+                ]),
+                key: false,
+            },
+        ],
+    })
+}
+
+#[test]
+fn test_member_column() {
+    let schema = schema();
+
+    let column = member_column(MODEL, &schema, "player").unwrap();
+    assert_eq!((column.table.as_str(), column.name.as_str()), (MODEL, "player"));
+
+    let column = member_column(MODEL, &schema, "position.x").unwrap();
+    assert_eq!((column.table.as_str(), column.name.as_str()), ("ns-Player$position", "x"));
+    assert!(!column.in_array);
+
+    let column = member_column(MODEL, &schema, "path.y").unwrap();
+    assert_eq!((column.table.as_str(), column.name.as_str()), ("ns-Player$path$data", "y"));
+    assert!(column.in_array);
+
+    let column = member_column(MODEL, &schema, "pair.1").unwrap();
+    assert_eq!((column.table.as_str(), column.name.as_str()), ("ns-Player$pair", "_1"));
+
+    assert!(member_column(MODEL, &schema, "position.z").is_err());
+    assert!(member_column(MODEL, &schema, "player.x").is_err());
+}
+
+#[test]
+fn test_model_clause_condition() {
+    let schema = schema();
+
+    let (condition, values) = model_clause_condition(
+        MODEL,
+        &schema,
+        &ClauseType::Member(proto::types::MemberClause {
+            model: MODEL.to_string(),
+            member: "position.x".to_string(),
+            operator: proto::types::ComparisonOperator::Gt as i32,
+            value: Some(Primitive::U32(Some(3)).into()),
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        condition,
+        "[ns-Player].id IN (SELECT id FROM [ns-Player$position] WHERE external_x > ?)"
+    );
+    assert_eq!(values, vec!["3"]);
+
+    let (condition, values) = model_clause_condition(
+        MODEL,
+        &schema,
+        &ClauseType::Enum(proto::types::EnumClause {
+            model: MODEL.to_string(),
+            member: "direction".to_string(),
+            operator: proto::types::ComparisonOperator::Neq as i32,
+            option: "Left".to_string(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(condition, "[ns-Player].external_direction != ?");
+    assert_eq!(values, vec!["Left"]);
+
+    // empty arrays match a length lower than 2
+    let (condition, _) = model_clause_condition(
+        MODEL,
+        &schema,
+        &ClauseType::Array(proto::types::ArrayClause {
+            model: MODEL.to_string(),
+            member: "scores".to_string(),
+            predicate: Some(proto::types::array_clause::Predicate::Length(
+                proto::types::ArrayLength {
+                    operator: proto::types::ComparisonOperator::Lt as i32,
+                    value: 2,
+                },
+            )),
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        condition,
+        "[ns-Player].id NOT IN (SELECT id FROM [ns-Player$scores] GROUP BY id HAVING NOT \
+         (COUNT(*) < 2))"
+    );
+
+    // arrays of structs can't be matched by value
+    assert!(model_clause_condition(
+        MODEL,
+        &schema,
+        &ClauseType::Array(proto::types::ArrayClause {
+            model: MODEL.to_string(),
+            member: "path".to_string(),
+            predicate: Some(proto::types::array_clause::Predicate::Contains(
+                Primitive::U32(Some(1)).into()
+            )),
+        }),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_build_subscription_condition() {
+    let member = proto::types::Clause {
+        clause_type: Some(ClauseType::Member(proto::types::MemberClause {
+            model: MODEL.to_string(),
+            member: "position.x".to_string(),
+            operator: proto::types::ComparisonOperator::Lt as i32,
+            value: Some(Primitive::U32(Some(5)).into()),
+        })),
+    };
+    let hashed_keys = proto::types::Clause {
+        clause_type: Some(ClauseType::HashedKeys(proto::types::HashedKeysClause {
+            hashed_keys: vec![Felt::ONE.to_bytes_be().to_vec()],
+        })),
+    };
+    let clause = proto::types::Clause {
+        clause_type: Some(ClauseType::Composite(proto::types::CompositeClause {
+            operator: proto::types::LogicalOperator::Or as i32,
+            clauses: vec![member, hashed_keys],
+        })),
+    };
+
+    let mut models = Vec::new();
+    clause_models(&clause, &mut models).unwrap();
+    assert_eq!(models, vec![MODEL]);
+
+    let schemas = HashMap::from([(MODEL.to_string(), schema())]);
+    let mut bind_values = Vec::new();
+    let condition =
+        build_subscription_condition(ENTITY_TABLES, &schemas, clause, &mut bind_values).unwrap();
+    assert_eq!(
+        condition,
+        "([entities].id IN (SELECT [ns-Player].entity_id FROM [ns-Player] WHERE [ns-Player].id IN \
+         (SELECT id FROM [ns-Player$position] WHERE external_x < ?)) OR [entities].id IN ('0x1'))"
+    );
+    assert_eq!(bind_values, vec!["5"]);
+}
+
+#[test]
+fn test_order_column() {
+    let schema = schema();
+
+    let column = order_column(MODEL, &schema, "direction", "entities", "entity_id").unwrap();
+    assert_eq!(column.expr, "[ns-Player].external_direction");
+    assert_eq!(column.placeholder, "?");
+    assert_eq!(column.join, "JOIN [ns-Player] ON [ns-Player].entity_id = [entities].id");
+
+    // integers are compared as integers with the cursor value
+    let column = order_column(MODEL, &schema, "position.y", "entities", "entity_id").unwrap();
+    assert_eq!(
+        column.expr,
+        "(SELECT external_y FROM [ns-Player$position] WHERE id = [ns-Player].id)"
+    );
+    assert_eq!(column.placeholder, "CAST(? AS INTEGER)");
+
+    // members with many values per entity can't be ordered by
+    assert!(order_column(MODEL, &schema, "position", "entities", "entity_id").is_err());
+    assert!(order_column(MODEL, &schema, "scores", "entities", "entity_id").is_err());
+    assert!(order_column(MODEL, &schema, "path.x", "entities", "entity_id").is_err());
+}
+
+#[test]
+fn test_cursor() {
+    let cursor = encode_cursor("0x1", Some("2024-01-01 00:00:00"));
+    assert_eq!(
+        decode_cursor(&cursor).unwrap(),
+        ("0x1".to_string(), Some("2024-01-01 00:00:00".to_string()))
+    );
+
+    let cursor = encode_cursor("0x2", Some("a/b"));
+    assert_eq!(decode_cursor(&cursor).unwrap(), ("0x2".to_string(), Some("a/b".to_string())));
+
+    // null values are told apart from empty ones
+    let cursor = encode_cursor("0x3", None);
+    assert_eq!(decode_cursor(&cursor).unwrap(), ("0x3".to_string(), None));
+    let cursor = encode_cursor("0x3", Some(""));
+    assert_eq!(decode_cursor(&cursor).unwrap(), ("0x3".to_string(), Some(String::new())));
+
+    assert!(decode_cursor("not a cursor").is_err());
+
+    let mut rows = vec![1, 2, 3];
+    assert_eq!(next_page_cursor(&mut rows, 3, |row| row.to_string()), "");
+    assert_eq!(next_page_cursor(&mut rows, 2, |row| row.to_string()), "2");
+    assert_eq!(rows, vec![1, 2]);
+}
+
+fn moves(name: &str, player: Felt, remaining: u8) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                key: true,
+            },
+            Member {
+                name: "remaining".to_string(),
+                ty: Ty::Primitive(Primitive::U8(Some(remaining))),
+                key: false,
+            },
+        ],
+    })
+}
+
+fn remaining_lt(value: u8) -> proto::types::Clause {
+    proto::types::Clause {
+        clause_type: Some(ClauseType::Member(proto::types::MemberClause {
+            model: "ns-Moves".to_string(),
+            member: "remaining".to_string(),
+            operator: proto::types::ComparisonOperator::Lt as i32,
+            value: Some(Primitive::U8(Some(value)).into()),
+        })),
+    }
+}
+
+async fn next_update(
+    rx: &mut Receiver<Result<SubscribeEntityResponse, tonic::Status>>,
+) -> (Option<Felt>, EntityUpdateType) {
+    let res = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap();
+    let res = res.unwrap().unwrap();
+    let hashed_keys =
+        res.entity.as_ref().map(|entity| Felt::from_bytes_be_slice(&entity.hashed_keys));
+    (hashed_keys, res.update_type())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subscription_clause_matching() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    let model = moves("Moves", Felt::ZERO, 0);
+    db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+        .await
+        .unwrap();
+
+    let (alice, bob) = (Felt::from(0xc1a05e_u64), Felt::from(0xc1a05f_u64));
+    let (alice_id, bob_id) = (poseidon_hash_many(&[alice]), poseidon_hash_many(&[bob]));
+    db.set_entity(moves("ns-Moves", alice, 3), "0x1", 0).await.unwrap();
+    db.set_entity(moves("ns-Moves", bob, 10), "0x1", 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let world = DojoWorld::new(pool.clone(), block_rx, Felt::ZERO, Arc::new(provider));
+
+    // the clauses are evaluated together, a failing clause only skipping its subscriber
+    let lt_5 = world.subscription_clause(ENTITY_TABLES, Some(remaining_lt(5))).await.unwrap();
+    let lt_2 = world.subscription_clause(ENTITY_TABLES, Some(remaining_lt(2))).await.unwrap();
+    let (lt_5, lt_2) = (lt_5.unwrap(), lt_2.unwrap());
+    let failing = SubscriptionClause::new("[missing].id IS NOT NULL".to_string(), vec![]);
+    let alice_key = format!("{:#x}", alice_id);
+    assert_eq!(
+        match_clauses(&pool, ENTITY_TABLES, &alice_key, &[&lt_5, &failing, &lt_2]).await,
+        vec![Some(true), None, Some(false)]
+    );
+    assert_eq!(
+        match_clauses(&pool, ENTITY_TABLES, &format!("{:#x}", bob_id), &[&lt_5, &lt_2]).await,
+        vec![Some(false), Some(false)]
+    );
+
+    // the subscriber is notified of the entities matching the clause, and once of those which
+    // no longer match it
+    let mut rx =
+        world.subscribe_entities(vec![], Some(remaining_lt(5)), 0, String::new()).await.unwrap();
+    assert_eq!(next_update(&mut rx).await, (None, EntityUpdateType::Updated));
+
+    db.set_entity(moves("ns-Moves", bob, 4), "0x2", 0).await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(next_update(&mut rx).await, (Some(bob_id), EntityUpdateType::Updated));
+
+    db.set_entity(moves("ns-Moves", alice, 8), "0x3", 0).await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(next_update(&mut rx).await, (Some(alice_id), EntityUpdateType::Unmatched));
+
+    // entities not matching the clause are left out
+    db.set_entity(moves("ns-Moves", alice, 9), "0x4", 0).await.unwrap();
+    db.execute().await.unwrap();
+    db.set_entity(moves("ns-Moves", bob, 1), "0x5", 0).await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(next_update(&mut rx).await, (Some(bob_id), EntityUpdateType::Updated));
+}
+
+fn order_by(member: &str, direction: proto::types::OrderDirection) -> proto::types::OrderBy {
+    proto::types::OrderBy {
+        model: if member.is_empty() { String::new() } else { "ns-Moves".to_string() },
+        member: member.to_string(),
+        direction: direction as i32,
+    }
+}
+
+// pages through the entities, returning their hashed keys
+async fn paginate(world: &DojoWorld, order_by: proto::types::OrderBy, limit: u32) -> Vec<Felt> {
+    let mut hashed_keys = Vec::new();
+    let mut cursor = String::new();
+    loop {
+        let query = proto::types::Query {
+            clause: None,
+            limit,
+            offset: 0,
+            cursor,
+            order_by: Some(order_by.clone()),
+        };
+        let response = world.retrieve_entities(query).await.unwrap();
+        assert!(response.entities.len() <= limit as usize);
+        hashed_keys.extend(
+            response.entities.iter().map(|entity| Felt::from_bytes_be_slice(&entity.hashed_keys)),
+        );
+
+        if response.next_cursor.is_empty() {
+            return hashed_keys;
+        }
+        cursor = response.next_cursor;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_paginate_entities() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    let model = moves("Moves", Felt::ZERO, 0);
+    db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+        .await
+        .unwrap();
+
+    // `100` is before `9` as text, and two players have the same value
+    let players = [(0x1_u64, 9), (0x2, 100), (0x3, 10), (0x4, 10)];
+    let mut ids = Vec::new();
+    for (player, remaining) in players {
+        let player = Felt::from(player);
+        ids.push(poseidon_hash_many(&[player]));
+        db.set_entity(moves("ns-Moves", player, remaining), "0x1", 0).await.unwrap();
+    }
+    db.execute().await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let world = DojoWorld::new(pool.clone(), block_rx, Felt::ZERO, Arc::new(provider));
+
+    // entities are ordered by their id by default
+    let mut by_id = ids.clone();
+    by_id.sort();
+    assert_eq!(paginate(&world, order_by("", proto::types::OrderDirection::Asc), 3).await, by_id);
+    by_id.reverse();
+    assert_eq!(paginate(&world, order_by("", proto::types::OrderDirection::Desc), 1).await, by_id);
+
+    // members are compared with their type, the id breaking ties
+    let (low, high) = if ids[2] < ids[3] { (ids[2], ids[3]) } else { (ids[3], ids[2]) };
+    let ascending = vec![ids[0], low, high, ids[1]];
+    for limit in 1..=4 {
+        let order_by = order_by("remaining", proto::types::OrderDirection::Asc);
+        assert_eq!(paginate(&world, order_by, limit).await, ascending);
+    }
+    let descending = ascending.iter().rev().copied().collect::<Vec<_>>();
+    let order_by_desc = order_by("remaining", proto::types::OrderDirection::Desc);
+    assert_eq!(paginate(&world, order_by_desc.clone(), 2).await, descending);
+
+    // the pages keep their position while entities are written before the cursor
+    let query = proto::types::Query {
+        clause: None,
+        limit: 2,
+        offset: 0,
+        cursor: String::new(),
+        order_by: Some(order_by_desc.clone()),
+    };
+    let response = world.retrieve_entities(query.clone()).await.unwrap();
+    assert_eq!(response.total_count, 4);
+    db.set_entity(moves("ns-Moves", Felt::from(0x5_u64), 200), "0x2", 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let query = proto::types::Query { cursor: response.next_cursor, ..query };
+    let response = world.retrieve_entities(query).await.unwrap();
+    let hashed_keys = response
+        .entities
+        .iter()
+        .map(|entity| Felt::from_bytes_be_slice(&entity.hashed_keys))
+        .collect::<Vec<_>>();
+    assert_eq!(hashed_keys, descending[2..]);
+    assert!(response.next_cursor.is_empty());
+
+    let query = proto::types::Query {
+        clause: None,
+        limit: 1,
+        offset: 0,
+        cursor: "not a cursor".to_string(),
+        order_by: Some(order_by_desc),
+    };
+    assert!(world.retrieve_entities(query).await.is_err());
+}
@@ -1 +1,9 @@
+mod aggregates_test;
+mod clauses_test;
 mod entities_test;
+mod search_test;
+#[cfg(feature = "client")]
+mod subscriptions_test;
+mod transport_test;
+mod updates_test;
+mod websocket_test;
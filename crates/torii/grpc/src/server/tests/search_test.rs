@@ -0,0 +1,121 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::Felt;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet_crypto::poseidon_hash_many;
+use torii_core::search::SearchField;
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::proto;
+use crate::proto::types::clause::ClauseType;
+use crate::server::DojoWorld;
+
+fn guild(name: &str, id: u32, guild_name: &str, tag: &str) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "id".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(id))),
+                key: true,
+            },
+            Member {
+                name: "name".to_string(),
+                ty: Ty::ByteArray(guild_name.to_string()),
+                key: false,
+            },
+            Member {
+                name: "tag".to_string(),
+                ty: Ty::Primitive(Primitive::Felt252(Some(
+                    cairo_short_string_to_felt(tag).unwrap(),
+                ))),
+                key: false,
+            },
+        ],
+    })
+}
+
+async fn setup() -> DojoWorld {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    let fields = ["ns-Guild.name", "ns-Guild.tag"].map(|f| SearchField::from_str(f).unwrap());
+    db.enable_search(fields.to_vec()).await.unwrap();
+
+    let model = guild("Guild", 0, "", "");
+    db.register_model("ns", model, Layout::Fixed(vec![]), Felt::ZERO, Felt::ZERO, 0, 0, 0)
+        .await
+        .unwrap();
+
+    let guilds =
+        [(1, "Iron Knights", "iron"), (2, "Silver Knights", "silver"), (3, "Bards", "lute")];
+    for (id, name, tag) in guilds {
+        db.set_entity(guild("ns-Guild", id, name, tag), &format!("{id:#x}"), 0).await.unwrap();
+    }
+    db.execute().await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+
+    DojoWorld::new(pool, block_rx, Felt::ZERO, Arc::new(provider))
+}
+
+async fn search(world: &DojoWorld, text: &str) -> (Vec<Felt>, u32) {
+    let response = world
+        .retrieve_entities(proto::types::Query {
+            clause: Some(proto::types::Clause {
+                clause_type: Some(ClauseType::Search(proto::types::SearchClause {
+                    text: text.to_string(),
+                })),
+            }),
+            limit: 10,
+            offset: 0,
+            cursor: String::new(),
+            order_by: None,
+        })
+        .await
+        .unwrap();
+
+    let mut ids = response
+        .entities
+        .iter()
+        .map(|entity| Felt::from_bytes_be_slice(&entity.hashed_keys))
+        .collect::<Vec<_>>();
+    ids.sort();
+
+    (ids, response.total_count)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search_entities() {
+    let world = setup().await;
+
+    let mut knights = vec![poseidon_hash_many(&[Felt::ONE]), poseidon_hash_many(&[Felt::TWO])];
+    knights.sort();
+    assert_eq!(search(&world, "knights").await, (knights, 2));
+
+    // the last word is matched as a prefix, on any of the searchable members
+    let bards = vec![poseidon_hash_many(&[Felt::THREE])];
+    assert_eq!(search(&world, "lu").await, (bards.clone(), 1));
+    assert_eq!(search(&world, "BARDS").await, (bards, 1));
+
+    // all the words have to match
+    assert_eq!(search(&world, "iron knights").await.1, 1);
+    assert_eq!(search(&world, "iron bards").await.1, 0);
+
+    // the text can't use the query syntax
+    assert_eq!(search(&world, "iron OR bards").await.1, 0);
+    assert_eq!(search(&world, "  ").await, (vec![], 0));
+}
@@ -0,0 +1,201 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use futures::StreamExt;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet_crypto::poseidon_hash_many;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::{JoinHandle, JoinSet};
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::client::{EntityUpdateStreaming, ReconnectConfig, WorldClient};
+use crate::server::{self, GrpcConfig};
+use crate::types::schema::{Entity, EntityUpdate};
+use crate::types::EntityKeysClause;
+
+fn position(name: &str, player: Felt, x: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                key: true,
+            },
+            Member {
+                name: "x".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(x))),
+                key: false,
+            },
+        ],
+    })
+}
+
+fn event_id(block: u64) -> String {
+    format!("{:#064x}:{:#x}:{:#04x}", block, 0, 0)
+}
+
+// A running server, and the sender keeping it from shutting down.
+struct Server {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+    _shutdown_tx: broadcast::Sender<()>,
+}
+
+async fn serve(pool: &Pool<Sqlite>) -> Server {
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+
+    let (addrs, server) = server::new(
+        shutdown_rx,
+        pool,
+        block_rx,
+        Felt::ZERO,
+        Arc::new(provider),
+        GrpcConfig::default(),
+    )
+    .await
+    .unwrap();
+    let handle = tokio::spawn(async move {
+        let _ = server.await;
+    });
+
+    Server { addr: addrs.grpc, handle, _shutdown_tx: shutdown_tx }
+}
+
+// Forwards the connections to the server. Aborting the proxy drops its connections, which
+// disconnects the clients like a restart of the server would.
+fn proxy(listener: TcpListener, server: SocketAddr) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        loop {
+            let (mut inbound, _) = listener.accept().await.unwrap();
+            connections.spawn(async move {
+                let mut outbound = TcpStream::connect(server).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+            });
+        }
+    })
+}
+
+async fn next(stream: &mut EntityUpdateStreaming) -> (u64, EntityUpdate) {
+    tokio::time::timeout(Duration::from_secs(10), stream.next()).await.unwrap().unwrap().unwrap()
+}
+
+// The hashed keys and the `x` of an updated position.
+fn updated_x(update: EntityUpdate) -> (Felt, u32) {
+    let EntityUpdate::Updated(entity) = update else {
+        panic!("expected an update, got {update:?}");
+    };
+
+    let x = match &entity.models[0].children[1].ty {
+        Ty::Primitive(Primitive::U32(Some(x))) => *x,
+        ty => panic!("unexpected x {ty:?}"),
+    };
+    (entity.hashed_keys, x)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_resume_entities_subscription() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    db.register_model(
+        "ns",
+        position("Position", Felt::ZERO, 0),
+        Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+
+    let (alice, bob) = (Felt::from(0x5e1a11ce_u64), Felt::from(0x5e10b0b_u64));
+    let (alice_id, bob_id) = (poseidon_hash_many(&[alice]), poseidon_hash_many(&[bob]));
+    db.set_entity(position("ns-Position", alice, 1), &event_id(1), 0).await.unwrap();
+    db.set_entity(position("ns-Position", bob, 1), &event_id(1), 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let server = serve(&pool).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = listener.local_addr().unwrap();
+    let proxy_handle = proxy(listener, server.addr);
+
+    let reconnect = ReconnectConfig {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(100),
+        max_retries: None,
+    };
+    let mut client = WorldClient::new(format!("http://{proxy_addr}"), Felt::ZERO)
+        .await
+        .unwrap()
+        .with_reconnect(reconnect);
+    let mut stream = client
+        .subscribe_entities(vec![EntityKeysClause::HashedKeys(vec![alice_id, bob_id])])
+        .await
+        .unwrap();
+
+    let (subscription_id, _) = next(&mut stream).await;
+
+    db.set_entity(position("ns-Position", alice, 2), &event_id(2), 0).await.unwrap();
+    db.execute().await.unwrap();
+    let (id, update) = next(&mut stream).await;
+    assert_eq!(id, subscription_id);
+    assert_eq!(updated_x(update), (alice_id, 2));
+
+    // the server restarts, missing the updates made while the client is disconnected
+    proxy_handle.abort();
+    server.handle.abort();
+    db.set_entity(position("ns-Position", alice, 3), &event_id(3), 0).await.unwrap();
+    db.execute().await.unwrap();
+    db.delete_entity(bob_id, position("ns-Position", bob, 1), &event_id(4)).await.unwrap();
+    db.execute().await.unwrap();
+
+    let server = serve(&pool).await;
+    let _proxy_handle = proxy(TcpListener::bind(proxy_addr).await.unwrap(), server.addr);
+
+    // the resumed subscription keeps its id and replays the missed updates, deletions included
+    let (id, update) = next(&mut stream).await;
+    assert_eq!(id, subscription_id);
+    assert_eq!(update, EntityUpdate::Updated(Entity { hashed_keys: Felt::ZERO, models: vec![] }));
+    let (id, update) = next(&mut stream).await;
+    assert_eq!(id, subscription_id);
+    assert_eq!(updated_x(update), (alice_id, 3));
+    let (id, update) = next(&mut stream).await;
+    assert_eq!(id, subscription_id);
+    assert_eq!(update, EntityUpdate::Deleted(bob_id));
+
+    // live updates follow the replay, and the subscription is updated with its id
+    client
+        .update_entities_subscription(
+            subscription_id,
+            vec![EntityKeysClause::HashedKeys(vec![bob_id])],
+        )
+        .await
+        .unwrap();
+    db.set_entity(position("ns-Position", alice, 5), &event_id(5), 0).await.unwrap();
+    db.set_entity(position("ns-Position", bob, 5), &event_id(5), 0).await.unwrap();
+    db.execute().await.unwrap();
+    let (id, update) = next(&mut stream).await;
+    assert_eq!(id, subscription_id);
+    assert_eq!(updated_x(update), (bob_id, 5));
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use hyper::{Body, Client, Method, Request, StatusCode};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use tokio::sync::broadcast;
+use url::Url;
+
+use crate::server::{self, GrpcAddrs, GrpcConfig};
+
+// The addresses of a running server, and the sender keeping it from shutting down.
+async fn serve(config: GrpcConfig) -> (GrpcAddrs, broadcast::Sender<()>) {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let (addrs, server) =
+        server::new(shutdown_rx, &pool, block_rx, Felt::ZERO, Arc::new(provider), config)
+            .await
+            .unwrap();
+    tokio::spawn(server);
+
+    (addrs, shutdown_tx)
+}
+
+async fn preflight(addrs: GrpcAddrs, origin: &str) -> hyper::Response<Body> {
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri(format!("http://{}/world.World/RetrieveEntities", addrs.grpc))
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "x-grpc-web,content-type")
+        .body(Body::empty())
+        .unwrap();
+    Client::new().request(request).await.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_web_cors() {
+    let config = GrpcConfig {
+        allowed_origins: Some(vec!["http://allowed.com".to_string()]),
+        ..Default::default()
+    };
+    let (addrs, _shutdown_tx) = serve(config).await;
+
+    let response = preflight(addrs, "http://allowed.com").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://allowed.com");
+
+    let response = preflight(addrs, "http://denied.com").await;
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+
+    // browsers can't reach the server from any origin without allowed origins
+    let (addrs, _shutdown_tx) = serve(GrpcConfig::default()).await;
+    let response = preflight(addrs, "http://allowed.com").await;
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+
+    let config = GrpcConfig { allowed_origins: Some(vec!["*".to_string()]), ..Default::default() };
+    let (addrs, _shutdown_tx) = serve(config).await;
+    let response = preflight(addrs, "http://any.com").await;
+    assert_eq!(response.headers()["access-control-allow-origin"], "http://any.com");
+}
+
+#[cfg(feature = "client")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_compression() {
+    use tonic::codec::CompressionEncoding;
+    use tonic::Code;
+
+    use crate::proto::types::Query;
+    use crate::proto::world::world_client::WorldClient;
+    use crate::proto::world::RetrieveEntitiesRequest;
+
+    let request = |cursor: &str| RetrieveEntitiesRequest {
+        query: Some(Query { limit: 10, cursor: cursor.to_string(), ..Default::default() }),
+    };
+
+    let config =
+        GrpcConfig { compression: true, max_decoding_message_size: 64, ..Default::default() };
+    let (addrs, _shutdown_tx) = serve(config).await;
+    let mut client = WorldClient::connect(format!("http://{}", addrs.grpc))
+        .await
+        .unwrap()
+        .accept_compressed(CompressionEncoding::Gzip);
+
+    // the responses are compressed for the clients accepting it
+    let response = client.retrieve_entities(request("")).await.unwrap();
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "gzip");
+
+    // and left as is for the others
+    let mut uncompressed = WorldClient::connect(format!("http://{}", addrs.grpc)).await.unwrap();
+    let response = uncompressed.retrieve_entities(request("")).await.unwrap();
+    assert!(response.metadata().get("grpc-encoding").is_none());
+
+    // the compressed requests are accepted
+    let mut client = client.send_compressed(CompressionEncoding::Gzip);
+    client.retrieve_entities(request("")).await.unwrap();
+
+    // and the size of the requests is limited
+    let status = uncompressed.retrieve_entities(request(&"a".repeat(128))).await.unwrap_err();
+    assert_eq!(status.code(), Code::OutOfRange);
+}
@@ -0,0 +1,165 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet_crypto::poseidon_hash_many;
+use tokio::sync::mpsc::Receiver;
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::proto::types::EntityUpdateType;
+use crate::proto::world::{SubscribeEntityResponse, SubscribeModelRegistrationsResponse};
+use crate::server::DojoWorld;
+use crate::types::EntityKeysClause;
+
+fn health(name: &str, player: Felt, hp: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                key: true,
+            },
+            Member {
+                name: "hp".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(hp))),
+                key: false,
+            },
+        ],
+    })
+}
+
+async fn setup() -> (Pool<Sqlite>, Sql, DojoWorld) {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let world = DojoWorld::new(pool.clone(), block_rx, Felt::ZERO, Arc::new(provider));
+
+    (pool, db, world)
+}
+
+async fn next_update(
+    rx: &mut Receiver<Result<SubscribeEntityResponse, tonic::Status>>,
+) -> (Option<Felt>, EntityUpdateType) {
+    let res = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap();
+    let res = res.unwrap().unwrap();
+    let hashed_keys =
+        res.entity.as_ref().map(|entity| Felt::from_bytes_be_slice(&entity.hashed_keys));
+    (hashed_keys, res.update_type())
+}
+
+async fn next_registration(
+    rx: &mut Receiver<Result<SubscribeModelRegistrationsResponse, tonic::Status>>,
+) -> Option<(String, String, String)> {
+    let res = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap();
+    res.unwrap().unwrap().model.map(|model| (model.namespace, model.name, model.class_hash))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deletion_updates() {
+    let (pool, mut db, world) = setup().await;
+    db.register_model(
+        "ns",
+        health("Health", Felt::ZERO, 0),
+        Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+
+    // the players are only used by this test, the broker being shared by the tests
+    let (alice, bob) = (Felt::from(0xde1e7ed_u64), Felt::from(0xde1e7ee_u64));
+    let (alice_id, bob_id) = (poseidon_hash_many(&[alice]), poseidon_hash_many(&[bob]));
+    db.set_entity(health("ns-Health", alice, 10), "0x1", 0).await.unwrap();
+    db.set_event_message(health("ns-Health", bob, 10), "0x1", 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let entity_clause = EntityKeysClause::HashedKeys(vec![alice_id]).into();
+    let mut entities_rx =
+        world.subscribe_entities(vec![entity_clause], None, 0, String::new()).await.unwrap();
+    let event_message_clause = EntityKeysClause::HashedKeys(vec![bob_id]).into();
+    let mut event_messages_rx = world
+        .subscribe_event_messages(vec![event_message_clause], None, 0, String::new())
+        .await
+        .unwrap();
+    assert_eq!(next_update(&mut entities_rx).await, (None, EntityUpdateType::Updated));
+    assert_eq!(next_update(&mut event_messages_rx).await, (None, EntityUpdateType::Updated));
+
+    // updates and deletions are told apart
+    db.set_entity(health("ns-Health", alice, 5), "0x2", 0).await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(next_update(&mut entities_rx).await, (Some(alice_id), EntityUpdateType::Updated));
+
+    db.delete_entity(alice_id, health("ns-Health", alice, 0), "0x3").await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(next_update(&mut entities_rx).await, (Some(alice_id), EntityUpdateType::Deleted));
+
+    // the record of an event message is deleted the same way
+    db.delete_entity(bob_id, health("ns-Health", bob, 0), "0x4").await.unwrap();
+    db.execute().await.unwrap();
+    assert_eq!(
+        next_update(&mut event_messages_rx).await,
+        (Some(bob_id), EntityUpdateType::Deleted)
+    );
+
+    let event_messages: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM event_messages").fetch_one(&pool).await.unwrap();
+    assert_eq!(event_messages, 0);
+    let rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM [ns-Health]").fetch_one(&pool).await.unwrap();
+    assert_eq!(rows, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_model_registration_updates() {
+    let (_, mut db, world) = setup().await;
+
+    // the namespace is only used by this test, the broker being shared by the tests
+    let mut rx = world.subscribe_model_registrations(vec!["registered".to_string()]).await.unwrap();
+    assert_eq!(next_registration(&mut rx).await, None);
+
+    let register = |namespace: &'static str, class_hash: u64| {
+        let model = health("Health", Felt::ZERO, 0);
+        (namespace, model, Felt::from(class_hash))
+    };
+
+    // models of other namespaces are left out
+    for (namespace, model, class_hash) in [register("other", 1), register("registered", 2)] {
+        db.register_model(namespace, model, Layout::Fixed(vec![]), class_hash, Felt::ZERO, 0, 0, 0)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        next_registration(&mut rx).await,
+        Some(("registered".to_string(), "Health".to_string(), "0x2".to_string()))
+    );
+
+    // upgrades register the model again
+    let (namespace, model, class_hash) = register("registered", 3);
+    db.register_model(namespace, model, Layout::Fixed(vec![]), class_hash, Felt::ZERO, 0, 0, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        next_registration(&mut rx).await,
+        Some(("registered".to_string(), "Health".to_string(), "0x3".to_string()))
+    );
+}
@@ -0,0 +1,231 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use dojo_world::contracts::abi::model::Layout;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::Felt;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet_crypto::poseidon_hash_many;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use torii_core::sql::Sql;
+use url::Url;
+
+use crate::proto::types::clause::ClauseType;
+use crate::proto::types::HashedKeysClause;
+use crate::proto::world::{RetrieveEntitiesRequest, SubscribeEntitiesRequest};
+use crate::server::websocket::{encode_response, parse_params, to_json, world_service, WsResponse};
+use crate::server::{self, GrpcConfig};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn health(name: &str, player: Felt, hp: u32) -> Ty {
+    Ty::Struct(Struct {
+        name: name.to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                key: true,
+            },
+            Member {
+                name: "hp".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(hp))),
+                key: false,
+            },
+        ],
+    })
+}
+
+fn base64(felt: Felt) -> String {
+    general_purpose::STANDARD.encode(felt.to_bytes_be())
+}
+
+async fn send(socket: &mut Socket, request: Value) {
+    socket.send(Message::text(request.to_string())).await.unwrap();
+}
+
+async fn next(socket: &mut Socket) -> Value {
+    let message =
+        tokio::time::timeout(Duration::from_secs(10), socket.next()).await.unwrap().unwrap();
+    serde_json::from_str(message.unwrap().to_text().unwrap()).unwrap()
+}
+
+#[test]
+fn test_parse_params() {
+    let method = world_service().methods().find(|m| m.name() == "RetrieveEntities").unwrap();
+
+    let request: RetrieveEntitiesRequest = parse_params(&method.input(), Value::Null).unwrap();
+    assert_eq!(request, RetrieveEntitiesRequest::default());
+
+    // the params are proto3 json, with camel case names and base64 bytes
+    let hashed_keys = base64(Felt::ONE);
+    let params = json!({
+        "query": {
+            "limit": 10,
+            "cursor": "abc",
+            "clause": { "hashedKeys": { "hashedKeys": [hashed_keys] } }
+        }
+    });
+    let request: RetrieveEntitiesRequest = parse_params(&method.input(), params).unwrap();
+    let query = request.query.unwrap();
+    assert_eq!(query.limit, 10);
+    assert_eq!(query.cursor, "abc");
+    assert_eq!(query.offset, 0);
+    assert_eq!(
+        query.clause.unwrap().clause_type,
+        Some(ClauseType::HashedKeys(HashedKeysClause {
+            hashed_keys: vec![Felt::ONE.to_bytes_be().to_vec()]
+        }))
+    );
+
+    assert!(
+        parse_params::<RetrieveEntitiesRequest>(&method.input(), json!({ "query": 1 })).is_err()
+    );
+    assert!(
+        parse_params::<RetrieveEntitiesRequest>(&method.input(), json!({ "unknown": 1 })).is_err()
+    );
+}
+
+#[test]
+fn test_to_json() {
+    let method = world_service().methods().find(|m| m.name() == "SubscribeEntities").unwrap();
+
+    let request = SubscribeEntitiesRequest {
+        clauses: vec![],
+        cursor: "0x1".to_string(),
+        clause: None,
+        subscription_id: 1 << 60,
+    };
+    // 64 bits integers are strings, and default values are left out
+    assert_eq!(
+        to_json(&method.input(), &request).unwrap(),
+        json!({ "cursor": "0x1", "subscriptionId": (1_u64 << 60).to_string() })
+    );
+}
+
+#[test]
+fn test_encode_response() {
+    let response = WsResponse::result(1, json!({ "entities": [] }));
+    let text = encode_response(response, usize::MAX);
+    assert_eq!(text, r#"{"id":1,"result":{"entities":[]}}"#);
+
+    let response = WsResponse::result(2, json!({ "entities": [] }));
+    let text: Value = serde_json::from_str(&encode_response(response, 16)).unwrap();
+    assert_eq!(text["id"], 2);
+    assert_eq!(text["error"]["code"], tonic::Code::ResourceExhausted as i32);
+    assert!(text.get("result").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_websocket() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
+    db.register_model(
+        "ns",
+        health("Health", Felt::ZERO, 0),
+        Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        0,
+    )
+    .await
+    .unwrap();
+
+    // the player is only used by this test, the broker being shared by the tests
+    let alice = Felt::from(0x50c4e7_u64);
+    let alice_id = poseidon_hash_many(&[alice]);
+    db.set_entity(health("ns-Health", alice, 10), "0x1", 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    let provider =
+        JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5050").unwrap()));
+    let (_, block_rx) = tokio::sync::mpsc::channel(1);
+    let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let config = GrpcConfig {
+        allowed_origins: Some(vec!["http://allowed.com".to_string()]),
+        ..Default::default()
+    };
+    let (addrs, server) =
+        server::new(shutdown_rx, &pool, block_rx, Felt::ZERO, Arc::new(provider), config)
+            .await
+            .unwrap();
+    tokio::spawn(server);
+
+    let url = format!("ws://{}/grpc/ws", addrs.websocket);
+
+    // browsers of the other origins are rejected
+    let mut request = url.as_str().into_client_request().unwrap();
+    request.headers_mut().insert("origin", "http://denied.com".parse().unwrap());
+    assert!(tokio_tungstenite::connect_async(request).await.is_err());
+
+    let mut request = url.as_str().into_client_request().unwrap();
+    request.headers_mut().insert("origin", "http://allowed.com".parse().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+    let clause = json!({ "hashedKeys": { "hashedKeys": [base64(alice_id)] } });
+    send(
+        &mut socket,
+        json!({
+            "id": 1,
+            "method": "RetrieveEntities",
+            "params": { "query": { "clause": clause, "limit": 10 } }
+        }),
+    )
+    .await;
+    let response = next(&mut socket).await;
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["totalCount"], 1);
+    assert_eq!(response["result"]["entities"][0]["hashedKeys"], base64(alice_id));
+
+    send(
+        &mut socket,
+        json!({
+            "id": 2,
+            "method": "SubscribeEntities",
+            "params": { "clauses": [clause] }
+        }),
+    )
+    .await;
+    let response = next(&mut socket).await;
+    assert_eq!(response["id"], 2);
+    assert!(response["result"].get("entity").is_none());
+
+    db.set_entity(health("ns-Health", alice, 5), "0x2", 0).await.unwrap();
+    db.execute().await.unwrap();
+    let response = next(&mut socket).await;
+    assert_eq!(response["id"], 2);
+    assert_eq!(response["result"]["entity"]["hashedKeys"], base64(alice_id));
+
+    // the cancelled subscription stops streaming, the requests being handled in order
+    send(&mut socket, json!({ "id": 2, "method": "Cancel" })).await;
+    send(&mut socket, json!({ "id": 3, "method": "Unknown" })).await;
+    let response = next(&mut socket).await;
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["error"]["code"], tonic::Code::Unimplemented as i32);
+
+    db.set_entity(health("ns-Health", alice, 1), "0x3", 0).await.unwrap();
+    db.execute().await.unwrap();
+    send(&mut socket, json!({ "id": 4, "method": "RetrieveEntities", "params": { "query": 1 } }))
+        .await;
+    let response = next(&mut socket).await;
+    assert_eq!(response["id"], 4);
+    assert_eq!(response["error"]["code"], tonic::Code::InvalidArgument as i32);
+}
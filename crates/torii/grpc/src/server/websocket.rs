@@ -0,0 +1,305 @@
+//! JSON over WebSocket transport of the `World` service, for the environments that can't use
+//! HTTP/2 nor gRPC-web.
+//!
+//! Every text frame sent by the client is a request `{"id": 1, "method": "RetrieveEntities",
+//! "params": {..}}` where `method` is the name of a `World` rpc and `params` the proto3 JSON
+//! encoding of its request message. Responses carry the id of the request they answer, with either
+//! a `result` holding the proto3 JSON encoding of the response message or an `error` with the gRPC
+//! status `code` and `message`. Streaming rpcs answer with one response per streamed message until
+//! the client sends a `Cancel` request with the same id.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+
+use futures::stream::BoxStream;
+use futures_util::{SinkExt, StreamExt};
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, ServiceDescriptor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::channel;
+use tokio::task::JoinHandle;
+use tonic::{Code, Request, Status};
+use tracing::trace;
+use warp::http::StatusCode;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::Filter;
+
+use super::{DojoWorld, GrpcConfig};
+use crate::proto;
+use crate::proto::world::world_server::World;
+
+pub(crate) const LOG_TARGET: &str = "torii::grpc::server::websocket";
+
+/// Method cancelling the streaming request with the same id.
+const CANCEL_METHOD: &str = "Cancel";
+
+#[derive(Debug, Deserialize)]
+struct WsRequest {
+    id: u64,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct WsResponse {
+    /// Id of the answered request, null if the request couldn't be parsed.
+    id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<WsError>,
+}
+
+#[derive(Debug, Serialize)]
+struct WsError {
+    code: i32,
+    message: String,
+}
+
+impl WsResponse {
+    pub(crate) fn result(id: u64, result: Value) -> Self {
+        Self { id: Some(id), result: Some(result), error: None }
+    }
+
+    fn error(id: Option<u64>, status: Status) -> Self {
+        let error = WsError { code: status.code() as i32, message: status.message().to_string() };
+        Self { id, result: None, error: Some(error) }
+    }
+}
+
+enum Call {
+    Unary(Value),
+    Stream(BoxStream<'static, Result<Value, Status>>),
+}
+
+pub fn new(
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+    world: DojoWorld,
+    config: &GrpcConfig,
+) -> Result<(SocketAddr, impl Future<Output = ()> + 'static), std::io::Error> {
+    let max_decoding_message_size = config.max_decoding_message_size;
+    let max_encoding_message_size = config.max_encoding_message_size;
+    let allowed_origins = config.allowed_origins.clone();
+    let service = world_service();
+
+    // browsers don't apply CORS to websockets, their origin is checked against the allowed ones
+    let routes = warp::path!("grpc" / "ws")
+        .and(warp::header::optional::<String>("origin"))
+        .and(warp::ws())
+        .map(move |origin: Option<String>, ws: Ws| {
+            if !is_allowed_origin(allowed_origins.as_deref(), origin.as_deref()) {
+                return Box::new(StatusCode::FORBIDDEN) as Box<dyn warp::Reply>;
+            }
+
+            let world = world.clone();
+            let service = service.clone();
+            Box::new(ws.max_message_size(max_decoding_message_size).on_upgrade(move |socket| {
+                handle_socket(socket, world, service, max_encoding_message_size)
+            }))
+        });
+
+    warp::serve(routes)
+        .try_bind_with_graceful_shutdown(config.websocket_addr, async move {
+            shutdown_rx.recv().await.ok();
+        })
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, e))
+}
+
+// the descriptor of the `World` service, giving the messages of its rpcs to encode them as json
+pub(crate) fn world_service() -> ServiceDescriptor {
+    DescriptorPool::decode(proto::world::FILE_DESCRIPTOR_SET)
+        .expect("world descriptor should be valid")
+        .get_service_by_name("world.World")
+        .expect("world descriptor should have the world service")
+}
+
+// clients without an origin aren't browsers, and are always allowed
+fn is_allowed_origin(allowed_origins: Option<&[String]>, origin: Option<&str>) -> bool {
+    match (allowed_origins, origin) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(allowed), Some(origin)) => {
+            allowed.iter().any(|allowed| allowed == "*" || allowed == origin)
+        }
+    }
+}
+
+async fn handle_socket(
+    socket: WebSocket,
+    world: DojoWorld,
+    service: ServiceDescriptor,
+    max_encoding_message_size: usize,
+) {
+    let (mut sink, mut incoming) = socket.split();
+    let (sender, mut receiver) = channel::<WsResponse>(64);
+
+    let writer = tokio::spawn(async move {
+        while let Some(response) = receiver.recv().await {
+            let text = encode_response(response, max_encoding_message_size);
+            if sink.send(Message::text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut requests: HashMap<u64, JoinHandle<()>> = HashMap::new();
+    while let Some(Ok(message)) = incoming.next().await {
+        if message.is_close() {
+            break;
+        }
+
+        // pings are answered by the websocket layer and binary frames aren't part of the protocol
+        let Ok(text) = message.to_str() else {
+            continue;
+        };
+
+        let request = match serde_json::from_str::<WsRequest>(text) {
+            Ok(request) => request,
+            Err(e) => {
+                let status = Status::invalid_argument(format!("Invalid request: {e}"));
+                let _ = sender.send(WsResponse::error(None, status)).await;
+                continue;
+            }
+        };
+
+        requests.retain(|_, handle| !handle.is_finished());
+
+        if request.method == CANCEL_METHOD {
+            if let Some(handle) = requests.remove(&request.id) {
+                trace!(target = LOG_TARGET, id = %request.id, "Cancelling request.");
+                handle.abort();
+            }
+            continue;
+        }
+
+        let world = world.clone();
+        let service = service.clone();
+        let sender = sender.clone();
+        let id = request.id;
+        let handle = tokio::spawn(async move {
+            match dispatch(&world, &service, &request.method, request.params).await {
+                Ok(Call::Unary(result)) => {
+                    let _ = sender.send(WsResponse::result(id, result)).await;
+                }
+                Ok(Call::Stream(mut stream)) => {
+                    while let Some(item) = stream.next().await {
+                        let response = match item {
+                            Ok(result) => WsResponse::result(id, result),
+                            Err(status) => WsResponse::error(Some(id), status),
+                        };
+
+                        if sender.send(response).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Err(status) => {
+                    let _ = sender.send(WsResponse::error(Some(id), status)).await;
+                }
+            }
+        });
+
+        if let Some(previous) = requests.insert(id, handle) {
+            previous.abort();
+        }
+    }
+
+    for (_, handle) in requests {
+        handle.abort();
+    }
+    writer.abort();
+}
+
+async fn dispatch(
+    world: &DojoWorld,
+    service: &ServiceDescriptor,
+    method: &str,
+    params: Value,
+) -> Result<Call, Status> {
+    let descriptor = service
+        .methods()
+        .find(|descriptor| descriptor.name() == method)
+        .ok_or_else(|| Status::unimplemented(format!("Unknown method {method}")))?;
+    let (input, output) = (descriptor.input(), descriptor.output());
+
+    macro_rules! call {
+        ($method:ident) => {
+            World::$method(world, Request::new(parse_params(&input, params)?)).await?.into_inner()
+        };
+    }
+
+    macro_rules! unary {
+        ($method:ident) => {
+            Call::Unary(to_json(&output, &call!($method))?)
+        };
+    }
+
+    macro_rules! stream {
+        ($method:ident) => {
+            Call::Stream(
+                call!($method).map(move |item| item.and_then(|m| to_json(&output, &m))).boxed(),
+            )
+        };
+    }
+
+    let call = match method {
+        "WorldMetadata" => unary!(world_metadata),
+        "SubscribeModels" => stream!(subscribe_models),
+        "SubscribeModelRegistrations" => stream!(subscribe_model_registrations),
+        "SubscribeEntities" => stream!(subscribe_entities),
+        "UpdateEntitiesSubscription" => unary!(update_entities_subscription),
+        "RetrieveEntities" => unary!(retrieve_entities),
+        "SubscribeEventMessages" => stream!(subscribe_event_messages),
+        "UpdateEventMessagesSubscription" => unary!(update_event_messages_subscription),
+        "RetrieveEventMessages" => unary!(retrieve_event_messages),
+        "RetrieveEvents" => unary!(retrieve_events),
+        "SubscribeEvents" => stream!(subscribe_events),
+        "RetrieveAggregates" => unary!(retrieve_aggregates),
+        _ => return Err(Status::unimplemented(format!("Unknown method {method}"))),
+    };
+
+    Ok(call)
+}
+
+// decodes the proto3 json encoding of a request message, e.g. with base64 bytes and camel case
+// field names
+pub(crate) fn parse_params<T: prost::Message + Default>(
+    descriptor: &MessageDescriptor,
+    params: Value,
+) -> Result<T, Status> {
+    // a missing `params` is the default request message
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    DynamicMessage::deserialize(descriptor.clone(), params)
+        .map_err(|e| Status::invalid_argument(format!("Invalid params: {e}")))?
+        .transcode_to()
+        .map_err(|e| Status::internal(e.to_string()))
+}
+
+pub(crate) fn to_json<T: prost::Message>(
+    descriptor: &MessageDescriptor,
+    message: &T,
+) -> Result<Value, Status> {
+    let mut dynamic = DynamicMessage::new(descriptor.clone());
+    dynamic.transcode_from(message).map_err(|e| Status::internal(e.to_string()))?;
+    serde_json::to_value(&dynamic).map_err(|e| Status::internal(e.to_string()))
+}
+
+pub(crate) fn encode_response(response: WsResponse, max_encoding_message_size: usize) -> String {
+    let id = response.id;
+    let text = serde_json::to_string(&response).expect("response should serialize");
+    if text.len() <= max_encoding_message_size {
+        return text;
+    }
+
+    let status = Status::new(
+        Code::ResourceExhausted,
+        format!(
+            "Response message of {} bytes is larger than the limit of {} bytes",
+            text.len(),
+            max_encoding_message_size
+        ),
+    );
+    serde_json::to_string(&WsResponse::error(id, status)).expect("response should serialize")
+}
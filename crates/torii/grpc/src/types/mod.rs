@@ -15,11 +15,63 @@ use crate::proto::{self};
 
 pub mod schema;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+/// A query of entities. The cursor and order fields were added for cursor pagination, they default
+/// to the previous behaviour when left out, e.g. with `..Default::default()` or in serialized
+/// queries.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
 pub struct Query {
     pub clause: Option<Clause>,
     pub limit: u32,
     pub offset: u32,
+    /// The `next_cursor` of the previous page, empty for the first one.
+    #[serde(default)]
+    pub cursor: String,
+    #[serde(default)]
+    pub order_by: Option<OrderBy>,
+}
+
+/// The member of a model to order entities by. When `model` is empty, entities are ordered by their
+/// last update if `member` is `updated_at`, and by their id otherwise.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct OrderBy {
+    pub model: String,
+    pub member: String,
+    pub direction: OrderDirection,
+}
+
+#[derive(
+    Debug, AsRefStr, Serialize, Deserialize, EnumIter, FromRepr, PartialEq, Hash, Eq, Clone,
+)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum OrderDirection {
+    Desc,
+    Asc,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct AggregateQuery {
+    pub model: String,
+    pub clause: Option<Clause>,
+    pub aggregates: Vec<Aggregate>,
+    pub group_by: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub member: String,
+}
+
+#[derive(
+    Debug, AsRefStr, Serialize, Deserialize, EnumIter, FromRepr, PartialEq, Hash, Eq, Clone,
+)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
@@ -27,6 +79,9 @@ pub enum Clause {
     Keys(KeysClause),
     Member(MemberClause),
     Composite(CompositeClause),
+    Enum(EnumClause),
+    Array(ArrayClause),
+    Search(SearchClause),
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
@@ -62,6 +117,32 @@ pub struct MemberClause {
     pub value: Primitive,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct EnumClause {
+    pub model: String,
+    pub member: String,
+    pub operator: ComparisonOperator,
+    pub option: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct ArrayClause {
+    pub model: String,
+    pub member: String,
+    pub predicate: ArrayPredicate,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub enum ArrayPredicate {
+    Contains(Primitive),
+    Length(ComparisonOperator, u32),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct SearchClause {
+    pub text: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
 pub struct CompositeClause {
     pub operator: LogicalOperator,
@@ -103,6 +184,19 @@ impl fmt::Display for ComparisonOperator {
     }
 }
 
+impl ComparisonOperator {
+    pub fn matches<T: PartialOrd>(&self, lhs: T, rhs: T) -> bool {
+        match self {
+            ComparisonOperator::Eq => lhs == rhs,
+            ComparisonOperator::Neq => lhs != rhs,
+            ComparisonOperator::Gt => lhs > rhs,
+            ComparisonOperator::Gte => lhs >= rhs,
+            ComparisonOperator::Lt => lhs < rhs,
+            ComparisonOperator::Lte => lhs <= rhs,
+        }
+    }
+}
+
 impl From<proto::types::ComparisonOperator> for ComparisonOperator {
     fn from(operator: proto::types::ComparisonOperator) -> Self {
         match operator {
@@ -173,7 +267,36 @@ impl TryFrom<proto::types::WorldMetadata> for dojo_types::WorldMetadata {
 
 impl From<Query> for proto::types::Query {
     fn from(value: Query) -> Self {
-        Self { clause: value.clause.map(|c| c.into()), limit: value.limit, offset: value.offset }
+        Self {
+            clause: value.clause.map(|c| c.into()),
+            limit: value.limit,
+            offset: value.offset,
+            cursor: value.cursor,
+            order_by: value.order_by.map(|o| o.into()),
+        }
+    }
+}
+
+impl From<OrderBy> for proto::types::OrderBy {
+    fn from(value: OrderBy) -> Self {
+        Self { model: value.model, member: value.member, direction: value.direction as i32 }
+    }
+}
+
+impl From<AggregateQuery> for proto::types::AggregateQuery {
+    fn from(value: AggregateQuery) -> Self {
+        Self {
+            model: value.model,
+            clause: value.clause.map(|c| c.into()),
+            aggregates: value.aggregates.into_iter().map(|a| a.into()).collect(),
+            group_by: value.group_by,
+        }
+    }
+}
+
+impl From<Aggregate> for proto::types::Aggregate {
+    fn from(value: Aggregate) -> Self {
+        Self { function: value.function as i32, member: value.member }
     }
 }
 
@@ -224,6 +347,15 @@ impl From<Clause> for proto::types::Clause {
             Clause::Composite(clause) => Self {
                 clause_type: Some(proto::types::clause::ClauseType::Composite(clause.into())),
             },
+            Clause::Enum(clause) => {
+                Self { clause_type: Some(proto::types::clause::ClauseType::Enum(clause.into())) }
+            }
+            Clause::Array(clause) => {
+                Self { clause_type: Some(proto::types::clause::ClauseType::Array(clause.into())) }
+            }
+            Clause::Search(clause) => {
+                Self { clause_type: Some(proto::types::clause::ClauseType::Search(clause.into())) }
+            }
         }
     }
 }
@@ -290,6 +422,41 @@ impl From<MemberClause> for proto::types::MemberClause {
     }
 }
 
+impl From<EnumClause> for proto::types::EnumClause {
+    fn from(value: EnumClause) -> Self {
+        Self {
+            model: value.model,
+            member: value.member,
+            operator: value.operator as i32,
+            option: value.option,
+        }
+    }
+}
+
+impl From<ArrayClause> for proto::types::ArrayClause {
+    fn from(value: ArrayClause) -> Self {
+        let predicate = match value.predicate {
+            ArrayPredicate::Contains(primitive) => {
+                proto::types::array_clause::Predicate::Contains(primitive.into())
+            }
+            ArrayPredicate::Length(operator, value) => {
+                proto::types::array_clause::Predicate::Length(proto::types::ArrayLength {
+                    operator: operator as i32,
+                    value,
+                })
+            }
+        };
+
+        Self { model: value.model, member: value.member, predicate: Some(predicate) }
+    }
+}
+
+impl From<SearchClause> for proto::types::SearchClause {
+    fn from(value: SearchClause) -> Self {
+        Self { text: value.text }
+    }
+}
+
 impl From<CompositeClause> for proto::types::CompositeClause {
     fn from(value: CompositeClause) -> Self {
         Self {
@@ -380,15 +547,25 @@ impl From<proto::types::Event> for Event {
     }
 }
 
+/// A query of raw events. The cursor was added for cursor pagination, it defaults to the first page
+/// when left out of serialized queries.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
 pub struct EventQuery {
     pub keys: KeysClause,
     pub limit: u32,
     pub offset: u32,
+    /// The `next_cursor` of the previous page, empty for the first one.
+    #[serde(default)]
+    pub cursor: String,
 }
 
 impl From<EventQuery> for proto::types::EventQuery {
     fn from(value: EventQuery) -> Self {
-        Self { keys: Some(value.keys.into()), limit: value.limit, offset: value.offset }
+        Self {
+            keys: Some(value.keys.into()),
+            limit: value.limit,
+            offset: value.offset,
+            cursor: value.cursor,
+        }
     }
 }
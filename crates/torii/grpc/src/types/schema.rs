@@ -30,6 +30,17 @@ pub struct Entity {
     pub models: Vec<Struct>,
 }
 
+/// An update of an entity streamed by a subscription.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub enum EntityUpdate {
+    /// Models of the entity were set.
+    Updated(Entity),
+    /// The entity with these hashed keys was deleted.
+    Deleted(Felt),
+    /// The entity with these hashed keys no longer matches the clause of the subscription.
+    Unmatched(Felt),
+}
+
 impl TryFrom<proto::types::Entity> for Entity {
     type Error = SchemaError;
     fn try_from(entity: proto::types::Entity) -> Result<Self, Self::Error> {
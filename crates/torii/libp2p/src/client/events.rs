@@ -1,12 +1,18 @@
 use gossipsub::Event as GossipsubEvent;
+use libp2p::request_response::Event as RequestResponseEvent;
 use libp2p::{gossipsub, identify, ping};
 
+use crate::types::{HistoryRequest, HistoryResponse};
+
+type HistoryEvent = RequestResponseEvent<HistoryRequest, HistoryResponse>;
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub(crate) enum ClientEvent {
     Gossipsub(GossipsubEvent),
     Identify(identify::Event),
     Ping(ping::Event),
+    History(HistoryEvent),
 }
 
 impl From<GossipsubEvent> for ClientEvent {
@@ -26,3 +32,9 @@ impl From<ping::Event> for ClientEvent {
         Self::Ping(event)
     }
 }
+
+impl From<HistoryEvent> for ClientEvent {
+    fn from(event: HistoryEvent) -> Self {
+        Self::History(event)
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -5,9 +6,12 @@ use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::channel::oneshot;
 use futures::lock::Mutex;
 use futures::{select, StreamExt};
+use libp2p::core::ConnectedPoint;
 use libp2p::gossipsub::{self, IdentTopic, MessageId};
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{self, OutboundRequestId, ProtocolSupport};
 use libp2p::swarm::{NetworkBehaviour, Swarm, SwarmEvent};
-use libp2p::{identify, identity, ping, Multiaddr, PeerId};
+use libp2p::{identify, identity, ping, Multiaddr, PeerId, StreamProtocol};
 #[cfg(not(target_arch = "wasm32"))]
 use libp2p::{noise, tcp, yamux};
 use tracing::info;
@@ -16,7 +20,7 @@ pub mod events;
 use crate::client::events::ClientEvent;
 use crate::constants;
 use crate::errors::Error;
-use crate::types::Message;
+use crate::types::{HistoryRequest, HistoryResponse, Message};
 
 pub(crate) const LOG_TARGET: &str = "torii::relay::client";
 
@@ -26,6 +30,7 @@ struct Behaviour {
     gossipsub: gossipsub::Behaviour,
     identify: identify::Behaviour,
     ping: ping::Behaviour,
+    history: request_response::json::Behaviour<HistoryRequest, HistoryResponse>,
 }
 
 #[derive(Debug)]
@@ -38,11 +43,24 @@ pub struct RelayClient {
 pub struct EventLoop {
     swarm: Swarm<Behaviour>,
     command_receiver: UnboundedReceiver<Command>,
+    // address of the relay dialed, and its peer id once connected, other peers can connect to the
+    // client
+    relay_addr: Multiaddr,
+    relay_peer_id: Option<PeerId>,
+    history_requests: HashMap<OutboundRequestId, oneshot::Sender<Result<History, Error>>>,
+}
+
+// The latest messages set by a relay, oldest first.
+#[derive(Debug)]
+pub struct History {
+    pub relay: PeerId,
+    pub messages: Vec<Message>,
 }
 
 #[derive(Debug)]
 enum Command {
     Publish(Message, oneshot::Sender<Result<MessageId, Error>>),
+    History(u32, oneshot::Sender<Result<History, Error>>),
 }
 
 impl RelayClient {
@@ -76,6 +94,13 @@ impl RelayClient {
                         key.public(),
                     )),
                     ping: ping::Behaviour::new(ping::Config::default()),
+                    history: request_response::json::Behaviour::new(
+                        [(
+                            StreamProtocol::new(constants::HISTORY_PROTOCOL),
+                            ProtocolSupport::Outbound,
+                        )],
+                        request_response::Config::default(),
+                    ),
                 }
             })?
             .with_swarm_config(|cfg| {
@@ -86,12 +111,13 @@ impl RelayClient {
             .build();
 
         info!(target: LOG_TARGET, addr = %relay_addr, "Dialing relay.");
-        swarm.dial(relay_addr.parse::<Multiaddr>()?)?;
+        let relay_addr = relay_addr.parse::<Multiaddr>()?;
+        swarm.dial(relay_addr.clone())?;
 
         let (command_sender, command_receiver) = futures::channel::mpsc::unbounded();
         Ok(Self {
             command_sender: CommandSender::new(command_sender),
-            event_loop: Arc::new(Mutex::new(EventLoop { swarm, command_receiver })),
+            event_loop: Arc::new(Mutex::new(EventLoop::new(swarm, command_receiver, relay_addr))),
         })
     }
 
@@ -127,6 +153,13 @@ impl RelayClient {
                         key.public(),
                     )),
                     ping: ping::Behaviour::new(ping::Config::default()),
+                    history: request_response::json::Behaviour::new(
+                        [(
+                            StreamProtocol::new(constants::HISTORY_PROTOCOL),
+                            ProtocolSupport::Outbound,
+                        )],
+                        request_response::Config::default(),
+                    ),
                 }
             })?
             .with_swarm_config(|cfg| {
@@ -137,12 +170,13 @@ impl RelayClient {
             .build();
 
         info!(target: LOG_TARGET, addr = %relay_addr, "Dialing relay.");
-        swarm.dial(relay_addr.parse::<Multiaddr>()?)?;
+        let relay_addr = relay_addr.parse::<Multiaddr>()?;
+        swarm.dial(relay_addr.clone())?;
 
         let (command_sender, command_receiver) = futures::channel::mpsc::unbounded();
         Ok(Self {
             command_sender: CommandSender::new(command_sender),
-            event_loop: Arc::new(Mutex::new(EventLoop { swarm, command_receiver })),
+            event_loop: Arc::new(Mutex::new(EventLoop::new(swarm, command_receiver, relay_addr))),
         })
     }
 }
@@ -164,9 +198,37 @@ impl CommandSender {
 
         rx.await.expect("Failed to receive response")
     }
+
+    // Requests the `limit` latest messages set by the relay.
+    pub async fn history(&self, limit: u32) -> Result<History, Error> {
+        let (tx, rx) = oneshot::channel();
+
+        self.sender.unbounded_send(Command::History(limit, tx)).expect("Failed to send command");
+
+        rx.await.expect("Failed to receive response")
+    }
 }
 
 impl EventLoop {
+    fn new(
+        swarm: Swarm<Behaviour>,
+        command_receiver: UnboundedReceiver<Command>,
+        relay_addr: Multiaddr,
+    ) -> Self {
+        let relay_peer_id = relay_addr.iter().find_map(|protocol| match protocol {
+            Protocol::P2p(peer_id) => Some(peer_id),
+            _ => None,
+        });
+
+        Self {
+            swarm,
+            command_receiver,
+            relay_addr,
+            relay_peer_id,
+            history_requests: HashMap::new(),
+        }
+    }
+
     async fn handle_command(
         &mut self,
         command: Command,
@@ -182,6 +244,22 @@ impl EventLoop {
                     sender.send(self.publish(&data)).expect("Failed to send response");
                 }
             }
+            Command::History(limit, sender) => {
+                if !is_relay_ready {
+                    commands_queue.lock().await.push(Command::History(limit, sender));
+                } else if let Some(relay_peer_id) = self.relay_peer_id {
+                    let request_id = self
+                        .swarm
+                        .behaviour_mut()
+                        .history
+                        .send_request(&relay_peer_id, HistoryRequest { limit });
+                    self.history_requests.insert(request_id, sender);
+                } else {
+                    sender
+                        .send(Err(Error::HistoryError("Not connected to the relay".to_string())))
+                        .expect("Failed to send response");
+                }
+            }
         }
     }
 
@@ -210,6 +288,38 @@ impl EventLoop {
                                 }
                             }
                         }
+                        SwarmEvent::ConnectionEstablished {
+                            peer_id,
+                            endpoint: ConnectedPoint::Dialer { address, .. },
+                            ..
+                        } => {
+                            if self.relay_peer_id.is_none() && address == self.relay_addr {
+                                self.relay_peer_id = Some(peer_id);
+                            }
+                        }
+                        SwarmEvent::Behaviour(ClientEvent::History(
+                            request_response::Event::Message {
+                                peer,
+                                message: request_response::Message::Response {
+                                    request_id,
+                                    response,
+                                },
+                            },
+                        )) => {
+                            if let Some(sender) = self.history_requests.remove(&request_id) {
+                                let history = History { relay: peer, messages: response.messages };
+                                sender.send(Ok(history)).expect("Failed to send response");
+                            }
+                        }
+                        SwarmEvent::Behaviour(ClientEvent::History(
+                            request_response::Event::OutboundFailure { request_id, error, .. },
+                        )) => {
+                            if let Some(sender) = self.history_requests.remove(&request_id) {
+                                sender
+                                    .send(Err(Error::HistoryError(error.to_string())))
+                                    .expect("Failed to send response");
+                            }
+                        }
                         SwarmEvent::ConnectionClosed { cause: Some(cause), .. } => {
                             info!(target: LOG_TARGET, cause = ?cause, "Connection closed.");
 
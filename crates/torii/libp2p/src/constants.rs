@@ -1,3 +1,9 @@
 pub(crate) const GOSSIPSUB_HEARTBEAT_INTERVAL_SECS: u64 = 10;
 pub(crate) const MESSAGING_TOPIC: &str = "message";
 pub(crate) const IDLE_CONNECTION_TIMEOUT_SECS: u64 = 60;
+// How long the hashes of the messages of models without a nonce are kept to reject replays.
+pub(crate) const MESSAGE_HASH_EXPIRY_SECS: u64 = 3600;
+// Number of messages kept by a relay for the clients requesting its history.
+pub(crate) const MESSAGE_HISTORY_SIZE: usize = 1000;
+pub(crate) const HISTORY_PROTOCOL: &str = "/torii-relay/history/0.0.1";
+pub(crate) const CLEANUP_INTERVAL_SECS: u64 = 10;
@@ -4,6 +4,7 @@ use std::io;
 use libp2p::gossipsub::{PublishError, SubscriptionError};
 #[cfg(not(target_arch = "wasm32"))]
 use libp2p::noise;
+use starknet::core::types::Felt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -40,6 +41,21 @@ pub enum Error {
     #[error("Failed to read certificate: {0}")]
     ReadCertificateError(anyhow::Error),
 
+    #[error("Peer address {0} is missing its /p2p/ peer id")]
+    MissingPeerId(String),
+
+    #[error("Failed to request message history: {0}")]
+    HistoryError(String),
+
     #[error("Invalid message provided: {0}")]
     InvalidMessageError(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid nonce: {0}")]
+    InvalidNonce(String),
+
+    #[error("Message {0:#x} was already received")]
+    ReplayedMessage(Felt),
 }
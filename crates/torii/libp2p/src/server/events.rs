@@ -1,7 +1,14 @@
+use std::convert::Infallible;
+
 use libp2p::gossipsub::Event as GossipsubEvent;
 use libp2p::identify::Event as IdentifyEvent;
 use libp2p::ping::Event as PingEvent;
 use libp2p::relay::Event as RelayEvent;
+use libp2p::request_response::Event as RequestResponseEvent;
+
+use crate::types::{HistoryRequest, HistoryResponse};
+
+type HistoryEvent = RequestResponseEvent<HistoryRequest, HistoryResponse>;
 
 #[derive(Debug)]
 pub enum ServerEvent {
@@ -9,6 +16,7 @@ pub enum ServerEvent {
     Ping(PingEvent),
     Relay(RelayEvent),
     Gossipsub(GossipsubEvent),
+    History(HistoryEvent),
 }
 
 impl From<IdentifyEvent> for ServerEvent {
@@ -34,3 +42,16 @@ impl From<GossipsubEvent> for ServerEvent {
         Self::Gossipsub(event)
     }
 }
+
+impl From<HistoryEvent> for ServerEvent {
+    fn from(event: HistoryEvent) -> Self {
+        Self::History(event)
+    }
+}
+
+// The block list behaviour doesn't emit any event
+impl From<Infallible> for ServerEvent {
+    fn from(event: Infallible) -> Self {
+        match event {}
+    }
+}
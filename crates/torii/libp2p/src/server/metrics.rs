@@ -0,0 +1,21 @@
+use dojo_metrics::metrics::{Counter, Gauge};
+use dojo_metrics::Metrics;
+
+#[derive(Metrics)]
+#[metrics(scope = "relay")]
+pub(crate) struct RelayMetrics {
+    /// The number of messages received from the peers.
+    pub(crate) messages_received_total: Counter,
+    /// The number of messages rejected because they are invalid.
+    pub(crate) messages_invalid_total: Counter,
+    /// The number of messages rejected because their peer exceeded its rate limit.
+    pub(crate) peer_rate_limited_total: Counter,
+    /// The number of messages rejected because their identity exceeded its rate limit.
+    pub(crate) identity_rate_limited_total: Counter,
+    /// The number of times peers were banned.
+    pub(crate) peers_banned_total: Counter,
+    /// The number of peers currently banned.
+    pub(crate) banned_peers: Gauge,
+    /// The number of connected peers with a gossipsub score below the graylist threshold.
+    pub(crate) graylisted_peers: Gauge,
+}
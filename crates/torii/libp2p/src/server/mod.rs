@@ -1,9 +1,11 @@
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::hash::{Hash, Hasher};
 use std::net::Ipv4Addr;
 use std::path::Path;
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{fs, io};
 
 use chrono::Utc;
@@ -13,17 +15,21 @@ use dojo_types::schema::Ty;
 use dojo_world::contracts::naming::compute_selector_from_names;
 use futures::StreamExt;
 use indexmap::IndexMap;
+use libp2p::allow_block_list::{self, BlockedPeers};
 use libp2p::core::multiaddr::Protocol;
 use libp2p::core::muxing::StreamMuxerBox;
 use libp2p::core::Multiaddr;
-use libp2p::gossipsub::{self, IdentTopic};
+use libp2p::gossipsub::{self, IdentTopic, PeerScoreParams, PeerScoreThresholds};
+use libp2p::request_response::{self, ProtocolSupport};
 use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
-use libp2p::{identify, identity, noise, ping, relay, tcp, yamux, PeerId, Swarm, Transport};
+use libp2p::{
+    identify, identity, noise, ping, relay, tcp, yamux, PeerId, StreamProtocol, Swarm, Transport,
+};
 use libp2p_webrtc as webrtc;
 use rand::thread_rng;
-use starknet::core::types::{BlockId, BlockTag, Felt, FunctionCall};
-use starknet::core::utils::get_selector_from_name;
-use starknet::providers::Provider;
+use starknet::core::types::{BlockId, BlockTag, Felt, FunctionCall, StarknetError};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::{Provider, ProviderError};
 use starknet_crypto::{poseidon_hash_many, verify};
 use torii_core::sql::Sql;
 use tracing::{info, warn};
@@ -33,14 +39,20 @@ use crate::constants;
 use crate::errors::Error;
 
 mod events;
+mod metrics;
+pub mod rate_limit;
 
 use dojo_world::contracts::model::ModelReader;
 
 use crate::server::events::ServerEvent;
+use crate::server::metrics::RelayMetrics;
+use crate::server::rate_limit::{RateLimitConfig, RateLimiter};
 use crate::typed_data::PrimitiveType;
-use crate::types::Message;
+use crate::types::{HistoryRequest, HistoryResponse, Message};
 
 pub(crate) const LOG_TARGET: &str = "torii::relay::server";
+// Member of the models holding the nonce of the entity, checked to reject replayed messages
+pub(crate) const NONCE_MEMBER: &str = "nonce";
 
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "ServerEvent")]
@@ -50,6 +62,8 @@ pub struct Behaviour {
     ping: ping::Behaviour,
     identify: identify::Behaviour,
     gossipsub: gossipsub::Behaviour,
+    history: request_response::json::Behaviour<HistoryRequest, HistoryResponse>,
+    blocked_peers: allow_block_list::Behaviour<BlockedPeers>,
 }
 
 #[allow(missing_debug_implementations)]
@@ -57,6 +71,17 @@ pub struct Relay<P: Provider + Sync> {
     swarm: Swarm<Behaviour>,
     db: Sql,
     provider: Box<P>,
+    history: MessageHistory,
+    // other relays of the world, redialed when their connection is closed
+    bootstrap_peers: HashMap<PeerId, Multiaddr>,
+    limits: RateLimitConfig,
+    peer_limiter: RateLimiter<PeerId>,
+    identity_limiter: RateLimiter<Felt>,
+    // rejected messages of the peers, which are banned when they exceed the ban threshold
+    penalties: RateLimiter<PeerId>,
+    // end of the bans of the peers
+    banned_peers: HashMap<PeerId, Instant>,
+    metrics: RelayMetrics,
 }
 
 impl<P: Provider + Sync> Relay<P> {
@@ -67,6 +92,8 @@ impl<P: Provider + Sync> Relay<P> {
         port_webrtc: u16,
         local_key_path: Option<String>,
         cert_path: Option<String>,
+        peers: Vec<String>,
+        limits: RateLimitConfig,
     ) -> Result<Self, Error> {
         let local_key = if let Some(path) = local_key_path {
             let path = Path::new(&path);
@@ -109,6 +136,17 @@ impl<P: Provider + Sync> Relay<P> {
                         .build()
                         .map_err(|msg| io::Error::new(io::ErrorKind::Other, msg)).unwrap(); // Temporary hack because `build` does not return a proper `std::error::Error`.
 
+                let mut gossipsub = gossipsub::Behaviour::new(
+                    gossipsub::MessageAuthenticity::Signed(key.clone()),
+                    gossipsub_config,
+                )
+                .unwrap();
+                // The application score of the peers is lowered by their rejected messages, they
+                // are ignored once their score is below the graylist threshold.
+                gossipsub
+                    .with_peer_score(PeerScoreParams::default(), PeerScoreThresholds::default())
+                    .unwrap();
+
                 Behaviour {
                     relay: relay::Behaviour::new(key.public().to_peer_id(), Default::default()),
                     ping: ping::Behaviour::new(ping::Config::new()),
@@ -116,11 +154,15 @@ impl<P: Provider + Sync> Relay<P> {
                         "/torii-relay/0.0.1".to_string(),
                         key.public(),
                     )),
-                    gossipsub: gossipsub::Behaviour::new(
-                        gossipsub::MessageAuthenticity::Signed(key.clone()),
-                        gossipsub_config,
-                    )
-                    .unwrap(),
+                    gossipsub,
+                    history: request_response::json::Behaviour::new(
+                        [(
+                            StreamProtocol::new(constants::HISTORY_PROTOCOL),
+                            ProtocolSupport::Inbound,
+                        )],
+                        request_response::Config::default(),
+                    ),
+                    blocked_peers: allow_block_list::Behaviour::default(),
                 }
             })?
             .with_swarm_config(|cfg| {
@@ -155,12 +197,143 @@ impl<P: Provider + Sync> Relay<P> {
             .subscribe(&IdentTopic::new(constants::MESSAGING_TOPIC))
             .unwrap();
 
-        Ok(Self { swarm, db: pool, provider: Box::new(provider) })
+        // Relays of the same world federate by joining each other's gossipsub mesh. They are added
+        // as explicit peers so that every message is forwarded to them.
+        let mut bootstrap_peers = HashMap::new();
+        for peer in peers {
+            let addr = peer.parse::<Multiaddr>()?;
+            let Some(Protocol::P2p(peer_id)) = addr.iter().last() else {
+                return Err(Error::MissingPeerId(peer));
+            };
+
+            info!(target: LOG_TARGET, addr = %addr, "Dialing bootstrap relay.");
+            swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
+            swarm.dial(addr.clone())?;
+            bootstrap_peers.insert(peer_id, addr);
+        }
+
+        Ok(Self {
+            swarm,
+            db: pool,
+            provider: Box::new(provider),
+            history: MessageHistory::new(constants::MESSAGE_HISTORY_SIZE),
+            bootstrap_peers,
+            peer_limiter: RateLimiter::new(limits.peer_messages, limits.window),
+            identity_limiter: RateLimiter::new(limits.identity_messages, limits.window),
+            penalties: RateLimiter::new(limits.ban_threshold, limits.window),
+            limits,
+            banned_peers: HashMap::new(),
+            metrics: RelayMetrics::default(),
+        })
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        *self.swarm.local_peer_id()
+    }
+
+    fn check_identity(&mut self, identity: Felt, source: PeerId) -> bool {
+        if self.identity_limiter.check(identity) {
+            return true;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            identity = %format!("{:#x}", identity),
+            peer_id = %source,
+            "Identity exceeded its rate limit."
+        );
+        self.metrics.identity_rate_limited_total.increment(1);
+        self.penalize(source);
+        false
+    }
+
+    fn reject(&mut self, peer_id: PeerId) {
+        self.metrics.messages_invalid_total.increment(1);
+        self.penalize(peer_id);
+    }
+
+    // Lowers the gossipsub score of the peer, and bans it if it exceeded the ban threshold. The
+    // other relays aren't penalized for the messages they forward.
+    fn penalize(&mut self, peer_id: PeerId) {
+        if self.bootstrap_peers.contains_key(&peer_id) {
+            return;
+        }
+
+        let below_threshold = self.penalties.check(peer_id);
+        self.swarm
+            .behaviour_mut()
+            .gossipsub
+            .set_application_score(&peer_id, -(self.penalties.count(&peer_id) as f64));
+
+        if !below_threshold && !self.banned_peers.contains_key(&peer_id) {
+            info!(
+                target: LOG_TARGET,
+                peer_id = %peer_id,
+                duration = ?self.limits.ban_duration,
+                "Banning peer."
+            );
+
+            self.banned_peers.insert(peer_id, Instant::now() + self.limits.ban_duration);
+            self.swarm.behaviour_mut().blocked_peers.block_peer(peer_id);
+            self.swarm.behaviour_mut().gossipsub.blacklist_peer(&peer_id);
+            self.metrics.peers_banned_total.increment(1);
+            self.metrics.banned_peers.set(self.banned_peers.len() as f64);
+        }
+    }
+
+    // Lifts the expired bans, forgets the ended rate limit windows and refreshes the scores of
+    // the peers.
+    fn cleanup(&mut self) {
+        let now = Instant::now();
+        let expired = self
+            .banned_peers
+            .iter()
+            .filter(|(_, until)| **until <= now)
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+
+        for peer_id in expired {
+            info!(target: LOG_TARGET, peer_id = %peer_id, "Unbanning peer.");
+
+            self.banned_peers.remove(&peer_id);
+            self.swarm.behaviour_mut().blocked_peers.unblock_peer(peer_id);
+            self.swarm.behaviour_mut().gossipsub.remove_blacklisted_peer(&peer_id);
+        }
+
+        self.peer_limiter.prune();
+        self.identity_limiter.prune();
+        self.penalties.prune();
+
+        let graylist_threshold = PeerScoreThresholds::default().graylist_threshold;
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        let peers = gossipsub.all_peers().map(|(peer_id, _)| *peer_id).collect::<Vec<_>>();
+
+        let mut graylisted_peers = 0;
+        for peer_id in peers {
+            gossipsub.set_application_score(&peer_id, -(self.penalties.count(&peer_id) as f64));
+            if gossipsub.peer_score(&peer_id).is_some_and(|score| score < graylist_threshold) {
+                graylisted_peers += 1;
+            }
+        }
+
+        self.metrics.banned_peers.set(self.banned_peers.len() as f64);
+        self.metrics.graylisted_peers.set(graylisted_peers as f64);
     }
 
     pub async fn run(&mut self) {
+        let mut cleanup_interval =
+            tokio::time::interval(Duration::from_secs(constants::CLEANUP_INTERVAL_SECS));
+
         loop {
-            match self.swarm.next().await.expect("Infinite Stream.") {
+            let event = tokio::select! {
+                event = self.swarm.next() => event.expect("Infinite Stream."),
+                _ = cleanup_interval.tick() => {
+                    self.cleanup();
+                    continue;
+                }
+            };
+
+            match event {
                 SwarmEvent::Behaviour(event) => {
                     match &event {
                         ServerEvent::Gossipsub(gossipsub::Event::Message {
@@ -168,6 +341,22 @@ impl<P: Provider + Sync> Relay<P> {
                             message_id,
                             message,
                         }) => {
+                            // messages forwarded by other relays are limited by their publisher
+                            let source = message.source.unwrap_or(*peer_id);
+                            self.metrics.messages_received_total.increment(1);
+
+                            if !self.peer_limiter.check(source) {
+                                info!(
+                                    target: LOG_TARGET,
+                                    message_id = %message_id,
+                                    peer_id = %source,
+                                    "Peer exceeded its rate limit."
+                                );
+                                self.metrics.peer_rate_limited_total.increment(1);
+                                self.penalize(source);
+                                continue;
+                            }
+
                             // Deserialize typed data.
                             // We shouldn't panic here
                             let data = match serde_json::from_slice::<Message>(&message.data) {
@@ -178,6 +367,7 @@ impl<P: Provider + Sync> Relay<P> {
                                         error = %e,
                                         "Deserializing message."
                                     );
+                                    self.reject(source);
                                     continue;
                                 }
                             };
@@ -190,6 +380,7 @@ impl<P: Provider + Sync> Relay<P> {
                                         error = %e,
                                         "Validating message."
                                     );
+                                    self.reject(source);
                                     continue;
                                 }
                             };
@@ -227,13 +418,32 @@ impl<P: Provider + Sync> Relay<P> {
                                 }
                             };
 
+                            let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
+
+                            // the nonce of the message has to be greater than the one of the
+                            // entity, if the model has one
+                            let has_nonce = match validate_nonce(&self.db, &ty, &entity_id).await {
+                                Ok(has_nonce) => has_nonce,
+                                Err(e) => {
+                                    info!(
+                                        target: LOG_TARGET,
+                                        error = %e,
+                                        message_id = %message_id,
+                                        peer_id = %peer_id,
+                                        "Validating nonce."
+                                    );
+                                    self.reject(source);
+                                    continue;
+                                }
+                            };
+
                             // select only identity field, if doesn't exist, empty string
                             let query = format!(
                                 "SELECT external_identity FROM [{}] WHERE id = ?",
                                 ty.name()
                             );
                             let entity_identity: Option<String> = match sqlx::query_scalar(&query)
-                                .bind(format!("{:#x}", poseidon_hash_many(&keys)))
+                                .bind(&entity_id)
                                 .fetch_optional(&mut *pool)
                                 .await
                             {
@@ -248,50 +458,35 @@ impl<P: Provider + Sync> Relay<P> {
                                 }
                             };
 
-                            if entity_identity.is_none() {
-                                // we can set the entity without checking identity
-                                if let Err(e) = self
-                                    .db
-                                    .set_entity(
-                                        ty,
-                                        &message_id.to_string(),
-                                        Utc::now().timestamp() as u64,
-                                    )
-                                    .await
-                                {
-                                    info!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Setting message."
-                                    );
-                                    continue;
-                                } else {
-                                    info!(
-                                        target: LOG_TARGET,
-                                        message_id = %message_id,
-                                        peer_id = %peer_id,
-                                        "Message set."
-                                    );
-                                    continue;
-                                }
-                            }
-
-                            let entity_identity = match Felt::from_str(&entity_identity.unwrap()) {
-                                Ok(identity) => identity,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Parsing identity."
-                                    );
-                                    continue;
-                                }
+                            // an existing entity is owned by the identity it was set with, a new
+                            // one by the identity of its message, which has to sign it either way
+                            let entity_identity = match entity_identity {
+                                Some(identity) => match Felt::from_str(&identity) {
+                                    Ok(identity) => identity,
+                                    Err(e) => {
+                                        warn!(
+                                            target: LOG_TARGET,
+                                            error = %e,
+                                            "Parsing identity."
+                                        );
+                                        continue;
+                                    }
+                                },
+                                None => match message_identity(&ty) {
+                                    Some(identity) => identity,
+                                    None => {
+                                        info!(
+                                            target: LOG_TARGET,
+                                            message_id = %message_id,
+                                            peer_id = %peer_id,
+                                            "Message has no identity to verify its signature."
+                                        );
+                                        self.reject(source);
+                                        continue;
+                                    }
+                                },
                             };
 
-                            // TODO: have a nonce in model to check
-                            // against entity nonce and message nonce
-                            // to prevent replay attacks.
-
                             // Verify the signature
                             let message_hash =
                                 if let Ok(message) = data.message.encode(entity_identity) {
@@ -304,57 +499,60 @@ impl<P: Provider + Sync> Relay<P> {
                                     continue;
                                 };
 
-                            let public_key = match self
-                                .provider
-                                .call(
-                                    FunctionCall {
-                                        contract_address: entity_identity,
-                                        entry_point_selector: get_selector_from_name(
-                                            "getPublicKey",
-                                        )
-                                        .unwrap(),
-                                        calldata: vec![],
-                                    },
-                                    BlockId::Tag(BlockTag::Pending),
-                                )
-                                .await
-                            {
-                                Ok(res) => res[0],
-                                Err(e) => {
-                                    warn!(
+                            // models without a nonce are protected against replays by the hashes
+                            // of the messages received recently
+                            let received_at = Utc::now().timestamp();
+                            if !has_nonce {
+                                if let Err(e) =
+                                    validate_message_hash(&self.db, message_hash, received_at).await
+                                {
+                                    info!(
                                         target: LOG_TARGET,
                                         error = %e,
-                                        "Fetching public key."
+                                        message_id = %message_id,
+                                        peer_id = %peer_id,
+                                        "Validating message hash."
                                     );
+                                    self.reject(source);
                                     continue;
                                 }
-                            };
+                            }
 
-                            if !match verify(
-                                &public_key,
-                                &message_hash,
-                                &data.signature_r,
-                                &data.signature_s,
-                            ) {
-                                Ok(valid) => valid,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Verifying signature."
-                                    );
-                                    continue;
-                                }
-                            } {
+                            if let Err(e) = validate_signature(
+                                &*self.provider,
+                                entity_identity,
+                                message_hash,
+                                &data.signature,
+                            )
+                            .await
+                            {
                                 info!(
                                     target: LOG_TARGET,
+                                    error = %e,
                                     message_id = %message_id,
                                     peer_id = %peer_id,
-                                    "Invalid signature."
+                                    "Verifying signature."
                                 );
+                                self.reject(source);
+                                continue;
+                            }
+
+                            // identities are limited once their signature is verified, so that
+                            // a peer can't use up the limit of another identity
+                            if !self.check_identity(entity_identity, source) {
                                 continue;
                             }
 
+                            // the hash is recorded with the entity, and the message rejected if it
+                            // has been received in the meantime
+                            if !has_nonce {
+                                self.db.record_message_hash(
+                                    message_hash,
+                                    received_at,
+                                    received_at + constants::MESSAGE_HASH_EXPIRY_SECS as i64,
+                                );
+                            }
+
                             if let Err(e) = self
                                 .db
                                 // event id is message id
@@ -370,8 +568,10 @@ impl<P: Provider + Sync> Relay<P> {
                                     error = %e,
                                     "Setting message."
                                 );
+                                continue;
                             }
 
+                            self.history.push(data);
                             info!(
                                 target: LOG_TARGET,
                                 message_id = %message_id,
@@ -410,6 +610,32 @@ impl<P: Provider + Sync> Relay<P> {
                             );
                             self.swarm.add_external_address(observed_addr.clone());
                         }
+                        ServerEvent::History(request_response::Event::Message {
+                            peer,
+                            message: request_response::Message::Request { request, channel, .. },
+                        }) => {
+                            info!(
+                                target: LOG_TARGET,
+                                peer_id = %peer,
+                                limit = %request.limit,
+                                "Received history request."
+                            );
+
+                            let messages = self.history.latest(request.limit as usize);
+                            if self
+                                .swarm
+                                .behaviour_mut()
+                                .history
+                                .send_response(channel, HistoryResponse { messages })
+                                .is_err()
+                            {
+                                warn!(
+                                    target: LOG_TARGET,
+                                    peer_id = %peer,
+                                    "Sending history response."
+                                );
+                            }
+                        }
                         ServerEvent::Ping(ping::Event { peer, result, .. }) => {
                             info!(
                                 target: LOG_TARGET,
@@ -424,6 +650,18 @@ impl<P: Provider + Sync> Relay<P> {
                 SwarmEvent::NewListenAddr { address, .. } => {
                     info!(target: LOG_TARGET, address = %address, "New listen address.");
                 }
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                    if let Some(addr) = self.bootstrap_peers.get(&peer_id) {
+                        info!(target: LOG_TARGET, addr = %addr, "Redialing bootstrap relay.");
+                        if let Err(e) = self.swarm.dial(addr.clone()) {
+                            warn!(
+                                target: LOG_TARGET,
+                                error = %e,
+                                "Dialing bootstrap relay."
+                            );
+                        }
+                    }
+                }
                 event => {
                     info!(target: LOG_TARGET, event = ?event, "Unhandled event.");
                 }
@@ -432,6 +670,11 @@ impl<P: Provider + Sync> Relay<P> {
     }
 }
 
+// The identity member of the message, for the models that have one
+fn message_identity(ty: &Ty) -> Option<Felt> {
+    ty.as_struct()?.get("identity")?.as_primitive()?.as_contract_address()
+}
+
 fn ty_keys(ty: &Ty) -> Result<Vec<Felt>, Error> {
     if let Ty::Struct(s) = &ty {
         let mut keys = Vec::new();
@@ -687,6 +930,203 @@ async fn validate_message(
     Ok(ty)
 }
 
+// Verifies the signature of the message hash with the SNIP-6 `is_valid_signature` entrypoint of
+// the identity account. Legacy accounts without it are verified against their public key.
+pub(crate) async fn validate_signature<P: Provider + Sync>(
+    provider: &P,
+    identity: Felt,
+    message_hash: Felt,
+    signature: &[Felt],
+) -> Result<(), Error> {
+    validate_signature_with(
+        |call| provider.call(call, BlockId::Tag(BlockTag::Pending)),
+        identity,
+        message_hash,
+        signature,
+    )
+    .await
+}
+
+// Same as `validate_signature`, with the calls to the identity account made by `call`.
+pub(crate) async fn validate_signature_with<F, Fut>(
+    call: F,
+    identity: Felt,
+    message_hash: Felt,
+    signature: &[Felt],
+) -> Result<(), Error>
+where
+    F: Fn(FunctionCall) -> Fut,
+    Fut: Future<Output = Result<Vec<Felt>, ProviderError>>,
+{
+    let mut calldata = vec![message_hash, Felt::from(signature.len())];
+    calldata.extend_from_slice(signature);
+
+    let is_valid_signature = get_selector_from_name("is_valid_signature").unwrap();
+    match call(FunctionCall {
+        contract_address: identity,
+        entry_point_selector: is_valid_signature,
+        calldata,
+    })
+    .await
+    {
+        // accounts return 'VALID', or true for the older ones
+        Ok(res) => {
+            return if res.first().is_some_and(|res| {
+                *res == cairo_short_string_to_felt("VALID").unwrap() || *res == Felt::ONE
+            }) {
+                Ok(())
+            } else {
+                Err(Error::InvalidSignature(format!("Rejected by account {:#x}", identity)))
+            };
+        }
+        // only the accounts without the entrypoint are verified against their public key, an
+        // account reverting rejects the signature
+        Err(e) if is_entrypoint_not_found(&e, is_valid_signature) => {}
+        Err(e) => {
+            return Err(Error::InvalidSignature(format!(
+                "Failed to call is_valid_signature: {}",
+                e
+            )));
+        }
+    }
+
+    let [signature_r, signature_s] = signature else {
+        return Err(Error::InvalidSignature(format!(
+            "Account {:#x} doesn't implement is_valid_signature, expected an (r, s) signature",
+            identity
+        )));
+    };
+
+    let public_key = call(FunctionCall {
+        contract_address: identity,
+        entry_point_selector: get_selector_from_name("getPublicKey").unwrap(),
+        calldata: vec![],
+    })
+    .await
+    .map_err(|e| Error::InvalidSignature(format!("Failed to fetch public key: {}", e)))?;
+
+    let public_key = public_key
+        .first()
+        .ok_or_else(|| Error::InvalidSignature("Public key is missing".to_string()))?;
+
+    match verify(public_key, &message_hash, signature_r, signature_s) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Error::InvalidSignature(format!(
+            "Not signed by the public key of account {:#x}",
+            identity
+        ))),
+        Err(e) => Err(Error::InvalidSignature(e.to_string())),
+    }
+}
+
+// Whether the call failed because the contract doesn't have the entrypoint `selector`, rather
+// than because the entrypoint reverted. The sequencer reports either the selector missing from the
+// class, or the `ENTRYPOINT_NOT_FOUND` error of the call, which isn't wrapped in the
+// `ENTRYPOINT_FAILED` error of a nested call.
+pub(crate) fn is_entrypoint_not_found(error: &ProviderError, selector: Felt) -> bool {
+    let ProviderError::StarknetError(StarknetError::ContractError(data)) = error else {
+        return false;
+    };
+
+    let felts = revert_error_felts(&data.revert_error).collect::<Vec<_>>();
+    let entrypoint_not_found = cairo_short_string_to_felt("ENTRYPOINT_NOT_FOUND").unwrap();
+
+    felts.first() == Some(&selector) || felts.last() == Some(&entrypoint_not_found)
+}
+
+// The felts of a revert error, in order
+fn revert_error_felts(revert_error: &str) -> impl Iterator<Item = Felt> + '_ {
+    revert_error
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.starts_with("0x"))
+        .filter_map(|word| Felt::from_hex(word).ok())
+}
+
+// The latest messages set by the relay, requested by the clients when they connect.
+#[derive(Debug)]
+pub(crate) struct MessageHistory {
+    size: usize,
+    messages: VecDeque<Message>,
+}
+
+impl MessageHistory {
+    pub(crate) fn new(size: usize) -> Self {
+        Self { size, messages: VecDeque::with_capacity(size) }
+    }
+
+    pub(crate) fn push(&mut self, message: Message) {
+        if self.messages.len() == self.size {
+            self.messages.pop_front();
+        }
+
+        self.messages.push_back(message);
+    }
+
+    // The `limit` latest messages, oldest first
+    pub(crate) fn latest(&self, limit: usize) -> Vec<Message> {
+        self.messages.iter().skip(self.messages.len().saturating_sub(limit)).cloned().collect()
+    }
+}
+
+// Checks that the nonce of the message is greater than the nonce stored for the entity. Returns
+// whether the model has a nonce member.
+pub(crate) async fn validate_nonce(db: &Sql, ty: &Ty, entity_id: &str) -> Result<bool, Error> {
+    let Some(nonce) = ty.as_struct().and_then(|s| s.get(NONCE_MEMBER)) else {
+        return Ok(false);
+    };
+
+    let nonce = nonce
+        .as_primitive()
+        .ok_or_else(|| Error::InvalidNonce(format!("{} is not a primitive", NONCE_MEMBER)))?
+        .to_sql_value()
+        .map_err(|_| Error::InvalidNonce("Nonce is missing".to_string()))?;
+
+    // both the integer and the hex string nonces can be compared in sql, hex strings are zero
+    // padded
+    let query = format!(
+        "SELECT EXISTS (SELECT 1 FROM [{}] WHERE id = ? AND external_{} >= ?)",
+        ty.name(),
+        NONCE_MEMBER
+    );
+    let replayed: bool = sqlx::query_scalar(&query)
+        .bind(entity_id)
+        .bind(&nonce)
+        .fetch_one(&db.pool)
+        .await
+        .map_err(|e| Error::InvalidNonce(format!("Failed to fetch entity nonce: {}", e)))?;
+
+    if replayed {
+        return Err(Error::InvalidNonce(format!(
+            "{} is not greater than the nonce of entity {}",
+            nonce, entity_id
+        )));
+    }
+
+    Ok(true)
+}
+
+// Checks that the message hasn't been received before, or that its hash has expired since.
+pub(crate) async fn validate_message_hash(
+    db: &Sql,
+    message_hash: Felt,
+    received_at: i64,
+) -> Result<(), Error> {
+    let replayed: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM message_hashes WHERE hash = ? AND expires_at > ?)",
+    )
+    .bind(format!("{:#x}", message_hash))
+    .bind(received_at)
+    .fetch_one(&db.pool)
+    .await
+    .map_err(|e| Error::InvalidMessageError(format!("Failed to fetch message hash: {}", e)))?;
+
+    if replayed {
+        return Err(Error::ReplayedMessage(message_hash));
+    }
+
+    Ok(())
+}
+
 fn read_or_create_identity(path: &Path) -> anyhow::Result<identity::Keypair> {
     if path.exists() {
         let bytes = fs::read(path)?;
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PEER_MESSAGES_LIMIT: u32 = 100;
+pub const DEFAULT_IDENTITY_MESSAGES_LIMIT: u32 = 50;
+pub const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
+pub const DEFAULT_BAN_THRESHOLD: u32 = 20;
+pub const DEFAULT_BAN_DURATION_SECS: u64 = 600;
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Maximum number of messages of a peer in a window.
+    pub peer_messages: u32,
+    /// Maximum number of messages of a signer identity in a window.
+    pub identity_messages: u32,
+    pub window: Duration,
+    /// Number of rejected messages of a peer in a window after which it is banned.
+    pub ban_threshold: u32,
+    pub ban_duration: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            peer_messages: DEFAULT_PEER_MESSAGES_LIMIT,
+            identity_messages: DEFAULT_IDENTITY_MESSAGES_LIMIT,
+            window: Duration::from_secs(DEFAULT_RATE_LIMIT_WINDOW_SECS),
+            ban_threshold: DEFAULT_BAN_THRESHOLD,
+            ban_duration: Duration::from_secs(DEFAULT_BAN_DURATION_SECS),
+        }
+    }
+}
+
+// Counts the messages of each key in fixed windows.
+#[derive(Debug)]
+pub(crate) struct RateLimiter<K> {
+    limit: u32,
+    window: Duration,
+    // start of the current window and number of messages in it
+    counts: HashMap<K, (Instant, u32)>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub(crate) fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window, counts: HashMap::new() }
+    }
+
+    // Counts a message of the key. Returns false if the key exceeded its limit in the window.
+    pub(crate) fn check(&mut self, key: K) -> bool {
+        let now = Instant::now();
+        let (start, count) = self.counts.entry(key).or_insert((now, 0));
+
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *count = 0;
+        }
+
+        *count += 1;
+        *count <= self.limit
+    }
+
+    pub(crate) fn count(&self, key: &K) -> u32 {
+        self.counts
+            .get(key)
+            .filter(|(start, _)| start.elapsed() < self.window)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    // Removes the keys whose window is over.
+    pub(crate) fn prune(&mut self) {
+        let window = self.window;
+        self.counts.retain(|_, (start, _)| start.elapsed() < window);
+    }
+}
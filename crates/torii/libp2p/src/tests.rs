@@ -265,46 +265,464 @@ mod test {
         );
     }
 
-    // This tests subscribing to a topic and receiving a message
     #[cfg(not(target_arch = "wasm32"))]
     #[tokio::test]
-    async fn test_client_messaging() -> Result<(), Box<dyn Error>> {
-        use std::time::Duration;
+    async fn test_validate_message_hash() -> Result<(), Box<dyn Error>> {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use torii_core::sql::Sql;
+
+        use crate::errors::Error as RelayError;
+        use crate::server::validate_message_hash;
+
+        let options = <SqliteConnectOptions as std::str::FromStr>::from_str("sqlite::memory:")
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await?;
+
+        validate_message_hash(&db, Felt::ONE, 100).await?;
+        db.record_message_hash(Felt::ONE, 100, 200);
+        db.execute().await?;
+
+        // the hashes are stored until they expire
+        assert!(matches!(
+            validate_message_hash(&db, Felt::ONE, 199).await,
+            Err(RelayError::ReplayedMessage(_))
+        ));
+        validate_message_hash(&db, Felt::TWO, 199).await?;
+        validate_message_hash(&db, Felt::ONE, 200).await?;
+
+        // the transaction recording a hash received in the meantime fails
+        db.record_message_hash(Felt::ONE, 150, 250);
+        assert!(db.execute().await.is_err());
+
+        // expired hashes are replaced
+        db.record_message_hash(Felt::ONE, 200, 300);
+        db.execute().await?;
+        assert!(matches!(
+            validate_message_hash(&db, Felt::ONE, 250).await,
+            Err(RelayError::ReplayedMessage(_))
+        ));
+
+        Ok(())
+    }
 
-        use dojo_types::schema::{Member, Struct, Ty};
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_validate_nonce() -> Result<(), Box<dyn Error>> {
         use dojo_world::contracts::abi::model::Layout;
-        use indexmap::IndexMap;
         use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-        use starknet::providers::jsonrpc::HttpTransport;
-        use starknet::providers::JsonRpcClient;
-        use starknet::signers::SigningKey;
-        use starknet_crypto::Felt;
-        use tokio::select;
-        use tokio::time::sleep;
         use torii_core::sql::Sql;
 
-        use crate::server::Relay;
-        use crate::typed_data::{Domain, Field, SimpleField, TypedData};
-        use crate::types::Message;
+        use crate::errors::Error as RelayError;
+        use crate::server::validate_nonce;
 
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
-            .try_init();
-
-        // Database
         let options = <SqliteConnectOptions as std::str::FromStr>::from_str("sqlite::memory:")
             .unwrap()
             .create_if_missing(true);
         let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
         sqlx::migrate!("../migrations").run(&pool).await.unwrap();
 
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await?;
+
+        let message = |nonce: Option<u32>| {
+            Ty::Struct(Struct {
+                name: "types_test-Message".to_string(),
+                children: vec![
+                    Member {
+                        name: "identity".to_string(),
+                        ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::ONE))),
+                        key: true,
+                    },
+                    Member {
+                        name: "nonce".to_string(),
+                        ty: Ty::Primitive(Primitive::U32(nonce)),
+                        key: false,
+                    },
+                ],
+            })
+        };
+
+        let mut model = message(None);
+        if let Ty::Struct(s) = &mut model {
+            s.name = "Message".to_string();
+        }
+        db.register_model(
+            "types_test",
+            model,
+            Layout::Fixed(vec![]),
+            Felt::ZERO,
+            Felt::ZERO,
+            0,
+            0,
+            0,
+        )
+        .await?;
+
+        let entity_id = format!("{:#x}", starknet_crypto::poseidon_hash_many(&[Felt::ONE]));
+
+        // any nonce is valid for a new entity
+        assert!(validate_nonce(&db, &message(Some(2)), &entity_id).await?);
+        db.set_entity(message(Some(2)), "0x1", 0).await?;
+
+        // the nonce has to increase
+        assert!(validate_nonce(&db, &message(Some(10)), &entity_id).await?);
+        assert!(matches!(
+            validate_nonce(&db, &message(Some(2)), &entity_id).await,
+            Err(RelayError::InvalidNonce(_))
+        ));
+        assert!(matches!(
+            validate_nonce(&db, &message(Some(1)), &entity_id).await,
+            Err(RelayError::InvalidNonce(_))
+        ));
+        assert!(matches!(
+            validate_nonce(&db, &message(None), &entity_id).await,
+            Err(RelayError::InvalidNonce(_))
+        ));
+
+        // models without a nonce are checked by message hash instead
+        let without_nonce = Ty::Struct(Struct {
+            name: "types_test-Message".to_string(),
+            children: vec![Member {
+                name: "identity".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::ONE))),
+                key: true,
+            }],
+        });
+        assert!(!validate_nonce(&db, &without_nonce, &entity_id).await?);
+
+        Ok(())
+    }
+
+    // The default Katana accounts are verified with SNIP-6 `is_valid_signature`, the legacy
+    // accounts, without it, with their public key
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_validate_signature() -> Result<(), Box<dyn Error>> {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use starknet::accounts::Account;
+        use starknet::contract::ContractFactory;
+        use starknet::core::types::contract::legacy::LegacyContractClass;
+        use starknet::core::utils::get_contract_address;
+        use starknet::signers::SigningKey;
+        use tokio::time::sleep;
+
+        use crate::errors::Error as RelayError;
+        use crate::server::validate_signature;
+
         let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+        let provider = sequencer.provider();
+        let message_hash = Felt::from(0x1234_u32);
 
-        let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+        // snip-6 account
+        let account_data = sequencer.account_data(0);
+        let signing_key = SigningKey::from_secret_scalar(
+            account_data.private_key.clone().unwrap().secret_scalar(),
+        );
 
-        let account = sequencer.account_data(0);
+        let signature = signing_key.sign(&message_hash).unwrap();
+        validate_signature(
+            provider,
+            account_data.address,
+            message_hash,
+            &[signature.r, signature.s],
+        )
+        .await?;
 
-        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await?;
+        let signature = signing_key.sign(&Felt::ONE).unwrap();
+        assert!(matches!(
+            validate_signature(
+                provider,
+                account_data.address,
+                message_hash,
+                &[signature.r, signature.s]
+            )
+            .await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        // legacy account, only exposing its public key
+        let account = sequencer.account(0);
+        let class: LegacyContractClass =
+            serde_json::from_str(include_str!("../../../katana/contracts/compiled/account.json"))?;
+        let class_hash = class.class_hash().unwrap();
+        account.declare_legacy(Arc::new(class)).send().await?;
+        sleep(Duration::from_secs(1)).await;
+
+        let legacy_key = SigningKey::from_random();
+        let constructor_calldata = vec![legacy_key.verifying_key().scalar()];
+        ContractFactory::new(class_hash, &account)
+            .deploy_v1(constructor_calldata.clone(), Felt::ZERO, false)
+            .send()
+            .await?;
+        sleep(Duration::from_secs(1)).await;
+
+        let legacy_address =
+            get_contract_address(Felt::ZERO, class_hash, &constructor_calldata, Felt::ZERO);
+
+        let signature = legacy_key.sign(&message_hash).unwrap();
+        validate_signature(provider, legacy_address, message_hash, &[signature.r, signature.s])
+            .await?;
+
+        // signed by another key
+        let signature = signing_key.sign(&message_hash).unwrap();
+        assert!(matches!(
+            validate_signature(provider, legacy_address, message_hash, &[signature.r, signature.s])
+                .await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        // legacy accounts only support (r, s) signatures
+        let signature = legacy_key.sign(&message_hash).unwrap();
+        assert!(matches!(
+            validate_signature(
+                provider,
+                legacy_address,
+                message_hash,
+                &[signature.r, signature.s, Felt::ONE]
+            )
+            .await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        Ok(())
+    }
+
+    // Accounts with their own signature schemes are verified by their `is_valid_signature`, with
+    // the whole signature
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_validate_account_signatures() -> Result<(), Box<dyn Error>> {
+        use std::path::PathBuf;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use dojo_world::migration::prepare_contract_declaration_params;
+        use starknet::accounts::Account;
+        use starknet::contract::ContractFactory;
+        use starknet::core::utils::{cairo_short_string_to_felt, get_contract_address};
+        use starknet::signers::SigningKey;
+        use tokio::time::sleep;
+
+        use crate::errors::Error as RelayError;
+        use crate::server::validate_signature;
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+        let provider = sequencer.provider();
+        let account = sequencer.account(0);
+        let message_hash = Felt::from(0x1234_u32);
+
+        // declares the account class of `artifact` and deploys an account with `calldata`
+        let deploy_account = |artifact: &str, calldata: Vec<Felt>| {
+            let account = &account;
+            let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../../katana/contracts/compiled")
+                .join(artifact);
+
+            async move {
+                let (class, compiled_class_hash) = prepare_contract_declaration_params(&path)?;
+                let class_hash = class.class_hash();
+                account.declare_v2(Arc::new(class), compiled_class_hash).send().await?;
+                sleep(Duration::from_secs(1)).await;
+
+                ContractFactory::new(class_hash, account)
+                    .deploy_v1(calldata.clone(), Felt::ZERO, false)
+                    .send()
+                    .await?;
+                sleep(Duration::from_secs(1)).await;
+
+                Ok::<_, Box<dyn Error>>(get_contract_address(
+                    Felt::ZERO,
+                    class_hash,
+                    &calldata,
+                    Felt::ZERO,
+                ))
+            }
+        };
+
+        // argent account with a guardian, signed by both its owner and its guardian
+        let owner = SigningKey::from_random();
+        let guardian = SigningKey::from_random();
+        let argent = deploy_account(
+            "argent_ArgentAccount_0.3.1.json",
+            vec![owner.verifying_key().scalar(), guardian.verifying_key().scalar()],
+        )
+        .await?;
+
+        let signers_signature = |signers: &[&SigningKey]| {
+            signers
+                .iter()
+                .flat_map(|signer| {
+                    let signature = signer.sign(&message_hash).unwrap();
+                    [signature.r, signature.s]
+                })
+                .collect::<Vec<_>>()
+        };
+
+        validate_signature(
+            provider,
+            argent,
+            message_hash,
+            &signers_signature(&[&owner, &guardian]),
+        )
+        .await?;
+
+        let signature = signers_signature(&[&owner, &SigningKey::from_random()]);
+        assert!(matches!(
+            validate_signature(provider, argent, message_hash, &signature).await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        // without the guardian signature the account reverts, and the owner signature alone isn't
+        // verified against a public key
+        let signature = signers_signature(&[&owner]);
+        assert!(matches!(
+            validate_signature(provider, argent, message_hash, &signature).await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        // controller, whose signatures are serialized signer signatures
+        let controller_owner = SigningKey::from_random();
+        let controller_key = controller_owner.verifying_key().scalar();
+        // owner: Signer::Starknet(pubkey), guardian: Option::None
+        let controller = deploy_account(
+            "controller_CartridgeAccount.contract_class.json",
+            vec![Felt::ZERO, controller_key, Felt::ONE],
+        )
+        .await?;
+
+        // Array<SignerSignature> with one SignerSignature::Starknet((pubkey, (r, s)))
+        let controller_signature = |key: &SigningKey| {
+            let signature = key.sign(&message_hash).unwrap();
+            vec![Felt::ONE, Felt::ZERO, key.verifying_key().scalar(), signature.r, signature.s]
+        };
+
+        validate_signature(
+            provider,
+            controller,
+            message_hash,
+            &controller_signature(&controller_owner),
+        )
+        .await?;
+        assert!(matches!(
+            validate_signature(
+                provider,
+                controller,
+                message_hash,
+                &controller_signature(&SigningKey::from_random())
+            )
+            .await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        // a session the controller didn't register is rejected by the account
+        let session = SigningKey::from_random();
+        let session_signature = session.sign(&message_hash).unwrap();
+        let signature = vec![
+            cairo_short_string_to_felt("session-token").unwrap(),
+            session.verifying_key().scalar(),
+            Felt::from(1_000_u32),
+            session_signature.r,
+            session_signature.s,
+        ];
+        assert!(matches!(
+            validate_signature(provider, controller, message_hash, &signature).await,
+            Err(RelayError::InvalidSignature(_))
+        ));
+
+        Ok(())
+    }
+
+    // Only the accounts without `is_valid_signature` fall back to their public key, not the ones
+    // reverting
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_is_entrypoint_not_found() {
+        use starknet::core::types::{ContractErrorData, StarknetError};
+        use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+        use starknet::providers::ProviderError;
+
+        use crate::server::is_entrypoint_not_found;
+
+        let selector = get_selector_from_name("is_valid_signature").unwrap();
+        let contract_error = |revert_error: String| {
+            ProviderError::StarknetError(StarknetError::ContractError(ContractErrorData {
+                revert_error,
+            }))
+        };
+        let not_found = cairo_short_string_to_felt("ENTRYPOINT_NOT_FOUND").unwrap();
+        let failed = cairo_short_string_to_felt("ENTRYPOINT_FAILED").unwrap();
+
+        // katana, and other sequencers, reporting the missing selector
+        assert!(is_entrypoint_not_found(
+            &contract_error(format!("entry point {:#x} not found in contract", selector)),
+            selector
+        ));
+        assert!(is_entrypoint_not_found(
+            &contract_error(format!(
+                "Entry point EntryPointSelector({:#x}) not found in contract.",
+                selector
+            )),
+            selector
+        ));
+        assert!(is_entrypoint_not_found(
+            &contract_error(format!(
+                "Execution failed. Failure reason: {:#x} ('ENTRYPOINT_NOT_FOUND').",
+                not_found
+            )),
+            selector
+        ));
+
+        // the account reverted, possibly because of one of its own calls
+        assert!(!is_entrypoint_not_found(
+            &contract_error(format!(
+                "Execution failed. Failure reason: {:#x} ('argent/invalid-signature-length').",
+                cairo_short_string_to_felt("argent/invalid-signature-length").unwrap()
+            )),
+            selector
+        ));
+        assert!(!is_entrypoint_not_found(
+            &contract_error(format!(
+                "Error in the called contract ({:#x}): Execution failed. Failure reason: ({:#x}, \
+                 {:#x}).",
+                Felt::ONE,
+                not_found,
+                failed
+            )),
+            selector
+        ));
+        assert!(!is_entrypoint_not_found(
+            &contract_error(format!(
+                "Error in the called contract ({:#x}) at entry point {:#x}: Entry point not found.",
+                Felt::ONE,
+                selector
+            )),
+            selector
+        ));
+        assert!(!is_entrypoint_not_found(
+            &ProviderError::StarknetError(StarknetError::ContractNotFound),
+            selector
+        ));
+    }
+
+    // Database with the model of our Message registered
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn message_db() -> (sqlx::Pool<sqlx::Sqlite>, torii_core::sql::Sql) {
+        use dojo_world::contracts::abi::model::Layout;
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use torii_core::sql::Sql;
+
+        let options = <SqliteConnectOptions as std::str::FromStr>::from_str("sqlite::memory:")
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let mut db = Sql::new(pool.clone(), Felt::ZERO, Felt::ZERO).await.unwrap();
 
         // Register the model of our Message
         db.register_model(
@@ -334,17 +752,14 @@ mod test {
         .await
         .unwrap();
 
-        // Initialize the relay server
-        let mut relay_server = Relay::new(db, provider, 9900, 9901, None, None)?;
-        tokio::spawn(async move {
-            relay_server.run().await;
-        });
+        (pool, db)
+    }
 
-        // Initialize the first client (listener)
-        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9900".to_string())?;
-        tokio::spawn(async move {
-            client.event_loop.lock().await.run().await;
-        });
+    #[cfg(not(target_arch = "wasm32"))]
+    fn message_typed_data(identity: Felt, text: &str) -> crate::typed_data::TypedData {
+        use indexmap::IndexMap;
+
+        use crate::typed_data::{Domain, Field, SimpleField, TypedData};
 
         let mut typed_data = TypedData::new(
             IndexMap::from_iter(vec![
@@ -411,11 +826,11 @@ mod test {
                 vec![
                     (
                         "identity".to_string(),
-                        crate::typed_data::PrimitiveType::String(account.address.to_string()),
+                        crate::typed_data::PrimitiveType::String(identity.to_string()),
                     ),
                     (
                         "message".to_string(),
-                        crate::typed_data::PrimitiveType::String("mimi".to_string()),
+                        crate::typed_data::PrimitiveType::String(text.to_string()),
                     ),
                 ]
                 .into_iter()
@@ -423,6 +838,52 @@ mod test {
             ),
         );
 
+        typed_data
+    }
+
+    // This tests subscribing to a topic and receiving a message
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_client_messaging() -> Result<(), Box<dyn Error>> {
+        use std::time::Duration;
+
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use starknet_crypto::Felt;
+        use tokio::select;
+        use tokio::time::sleep;
+
+        use crate::server::Relay;
+        use crate::types::Message;
+
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
+            .try_init();
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+
+        let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+
+        let account = sequencer.account_data(0);
+
+        let (pool, db) = message_db().await;
+
+        // Initialize the relay server
+        let mut relay_server =
+            Relay::new(db, provider, 9900, 9901, None, None, vec![], Default::default())?;
+        tokio::spawn(async move {
+            relay_server.run().await;
+        });
+
+        // Initialize the first client (listener)
+        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9900".to_string())?;
+        tokio::spawn(async move {
+            client.event_loop.lock().await.run().await;
+        });
+
+        let typed_data = message_typed_data(account.address, "mimi");
+
         let message_hash = typed_data.encode(account.address).unwrap();
         let signature =
             SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar())
@@ -431,11 +892,7 @@ mod test {
 
         client
             .command_sender
-            .publish(Message {
-                message: typed_data,
-                signature_r: signature.r,
-                signature_s: signature.s,
-            })
+            .publish(Message { message: typed_data, signature: vec![signature.r, signature.s] })
             .await?;
 
         sleep(std::time::Duration::from_secs(2)).await;
@@ -454,6 +911,255 @@ mod test {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_message_history() {
+        use crate::server::MessageHistory;
+        use crate::types::Message;
+
+        let mut history = MessageHistory::new(2);
+        for i in 0..3_u32 {
+            history.push(Message {
+                message: message_typed_data(Felt::ONE, "mimi"),
+                signature: vec![Felt::from(i)],
+            });
+        }
+
+        // the oldest message is dropped
+        let signatures = |messages: Vec<Message>| {
+            messages.into_iter().map(|m| m.signature[0]).collect::<Vec<_>>()
+        };
+        assert_eq!(signatures(history.latest(10)), vec![Felt::ONE, Felt::TWO]);
+        assert_eq!(signatures(history.latest(1)), vec![Felt::TWO]);
+        assert!(history.latest(0).is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_message_formats() {
+        use crate::types::Message;
+
+        // the signature was (r, s) before the signatures of any length
+        let typed_data = serde_json::to_value(message_typed_data(Felt::ONE, "mimi")).unwrap();
+        let legacy = serde_json::json!({
+            "message": typed_data,
+            "signature_r": "0x1",
+            "signature_s": "0x2",
+        });
+        let message: Message = serde_json::from_value(legacy).unwrap();
+        assert_eq!(message.signature, vec![Felt::ONE, Felt::TWO]);
+
+        let signature = vec![Felt::ONE, Felt::TWO, Felt::THREE];
+        let message = Message { message: message.message, signature: signature.clone() };
+        let message: Message =
+            serde_json::from_slice(&serde_json::to_vec(&message).unwrap()).unwrap();
+        assert_eq!(message.signature, signature);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_rate_limiter() {
+        use std::time::Duration;
+
+        use crate::server::rate_limit::RateLimiter;
+
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(60));
+        assert!(limiter.check(Felt::ONE));
+        assert!(limiter.check(Felt::ONE));
+        assert!(!limiter.check(Felt::ONE));
+        assert_eq!(limiter.count(&Felt::ONE), 3);
+
+        // keys are limited independently
+        assert!(limiter.check(Felt::TWO));
+        assert_eq!(limiter.count(&Felt::TWO), 1);
+
+        // a new window starts once the previous one is over
+        let mut limiter = RateLimiter::new(1, Duration::ZERO);
+        assert!(limiter.check(Felt::ONE));
+        assert!(limiter.check(Felt::ONE));
+        assert_eq!(limiter.count(&Felt::ONE), 0);
+        limiter.prune();
+        assert_eq!(limiter.count(&Felt::ONE), 0);
+    }
+
+    // Messages are limited per peer before being verified, and per identity once their signature
+    // is verified
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_relay_rate_limits() -> Result<(), Box<dyn Error>> {
+        use std::time::Duration;
+
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use tokio::time::sleep;
+
+        use crate::server::rate_limit::RateLimitConfig;
+        use crate::server::Relay;
+        use crate::types::Message;
+
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
+            .try_init();
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+        let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+
+        let (pool, db) = message_db().await;
+        let limits =
+            RateLimitConfig { peer_messages: 3, identity_messages: 1, ..Default::default() };
+        let mut relay = Relay::new(db, provider, 9920, 9921, None, None, vec![], limits)?;
+        tokio::spawn(async move {
+            relay.run().await;
+        });
+
+        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9920".to_string())?;
+        let event_loop = client.event_loop.clone();
+        tokio::spawn(async move {
+            event_loop.lock().await.run().await;
+        });
+
+        let message = |index: usize, key: &SigningKey, text: &str| {
+            let identity = sequencer.account_data(index).address;
+            let typed_data = message_typed_data(identity, text);
+            let signature = key.sign(&typed_data.encode(identity).unwrap()).unwrap();
+            Message { message: typed_data, signature: vec![signature.r, signature.s] }
+        };
+        let account_key = |index: usize| {
+            let account = sequencer.account_data(index);
+            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar())
+        };
+
+        // a message claiming the identity without its signature doesn't count towards its limit
+        client.command_sender.publish(message(0, &SigningKey::from_random(), "forged")).await?;
+        client.command_sender.publish(message(0, &account_key(0), "mimi")).await?;
+        // over the limit of the identity
+        client.command_sender.publish(message(0, &account_key(0), "mimo")).await?;
+        // over the limit of the peer
+        client.command_sender.publish(message(1, &account_key(1), "mimu")).await?;
+
+        sleep(Duration::from_secs(3)).await;
+
+        let messages: Vec<String> =
+            sqlx::query_scalar("SELECT external_message FROM [types_test-Message]")
+                .fetch_all(&pool)
+                .await?;
+        assert_eq!(messages, vec!["mimi".to_string()]);
+
+        Ok(())
+    }
+
+    // A message published to a relay is set by the relays federated with it, which serve it to
+    // the clients requesting their history
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_relay_federation() -> Result<(), Box<dyn Error>> {
+        use std::time::Duration;
+
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use tokio::time::sleep;
+
+        use crate::server::Relay;
+        use crate::types::Message;
+
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
+            .try_init();
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+        let provider = || JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+        let account = sequencer.account_data(0);
+
+        let (pool_a, db_a) = message_db().await;
+        let mut relay_a =
+            Relay::new(db_a, provider(), 9910, 9911, None, None, vec![], Default::default())?;
+        let peer_a = relay_a.peer_id();
+        tokio::spawn(async move {
+            relay_a.run().await;
+        });
+
+        let (pool_b, db_b) = message_db().await;
+        let mut relay_b = Relay::new(
+            db_b,
+            provider(),
+            9912,
+            9913,
+            None,
+            None,
+            vec![format!("/ip4/127.0.0.1/tcp/9910/p2p/{}", peer_a)],
+            Default::default(),
+        )?;
+        let peer_b = relay_b.peer_id();
+        tokio::spawn(async move {
+            relay_b.run().await;
+        });
+
+        // bootstrap peers need a peer id
+        assert!(Relay::new(
+            message_db().await.1,
+            provider(),
+            9914,
+            9915,
+            None,
+            None,
+            vec!["/ip4/127.0.0.1/tcp/9910".to_string()],
+            Default::default(),
+        )
+        .is_err());
+
+        let client_b = RelayClient::new("/ip4/127.0.0.1/tcp/9912".to_string())?;
+        let event_loop = client_b.event_loop.clone();
+        tokio::spawn(async move {
+            event_loop.lock().await.run().await;
+        });
+
+        let typed_data = message_typed_data(account.address, "mimi");
+        let signature =
+            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar())
+                .sign(&typed_data.encode(account.address).unwrap())
+                .unwrap();
+
+        client_b
+            .command_sender
+            .publish(Message { message: typed_data, signature: vec![signature.r, signature.s] })
+            .await?;
+
+        // the message is verified and set by the relay it was published to, which forwards it
+        let mut received = false;
+        for _ in 0..10 {
+            sleep(Duration::from_secs(1)).await;
+            if sqlx::query("SELECT * FROM entities").fetch_optional(&pool_a).await?.is_some() {
+                received = true;
+                break;
+            }
+        }
+        assert!(received, "Message wasn't forwarded to the federated relay");
+        assert!(sqlx::query("SELECT * FROM entities").fetch_optional(&pool_b).await?.is_some());
+
+        // both relays serve it to the clients connected to them
+        let client_a = RelayClient::new(format!("/ip4/127.0.0.1/tcp/9910/p2p/{}", peer_a))?;
+        let event_loop = client_a.event_loop.clone();
+        tokio::spawn(async move {
+            event_loop.lock().await.run().await;
+        });
+
+        let history = client_a.command_sender.history(10).await?;
+        assert_eq!(history.relay, peer_a);
+        assert_eq!(history.messages.len(), 1);
+        assert!(matches!(
+            &history.messages[0].message.message["model"],
+            PrimitiveType::String(model) if model == "types_test-Message"
+        ));
+
+        let history = client_b.command_sender.history(10).await?;
+        assert_eq!(history.relay, peer_b);
+        assert_eq!(history.messages.len(), 1);
+
+        Ok(())
+    }
+
     #[cfg(target_arch = "wasm32")]
     #[wasm_bindgen_test]
     async fn test_client_connection_wasm() -> Result<(), Box<dyn Error>> {
@@ -4,8 +4,41 @@ use starknet::core::types::Felt;
 use crate::typed_data::TypedData;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "MessageFormat")]
 pub struct Message {
     pub message: TypedData,
-    pub signature_r: Felt,
-    pub signature_s: Felt,
+    // Signature checked by the `is_valid_signature` entrypoint of the identity account, (r, s) for
+    // the legacy accounts.
+    pub signature: Vec<Felt>,
+}
+
+// Messages are still accepted in the format of the clients predating the signatures of any
+// length, with an (r, s) signature.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageFormat {
+    Signature { message: TypedData, signature: Vec<Felt> },
+    Legacy { message: TypedData, signature_r: Felt, signature_s: Felt },
+}
+
+impl From<MessageFormat> for Message {
+    fn from(format: MessageFormat) -> Self {
+        match format {
+            MessageFormat::Signature { message, signature } => Self { message, signature },
+            MessageFormat::Legacy { message, signature_r, signature_s } => {
+                Self { message, signature: vec![signature_r, signature_s] }
+            }
+        }
+    }
+}
+
+// Request of the latest messages set by a relay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRequest {
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryResponse {
+    pub messages: Vec<Message>,
 }
@@ -0,0 +1,12 @@
+-- Deleted entities, so that the deletions are replayed to the subscriptions resumed from an
+-- earlier event.
+CREATE TABLE entity_tombstones (
+    id TEXT NOT NULL PRIMARY KEY,
+    keys TEXT NOT NULL,
+    -- Tag of the deleted model.
+    model TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    deleted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_entity_tombstones_event_id ON entity_tombstones (event_id);
@@ -0,0 +1,9 @@
+-- Hashes of the offchain messages of models without a nonce, so that the relay rejects them when
+-- replayed before they expire.
+CREATE TABLE message_hashes (
+    hash TEXT NOT NULL PRIMARY KEY,
+    -- Unix timestamp after which the message can be received again.
+    expires_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_message_hashes_expires_at ON message_hashes (expires_at);
@@ -35,7 +35,8 @@ const DEFAULT_EXPOSED_HEADERS: [&str; 3] =
 const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
 
 lazy_static::lazy_static! {
-    static ref GRAPHQL_PROXY_CLIENT: ReverseProxy<HttpConnector<GaiResolver>> = {
+    // shared by the graphql and the grpc websocket endpoints, both over http1
+    static ref HTTP_PROXY_CLIENT: ReverseProxy<HttpConnector<GaiResolver>> = {
         ReverseProxy::new(
             Client::builder()
              .build_http(),
@@ -56,6 +57,7 @@ pub struct Proxy {
     addr: SocketAddr,
     allowed_origins: Option<Vec<String>>,
     grpc_addr: Option<SocketAddr>,
+    grpc_websocket_addr: Option<SocketAddr>,
     graphql_addr: Arc<RwLock<Option<SocketAddr>>>,
 }
 
@@ -64,9 +66,16 @@ impl Proxy {
         addr: SocketAddr,
         allowed_origins: Option<Vec<String>>,
         grpc_addr: Option<SocketAddr>,
+        grpc_websocket_addr: Option<SocketAddr>,
         graphql_addr: Option<SocketAddr>,
     ) -> Self {
-        Self { addr, allowed_origins, grpc_addr, graphql_addr: Arc::new(RwLock::new(graphql_addr)) }
+        Self {
+            addr,
+            allowed_origins,
+            grpc_addr,
+            grpc_websocket_addr,
+            graphql_addr: Arc::new(RwLock::new(graphql_addr)),
+        }
     }
 
     pub async fn set_graphql_addr(&self, addr: SocketAddr) {
@@ -81,6 +90,7 @@ impl Proxy {
         let addr = self.addr;
         let allowed_origins = self.allowed_origins.clone();
         let grpc_addr = self.grpc_addr;
+        let grpc_websocket_addr = self.grpc_websocket_addr;
         let graphql_addr = self.graphql_addr.clone();
 
         let make_svc = make_service_fn(move |conn: &AddrStream| {
@@ -123,7 +133,7 @@ impl Proxy {
                 let graphql_addr = graphql_addr_clone.clone();
                 async move {
                     let graphql_addr = graphql_addr.read().await;
-                    handle(remote_addr, grpc_addr, *graphql_addr, req).await
+                    handle(remote_addr, grpc_addr, grpc_websocket_addr, *graphql_addr, req).await
                 }
             });
 
@@ -143,50 +153,21 @@ impl Proxy {
 async fn handle(
     client_ip: IpAddr,
     grpc_addr: Option<SocketAddr>,
+    grpc_websocket_addr: Option<SocketAddr>,
     graphql_addr: Option<SocketAddr>,
     req: Request<Body>,
 ) -> Result<Response<Body>, Infallible> {
+    if req.uri().path().starts_with("/grpc/ws") {
+        return Ok(forward(&HTTP_PROXY_CLIENT, client_ip, grpc_websocket_addr, req).await);
+    }
+
     if req.uri().path().starts_with("/graphql") {
-        if let Some(graphql_addr) = graphql_addr {
-            let graphql_addr = format!("http://{}", graphql_addr);
-            return match GRAPHQL_PROXY_CLIENT.call(client_ip, &graphql_addr, req).await {
-                Ok(response) => Ok(response),
-                Err(_error) => {
-                    error!("{:?}", _error);
-                    Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(Body::empty())
-                        .unwrap())
-                }
-            };
-        } else {
-            return Ok(Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .body(Body::empty())
-                .unwrap());
-        }
+        return Ok(forward(&HTTP_PROXY_CLIENT, client_ip, graphql_addr, req).await);
     }
 
     if let Some(content_type) = req.headers().get(CONTENT_TYPE) {
         if content_type.to_str().unwrap().starts_with("application/grpc") {
-            if let Some(grpc_addr) = grpc_addr {
-                let grpc_addr = format!("http://{}", grpc_addr);
-                return match GRPC_PROXY_CLIENT.call(client_ip, &grpc_addr, req).await {
-                    Ok(response) => Ok(response),
-                    Err(_error) => {
-                        error!("{:?}", _error);
-                        Ok(Response::builder()
-                            .status(StatusCode::INTERNAL_SERVER_ERROR)
-                            .body(Body::empty())
-                            .unwrap())
-                    }
-                };
-            } else {
-                return Ok(Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::empty())
-                    .unwrap());
-            }
+            return Ok(forward(&GRPC_PROXY_CLIENT, client_ip, grpc_addr, req).await);
         }
     }
 
@@ -202,3 +183,25 @@ async fn handle(
         .unwrap();
     Ok(response)
 }
+
+async fn forward(
+    client: &ReverseProxy<HttpConnector<GaiResolver>>,
+    client_ip: IpAddr,
+    addr: Option<SocketAddr>,
+    req: Request<Body>,
+) -> Response<Body> {
+    let Some(addr) = addr else {
+        return Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap();
+    };
+
+    match client.call(client_ip, &format!("http://{}", addr), req).await {
+        Ok(response) => response,
+        Err(_error) => {
+            error!("{:?}", _error);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .unwrap()
+        }
+    }
+}